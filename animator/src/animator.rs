@@ -1,30 +1,40 @@
-use std::{borrow::Cow, collections::VecDeque, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap, VecDeque},
+    fmt::{Display, Write},
+    sync::Arc,
+};
 
-use fraction::{ConstZero, Fraction};
+use fraction::{ConstOne, ConstZero, Fraction};
 use naviz_parser::{
+    common::easing::Easing,
     config::{
-        machine::MachineConfig,
+        machine::{MachineConfig, MoveInterpolation, MovementConfig, MovementModel},
         visual::{
-            LeftRightPosition, OperationConfigConfigConfig, TopBottomPosition, VisualConfig,
-            ZoneConfigConfig,
+            AtomLabel, LeftRightPosition, OperationConfigConfigConfig, TopBottomPosition,
+            VisualConfig, YAxis, ZoneConfigConfig,
         },
     },
     input::concrete::{InstructionGroup, Instructions, SetupInstruction, TimedInstruction},
 };
 use naviz_state::{
     config::{
-        AtomsConfig, Config, FontConfig, GridConfig, GridLegendConfig, HPosition, LegendConfig,
-        LegendEntry, LegendSection, LineConfig, TimeConfig, TrapConfig, VPosition, ZoneConfig,
+        AtomsConfig, BackgroundConfig, Config, FontConfig, GhostConfig, GridConfig,
+        GridLegendConfig, HPosition, InteractionConfig, LegendConfig, LegendEntry, LegendSection,
+        LineConfig, OutlineConfig, Padding, TimeConfig, TrapConfig, VPosition, ZoneConfig,
+        ZoneShape,
     },
-    state::{AtomState, State},
+    state::{AtomState, AtomsDiff, State},
+    Pattern, Shape,
 };
 use regex::Regex;
 
 use crate::{
     color::Color,
     interpolator::{
-        Constant, ConstantJerkFixedAverageVelocity, ConstantJerkFixedMaxVelocity,
-        ConstantTransitionPoint, Diagonal, DurationCalculable, MaxVelocity, Triangle,
+        Acceleration, AxisAligned, Constant, ConstantJerkFixedAverageVelocity,
+        ConstantJerkFixedMaxVelocity, ConstantTransitionPoint, Diagonal, DurationCalculable,
+        FixedArgument, Flash, InterpolationFunction, Linear, MaxVelocity, Trapezoidal,
     },
     position::Position,
     timeline::{Time, Timeline},
@@ -33,45 +43,212 @@ use crate::{
 
 /// The timelines for a single atom
 pub struct AtomTimelines {
-    position: Timeline<(), Position, f32, Diagonal<ConstantJerkFixedAverageVelocity>>,
-    overlay_color: Timeline<(), Color, f32, Triangle>,
-    size: Timeline<(), f32, f32, Triangle>,
+    position: Timeline<(), Position, f32, PositionInterpolator>,
+    overlay_color: Timeline<Easing, Color, f32, Flash>,
+    size: Timeline<Easing, f32, f32, Flash>,
     shuttling: Timeline<ConstantTransitionPoint, bool, f32, Constant>,
+    /// The color of the last measurement-result (transparent if none or reset)
+    result_color: Timeline<ConstantTransitionPoint, Color, f32, Constant>,
+    /// Whether the atom exists (atoms may appear during the animation)
+    visible: Timeline<ConstantTransitionPoint, bool, f32, Constant>,
+    /// The pattern of the current operation
+    pattern: Timeline<ConstantTransitionPoint, Pattern, f32, Constant>,
+    /// Whether to indicate the interaction distance (during `cz`-gates)
+    interaction: Timeline<ConstantTransitionPoint, bool, f32, Constant>,
+    /// The factor by which all sizes of the atom are scaled
+    weight: Fraction,
 }
 
 impl AtomTimelines {
-    /// Creates new AtomTimelines from the passed default values
-    pub fn new(position: Position, overlay_color: Color, size: f32, shuttling: bool) -> Self {
+    /// Creates new AtomTimelines from the passed default values.
+    /// Positions are interpolated according to the passed [MovementConfig].
+    pub fn new(
+        position: Position,
+        overlay_color: Color,
+        size: f32,
+        shuttling: bool,
+        movement: &MovementConfig,
+    ) -> Self {
         Self {
-            position: Timeline::new_with_interpolation(
-                position,
-                Diagonal(ConstantJerkFixedAverageVelocity()),
-            ),
+            position: Timeline::new_with_interpolation(position, movement.into()),
             overlay_color: Timeline::new(overlay_color),
             size: Timeline::new(size),
             shuttling: Timeline::new(shuttling),
+            result_color: Timeline::new(Color::default()),
+            visible: Timeline::new(true),
+            pattern: Timeline::new(Pattern::Solid),
+            interaction: Timeline::new(false),
+            weight: Fraction::ONE,
         }
     }
 
+    /// Scales the base size and all later operation-sizes by the passed `weight`.
+    /// Must be called before any animations are inserted.
+    pub fn set_weight(&mut self, base_size: Fraction, weight: Fraction) {
+        self.weight = weight;
+        self.size = Timeline::new((base_size * weight).f32());
+    }
+
+    /// Resets all timelines which depend on the [VisualConfig]
+    /// (i.e., everything but the position, shuttling and visibility)
+    /// to their default values.
+    /// The size is reset to the passed `base_size` scaled by the weight of the atom.
+    fn reset_style(&mut self, base_size: Fraction) {
+        self.overlay_color = Timeline::new(Color::default());
+        self.size = Timeline::new((base_size * self.weight).f32());
+        self.result_color = Timeline::new(Color::default());
+        self.pattern = Timeline::new(Pattern::Solid);
+        self.interaction = Timeline::new(false);
+    }
+
+    /// Hides the atom until the passed `time`
+    pub fn appear_at(&mut self, time: f32) {
+        self.visible = Timeline::new(false);
+        self.visible
+            .add((time, 0., ConstantTransitionPoint::Start, true));
+    }
+
+    /// Maps the times of the keyframes of all timelines using the passed function `f`.
+    /// See [Timeline::map_times].
+    fn map_times(&mut self, f: impl Fn(Time) -> Time + Copy) {
+        self.position.map_times(f);
+        self.overlay_color.map_times(f);
+        self.size.map_times(f);
+        self.shuttling.map_times(f);
+        self.result_color.map_times(f);
+        self.visible.map_times(f);
+        self.pattern.map_times(f);
+        self.interaction.map_times(f);
+    }
+
     /// Gets the values of these timelines at the passed time
-    pub fn get(&self, time: Time) -> (Position, Color, f32, bool) {
+    pub fn get(&self, time: Time) -> (Position, Color, f32, bool, Color, bool, Pattern, bool) {
         (
             self.position.get(time),
             self.overlay_color.get(time),
             self.size.get(time),
             self.shuttling.get(time),
+            self.result_color.get(time),
+            self.visible.get(time),
+            self.pattern.get(time),
+            self.interaction.get(time),
         )
     }
 }
 
+/// The interpolation-function for atom-positions as selected by the [MovementModel]
+enum MovementInterpolator {
+    ConstantJerk(ConstantJerkFixedAverageVelocity),
+    ConstantVelocity(Linear),
+    Trapezoidal(FixedArgument<(MaxVelocity, Acceleration), Trapezoidal>),
+}
+
+impl From<&MovementConfig> for MovementInterpolator {
+    fn from(movement: &MovementConfig) -> Self {
+        match movement.model {
+            MovementModel::ConstantJerk => Self::ConstantJerk(ConstantJerkFixedAverageVelocity()),
+            MovementModel::ConstantVelocity => Self::ConstantVelocity(Linear()),
+            MovementModel::Trapezoidal { acceleration } => {
+                Self::Trapezoidal(Trapezoidal::new_fixed(
+                    MaxVelocity(movement.max_speed.f32()),
+                    Acceleration(acceleration.f32()),
+                ))
+            }
+        }
+    }
+}
+
+impl InterpolationFunction<(), f32> for MovementInterpolator {
+    fn interpolate(&self, fraction: Time, argument: (), from: f32, to: f32) -> f32 {
+        match self {
+            Self::ConstantJerk(i) => i.interpolate(fraction, argument, from, to),
+            Self::ConstantVelocity(i) => i.interpolate(fraction, argument, from, to),
+            Self::Trapezoidal(i) => i.interpolate(fraction, argument, from, to),
+        }
+    }
+}
+
+/// The interpolation-function for atom-positions
+/// as selected by the [MoveInterpolation]
+enum PositionInterpolator {
+    Diagonal(Diagonal<MovementInterpolator>),
+    ComponentWise(AxisAligned<MovementInterpolator>),
+}
+
+impl From<&MovementConfig> for PositionInterpolator {
+    fn from(movement: &MovementConfig) -> Self {
+        match movement.interpolation {
+            MoveInterpolation::Diagonal => Self::Diagonal(Diagonal(movement.into())),
+            MoveInterpolation::ComponentWise => Self::ComponentWise(AxisAligned(movement.into())),
+        }
+    }
+}
+
+impl InterpolationFunction<(), Position> for PositionInterpolator {
+    fn interpolate(&self, fraction: Time, argument: (), from: Position, to: Position) -> Position {
+        match self {
+            Self::Diagonal(i) => i.interpolate(fraction, argument, from, to),
+            Self::ComponentWise(i) => i.interpolate(fraction, argument, from, to),
+        }
+    }
+}
+
 /// An atom-state in the animator
 struct Atom {
     /// id of the atom
     id: String,
     /// display name (label) of the atom
     name: String,
+    /// the shape of the atom
+    shape: Shape,
+    /// the shape of the atom as set in the setup, overriding the style
+    setup_shape: Option<naviz_parser::common::shape::Shape>,
     /// the timelines of the atom
     timelines: AtomTimelines,
+    /// the operations on the atom, which are animated depending on the [VisualConfig]
+    operations: Vec<Operation>,
+}
+
+/// An operation on a single [Atom].
+/// Operations only affect the style of an atom
+/// and are therefore recorded to be re-applied when the [VisualConfig] changes.
+#[derive(Clone, Copy)]
+struct Operation {
+    start: f32,
+    duration: f32,
+    kind: OperationKind,
+}
+
+/// The kind of an [Operation]
+#[derive(Clone, Copy)]
+enum OperationKind {
+    Rz,
+    Ry,
+    Cz,
+    /// A measurement with the measured result, if known
+    Measure(Option<bool>),
+    Reset,
+    /// The atom is a candidate of a `cz`-gate
+    Interaction,
+}
+
+impl OperationKind {
+    /// Gets the [OperationKind] of the passed `instruction`,
+    /// or [None] if the instruction is not an operation
+    fn of(instruction: &TimedInstruction) -> Option<Self> {
+        match instruction {
+            TimedInstruction::Rz { .. } => Some(Self::Rz),
+            TimedInstruction::Ry { .. } => Some(Self::Ry),
+            TimedInstruction::Cz { .. } => Some(Self::Cz),
+            TimedInstruction::Measure { result, .. } => Some(Self::Measure(*result)),
+            TimedInstruction::Reset { .. } => Some(Self::Reset),
+            TimedInstruction::Load { .. }
+            | TimedInstruction::Store { .. }
+            | TimedInstruction::Move { .. }
+            | TimedInstruction::MoveBy { .. }
+            | TimedInstruction::Highlight { .. } => None,
+        }
+    }
 }
 
 /// The animator.
@@ -86,31 +263,154 @@ pub struct Animator {
     /// The total durations of the animations
     duration: Fraction,
 
+    /// The absolute start times and names of all instructions, ordered by time
+    instruction_times: Vec<(Fraction, &'static str)>,
+
+    /// The start of the shown time-window, if the timeline is clipped to a window
+    window_start: Option<f32>,
+
+    /// The frames per second the keyframes are quantized to, if quantized.
+    /// See [Animator::quantize_to_fps].
+    fps: Option<u32>,
+
+    /// The line colors of all highlighted zones by id
+    /// ([None] while the zone uses the color of its style)
+    zone_highlights:
+        HashMap<String, Timeline<ConstantTransitionPoint, Option<Color>, f32, Constant>>,
+
+    /// Whether the zones with a capacity hold more atoms than their capacity, by id
+    /// (only contains zones which exceed their capacity at some point)
+    zone_over_capacity: HashMap<String, Timeline<ConstantTransitionPoint, bool, f32, Constant>>,
+
+    /// The extent of the atoms (tl_x, tl_y, br_x, br_y),
+    /// before growing it to fit the machine and adding the margin
+    atom_extent: (Fraction, Fraction, Fraction, Fraction),
+
     machine: MachineConfig,
     visual: VisualConfig,
 }
 
+/// A problem of the configs passed to the [Animator],
+/// which does not prevent animating but may lead to unexpected visuals.
+/// Created by [Animator::new_with_warnings] and [Animator::try_new].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AnimatorWarning {
+    /// A zone of the machine has no matching style;
+    /// it is drawn invisibly
+    MissingZoneStyle { zone: String },
+    /// An operation is used by the instructions but has no style;
+    /// it is not animated
+    MissingOperationStyle { operation: &'static str },
+    /// An operation is used by the instructions but its color is fully transparent
+    TransparentOperationColor { operation: &'static str },
+    /// A zone holds more atoms than its capacity;
+    /// reported for the first `time` at which this happens
+    ZoneOverCapacity {
+        zone: String,
+        time: Fraction,
+        atoms: usize,
+        capacity: usize,
+    },
+}
+
+impl Display for AnimatorWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingZoneStyle { zone } => {
+                write!(f, "Zone `{zone}` has no matching style config")
+            }
+            Self::MissingOperationStyle { operation } => {
+                write!(f, "Operation `{operation}` has no style config")
+            }
+            Self::TransparentOperationColor { operation } => {
+                write!(f, "Operation `{operation}` color unset (fully transparent)")
+            }
+            Self::ZoneOverCapacity {
+                zone,
+                time,
+                atoms,
+                capacity,
+            } => write!(
+                f,
+                "Zone `{zone}` holds {atoms} atoms at time {time}, exceeding its capacity of {capacity}"
+            ),
+        }
+    }
+}
+
 impl Animator {
-    /// Creates the [Animator]: calculates the timelines and the [Config]
+    /// Creates the [Animator]: calculates the timelines and the [Config].
+    ///
+    /// Problems of the configs are ignored;
+    /// use [Animator::new_with_warnings] to get them.
     pub fn new(machine: MachineConfig, visual: VisualConfig, input: Instructions) -> Self {
+        Self::new_with_warnings(machine, visual, input).0
+    }
+
+    /// Creates the [Animator] like [Animator::new],
+    /// but fails with the collected [AnimatorWarning]s if there are any.
+    pub fn try_new(
+        machine: MachineConfig,
+        visual: VisualConfig,
+        input: Instructions,
+    ) -> Result<Self, Vec<AnimatorWarning>> {
+        let (animator, warnings) = Self::new_with_warnings(machine, visual, input);
+        if warnings.is_empty() {
+            Ok(animator)
+        } else {
+            Err(warnings)
+        }
+    }
+
+    /// Creates the [Animator] like [Animator::new],
+    /// additionally returning the [AnimatorWarning]s encountered while creating it.
+    /// The returned [Animator] is usable regardless of any warnings.
+    pub fn new_with_warnings(
+        machine: MachineConfig,
+        visual: VisualConfig,
+        input: Instructions,
+    ) -> (Self, Vec<AnimatorWarning>) {
+        let mut warnings = Vec::new();
+
         // Create the atoms
         let mut atoms: Vec<_> = input
             .setup
             .iter()
             .map(|a| match a {
-                SetupInstruction::Atom { position, id } => Atom {
-                    id: id.clone(),
-                    name: get_name(&visual.atom.legend.name, id),
-                    timelines: AtomTimelines::new(
+                SetupInstruction::Atom {
+                    position,
+                    id,
+                    appear_time,
+                    shape,
+                } => {
+                    let mut timelines = AtomTimelines::new(
                         (*position).into(),
                         Color::default(),
                         visual.atom.radius.f32(),
                         false,
-                    ),
-                },
+                        &machine.movement,
+                    );
+                    if let Some(&weight) = input.directives.weights.get(id) {
+                        timelines.set_weight(visual.atom.radius, weight);
+                    }
+                    if let Some(appear_time) = appear_time {
+                        timelines.appear_at(appear_time.f32());
+                    }
+                    Atom {
+                        id: id.clone(),
+                        // Name and shape are set by `style_atoms`
+                        name: String::new(),
+                        shape: Shape::Circle,
+                        setup_shape: *shape,
+                        timelines,
+                        operations: Vec::new(),
+                    }
+                }
             })
             .collect();
 
+        let window = input.directives.window;
+
         // Convert the `Vec`s to `VecDeque`s to allow popping from front
         let mut absolute_timeline: VecDeque<(_, VecDeque<_>)> = input
             .instructions
@@ -123,7 +423,7 @@ impl Animator {
             SetupInstruction::Atom { position, .. } => position,
         });
         let setup_xs = setup_positions.clone().map(|p| p.0);
-        let setup_ys = setup_positions.clone().map(|p| p.0);
+        let setup_ys = setup_positions.clone().map(|p| p.1);
 
         // (tl_x, tl_y, br_x, br_y)
         let mut content_extent = (
@@ -134,6 +434,14 @@ impl Animator {
         );
 
         let mut duration_total = Fraction::ZERO;
+        let mut instruction_times = Vec::new();
+        let mut zone_highlights = HashMap::new();
+        // The times at which the zone capacities are checked
+        let mut sample_times: Vec<_> = std::iter::once(Fraction::ZERO)
+            .chain(input.setup.iter().filter_map(|a| match a {
+                SetupInstruction::Atom { appear_time, .. } => *appear_time,
+            }))
+            .collect();
 
         // Animate the atoms
         while let Some((time, mut relative_timeline)) = absolute_timeline.pop_front() {
@@ -166,6 +474,17 @@ impl Animator {
                     // Update duration of group
                     duration = duration.max(current_duration);
 
+                    // Drop instructions which lie fully outside of the window
+                    if window.is_some_and(|(start, end)| {
+                        start_time + current_duration < start || start_time >= end
+                    }) {
+                        continue;
+                    }
+
+                    instruction_times.push((start_time, instruction.str()));
+                    sample_times.push(start_time);
+                    sample_times.push(start_time + current_duration);
+
                     // update extent
                     if let Some(position) = get_position(&instruction) {
                         content_extent.0 = content_extent.0.min(position.0);
@@ -174,14 +493,56 @@ impl Animator {
                         content_extent.3 = content_extent.3.max(position.1);
                     }
 
+                    if let TimedInstruction::Highlight { id, color, .. } = &instruction {
+                        zone_highlights
+                            .entry(id.clone())
+                            .or_insert_with(|| Timeline::new(None))
+                            .add((
+                                start_time_f32,
+                                0.,
+                                ConstantTransitionPoint::Start,
+                                Some((*color).into()),
+                            ));
+                    }
+
+                    // Indicate the interaction distance around all candidates of a cz
+                    if let TimedInstruction::Cz { targets, .. } = &instruction {
+                        for (idx, _) in cz_candidates(&atoms, targets, start_time, &machine) {
+                            atoms[idx].operations.push(Operation {
+                                start: start_time_f32,
+                                duration: current_duration_f32,
+                                kind: OperationKind::Interaction,
+                            });
+                        }
+                    }
+
                     targeted(&mut atoms, &instruction, start_time, &machine).for_each(|a| {
-                        insert_animation(
-                            &mut a.timelines,
-                            &instruction,
-                            start_time_f32,
-                            current_duration_f32,
-                            &visual,
-                        )
+                        match OperationKind::of(&instruction) {
+                            Some(kind) => a.operations.push(Operation {
+                                start: start_time_f32,
+                                duration: current_duration_f32,
+                                kind,
+                            }),
+                            None => insert_movement(
+                                &mut a.timelines,
+                                &instruction,
+                                start_time_f32,
+                                current_duration_f32,
+                            ),
+                        }
+
+                        // update extent by relative targets
+                        if let TimedInstruction::MoveBy { .. } = instruction {
+                            let position = a
+                                .timelines
+                                .position
+                                .get((start_time_f32 + current_duration_f32).into());
+                            let position = (Fraction::from(position.x), Fraction::from(position.y));
+                            content_extent.0 = content_extent.0.min(position.0);
+                            content_extent.1 = content_extent.1.min(position.1);
+                            content_extent.2 = content_extent.2.max(position.0);
+                            content_extent.3 = content_extent.3.max(position.1);
+                        }
                     });
                 }
 
@@ -205,226 +566,124 @@ impl Animator {
             }
         }
 
-        // Grow content extent to fit zones and traps
-        for (x, y) in machine
-            .zone
-            .iter()
-            .flat_map(|z| [z.1.from, z.1.to])
-            .chain(machine.trap.iter().map(|t| t.1.position))
-        {
-            content_extent.0 = content_extent.0.min(x);
-            content_extent.1 = content_extent.1.min(y);
-            content_extent.2 = content_extent.2.max(x);
-            content_extent.3 = content_extent.3.max(y);
-        }
-
-        // Add margin to extent
-        content_extent.0 -= visual.coordinate.margin;
-        content_extent.1 -= visual.coordinate.margin;
-        content_extent.2 += visual.coordinate.margin;
-        content_extent.3 += visual.coordinate.margin;
-
-        // The legend entries
-        let mut legend_entries = Vec::new();
-        if visual.zone.legend.display {
-            legend_entries.push(LegendSection {
-                name: visual.zone.legend.title.clone(),
-                entries: machine
-                    .zone
+        // Instructions are processed mostly in order of time; ensure full ordering
+        instruction_times.sort_by_key(|(time, _)| *time);
+
+        // Check the styles of all used operations
+        check_operation_styles(&instruction_times, &visual, &mut warnings);
+
+        // Check the capacities of the zones at all sample times
+        sample_times.sort();
+        sample_times.dedup();
+        // The positions of the visible atoms at each sample time, shared by all zones
+        let samples: Vec<_> = sample_times
+            .into_iter()
+            .map(|time| {
+                let time_f32: Time = time.f32().into();
+                let positions: Vec<_> = atoms
                     .iter()
-                    .filter_map(|(id, _)| {
-                        get_first_match_with_regex(&visual.zone.config, id)
-                            .filter(|(_, zone)| !zone.name.is_empty())
-                            .map(|(regex, zone)| LegendEntry {
-                                text: regex.replace(id, &zone.name).into_owned(),
-                                color: Some(zone.color.rgba()),
-                            })
-                    })
-                    .collect(),
-            });
-        }
-        if visual.operation.legend.display {
-            legend_entries.push(LegendSection {
-                name: visual.operation.legend.title.clone(),
-                entries: [
-                    (
-                        &visual.operation.config.rz.name,
-                        visual.operation.config.rz.color,
-                    ),
-                    (
-                        &visual.operation.config.ry.name,
-                        visual.operation.config.ry.color,
-                    ),
-                    (
-                        &visual.operation.config.cz.name,
-                        visual.operation.config.cz.color,
-                    ),
-                ]
-                .into_iter()
-                .filter(|(name, _)| !name.is_empty())
-                .map(|(name, color)| LegendEntry {
-                    text: name.clone(),
-                    color: Some(color.rgba()),
-                })
-                .collect(),
-            });
+                    .filter(|a| a.timelines.visible.get(time_f32))
+                    .map(|a| a.timelines.position.get(time_f32))
+                    .map(|p| (p.x, p.y))
+                    .collect();
+                (time, positions)
+            })
+            .collect();
+        let mut zone_over_capacity = HashMap::new();
+        let mut zones: Vec<_> = machine.zone.iter().collect();
+        zones.sort_by_key(|(id, _)| *id);
+        for (id, zone) in zones {
+            let Some(capacity) = zone.capacity else {
+                continue;
+            };
+            let mut over_capacity = Timeline::new(false);
+            let mut was_over = false;
+            let mut first_over = None;
+            for (time, positions) in &samples {
+                let count = positions
+                    .iter()
+                    .filter(|&&p| zone.shape.contains(p))
+                    .count();
+                let is_over = count > capacity;
+                if is_over != was_over {
+                    over_capacity.add((time.f32(), 0., ConstantTransitionPoint::Start, is_over));
+                    was_over = is_over;
+                }
+                if is_over && first_over.is_none() {
+                    first_over = Some((*time, count));
+                }
+            }
+            if let Some((time, atoms)) = first_over {
+                warnings.push(AnimatorWarning::ZoneOverCapacity {
+                    zone: id.clone(),
+                    time,
+                    atoms,
+                    capacity,
+                });
+                zone_over_capacity.insert(id.clone(), over_capacity);
+            }
         }
-        if visual.machine.legend.display {
-            legend_entries.push(LegendSection {
-                name: visual.machine.legend.title.clone(),
-                entries: [
-                    (&visual.machine.trap.name, visual.atom.trapped.color),
-                    (&visual.machine.shuttle.name, visual.atom.shuttling.color),
-                ]
-                .into_iter()
-                .filter(|(name, _)| !name.is_empty())
-                .map(|(name, color)| LegendEntry {
-                    text: name.clone(),
-                    color: Some(color.rgba()),
-                })
-                .collect(),
-            });
+
+        // Clip the timeline to the window and shift it to start at the window
+        if let Some((start, end)) = window {
+            duration_total = (end - start).max(Fraction::ZERO);
+            for (time, _) in &mut instruction_times {
+                *time = (*time - start).max(Fraction::ZERO);
+            }
         }
 
-        // Create static config
-        let config = Config {
-            machine: naviz_state::config::MachineConfig {
-                grid: GridConfig {
-                    step: (
-                        visual.coordinate.tick.x.f32(),
-                        visual.coordinate.tick.y.f32(),
-                    ),
-                    line: LineConfig {
-                        width: visual.coordinate.tick.line.thickness.f32(),
-                        segment_length: visual.coordinate.tick.line.dash.length.f32(),
-                        duty: Into::<Fraction>::into(visual.coordinate.tick.line.dash.duty).f32(),
-                        color: visual.coordinate.tick.color.rgba(),
-                    },
-                    display_ticks: visual.coordinate.tick.display,
-                    legend: GridLegendConfig {
-                        step: (
-                            visual.coordinate.number.x.distance.f32(),
-                            visual.coordinate.number.y.distance.f32(),
-                        ),
-                        font: FontConfig {
-                            size: visual.coordinate.number.font.size.f32(),
-                            color: visual.coordinate.number.font.color.rgba(),
-                            family: visual.coordinate.number.font.family.to_owned(),
-                        },
-                        labels: (
-                            visual.coordinate.axis.x.clone(),
-                            visual.coordinate.axis.y.clone(),
-                        ),
-                        position: (
-                            match visual.coordinate.number.x.position {
-                                TopBottomPosition::Bottom => VPosition::Bottom,
-                                TopBottomPosition::Top => VPosition::Top,
-                            },
-                            match visual.coordinate.number.y.position {
-                                LeftRightPosition::Left => HPosition::Left,
-                                LeftRightPosition::Right => HPosition::Right,
-                            },
-                        ),
-                        display_labels: visual.coordinate.axis.display,
-                        display_numbers: visual.coordinate.number.display,
-                    },
-                },
-                traps: TrapConfig {
-                    positions: machine
-                        .trap
-                        .values()
-                        .map(|t| (t.position.0.f32(), t.position.1.f32()))
-                        .collect(),
-                    radius: visual.machine.trap.radius.f32(),
-                    line_width: visual.machine.trap.line_width.f32(),
-                    color: visual.machine.trap.color.rgba(),
-                },
-                zones: machine
-                    .zone
-                    .iter()
-                    .map(|(id, zone)| {
-                        let start: (f32, f32) = (zone.from.0.f32(), zone.from.1.f32());
-                        let end: (f32, f32) = (zone.to.0.f32(), zone.to.1.f32());
-                        let size = (end.0 - start.0, end.1 - start.1);
-                        let default_line = ZoneConfigConfig {
-                            color: naviz_parser::common::color::Color {
-                                r: 0,
-                                g: 0,
-                                b: 0,
-                                a: 0,
-                            },
-                            line: naviz_parser::config::visual::LineConfig {
-                                dash: naviz_parser::config::visual::DashConfig {
-                                    length: Default::default(),
-                                    duty: naviz_parser::common::percentage::Percentage(
-                                        Default::default(),
-                                    ),
-                                },
-                                thickness: Default::default(),
-                            },
-                            name: "".to_owned(),
-                        };
-                        let line =
-                            get_first_match(&visual.zone.config, id).unwrap_or(&default_line);
-                        ZoneConfig {
-                            start,
-                            size,
-                            line: LineConfig {
-                                width: line.line.thickness.f32(),
-                                segment_length: line.line.dash.length.f32(),
-                                duty: line.line.dash.duty.0.f32(),
-                                color: line.color.rgba(),
-                            },
-                        }
-                    })
-                    .collect(),
-            },
-            atoms: AtomsConfig {
-                label: FontConfig {
-                    size: visual.atom.legend.font.size.f32(),
-                    color: visual.atom.legend.font.color.rgba(),
-                    family: visual.atom.legend.font.family.to_owned(),
-                },
-                shuttle: LineConfig {
-                    width: visual.machine.shuttle.line.thickness.f32(),
-                    segment_length: visual.machine.shuttle.line.dash.length.f32(),
-                    duty: Into::<Fraction>::into(visual.machine.shuttle.line.dash.duty).f32(),
-                    color: visual.machine.shuttle.color.rgba(),
-                },
-            },
-            content_extent: (
-                (content_extent.0.f32(), content_extent.1.f32()),
-                (content_extent.2.f32(), content_extent.3.f32()),
-            ),
-            legend: LegendConfig {
-                font: FontConfig {
-                    size: visual.sidebar.font.size.f32(),
-                    color: visual.sidebar.font.color.rgba(),
-                    family: visual.sidebar.font.family.to_owned(),
-                },
-                heading_skip: visual.sidebar.padding.heading.f32(),
-                entry_skip: visual.sidebar.padding.entry.f32(),
-                color_circle_radius: visual.sidebar.color_radius.f32(),
-                color_padding: visual.sidebar.padding.color.f32(),
-                entries: legend_entries,
-            },
-            time: TimeConfig {
-                font: FontConfig {
-                    size: visual.time.font.size.f32(),
-                    color: visual.time.font.color.rgba(),
-                    family: visual.time.font.family.clone(),
-                },
-                display: visual.time.display,
+        style_atoms(&mut atoms, &visual);
+        let mut config = build_config(&machine, &visual, content_extent, &mut warnings);
+        set_ghost_positions(&mut config, &atoms);
+
+        (
+            Self {
+                atoms,
+                config: Arc::new(config),
+                duration: duration_total,
+                instruction_times,
+                window_start: window.map(|(start, _)| start.f32()),
+                fps: None,
+                zone_highlights,
+                zone_over_capacity,
+                atom_extent: content_extent,
+                machine,
+                visual,
             },
-        };
+            warnings,
+        )
+    }
 
-        Self {
-            atoms,
-            config: Arc::new(config),
-            duration: duration_total,
-            machine,
-            visual,
+    /// Applies a new [VisualConfig] to this [Animator]
+    /// without recalculating the timelines of the atoms.
+    ///
+    /// The positions, shuttling and visibility of the atoms only depend on
+    /// the machine and the instructions and are kept,
+    /// while the styles of all operations, the atom names and shapes,
+    /// and the [Config] are recreated from the new `visual`.
+    ///
+    /// Problems of the config are ignored;
+    /// use [Animator::apply_visual_with_warnings] to get them.
+    pub fn apply_visual(&mut self, visual: VisualConfig) {
+        self.apply_visual_with_warnings(visual);
+    }
+
+    /// Applies a new [VisualConfig] like [Animator::apply_visual],
+    /// additionally returning the [AnimatorWarning]s caused by the new `visual`.
+    /// Warnings which do not depend on the `visual` (e.g., zone capacities) are not repeated.
+    pub fn apply_visual_with_warnings(&mut self, visual: VisualConfig) -> Vec<AnimatorWarning> {
+        let mut warnings = Vec::new();
+        check_operation_styles(&self.instruction_times, &visual, &mut warnings);
+        style_atoms(&mut self.atoms, &visual);
+        if let Some(fps) = self.fps {
+            // The recreated styles are not quantized yet
+            self.quantize_timelines(fps);
         }
+        let mut config = build_config(&self.machine, &visual, self.atom_extent, &mut warnings);
+        set_ghost_positions(&mut config, &self.atoms);
+        self.config = Arc::new(config);
+        self.visual = visual;
+        warnings
     }
 
     /// The calculated [Config]
@@ -437,36 +696,270 @@ impl Animator {
         self.duration
     }
 
+    /// The final position of each atom
+    /// (i.e., the position of the last keyframe of its position-timeline)
+    /// as `(id, position)`-pairs in the order the atoms were declared in the setup.
+    pub fn final_positions(&self) -> Vec<(String, Position)> {
+        self.atoms
+            .iter()
+            .map(|Atom { id, timelines, .. }| (id.clone(), timelines.position.last_value()))
+            .collect()
+    }
+
+    /// The absolute start times and names of all instructions in this [Animator],
+    /// ordered by start time.
+    pub fn instruction_times(&self) -> Vec<(Fraction, &'static str)> {
+        self.instruction_times.clone()
+    }
+
     /// Gets the [State] at the passed [Time]
     pub fn state(&self, time: Time) -> State {
-        State {
-            atoms: self
-                .atoms
-                .iter()
-                .map(
-                    |Atom {
-                         id: _,
-                         name,
-                         timelines,
-                     }| (timelines.get(time), name),
-                )
-                .map(
-                    |((position, overlay_color, size, shuttling), name)| AtomState {
-                        position: position.into(),
-                        size,
-                        color: overlay_color
-                            .over(&if shuttling {
-                                self.visual.atom.shuttling.color.into()
-                            } else {
-                                self.visual.atom.trapped.color.into()
-                            })
-                            .0,
-                        shuttle: shuttling,
-                        label: name.clone(),
-                    },
-                )
-                .collect(),
-            time: self.format_time(time),
+        let mut state = State::default();
+        self.state_into(time, &mut state);
+        state
+    }
+
+    /// Writes the [State] at the passed [Time] into the passed `state`.
+    /// Reuses the allocations of the passed `state`
+    /// (i.e., the atoms and strings are overwritten instead of reallocated),
+    /// so this should be preferred over [Animator::state] when getting states repeatedly.
+    ///
+    /// Returns the atoms which changed compared to the previous contents of `state`,
+    /// which allows only updating the changed atoms when rendering.
+    pub fn state_into(&self, time: Time, state: &mut State) -> AtomsDiff {
+        let time = self.timeline_time(time);
+        let all_changed = state.atoms.len() != self.atoms.len();
+        let mut changed = Vec::new();
+        state.atoms.truncate(self.atoms.len());
+        for (
+            idx,
+            Atom {
+                name,
+                shape,
+                timelines,
+                ..
+            },
+        ) in self.atoms.iter().enumerate()
+        {
+            let (
+                position,
+                overlay_color,
+                size,
+                shuttling,
+                result_color,
+                visible,
+                pattern,
+                interaction,
+            ) = timelines.get(time);
+            let position = position.into();
+            // Atoms which did not appear yet are not drawn
+            let size = if visible { size } else { 0. };
+            let interaction = visible && interaction;
+            let color = overlay_color
+                .over(&result_color.over(&if shuttling {
+                    self.visual.atom.shuttling.color.into()
+                } else {
+                    self.visual.atom.trapped.color.into()
+                }))
+                .0;
+
+            if let Some(atom) = state.atoms.get_mut(idx) {
+                let label = if visible { name.as_str() } else { "" };
+                if atom.position == position
+                    && atom.size == size
+                    && atom.color == color
+                    && atom.shape == *shape
+                    && atom.pattern == pattern
+                    && atom.shuttle == shuttling
+                    && atom.interaction == interaction
+                    && atom.label == label
+                {
+                    continue;
+                }
+                changed.push(idx);
+                atom.position = position;
+                atom.size = size;
+                atom.color = color;
+                atom.shape = *shape;
+                atom.pattern = pattern;
+                atom.shuttle = shuttling;
+                atom.interaction = interaction;
+                atom.label.clear();
+                atom.label.push_str(label);
+            } else {
+                state.atoms.push(AtomState {
+                    position,
+                    size,
+                    color,
+                    shape: *shape,
+                    pattern,
+                    shuttle: shuttling,
+                    interaction,
+                    label: if visible { name.clone() } else { String::new() },
+                });
+            }
+        }
+        self.write_time(time, &mut state.time);
+
+        // The zones of the config are created in the iteration-order of the machine
+        state.zones.clear();
+        state.zones.extend(
+            self.machine
+                .zone
+                .keys()
+                .zip(&self.config.machine.zones)
+                .map(|(id, zone)| {
+                    self.zone_over_capacity
+                        .get(id)
+                        .filter(|over_capacity| over_capacity.get(time))
+                        .and_then(|_| get_first_match(&self.visual.zone.config, id))
+                        .and_then(|style| style.over_capacity)
+                        .map(|color| color.rgba())
+                        .or_else(|| {
+                            self.zone_highlights
+                                .get(id)
+                                .and_then(|highlight| highlight.get(time))
+                                .map(|color| color.0)
+                        })
+                        .unwrap_or(zone.line.color)
+                }),
+        );
+
+        if all_changed {
+            AtomsDiff::All
+        } else {
+            AtomsDiff::Changed(changed)
+        }
+    }
+
+    /// Gets the id of the zone the atom with the passed `atom_id` is in at the passed [Time].
+    /// If the zones overlap, the first zone (ordered by id) is returned.
+    /// Returns [None] if there is no such atom,
+    /// the atom did not appear yet,
+    /// or the atom is not in any zone.
+    pub fn zone_of(&self, atom_id: &str, time: Time) -> Option<&str> {
+        let time = self.timeline_time(time);
+        let atom = self.atoms.iter().find(|a| a.id == atom_id)?;
+        if !atom.timelines.visible.get(time) {
+            return None;
+        }
+        self.machine
+            .zone
+            .iter()
+            .filter(|(_, zone)| is_in_zone(atom, zone, time))
+            .map(|(id, _)| id.as_str())
+            .min()
+    }
+
+    /// Gets the position of the atom with the passed `atom_id` at the passed [Time].
+    /// Returns [None] if there is no such atom or the atom did not appear yet.
+    pub fn atom_position(&self, atom_id: &str, time: Time) -> Option<Position> {
+        let time = self.timeline_time(time);
+        let atom = self.atoms.iter().find(|a| a.id == atom_id)?;
+        atom.timelines
+            .visible
+            .get(time)
+            .then(|| atom.timelines.position.get(time))
+    }
+
+    /// Gets the id of the atom at the passed `index` in the [atoms][State::atoms] of the [State].
+    /// Returns [None] if there is no such atom.
+    pub fn atom_id(&self, index: usize) -> Option<&str> {
+        self.atoms.get(index).map(|a| a.id.as_str())
+    }
+
+    /// Gets the ids of all atoms in the zone with the passed `zone_id` at the passed [Time],
+    /// ordered by the order the atoms were declared in the setup.
+    /// Atoms which did not appear yet are not part of any zone.
+    /// Returns an empty list if there is no such zone.
+    pub fn atoms_in_zone(&self, zone_id: &str, time: Time) -> Vec<&str> {
+        let time = self.timeline_time(time);
+        let Some(zone) = self.machine.zone.get(zone_id) else {
+            return Vec::new();
+        };
+        self.atoms
+            .iter()
+            .filter(|a| a.timelines.visible.get(time))
+            .filter(|a| is_in_zone(a, zone, time))
+            .map(|a| a.id.as_str())
+            .collect()
+    }
+
+    /// Exports the trajectories of all atoms as CSV
+    /// with the columns `time,atom_id,x,y,shuttling`.
+    /// The trajectories are sampled with `fps` frames per second over the whole [Animator::duration]
+    /// (including the first and last frame).
+    /// Rows are ordered by time and then by the order the atoms were declared in the setup.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is `0`.
+    pub fn export_trajectories_csv(&self, fps: u32) -> String {
+        assert!(fps > 0, "Cannot sample trajectories at 0 fps");
+        let frame_count: u64 = (self.duration * fps).ceil().try_into().unwrap();
+
+        let mut csv = String::from("time,atom_id,x,y,shuttling\n");
+        for frame in 0..=frame_count {
+            let time = frame as f32 / fps as f32;
+            for Atom { id, timelines, .. } in &self.atoms {
+                let (position, _, _, shuttling, _, _, _, _) =
+                    timelines.get(self.timeline_time(time.into()));
+                let (x, y): (f32, f32) = position.into();
+                writeln!(csv, "{time:.6},{id},{x:.6},{y:.6},{shuttling}")
+                    .expect("Writing to a string cannot fail");
+            }
+        }
+        csv
+    }
+
+    /// Quantizes the times of all keyframes to the frame-grid of a video with `fps` frames per second,
+    /// i.e., moves the start and end of each keyframe to the nearest multiple of `1 / fps` (in playback-time).
+    /// This avoids sub-frame jitter caused by drift when converting long chains of times to [f32],
+    /// such that instructions start and end cleanly on frames.
+    ///
+    /// The [duration][Animator::duration] and the [instruction times][Animator::instruction_times]
+    /// are quantized as well.
+    /// The quantization is kept when applying a new visual using [Animator::apply_visual].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is `0`.
+    pub fn quantize_to_fps(&mut self, fps: u32) {
+        assert!(fps > 0, "Cannot quantize to 0 fps");
+        self.fps = Some(fps);
+        self.quantize_timelines(fps);
+        let fps = Fraction::from(fps);
+        let quantize = |time: Fraction| (time * fps).round() / fps;
+        self.duration = quantize(self.duration);
+        for (time, _) in &mut self.instruction_times {
+            *time = quantize(*time);
+        }
+    }
+
+    /// Quantizes the times of all keyframes to the frame-grid of `fps`.
+    /// See [Animator::quantize_to_fps].
+    fn quantize_timelines(&mut self, fps: u32) {
+        let fps = fps as f32;
+        let start = self.window_start.unwrap_or(0.);
+        let quantize =
+            move |time: Time| Time::from(((time.into_inner() - start) * fps).round() / fps + start);
+        for Atom { timelines, .. } in &mut self.atoms {
+            timelines.map_times(quantize);
+        }
+        for timeline in self.zone_highlights.values_mut() {
+            timeline.map_times(quantize);
+        }
+        for timeline in self.zone_over_capacity.values_mut() {
+            timeline.map_times(quantize);
+        }
+    }
+
+    /// Converts the passed playback-[Time] into the [Time] of the timelines:
+    /// When clipped to a window, the time is clamped to the window and shifted by its start.
+    fn timeline_time(&self, time: Time) -> Time {
+        match self.window_start {
+            Some(start) => time.clamp(0.0.into(), self.duration.f32().into()) + start,
+            None => time,
         }
     }
 
@@ -475,73 +968,471 @@ impl Animator {
         self.visual.viewport.color.rgba()
     }
 
-    /// Format the given [Time] into a time-string according to the [TimeConfig] in the current [VisualConfig].
-    fn format_time(&self, time: Time) -> String {
+    /// Format the given [Time] into a time-string according to the [TimeConfig] in the current [VisualConfig]
+    /// and write it into the passed `target` (replacing its contents).
+    /// When clipped to a window, the time is shown relative to the start of the window.
+    fn write_time(&self, time: Time, target: &mut String) {
+        target.clear();
+
         if !self.visual.time.display {
             // Don't display the time
-            return String::new();
+            return;
         }
 
-        format!(
+        let time = time.into_inner() - self.window_start.unwrap_or(0.);
+
+        write!(
+            target,
             "{}{:.*} {}",
-            self.visual.time.prefix,
-            self.visual.time.precision.f64().abs().floor() as usize,
-            time,
-            self.machine.time.unit,
+            self.visual.time.prefix, self.visual.time.precision, time, self.machine.time.unit,
         )
+        .expect("Writing to a string cannot fail");
     }
 }
 
-/// Extracts the position of a [TimedInstruction],
-/// if the instruction has a position,
-/// otherwise returns [None].
-fn get_position(instruction: &TimedInstruction) -> Option<(Fraction, Fraction)> {
-    match instruction {
-        TimedInstruction::Load { position, .. } | TimedInstruction::Store { position, .. } => {
-            *position
+/// Warns about all operations in the passed `instruction_times`
+/// which have no style or a fully transparent color in the passed `visual`
+fn check_operation_styles(
+    instruction_times: &[(Fraction, &'static str)],
+    visual: &VisualConfig,
+    warnings: &mut Vec<AnimatorWarning>,
+) {
+    let used_operations: BTreeSet<_> = instruction_times.iter().map(|(_, i)| *i).collect();
+    for operation in used_operations {
+        let config = match operation {
+            "rz" => Some(&visual.operation.config.rz),
+            "ry" => Some(&visual.operation.config.ry),
+            "cz" => Some(&visual.operation.config.cz.operation),
+            "measure" => visual
+                .operation
+                .config
+                .measure
+                .as_ref()
+                .map(|m| &m.operation),
+            "reset" => visual.operation.config.reset.as_ref(),
+            _ => continue, // not an operation
+        };
+        match config {
+            None => warnings.push(AnimatorWarning::MissingOperationStyle { operation }),
+            Some(config) if config.color.a == 0 => {
+                warnings.push(AnimatorWarning::TransparentOperationColor { operation })
+            }
+            Some(_) => {}
         }
-        TimedInstruction::Move { position, .. } => Some(*position),
-        _ => None,
     }
 }
 
-/// Checks whether an `atom` is in the passed `zone` at the specified `time`.
-fn is_in_zone(
-    atom: &Atom,
-    zone: &naviz_parser::config::machine::ZoneConfig,
-    time: Fraction,
-) -> bool {
-    let position = atom.timelines.position.get(time.f32().into());
-    position.x >= zone.from.0.f32()
-        && position.y >= zone.from.1.f32()
-        && position.x <= zone.to.0.f32()
-        && position.y <= zone.to.1.f32()
+/// Applies the passed `visual` to the passed `atoms`:
+/// Sets their names and shapes and (re-)animates their [Operation]s.
+fn style_atoms(atoms: &mut [Atom], visual: &VisualConfig) {
+    for atom in atoms {
+        let name = get_name(&visual.atom.legend.name, &atom.id).unwrap_or_default();
+        atom.name = match visual.atom.legend.show {
+            AtomLabel::Name => name,
+            AtomLabel::Id => atom.id.clone(),
+            AtomLabel::Both if name.is_empty() || name == atom.id => atom.id.clone(),
+            AtomLabel::Both => format!("{name} ({})", atom.id),
+        };
+        atom.shape = get_shape(atom.setup_shape.unwrap_or(visual.atom.shape));
+        atom.timelines.reset_style(visual.atom.radius);
+        for operation in &atom.operations {
+            insert_operation(&mut atom.timelines, operation, visual);
+        }
+    }
 }
 
-/// Checks whether two positions `a` and `b` are at most `max_distance` apart.
-fn is_close(a: &Position, b: &Position, max_distance: Fraction) -> bool {
-    let distance_sq = (a.x - b.x).powi(2) + (a.y - b.y).powi(2);
-    distance_sq <= max_distance.f32().powi(2)
+/// Sets the [positions][GhostConfig::positions] of the ghosts in the passed `config`
+/// (if ghosts are configured) to the final positions of the passed `atoms`.
+/// See [Animator::final_positions].
+fn set_ghost_positions(config: &mut Config, atoms: &[Atom]) {
+    if let Some(ghost) = &mut config.atoms.ghost {
+        ghost.positions = atoms
+            .iter()
+            .map(|atom| atom.timelines.position.last_value().into())
+            .collect();
+    }
 }
 
-/// Filters the passed `atoms`-slice to only contain the atoms that are targeted
-/// by the  passed `instruction` at the specified `start_time` (time the instruction starts)
-/// and returns an iterator over all qualifying atoms.
-fn targeted<'a>(
-    atoms: &'a mut [Atom],
-    instruction: &'a TimedInstruction,
-    start_time: Fraction,
-    machine: &'a MachineConfig,
-) -> impl Iterator<Item = &'a mut Atom> {
-    enum Match<'a> {
-        /// Match a single atom by ID
-        Atom(&'a str),
-        /// Match multiple atoms by id or zones by config
-        AtomsOrZones {
-            atoms: Vec<&'a str>,
-            zones: Vec<&'a naviz_parser::config::machine::ZoneConfig>,
-        },
-        /// Match by index
+/// Creates the static [Config] from the passed `machine` and `visual`.
+/// The `content_extent` of the atoms (tl_x, tl_y, br_x, br_y)
+/// is grown to fit the machine and the margin.
+fn build_config(
+    machine: &MachineConfig,
+    visual: &VisualConfig,
+    mut content_extent: (Fraction, Fraction, Fraction, Fraction),
+    warnings: &mut Vec<AnimatorWarning>,
+) -> Config {
+    // Grow content extent to fit zones and traps (including their radius)
+    let trap_radius = visual.machine.trap.radius;
+    for (x, y) in machine
+        .zone
+        .values()
+        .flat_map(|z| z.shape.bounding_points())
+        .chain(machine.trap.iter().flat_map(|t| {
+            let (x, y) = t.1.position;
+            [
+                (x - trap_radius, y - trap_radius),
+                (x + trap_radius, y + trap_radius),
+            ]
+        }))
+    {
+        content_extent.0 = content_extent.0.min(x);
+        content_extent.1 = content_extent.1.min(y);
+        content_extent.2 = content_extent.2.max(x);
+        content_extent.3 = content_extent.3.max(y);
+    }
+
+    // Add margin to extent
+    content_extent.0 -= visual.coordinate.margin;
+    content_extent.1 -= visual.coordinate.margin;
+    content_extent.2 += visual.coordinate.margin;
+    content_extent.3 += visual.coordinate.margin;
+
+    // The legend entries
+    let mut legend_entries = Vec::new();
+    if visual.zone.legend.display {
+        legend_entries.push(LegendSection {
+            name: visual.zone.legend.title.clone(),
+            entries: machine
+                .zone
+                .iter()
+                .filter_map(|(id, _)| {
+                    get_first_match_with_regex(&visual.zone.config, id)
+                        .filter(|(_, zone)| !zone.name.is_empty())
+                        .map(|(regex, zone)| LegendEntry {
+                            text: regex.replace(id, &zone.name).into_owned(),
+                            color: Some(zone.color.rgba()),
+                            shape: Shape::Circle,
+                            pattern: Pattern::Solid,
+                        })
+                })
+                .collect(),
+        });
+    }
+    if visual.operation.legend.display {
+        legend_entries.push(LegendSection {
+            name: visual.operation.legend.title.clone(),
+            entries: visual
+                .operation
+                .config
+                .iter()
+                .filter(|op| !op.name.is_empty())
+                .map(|op| LegendEntry {
+                    text: op.name.clone(),
+                    color: Some(op.color.rgba()),
+                    shape: Shape::Circle,
+                    pattern: get_pattern(op.pattern),
+                })
+                .collect(),
+        });
+    }
+    if visual.machine.legend.display {
+        legend_entries.push(LegendSection {
+            name: visual.machine.legend.title.clone(),
+            entries: [
+                (&visual.machine.trap.name, visual.atom.trapped.color),
+                (&visual.machine.shuttle.name, visual.atom.shuttling.color),
+            ]
+            .into_iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, color)| LegendEntry {
+                text: name.clone(),
+                color: Some(color.rgba()),
+                // Trapped and shuttling atoms are drawn with the atom shape
+                shape: get_shape(visual.atom.shape),
+                pattern: Pattern::Solid,
+            })
+            .collect(),
+        });
+    }
+
+    // Create static config
+    Config {
+        machine: naviz_state::config::MachineConfig {
+            grid: GridConfig {
+                step: (
+                    visual.coordinate.tick.x.f32(),
+                    visual.coordinate.tick.y.f32(),
+                ),
+                origin: (
+                    visual.coordinate.tick.origin.0.f32(),
+                    visual.coordinate.tick.origin.1.f32(),
+                ),
+                ticks: visual.coordinate.tick.positions.as_ref().map(|p| {
+                    (
+                        p.x.iter().copied().map(ToFloat::f32).collect(),
+                        p.y.iter().copied().map(ToFloat::f32).collect(),
+                    )
+                }),
+                line: LineConfig {
+                    width: visual.coordinate.tick.line.thickness.f32(),
+                    segment_length: visual.coordinate.tick.line.dash.length.f32(),
+                    duty: Into::<Fraction>::into(visual.coordinate.tick.line.dash.duty).f32(),
+                    color: visual.coordinate.tick.color.rgba(),
+                },
+                display_ticks: visual.coordinate.tick.display,
+                legend: GridLegendConfig {
+                    step: (
+                        visual.coordinate.number.x.distance.f32(),
+                        visual.coordinate.number.y.distance.f32(),
+                    ),
+                    font: FontConfig {
+                        size: visual
+                            .coordinate
+                            .number
+                            .font
+                            .size
+                            .get(visual.font.size)
+                            .f32(),
+                        color: visual.coordinate.number.font.color.rgba(),
+                        family: visual.coordinate.number.font.family.to_owned(),
+                    },
+                    labels: (
+                        visual.coordinate.axis.x.clone(),
+                        visual.coordinate.axis.y.clone(),
+                    ),
+                    position: (
+                        match visual.coordinate.number.x.position {
+                            TopBottomPosition::Bottom => VPosition::Bottom,
+                            TopBottomPosition::Top => VPosition::Top,
+                        },
+                        match visual.coordinate.number.y.position {
+                            LeftRightPosition::Left => HPosition::Left,
+                            LeftRightPosition::Right => HPosition::Right,
+                        },
+                    ),
+                    display_labels: visual.coordinate.axis.display,
+                    display_numbers: visual.coordinate.number.display,
+                    precision: visual
+                        .coordinate
+                        .number
+                        .precision
+                        .unwrap_or(GridLegendConfig::DEFAULT_PRECISION),
+                },
+            },
+            traps: TrapConfig {
+                positions: machine
+                    .trap
+                    .values()
+                    .map(|t| (t.position.0.f32(), t.position.1.f32()))
+                    .collect(),
+                radius: visual.machine.trap.radius.f32(),
+                line_width: visual.machine.trap.line_width.f32(),
+                color: visual.machine.trap.color.rgba(),
+            },
+            zones: machine
+                .zone
+                .iter()
+                .map(|(id, zone)| {
+                    let (start, size, shape) = match &zone.shape {
+                        naviz_parser::config::machine::ZoneShape::Rectangle {
+                            from,
+                            to,
+                            rotation,
+                        } => (
+                            (from.0.f32(), from.1.f32()),
+                            ((to.0 - from.0).f32(), (to.1 - from.1).f32()),
+                            ZoneShape::Rectangle {
+                                rotation: rotation.f32(),
+                            },
+                        ),
+                        naviz_parser::config::machine::ZoneShape::Circle { center, radius } => (
+                            ((center.0 - *radius).f32(), (center.1 - *radius).f32()),
+                            (
+                                (*radius * Fraction::from(2)).f32(),
+                                (*radius * Fraction::from(2)).f32(),
+                            ),
+                            ZoneShape::Circle,
+                        ),
+                    };
+                    let default_line = ZoneConfigConfig {
+                        color: naviz_parser::common::color::Color {
+                            r: 0,
+                            g: 0,
+                            b: 0,
+                            a: 0,
+                        },
+                        fill: None,
+                        over_capacity: None,
+                        line: naviz_parser::config::visual::LineConfig {
+                            dash: naviz_parser::config::visual::DashConfig {
+                                length: Default::default(),
+                                duty: naviz_parser::common::percentage::Percentage(
+                                    Default::default(),
+                                ),
+                            },
+                            thickness: Default::default(),
+                        },
+                        name: "".to_owned(),
+                    };
+                    let line = get_first_match(&visual.zone.config, id).unwrap_or_else(|| {
+                        warnings.push(AnimatorWarning::MissingZoneStyle { zone: id.clone() });
+                        &default_line
+                    });
+                    ZoneConfig {
+                        start,
+                        size,
+                        shape,
+                        line: LineConfig {
+                            width: line.line.thickness.f32(),
+                            segment_length: line.line.dash.length.f32(),
+                            duty: line.line.dash.duty.0.f32(),
+                            color: line.color.rgba(),
+                        },
+                        fill: line.fill.map(|c| c.rgba()),
+                    }
+                })
+                .collect(),
+        },
+        atoms: AtomsConfig {
+            label: FontConfig {
+                size: visual.atom.legend.font.size.get(visual.font.size).f32(),
+                color: visual.atom.legend.font.color.rgba(),
+                family: visual.atom.legend.font.family.to_owned(),
+            },
+            shuttle: LineConfig {
+                width: visual.machine.shuttle.line.thickness.f32(),
+                segment_length: visual.machine.shuttle.line.dash.length.f32(),
+                duty: Into::<Fraction>::into(visual.machine.shuttle.line.dash.duty).f32(),
+                color: visual.machine.shuttle.color.rgba(),
+            },
+            interaction: InteractionConfig {
+                radius: machine.distance.interaction.f32(),
+                width: visual.machine.trap.line_width.f32(),
+                // Faint version of the color of the cz-gate
+                color: {
+                    let [r, g, b, a] = visual.operation.config.cz.operation.color.rgba();
+                    [r, g, b, a / 4]
+                },
+            },
+            outline: visual.atom.outline.as_ref().map(|outline| OutlineConfig {
+                width: outline.width.f32(),
+                color: outline.color.rgba(),
+            }),
+            // Positions are set from the timelines (see `set_ghost_positions`)
+            ghost: visual.atom.ghost.as_ref().map(|ghost| GhostConfig {
+                radius: visual.atom.radius.f32(),
+                color: ghost.color.rgba(),
+                positions: Vec::new(),
+            }),
+        },
+        content_extent: (
+            (content_extent.0.f32(), content_extent.1.f32()),
+            (content_extent.2.f32(), content_extent.3.f32()),
+        ),
+        padding: Padding {
+            top: visual.viewport.padding.top.f32(),
+            bottom: visual.viewport.padding.bottom.f32(),
+            left: visual.viewport.padding.left.f32(),
+            right: visual.viewport.padding.right.f32(),
+        },
+        flip_y: matches!(visual.coordinate.y_axis, YAxis::Up),
+        background: visual
+            .viewport
+            .background_image
+            .as_ref()
+            .map(|image| BackgroundConfig {
+                path: image.path.clone(),
+                extent: (
+                    (image.extent.from.0.f32(), image.extent.from.1.f32()),
+                    (image.extent.to.0.f32(), image.extent.to.1.f32()),
+                ),
+                opacity: image.opacity.f32(),
+            }),
+        legend: LegendConfig {
+            font: FontConfig {
+                size: visual.sidebar.font.size.get(visual.font.size).f32(),
+                color: visual.sidebar.font.color.rgba(),
+                family: visual.sidebar.font.family.to_owned(),
+            },
+            heading_skip: visual.sidebar.padding.heading.f32(),
+            entry_skip: visual.sidebar.padding.entry.f32(),
+            color_circle_radius: visual.sidebar.color_radius.f32(),
+            color_padding: visual.sidebar.padding.color.f32(),
+            entries: legend_entries,
+        },
+        time: TimeConfig {
+            font: FontConfig {
+                size: visual.time.font.size.get(visual.font.size).f32(),
+                color: visual.time.font.color.rgba(),
+                family: visual.time.font.family.clone(),
+            },
+            display: visual.time.display,
+        },
+    }
+}
+
+/// Extracts the position of a [TimedInstruction],
+/// if the instruction has a position,
+/// otherwise returns [None].
+fn get_position(instruction: &TimedInstruction) -> Option<(Fraction, Fraction)> {
+    match instruction {
+        TimedInstruction::Load { position, .. } | TimedInstruction::Store { position, .. } => {
+            *position
+        }
+        TimedInstruction::Move { position, .. } => Some(*position),
+        _ => None,
+    }
+}
+
+/// Checks whether an `atom` is in the passed `zone` at the specified `time`.
+fn is_in_zone(atom: &Atom, zone: &naviz_parser::config::machine::ZoneConfig, time: Time) -> bool {
+    let position = atom.timelines.position.get(time);
+    zone.shape.contains((position.x, position.y))
+}
+
+/// Checks whether two positions `a` and `b` are at most `max_distance` apart.
+fn is_close(a: &Position, b: &Position, max_distance: Fraction) -> bool {
+    let distance_sq = (a.x - b.x).powi(2) + (a.y - b.y).powi(2);
+    distance_sq <= max_distance.f32().powi(2)
+}
+
+/// Gets the atoms (by index) which are candidates for a `cz` on the passed `targets`
+/// at the specified `start_time` (i.e., the atoms which are targeted directly or are in a targeted zone)
+/// together with their positions at that time.
+/// Of these, only atoms within the interaction distance of another candidate interact.
+fn cz_candidates(
+    atoms: &[Atom],
+    targets: &[String],
+    start_time: Fraction,
+    machine: &MachineConfig,
+) -> Vec<(usize, Position)> {
+    let zones: Vec<_> = targets
+        .iter()
+        .filter_map(|id| machine.zone.get(id))
+        .collect();
+
+    atoms
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| {
+            targets.contains(&a.id)
+                || zones
+                    .iter()
+                    .any(|zone| is_in_zone(a, zone, start_time.f32().into()))
+        })
+        .map(|(idx, a)| (idx, a.timelines.position.get(start_time.f32().into())))
+        .collect()
+}
+
+/// Filters the passed `atoms`-slice to only contain the atoms that are targeted
+/// by the  passed `instruction` at the specified `start_time` (time the instruction starts)
+/// and returns an iterator over all qualifying atoms.
+fn targeted<'a>(
+    atoms: &'a mut [Atom],
+    instruction: &'a TimedInstruction,
+    start_time: Fraction,
+    machine: &'a MachineConfig,
+) -> impl Iterator<Item = &'a mut Atom> {
+    enum Match<'a> {
+        /// Match a single atom by ID
+        Atom(&'a str),
+        /// Match multiple atoms by id or zones by config
+        AtomsOrZones {
+            atoms: Vec<&'a str>,
+            zones: Vec<&'a naviz_parser::config::machine::ZoneConfig>,
+        },
+        /// Match by index
         Index(Vec<usize>),
     }
     let m = match instruction {
@@ -549,33 +1440,27 @@ fn targeted<'a>(
         TimedInstruction::Load { id, .. }
         | TimedInstruction::Store { id, .. }
         | TimedInstruction::Move { id, .. } => Match::Atom(id),
+        // Instructions that target multiple individual atoms
+        TimedInstruction::MoveBy { ids, .. } => Match::AtomsOrZones {
+            atoms: ids.iter().map(AsRef::as_ref).collect(),
+            zones: Vec::new(),
+        },
         // Instructions that target arbitrary targets
-        TimedInstruction::Rz { targets, .. } | TimedInstruction::Ry { targets, .. } => {
-            Match::AtomsOrZones {
-                zones: targets
-                    .iter()
-                    .filter_map(|id| machine.zone.get(id))
-                    .collect(),
-                atoms: targets.iter().map(AsRef::as_ref).collect(),
-            }
-        }
-        // Instructions that target arbitrary targets and require interaction distance
-        TimedInstruction::Cz { targets, .. } => {
-            let zones: Vec<_> = targets
+        TimedInstruction::Rz { targets, .. }
+        | TimedInstruction::Ry { targets, .. }
+        | TimedInstruction::Measure { targets, .. }
+        | TimedInstruction::Reset { targets, .. } => Match::AtomsOrZones {
+            zones: targets
                 .iter()
                 .filter_map(|id| machine.zone.get(id))
-                .collect();
-
-            // Get the position for each atom (identified by index) that is targeted at the start_time
-            let in_zone: Vec<_> = atoms
-                .iter()
-                .enumerate()
-                .filter(|(_, a)| {
-                    targets.contains(&a.id)
-                        || zones.iter().any(|zone| is_in_zone(a, zone, start_time))
-                })
-                .map(|(idx, a)| (idx, a.timelines.position.get(start_time.f32().into())))
-                .collect();
+                .collect(),
+            atoms: targets.iter().map(AsRef::as_ref).collect(),
+        },
+        // Instructions that only target zones
+        TimedInstruction::Highlight { .. } => Match::Index(Vec::new()),
+        // Instructions that target arbitrary targets and require interaction distance
+        TimedInstruction::Cz { targets, .. } => {
+            let in_zone = cz_candidates(atoms, targets, start_time, machine);
 
             // Generate the targeted atom indices using nested loop.
             // Assuming that at any time only clusters of two atoms exist,
@@ -603,7 +1488,10 @@ fn targeted<'a>(
         .filter(move |(idx, a)| match &m {
             Match::Atom(id) => &a.id == id,
             Match::AtomsOrZones { atoms, zones } => {
-                atoms.contains(&&*a.id) || zones.iter().any(|zone| is_in_zone(a, zone, start_time))
+                atoms.contains(&&*a.id)
+                    || zones
+                        .iter()
+                        .any(|zone| is_in_zone(a, zone, start_time.f32().into()))
             }
             Match::Index(indices) => indices.contains(idx),
         })
@@ -611,16 +1499,20 @@ fn targeted<'a>(
 }
 
 /// Gets the duration of the passed `instruction` when starting at the passed `time`,
+/// or its explicit duration (`dur=<time>`) if present.
 fn get_duration(
     instruction: &TimedInstruction,
     atoms: &[Atom],
     machine: &MachineConfig,
     time: Fraction,
 ) -> Fraction {
+    if let Some(duration) = instruction.duration() {
+        return duration;
+    }
     match instruction {
         TimedInstruction::Load { .. } => machine.time.load,
         TimedInstruction::Store { .. } => machine.time.store,
-        TimedInstruction::Move { position, id } => (|| {
+        TimedInstruction::Move { position, id, .. } => (|| {
             let start = atoms
                 .iter()
                 .find(|a| &a.id == id)?
@@ -629,44 +1521,100 @@ fn get_duration(
                 .get(time.f32().into());
             let end = (position.0.f32(), position.1.f32());
 
-            Some(
-                Diagonal(ConstantJerkFixedMaxVelocity::new_fixed(MaxVelocity(
-                    machine.movement.max_speed.f32(),
-                )))
-                .duration((), start, Position { x: end.0, y: end.1 }),
-            )
+            Some(get_move_duration(
+                &machine.movement,
+                start,
+                Position { x: end.0, y: end.1 },
+            ))
         })()
         .map(Fraction::from)
         .unwrap_or_default(),
+        // All atoms move in parallel: take the longest move
+        TimedInstruction::MoveBy { offset, ids, .. } => atoms
+            .iter()
+            .filter(|a| ids.contains(&a.id))
+            .map(|a| {
+                let start = a.timelines.position.get(time.f32().into());
+                let end = offset_position(start, *offset);
+                Fraction::from(get_move_duration(&machine.movement, start, end))
+            })
+            .max()
+            .unwrap_or_default(),
         TimedInstruction::Rz { .. } => machine.time.rz,
         TimedInstruction::Ry { .. } => machine.time.ry,
         TimedInstruction::Cz { .. } => machine.time.cz,
+        TimedInstruction::Measure { .. } => machine.time.measure,
+        TimedInstruction::Reset { .. } => machine.time.reset,
+        // Highlights change the color instantly
+        TimedInstruction::Highlight { .. } => Fraction::ZERO,
+    }
+}
+
+/// Offsets the passed `position` by the passed `offset`
+fn offset_position(position: Position, offset: (Fraction, Fraction)) -> Position {
+    Position {
+        x: position.x + offset.0.f32(),
+        y: position.y + offset.1.f32(),
+    }
+}
+
+/// Gets the duration of a move from `start` to `end` according to the [MovementModel]
+/// along the path selected by the [MoveInterpolation],
+/// capped to the [max_move_time][MovementConfig::max_move_time].
+/// As the interpolation is relative to the duration,
+/// a capped move keeps its profile but finishes faster.
+fn get_move_duration(movement: &MovementConfig, start: Position, end: Position) -> f32 {
+    /// The duration along the path selected by `interpolation`
+    fn path_duration<A>(
+        interpolation: MoveInterpolation,
+        interpolator: impl DurationCalculable<A, f32>,
+        argument: A,
+        start: Position,
+        end: Position,
+    ) -> f32 {
+        match interpolation {
+            MoveInterpolation::Diagonal => Diagonal(interpolator).duration(argument, start, end),
+            MoveInterpolation::ComponentWise => {
+                AxisAligned(interpolator).duration(argument, start, end)
+            }
+        }
+    }
+
+    let max_velocity = MaxVelocity(movement.max_speed.f32());
+    let interpolation = movement.interpolation;
+    let duration = match movement.model {
+        MovementModel::ConstantJerk => path_duration(
+            interpolation,
+            ConstantJerkFixedMaxVelocity::new_fixed(max_velocity),
+            (),
+            start,
+            end,
+        ),
+        MovementModel::ConstantVelocity => {
+            path_duration(interpolation, Linear(), max_velocity, start, end)
+        }
+        MovementModel::Trapezoidal { acceleration } => path_duration(
+            interpolation,
+            Trapezoidal(),
+            (max_velocity, Acceleration(acceleration.f32())),
+            start,
+            end,
+        ),
+    };
+    match movement.max_move_time {
+        Some(max) => duration.min(max.f32()),
+        None => duration,
     }
 }
 
-/// Inserts an animation for the passed `instruction` into the passed `timelines`
-fn insert_animation(
+/// Inserts the movement (or shuttling) for the passed `instruction` into the passed `timelines`.
+/// Operations are recorded as [Operation]s and animated by [insert_operation] instead.
+fn insert_movement(
     timelines: &mut AtomTimelines,
     instruction: &TimedInstruction,
     start_time: f32,
     duration: f32,
-    visual: &VisualConfig,
 ) {
-    fn add_operation(
-        timelines: &mut AtomTimelines,
-        time: f32,
-        duration: f32,
-        config: &OperationConfigConfigConfig,
-        visual: &VisualConfig,
-    ) {
-        timelines
-            .overlay_color
-            .add((time, duration, config.color.into()));
-        timelines
-            .size
-            .add((time, duration, config.radius.get(visual.atom.radius).f32()));
-    }
-
     fn add_move(
         timelines: &mut AtomTimelines,
         time: f32,
@@ -708,7 +1656,66 @@ fn insert_animation(
         TimedInstruction::Move { position, .. } => {
             add_move(timelines, start_time, duration, *position);
         }
-        TimedInstruction::Rz { .. } => {
+        TimedInstruction::MoveBy { offset, .. } => {
+            let start = timelines.position.get(start_time.into());
+            let end = offset_position(start, *offset);
+            timelines.position.add((start_time, duration, (), end));
+        }
+        TimedInstruction::Rz { .. }
+        | TimedInstruction::Ry { .. }
+        | TimedInstruction::Cz { .. }
+        | TimedInstruction::Measure { .. }
+        | TimedInstruction::Reset { .. }
+        | TimedInstruction::Highlight { .. } => {}
+    }
+}
+
+/// Inserts an animation for the passed `operation` into the passed `timelines`
+/// using the styles of the passed `visual`
+fn insert_operation(timelines: &mut AtomTimelines, operation: &Operation, visual: &VisualConfig) {
+    fn add_operation(
+        timelines: &mut AtomTimelines,
+        time: f32,
+        duration: f32,
+        config: &OperationConfigConfigConfig,
+        visual: &VisualConfig,
+    ) {
+        timelines
+            .overlay_color
+            .add((time, duration, config.easing, config.color.into()));
+        let radius = config.radius.get(visual.atom.radius) * timelines.weight;
+        timelines
+            .size
+            .add((time, duration, config.easing, radius.f32()));
+        // Show the pattern for the duration of the operation
+        timelines.pattern.add((
+            time,
+            0.,
+            ConstantTransitionPoint::Start,
+            get_pattern(config.pattern),
+        ));
+        timelines.pattern.add((
+            time + duration,
+            0.,
+            ConstantTransitionPoint::Start,
+            Pattern::Solid,
+        ));
+    }
+
+    fn add_result(timelines: &mut AtomTimelines, time: f32, duration: f32, color: Color) {
+        // Hold the result from the end of the measurement onwards
+        timelines
+            .result_color
+            .add((time, duration, ConstantTransitionPoint::End, color));
+    }
+
+    let &Operation {
+        start: start_time,
+        duration,
+        kind,
+    } = operation;
+    match kind {
+        OperationKind::Rz => {
             add_operation(
                 timelines,
                 start_time,
@@ -717,7 +1724,7 @@ fn insert_animation(
                 visual,
             );
         }
-        TimedInstruction::Ry { .. } => {
+        OperationKind::Ry => {
             add_operation(
                 timelines,
                 start_time,
@@ -726,27 +1733,91 @@ fn insert_animation(
                 visual,
             );
         }
-        TimedInstruction::Cz { .. } => {
+        OperationKind::Cz => {
             add_operation(
                 timelines,
                 start_time,
                 duration,
-                &visual.operation.config.cz,
+                &visual.operation.config.cz.operation,
                 visual,
             );
         }
+        OperationKind::Measure(result) => {
+            if let Some(measure) = &visual.operation.config.measure {
+                add_operation(timelines, start_time, duration, &measure.operation, visual);
+                if let Some(result) = result {
+                    add_result(
+                        timelines,
+                        start_time,
+                        duration,
+                        measure.result.get(result).into(),
+                    );
+                }
+            }
+        }
+        OperationKind::Reset => {
+            if let Some(reset) = &visual.operation.config.reset {
+                add_operation(timelines, start_time, duration, reset, visual);
+            }
+            add_result(timelines, start_time, duration, Color::default());
+        }
+        OperationKind::Interaction => {
+            // Indicate the interaction distance around all candidates of a cz
+            if visual.operation.config.cz.show_interaction_radius {
+                timelines
+                    .interaction
+                    .add((start_time, 0., ConstantTransitionPoint::Start, true));
+                timelines.interaction.add((
+                    start_time + duration,
+                    0.,
+                    ConstantTransitionPoint::Start,
+                    false,
+                ));
+            }
+        }
     }
 }
 
-/// Gets a name based of an id (from a regex-string-map)
-fn get_name(names: &[(Regex, String)], id: &str) -> String {
-    names
-        .iter()
-        .find_map(|(regex, replace)| match regex.replace(id, replace) {
+/// Converts the [Shape][naviz_parser::common::shape::Shape] from the parser
+/// to the [Shape] for the renderer
+fn get_shape(shape: naviz_parser::common::shape::Shape) -> Shape {
+    use naviz_parser::common::shape::Shape as ParserShape;
+    match shape {
+        ParserShape::Circle => Shape::Circle,
+        ParserShape::Square => Shape::Square,
+        ParserShape::Diamond => Shape::Diamond,
+        ParserShape::Cross => Shape::Cross,
+    }
+}
+
+/// Converts the [Pattern][naviz_parser::common::pattern::Pattern] from the parser
+/// to the [Pattern] for the renderer
+fn get_pattern(pattern: naviz_parser::common::pattern::Pattern) -> Pattern {
+    use naviz_parser::common::pattern::Pattern as ParserPattern;
+    match pattern {
+        ParserPattern::Solid => Pattern::Solid,
+        ParserPattern::Ring => Pattern::Ring,
+        ParserPattern::Hatch => Pattern::Hatch,
+    }
+}
+
+/// Gets a name based of an id (from a regex-string-map).
+///
+/// All entries whose regex matches are applied in order as a pipeline:
+/// Each entry replaces all matches in the name produced by the previous entries
+/// (starting with the `id`).
+/// Replacements can reference the capture-groups of their regex (e.g., `$1`).
+///
+/// Returns [None] if no entry matched
+/// or [Some] name (which may be explicitly empty) otherwise.
+fn get_name(names: &[(Regex, String)], id: &str) -> Option<String> {
+    names.iter().fold(None, |name, (regex, replace)| {
+        let replaced = match regex.replace_all(name.as_deref().unwrap_or(id), replace) {
             Cow::Borrowed(_) => None, // borrowed => original input => did not match
             Cow::Owned(n) => Some(n),
-        })
-        .unwrap_or_default()
+        };
+        replaced.or(name)
+    })
 }
 
 /// Gets the first entry of the passed `input`-map where the id matches the regex.
@@ -758,3 +1829,1045 @@ fn get_first_match_with_regex<'t, T>(input: &'t [(Regex, T)], id: &str) -> Optio
 fn get_first_match<'t, T>(input: &'t [(Regex, T)], id: &str) -> Option<&'t T> {
     get_first_match_with_regex(input, id).map(|(_, t)| t)
 }
+
+#[cfg(test)]
+mod test {
+    use std::{f32::consts::PI, slice};
+
+    use naviz_parser::config::generic::Config as GenericConfig;
+
+    use super::*;
+
+    /// Parses the passed `input` into a [GenericConfig]
+    fn config(input: &str) -> GenericConfig {
+        let lexed = naviz_parser::config::lexer::lex(input).expect("Failed to lex config");
+        naviz_parser::config::parser::parse(&lexed)
+            .expect("Failed to parse config")
+            .into()
+    }
+
+    /// Loads the example-machine of the parser
+    fn example_machine() -> MachineConfig {
+        config(include_str!("../../parser/rsc/test/example.namachine"))
+            .try_into()
+            .expect("Failed to load machine")
+    }
+
+    /// Creates an [Animator] from the example-inputs of the parser
+    fn example_animator() -> Animator {
+        animator(include_str!("../../parser/rsc/test/example.naviz"))
+    }
+
+    /// Creates an [Animator] for the passed `input`
+    /// using the example-machine and -style of the parser
+    fn animator(input: &str) -> Animator {
+        Animator::new(example_machine(), example_visual(), instructions(input))
+    }
+
+    /// Loads the example-style of the parser
+    fn example_visual() -> VisualConfig {
+        config(include_str!("../../parser/rsc/test/example.nastyle"))
+            .try_into()
+            .expect("Failed to load style")
+    }
+
+    /// Parses the passed `input` into [Instructions]
+    fn instructions(input: &str) -> Instructions {
+        let input = naviz_parser::input::lexer::lex(input).expect("Failed to lex instructions");
+        let input =
+            naviz_parser::input::parser::parse(&input).expect("Failed to parse instructions");
+        Instructions::new(input).expect("Failed to convert instructions")
+    }
+
+    /// Creates a regex-string-map for [get_name] from the passed `(regex, replacement)`-pairs
+    fn names<'a>(names: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<(Regex, String)> {
+        names
+            .into_iter()
+            .map(|(regex, replace)| (Regex::new(regex).unwrap(), replace.to_string()))
+            .collect()
+    }
+
+    /// Replacements should be able to reference capture-groups.
+    #[test]
+    fn name_capture_groups() {
+        let names = names([("^atom[_-]?(.*)$", "$1")]);
+        assert_eq!(get_name(&names, "atom_3").as_deref(), Some("3"));
+        assert_eq!(get_name(&names, "atom12").as_deref(), Some("12"));
+        assert_eq!(get_name(&names, "q3"), None, "Non-matching id got a name");
+    }
+
+    /// All matching entries should be applied in order,
+    /// each on the result of the previous entries.
+    #[test]
+    fn name_chained_substitution() {
+        let subscripts = ('0'..='9')
+            .zip('₀'..='₉')
+            .map(|(digit, subscript)| (digit.to_string(), subscript.to_string()))
+            .collect::<Vec<_>>();
+        let names = names(
+            [(r"^q\[(\d+)\]$", "q$1")]
+                .into_iter()
+                .chain(subscripts.iter().map(|(d, s)| (d.as_str(), s.as_str()))),
+        );
+        assert_eq!(get_name(&names, "q[5]").as_deref(), Some("q₅"));
+        assert_eq!(get_name(&names, "q[15]").as_deref(), Some("q₁₅"));
+        assert_eq!(get_name(&names, "atom").as_deref(), None);
+    }
+
+    /// An explicitly empty replacement should be distinguishable from no match.
+    #[test]
+    fn name_explicitly_empty() {
+        let names = names([("^hidden$", ""), ("^shown$", "$0")]);
+        assert_eq!(get_name(&names, "hidden").as_deref(), Some(""));
+        assert_eq!(get_name(&names, "shown").as_deref(), Some("shown"));
+        assert_eq!(get_name(&names, "other"), None);
+    }
+
+    /// The instruction times should contain all instructions at their absolute start times.
+    #[test]
+    fn instruction_times() {
+        let animator = example_animator();
+        let times = animator.instruction_times();
+
+        let names: Vec<_> = times.iter().map(|(_, name)| *name).collect();
+        assert_eq!(
+            names,
+            ["load", "load", "move", "move", "store", "store", "rz", "ry", "cz", "cz", "ry"],
+            "Instructions missing or in wrong order"
+        );
+        assert!(
+            times.is_sorted_by_key(|(time, _)| *time),
+            "Instruction times are not ordered"
+        );
+
+        let load = Fraction::new(21u64, 5u64);
+        assert_eq!(times[0].0, Fraction::ZERO);
+        assert_eq!(times[1].0, Fraction::ZERO);
+        assert_eq!(times[2].0, load, "Relative time not resolved");
+        assert_eq!(times[2].0, times[3].0, "Simultaneous instructions differ");
+        assert!(
+            times.last().unwrap().0 <= animator.duration(),
+            "Instruction starts after end of animation"
+        );
+    }
+
+    /// The same move should take a different time under each [MovementModel].
+    #[test]
+    fn move_duration_per_model() {
+        let mut machine = example_machine();
+        let max_speed = machine.movement.max_speed;
+        let atom = Atom {
+            id: "atom".to_string(),
+            name: String::new(),
+            shape: Shape::Circle,
+            setup_shape: None,
+            timelines: AtomTimelines::new(
+                Position::default(),
+                Color::default(),
+                1.,
+                false,
+                &machine.movement,
+            ),
+            operations: Vec::new(),
+        };
+        let instruction = TimedInstruction::Move {
+            position: (max_speed * Fraction::from(2), Fraction::ZERO),
+            id: "atom".to_string(),
+            duration: None,
+        };
+        let mut duration = |model| {
+            machine.movement.model = model;
+            get_duration(
+                &instruction,
+                slice::from_ref(&atom),
+                &machine,
+                Fraction::ZERO,
+            )
+            .f32()
+        };
+
+        let constant_jerk = duration(MovementModel::ConstantJerk);
+        let constant_velocity = duration(MovementModel::ConstantVelocity);
+        let trapezoidal = duration(MovementModel::Trapezoidal {
+            acceleration: max_speed * Fraction::from(2),
+        });
+
+        assert!((constant_jerk - 3.).abs() < 1e-4, "{constant_jerk}");
+        assert!((constant_velocity - 2.).abs() < 1e-4, "{constant_velocity}");
+        assert!((trapezoidal - 2.5).abs() < 1e-4, "{trapezoidal}");
+    }
+
+    /// Moves longer than the `max_move_time` should be clamped, shorter ones unchanged.
+    #[test]
+    fn max_move_time() {
+        let mut movement = example_machine().movement;
+        movement.model = MovementModel::ConstantJerk;
+        let start = Position::default();
+        let end = Position { x: 0., y: 30. };
+        let uncapped = get_move_duration(&movement, start, end);
+
+        movement.max_move_time = Some(Fraction::from(uncapped / 2.));
+        assert!(
+            (get_move_duration(&movement, start, end) - uncapped / 2.).abs() < 1e-4,
+            "Move exceeding the cap was not clamped"
+        );
+
+        movement.max_move_time = Some(Fraction::from(uncapped * 2.));
+        assert_eq!(
+            get_move_duration(&movement, start, end),
+            uncapped,
+            "Move under the cap was changed"
+        );
+    }
+
+    /// Under [MoveInterpolation::ComponentWise],
+    /// a diagonal move should follow the axis-aligned path instead of the straight line.
+    #[test]
+    fn component_wise_move() {
+        let input = "atom (0, 0) atom0\n@0 move (8, 8) atom0";
+        let midpoint = |interpolation| {
+            let mut machine = example_machine();
+            machine.movement.interpolation = interpolation;
+            let start = Position::default();
+            let end = Position { x: 8., y: 8. };
+            let duration = get_move_duration(&machine.movement, start, end);
+            let animator = Animator::new(machine, example_visual(), instructions(input));
+            animator.state((duration / 2.).into()).atoms[0].position
+        };
+
+        let (x, y) = midpoint(MoveInterpolation::Diagonal);
+        assert!((x - 4.).abs() < 1e-3 && (y - 4.).abs() < 1e-3, "{x}, {y}");
+
+        let (x, y) = midpoint(MoveInterpolation::ComponentWise);
+        assert!((x - 8.).abs() < 1e-3 && y.abs() < 1e-3, "{x}, {y}");
+    }
+
+    /// The operation-legend should contain all configured operations, including measurements.
+    #[test]
+    fn operation_legend() {
+        let animator = example_animator();
+        let config = animator.config();
+        let operations = config
+            .legend
+            .entries
+            .iter()
+            .find(|section| section.name == "Operations")
+            .expect("No operation legend");
+
+        let names: Vec<_> = operations.entries.iter().map(|e| e.text.as_str()).collect();
+        assert!(names.contains(&"measure"), "Measurement missing: {names:?}");
+        let measure = operations
+            .entries
+            .iter()
+            .find(|e| e.text == "measure")
+            .unwrap();
+        assert_eq!(measure.color, Some([255, 255, 0, 255]));
+    }
+
+    /// The trajectory-export should contain a header and one row per atom and frame.
+    #[test]
+    fn export_trajectories_csv() {
+        let animator = example_animator();
+        let fps = 10;
+        let csv = animator.export_trajectories_csv(fps);
+        let mut lines = csv.lines();
+
+        assert_eq!(lines.next(), Some("time,atom_id,x,y,shuttling"));
+
+        let frame_count: u64 = (animator.duration() * fps).ceil().try_into().unwrap();
+        let rows: Vec<_> = lines.collect();
+        assert_eq!(
+            rows.len() as u64,
+            animator.atoms.len() as u64 * (frame_count + 1)
+        );
+
+        // Atoms in setup-order
+        let ids: Vec<_> = animator.atoms.iter().map(|a| a.id.as_str()).collect();
+        for (row, id) in rows.iter().zip(ids.iter().cycle()) {
+            let columns: Vec<_> = row.split(',').collect();
+            assert_eq!(columns.len(), 5, "Wrong number of columns: {row}");
+            assert_eq!(columns[1], *id, "Atoms not in setup-order");
+        }
+        assert!(
+            rows[0].starts_with("0.000000,"),
+            "Time not formatted consistently"
+        );
+    }
+
+    /// The trajectory-export should reject `0` fps instead of producing `NaN`-times.
+    #[test]
+    #[should_panic(expected = "0 fps")]
+    fn export_trajectories_csv_zero_fps() {
+        example_animator().export_trajectories_csv(0);
+    }
+
+    /// The final position of a moved atom should be the target of its last move,
+    /// while an unmoved atom stays at its setup-position.
+    #[test]
+    fn final_positions() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             atom (5, 5) atom1\n\
+             @0 move (10, 0) atom0\n\
+             @+ move (3, 7) atom0",
+        );
+        let positions: Vec<_> = animator
+            .final_positions()
+            .into_iter()
+            .map(|(id, position)| (id, position.x, position.y))
+            .collect();
+        assert_eq!(
+            positions,
+            [("atom0".to_owned(), 3., 7.), ("atom1".to_owned(), 5., 5.)]
+        );
+    }
+
+    /// After quantizing, all keyframes should lie on the frame-grid.
+    #[test]
+    fn quantize_to_fps() {
+        let mut animator = animator(
+            "atom (0, 0) atom0\n\
+             @0.013 move (10, 0) atom0\n\
+             @+0.37 load atom0\n\
+             @+0.111 move (3, 7) atom0",
+        );
+        let fps = 30;
+        animator.quantize_to_fps(fps);
+        assert_quantized(&animator, fps);
+
+        // Quantization should be kept for the recreated styles
+        animator.apply_visual(example_visual());
+        assert_quantized(&animator, fps);
+    }
+
+    /// Asserts that all keyframes, the duration, and the instruction times
+    /// of the passed `animator` lie on the frame-grid of `fps`.
+    fn assert_quantized(animator: &Animator, fps: u32) {
+        let on_grid = |time: f32| {
+            let frames = time * fps as f32;
+            assert!(
+                (frames - frames.round()).abs() < 1e-3,
+                "{time} is not a multiple of 1/{fps}"
+            );
+        };
+        on_grid(animator.duration().f32());
+        for (time, _) in animator.instruction_times() {
+            on_grid(time.f32());
+        }
+        for Atom { timelines, .. } in &animator.atoms {
+            let mut times = Vec::new();
+            times.extend(
+                timelines
+                    .position
+                    .keyframes()
+                    .iter()
+                    .flat_map(|k| [*k.time(), *k.time() + k.duration()]),
+            );
+            times.extend(timelines.shuttling.keyframes().iter().map(|k| *k.time()));
+            times.extend(
+                timelines
+                    .size
+                    .keyframes()
+                    .iter()
+                    .flat_map(|k| [*k.time(), *k.time() + k.duration()]),
+            );
+            assert!(!times.is_empty(), "No keyframes to check");
+            for time in times {
+                on_grid(time.into_inner());
+            }
+        }
+    }
+
+    /// Quantizing to `0` fps would not produce a frame-grid.
+    #[test]
+    #[should_panic(expected = "0 fps")]
+    fn quantize_to_zero_fps() {
+        animator("atom (0, 0) atom0").quantize_to_fps(0);
+    }
+
+    /// A measured atom should keep its result-color until it is reset.
+    #[test]
+    fn measurement_result_color() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             @0 measure atom0 1\n\
+             @10 reset atom0",
+        );
+        let color = |time: f32| animator.state(time.into()).atoms[0].color;
+        let trapped = animator.visual.atom.trapped.color.rgba();
+        let one = animator
+            .visual
+            .operation
+            .config
+            .measure
+            .as_ref()
+            .expect("Example style has no measurement config")
+            .result
+            .one
+            .rgba();
+
+        assert_eq!(color(5.), one, "Result color not applied after measurement");
+        assert_eq!(color(9.), one, "Result color not held until reset");
+        assert_eq!(color(11.), trapped, "Result color not cleared by reset");
+    }
+
+    /// A `move` with a set of ids should move all atoms by the same offset in parallel.
+    #[test]
+    fn move_by_multiple_atoms() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             atom (10, 0) atom1\n\
+             atom (20, 5) atom2\n\
+             @0 move (0, 30) { atom0, atom1, atom2 }\n\
+             @+ rz 1 atom0",
+        );
+
+        // All atoms move the same distance, so the move takes as long as a single one
+        let single = get_move_duration(
+            &animator.machine.movement,
+            Position::default(),
+            Position { x: 0., y: 30. },
+        );
+        let times = animator.instruction_times();
+        assert_eq!(times[0].1, "move");
+        assert!(
+            (times[1].0.f32() - single).abs() < 1e-4,
+            "Parallel move did not take the duration of a single move"
+        );
+
+        let positions: Vec<_> = animator
+            .state(single.into())
+            .atoms
+            .iter()
+            .map(|a| a.position)
+            .collect();
+        assert_eq!(positions, [(0., 30.), (10., 30.), (20., 35.)]);
+    }
+
+    /// An explicit `dur=<time>` should override the duration from the machine.
+    #[test]
+    fn duration_override() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             atom (1, 0) atom1\n\
+             @0 cz {atom0, atom1} dur=7\n\
+             @+ rz 1 atom0",
+        );
+
+        let times = animator.instruction_times();
+        assert_eq!(times[0].1, "cz");
+        assert_ne!(animator.machine.time.cz, Fraction::from(7));
+        assert_eq!(times[1].0, Fraction::from(7));
+    }
+
+    /// Getting the state into an existing [State] should reuse its allocations.
+    #[test]
+    fn state_into_reuses_allocations() {
+        let animator = example_animator();
+        let mut state = animator.state(Time::from(0.));
+        let atoms = (state.atoms.as_ptr(), state.atoms.capacity());
+        let time = (state.time.as_ptr(), state.time.capacity());
+
+        animator.state_into(Time::from(1.), &mut state);
+        animator.state_into(Time::from(2.), &mut state);
+
+        assert_eq!(
+            (state.atoms.as_ptr(), state.atoms.capacity()),
+            atoms,
+            "Atoms were reallocated"
+        );
+        assert_eq!(
+            (state.time.as_ptr(), state.time.capacity()),
+            time,
+            "Time string was reallocated"
+        );
+        assert_eq!(
+            state.time,
+            animator.state(Time::from(2.)).time,
+            "State differs from freshly created state"
+        );
+    }
+
+    /// Only the moved atom should be reported as changed.
+    #[test]
+    fn state_into_reports_changed_atoms() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             atom (10, 0) atom1\n\
+             atom (20, 0) atom2\n\
+             @0 move (10, 30) atom1",
+        );
+        let mut state = State::default();
+
+        assert_eq!(
+            animator.state_into(Time::from(0.), &mut state),
+            AtomsDiff::All,
+            "Initial state not reported as fully changed"
+        );
+        let end = animator.duration().f32();
+        assert_eq!(
+            animator.state_into(Time::from(end), &mut state),
+            AtomsDiff::Changed(vec![1])
+        );
+        assert!(
+            animator.state_into(Time::from(end), &mut state).is_empty(),
+            "Unchanged state reported changes"
+        );
+        assert_eq!(
+            state.diff_atoms(&animator.state(Time::from(0.))),
+            AtomsDiff::Changed(vec![1])
+        );
+    }
+
+    /// An atom with a setup-time should only be visible from that time on.
+    #[test]
+    fn atom_appear_time() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             @5 atom (10, 10) atom1",
+        );
+        let atom = |time: f32| animator.state(time.into()).atoms[1].clone();
+        let radius = animator.visual.atom.radius.f32();
+
+        assert_eq!(atom(2.).size, 0., "Atom visible before appear time");
+        assert!(
+            atom(2.).label.is_empty(),
+            "Label visible before appear time"
+        );
+        assert_eq!(atom(6.).size, radius, "Atom not visible after appear time");
+        assert_eq!(animator.state(2.0.into()).atoms[0].size, radius);
+    }
+
+    /// The position of an atom should follow its moves and only exist once the atom appeared.
+    #[test]
+    fn atom_position() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             @0 move (10, 0) atom0\n\
+             @5 atom (10, 10) atom1",
+        );
+        let position = |id, time: f32| animator.atom_position(id, time.into()).map(|p| (p.x, p.y));
+
+        assert_eq!(position("atom0", 0.), Some((0., 0.)));
+        assert_eq!(
+            position("atom0", animator.duration().f32()),
+            Some((10., 0.))
+        );
+        assert_eq!(
+            position("atom1", 2.),
+            None,
+            "Atom exists before appear time"
+        );
+        assert_eq!(position("atom1", 6.), Some((10., 10.)));
+        assert_eq!(position("unknown", 0.), None);
+    }
+
+    /// An atom hit in the [State] should map back to its id.
+    #[test]
+    fn atom_id_of_hit() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             atom (10, 10) atom1",
+        );
+        let state = animator.state(0.0.into());
+        let (idx, _) = state.atom_at((10.5, 10.)).expect("No atom at position");
+        assert_eq!(animator.atom_id(idx), Some("atom1"));
+        assert_eq!(animator.atom_id(state.atoms.len()), None);
+    }
+
+    /// Showing ids should label the atoms with their ids from the setup,
+    /// regardless of the name-mapping.
+    #[test]
+    fn show_atom_ids() {
+        let instructions = instructions(
+            "atom (0, 0) atom0\n\
+             atom (10, 10) qubit_1\n\
+             @0 move (5, 5) atom0",
+        );
+        let mut visual = example_visual();
+        visual.atom.legend.name = names([("^atom(\\d+)$", "a$1"), ("^qubit_(\\d+)$", "q$1")]);
+        visual.atom.legend.show = AtomLabel::Id;
+        let mut animator = Animator::new(example_machine(), visual.clone(), instructions);
+
+        let labels = |animator: &Animator| {
+            animator
+                .state(1f32.into())
+                .atoms
+                .into_iter()
+                .map(|atom| atom.label)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&animator), ["atom0", "qubit_1"]);
+
+        visual.atom.legend.show = AtomLabel::Both;
+        animator.apply_visual(visual);
+        assert_eq!(labels(&animator), ["a0 (atom0)", "q1 (qubit_1)"]);
+    }
+
+    /// A weighted atom should be scaled, including during operations.
+    #[test]
+    fn atom_weight() {
+        let animator = animator(
+            "#weight atom1 2\n\
+             atom (0, 0) atom0\n\
+             atom (10, 10) atom1\n\
+             @1 [\n\
+                 ry 1 atom0\n\
+                 ry 1 atom1\n\
+             ]",
+        );
+        let radius = animator.visual.atom.radius.f32();
+        let duration = animator.machine.time.ry.f32();
+        let sizes = |time: f32| {
+            let state = animator.state(time.into());
+            (state.atoms[0].size, state.atoms[1].size)
+        };
+
+        let (unweighted, weighted) = sizes(0.);
+        assert_eq!(unweighted, radius);
+        assert_eq!(weighted, 2. * radius, "Base size is not scaled");
+
+        let (unweighted, weighted) = sizes(1. + duration / 2.);
+        assert!(
+            (weighted - 2. * unweighted).abs() < 1e-4,
+            "Operation size is not relative to the weighted base"
+        );
+    }
+
+    /// A `sine`-easing should flash along a (non-linear) half sine-wave
+    /// and return to the base size.
+    #[test]
+    fn sine_easing() {
+        let mut visual = example_visual();
+        visual.operation.config.ry.easing = Easing::Sine;
+        // The peak needs to differ from the base radius to measure the progress
+        visual.operation.config.ry.radius =
+            naviz_parser::config::visual::NumberOrPercentage::Number(visual.atom.radius * 2);
+        let animator = Animator::new(
+            example_machine(),
+            visual,
+            instructions("atom (0, 0) atom0\n@1 ry 1 atom0"),
+        );
+        let radius = animator.visual.atom.radius.f32();
+        let peak = animator
+            .visual
+            .operation
+            .config
+            .ry
+            .radius
+            .get(animator.visual.atom.radius)
+            .f32();
+        let duration = animator.machine.time.ry.f32();
+        // The progress of the flash (`0` at the base size, `1` at the peak)
+        let progress = |fraction: f32| {
+            let size = animator.state((1. + fraction * duration).into()).atoms[0].size;
+            (size - radius) / (peak - radius)
+        };
+
+        let approx = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(
+            approx(progress(0.25), (PI / 4.).sin()),
+            "Flash does not follow a sine-wave"
+        );
+        assert!(
+            !approx(progress(0.25), 0.5),
+            "Flash is linear (like the triangle-easing)"
+        );
+        assert!(
+            approx(progress(0.5), 1.),
+            "Flash does not peak in the middle"
+        );
+        assert!(
+            approx(progress(1.5), 0.),
+            "Flash does not return to the base size"
+        );
+    }
+
+    /// The example-configs should not produce any warnings.
+    #[test]
+    fn no_warnings() {
+        let input = instructions(include_str!("../../parser/rsc/test/example.naviz"));
+        assert!(
+            Animator::try_new(example_machine(), example_visual(), input).is_ok(),
+            "Example produced warnings"
+        );
+    }
+
+    /// A zone without a matching style should be reported,
+    /// while still creating a usable animator.
+    #[test]
+    fn zone_without_style_warns() {
+        let mut visual = example_visual();
+        visual.zone.config.truncate(1);
+        visual.zone.config[0].0 = Regex::new("^zone0$").unwrap();
+
+        let (animator, warnings) = Animator::new_with_warnings(
+            example_machine(),
+            visual.clone(),
+            instructions("atom (0, 0) atom0"),
+        );
+        assert_eq!(
+            warnings,
+            vec![AnimatorWarning::MissingZoneStyle {
+                zone: "zone1".to_string()
+            }]
+        );
+        assert_eq!(
+            animator.config().machine.zones.len(),
+            2,
+            "Zone without style was dropped"
+        );
+
+        let warnings =
+            Animator::try_new(example_machine(), visual, instructions("atom (0, 0) atom0"))
+                .err()
+                .expect("Missing zone style did not fail");
+        assert_eq!(warnings.len(), 1);
+    }
+
+    /// Used operations without a style should be reported.
+    #[test]
+    fn operation_without_style_warns() {
+        let (_, warnings) = Animator::new_with_warnings(
+            example_machine(),
+            example_visual(),
+            instructions(
+                "atom (0, 0) atom0\n\
+                 @0 reset atom0",
+            ),
+        );
+        assert_eq!(
+            warnings,
+            vec![AnimatorWarning::MissingOperationStyle { operation: "reset" }]
+        );
+    }
+
+    /// A `#window` should clip the timeline to the window
+    /// and only animate the instructions inside of it.
+    #[test]
+    fn window() {
+        let animator = animator(
+            "#window 10 20\n\
+             atom (0, 0) atom0\n\
+             atom (5, 5) atom1\n\
+             @0 move (1, 1) atom0\n\
+             @12 rz 1 atom0\n\
+             @25 rz 1 atom1",
+        );
+
+        assert_eq!(animator.duration(), Fraction::from(10));
+        assert_eq!(
+            animator.instruction_times(),
+            [(Fraction::from(2), "rz")],
+            "Instructions outside of the window were not dropped"
+        );
+
+        let state = |time: f32| animator.state(time.into());
+        assert_eq!(
+            state(0.).atoms[0].position,
+            (0., 0.),
+            "Instruction before the window was animated"
+        );
+        assert_ne!(
+            state(3.5).atoms[0].color,
+            state(0.).atoms[0].color,
+            "Instruction inside the window was not animated"
+        );
+        assert_eq!(
+            state(10.).atoms[1].color,
+            state(0.).atoms[1].color,
+            "Instruction after the window was animated"
+        );
+        assert_eq!(
+            state(15.).atoms[1].color,
+            state(10.).atoms[1].color,
+            "Time after the window was not clamped"
+        );
+        let unclipped = self::animator("atom (0, 0) atom0").state(5.0.into());
+        assert!(!unclipped.time.is_empty(), "Time is not displayed");
+        assert_eq!(
+            state(5.).time,
+            unclipped.time,
+            "Time is not relative to the window"
+        );
+    }
+
+    /// Different operations should select their configured patterns
+    /// for their duration only.
+    #[test]
+    fn operation_patterns() {
+        use naviz_parser::common::pattern::Pattern as ParserPattern;
+
+        let mut visual = example_visual();
+        visual.operation.config.rz.pattern = ParserPattern::Ring;
+        visual.operation.config.ry.pattern = ParserPattern::Hatch;
+        let animator = Animator::new(
+            example_machine(),
+            visual,
+            instructions(
+                "atom (0, 0) atom0\n\
+                 @0 rz 1 atom0\n\
+                 @10 ry 1 atom0\n\
+                 @20 cz zone0",
+            ),
+        );
+        let pattern = |time: f32| animator.state(time.into()).atoms[0].pattern;
+
+        assert_eq!(pattern(1.5), Pattern::Ring, "Wrong pattern during rz");
+        assert_eq!(pattern(10.05), Pattern::Hatch, "Wrong pattern during ry");
+        assert_eq!(pattern(20.5), Pattern::Solid, "Wrong pattern during cz");
+        assert_eq!(pattern(5.), Pattern::Solid, "Pattern held after operation");
+
+        let operations = &animator.config().legend.entries[1];
+        assert_eq!(
+            operations
+                .entries
+                .iter()
+                .map(|e| e.pattern)
+                .collect::<Vec<_>>()[..2],
+            [Pattern::Ring, Pattern::Hatch],
+            "Legend does not show the patterns"
+        );
+    }
+
+    #[test]
+    fn interaction_radius() {
+        let input = "atom (0, 0) atom0\n\
+                     atom (5, 0) atom1\n\
+                     atom (50, 50) atom2\n\
+                     @0 cz zone0";
+        let interacting = |show: bool, time: f32| {
+            let mut visual = example_visual();
+            visual.operation.config.cz.show_interaction_radius = show;
+            Animator::new(example_machine(), visual, instructions(input))
+                .state(time.into())
+                .atoms
+                .iter()
+                .map(|a| a.interaction)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            interacting(true, 0.5),
+            [true, true, false],
+            "Interaction not indicated around atoms in the targeted zone"
+        );
+        assert_eq!(
+            interacting(true, 5.),
+            [false, false, false],
+            "Interaction indicated after cz"
+        );
+        assert_eq!(
+            interacting(false, 0.5),
+            [false, false, false],
+            "Interaction indicated while disabled"
+        );
+    }
+
+    /// Applying a new visual config should keep the position timelines
+    /// and style the atoms like a newly created animator.
+    #[test]
+    fn apply_visual() {
+        let input = include_str!("../../parser/rsc/test/example.naviz");
+        let mut visual = example_visual();
+        visual.atom.radius *= Fraction::from(2);
+        visual.operation.config.rz.color.r = 255 - visual.operation.config.rz.color.r;
+        visual.operation.config.cz.show_interaction_radius =
+            !visual.operation.config.cz.show_interaction_radius;
+
+        let mut animator = example_animator();
+        let times: Vec<f32> = (0..=(animator.duration().f32() * 4.) as u32)
+            .map(|t| t as f32 / 4.)
+            .collect();
+        let positions = |animator: &Animator| {
+            times
+                .iter()
+                .flat_map(|&t| animator.state(t.into()).atoms)
+                .map(|a| a.position)
+                .collect::<Vec<_>>()
+        };
+        let before = positions(&animator);
+
+        animator.apply_visual(visual.clone());
+        assert_eq!(positions(&animator), before, "Positions changed");
+
+        let recreated = Animator::new(example_machine(), visual, instructions(input));
+        for &t in &times {
+            assert_eq!(
+                animator.state(t.into()).atoms,
+                recreated.state(t.into()).atoms,
+                "Atoms styled differently than a new animator at {t}"
+            );
+        }
+        assert_eq!(
+            animator.config().content_extent,
+            recreated.config().content_extent
+        );
+    }
+
+    /// Creates an [Animator] for the passed `input`
+    /// using the example-machine with the passed `capacity` for `zone0`
+    /// and returns the [AnimatorWarning]s
+    fn capacity_warnings(capacity: usize, input: &str) -> Vec<AnimatorWarning> {
+        let mut machine = example_machine();
+        machine
+            .zone
+            .get_mut("zone0")
+            .expect("Example machine has no zone0")
+            .capacity = Some(capacity);
+        Animator::new_with_warnings(machine, example_visual(), instructions(input)).1
+    }
+
+    /// A zone holding more atoms than its capacity should be reported.
+    #[test]
+    fn zone_over_capacity() {
+        let warnings = capacity_warnings(
+            1,
+            "atom (1, 1) atom0\n\
+             atom (20, 20) atom1\n\
+             @0 move (2, 2) atom1",
+        );
+        assert_eq!(warnings.len(), 1, "Unexpected warnings: {warnings:?}");
+        let AnimatorWarning::ZoneOverCapacity {
+            zone, atoms, time, ..
+        } = &warnings[0]
+        else {
+            panic!("Unexpected warning: {:?}", warnings[0]);
+        };
+        assert_eq!(zone, "zone0");
+        assert_eq!(*atoms, 2);
+        assert!(*time > Fraction::ZERO, "Zone over capacity before the move");
+    }
+
+    /// A zone within its capacity should not be reported.
+    #[test]
+    fn zone_within_capacity() {
+        let warnings = capacity_warnings(
+            2,
+            "atom (1, 1) atom0\n\
+             atom (20, 20) atom1\n\
+             @0 move (2, 2) atom1",
+        );
+        assert!(warnings.is_empty(), "Unexpected warnings: {warnings:?}");
+    }
+
+    /// A zone over its capacity should be drawn with the `over_capacity` color of its style
+    /// for as long as its capacity is exceeded.
+    #[test]
+    fn zone_over_capacity_tint() {
+        let mut machine = example_machine();
+        machine
+            .zone
+            .get_mut("zone0")
+            .expect("Example machine has no zone0")
+            .capacity = Some(1);
+        let mut visual = example_visual();
+        for (_, style) in &mut visual.zone.config {
+            style.over_capacity = Some(naviz_parser::common::color::Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            });
+        }
+        let animator = Animator::new(
+            machine,
+            visual,
+            instructions(
+                "atom (1, 1) atom0\n\
+                 atom (20, 20) atom1\n\
+                 @0 move (2, 2) atom1\n\
+                 @10 move (20, 20) atom1",
+            ),
+        );
+        let idx = animator
+            .machine
+            .zone
+            .keys()
+            .position(|id| id == "zone0")
+            .expect("Example machine has no zone0");
+        let color = |time: f32| animator.state(time.into()).zones[idx];
+        let normal = animator.config().machine.zones[idx].line.color;
+
+        assert_eq!(
+            color(0.),
+            normal,
+            "Zone tinted before exceeding its capacity"
+        );
+        assert_eq!(color(9.), [255, 0, 0, 255], "Zone not tinted over capacity");
+        assert_eq!(
+            color(animator.duration().f32()),
+            normal,
+            "Zone still tinted after leaving its capacity"
+        );
+    }
+
+    /// A highlighted zone should change its color at the time of the highlight.
+    #[test]
+    fn zone_highlight() {
+        let animator = animator(
+            "atom (0, 0) atom0\n\
+             @5 highlight zone0 #ffcc00",
+        );
+        let idx = animator
+            .machine
+            .zone
+            .keys()
+            .position(|id| id == "zone0")
+            .expect("Example machine has no zone0");
+        let color = |time: f32| animator.state(time.into()).zones[idx];
+
+        assert_ne!(color(4.), color(6.), "Highlight did not change the color");
+        assert_eq!(color(6.), [255, 204, 0, 255]);
+        assert_eq!(
+            color(4.),
+            animator.config().machine.zones[idx].line.color,
+            "Zone highlighted before the highlight"
+        );
+    }
+
+    /// Zone membership should follow atoms moving into a zone.
+    #[test]
+    fn zone_membership() {
+        let animator = animator(
+            "atom (1, 1) atom0\n\
+             atom (20, 20) atom1\n\
+             @0 move (2, 2) atom1",
+        );
+        let start = Time::from(0.);
+        let end = Time::from(animator.duration().f32());
+
+        assert_eq!(animator.zone_of("atom0", start), Some("zone0"));
+        assert_eq!(animator.zone_of("atom1", start), None);
+        assert_eq!(animator.atoms_in_zone("zone0", start), vec!["atom0"]);
+
+        assert_eq!(animator.zone_of("atom1", end), Some("zone0"));
+        assert_eq!(animator.atoms_in_zone("zone0", end), vec!["atom0", "atom1"]);
+
+        assert_eq!(animator.zone_of("unknown", end), None);
+        assert!(animator.atoms_in_zone("unknown", end).is_empty());
+    }
+
+    /// The content extent should include traps (with their radius) far from all atoms.
+    #[test]
+    fn content_extent_includes_traps() {
+        let mut machine = example_machine();
+        machine.trap.insert(
+            "far".to_string(),
+            naviz_parser::config::machine::TrapConfig {
+                position: (Fraction::from(100), -Fraction::from(50)),
+            },
+        );
+        let visual = example_visual();
+        let padding = (visual.machine.trap.radius + visual.coordinate.margin).f32();
+        let animator = Animator::new(machine, visual, instructions("atom (1, 2) atom0"));
+
+        let ((left, top), (right, bottom)) = animator.config().content_extent;
+        assert!(
+            right >= 100. + padding,
+            "Extent does not reach trap: {right}"
+        );
+        assert!(top <= -50. - padding, "Extent does not reach trap: {top}");
+        assert!(left <= 1. && bottom >= 2., "Extent does not contain atom");
+    }
+}
@@ -1,6 +1,11 @@
 //! [InterpolationFunction] trait and some interpolation functions.
 
-use std::ops::{Add, Mul};
+use std::{
+    f32::consts::PI,
+    ops::{Add, Mul},
+};
+
+use naviz_parser::common::easing::Easing;
 
 use crate::{
     position::Position,
@@ -107,6 +112,13 @@ impl<T: Mul<f32, Output = I>, I: Add<Output = T>> InterpolationFunction<(), T> f
     }
 }
 
+/// Duration of a linear interpolation with a constant [MaxVelocity].
+impl DurationCalculable<MaxVelocity, f32> for Linear {
+    fn duration(&self, max_velocity: MaxVelocity, from: f32, to: f32) -> f32 {
+        (to - from).abs() / max_velocity.0
+    }
+}
+
 /// Triangle interpolation
 ///
 /// Will interpolate linearly from `from` to `to` in the first half
@@ -128,6 +140,31 @@ impl<T: Mul<f32, Output = I>, I: Add<Output = T>> InterpolationFunction<(), T> f
     }
 }
 
+/// Flash interpolation
+///
+/// Will interpolate from `from` to `to` and back to `from`,
+/// using the [Easing] passed as the argument:
+/// - [Easing::Triangle]: Like [Triangle]
+/// - [Easing::Sine]: Along a half sine-wave
+/// - [Easing::Pulse]: Holds `to` for the whole duration
+///
+/// This will always cycle back to the initial value.
+#[derive(Default)]
+pub struct Flash();
+impl<T: Mul<f32, Output = I>, I: Add<Output = T>> InterpolationFunction<Easing, T> for Flash {
+    const ENDPOINT: Endpoint = Endpoint::FROM;
+
+    fn interpolate(&self, fraction: Time, argument: Easing, from: T, to: T) -> T {
+        let fraction = match argument {
+            Easing::Triangle => return Triangle().interpolate(fraction, (), from, to),
+            Easing::Sine => (fraction.0 * PI).sin(),
+            Easing::Pulse => 1.,
+        };
+
+        from * (1. - fraction) + to * fraction
+    }
+}
+
 /// A cubic interpolation
 ///
 /// Will interpolate from `from` to `to` using cubic functions.
@@ -387,6 +424,85 @@ impl ConstantJerkImpl<()> for ConstantJerkFixedAverageVelocity {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Acceleration(pub f32);
+
+/// An interpolation-function which applies trapezoidal movements to [f32]s:
+/// Constant [Acceleration] up to the [MaxVelocity],
+/// followed by constant velocity and constant deceleration.
+/// If the [MaxVelocity] cannot be reached, the velocity-profile is a triangle.
+///
+/// When interpolating over a longer duration than [DurationCalculable::duration],
+/// the profile is stretched in time.
+pub struct Trapezoidal();
+
+impl Trapezoidal {
+    pub fn new_fixed(
+        max_velocity: MaxVelocity,
+        acceleration: Acceleration,
+    ) -> FixedArgument<(MaxVelocity, Acceleration), Self> {
+        FixedArgument {
+            argument: (max_velocity, acceleration),
+            interpolator: Self(),
+        }
+    }
+
+    /// The time spent accelerating (and also decelerating)
+    /// and the total time for a move of the passed `distance`.
+    fn times(
+        (max_velocity, acceleration): (MaxVelocity, Acceleration),
+        distance: f32,
+    ) -> (f32, f32) {
+        if distance >= max_velocity.0.powi(2) / acceleration.0 {
+            // Reaches max velocity
+            let t_acceleration = max_velocity.0 / acceleration.0;
+            (t_acceleration, distance / max_velocity.0 + t_acceleration)
+        } else {
+            // Triangle-profile
+            let t_acceleration = (distance / acceleration.0).sqrt();
+            (t_acceleration, 2. * t_acceleration)
+        }
+    }
+}
+
+impl DurationCalculable<(MaxVelocity, Acceleration), f32> for Trapezoidal {
+    fn duration(&self, argument: (MaxVelocity, Acceleration), from: f32, to: f32) -> f32 {
+        Self::times(argument, (to - from).abs()).1
+    }
+}
+
+impl InterpolationFunction<(MaxVelocity, Acceleration), f32> for Trapezoidal {
+    fn interpolate(
+        &self,
+        fraction: Time,
+        argument: (MaxVelocity, Acceleration),
+        from: f32,
+        to: f32,
+    ) -> f32 {
+        if from == to {
+            // from = to => no need to interpolate
+            // would otherwise lead to division by 0
+            return from;
+        }
+
+        let (t_acceleration, t_total) = Self::times(argument, (to - from).abs());
+        // Normalized acceleration-time and peak-velocity
+        let r = t_acceleration / t_total;
+        let v = 1. / (1. - r);
+
+        let f = fraction.0;
+        let s = if f < r {
+            v * f.powi(2) / (2. * r)
+        } else if f <= 1. - r {
+            v * (f - r / 2.)
+        } else {
+            1. - v * (1. - f).powi(2) / (2. * r)
+        };
+
+        Linear().interpolate(s.into(), (), from, to)
+    }
+}
+
 /// Diagonal interpolator for a [Position].
 /// Interpolates the direct (diagonal) connection between `from` and `to`.
 ///
@@ -440,6 +556,53 @@ impl<A, I: InterpolationFunction<A, f32>> InterpolationFunction<A, Position> for
     }
 }
 
+/// Axis-aligned interpolator for a [Position].
+/// Interpolates along an L-shaped path from `from` to `to`:
+/// first in `x`-direction, then in `y`-direction.
+/// The passed interpolator is applied to the whole path,
+/// so the movement does not stop at the corner.
+///
+/// Note that while it is possible to wrap any types here,
+/// only wrapped [InterpolationFunction]s will make this type be an [InterpolationFunction].
+pub struct AxisAligned<I>(pub I);
+
+impl<A, I: DurationCalculable<A, f32>> DurationCalculable<A, Position> for AxisAligned<I> {
+    fn duration(&self, argument: A, from: Position, to: Position) -> f32 {
+        self.0
+            .duration(argument, 0., (to.x - from.x).abs() + (to.y - from.y).abs())
+    }
+}
+
+impl<A, I: InterpolationFunction<A, f32>> InterpolationFunction<A, Position> for AxisAligned<I> {
+    fn interpolate(&self, fraction: Time, argument: A, from: Position, to: Position) -> Position {
+        // Similar to [Diagonal], we interpolate in a 1D-coordinate-system,
+        // which is the distance traveled along the path
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let length = dx.abs() + dy.abs();
+        if length <= 0. {
+            // from = to
+            return from;
+        }
+
+        let s = self.0.interpolate(fraction, argument, 0., length);
+
+        if s <= dx.abs() {
+            // First leg: in x-direction
+            Position {
+                x: from.x + dx.signum() * s,
+                y: from.y,
+            }
+        } else {
+            // Second leg: in y-direction
+            Position {
+                x: to.x,
+                y: from.y + dy.signum() * (s - dx.abs()),
+            }
+        }
+    }
+}
+
 /// Component-Wise interpolator for a [Position].
 /// Interpolates `x`- and `y`-coordinates separately using the same passed interpolator.
 /// The duration must be calculable so that each component may take its specified time.
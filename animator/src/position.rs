@@ -1,4 +1,4 @@
-use std::ops::{Add, Mul};
+use std::ops::{Add, Mul, Sub};
 
 use crate::to_float::ToFloat;
 
@@ -42,3 +42,13 @@ impl Add<Position> for Position {
         }
     }
 }
+
+impl Sub<Position> for Position {
+    type Output = Self;
+    fn sub(self, rhs: Position) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
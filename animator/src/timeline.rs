@@ -7,6 +7,10 @@
 //! the new [Keyframe] will take precedence
 //! (which leads to a jump to the previous [Keyframe]'s [value][Keyframe::value]
 //! at the start of the new [Keyframe]).
+//! This can be changed using a [KeyframeConflict]-policy
+//! (see [Timeline::with_conflict]).
+
+use std::ops::{Mul, Sub};
 
 use ordered_float::OrderedFloat;
 
@@ -18,18 +22,28 @@ pub type Time = OrderedFloat<f32>;
 pub trait Duration {
     /// Convert this duration to [f32]
     fn as_f32(&self) -> f32;
+
+    /// Set this duration from an [f32].
+    /// Durations which cannot represent the passed value stay unchanged.
+    fn set_f32(&mut self, duration: f32);
 }
 
 impl Duration for f32 {
     fn as_f32(&self) -> f32 {
         *self
     }
+
+    fn set_f32(&mut self, duration: f32) {
+        *self = duration;
+    }
 }
 
 impl Duration for () {
     fn as_f32(&self) -> f32 {
         0.
     }
+
+    fn set_f32(&mut self, _duration: f32) {}
 }
 
 /// A single keyframe.
@@ -139,6 +153,18 @@ impl<A, T, Dur: Duration> From<(f32, Dur, A, T)> for Keyframe<A, T, Dur> {
     }
 }
 
+/// How a [Timeline] handles a [Keyframe] which starts
+/// while the previous [Keyframe] is still interpolating
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyframeConflict {
+    /// The new [Keyframe] starts at the previous [Keyframe]'s [value][Keyframe::value],
+    /// which leads to a jump
+    #[default]
+    Override,
+    /// The new [Keyframe] continues from the current interpolated value
+    Blend,
+}
+
 /// A timeline which holds many keyframes and specifies the used interpolation function.
 pub struct Timeline<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A, T>> {
     /// The keyframes of this functions.
@@ -148,6 +174,8 @@ pub struct Timeline<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A,
     default: T,
     /// The used interpolation function
     interpolation_function: I,
+    /// How overlapping keyframes are handled
+    conflict: KeyframeConflict,
 }
 
 impl<A: Copy, T: Copy + Default, Dur: Duration, I: InterpolationFunction<A, T> + Default> Default
@@ -158,6 +186,7 @@ impl<A: Copy, T: Copy + Default, Dur: Duration, I: InterpolationFunction<A, T> +
             keyframes: Vec::new(),
             default: T::default(),
             interpolation_function: Default::default(),
+            conflict: Default::default(),
         }
     }
 }
@@ -172,6 +201,7 @@ impl<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A, T> + Default>
             keyframes: Vec::new(),
             default,
             interpolation_function: Default::default(),
+            conflict: Default::default(),
         }
     }
 }
@@ -184,9 +214,16 @@ impl<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A, T>> Timeline<A
             keyframes: Vec::new(),
             default,
             interpolation_function,
+            conflict: Default::default(),
         }
     }
 
+    /// Sets the [KeyframeConflict]-policy of this [Timeline]
+    pub fn with_conflict(mut self, conflict: KeyframeConflict) -> Self {
+        self.conflict = conflict;
+        self
+    }
+
     /// Searches the keyframes for the index of the passed time.
     /// See [slice::binary_search] for more information on the return type.
     fn search_time(&self, time: Time) -> Result<usize, usize> {
@@ -221,36 +258,111 @@ impl<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A, T>> Timeline<A
     /// Gets the value at the passed time.
     /// Will interpolate the keyframe.
     pub fn get(&self, time: Time) -> T {
-        let idx = self.find_idx(time);
-        if let Some(idx) = idx {
-            let keyframe = &self.keyframes[idx];
-            let to = keyframe.value;
-            let from = idx
-                .checked_sub(1)
+        if let Some(idx) = self.find_idx(time) {
+            self.interpolate(idx, self.start_value(idx), time)
+        } else {
+            self.default
+        }
+    }
+
+    /// Gets the value the keyframe at `idx` starts interpolating from,
+    /// based on the [KeyframeConflict]-policy
+    fn start_value(&self, idx: usize) -> T {
+        let settled = |idx: usize| {
+            idx.checked_sub(1)
                 .map(|i| &self.keyframes[i])
                 .map(|k| I::ENDPOINT.get(self.default, k.value))
-                .unwrap_or(self.default);
-            let duration = keyframe.duration.as_f32().into();
-            let keyframe_relative_time = time - keyframe.time; // time inside keyframe
-            if keyframe_relative_time >= duration {
-                // outside of keyframe: return endpoint
-                return I::ENDPOINT.get(from, to);
+                .unwrap_or(self.default)
+        };
+        match self.conflict {
+            KeyframeConflict::Override => settled(idx),
+            KeyframeConflict::Blend => {
+                // Find the first keyframe of the chain of overlapping keyframes
+                let mut start = idx;
+                while start > 0 {
+                    let previous = &self.keyframes[start - 1];
+                    if previous.time + previous.duration() <= self.keyframes[start].time {
+                        break;
+                    }
+                    start -= 1;
+                }
+                // Continue each keyframe of the chain from the value of the previous one
+                (start..idx).fold(settled(start), |from, i| {
+                    self.interpolate(i, from, self.keyframes[i + 1].time)
+                })
             }
-            let fraction = keyframe_relative_time / duration;
-            self.interpolation_function
-                .interpolate(fraction, keyframe.argument, from, to)
-        } else {
-            self.default
         }
     }
 
-    /// Adds a keyframe into this [Timeline]
+    /// Interpolates the keyframe at `idx` starting from `from` at the passed `time`
+    fn interpolate(&self, idx: usize, from: T, time: Time) -> T {
+        let keyframe = &self.keyframes[idx];
+        let to = keyframe.value;
+        let duration = keyframe.duration.as_f32().into();
+        let keyframe_relative_time = time - keyframe.time; // time inside keyframe
+        if keyframe_relative_time >= duration {
+            // outside of keyframe: return endpoint
+            return I::ENDPOINT.get(from, to);
+        }
+        let fraction = keyframe_relative_time / duration;
+        self.interpolation_function
+            .interpolate(fraction, keyframe.argument, from, to)
+    }
+
+    /// Gets the first keyframe which starts strictly after the passed `time`,
+    /// or [None] if no keyframe starts after `time`.
+    pub fn next_keyframe_after(&self, time: Time) -> Option<&Keyframe<A, T, Dur>> {
+        let idx = self.keyframes.partition_point(|k| k.time <= time);
+        self.keyframes.get(idx)
+    }
+
+    /// Gets the last keyframe which starts strictly before the passed `time`,
+    /// or [None] if no keyframe starts before `time`.
+    pub fn prev_keyframe_before(&self, time: Time) -> Option<&Keyframe<A, T, Dur>> {
+        let idx = self.keyframes.partition_point(|k| k.time < time);
+        idx.checked_sub(1).map(|idx| &self.keyframes[idx])
+    }
+
+    /// The value of the last keyframe of this [Timeline]
+    /// (i.e., the value after all keyframes finished),
+    /// or the default value if this [Timeline] has no keyframes.
+    pub fn last_value(&self) -> T {
+        self.keyframes
+            .last()
+            .map(|keyframe| keyframe.value)
+            .unwrap_or(self.default)
+    }
+
+    /// Adds a keyframe into this [Timeline].
+    /// If it starts while another keyframe is interpolating,
+    /// the [KeyframeConflict]-policy decides where it starts interpolating from.
     pub fn add(&mut self, keyframe: impl Into<Keyframe<A, T, Dur>>) -> &mut Self {
         let keyframe = keyframe.into();
         self.keyframes.insert(self.get_idx(keyframe.time), keyframe);
         self
     }
 
+    /// Maps the start- and end-times of all keyframes of this [Timeline] using the passed function `f`.
+    /// The durations are adjusted to end at the mapped end-times (but never become negative).
+    /// Keyframes which are mapped onto the same time keep their previous order.
+    pub fn map_times(&mut self, mut f: impl FnMut(Time) -> Time) -> &mut Self {
+        for keyframe in &mut self.keyframes {
+            let end = f(keyframe.time + keyframe.duration.as_f32());
+            keyframe.time = f(keyframe.time);
+            keyframe
+                .duration
+                .set_f32((end - keyframe.time).into_inner().max(0.));
+        }
+        // stable sort: keeps the order of equal times
+        self.keyframes.sort();
+        self
+    }
+
+    /// The keyframes of this [Timeline], ordered by time
+    pub fn keyframes(&self) -> &[Keyframe<A, T, Dur>] {
+        &self.keyframes
+    }
+
     /// Adds multiple keyframes into this [Timeline]
     pub fn add_all(
         &mut self,
@@ -261,3 +373,116 @@ impl<A: Copy, T: Copy, Dur: Duration, I: InterpolationFunction<A, T>> Timeline<A
         self
     }
 }
+
+impl<A: Copy, T, Dur: Duration, I: InterpolationFunction<A, T>> Timeline<A, T, Dur, I>
+where
+    T: Copy + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    /// Time-step used to approximate the velocity in [Timeline::get_with_velocity]
+    const VELOCITY_STEP: f32 = 1e-3;
+
+    /// Gets the value at the passed time together with its instantaneous velocity
+    /// (change of the value per unit of time).
+    /// The velocity is approximated using a central finite difference.
+    pub fn get_with_velocity(&self, time: Time) -> (T, T) {
+        let step = Self::VELOCITY_STEP;
+        let before = self.get(time - OrderedFloat(step));
+        let after = self.get(time + OrderedFloat(step));
+        (self.get(time), (after - before) * (0.5 / step))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{interpolator::Linear, position::Position};
+
+    /// A [Timeline] of [Position]s that moves from `(0, 0)` to `(4, 2)`
+    /// in the time from `1` to `3`.
+    fn moving() -> Timeline<(), Position, f32, Linear> {
+        let mut timeline = Timeline::new(Position::default());
+        timeline
+            .add((0., 0., Position::default()))
+            .add((1., 2., Position { x: 4., y: 2. }));
+        timeline
+    }
+
+    #[test]
+    fn stationary_velocity() {
+        let timeline = moving();
+        for time in [0.5f32, 4.] {
+            let (_, velocity) = timeline.get_with_velocity(time.into());
+            assert!(velocity.x.abs() < 1e-3 && velocity.y.abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn moving_velocity() {
+        let (position, velocity) = moving().get_with_velocity(2f32.into());
+        assert!((position.x - 2.).abs() < 1e-3 && (position.y - 1.).abs() < 1e-3);
+        assert!((velocity.x - 2.).abs() < 1e-2 && (velocity.y - 1.).abs() < 1e-2);
+    }
+
+    /// A [Timeline] which interpolates from `0` to `10` in the time from `0` to `2`,
+    /// interrupted by a keyframe interpolating to `20` in the time from `1` to `3`
+    fn interrupted(conflict: KeyframeConflict) -> Timeline<(), f32, f32, Linear> {
+        let mut timeline = Timeline::new(0.).with_conflict(conflict);
+        timeline.add((0., 2., 10.)).add((1., 2., 20.));
+        timeline
+    }
+
+    #[test]
+    fn conflict_override() {
+        let timeline = interrupted(KeyframeConflict::Override);
+        assert_eq!(timeline.get(0.5f32.into()), 2.5);
+        assert_eq!(
+            timeline.get(1f32.into()),
+            10.,
+            "Did not jump to previous value"
+        );
+        assert_eq!(timeline.get(2f32.into()), 15.);
+        assert_eq!(timeline.get(3f32.into()), 20.);
+    }
+
+    #[test]
+    fn conflict_blend() {
+        let timeline = interrupted(KeyframeConflict::Blend);
+        assert_eq!(timeline.get(0.5f32.into()), 2.5);
+        assert_eq!(
+            timeline.get(1f32.into()),
+            5.,
+            "Did not continue from current value"
+        );
+        assert_eq!(timeline.get(2f32.into()), 12.5);
+        assert_eq!(timeline.get(3f32.into()), 20.);
+    }
+
+    #[test]
+    fn neighboring_keyframes() {
+        let mut timeline: Timeline<(), f32, (), Linear> = Timeline::new(0.);
+        timeline.add_all([(1., 10.), (3., 30.), (5., 50.)]);
+
+        let next = |time: f32| {
+            timeline
+                .next_keyframe_after(time.into())
+                .map(|k| *k.value())
+        };
+        let prev = |time: f32| {
+            timeline
+                .prev_keyframe_before(time.into())
+                .map(|k| *k.value())
+        };
+
+        assert_eq!(next(0.), Some(10.));
+        assert_eq!(next(1.), Some(30.), "Keyframe at exact time is not after");
+        assert_eq!(next(2.), Some(30.));
+        assert_eq!(next(5.), None);
+        assert_eq!(next(6.), None);
+
+        assert_eq!(prev(0.), None);
+        assert_eq!(prev(1.), None, "Keyframe at exact time is not before");
+        assert_eq!(prev(3.), Some(10.));
+        assert_eq!(prev(4.), Some(30.));
+        assert_eq!(prev(6.), Some(50.));
+    }
+}
@@ -83,8 +83,10 @@ fn export_video(
             .import(input.as_bytes())
             .map_err(|e| ParseError::new_err(format!("Failed to import input: {e:?}")))?
     } else {
-        // Load the input
-        let input = naviz_parser::input::lexer::lex(input)
+        // Load the input (includes are resolved relative to the working directory)
+        let input = naviz_parser::input::preprocess(input, |path| std::fs::read_to_string(path))
+            .map_err(|e| ParseError::new_err(format!("Failed to include file: {e}")))?;
+        let input = naviz_parser::input::lexer::lex(input.as_str())
             .map_err(|_| ParseError::new_err("Failed to lex input"))?;
         let input = naviz_parser::input::parser::parse(&input)
             .map_err(|_| ParseError::new_err("Failed to parse input"))?;
@@ -112,11 +114,21 @@ fn export_video(
         .try_into()
         .map_err(|_| ParseError::new_err("Failed to convert machine to config"))?;
 
+    // Background image (relative paths are resolved relative to the working directory)
+    let background_image = style
+        .viewport
+        .background_image
+        .as_ref()
+        .map(|image| naviz_video::load_background_image(Path::new(&image.path)))
+        .transpose()
+        .map_err(VideoExportError::new_err)?;
+
     // Create animator
     let animator = Animator::new(machine, style, input);
 
     // Setup video export and start exporting
     let mut video = futures::executor::block_on(VideoExport::new(animator, resolution, fps));
+    video.set_background_image(background_image.as_ref());
     let (tx, rx) = std::sync::mpsc::channel();
     video.export_video(Path::new(output), tx);
 
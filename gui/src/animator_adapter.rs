@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+};
 
 use egui::Ui;
 use naviz_animator::animator::Animator;
@@ -6,11 +9,20 @@ use naviz_parser::{
     config::{machine::MachineConfig, visual::VisualConfig},
     input::concrete::Instructions,
 };
-use naviz_renderer::{buffer_updater::BufferUpdater, renderer::Renderer};
-use naviz_state::{config::Config, state::State};
+use naviz_renderer::{
+    buffer_updater::BufferUpdater,
+    component::background::BackgroundImage,
+    renderer::{content_projection, Renderer},
+    viewport::{Camera, ViewportProjection},
+};
+use naviz_state::{
+    config::Config,
+    state::{AtomState, State},
+    Position,
+};
 use wgpu::{Device, Queue};
 
-use crate::progress_bar::ProgressBar;
+use crate::{progress_bar::ProgressBar, shortcuts::Action, zoom::ZoomState};
 
 #[derive(Default)]
 pub struct AnimatorAdapter {
@@ -25,6 +37,21 @@ pub struct AnimatorAdapter {
     /// Force Zen-mode.
     /// See [Renderer::force_zen].
     force_zen: bool,
+    /// The zoom- and pan-state of the content
+    zoom: ZoomState,
+    /// The [Camera] the last [AnimatorState] was created with.
+    /// See [AnimatorAdapter::get].
+    camera: Option<Camera>,
+
+    /// The current state.
+    /// Reused by [AnimatorAdapter::get] to avoid reallocations on every frame.
+    state: Arc<State>,
+
+    /// The background image loaded from the visual config, if any
+    background_image: Option<Arc<BackgroundImage>>,
+    /// Receives the background image while it is being loaded in the background.
+    /// See [AnimatorAdapter::is_loading_background_image].
+    background_image_loader: Option<mpsc::Receiver<Option<BackgroundImage>>>,
 }
 
 /// The animator state at a current time (as set by [AnimatorAdapter::set_time]),
@@ -35,7 +62,7 @@ pub struct AnimatorState {
     /// (see [Updatable::update_full][naviz_renderer::component::updatable::Updatable::update_full])
     update_full: bool,
     /// The current state
-    state: State,
+    state: Arc<State>,
     /// The current config
     config: Arc<Config>,
     /// The background color
@@ -44,13 +71,28 @@ pub struct AnimatorState {
     /// See [Animator::force_zen].
     /// Will only be updated on a [full update][AnimatorState::update_full].
     force_zen: bool,
+    /// The [Camera] looking at the content.
+    /// See [Renderer::set_camera].
+    /// Will only be updated on a [full update][AnimatorState::update_full].
+    camera: Option<Camera>,
+    /// The background image.
+    /// Will only be updated on a [full update][AnimatorState::update_full].
+    background_image: Option<Arc<BackgroundImage>>,
 }
 
+/// The [State] a [Renderer] was last updated with (if known).
+/// Used by [AnimatorState::update] to only update the atoms which changed since.
+#[derive(Default)]
+pub struct RenderedState(Option<State>);
+
 impl AnimatorState {
-    /// Updates the passed [Renderer] to represent the current animator-state
+    /// Updates the passed [Renderer] to represent the current animator-state.
+    /// `rendered` is the [RenderedState] of the passed [Renderer]
+    /// and will be updated to the current state.
     pub fn update(
         &self,
         renderer: &mut Renderer,
+        rendered: &mut RenderedState,
         updater: &mut impl BufferUpdater,
         device: &Device,
         queue: &Queue,
@@ -59,16 +101,46 @@ impl AnimatorState {
         let state = &self.state;
         if self.update_full {
             renderer.set_force_zen(self.force_zen);
+            renderer.set_camera(self.camera);
+            renderer.set_background_image(updater, device, queue, self.background_image.as_deref());
             renderer.update_full(updater, device, queue, config, state);
+        } else if let Some(previous) = &rendered.0 {
+            let diff = state.diff_atoms(previous);
+            renderer.update_changed(updater, device, queue, config, state, &diff);
         } else {
             renderer.update(updater, device, queue, config, state);
         }
+        match &mut rendered.0 {
+            // Reuse allocations of the previous state
+            Some(previous) => previous.clone_from(state),
+            None => rendered.0 = Some(State::clone(state)),
+        }
     }
 
     /// Gets the background-color of this [AnimatorState]
     pub fn background(&self) -> [u8; 4] {
         self.background
     }
+
+    /// Gets the atom at the passed `position` (in content-coordinates) and its index.
+    /// See [State::atom_at].
+    pub fn atom_at(&self, position: Position) -> Option<(usize, &AtomState)> {
+        self.state.atom_at(position)
+    }
+
+    /// Gets the [ViewportProjection] of the content
+    /// when drawn on a canvas with the passed `screen_resolution`.
+    /// See [content_projection].
+    pub fn content_projection(&self, screen_resolution: (u32, u32)) -> ViewportProjection {
+        content_projection(&self.config, screen_resolution, self.force_zen, self.camera)
+    }
+
+    /// Gets the [ViewportProjection] of the whole content (i.e., without the [Camera])
+    /// when drawn on a canvas with the passed `screen_resolution`.
+    /// See [content_projection].
+    pub fn fitted_projection(&self, screen_resolution: (u32, u32)) -> ViewportProjection {
+        content_projection(&self.config, screen_resolution, self.force_zen, None)
+    }
 }
 
 impl AnimatorAdapter {
@@ -78,10 +150,29 @@ impl AnimatorAdapter {
         self.recreate_animator(false);
     }
 
-    /// Sets the visual config
-    pub fn set_visual_config(&mut self, config: VisualConfig) {
-        self.visual = Some(config);
-        self.recreate_animator(false);
+    /// Sets the visual config.
+    /// A relative background image path is resolved relative to `dir`
+    /// (the directory of the config, if known).
+    /// The background image is loaded in the background
+    /// and will be shown once loaded.
+    pub fn set_visual_config(&mut self, config: VisualConfig, dir: Option<&Path>) {
+        self.background_image = None;
+        self.background_image_loader = config
+            .viewport
+            .background_image
+            .as_ref()
+            .map(|image| spawn_background_image_loader(resolve_path(&image.path, dir)));
+        if let Some(animator) = &mut self.animator {
+            // Only the style changed => keep the timelines
+            for warning in animator.apply_visual_with_warnings(config.clone()) {
+                log::warn!("{warning}");
+            }
+            self.update_full = true;
+            self.visual = Some(config);
+        } else {
+            self.visual = Some(config);
+            self.recreate_animator(false);
+        }
     }
 
     /// Sets the instructions
@@ -113,6 +204,17 @@ impl AnimatorAdapter {
         self.force_zen
     }
 
+    /// Gets the [ZoomState] of the content
+    pub fn zoom(&self) -> &ZoomState {
+        &self.zoom
+    }
+
+    /// Gets the [ZoomState] of the content mutably.
+    /// Changes are applied to the next [AnimatorState] (see [AnimatorAdapter::get]).
+    pub fn zoom_mut(&mut self) -> &mut ZoomState {
+        &mut self.zoom
+    }
+
     /// Recreates the animator.
     /// Call this when new machine, visual, instructions are set.
     ///
@@ -123,15 +225,31 @@ impl AnimatorAdapter {
         if let (Some(machine), Some(visual), Some(instructions)) =
             (&self.machine, &self.visual, &self.instructions)
         {
-            let animator = Animator::new(machine.clone(), visual.clone(), instructions.clone());
+            // Semantic problems do not prevent animating
+            for warning in instructions.validate_against(machine) {
+                log::warn!("{warning}");
+            }
+            let (animator, warnings) =
+                Animator::new_with_warnings(machine.clone(), visual.clone(), instructions.clone());
+            for warning in warnings {
+                log::warn!("{warning}");
+            }
             self.update_full = true;
             if reset_time || self.animator.is_none() {
-                // Recreate progress bar while keeping the old speed
+                // Recreate progress bar while keeping the old speed and mode
+                let mode = self.progress_bar.get_mode();
                 self.progress_bar = ProgressBar::new_with_speed(
                     animator.duration().try_into().unwrap(),
                     self.progress_bar.get_speed(),
                 );
+                self.progress_bar.set_mode(mode);
             }
+            self.progress_bar.set_steps(
+                animator
+                    .instruction_times()
+                    .into_iter()
+                    .map(|(time, _)| time.try_into().unwrap()),
+            );
             self.animator = Some(animator);
         }
     }
@@ -149,30 +267,63 @@ impl AnimatorAdapter {
     /// for each instance),
     /// as otherwise not all [Animator]s may get updated fully.
     ///
-    /// If the [AnimatorState] should be gotten without changing any internal state,
-    /// use [AnimatorAdapter::peek] instead.
+    /// The [State] is reused between calls
+    /// if the previously returned [AnimatorState] was dropped.
     #[must_use]
     pub fn get(&mut self) -> Option<AnimatorState> {
-        let state = self.peek();
-        self.update_full = false;
-        state
-    }
-
-    /// Gets an [AnimatorState] from this [AnimatorAdapter]
-    /// or [None] if not enough inputs were set.
-    ///
-    /// Will not do any internal state updates.
-    /// If piping this output directly into an [Animator],
-    /// [AnimatorAdapter::get] should probably used instead
-    /// as this updates internal state to prevent unnecessary updates.
-    pub fn peek(&self) -> Option<AnimatorState> {
-        self.animator.as_ref().map(|animator| AnimatorState {
+        self.poll_background_image();
+        let animator = self.animator.as_ref()?;
+        // Clones the state only if the previous `AnimatorState` is still alive
+        animator.state_into(
+            (self.progress_bar.animation_time() as f32).into(),
+            Arc::make_mut(&mut self.state),
+        );
+        // A moved camera requires re-layout => requires full update
+        let camera = self.zoom.camera();
+        if camera != self.camera {
+            self.camera = camera;
+            self.update_full = true;
+        }
+        let state = AnimatorState {
             update_full: self.update_full,
             config: animator.config(),
-            state: animator.state((self.progress_bar.animation_time() as f32).into()),
+            state: self.state.clone(),
             background: animator.background(),
             force_zen: self.force_zen,
-        })
+            camera: self.camera,
+            background_image: self.background_image.clone(),
+        };
+        self.update_full = false;
+        Some(state)
+    }
+
+    /// Takes the background image if it finished loading.
+    /// Requires a full update to apply it.
+    fn poll_background_image(&mut self) {
+        if let Some(loader) = &self.background_image_loader {
+            match loader.try_recv() {
+                Ok(image) => {
+                    self.background_image = image.map(Arc::new);
+                    self.background_image_loader = None;
+                    self.update_full = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.background_image_loader = None,
+            }
+        }
+    }
+
+    /// Gets the loaded background image, if any.
+    /// Returns [None] while the image is still [loading][AnimatorAdapter::is_loading_background_image].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn background_image(&self) -> Option<Arc<BackgroundImage>> {
+        self.background_image.clone()
+    }
+
+    /// Whether a background image is currently being loaded.
+    /// The UI should be repainted until it is done.
+    pub fn is_loading_background_image(&self) -> bool {
+        self.background_image_loader.is_some()
     }
 
     /// Creates an [Animator] from this [AnimatorAdapter],
@@ -192,6 +343,23 @@ impl AnimatorAdapter {
         }
     }
 
+    /// Gets the id of the atom at the passed `index` in the atoms of the current [State]
+    /// (e.g., as returned by [AnimatorState::atom_at]).
+    /// Returns [None] if no animation is loaded or there is no such atom.
+    pub fn atom_id(&self, index: usize) -> Option<&str> {
+        self.animator.as_ref()?.atom_id(index)
+    }
+
+    /// Gets the current position of the atom with the passed `atom_id`.
+    /// Returns [None] if no animation is loaded or there is no such (visible) atom.
+    /// See [Animator::atom_position].
+    pub fn atom_position(&self, atom_id: &str) -> Option<Position> {
+        self.animator
+            .as_ref()?
+            .atom_position(atom_id, (self.progress_bar.animation_time() as f32).into())
+            .map(Into::into)
+    }
+
     /// Checks if all three inputs
     /// ([machine][AnimatorAdapter::set_machine_config],
     /// [visual][AnimatorAdapter::set_visual_config],
@@ -201,9 +369,76 @@ impl AnimatorAdapter {
         self.machine.is_some() && self.visual.is_some() && self.instructions.is_some()
     }
 
+    /// Applies a playback-[Action] to the progress-bar.
+    /// Does nothing if no animation is loaded or the [Action] does not concern playback.
+    pub fn apply_action(&mut self, action: Action) {
+        if self.animator.is_none() {
+            return;
+        }
+
+        match action {
+            Action::TogglePause => self.progress_bar.toggle_pause(),
+            Action::StepBackward => self.progress_bar.seek_backward(),
+            Action::StepForward => self.progress_bar.seek_forward(),
+            Action::PreviousInstruction => self.progress_bar.step_previous(),
+            Action::NextInstruction => self.progress_bar.step_next(),
+            Action::JumpToStart => self.progress_bar.jump_to_start(),
+            Action::JumpToEnd => self.progress_bar.jump_to_end(),
+            Action::ZoomIn | Action::ZoomOut => {}
+        }
+    }
+
+    /// Gets the current animation-time.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time(&self) -> f32 {
+        self.progress_bar.animation_time() as f32
+    }
+
     /// Draws the progress-bar of this [AnimatorAdapter] using [ProgressBar::draw].
     /// Will also update the animation-time.
     pub fn draw_progress_bar(&mut self, ui: &mut Ui) {
         self.progress_bar.draw(ui);
     }
 }
+
+/// Resolves `path` relative to `dir` (if it is relative and `dir` is known).
+fn resolve_path(path: &str, dir: Option<&Path>) -> PathBuf {
+    match dir {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Loads the background image from the passed `path` on a separate thread.
+/// The image will be sent over the returned channel.
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_background_image_loader(path: PathBuf) -> mpsc::Receiver<Option<BackgroundImage>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        // Receiver may be gone when another config was set in the meantime
+        let _ = tx.send(load_background_image(&path));
+    });
+    rx
+}
+
+/// Background images cannot be loaded from the file system on the web.
+/// Logs a warning and sends [None] over the returned channel.
+#[cfg(target_arch = "wasm32")]
+fn spawn_background_image_loader(path: PathBuf) -> mpsc::Receiver<Option<BackgroundImage>> {
+    log::warn!(
+        "Background images are not supported on the web: {}",
+        path.display()
+    );
+    let (tx, rx) = mpsc::channel();
+    let _ = tx.send(None);
+    rx
+}
+
+/// Loads the background image from the passed `path`.
+/// Logs a warning and returns [None] if the image could not be loaded.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_background_image(path: &Path) -> Option<BackgroundImage> {
+    naviz_video::load_background_image(path)
+        .inspect_err(|e| log::warn!("Failed to load background image: {e}"))
+        .ok()
+}
@@ -1,11 +1,10 @@
+use std::cmp::Ordering;
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
 use std::str;
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
-use std::{
-    path::{Path, PathBuf},
-    sync::mpsc::Sender,
-    thread,
-};
+use std::{path::PathBuf, sync::mpsc::Sender, thread};
 
 use eframe::egui_wgpu::CallbackTrait;
 use log::error;
@@ -17,20 +16,24 @@ use naviz_state::{config::Config, state::State};
 #[cfg(not(target_arch = "wasm32"))]
 use naviz_video::{VideoExport, VideoProgress};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export_dialog::{ExportTarget, FrameOptions};
+
 use crate::{
-    animator_adapter::{AnimatorAdapter, AnimatorState},
+    animator_adapter::{AnimatorAdapter, AnimatorState, RenderedState},
     aspect_panel::AspectPanel,
     canvas::{CanvasContent, EmptyCanvas, WgpuCanvas},
-    current_machine::CurrentMachine,
+    current_machine::{CompatibilityReport, CurrentMachine},
     error::{
-        ConfigError, ConfigFormat, Error, ErrorLocation, InputError, InputType, RepositoryError,
-        RepositoryLoadSource, Result,
+        ConfigError, ConfigFormat, Error, ErrorLocation, InputError, InputType, RendererSetupError,
+        RepositoryError, RepositoryLoadSource, Result,
     },
     errors::{ErrorEmitter, Errors},
     file_type::FileType,
     future_helper::FutureHelper,
-    init::{IdOrManual, InitOptions, Persistence},
+    init::{IdOrManual, InitOptions, Persistence, Project},
     menu::MenuBar,
+    shortcuts::Action,
     util::WEB,
 };
 
@@ -60,43 +63,66 @@ pub struct AppState {
 #[derive(Default)]
 struct AppCache {
     /// sorted machine list
-    machines: Vec<(String, String, bool)>,
+    machines: Vec<CachedEntry>,
     /// sorted style list
-    styles: Vec<(String, String, bool)>,
+    styles: Vec<CachedEntry>,
+}
+
+/// An entry of a [Repository] as cached in the [AppCache]
+struct CachedEntry {
+    id: String,
+    name: String,
+    category: Option<String>,
+    removable: bool,
+}
+
+impl CachedEntry {
+    /// Orders [CachedEntry]s by category and then by name.
+    /// Entries without a category are placed after all categories.
+    fn cmp_by_category(&self, other: &Self) -> Ordering {
+        (self.category.is_none(), &self.category, &self.name).cmp(&(
+            other.category.is_none(),
+            &other.category,
+            &other.name,
+        ))
+    }
 }
 
 impl AppCache {
-    /// Materializes the [list][Repository::list] of the passed [Repository].
-    fn materialize(repo: &Repository) -> Vec<(String, String, bool)> {
-        repo.list()
-            .map(|(id, name, removable)| (id.to_string(), name.to_string(), removable))
+    /// Materializes the [list][Repository::list_with_categories] of the passed [Repository].
+    fn materialize(repo: &Repository) -> Vec<CachedEntry> {
+        repo.list_with_categories()
+            .map(|(id, name, category, removable)| CachedEntry {
+                id: id.to_string(),
+                name: name.to_string(),
+                category: category.map(str::to_string),
+                removable,
+            })
             .collect()
     }
 
     /// Iterates over the passed list
-    fn iter(vec: &[(String, String, bool)]) -> impl Iterator<Item = (&str, &str, bool)> {
+    fn iter(vec: &[CachedEntry]) -> impl Iterator<Item = (&str, &str, bool)> {
         vec.iter()
-            .map(|(id, name, removable)| (id.as_str(), name.as_str(), *removable))
+            .map(|entry| (entry.id.as_str(), entry.name.as_str(), entry.removable))
     }
 
     /// Updates the cached list of machines from the passed machine-[Repository] and the passed list of compatible machine-IDs
     pub fn update_machines(&mut self, machine_repository: &Repository, compatible: &[String]) {
         self.machines = Self::materialize(machine_repository);
-        self.machines
-            .sort_by(|(a_id, a_name, _), (b_id, b_name, _)| {
-                compatible
-                    .contains(a_id)
-                    .cmp(&compatible.contains(b_id))
-                    .reverse()
-                    .then_with(|| a_name.cmp(b_name))
-            });
+        self.machines.sort_by(|a, b| {
+            compatible
+                .contains(&a.id)
+                .cmp(&compatible.contains(&b.id))
+                .reverse()
+                .then_with(|| a.cmp_by_category(b))
+        });
     }
 
     /// Updates the cached list of styles from the passed style-[Repository]
     pub fn update_styles(&mut self, style_repository: &Repository) {
         self.styles = Self::materialize(style_repository);
-        self.styles
-            .sort_by(|(_, a_name, _), (_, b_name, _)| a_name.cmp(b_name));
+        self.styles.sort_by(CachedEntry::cmp_by_category);
     }
 
     /// Gets the (cached and sorted) list of machines
@@ -140,7 +166,7 @@ impl App {
     ) -> Self {
         let mut errors = Errors::default();
 
-        RendererAdapter::setup(cc);
+        RendererAdapter::setup(cc).pipe_void(&mut errors);
 
         Self {
             state: constructor(&mut errors),
@@ -226,6 +252,43 @@ impl AppState {
                 .pipe(errors, Repository::empty);
         }
 
+        // Warn about user-dir entries which are listed but cannot be loaded
+        for (id, e) in machine_repository.verify_user_dir::<MachineConfig>() {
+            errors.add_error(Error::Repository(
+                RepositoryError::Verify(id, e),
+                ConfigFormat::Machine,
+            ));
+        }
+        for (id, e) in style_repository.verify_user_dir::<VisualConfig>() {
+            errors.add_error(Error::Repository(
+                RepositoryError::Verify(id, e),
+                ConfigFormat::Style,
+            ));
+        }
+
+        // Watch user-dirs for changes (only available on native builds)
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            machine_repository
+                .watch_user_dir_machines()
+                .map_err(|e| {
+                    Error::Repository(
+                        RepositoryError::Load(RepositoryLoadSource::UserDir, e),
+                        ConfigFormat::Machine,
+                    )
+                })
+                .pipe_void(errors);
+            style_repository
+                .watch_user_dir_styles()
+                .map_err(|e| {
+                    Error::Repository(
+                        RepositoryError::Load(RepositoryLoadSource::UserDir, e),
+                        ConfigFormat::Style,
+                    )
+                })
+                .pipe_void(errors);
+        }
+
         let mut app = Self {
             animator_adapter: AnimatorAdapter::default(),
             machine_repository,
@@ -245,7 +308,12 @@ impl AppState {
         }
         // Load any style as default (if any style is available)
         if let Some((id, style)) = app.style_repository.try_get_any() {
-            app.set_loaded_style(Some(id.to_string()), style);
+            let dir = app
+                .style_repository
+                .user_dir_path(id)
+                .and_then(Path::parent)
+                .map(Path::to_path_buf);
+            app.set_loaded_style(Some(id.to_string()), style, dir.as_deref());
         }
 
         app
@@ -255,48 +323,74 @@ impl AppState {
     /// Errors will be piped to the passed [Errors].
     fn new_with_init(init_options: InitOptions<'_>, errors: &mut Errors) -> Self {
         let mut app = Self::new(errors);
+        app.load_init(init_options, None, errors);
+        app
+    }
+
+    /// Create a new instance of the [AppState] with the specified [InitOptions] and [Persistence].
+    /// The passed [InitOptions] will overwrite options in [Persistence].
+    /// Errors will be piped to the passed [Errors].
+    fn new_with_init_and_persistence(
+        persistence: Option<Persistence>,
+        init_options: InitOptions<'_>,
+        errors: &mut Errors,
+    ) -> Self {
+        if let Some(persistence) = persistence {
+            let persisted: InitOptions<'_> = (&persistence).into();
+            Self::new_with_init(persisted.merge(init_options), errors)
+        } else {
+            // Nothing previously persisted
+            Self::new_with_init(init_options, errors)
+        }
+    }
 
+    /// Loads the values set in the passed [InitOptions].
+    /// Includes of the input are resolved relative to `dir` (see [Self::open_in]).
+    /// Errors will be piped to the passed [Errors].
+    fn load_init(
+        &mut self,
+        init_options: InitOptions<'_>,
+        dir: Option<&Path>,
+        errors: &mut Errors,
+    ) {
         if let Some((import_options, data)) = init_options.input {
             match import_options {
-                Some(import_options) => app.import(import_options, data).map_err(Error::Import),
-                None => app.open(data),
+                Some(import_options) => self.import(import_options, data).map_err(Error::Import),
+                None => self.open_in(data, dir),
             }
             .pipe_void(errors)
         }
 
         if let Some(machine) = init_options.machine {
             match machine {
-                IdOrManual::Id(machine_id) => app.set_machine(machine_id),
-                IdOrManual::Manual(data) => app.set_machine_manually(data),
+                IdOrManual::Id(machine_id) => self.set_machine(machine_id),
+                IdOrManual::Manual(data) => self.set_machine_manually(data),
             }
             .pipe_void(errors)
         }
         if let Some(style) = init_options.style {
             match style {
-                IdOrManual::Id(style_id) => app.set_style(style_id),
-                IdOrManual::Manual(data) => app.set_style_manually(data),
+                IdOrManual::Id(style_id) => self.set_style(style_id),
+                IdOrManual::Manual(data) => self.set_style_manually(data, None),
             }
             .pipe_void(errors)
         }
-
-        app
     }
 
-    /// Create a new instance of the [AppState] with the specified [InitOptions] and [Persistence].
-    /// The passed [InitOptions] will overwrite options in [Persistence].
-    /// Errors will be piped to the passed [Errors].
-    fn new_with_init_and_persistence(
-        persistence: Option<Persistence>,
-        init_options: InitOptions<'_>,
+    /// Opens the [Project] from the JSON in `data`.
+    /// Includes of the contained input are resolved relative to `dir`
+    /// (usually the directory of the project file; see [Self::open_in]).
+    /// Errors when loading the contained values will be piped to the passed [Errors].
+    pub fn open_project(
+        &mut self,
+        data: &[u8],
+        dir: Option<&Path>,
         errors: &mut Errors,
-    ) -> Self {
-        if let Some(persistence) = persistence {
-            let persisted: InitOptions<'_> = (&persistence).into();
-            Self::new_with_init(persisted.merge(init_options), errors)
-        } else {
-            // Nothing previously persisted
-            Self::new_with_init(init_options, errors)
-        }
+    ) -> Result<()> {
+        let project =
+            Project::from_json(data).map_err(|e| Error::FileOpen(InputType::Project(e)))?;
+        self.load_init((&project).into(), dir, errors);
+        Ok(())
     }
 
     /// Import the instructions from `data` using the specified [ImportOptions]
@@ -311,53 +405,73 @@ impl AppState {
         Ok(())
     }
 
-    /// Open the naviz-instructions from `data`
+    /// Open the naviz-instructions from `data`.
+    /// Any `#include`s are resolved relative to the current working directory;
+    /// see [Self::open_in].
     pub fn open(&mut self, data: &[u8]) -> Result<()> {
+        self.open_in(data, None)
+    }
+
+    /// Open the naviz-instructions from `data`,
+    /// resolving any `#include`s relative to the passed `dir`
+    /// (usually the directory of the opened file)
+    /// or the current working directory if no `dir` is passed.
+    /// Includes of included files are resolved relative to the including file.
+    pub fn open_in(&mut self, data: &[u8], dir: Option<&Path>) -> Result<()> {
         let text = str::from_utf8(data)
             .map_err(|e| Error::FileOpen(InputType::Instruction(InputError::UTF8(e))))?;
+        let root = naviz_parser::normalize_source(text);
 
-        let input = naviz_parser::input::lexer::lex(text).map_err(|e| {
-            let location = ErrorLocation::from_offset(text, e.offset());
-            Error::FileOpen(InputType::Instruction(InputError::Lex(
-                e.into_inner(),
-                Some(location),
-            )))
-        })?;
-
-        let input = naviz_parser::input::parser::parse(&input).map_err(|e| {
-            // Estimate line from number of separator tokens before the error index.
-            let token_index = e.offset();
-            use naviz_parser::input::lexer::Token as InTok;
-            let line = 1 + input
-                .iter()
-                .take(token_index.min(input.len()))
-                .filter(|t| matches!(t, InTok::Separator))
-                .count();
-            // Find byte offset of start of that line
-            let mut current_line = 1usize;
-            let mut line_start_offset = 0usize;
-            for (i, ch) in text.char_indices() {
-                if current_line == line {
-                    break;
-                }
-                if ch == '\n' {
-                    current_line += 1;
-                    line_start_offset = i + 1;
-                }
-            }
-            let location = ErrorLocation {
-                line,
-                column: 1,
-                offset: line_start_offset,
-            };
-            Error::FileOpen(InputType::Instruction(InputError::Parse(
-                e.into_inner(),
-                Some(location),
-            )))
-        })?;
-
-        let input = naviz_parser::input::concrete::Instructions::new(input)
-            .map_err(|e| Error::FileOpen(InputType::Instruction(InputError::Convert(e))))?;
+        let dir = dir.unwrap_or(Path::new(""));
+        let (text, source_map) = naviz_parser::input::preprocess_with_map(&root, |path| {
+            std::fs::read_to_string(dir.join(path))
+                .map(|included| naviz_parser::normalize_source(&included).into_owned())
+        })
+        .map_err(|e| Error::FileOpen(InputType::Instruction(InputError::Include(e))))?;
+        let text = text.as_str();
+        // Locates the passed offset of the preprocessed text in the opened or included file
+        let locate = |offset| {
+            let (file, source, offset) = source_map.locate(&root, offset);
+            ErrorLocation::from_offset(source, offset).in_file(file)
+        };
+
+        let (input, spans): (Vec<_>, Vec<_>) = naviz_parser::input::lexer::lex_with_spans(text)
+            .map_err(|e| {
+                let location = locate(e.offset());
+                Error::FileOpen(InputType::Instruction(InputError::Lex(
+                    e.into_inner(),
+                    Some(location),
+                )))
+            })?
+            .into_iter()
+            .unzip();
+
+        let (input, offsets): (Vec<_>, Vec<_>) =
+            naviz_parser::input::parser::parse_with_offsets(&input)
+                .map_err(|e| {
+                    // Map the index of the failing token to its position in the source
+                    let offset = spans
+                        .get(e.offset())
+                        .map(|span| span.start)
+                        .unwrap_or(text.len());
+                    Error::FileOpen(InputType::Instruction(InputError::Parse(
+                        e.into_inner(),
+                        Some(locate(offset)),
+                    )))
+                })?
+                .into_iter()
+                .unzip();
+
+        let input =
+            naviz_parser::input::concrete::Instructions::new_located(input, Default::default())
+                .map_err(|(index, e)| {
+                    // Map the index of the failing instruction to the position of its first token
+                    let location = index
+                        .and_then(|index| offsets.get(index))
+                        .and_then(|offset| spans.get(*offset))
+                        .map(|span| locate(span.start));
+                    Error::FileOpen(InputType::Instruction(InputError::Convert(e, location)))
+                })?;
         self.animator_adapter.set_instructions(input);
         self.update_machines(); // update compatible machines
         self.select_compatible_machine()?;
@@ -365,11 +479,21 @@ impl AppState {
     }
 
     /// Opens a file by [FileType].
-    pub fn open_by_type(&mut self, file_type: FileType, data: &[u8]) -> Result<()> {
+    /// Includes of [FileType::Instructions] and [FileType::Project]
+    /// are resolved relative to `dir` (see [Self::open_in]).
+    /// Errors when loading the contents of a [FileType::Project] will be piped to the passed [Errors].
+    pub fn open_by_type(
+        &mut self,
+        file_type: FileType,
+        data: &[u8],
+        dir: Option<&std::path::Path>,
+        errors: &mut Errors,
+    ) -> Result<()> {
         match file_type {
-            FileType::Instructions => self.open(data),
+            FileType::Instructions => self.open_in(data, dir),
             FileType::Machine => self.set_machine_manually(data),
-            FileType::Style => self.set_style_manually(data),
+            FileType::Style => self.set_style_manually(data, dir),
+            FileType::Project => self.open_project(data, dir, errors),
         }
     }
 
@@ -412,6 +536,16 @@ impl AppState {
         Ok(false)
     }
 
+    /// Explains which machines in the repository are compatible with the loaded instructions.
+    pub fn machine_compatibility_report(&self) -> CompatibilityReport {
+        CompatibilityReport::new(
+            self.animator_adapter
+                .get_instructions()
+                .map(|i| i.directives.targets.as_slice()),
+            |id| self.machine_repository.has(id),
+        )
+    }
+
     /// Sets the machine to the one with the specified `id`
     pub fn set_machine(&mut self, id: impl Into<String>) -> Result<()> {
         let id = id.into();
@@ -442,32 +576,7 @@ impl AppState {
 
     /// Set the current machine to the one specified in `data`.
     pub fn set_machine_manually(&mut self, data: &[u8]) -> Result<()> {
-        let machine = str::from_utf8(data).map_err(|e| {
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Machine,
-                ConfigError::UTF8(e),
-            ))
-        })?;
-        let machine = naviz_parser::config::lexer::lex(machine).map_err(|e| {
-            let loc = ErrorLocation::from_offset(machine, e.offset());
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Machine,
-                ConfigError::Lex(e.into_inner(), Some(loc)),
-            ))
-        })?;
-        let machine = naviz_parser::config::parser::parse(&machine).map_err(|e| {
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Machine,
-                ConfigError::Parse(e.into_inner(), None),
-            ))
-        })?;
-        let machine: naviz_parser::config::generic::Config = machine.into();
-        let machine: MachineConfig = machine.try_into().map_err(|e| {
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Machine,
-                ConfigError::Convert(e),
-            ))
-        })?;
+        let machine = parse_config(data, ConfigFormat::Machine)?;
         self.set_loaded_machine(None::<String>, machine);
         // keep machine in persistence
         self.persistence.machine = Some(IdOrManual::Manual(data.into()));
@@ -485,7 +594,12 @@ impl AppState {
                 ConfigFormat::Style,
             ))?
             .map_err(|e| Error::Repository(RepositoryError::Open(e), ConfigFormat::Style))?;
-        self.set_loaded_style(Some(id.clone()), style);
+        let dir = self
+            .style_repository
+            .user_dir_path(&id)
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+        self.set_loaded_style(Some(id.clone()), style, dir.as_deref());
         // keep style in persistence
         self.persistence.style = Some(IdOrManual::Id(id));
         Ok(())
@@ -493,37 +607,22 @@ impl AppState {
 
     /// Sets the current style to `style` with the optional `id`.
     /// If `id` is [None], the style is assumed to be set manually.
-    fn set_loaded_style(&mut self, id: Option<impl Into<String>>, style: VisualConfig) {
+    /// Relative paths in the style are resolved relative to `dir`.
+    fn set_loaded_style(
+        &mut self,
+        id: Option<impl Into<String>>,
+        style: VisualConfig,
+        dir: Option<&Path>,
+    ) {
         self.current_style_id = id.map(Into::into);
-        self.animator_adapter.set_visual_config(style);
+        self.animator_adapter.set_visual_config(style, dir);
     }
 
     /// Set the current style to the one specified in `data`.
-    pub fn set_style_manually(&mut self, data: &[u8]) -> Result<()> {
-        let visual = str::from_utf8(data).map_err(|e| {
-            Error::FileOpen(InputType::Config(ConfigFormat::Style, ConfigError::UTF8(e)))
-        })?;
-        let visual = naviz_parser::config::lexer::lex(visual).map_err(|e| {
-            let loc = ErrorLocation::from_offset(visual, e.offset());
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Style,
-                ConfigError::Lex(e.into_inner(), Some(loc)),
-            ))
-        })?;
-        let visual = naviz_parser::config::parser::parse(&visual).map_err(|e| {
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Style,
-                ConfigError::Parse(e.into_inner(), None),
-            ))
-        })?;
-        let visual: naviz_parser::config::generic::Config = visual.into();
-        let visual: VisualConfig = visual.try_into().map_err(|e| {
-            Error::FileOpen(InputType::Config(
-                ConfigFormat::Style,
-                ConfigError::Convert(e),
-            ))
-        })?;
-        self.set_loaded_style(None::<String>, visual);
+    /// Relative paths in the style are resolved relative to `dir`.
+    pub fn set_style_manually(&mut self, data: &[u8], dir: Option<&Path>) -> Result<()> {
+        let visual = parse_config(data, ConfigFormat::Style)?;
+        self.set_loaded_style(None::<String>, visual, dir);
         // keep style in persistence
         self.persistence.style = Some(IdOrManual::Manual(data.into()));
         Ok(())
@@ -544,12 +643,13 @@ impl AppState {
     }
 
     /// Gets the list of loaded machines.
-    /// Will be sorted by name and compatible machines will be at the top.
+    /// Will be grouped by category and sorted by name,
+    /// and compatible machines will be at the top.
     pub fn get_machines(&self) -> impl Iterator<Item = (&str, &str, bool)> {
         self.cache.machines()
     }
 
-    /// Gets the list of loaded styles sorted by name.
+    /// Gets the list of loaded styles grouped by category and sorted by name.
     pub fn get_styles(&self) -> impl Iterator<Item = (&str, &str, bool)> {
         self.cache.styles()
     }
@@ -562,15 +662,7 @@ impl AppState {
         self.machine_repository
             .import_machine_to_user_dir(path)
             .map_err(|e| {
-                use naviz_repository::error::Error as RErr;
-                let loc = match &e {
-                    RErr::LexError(offset, _) | RErr::ParseError(offset, _) => {
-                        std::fs::read_to_string(path)
-                            .ok()
-                            .map(|c| ErrorLocation::from_offset(&c, *offset))
-                    }
-                    _ => None,
-                };
+                let loc = import_error_location(path, &e);
                 Error::Repository(RepositoryError::Import(e, loc), ConfigFormat::Machine)
             })?;
         self.update_machines();
@@ -585,15 +677,7 @@ impl AppState {
         self.style_repository
             .import_style_to_user_dir(path)
             .map_err(|e| {
-                use naviz_repository::error::Error as RErr;
-                let loc = match &e {
-                    RErr::LexError(offset, _) | RErr::ParseError(offset, _) => {
-                        std::fs::read_to_string(path)
-                            .ok()
-                            .map(|c| ErrorLocation::from_offset(&c, *offset))
-                    }
-                    _ => None,
-                };
+                let loc = import_error_location(path, &e);
                 Error::Repository(RepositoryError::Import(e, loc), ConfigFormat::Style)
             })?;
         self.update_styles();
@@ -621,34 +705,171 @@ impl AppState {
     }
 
     /// Starts an export of the visualization to the specified `target`-path
+    /// (a file for [ExportTarget::Video] or a directory for [ExportTarget::PngSequence])
     /// with the specified `resolution` and `fps`.
+    /// The [FrameOptions] select whether the background is left transparent
+    /// (if the format of `target` supports it),
+    /// whether the time and a progress-bar are burned into the frames,
+    /// whether moving atoms are blurred,
+    /// and the quality to render with.
     /// Updates will be sent over the `progress`-channel.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn export(
         &self,
+        kind: ExportTarget,
         target: PathBuf,
         resolution: (u32, u32),
         fps: u32,
+        options: FrameOptions,
         progress: Sender<VideoProgress>,
     ) {
         if let Some(animator) = self.animator_adapter.animator() {
             let video = VideoExport::new(animator, resolution, fps);
+            let background_image = self.animator_adapter.background_image();
             thread::spawn(move || {
                 let mut video = futures::executor::block_on(video);
-                video.export_video(&target, progress);
+                video.set_background_image(background_image.as_deref());
+                video.set_transparent(options.transparent);
+                video.set_overlay(options.overlay);
+                video.set_motion_blur(options.motion_blur);
+                video.set_quality(options.quality);
+                match kind {
+                    ExportTarget::Video => video.export_video(&target, progress),
+                    ExportTarget::PngSequence => video.export_png_sequence(&target, progress),
+                }
             });
         }
     }
 
+    /// Gets the current animation-time.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time(&self) -> f32 {
+        self.animator_adapter.time()
+    }
+
+    /// Renders the visualization at the specified `time` with the specified `resolution`
+    /// and saves it as a PNG to the specified `target`-path.
+    /// Rendering happens on a separate thread;
+    /// the result will be sent over the `result`-channel.
+    /// Does nothing if no visualization is loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snapshot(
+        &self,
+        time: f32,
+        resolution: (u32, u32),
+        target: PathBuf,
+        result: Sender<Result<()>>,
+    ) {
+        if let Some(animator) = self.animator_adapter.animator() {
+            let video = VideoExport::new(animator, resolution, 1);
+            let background_image = self.animator_adapter.background_image();
+            thread::spawn(move || {
+                let mut video = futures::executor::block_on(video);
+                video.set_background_image(background_image.as_deref());
+                let _ = result.send(
+                    video
+                        .export_snapshot(time, &target)
+                        .map_err(Error::Snapshot),
+                );
+            });
+        }
+    }
+
+    /// Applies changes to the watched user-dirs of the repositories.
+    /// Will re-apply the current machine or style if its file changed.
+    /// Errors will be piped to the passed [Errors].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn poll_repository_changes(&mut self, errors: &mut Errors) {
+        use naviz_repository::watch::ChangeEvent;
+
+        /// Pipes errors of the passed `changes` to `errors`
+        /// and returns the ids of the changed entries.
+        fn handle(
+            changes: Vec<ChangeEvent>,
+            format: fn() -> ConfigFormat,
+            errors: &mut Errors,
+        ) -> Vec<String> {
+            changes
+                .into_iter()
+                .filter_map(|change| match change {
+                    ChangeEvent::Added(id) | ChangeEvent::Modified(id) => Some(id),
+                    ChangeEvent::Removed(_) => None,
+                    ChangeEvent::Invalid(_, e) => {
+                        errors.add_error(Error::Repository(RepositoryError::Open(e), format()));
+                        None
+                    }
+                    ChangeEvent::WatchError(e) => {
+                        errors.add_error(Error::Repository(
+                            RepositoryError::Load(RepositoryLoadSource::UserDir, e),
+                            format(),
+                        ));
+                        None
+                    }
+                })
+                .collect()
+        }
+
+        let machine_changes = self.machine_repository.poll_changes();
+        if !machine_changes.is_empty() {
+            let changed = handle(machine_changes, || ConfigFormat::Machine, errors);
+            self.update_machines();
+            if let Some(id) = self
+                .get_current_machine_id()
+                .filter(|id| changed.iter().any(|c| c == id))
+                .map(str::to_string)
+            {
+                self.set_machine(id).pipe_void(errors);
+            }
+        }
+
+        let style_changes = self.style_repository.poll_changes();
+        if !style_changes.is_empty() {
+            let changed = handle(style_changes, || ConfigFormat::Style, errors);
+            self.update_styles();
+            if let Some(id) = self
+                .get_current_style_id()
+                .filter(|id| changed.iter().any(|c| c == id))
+                .map(str::to_string)
+            {
+                self.set_style(id).pipe_void(errors);
+            }
+        }
+    }
+
     /// Updates the cached list of machines.
     fn update_machines(&mut self) {
-        self.cache.update_machines(
-            &self.machine_repository,
-            self.animator_adapter
-                .get_instructions()
-                .map(|i| i.directives.targets.as_slice())
-                .unwrap_or(&[]),
-        );
+        let compatible = self.compatible_machines();
+        self.cache
+            .update_machines(&self.machine_repository, &compatible);
+    }
+
+    /// Gets the ids of the machines compatible with the loaded instructions:
+    /// The [targets][naviz_parser::input::concrete::Directives::targets] of the instructions,
+    /// or the machines which define all zones referenced by the instructions
+    /// if no targets are specified.
+    fn compatible_machines(&self) -> Vec<String> {
+        let Some(instructions) = self.animator_adapter.get_instructions() else {
+            return Vec::new();
+        };
+        if !instructions.directives.targets.is_empty() {
+            return instructions.directives.targets.clone();
+        }
+
+        let zones = instructions.referenced_zone_ids();
+        if zones.is_empty() {
+            // Nothing to infer the machine from
+            return Vec::new();
+        }
+        self.machine_repository
+            .list()
+            .filter(|(id, _, _)| {
+                self.machine_repository
+                    .get::<MachineConfig>(id)
+                    .and_then(Result::ok)
+                    .is_some_and(|machine| zones.iter().all(|z| machine.zone.contains_key(*z)))
+            })
+            .map(|(id, _, _)| id.to_string())
+            .collect()
     }
 
     /// Updates the cached list of styles.
@@ -672,12 +893,158 @@ impl AppState {
     pub fn get_force_zen(&mut self) -> bool {
         self.animator_adapter.get_force_zen()
     }
+
+    /// Fits the whole content onto the canvas again.
+    /// See [ZoomState::fit][crate::zoom::ZoomState::fit].
+    pub fn fit_content(&mut self) {
+        self.animator_adapter.zoom_mut().fit();
+    }
+
+    /// Whether the whole content is fitted onto the canvas
+    /// (i.e., it is not zoomed, panned, or following an atom).
+    pub fn content_fitted(&self) -> bool {
+        self.animator_adapter.zoom().auto_fit
+    }
+}
+
+/// Gets the [ErrorLocation] of an error which occurred while importing the file at `path`
+/// (if the error has a location).
+/// The file is normalized like the repository does before parsing,
+/// so that the offset of the error matches.
+#[cfg(not(target_arch = "wasm32"))]
+fn import_error_location(
+    path: &Path,
+    error: &naviz_repository::error::Error,
+) -> Option<ErrorLocation> {
+    use naviz_repository::error::Error as RErr;
+    let offset = match error {
+        RErr::LexError(offset, _) | RErr::ParseError(offset, _) => Some(*offset),
+        RErr::ConfigReadError(e) => e.offset(),
+        _ => None,
+    }?;
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(ErrorLocation::from_offset(
+        &naviz_parser::normalize_source(&text),
+        offset,
+    ))
+}
+
+/// Parses a config from the passed `data`.
+/// See [naviz_repository::parse_config_bytes].
+fn parse_config<C>(data: &[u8], format: ConfigFormat) -> Result<C>
+where
+    naviz_parser::config::generic::Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+{
+    naviz_repository::parse_config_bytes(data).map_err(|e| {
+        use naviz_repository::error::Error as RErr;
+        let error = match e {
+            RErr::UTF8Error(e) => ConfigError::UTF8(e),
+            RErr::LexError(offset, inner) => ConfigError::Lex(
+                inner,
+                str::from_utf8(data).ok().map(|text| {
+                    ErrorLocation::from_offset(&naviz_parser::normalize_source(text), offset)
+                }),
+            ),
+            RErr::ParseError(_, inner) => ConfigError::Parse(inner, None),
+            RErr::ConfigReadError(e) => {
+                // Offsets refer to the normalized text (see `config_from_bytes`)
+                let location = e.offset().and_then(|offset| {
+                    str::from_utf8(data).ok().map(|text| {
+                        ErrorLocation::from_offset(&naviz_parser::normalize_source(text), offset)
+                    })
+                });
+                ConfigError::Convert(e, location)
+            }
+            // Other errors do not occur when parsing
+            e => return Error::Repository(RepositoryError::Open(e), format),
+        };
+        Error::FileOpen(InputType::Config(format, error))
+    })
+}
+
+/// The speed at which scrolling zooms the content (per scrolled point)
+const SCROLL_ZOOM_SPEED: f32 = 0.005;
+
+impl App {
+    /// Handles the interactions with the canvas showing the passed [AnimatorState] in `rect`:
+    /// Dragging pans the content, scrolling zooms the content,
+    /// and double-clicking follows the atom under the pointer (or fits the content again).
+    /// Hovering an atom shows its id and position.
+    ///
+    /// Also moves the view towards the followed atom (see [ZoomState::update][crate::zoom::ZoomState::update]).
+    fn interact_with_canvas(
+        &mut self,
+        ui: &egui::Ui,
+        rect: egui::Rect,
+        animator_state: &AnimatorState,
+    ) {
+        let adapter = &mut self.state.animator_adapter;
+        let resolution = canvas_resolution(ui, rect);
+        let projection = animator_state.content_projection(resolution);
+        let to_content = |point: egui::Pos2| projection.unproject(canvas_target(rect, point));
+
+        let response = ui.interact(rect, ui.id().with("canvas"), egui::Sense::click_and_drag());
+        let hovered_atom = response.hover_pos().and_then(|pointer| {
+            let (index, atom) = animator_state.atom_at(to_content(pointer))?;
+            Some((adapter.atom_id(index)?.to_owned(), atom.position))
+        });
+
+        if let Some(pointer) = response
+            .interact_pointer_pos()
+            .filter(|_| response.dragged())
+        {
+            let (x, y) = to_content(pointer);
+            let (previous_x, previous_y) = to_content(pointer - response.drag_delta());
+            adapter.zoom_mut().pan((x - previous_x, y - previous_y));
+        }
+        if let Some(pointer) = response.hover_pos() {
+            let (scroll, pinch) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = pinch * (scroll * SCROLL_ZOOM_SPEED).exp();
+            if factor != 1. {
+                adapter.zoom_mut().zoom_at(to_content(pointer), factor);
+            }
+        }
+        if response.double_clicked() {
+            match &hovered_atom {
+                Some((id, _)) => adapter.zoom_mut().follow(id.clone()),
+                None => adapter.zoom_mut().fit(),
+            }
+        }
+        if let Some((id, (x, y))) = hovered_atom {
+            response.on_hover_text_at_pointer(format!("{id} ({x:.1}, {y:.1})"));
+        }
+
+        let followed = adapter
+            .zoom()
+            .following()
+            .and_then(|id| adapter.atom_position(id));
+        let dt = ui.input(|i| i.stable_dt);
+        adapter
+            .zoom_mut()
+            .update(&animator_state.fitted_projection(resolution), followed, dt);
+    }
+
+    /// Applies the [Action]s of all keyboard shortcuts pressed in the current frame
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        for action in Action::pressed(ctx) {
+            match action {
+                Action::ZoomIn => egui::gui_zoom::zoom_in(ctx),
+                Action::ZoomOut => egui::gui_zoom::zoom_out(ctx),
+                action => self.state.animator_adapter.apply_action(action),
+            }
+        }
+    }
 }
 
 impl eframe::App for App {
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
         let ctx = ui.ctx().clone();
 
+        #[cfg(not(target_arch = "wasm32"))]
+        self.state.poll_repository_changes(&mut self.ui.errors);
+
+        self.handle_shortcuts(&ctx);
+
         // Menu
         egui::Panel::top("app_menu").show(ui, |ui| {
             self.ui.menu_bar.draw(
@@ -702,11 +1069,22 @@ impl eframe::App for App {
                 right: 0.,
             };
             let animator_state = self.state.animator_adapter.get();
+            if self.state.animator_adapter.is_loading_background_image() {
+                ctx.request_repaint();
+            }
+            let mut canvas = None;
             panel.draw(
                 ui,
                 |ui| {
                     if let Some(animator_state) = animator_state {
-                        WgpuCanvas::new(RendererAdapter::new(animator_state)).draw(&ctx, ui);
+                        let frame_time = Duration::from_secs_f32(ctx.input(|i| i.stable_dt));
+                        let rect = WgpuCanvas::new(RendererAdapter::new(
+                            animator_state.clone(),
+                            frame_time,
+                            ctx.clone(),
+                        ))
+                        .draw(&ctx, ui);
+                        canvas = Some((rect, animator_state));
                     } else {
                         // Animator is not ready (something missing) => empty canvas
                         WgpuCanvas::new(EmptyCanvas::new()).draw(&ctx, ui);
@@ -720,6 +1098,9 @@ impl eframe::App for App {
                 },
                 |_| {},
             );
+            if let Some((rect, animator_state)) = canvas {
+                self.interact_with_canvas(ui, rect, &animator_state);
+            }
         });
 
         self.ui.errors.draw(&ctx);
@@ -730,6 +1111,24 @@ impl eframe::App for App {
     }
 }
 
+/// Gets the resolution (in pixels) of the canvas drawn in `rect`
+fn canvas_resolution(ui: &egui::Ui, rect: egui::Rect) -> (u32, u32) {
+    let pixels_per_point = ui.ctx().pixels_per_point();
+    (
+        (rect.width() * pixels_per_point) as u32,
+        (rect.height() * pixels_per_point) as u32,
+    )
+}
+
+/// Maps the passed `point` on the canvas drawn in `rect`
+/// into wgpu-coordinates (`-1` to `1`, pointing upwards)
+fn canvas_target(rect: egui::Rect, point: egui::Pos2) -> (f32, f32) {
+    (
+        (point.x - rect.left()) / rect.width() * 2. - 1.,
+        1. - (point.y - rect.top()) / rect.height() * 2.,
+    )
+}
+
 /// An adapter from [naviz_renderer] to [CallbackTrait].
 ///
 /// Setup the renderer using [RendererAdapter::setup]
@@ -739,8 +1138,15 @@ struct RendererAdapter {
     size: (f32, f32),
     /// The animator_state to render
     animator_state: AnimatorState,
+    /// The time since the last frame, used to advance fades
+    frame_time: Duration,
+    /// The egui [Context][egui::Context] to request repaints on while fading
+    ctx: egui::Context,
 }
 
+/// The duration over which parts of the visualization fade in and out
+const FADE_DURATION: Duration = Duration::from_millis(200);
+
 impl RendererAdapter {
     /// Creates a [Renderer] and stores it in the egui [RenderState][eframe::egui_wgpu::RenderState].
     /// This created renderer will later be rendered from [RendererAdapter::paint].
@@ -748,31 +1154,43 @@ impl RendererAdapter {
     /// The renderer is stored in the renderer state
     /// in order for the graphics pipeline to have the same lifetime as the egui render pass.
     /// See [this section from the egui demo][https://github.com/emilk/egui/blob/0.28.1/crates/egui_demo_app/src/apps/custom3d_wgpu.rs#L83-L85]
-    pub fn setup(cc: &eframe::CreationContext<'_>) {
+    ///
+    /// Returns an error if no render state is available or the renderer could not be created.
+    pub fn setup(cc: &eframe::CreationContext<'_>) -> Result<()> {
         let wgpu_render_state = cc
             .wgpu_render_state
             .as_ref()
-            .expect("No wgpu render state found"); // Should not happen when `wgpu` is enabled
+            .ok_or(Error::Renderer(RendererSetupError::NoRenderState))?; // Should not happen when `wgpu` is enabled
+
+        let mut renderer = Renderer::try_new(
+            &wgpu_render_state.device,
+            &wgpu_render_state.queue,
+            wgpu_render_state.target_format,
+            &Config::example_minimal(),
+            &State::example_minimal(),
+            (64, 64), // Use some small default resolution to create renderer, as the canvas-resolution is not yet known
+        )
+        .map_err(|e| Error::Renderer(RendererSetupError::Renderer(e)))?;
+        renderer.set_fade_duration(FADE_DURATION);
 
         wgpu_render_state
             .renderer
             .write()
             .callback_resources
-            .insert(Renderer::new(
-                &wgpu_render_state.device,
-                &wgpu_render_state.queue,
-                wgpu_render_state.target_format,
-                &Config::example(),
-                &State::example(),
-                (1920, 1080), // Use some default resolution to create renderer, as the canvas-resolution is not yet known
-            ));
+            .insert(renderer);
+
+        Ok(())
     }
 
-    /// Creates a new [RendererAdapter] from the passed [AnimatorState]
-    pub fn new(animator_state: AnimatorState) -> Self {
+    /// Creates a new [RendererAdapter] from the passed [AnimatorState].
+    /// `frame_time` is the time since the last frame.
+    /// Repaints will be requested on the passed `ctx` while fades are running.
+    pub fn new(animator_state: AnimatorState, frame_time: Duration, ctx: egui::Context) -> Self {
         Self {
             animator_state,
             size: Default::default(),
+            frame_time,
+            ctx,
         }
     }
 }
@@ -786,6 +1204,9 @@ impl CallbackTrait for RendererAdapter {
         _egui_encoder: &mut wgpu::CommandEncoder,
         callback_resources: &mut eframe::egui_wgpu::CallbackResources,
     ) -> Vec<wgpu::CommandBuffer> {
+        let mut rendered = callback_resources
+            .remove::<RenderedState>()
+            .unwrap_or_default();
         if let Some(r) = callback_resources.get_mut::<Renderer>() {
             r.update_viewport(
                 device,
@@ -796,10 +1217,15 @@ impl CallbackTrait for RendererAdapter {
                 ),
             );
             self.animator_state
-                .update(r, &mut (device, queue), device, queue);
+                .update(r, &mut rendered, &mut (device, queue), device, queue);
+            if r.animate(&mut (device, queue), device, queue, self.frame_time) {
+                // Fade still running => keep drawing frames
+                self.ctx.request_repaint();
+            }
         } else {
             error!("Failed to get renderer");
         }
+        callback_resources.insert(rendered);
         Vec::new()
     }
 
@@ -819,11 +1245,47 @@ impl CallbackTrait for RendererAdapter {
 
 impl CanvasContent for RendererAdapter {
     fn background_color(&self) -> egui::Color32 {
-        let [r, g, b, a] = self.animator_state.background();
-        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+        naviz_state::color::to_egui(self.animator_state.background())
     }
 
     fn target_size(&mut self, size: (f32, f32)) {
         self.size = size;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Entries should be grouped by category and sorted by name within their category.
+    #[test]
+    fn cache_sorts_by_category() {
+        let entry = |id: &str, name: &str, category: Option<&str>| {
+            let category = category
+                .map(|c| format!("category: \"{c}\"\n"))
+                .unwrap_or_default();
+            (
+                id.to_string(),
+                format!("name: \"{name}\"\n{category}").into_bytes(),
+            )
+        };
+        let repo = Repository::from_entries([
+            entry("d", "Delta", None),
+            entry("c", "Charlie", Some("Production")),
+            entry("b", "Bravo", Some("Experimental")),
+            entry("a", "Alpha", Some("Production")),
+            entry("e", "Echo", Some("Experimental")),
+        ])
+        .expect("Failed to create repository");
+
+        let mut cache = AppCache::default();
+        cache.update_styles(&repo);
+        let ids: Vec<_> = cache.styles().map(|(id, _, _)| id).collect();
+        assert_eq!(ids, vec!["b", "e", "a", "c", "d"]);
+
+        // Compatible machines come first, independent of their category
+        cache.update_machines(&repo, &["d".to_string()]);
+        let ids: Vec<_> = cache.machines().map(|(id, _, _)| id).collect();
+        assert_eq!(ids, vec!["d", "b", "e", "a", "c"]);
+    }
+}
@@ -1,5 +1,5 @@
 use eframe::egui_wgpu::{Callback, CallbackTrait};
-use egui::{Color32, Context, Ui};
+use egui::{Color32, Context, Rect, Ui};
 
 /// A canvas that allows drawing using OpenGL.
 /// The content to draw must implement [CanvasContent] and be set in [WgpuCanvas::new].
@@ -16,7 +16,8 @@ impl<C: CanvasContent + 'static> WgpuCanvas<C> {
     /// Draws this canvas.
     /// Takes remaining space of parent.
     /// Also requests a repaint immediately.
-    pub fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+    /// Returns the [Rect] the content was drawn to.
+    pub fn draw(&mut self, ctx: &Context, ui: &mut Ui) -> Rect {
         egui::Frame::canvas(ui.style())
             .fill(self.content.background_color())
             .show(ui, |ui| {
@@ -27,7 +28,9 @@ impl<C: CanvasContent + 'static> WgpuCanvas<C> {
                     .add(Callback::new_paint_callback(rect, self.content.clone()));
 
                 ctx.request_repaint();
-            });
+                rect
+            })
+            .inner
     }
 }
 
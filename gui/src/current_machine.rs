@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 /// The currently selected machine
 pub enum CurrentMachine {
     /// No machine selected
@@ -34,3 +36,143 @@ impl CurrentMachine {
         }
     }
 }
+
+/// Explains which machines are compatible with the loaded instructions.
+/// Created by [CompatibilityReport::new].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatibilityReport {
+    /// No instructions are loaded
+    NoInstructions,
+    /// The instructions do not specify any target machines
+    NoTargets,
+    /// Some targets are available in the repository
+    Available {
+        /// The targets of the instructions
+        targets: Vec<String>,
+        /// The targets which are available in the repository
+        available: Vec<String>,
+    },
+    /// None of the targets are available in the repository
+    Unknown {
+        /// The targets of the instructions
+        targets: Vec<String>,
+    },
+}
+
+impl CompatibilityReport {
+    /// Creates a [CompatibilityReport] for the passed `targets` of the instructions
+    /// (or [None] if no instructions are loaded),
+    /// where `available` checks whether a machine is available in the repository.
+    pub fn new(targets: Option<&[String]>, available: impl Fn(&str) -> bool) -> Self {
+        let Some(targets) = targets else {
+            return Self::NoInstructions;
+        };
+        if targets.is_empty() {
+            return Self::NoTargets;
+        }
+        let targets = targets.to_vec();
+        let available: Vec<_> = targets.iter().filter(|id| available(id)).cloned().collect();
+        if available.is_empty() {
+            Self::Unknown { targets }
+        } else {
+            Self::Available { targets, available }
+        }
+    }
+
+    /// Whether any compatible machine is available
+    pub fn is_available(&self) -> bool {
+        matches!(self, Self::Available { .. })
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Writes the passed `ids` as a comma-separated list
+        fn list(f: &mut std::fmt::Formatter<'_>, ids: &[String]) -> std::fmt::Result {
+            for (idx, id) in ids.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "`{id}`")?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Self::NoInstructions => write!(f, "No instructions are loaded."),
+            Self::NoTargets => write!(
+                f,
+                "The instructions do not specify any target machines; select a machine manually."
+            ),
+            Self::Available { available, .. } => {
+                write!(f, "Compatible machines: ")?;
+                list(f, available)
+            }
+            Self::Unknown { targets } => {
+                write!(f, "The instructions target ")?;
+                list(f, targets)?;
+                if targets.len() == 1 {
+                    write!(f, ", which is not installed.")
+                } else {
+                    write!(f, ", none of which are installed.")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The machines available in the test-repository
+    fn available(id: &str) -> bool {
+        id == "present"
+    }
+
+    /// Targets as owned strings
+    fn targets(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn present_machine() {
+        let targets = targets(&["absent", "present"]);
+        let report = CompatibilityReport::new(Some(&targets), available);
+        assert_eq!(
+            report,
+            CompatibilityReport::Available {
+                targets: targets.clone(),
+                available: vec!["present".to_string()],
+            }
+        );
+        assert!(report.is_available());
+    }
+
+    #[test]
+    fn absent_machine() {
+        let targets = targets(&["hardware_x"]);
+        let report = CompatibilityReport::new(Some(&targets), available);
+        assert_eq!(
+            report,
+            CompatibilityReport::Unknown {
+                targets: targets.clone()
+            }
+        );
+        assert!(!report.is_available());
+        assert_eq!(
+            report.to_string(),
+            "The instructions target `hardware_x`, which is not installed."
+        );
+    }
+
+    #[test]
+    fn no_targets() {
+        let report = CompatibilityReport::new(Some(&[]), available);
+        assert_eq!(report, CompatibilityReport::NoTargets);
+        assert!(!report.is_available());
+
+        let report = CompatibilityReport::new(None, available);
+        assert_eq!(report, CompatibilityReport::NoInstructions);
+    }
+}
@@ -2,8 +2,14 @@ use std::str::Utf8Error;
 
 use naviz_import::ImportError;
 use naviz_parser::{
-    byte_offset_to_line_column, config, input::concrete::ParseInstructionsError, ParseErrorInner,
+    byte_offset_to_line_column, config,
+    input::{
+        concrete::{LimitKind, ParseInstructionsError},
+        preprocess::PreprocessError,
+    },
+    ParseErrorInner,
 };
+use naviz_renderer::error::RendererError;
 
 /// A [Result][std::result::Result] pre-filled with [Error]
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -18,6 +24,20 @@ pub enum Error {
     Import(ImportError),
     /// Error when interacting with the repository.
     Repository(RepositoryError, ConfigFormat),
+    /// Error while saving a snapshot of the current frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    Snapshot(String),
+    /// Error while setting up the renderer.
+    Renderer(RendererSetupError),
+}
+
+/// An error which occurred while setting up the renderer
+#[derive(Debug)]
+pub enum RendererSetupError {
+    /// No [RenderState][eframe::egui_wgpu::RenderState] is available
+    NoRenderState,
+    /// The [Renderer][naviz_renderer::renderer::Renderer] could not be created
+    Renderer(RendererError),
 }
 
 /// An Error occurred while opening one of the input-types
@@ -25,15 +45,17 @@ pub enum Error {
 pub enum InputType {
     Instruction(InputError),
     Config(ConfigFormat, ConfigError),
+    Project(serde_json::Error),
 }
 
 /// An error to do with the instruction input
 #[derive(Debug)]
 pub enum InputError {
     UTF8(Utf8Error),
+    Include(PreprocessError<std::io::Error>),
     Lex(ParseErrorInner, Option<ErrorLocation>),
     Parse(ParseErrorInner, Option<ErrorLocation>),
-    Convert(ParseInstructionsError),
+    Convert(ParseInstructionsError, Option<ErrorLocation>),
 }
 
 /// A config-format
@@ -49,13 +71,14 @@ pub enum ConfigError {
     UTF8(Utf8Error),
     Lex(ParseErrorInner, Option<ErrorLocation>),
     Parse(ParseErrorInner, Option<ErrorLocation>),
-    Convert(config::error::Error),
+    Convert(config::error::Error, Option<ErrorLocation>),
 }
 /// An error to do with the [Repository][naviz_repository::Repository]
 #[derive(Debug)]
 pub enum RepositoryError {
     Load(RepositoryLoadSource, naviz_repository::error::Error),
     Open(naviz_repository::error::Error),
+    Verify(String, naviz_repository::error::Error),
     Import(naviz_repository::error::Error, Option<ErrorLocation>),
     Remove(naviz_repository::error::Error),
     Search,
@@ -77,11 +100,14 @@ impl Error {
             Self::FileOpen(InputType::Config(ConfigFormat::Machine, _)) => "Invalid machine definition",
             Self::FileOpen(InputType::Config(ConfigFormat::Style, _)) => "Invalid style definition",
             Self::FileOpen(InputType::Instruction(_)) => "Invalid instructions",
+            Self::FileOpen(InputType::Project(_)) => "Invalid project file",
             Self::Import(_) => "Failed to import",
             Self::Repository(RepositoryError::Search, ConfigFormat::Machine) => "Machine not found",
             Self::Repository(RepositoryError::Search, ConfigFormat::Style) => "Style not found",
             Self::Repository(RepositoryError::Open(_), ConfigFormat::Machine) => "Invalid machine in repository",
             Self::Repository(RepositoryError::Open(_), ConfigFormat::Style) => "Invalid style in repository",
+            Self::Repository(RepositoryError::Verify(_, _), ConfigFormat::Machine) => "Invalid machine in user-dir",
+            Self::Repository(RepositoryError::Verify(_, _), ConfigFormat::Style) => "Invalid style in user-dir",
             Self::Repository(RepositoryError::Load(RepositoryLoadSource::Bundled, _), ConfigFormat::Machine) => "Failed to load bundled machines",
             Self::Repository(RepositoryError::Load(RepositoryLoadSource::Bundled, _), ConfigFormat::Style) => "Failed to load bundled styles",
             Self::Repository(RepositoryError::Load(RepositoryLoadSource::UserDir, _), ConfigFormat::Machine) => "Failed to load machines from user-dir",
@@ -90,6 +116,9 @@ impl Error {
             Self::Repository(RepositoryError::Import(_, _), ConfigFormat::Style) => "Failed to import style to user-dir",
             Self::Repository(RepositoryError::Remove(_), ConfigFormat::Machine) => "Failed to remove machine from user-dir",
             Self::Repository(RepositoryError::Remove(_), ConfigFormat::Style) => "Failed to remove style from user-dir",
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Snapshot(_) => "Failed to save image",
+            Self::Renderer(_) => "Failed to set up renderer",
         }
     }
 
@@ -101,10 +130,32 @@ impl Error {
                 format_config_error(format, config_error)
             }
             Self::FileOpen(InputType::Instruction(input_error)) => format_input_error(input_error),
+            Self::FileOpen(InputType::Project(json_error)) => format!(
+                "Failed to load the project file.\n\n\
+                Error: {json_error}\n\n\
+                Please ensure the file is a valid JSON project file."
+            ),
             Self::Import(import_error) => format_import_error(import_error),
             Self::Repository(repo_error, config_format) => {
                 format_repository_error(repo_error, config_format)
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Snapshot(error) => format!(
+                "Failed to save the current frame as an image.\n\n\
+                Error: {error}\n\n\
+                Please verify that the target location is writable."
+            ),
+            Self::Renderer(RendererSetupError::NoRenderState) => {
+                "No wgpu render state was found.\n\n\
+                The visualization can not be displayed."
+                    .to_string()
+            }
+            Self::Renderer(RendererSetupError::Renderer(error)) => format!(
+                "Failed to create the renderer.\n\n\
+                Error: {error}\n\n\
+                The visualization can not be displayed. \
+                Please verify that your graphics adapter is supported."
+            ),
         }
     }
 }
@@ -161,9 +212,13 @@ fn format_config_error(format: &ConfigFormat, error: &ConfigError) -> String {
                 format_parse_error_context(parse_error)
             )
         }
-        ConfigError::Convert(config_error) => {
+        ConfigError::Convert(config_error, location) => {
+            let location_info = location
+                .as_ref()
+                .map(|loc| format!(" at line {}, column {}", loc.line, loc.column))
+                .unwrap_or_default();
             format!(
-                "Failed to process {file_type} file content.\n\n\
+                "Failed to process {file_type} file content{location_info}.\n\n\
                 {config_error}\n\n\
                 The file syntax is correct, but the content validation failed.\n\
                 Please check that all required fields are present and have valid values."
@@ -182,10 +237,24 @@ fn format_input_error(error: &InputError) -> String {
                 Please ensure the file is saved with proper UTF-8 encoding."
             )
         }
+        InputError::Include(include_error) => {
+            format!(
+                "Failed to include a file into the instruction file.\n\n\
+                Error details: {include_error}\n\n\
+                Included paths are resolved relative to the directory of the opened file."
+            )
+        }
         InputError::Lex(parse_error, location) => {
             let location_info = location
                 .as_ref()
-                .map(|loc| format!(" at line {}, column {}", loc.line, loc.column))
+                .map(|loc| {
+                    format!(
+                        " at line {}, column {}{}",
+                        loc.line,
+                        loc.column,
+                        loc.file_info()
+                    )
+                })
                 .unwrap_or_default();
             format!(
                 "Failed to parse instruction file due to invalid syntax{}.\n\n\
@@ -203,7 +272,14 @@ fn format_input_error(error: &InputError) -> String {
         InputError::Parse(parse_error, location) => {
             let location_info = location
                 .as_ref()
-                .map(|loc| format!(" at line {}, column {}", loc.line, loc.column))
+                .map(|loc| {
+                    format!(
+                        " at line {}, column {}{}",
+                        loc.line,
+                        loc.column,
+                        loc.file_info()
+                    )
+                })
                 .unwrap_or_default();
             format!(
                 "Failed to parse instruction file structure{}.\n\n\
@@ -218,12 +294,33 @@ fn format_input_error(error: &InputError) -> String {
                 format_parse_error_context(parse_error)
             )
         }
-        InputError::Convert(convert_error) => format_parse_instructions_error(convert_error),
+        InputError::Convert(convert_error, location) => {
+            let location_info = location
+                .as_ref()
+                .map(|loc| {
+                    format!(
+                        "Error at line {}, column {}{}.\n\n",
+                        loc.line,
+                        loc.column,
+                        loc.file_info()
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                "{location_info}{}",
+                format_parse_instructions_error(convert_error)
+            )
+        }
     }
 }
 
 /// Format parse error context information in a user-friendly way
 fn format_parse_error_context(error: &ParseErrorInner) -> String {
+    // Specific causes (e.g., unbalanced groups) are more helpful than the generic context
+    if let Some(cause) = error.cause() {
+        return cause.to_string();
+    }
+
     // Extract context information from winnow's ContextError
     let context_info: Vec<String> = error.context().map(|ctx| ctx.to_string()).collect();
 
@@ -307,6 +404,13 @@ fn format_repository_error(error: &RepositoryError, config_format: &ConfigFormat
                 The {item_type} file may be corrupted or in an incompatible format."
             )
         }
+        RepositoryError::Verify(id, repo_error) => {
+            format!(
+                "The {item_type} \"{id}\" in the user directory cannot be loaded.\n\n\
+                Error: {repo_error:?}\n\n\
+                Please fix or remove the {item_type} file."
+            )
+        }
         RepositoryError::Import(repo_error, location) => {
             use naviz_repository::error::Error as RErr;
             match repo_error {
@@ -368,12 +472,18 @@ fn format_repository_error(error: &RepositoryError, config_format: &ConfigFormat
                         format_expected_hint(inner)
                     )
                 }
-                RErr::ConfigReadError(cfg_err) => format!(
-                    "Imported {item_type} file has invalid semantic content.\n\n\
-                    Validation error: {cfg_err}\n\
-                    The syntax was correct but values failed validation.\n\
-                    Check that required fields exist and values are within allowed ranges."
-                ),
+                RErr::ConfigReadError(cfg_err) => {
+                    let loc_info = location
+                        .as_ref()
+                        .map(|l| format!(" (line {}, column {})", l.line, l.column))
+                        .unwrap_or_else(String::new);
+                    format!(
+                        "Imported {item_type} file has invalid semantic content{loc_info}.\n\n\
+                        Validation error: {cfg_err}\n\
+                        The syntax was correct but values failed validation.\n\
+                        Check that required fields exist and values are within allowed ranges."
+                    )
+                }
                 RErr::IdError => format!(
                     "Imported {item_type} contains invalid or duplicate identifiers.\n\n\
                     Ensure all identifiers are unique and follow naming rules."
@@ -381,6 +491,11 @@ fn format_repository_error(error: &RepositoryError, config_format: &ConfigFormat
                 RErr::NotRemovableError => format!(
                     "Internal error: attempted to treat a non-removable {item_type} as removable during import."
                 ),
+                #[cfg(not(target_arch = "wasm32"))]
+                RErr::WatchError(e) => format!(
+                    "Failed to watch the user directory for changes to {item_type} files.\n\n\
+                    Error: {e}"
+                ),
              }
         }
         RepositoryError::Remove(repo_error) => format!(
@@ -419,6 +534,9 @@ pub struct ErrorLocation {
     pub column: usize,
     /// Byte offset in the original text
     pub offset: usize,
+    /// The included file containing the location,
+    /// or [None] if the location is in the opened file itself
+    pub file: Option<String>,
 }
 
 impl ErrorLocation {
@@ -429,8 +547,24 @@ impl ErrorLocation {
             line,
             column,
             offset,
+            file: None,
         }
     }
+
+    /// Sets the included file containing this location
+    pub fn in_file(mut self, file: Option<&str>) -> Self {
+        self.file = file.map(str::to_string);
+        self
+    }
+
+    /// Describes the included file containing this location (if any)
+    /// to be appended to the line and column
+    fn file_info(&self) -> String {
+        self.file
+            .as_ref()
+            .map(|file| format!(" of included file \"{file}\""))
+            .unwrap_or_default()
+    }
 }
 
 /// Format a parse instructions error with detailed messages
@@ -500,8 +634,44 @@ fn format_parse_instructions_error(err: &ParseInstructionsError) -> String {
             let list = name.join(", ");
             format!(
                 "Superfluous time specifier for setup instruction(s): {list}.\n\n\
-                 Setup instructions must not be preceded by a time marker.\n\
-                 Remove the '@<time>' prefix."
+                 Setup instructions may only be preceded by an absolute time marker.\n\
+                 Remove the relative time prefix or use an absolute '@<time>'."
+            )
+        }
+        ParseInstructionsError::UnknownNamedArgument { name, argument } => format!(
+            "Unknown named argument '{argument}' of '{name}'.\n\n\
+             The instruction does not accept this named argument.\n\
+             Timed instructions accept 'dur=<time>' to override their duration."
+        ),
+        ParseInstructionsError::DuplicateAtomId { id } => format!(
+            "Duplicate atom id: '{id}'.\n\n\
+             Multiple atoms were declared with the same id.\n\
+             Give each atom a unique id."
+        ),
+        ParseInstructionsError::DuplicateAlias { name } => format!(
+            "Duplicate alias: '{name}'.\n\n\
+             The alias was defined multiple times or has the same id as an atom.\n\
+             Give each alias a unique name."
+        ),
+        ParseInstructionsError::UndefinedAlias { name } => format!(
+            "Alias '{name}' is used before it is defined.\n\n\
+             Aliases have to be defined before their first use.\n\
+             Move the '#alias' directive before the instructions using it."
+        ),
+        ParseInstructionsError::UnknownAliasMember { name, member } => format!(
+            "Unknown member '{member}' of alias '{name}'.\n\n\
+             Members of an alias have to be previously declared atoms or aliases.\n\
+             Declare the member before defining the alias."
+        ),
+        ParseInstructionsError::LimitExceeded { kind, limit } => {
+            let kind = match kind {
+                LimitKind::Atoms => "atoms",
+                LimitKind::Instructions => "instructions",
+            };
+            format!(
+                "Too many {kind}: the limit is {limit}.\n\n\
+                 The input exceeds the maximum number of {kind} allowed.\n\
+                 Split the input into smaller parts."
             )
         }
     }
@@ -556,4 +726,60 @@ mod parse_instructions_error_format_tests {
         let msg = format_parse_instructions_error(&e);
         assert!(msg.contains("Superfluous time"));
     }
+
+    #[test]
+    fn duplicate_atom_id() {
+        let e = ParseInstructionsError::DuplicateAtomId { id: "q0".into() };
+        let msg = format_parse_instructions_error(&e);
+        assert!(msg.contains("Duplicate atom id"));
+        assert!(msg.contains("q0"));
+    }
+
+    #[test]
+    fn limit_exceeded() {
+        let e = ParseInstructionsError::LimitExceeded {
+            kind: LimitKind::Atoms,
+            limit: 10,
+        };
+        let msg = format_parse_instructions_error(&e);
+        assert!(msg.contains("Too many atoms"));
+        assert!(msg.contains("10"));
+    }
+}
+
+#[cfg(test)]
+mod input_error_format_tests {
+    use super::*;
+
+    #[test]
+    fn convert_with_location() {
+        let text = "atom (0, 0) q0\n@0 unknown q0\n";
+        let error = InputError::Convert(
+            ParseInstructionsError::UnknownInstruction {
+                name: "unknown".to_string(),
+            },
+            Some(ErrorLocation::from_offset(text, 15)),
+        );
+        let msg = format_input_error(&error);
+        assert!(msg.contains("at line 2, column 1"));
+        assert!(msg.contains("Unknown instruction 'unknown'"));
+    }
+}
+
+#[cfg(test)]
+mod config_error_format_tests {
+    use super::*;
+    use naviz_parser::config::error::ErrorKind;
+
+    #[test]
+    fn convert_with_location() {
+        let text = "name: \"Test\"\n\ntime {\n\tstore: 1\n}\n";
+        let error = ConfigError::Convert(
+            ErrorKind::MissingField("load").into(),
+            Some(ErrorLocation::from_offset(text, 14)),
+        );
+        let msg = format_config_error(&ConfigFormat::Machine, &error);
+        assert!(msg.contains("at line 3, column 1"));
+        assert!(msg.contains("missing field: load"));
+    }
 }
@@ -8,14 +8,63 @@ use std::{
 };
 
 use egui::{Align2, Context, DragValue, Grid, Id, Layout, ProgressBar, Spinner, Window};
-use naviz_video::VideoProgress;
+use naviz_renderer::quality::RenderQuality;
+use naviz_video::{FrameProgress, VideoProgress, MOTION_BLUR_SAMPLES, TRANSPARENT_FORMATS};
+
+/// The kind of output to export to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ExportTarget {
+    /// A video-file, encoded using `ffmpeg`
+    #[default]
+    Video,
+    /// A directory of PNG-frames
+    PngSequence,
+}
+
+impl ExportTarget {
+    /// All available [ExportTarget]s
+    const ALL: [Self; 2] = [Self::Video, Self::PngSequence];
+
+    /// The name to display for this [ExportTarget]
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Video => "Video",
+            Self::PngSequence => "PNG sequence",
+        }
+    }
+}
+
+/// Options applied to the rendered frames of an export
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct FrameOptions {
+    /// Whether to export with a transparent background
+    pub transparent: bool,
+    /// Whether to burn the time and a progress-bar into the frames
+    pub overlay: bool,
+    /// The number of sub-frames to average per frame, if motion blur is enabled
+    pub motion_blur: Option<u32>,
+    /// The quality to render the frames with
+    pub quality: RenderQuality,
+}
 
 /// Settings-Dialog for the export
 pub struct ExportSettings {
+    /// The kind of output to export to
+    target: ExportTarget,
     /// Resolution to render at
     resolution: (u32, u32),
     /// FPS to render at
     fps: u32,
+    /// Whether to export with a transparent background
+    transparent: bool,
+    /// Whether to burn the time and a progress-bar into the frames
+    overlay: bool,
+    /// Whether to blur moving atoms
+    motion_blur: bool,
+    /// The number of sub-frames to average per frame for motion blur
+    motion_blur_samples: u32,
+    /// The quality to render the frames with
+    quality: RenderQuality,
     /// Whether the export settings dialog is shown
     show: bool,
 }
@@ -23,8 +72,14 @@ pub struct ExportSettings {
 impl Default for ExportSettings {
     fn default() -> Self {
         Self {
+            target: ExportTarget::default(),
             resolution: (1920, 1080),
             fps: 30,
+            transparent: false,
+            overlay: false,
+            motion_blur: false,
+            motion_blur_samples: 4,
+            quality: RenderQuality::default(),
             show: false,
         }
     }
@@ -51,6 +106,14 @@ impl ExportSettings {
                     let w = Grid::new("export_dialog")
                         .num_columns(2)
                         .show(ui, |ui| {
+                            ui.label("Format:");
+                            ui.horizontal(|ui| {
+                                for target in ExportTarget::ALL {
+                                    ui.radio_value(&mut self.target, target, target.label());
+                                }
+                            });
+                            ui.end_row();
+
                             ui.label("Resolution:");
                             ui.horizontal(|ui| {
                                 ui.add(DragValue::new(&mut self.resolution.0));
@@ -62,6 +125,48 @@ impl ExportSettings {
                             ui.label("FPS:");
                             ui.add(DragValue::new(&mut self.fps));
                             ui.end_row();
+
+                            ui.label("Background:");
+                            ui.checkbox(&mut self.transparent, "Transparent")
+                                .on_hover_text(match self.target {
+                                    ExportTarget::Video => format!(
+                                        "Only supported for {}",
+                                        TRANSPARENT_FORMATS.join(", ")
+                                    ),
+                                    ExportTarget::PngSequence => {
+                                        "Supported for PNG sequences".to_string()
+                                    }
+                                });
+                            ui.end_row();
+
+                            ui.label("Overlay:");
+                            ui.checkbox(&mut self.overlay, "Time and progress")
+                                .on_hover_text("Burn the time and a progress-bar into the frames");
+                            ui.end_row();
+
+                            ui.label("Motion blur:");
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.motion_blur, "Enabled")
+                                    .on_hover_text("Smooth fast moves at low frame rates");
+                                ui.add_enabled(
+                                    self.motion_blur,
+                                    DragValue::new(&mut self.motion_blur_samples)
+                                        .range(MOTION_BLUR_SAMPLES)
+                                        .suffix(" sub-frames"),
+                                );
+                            });
+                            ui.end_row();
+
+                            ui.label("Quality:");
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut self.quality, RenderQuality::Smooth, "Smooth");
+                                ui.radio_value(&mut self.quality, RenderQuality::Crisp, "Crisp")
+                                    .on_hover_text(
+                                        "Snap lines and text to whole pixels \
+                                         (e.g., for publication figures)",
+                                    );
+                            });
+                            ui.end_row();
                         })
                         .response
                         .rect
@@ -83,6 +188,12 @@ impl ExportSettings {
         ok_clicked
     }
 
+    /// Gets the currently selected [ExportTarget].
+    /// Note: Changes with user-input when shown.
+    pub fn target(&self) -> ExportTarget {
+        self.target
+    }
+
     /// Gets the currently selected resolution.
     /// Note: Changes with user-input when shown.
     pub fn resolution(&self) -> (u32, u32) {
@@ -94,6 +205,17 @@ impl ExportSettings {
     pub fn fps(&self) -> u32 {
         self.fps
     }
+
+    /// Gets the selected [FrameOptions].
+    /// Note: Changes with user-input when shown.
+    pub fn frame_options(&self) -> FrameOptions {
+        FrameOptions {
+            transparent: self.transparent,
+            overlay: self.overlay,
+            motion_blur: self.motion_blur.then_some(self.motion_blur_samples),
+            quality: self.quality,
+        }
+    }
 }
 
 /// Container for multiple [ExportProgress]es
@@ -134,9 +256,13 @@ enum ExportProgressState {
         render: (f32, f32),
         /// The encoding-progress (`current_time`, `duration`)
         encode: (f32, f32),
+        /// The rendered frames, if any were reported yet
+        frames: Option<FrameProgress>,
     },
     /// The video exporter is finished with the specified [ExitStatus]
     Done(ExitStatus),
+    /// The image-sequence exporter is finished (with the error, if one occurred)
+    SequenceDone(Result<(), String>),
     /// Channel dropped during export (while not [Done][ExportProgressState::Done]);
     /// Unknown what the current status is.
     /// Should not happen in normal operation.
@@ -149,12 +275,13 @@ impl ExportProgressState {
     /// If state was not [ExportProgressState::Working],
     /// will change change state and set max `render`-time to `max`.
     fn set_encode(&mut self, current: f32, max: f32) {
-        if let Self::Working { encode, render: _ } = self {
+        if let Self::Working { encode, .. } = self {
             *encode = (current, max);
         } else {
             *self = Self::Working {
                 encode: (current, max),
                 render: (0., max),
+                frames: None,
             };
         }
     }
@@ -164,15 +291,26 @@ impl ExportProgressState {
     /// If state was not [ExportProgressState::Working],
     /// will change change state and set max `encode`-time to `max`.
     fn set_render(&mut self, current: f32, max: f32) {
-        if let Self::Working { encode: _, render } = self {
+        if let Self::Working { render, .. } = self {
             *render = (current, max);
         } else {
             *self = Self::Working {
                 encode: (0., max),
                 render: (current, max),
+                frames: None,
             };
         }
     }
+
+    /// Received a `frames`-progress.
+    /// Updates the state to contain the progress.
+    /// Ignored if state is not [ExportProgressState::Working],
+    /// as a [VideoProgress::Render] is always sent first.
+    fn set_frames(&mut self, progress: FrameProgress) {
+        if let Self::Working { frames, .. } = self {
+            *frames = Some(progress);
+        }
+    }
 }
 
 /// A dialog that displays the export progress
@@ -207,23 +345,34 @@ impl ExportProgress {
     /// This function returns `true` when the user closed the window.
     /// This [ExportProgress] may then be disposed.
     fn draw(&mut self, ctx: &Context) -> bool {
-        // Get next event
-        match self.receiver.try_recv() {
-            Ok(VideoProgress::Encode(cur, max)) => {
-                self.state.set_encode(cur, max);
-            }
-            Ok(VideoProgress::Render(cur, max)) => {
-                self.state.set_render(cur, max);
-            }
-            Ok(VideoProgress::Done(status)) => {
-                self.state = ExportProgressState::Done(status);
-            }
-            Err(TryRecvError::Empty) => {}
-            Err(TryRecvError::Disconnected) => match self.state {
-                ExportProgressState::Done(_) => {}
-                _ => self.state = ExportProgressState::Unknown,
-            },
-        };
+        // Get all pending events
+        loop {
+            match self.receiver.try_recv() {
+                Ok(VideoProgress::Encode(cur, max)) => {
+                    self.state.set_encode(cur, max);
+                }
+                Ok(VideoProgress::Render(cur, max)) => {
+                    self.state.set_render(cur, max);
+                }
+                Ok(VideoProgress::Frames(progress)) => {
+                    self.state.set_frames(progress);
+                }
+                Ok(VideoProgress::Done(status)) => {
+                    self.state = ExportProgressState::Done(status);
+                }
+                Ok(VideoProgress::SequenceDone(result)) => {
+                    self.state = ExportProgressState::SequenceDone(result);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    match self.state {
+                        ExportProgressState::Done(_) | ExportProgressState::SequenceDone(_) => {}
+                        _ => self.state = ExportProgressState::Unknown,
+                    }
+                    break;
+                }
+            };
+        }
 
         // Draw window based on state
         let window = Window::new("Export Progress")
@@ -231,7 +380,7 @@ impl ExportProgress {
             .resizable(false)
             .max_width(480.)
             .collapsible(false);
-        match self.state {
+        match &self.state {
             ExportProgressState::Creating => {
                 window.show(ctx, |ui| {
                     ui.allocate_ui([128., 98.].into(), |ui| {
@@ -240,7 +389,11 @@ impl ExportProgress {
                 });
                 false
             }
-            ExportProgressState::Working { encode, render } => {
+            ExportProgressState::Working {
+                encode,
+                render,
+                frames,
+            } => {
                 window.show(ctx, |ui| {
                     Grid::new(&self.grid_id).show(ui, |ui| {
                         ui.label("Render:");
@@ -251,6 +404,21 @@ impl ExportProgress {
                         ui.add(ProgressBar::new(encode.0 / encode.1).show_percentage());
                         ui.label(format!("{:.1}", encode.0));
                         ui.end_row();
+                        if let Some(frames) = frames {
+                            ui.label("Frames:");
+                            ui.label(format!("{} / {}", frames.done, frames.total));
+                            ui.label(match frames.fps() {
+                                Some(fps) => format!("{fps:.1} fps"),
+                                None => "- fps".to_string(),
+                            });
+                            ui.end_row();
+                            ui.label("ETA:");
+                            ui.label(match frames.eta() {
+                                Some(eta) => format_duration(eta.as_secs()),
+                                None => "-".to_string(),
+                            });
+                            ui.end_row();
+                        }
                     });
                 });
                 false
@@ -270,6 +438,14 @@ impl ExportProgress {
                 });
                 !open
             }
+            ExportProgressState::SequenceDone(result) => {
+                let mut open = true;
+                window.open(&mut open).show(ctx, |ui| match result {
+                    Ok(()) => ui.label("Finished exporting!"),
+                    Err(e) => ui.label(format!("Error during export! {e}")),
+                });
+                !open
+            }
             ExportProgressState::Unknown => {
                 let mut open = true;
                 window.open(&mut open).show(ctx, |ui| {
@@ -280,3 +456,13 @@ impl ExportProgress {
         }
     }
 }
+
+/// Formats the passed duration in `seconds` as `h:mm:ss` (or `m:ss` if below one hour)
+fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
@@ -4,6 +4,7 @@ pub enum FileType {
     Instructions,
     Machine,
     Style,
+    Project,
 }
 
 /// Something which can be used to filter files by extension
@@ -20,6 +21,7 @@ impl FileFilter for FileType {
             FileType::Instructions => "NAViz instructions",
             FileType::Machine => "NAViz machine",
             FileType::Style => "NAViz style",
+            FileType::Project => "NAViz project",
         }
     }
     fn extensions(&self) -> &'static [&'static str] {
@@ -27,6 +29,7 @@ impl FileFilter for FileType {
             FileType::Instructions => &["naviz"],
             FileType::Machine => &["namachine"],
             FileType::Style => &["nastyle"],
+            FileType::Project => &["json"],
         }
     }
 }
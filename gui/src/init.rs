@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 /// Something that is either specified by an `id` from the [Repository]
 /// or manually loaded.
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum IdOrManual<ID, MAN> {
     Id(ID),
     Manual(MAN),
@@ -48,6 +48,7 @@ impl<ID: Deref, MAN: Deref> IdOrManual<ID, MAN> {
 
 type IdOrManualInit<'a> = IdOrManual<&'a str, &'a [u8]>;
 type IdOrManualPersistence = IdOrManual<String, Arc<[u8]>>;
+type IdOrManualProject = IdOrManual<String, String>;
 
 /// Options to start the app with.
 /// Leave [None] to keep unset or set to [Some] value to initialize with the value.
@@ -102,3 +103,102 @@ impl<'a> From<&'a Persistence> for InitOptions<'a> {
         }
     }
 }
+
+/// A project file which bundles the machine, style, and input to open.
+/// Manual machines, styles, and inputs are stored inline as their text content.
+/// Can be (de)serialized from/to JSON and converted to [InitOptions].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct Project {
+    /// The machine to load
+    pub machine: Option<IdOrManualProject>,
+    /// The style to load
+    pub style: Option<IdOrManualProject>,
+    /// The visualization input to load
+    pub input: Option<ProjectInput>,
+}
+
+/// The visualization input of a [Project]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProjectInput {
+    /// The [ImportOptions] if the content needs to be imported
+    #[serde(default)]
+    pub import: Option<ImportOptions>,
+    /// The content of the input
+    pub content: String,
+}
+
+impl Project {
+    /// Loads a [Project] from its JSON representation
+    pub fn from_json(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+
+    /// Serializes this [Project] to JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl<'a> From<&'a Project> for InitOptions<'a> {
+    fn from(value: &'a Project) -> Self {
+        Self {
+            machine: value
+                .machine
+                .as_ref()
+                .map(|m| m.as_ref().map(String::as_str, String::as_bytes)),
+            style: value
+                .style
+                .as_ref()
+                .map(|s| s.as_ref().map(String::as_str, String::as_bytes)),
+            input: value
+                .input
+                .as_ref()
+                .map(|i| (i.import.clone(), i.content.as_bytes())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An example [Project] using all kinds of values
+    fn example_project() -> Project {
+        Project {
+            machine: Some(IdOrManual::Id("example".to_string())),
+            style: Some(IdOrManual::Manual("name: \"Style\"".to_string())),
+            input: Some(ProjectInput {
+                import: None,
+                content: "#target example\n".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn project_json_roundtrip() {
+        let project = example_project();
+        let json = project.to_json().expect("Failed to serialize project");
+        let deserialized =
+            Project::from_json(json.as_bytes()).expect("Failed to deserialize project");
+        assert_eq!(deserialized, project);
+    }
+
+    #[test]
+    fn project_missing_fields() {
+        let project = Project::from_json(b"{}").expect("Failed to deserialize empty project");
+        assert_eq!(project, Project::default());
+    }
+
+    #[test]
+    fn project_into_init_options() {
+        let project = example_project();
+        let init: InitOptions<'_> = (&project).into();
+        assert_eq!(init.machine, Some(IdOrManual::Id("example")));
+        assert_eq!(
+            init.style,
+            Some(IdOrManual::Manual(b"name: \"Style\"".as_slice()))
+        );
+        assert_eq!(init.input, Some((None, b"#target example\n".as_slice())));
+    }
+}
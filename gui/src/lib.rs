@@ -16,5 +16,7 @@ mod import;
 pub mod init;
 mod menu;
 mod progress_bar;
+mod shortcuts;
 mod util;
+mod zoom;
 pub use app::App;
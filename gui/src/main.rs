@@ -6,6 +6,8 @@ use std::sync::Arc;
 
 #[cfg(target_arch = "wasm32")]
 use eframe::egui_wgpu::{WgpuSetup, WgpuSetupCreateNew};
+#[cfg(not(target_arch = "wasm32"))]
+use naviz_gui::init::Project;
 use naviz_gui::App;
 #[cfg(target_arch = "wasm32")]
 use wgpu::{Adapter, DeviceDescriptor};
@@ -15,6 +17,8 @@ use wgpu::{Adapter, DeviceDescriptor};
 fn main() -> eframe::Result {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let project = project_from_args();
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title("NAViz")
@@ -32,15 +36,37 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "naviz",
         native_options,
-        Box::new(|cc| {
+        Box::new(move |cc| {
             Ok(Box::new(App::new_with_init_and_persistence(
                 cc,
-                Default::default(),
+                project.as_ref().map(Into::into).unwrap_or_default(),
             )))
         }),
     )
 }
 
+/// Loads the [Project] passed using `--project <path>`, if any.
+/// Failing to load the project is logged and ignored.
+#[cfg(not(target_arch = "wasm32"))]
+fn project_from_args() -> Option<Project> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .by_ref()
+        .find(|a| a == "--project")
+        .and_then(|_| args.next())?;
+    match std::fs::read(&path).map(|data| Project::from_json(&data)) {
+        Ok(Ok(project)) => Some(project),
+        Ok(Err(e)) => {
+            log::error!("Failed to parse project {path}: {e}");
+            None
+        }
+        Err(e) => {
+            log::error!("Failed to read project {path}: {e}");
+            None
+        }
+    }
+}
+
 // When compiling to web using trunk:
 #[cfg(target_arch = "wasm32")]
 fn main() {
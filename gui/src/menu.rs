@@ -1,9 +1,7 @@
 //! [MenuBar] to show a menu on the top.
 
-#[cfg(not(target_arch = "wasm32"))]
-use std::path::PathBuf;
 use std::{
-    path::Path,
+    path::{Path, PathBuf},
     sync::{
         mpsc::{channel, Receiver, Sender},
         Arc,
@@ -18,6 +16,7 @@ use rfd::FileHandle;
 
 use crate::{
     app::AppState,
+    current_machine::CompatibilityReport,
     drawable::Drawable,
     error::{Error, Result},
     errors::{ErrorEmitter, Errors},
@@ -32,6 +31,9 @@ type SendReceivePair<T> = (Sender<T>, Receiver<T>);
 pub struct MenuBar {
     /// Internal channel for async events
     event_channel: SendReceivePair<MenuEvent>,
+    /// Channel for the results of snapshots rendered in the background
+    #[cfg(not(target_arch = "wasm32"))]
+    snapshot_channel: SendReceivePair<Result<()>>,
     /// Whether to draw the about-window
     about_open: bool,
     /// Export interaction handling (menu, config, progress)
@@ -46,7 +48,8 @@ pub struct MenuBar {
 /// An event which can be triggered by asynchronous actions like the user choosing a file
 enum MenuEvent {
     /// A file of the specified [FileType] with the specified content was opened
-    FileOpen(FileType, Arc<[u8]>),
+    /// (from the specified path, if known)
+    FileOpen(FileType, Arc<[u8]>, Option<PathBuf>),
     /// A file should be imported
     FileImport(ImportOptions, Arc<[u8]>),
     /// The machine at the specified `path` should be imported
@@ -55,12 +58,20 @@ enum MenuEvent {
     /// The style at the specified `path` should be imported
     #[cfg(not(target_arch = "wasm32"))]
     ImportStyle(PathBuf),
+    /// A snapshot of the visualization at the specified `time`
+    /// should be saved to the specified `path`
+    #[cfg(not(target_arch = "wasm32"))]
+    Snapshot(f32, PathBuf),
 }
 
 impl MenuEvent {
     /// Creates a [MenuEvent::FileOpen] for [MenuBar::choose_file]
     async fn file_open(file_type: FileType, handle: FileHandle) -> Self {
-        Self::FileOpen(file_type, handle.read().await.into())
+        #[cfg(not(target_arch = "wasm32"))]
+        let path = Some(handle.path().to_owned());
+        #[cfg(target_arch = "wasm32")]
+        let path = None;
+        Self::FileOpen(file_type, handle.read().await.into(), path)
     }
 
     /// Creates a [MenuEvent::ImportMachine] or [MenuEvent::ImportStyle] for [MenuBar::choose_file]
@@ -68,6 +79,7 @@ impl MenuEvent {
     async fn file_import(file_type: FileType, handle: FileHandle) -> Self {
         match file_type {
             FileType::Instructions => panic!("Unable to import instructions"),
+            FileType::Project => panic!("Unable to import projects"),
             FileType::Machine => Self::ImportMachine(handle.path().to_owned()),
             FileType::Style => Self::ImportStyle(handle.path().to_owned()),
         }
@@ -79,6 +91,8 @@ impl MenuBar {
     pub fn new() -> Self {
         Self {
             event_channel: channel(),
+            #[cfg(not(target_arch = "wasm32"))]
+            snapshot_channel: channel(),
             about_open: false,
             export_menu: ExportMenu::new(),
             current_import_options: None,
@@ -89,20 +103,28 @@ impl MenuBar {
     /// Handles the file-types defined in [FileType]
     /// and file-types defined in [IMPORT_FORMATS].
     /// Extension-collisions will simply pick the first match.
+    /// Includes are resolved relative to `dir` (see [AppState::open_in]).
     fn load_file_by_extension(
         &mut self,
         name: &str,
         contents: Arc<[u8]>,
+        dir: Option<&Path>,
         state: &mut AppState,
+        errors: &mut Errors,
     ) -> Result<()> {
         // Extract extension
         if let Some(extension) = Path::new(name).extension() {
             let extension = &*extension.to_string_lossy();
             // Internal formats
-            for file_type in [FileType::Instructions, FileType::Machine, FileType::Style] {
+            for file_type in [
+                FileType::Instructions,
+                FileType::Machine,
+                FileType::Style,
+                FileType::Project,
+            ] {
                 // File extension is known?
                 if file_type.extensions().contains(&extension) {
-                    return state.open_by_type(file_type, &contents);
+                    return state.open_by_type(file_type, &contents, dir, errors);
                 }
             }
             // Imported formats
@@ -120,10 +142,16 @@ impl MenuBar {
 
     /// Handles any files dropped onto the application.
     /// Will use [Self::load_file_by_extension] to load the file.
-    fn handle_file_drop(&mut self, ctx: &egui::Context, state: &mut AppState) -> Result<()> {
+    fn handle_file_drop(
+        &mut self,
+        ctx: &egui::Context,
+        state: &mut AppState,
+        errors: &mut Errors,
+    ) -> Result<()> {
         for file in ctx.input_mut(|input| std::mem::take(&mut input.raw.dropped_files)) {
             if let Some(contents) = file.bytes {
-                self.load_file_by_extension(&file.name, contents, state)?;
+                let dir = file.path.as_deref().and_then(Path::parent);
+                self.load_file_by_extension(&file.name, contents, dir, state, errors)?;
             }
         }
         Ok(())
@@ -154,11 +182,22 @@ impl MenuBar {
                         // A file exists at that path
                         #[allow(clippy::needless_borrows_for_generic_args)] // borrow is needed
                         if let Ok(contents) = std::fs::read(&text) {
-                            self.load_file_by_extension(text, contents.into(), state)
-                                .pipe_void(errors);
+                            self.load_file_by_extension(
+                                text,
+                                contents.into(),
+                                Path::new(text).parent(),
+                                state,
+                                errors,
+                            )
+                            .pipe_void(errors);
                         } else {
                             log::error!("Failed to read file");
                         }
+                    } else if text.trim_start().starts_with('{') {
+                        // Pasted a project (instructions never start with a brace)
+                        state
+                            .open_project(text.as_bytes(), None, errors)
+                            .pipe_void(errors);
                     } else {
                         // Pasted text-content directly
                         state.open(text.as_bytes()).pipe_void(errors);
@@ -175,7 +214,12 @@ impl MenuBar {
     fn process_events(&mut self, state: &mut AppState, errors: &mut Errors) {
         while let Ok(event) = self.event_channel.1.try_recv() {
             match event {
-                MenuEvent::FileOpen(file_type, data) => state.open_by_type(file_type, &data),
+                MenuEvent::FileOpen(file_type, data, path) => state.open_by_type(
+                    file_type,
+                    &data,
+                    path.as_deref().and_then(Path::parent),
+                    errors,
+                ),
                 MenuEvent::FileImport(import_options, data) => {
                     state.import(import_options, &data).map_err(Error::Import)
                 }
@@ -183,9 +227,23 @@ impl MenuBar {
                 MenuEvent::ImportMachine(path) => state.import_machine(&path),
                 #[cfg(not(target_arch = "wasm32"))]
                 MenuEvent::ImportStyle(path) => state.import_style(&path),
+                #[cfg(not(target_arch = "wasm32"))]
+                MenuEvent::Snapshot(time, path) => {
+                    state.snapshot(
+                        time,
+                        self.export_menu.resolution(),
+                        path,
+                        self.snapshot_channel.0.clone(),
+                    );
+                    Ok(())
+                }
             }
             .pipe_void(errors);
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        while let Ok(result) = self.snapshot_channel.1.try_recv() {
+            result.pipe_void(errors);
+        }
     }
 
     /// Draw the [MenuBar].
@@ -206,7 +264,7 @@ impl MenuBar {
         self.show_import_dialog(state, future_helper, ctx)
             .pipe_void(errors);
 
-        self.handle_file_drop(ctx, state).pipe_void(errors);
+        self.handle_file_drop(ctx, state, errors).pipe_void(errors);
 
         self.handle_clipboard(ctx, state, errors);
 
@@ -217,6 +275,11 @@ impl MenuBar {
                     ui.close_kind(egui::UiKind::Menu);
                 }
 
+                if ui.button("Open Project").clicked() {
+                    self.choose_file(FileType::Project, future_helper, MenuEvent::file_open);
+                    ui.close_kind(egui::UiKind::Menu);
+                }
+
                 ui.menu_button("Import", |ui| {
                     for import_format in IMPORT_FORMATS {
                         if ui.button(import_format.name()).clicked() {
@@ -229,6 +292,15 @@ impl MenuBar {
                 self.export_menu
                     .draw_button(state.visualization_loaded(), ui);
 
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .add_enabled(state.visualization_loaded(), Button::new("Save Image…"))
+                    .clicked()
+                {
+                    self.save_snapshot(state.time(), future_helper);
+                    ui.close_kind(egui::UiKind::Menu);
+                }
+
                 if !WEB {
                     // Quit-button only on native
                     ui.separator();
@@ -252,6 +324,14 @@ impl MenuBar {
                 if ui.checkbox(&mut force_zen, "Zen-Mode").changed() {
                     state.set_force_zen(force_zen);
                 }
+
+                // Zoom-reset (zooming, panning, and following happen on the canvas)
+                if ui
+                    .add_enabled(!state.content_fitted(), egui::Button::new("Fit to Content"))
+                    .clicked()
+                {
+                    state.fit_content();
+                }
             });
 
             ui.menu_button("Help", |ui| {
@@ -344,6 +424,23 @@ impl MenuBar {
         );
     }
 
+    /// Show the file-saving dialog for a PNG
+    /// and save a snapshot of the visualization at the passed `time` if a file was selected.
+    /// The snapshot is rendered at the resolution selected in the export-settings.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_snapshot(&self, time: f32, future_helper: &FutureHelper) {
+        future_helper.execute_maybe_to(
+            async move {
+                rfd::AsyncFileDialog::new()
+                    .add_filter("PNG", &["png"])
+                    .save_file()
+                    .await
+                    .map(|handle| MenuEvent::Snapshot(time, handle.path().to_path_buf()))
+            },
+            self.event_channel.0.clone(),
+        );
+    }
+
     /// Draws the about-window if [Self::about_open] is `true`
     fn draw_about_window(&mut self, ctx: &egui::Context) {
         Window::new("About NAViz")
@@ -396,6 +493,10 @@ mod selection_menu {
         fn remove(state: &mut AppState, id: &str) -> Result<()>;
         fn items(state: &AppState) -> impl Iterator<Item = (&str, &str, bool)>;
         fn selected(state: &AppState) -> Option<&str>;
+        /// A note to display above the items, if any
+        fn note(_state: &AppState) -> Option<String> {
+            None
+        }
     }
 
     /// Selection-menu for the machines.
@@ -420,6 +521,12 @@ mod selection_menu {
         fn selected(state: &AppState) -> Option<&str> {
             state.get_current_machine_id()
         }
+        fn note(state: &AppState) -> Option<String> {
+            // Explain why no compatible machine was found
+            let report = state.machine_compatibility_report();
+            (!report.is_available() && report != CompatibilityReport::NoInstructions)
+                .then(|| report.to_string())
+        }
     }
 
     /// Selection-menu for the styles.
@@ -489,6 +596,11 @@ mod selection_menu {
 
             ui.separator();
 
+            if let Some(note) = M::note(state) {
+                ui.label(note);
+                ui.separator();
+            }
+
             ScrollArea::vertical().show(ui, |ui| {
                 // Store previous widths to layout
 
@@ -623,19 +735,24 @@ pub mod export {
     use std::{path::PathBuf, sync::mpsc::channel};
 
     use egui::{Button, Context};
+    use naviz_video::TRANSPARENT_FORMATS;
 
     use crate::{
         app::AppState,
-        export_dialog::{ExportProgresses, ExportSettings},
+        export_dialog::{ExportProgresses, ExportSettings, ExportTarget, FrameOptions},
         future_helper::FutureHelper,
     };
 
     use super::SendReceivePair;
 
+    /// The selected export-settings:
+    /// ([ExportTarget], `target`-path, `resolution`, `fps`, [FrameOptions])
+    type ExportRequest = (ExportTarget, PathBuf, (u32, u32), u32, FrameOptions);
+
     /// Menu components concerning export
     pub struct ExportMenu {
         /// Channel for selected export-settings
-        export_channel: SendReceivePair<(PathBuf, (u32, u32), u32)>,
+        export_channel: SendReceivePair<ExportRequest>,
         /// The export-settings-dialog to show when the user wants to export a video
         export_settings: ExportSettings,
         /// The export-progress-dialogs to show
@@ -654,11 +771,23 @@ pub mod export {
 
         /// Processes events concerning export
         pub fn process_events(&mut self, state: &mut AppState) {
-            if let Ok((target, resolution, fps)) = self.export_channel.1.try_recv() {
-                state.export(target, resolution, fps, self.export_progresses.add());
+            if let Ok((kind, target, resolution, fps, options)) = self.export_channel.1.try_recv() {
+                state.export(
+                    kind,
+                    target,
+                    resolution,
+                    fps,
+                    options,
+                    self.export_progresses.add(),
+                );
             }
         }
 
+        /// Gets the resolution selected in the export-settings
+        pub fn resolution(&self) -> (u32, u32) {
+            self.export_settings.resolution()
+        }
+
         /// Draws the menu button concerning export
         pub fn draw_button(&mut self, enabled: bool, ui: &mut egui::Ui) {
             if ui
@@ -679,17 +808,31 @@ pub mod export {
             self.export_progresses.draw(ctx);
         }
 
-        /// Show the file-saving dialog and get the path to export to if a file was selected
+        /// Show the file-saving dialog (or directory-picker for [ExportTarget::PngSequence])
+        /// and get the path to export to if one was selected.
+        /// When exporting a video with a transparent background,
+        /// the dialog is restricted to formats which support transparency.
         fn export(&self, future_helper: &FutureHelper) {
+            let kind = self.export_settings.target();
             let resolution = self.export_settings.resolution();
             let fps = self.export_settings.fps();
+            let options = self.export_settings.frame_options();
             future_helper.execute_maybe_to(
                 async move {
-                    rfd::AsyncFileDialog::new()
-                        .save_file()
-                        .await
+                    let dialog = rfd::AsyncFileDialog::new();
+                    let handle = match kind {
+                        ExportTarget::Video if options.transparent => {
+                            dialog
+                                .add_filter("Transparent", &TRANSPARENT_FORMATS)
+                                .save_file()
+                                .await
+                        }
+                        ExportTarget::Video => dialog.save_file().await,
+                        ExportTarget::PngSequence => dialog.pick_folder().await,
+                    };
+                    handle
                         .map(|handle| handle.path().to_path_buf())
-                        .map(|target| (target, resolution, fps))
+                        .map(|target| (kind, target, resolution, fps, options))
                 },
                 self.export_channel.0.clone(),
             );
@@ -1,12 +1,16 @@
-use egui::{style::HandleShape, Button, DragValue, Slider, Ui};
+use egui::{style::HandleShape, Button, ComboBox, DragValue, Slider, Ui};
 use egui_extras::{Size, StripBuilder};
 
 /// Height of the progress bar
 const BAR_HEIGHT: f32 = 20.;
 /// Width of the play/pause button
 const PLAY_PAUSE_WIDTH: f32 = BAR_HEIGHT;
+/// Width of the step buttons
+const STEP_WIDTH: f32 = BAR_HEIGHT;
 /// Width of the speed field
 const SPEED_WIDTH: f32 = BAR_HEIGHT * 2.;
+/// Width of the playback-mode selection
+const MODE_WIDTH: f32 = BAR_HEIGHT * 4.;
 
 /// Play icon (unicode)
 const PLAY_ICON: &str = "\u{25B6}";
@@ -14,6 +18,17 @@ const PLAY_ICON: &str = "\u{25B6}";
 const PAUSE_ICON: &str = "\u{23F8}";
 /// Replay icon (unicode)
 const REPLAY_ICON: &str = "\u{27F2}";
+/// Step to previous instruction icon (unicode)
+const PREVIOUS_ICON: &str = "\u{23EE}";
+/// Step to next instruction icon (unicode)
+const NEXT_ICON: &str = "\u{23ED}";
+
+/// Tolerance when comparing the current time to step times
+const STEP_EPSILON: f64 = 1e-6;
+
+/// Fraction of the duration to seek by when using [ProgressBar::seek_forward]
+/// or [ProgressBar::seek_backward]
+const SEEK_FRACTION: f64 = 0.01;
 
 /// Maximum speed
 const MAX_SPEED: f64 = 5.;
@@ -22,6 +37,32 @@ const MAX_SPEED: f64 = 5.;
 /// Thin Space, Multiplication Sign
 const TIMES_PREFIX: &str = "\u{00D7}\u{2009}";
 
+/// How playback continues when reaching the end of the animation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlaybackMode {
+    /// Pause at the end
+    #[default]
+    Once,
+    /// Restart from the beginning
+    Loop,
+    /// Play backwards to the beginning, then forwards again
+    PingPong,
+}
+
+impl PlaybackMode {
+    /// All [PlaybackMode]s
+    const ALL: [Self; 3] = [Self::Once, Self::Loop, Self::PingPong];
+
+    /// The name to display for this [PlaybackMode]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Once => "Once",
+            Self::Loop => "Loop",
+            Self::PingPong => "Ping-Pong",
+        }
+    }
+}
+
 /// The progress bar that displays the animation progress,
 /// allows scrubbing though the animation,
 /// pausing, and changing the playback speed.
@@ -38,6 +79,14 @@ pub struct ProgressBar {
     speed: f64,
     /// Whether playback is currently paused
     paused: bool,
+    /// What happens when reaching the end
+    mode: PlaybackMode,
+    /// Whether playback currently runs backwards
+    /// (only in [PlaybackMode::PingPong])
+    reverse: bool,
+    /// The sorted times which can be stepped to
+    /// (i.e., the start times of the instructions)
+    steps: Vec<f64>,
 }
 
 impl Default for ProgressBar {
@@ -59,22 +108,141 @@ impl ProgressBar {
             speed,
             duration,
             paused: false,
+            mode: PlaybackMode::default(),
+            reverse: false,
+            steps: Vec::new(),
         }
     }
 
+    /// Sets the times which can be stepped to using the step-buttons.
+    /// Duplicate times will be removed.
+    pub fn set_steps(&mut self, steps: impl IntoIterator<Item = f64>) {
+        self.steps = steps.into_iter().collect();
+        self.steps.sort_by(f64::total_cmp);
+        self.steps.dedup();
+    }
+
+    /// Pauses and steps to the next step-time after the current time (if any)
+    pub fn step_next(&mut self) {
+        if let Some(&time) = self
+            .steps
+            .iter()
+            .find(|&&t| t > self.animation_time + STEP_EPSILON)
+        {
+            self.paused = true;
+            self.animation_time = time;
+        }
+    }
+
+    /// Pauses and steps to the previous step-time before the current time (if any)
+    pub fn step_previous(&mut self) {
+        if let Some(&time) = self
+            .steps
+            .iter()
+            .rfind(|&&t| t < self.animation_time - STEP_EPSILON)
+        {
+            self.paused = true;
+            self.animation_time = time;
+        }
+    }
+
+    /// Toggles between playing and pausing.
+    /// Restarts the playback when playing after the end was reached.
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+
+        if self.is_end() {
+            // replay when pressing play on end
+            self.animation_time = 0.;
+        }
+    }
+
+    /// Seeks forwards by a small fraction of the duration
+    pub fn seek_forward(&mut self) {
+        self.seek_to(self.animation_time + self.duration * SEEK_FRACTION);
+    }
+
+    /// Seeks backwards by a small fraction of the duration
+    pub fn seek_backward(&mut self) {
+        self.seek_to(self.animation_time - self.duration * SEEK_FRACTION);
+    }
+
+    /// Jumps to the start of the animation
+    pub fn jump_to_start(&mut self) {
+        self.seek_to(0.);
+    }
+
+    /// Jumps to the end of the animation
+    pub fn jump_to_end(&mut self) {
+        self.seek_to(self.duration);
+    }
+
+    /// Sets the current time to `time` (clamped to the duration)
+    fn seek_to(&mut self, time: f64) {
+        self.animation_time = time.clamp(0., self.duration);
+    }
+
+    /// Sets the [PlaybackMode].
+    /// Playback will continue forwards.
+    pub fn set_mode(&mut self, mode: PlaybackMode) {
+        self.mode = mode;
+        self.reverse = false;
+    }
+
+    /// Gets the currently set [PlaybackMode]
+    pub fn get_mode(&self) -> PlaybackMode {
+        self.mode
+    }
+
     /// Gets the currently set speed
     pub fn get_speed(&self) -> f64 {
         self.speed
     }
 
-    /// Updates the `animation_time` respecting `paused` and `speed`.
+    /// Updates the `animation_time` respecting `paused`, `speed`, and `mode`.
     fn update_time(&mut self, delta: f32) {
-        if !self.paused {
-            self.animation_time += self.speed * delta as f64;
+        if self.paused {
+            return;
+        }
+
+        let direction = if self.reverse { -1. } else { 1. };
+        self.animation_time += direction * self.speed * delta as f64;
 
-            if self.is_end() {
-                // pause on end
-                self.paused = true;
+        match self.mode {
+            PlaybackMode::Once => {
+                if self.is_end() {
+                    // pause on end
+                    self.paused = true;
+                }
+            }
+            PlaybackMode::Loop => {
+                if self.duration <= 0. {
+                    // nothing to loop
+                    self.animation_time = 0.;
+                    self.paused = true;
+                } else if self.is_end() {
+                    // wrap around to start
+                    self.animation_time %= self.duration;
+                }
+            }
+            PlaybackMode::PingPong => {
+                if self.duration <= 0. {
+                    // nothing to bounce between
+                    self.animation_time = 0.;
+                    self.paused = true;
+                    return;
+                }
+                // bounce at the boundaries (multiple times for large deltas)
+                loop {
+                    if self.animation_time > self.duration {
+                        self.animation_time = 2. * self.duration - self.animation_time;
+                    } else if self.animation_time < 0. {
+                        self.animation_time = -self.animation_time;
+                    } else {
+                        break;
+                    }
+                    self.reverse = !self.reverse;
+                }
             }
         }
     }
@@ -95,12 +263,20 @@ impl ProgressBar {
             .add_sized([PLAY_PAUSE_WIDTH, BAR_HEIGHT], Button::new(icon))
             .clicked()
         {
-            self.paused = !self.paused;
+            self.toggle_pause();
+        }
+    }
 
-            if self.is_end() {
-                // replay when pressing play on end
-                self.animation_time = 0.;
-            }
+    /// Draws a button which calls `step` when clicked
+    fn draw_step(&mut self, ui: &mut Ui, icon: &str, step: fn(&mut Self)) {
+        if ui
+            .add_enabled(
+                !self.steps.is_empty(),
+                Button::new(icon).min_size([STEP_WIDTH, BAR_HEIGHT].into()),
+            )
+            .clicked()
+        {
+            step(self);
         }
     }
 
@@ -129,6 +305,22 @@ impl ProgressBar {
         );
     }
 
+    /// Draws the selection of the [PlaybackMode]
+    fn draw_mode(&mut self, ui: &mut Ui) {
+        let mut mode = self.mode;
+        ComboBox::from_id_salt("playback_mode")
+            .width(MODE_WIDTH)
+            .selected_text(mode.name())
+            .show_ui(ui, |ui| {
+                for option in PlaybackMode::ALL {
+                    ui.selectable_value(&mut mode, option, option.name());
+                }
+            });
+        if mode != self.mode {
+            self.set_mode(mode);
+        }
+    }
+
     /// Gets the current time in the animation.
     pub fn animation_time(&self) -> f64 {
         self.animation_time
@@ -139,13 +331,68 @@ impl ProgressBar {
         self.update_time(ui.input(|i| i.unstable_dt));
 
         StripBuilder::new(ui)
+            .size(Size::exact(STEP_WIDTH))
             .size(Size::exact(PLAY_PAUSE_WIDTH))
+            .size(Size::exact(STEP_WIDTH))
             .size(Size::remainder())
             .size(Size::initial(SPEED_WIDTH))
+            .size(Size::initial(MODE_WIDTH))
             .horizontal(|mut strip| {
+                strip.cell(|ui| self.draw_step(ui, PREVIOUS_ICON, Self::step_previous));
                 strip.cell(|ui| self.draw_pause(ui));
+                strip.cell(|ui| self.draw_step(ui, NEXT_ICON, Self::step_next));
                 strip.cell(|ui| self.draw_progress(ui));
                 strip.cell(|ui| self.draw_speed(ui));
+                strip.cell(|ui| self.draw_mode(ui));
             });
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A playing [ProgressBar] with a duration of `10` in the passed `mode`,
+    /// advanced to `9`
+    fn progress_bar(mode: PlaybackMode) -> ProgressBar {
+        let mut progress_bar = ProgressBar::new(10.);
+        progress_bar.set_mode(mode);
+        progress_bar.update_time(9.);
+        progress_bar
+    }
+
+    #[test]
+    fn once_pauses_at_end() {
+        let mut progress_bar = progress_bar(PlaybackMode::Once);
+        progress_bar.update_time(3.);
+
+        assert!(progress_bar.paused, "Not paused at end");
+        assert!(progress_bar.is_end());
+        assert!(!progress_bar.reverse);
+    }
+
+    #[test]
+    fn loop_wraps_to_start() {
+        let mut progress_bar = progress_bar(PlaybackMode::Loop);
+        progress_bar.update_time(3.);
+
+        assert!(!progress_bar.paused, "Paused at end");
+        assert!((progress_bar.animation_time() - 2.).abs() < 1e-6);
+        assert!(!progress_bar.reverse);
+    }
+
+    #[test]
+    fn ping_pong_bounces() {
+        let mut progress_bar = progress_bar(PlaybackMode::PingPong);
+        progress_bar.update_time(3.);
+
+        assert!(!progress_bar.paused, "Paused at end");
+        assert!((progress_bar.animation_time() - 8.).abs() < 1e-6);
+        assert!(progress_bar.reverse, "Direction not flipped at end");
+
+        // Bounce at start
+        progress_bar.update_time(10.);
+        assert!((progress_bar.animation_time() - 2.).abs() < 1e-6);
+        assert!(!progress_bar.reverse, "Direction not flipped at start");
+    }
+}
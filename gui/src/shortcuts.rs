@@ -0,0 +1,132 @@
+//! Keyboard shortcuts for playback and navigation.
+
+use egui::{Context, Event, Key, Modifiers};
+
+/// An action which can be triggered by a keyboard shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Toggle between playing and pausing
+    TogglePause,
+    /// Step backwards by a small time delta
+    StepBackward,
+    /// Step forwards by a small time delta
+    StepForward,
+    /// Step to the previous instruction
+    PreviousInstruction,
+    /// Step to the next instruction
+    NextInstruction,
+    /// Jump to the start of the animation
+    JumpToStart,
+    /// Jump to the end of the animation
+    JumpToEnd,
+    /// Zoom in
+    ZoomIn,
+    /// Zoom out
+    ZoomOut,
+}
+
+impl Action {
+    /// Gets the [Action] for the passed `key` when pressed with the passed `modifiers`,
+    /// or [None] if no [Action] is bound.
+    ///
+    /// Shortcuts with `alt` or `command` are left to the system or [egui] (e.g., gui-zoom).
+    pub fn from_key(key: Key, modifiers: Modifiers) -> Option<Self> {
+        if modifiers.alt || modifiers.command || modifiers.ctrl || modifiers.mac_cmd {
+            return None;
+        }
+
+        Some(match key {
+            Key::Space => Self::TogglePause,
+            Key::ArrowLeft if modifiers.shift => Self::PreviousInstruction,
+            Key::ArrowLeft => Self::StepBackward,
+            Key::ArrowRight if modifiers.shift => Self::NextInstruction,
+            Key::ArrowRight => Self::StepForward,
+            Key::Home => Self::JumpToStart,
+            Key::End => Self::JumpToEnd,
+            // `+` usually requires shift (`shift` + `=`), so also allow `=`
+            Key::Plus | Key::Equals => Self::ZoomIn,
+            Key::Minus => Self::ZoomOut,
+            _ => return None,
+        })
+    }
+
+    /// Gets the [Action]s of all keys pressed in the current frame.
+    ///
+    /// Returns no [Action]s while a text field has focus,
+    /// so that typing does not trigger any shortcuts.
+    pub fn pressed(ctx: &Context) -> Vec<Self> {
+        if ctx.text_edit_focused() {
+            return Vec::new();
+        }
+
+        ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|e| match e {
+                    Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Self::from_key(*key, *modifiers),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Shift should switch the arrow keys from time-steps to instruction-steps.
+    #[test]
+    fn arrows() {
+        assert_eq!(
+            Action::from_key(Key::ArrowLeft, Modifiers::NONE),
+            Some(Action::StepBackward)
+        );
+        assert_eq!(
+            Action::from_key(Key::ArrowRight, Modifiers::NONE),
+            Some(Action::StepForward)
+        );
+        assert_eq!(
+            Action::from_key(Key::ArrowLeft, Modifiers::SHIFT),
+            Some(Action::PreviousInstruction)
+        );
+        assert_eq!(
+            Action::from_key(Key::ArrowRight, Modifiers::SHIFT),
+            Some(Action::NextInstruction)
+        );
+    }
+
+    /// Playback-, jump-, and zoom-keys should map to their actions.
+    #[test]
+    fn keys() {
+        for (key, action) in [
+            (Key::Space, Action::TogglePause),
+            (Key::Home, Action::JumpToStart),
+            (Key::End, Action::JumpToEnd),
+            (Key::Plus, Action::ZoomIn),
+            (Key::Equals, Action::ZoomIn),
+            (Key::Minus, Action::ZoomOut),
+        ] {
+            assert_eq!(Action::from_key(key, Modifiers::NONE), Some(action));
+        }
+        assert_eq!(
+            Action::from_key(Key::Plus, Modifiers::SHIFT),
+            Some(Action::ZoomIn)
+        );
+        assert_eq!(Action::from_key(Key::A, Modifiers::NONE), None);
+    }
+
+    /// Shortcuts with `command` or `alt` should be left to the system.
+    #[test]
+    fn system_modifiers() {
+        for modifiers in [Modifiers::COMMAND, Modifiers::CTRL, Modifiers::ALT] {
+            assert_eq!(Action::from_key(Key::Space, modifiers), None);
+            assert_eq!(Action::from_key(Key::Plus, modifiers), None);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use naviz_renderer::viewport::{Camera, ViewportProjection};
+
+/// The smallest allowed [zoom][ZoomState::zoom]
+const MIN_ZOOM: f32 = 0.5;
+/// The largest allowed [zoom][ZoomState::zoom]
+const MAX_ZOOM: f32 = 50.;
+/// The rate (per second) at which the view approaches a followed atom.
+/// See [approach].
+const FOLLOW_RATE: f32 = 8.;
+
+/// The zoom- and pan-state of the content on the canvas.
+/// Can follow an atom (see [ZoomState::follow]).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoomState {
+    /// The magnification of the content (`1` shows the fitted content)
+    pub zoom: f32,
+    /// The point of the content (in content-coordinates) shown in the center of the canvas
+    pub zoom_center: (f32, f32),
+    /// Whether to fit the whole content onto the canvas.
+    /// [ZoomState::zoom] and [ZoomState::zoom_center] will then track the fitted content.
+    pub auto_fit: bool,
+    /// The id of the followed atom, if any
+    following: Option<String>,
+}
+
+impl Default for ZoomState {
+    fn default() -> Self {
+        Self {
+            zoom: 1.,
+            zoom_center: (0., 0.),
+            auto_fit: true,
+            following: None,
+        }
+    }
+}
+
+impl ZoomState {
+    /// Follows the atom with the passed `atom_id`,
+    /// keeping it in the center of the canvas (see [ZoomState::update]).
+    /// Disables [auto_fit][ZoomState::auto_fit].
+    pub fn follow(&mut self, atom_id: impl Into<String>) {
+        self.following = Some(atom_id.into());
+        self.auto_fit = false;
+    }
+
+    /// The id of the followed atom, if any
+    pub fn following(&self) -> Option<&str> {
+        self.following.as_deref()
+    }
+
+    /// Moves the view by `delta` (in content-coordinates).
+    /// Releases the followed atom and disables [auto_fit][ZoomState::auto_fit].
+    pub fn pan(&mut self, delta: (f32, f32)) {
+        self.following = None;
+        self.auto_fit = false;
+        self.zoom_center = (self.zoom_center.0 - delta.0, self.zoom_center.1 - delta.1);
+    }
+
+    /// Magnifies the view by `factor`, keeping the passed `point` (in content-coordinates) in place.
+    /// When following an atom, the view is magnified around the atom instead.
+    /// Disables [auto_fit][ZoomState::auto_fit].
+    pub fn zoom_at(&mut self, point: (f32, f32), factor: f32) {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        if self.following.is_none() {
+            // Scale the distance of the center to the point
+            let scale = self.zoom / zoom;
+            self.zoom_center = (
+                point.0 + (self.zoom_center.0 - point.0) * scale,
+                point.1 + (self.zoom_center.1 - point.1) * scale,
+            );
+        }
+        self.zoom = zoom;
+        self.auto_fit = false;
+    }
+
+    /// Fits the whole content onto the canvas again.
+    /// Releases the followed atom.
+    pub fn fit(&mut self) {
+        self.following = None;
+        self.auto_fit = true;
+    }
+
+    /// Updates this [ZoomState] for the next frame, which comes `dt` seconds after the previous one.
+    ///
+    /// - `fitted`: The content-projection of the fitted content (i.e., without a [Camera])
+    /// - `followed`: The current position of the [followed][ZoomState::following] atom, if any
+    pub fn update(&mut self, fitted: &ViewportProjection, followed: Option<(f32, f32)>, dt: f32) {
+        if self.auto_fit {
+            self.zoom = 1.;
+            self.zoom_center = fitted.source.center();
+        } else if let Some(position) = followed.filter(|_| self.following.is_some()) {
+            self.zoom_center = approach(self.zoom_center, position, dt, FOLLOW_RATE);
+        }
+    }
+
+    /// The [Camera] showing the current view,
+    /// or [None] if the content is [fitted][ZoomState::auto_fit].
+    pub fn camera(&self) -> Option<Camera> {
+        (!self.auto_fit).then_some(Camera {
+            center: self.zoom_center,
+            zoom: self.zoom,
+        })
+    }
+}
+
+/// Moves `current` towards `target` after `dt` seconds,
+/// closing the distance exponentially at the passed `rate` (per second).
+/// Independent of the frame rate:
+/// two steps of `dt / 2` end up at the same point as one step of `dt`.
+pub fn approach(current: (f32, f32), target: (f32, f32), dt: f32, rate: f32) -> (f32, f32) {
+    let t = 1. - (-rate * dt).exp();
+    (
+        current.0 + (target.0 - current.0) * t,
+        current.1 + (target.1 - current.1) * t,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Asserts that the points `a` and `b` are equal (up to rounding errors).
+    fn assert_close(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4,
+            "{a:?} is not {b:?}"
+        );
+    }
+
+    /// [approach] should move towards the target without overshooting,
+    /// independent of the frame rate.
+    #[test]
+    fn approach_target() {
+        let current = (0., 10.);
+        let target = (10., 0.);
+
+        assert_close(approach(current, target, 0., 8.), current);
+        assert_close(approach(current, target, 100., 8.), target);
+
+        let (x, y) = approach(current, target, 0.1, 8.);
+        assert!(0. < x && x < 10., "Did not move towards target: {x}");
+        assert!(0. < y && y < 10., "Did not move towards target: {y}");
+        // A faster rate gets closer
+        assert!(approach(current, target, 0.1, 16.).0 > x);
+
+        let halfway = approach(current, target, 0.05, 8.);
+        assert_close(approach(halfway, target, 0.05, 8.), (x, y));
+    }
+
+    /// Following should disable auto-fit and be released by panning.
+    #[test]
+    fn follow_and_pan() {
+        let fitted = ViewportProjection::identity();
+        let mut zoom = ZoomState::default();
+        assert_eq!(zoom.camera(), None);
+
+        zoom.follow("atom0");
+        assert!(!zoom.auto_fit);
+        zoom.update(&fitted, Some((4., 2.)), 100.);
+        assert_close(zoom.zoom_center, (4., 2.));
+        assert_eq!(
+            zoom.camera(),
+            Some(Camera {
+                center: zoom.zoom_center,
+                zoom: 1.,
+            })
+        );
+
+        // Zooming keeps the followed atom centered
+        zoom.zoom_at((0., 0.), 2.);
+        assert_eq!(zoom.following(), Some("atom0"));
+        assert_close(zoom.zoom_center, (4., 2.));
+
+        zoom.pan((1., 1.));
+        assert_eq!(zoom.following(), None);
+        assert_close(zoom.zoom_center, (3., 1.));
+        zoom.update(&fitted, Some((4., 2.)), 100.);
+        assert_close(zoom.zoom_center, (3., 1.));
+
+        zoom.fit();
+        zoom.update(&fitted, None, 0.);
+        assert_eq!(zoom.camera(), None);
+        assert_close(zoom.zoom_center, fitted.source.center());
+    }
+}
@@ -0,0 +1,148 @@
+//! Automatic placement of atoms for imported circuits which do not define positions.
+//! See [auto_place].
+
+use fraction::Fraction;
+use naviz_parser::config::{machine::MachineConfig, position::Position};
+
+/// The distance between generated positions if no machine is passed to [auto_place]
+pub const DEFAULT_SPACING: u64 = 10;
+
+/// Generates positions for `n_qubits` atoms.
+///
+/// The atoms are laid out row-wise on a square grid.
+/// If a `machine` is passed, the grid starts at the top-left-most trap,
+/// uses the interaction distance of the machine as spacing,
+/// and every generated position is snapped onto the nearest free trap of the machine.
+/// Positions which find no free trap (as the machine has too few traps)
+/// are kept on the grid.
+/// If no `machine` is passed, the grid uses the [DEFAULT_SPACING] and starts at the origin.
+pub fn auto_place(n_qubits: usize, machine: Option<&MachineConfig>) -> Vec<Position> {
+    let mut traps: Vec<Position> = machine
+        .map(|m| m.trap.values().map(|t| t.position).collect())
+        .unwrap_or_default();
+    // Traps are stored in a map; sort for a deterministic layout
+    traps.sort_by(|(ax, ay), (bx, by)| ay.cmp(by).then(ax.cmp(bx)));
+
+    let origin = traps.iter().fold(None, |origin, &(x, y)| match origin {
+        None => Some((x, y)),
+        Some((ox, oy)) => Some((x.min(ox), y.min(oy))),
+    });
+    let origin = origin.unwrap_or((Fraction::from(0), Fraction::from(0)));
+    let spacing = machine
+        .map(|m| m.distance.interaction)
+        .filter(|s| *s > Fraction::from(0))
+        .unwrap_or(Fraction::from(DEFAULT_SPACING));
+
+    grid(n_qubits, origin, spacing)
+        .map(|position| snap(position, &mut traps).unwrap_or(position))
+        .collect()
+}
+
+/// Generates `n` positions on a square grid with the passed `spacing`
+/// starting at `origin`.
+fn grid(n: usize, origin: Position, spacing: Fraction) -> impl Iterator<Item = Position> {
+    let columns = (n as f64).sqrt().ceil().max(1.) as usize;
+    (0..n).map(move |i| {
+        let (column, row) = (i % columns, i / columns);
+        (
+            origin.0 + spacing * Fraction::from(column as u64),
+            origin.1 + spacing * Fraction::from(row as u64),
+        )
+    })
+}
+
+/// Removes the trap nearest to `position` from the free `traps` and returns it,
+/// or [None] if no free trap is left.
+fn snap(position: Position, traps: &mut Vec<Position>) -> Option<Position> {
+    let distance = |(x, y): &Position| {
+        let (dx, dy) = (*x - position.0, *y - position.1);
+        dx * dx + dy * dy
+    };
+    let (idx, _) = traps
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a).cmp(&distance(b)))?;
+    Some(traps.remove(idx))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use naviz_parser::config::{generic::Config, machine::TrapConfig};
+
+    use super::*;
+
+    /// The example machine of the parser with a `4x4`-grid of traps
+    fn machine() -> MachineConfig {
+        let lexed = naviz_parser::config::lexer::lex(include_str!(
+            "../../parser/rsc/test/example.namachine"
+        ))
+        .expect("Failed to lex machine");
+        let generic: Config = naviz_parser::config::parser::parse(&lexed)
+            .expect("Failed to parse machine")
+            .into();
+        let mut machine: MachineConfig = generic.try_into().expect("Failed to load machine");
+        machine.trap = (0..16u64)
+            .map(|i| {
+                (
+                    format!("trap{i}"),
+                    TrapConfig {
+                        position: (Fraction::from(i % 4 * 5), Fraction::from(i / 4 * 5)),
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        machine
+    }
+
+    #[test]
+    fn distinct_traps() {
+        let machine = machine();
+        let traps: HashSet<_> = machine.trap.values().map(|t| t.position).collect();
+
+        let placed = auto_place(9, Some(&machine));
+
+        assert_eq!(placed.len(), 9);
+        assert!(
+            placed.iter().all(|p| traps.contains(p)),
+            "Atom was not placed on a trap"
+        );
+        assert_eq!(
+            placed.iter().collect::<HashSet<_>>().len(),
+            9,
+            "Atoms share a trap"
+        );
+    }
+
+    #[test]
+    fn too_few_traps() {
+        let mut machine = machine();
+        machine
+            .trap
+            .retain(|_, t| t.position.1 == Fraction::from(0));
+
+        let placed = auto_place(9, Some(&machine));
+
+        assert_eq!(
+            placed.iter().collect::<HashSet<_>>().len(),
+            9,
+            "Atoms share a position"
+        );
+    }
+
+    #[test]
+    fn uniform_grid() {
+        let spacing = Fraction::from(DEFAULT_SPACING);
+        assert_eq!(
+            auto_place(4, None),
+            vec![
+                (Fraction::from(0), Fraction::from(0)),
+                (spacing, Fraction::from(0)),
+                (Fraction::from(0), spacing),
+                (spacing, spacing),
+            ]
+        );
+        assert!(auto_place(0, None).is_empty());
+    }
+}
@@ -2,6 +2,7 @@ use std::str::Utf8Error;
 
 use naviz_parser::input::concrete::Instructions;
 
+pub mod layout;
 pub mod mqt;
 pub mod separated_display;
 
@@ -17,6 +18,7 @@ pub enum ImportFormat {
 pub static IMPORT_FORMATS: [ImportFormat; 1] = [ImportFormat::MqtNa];
 
 /// The options for the different import formats
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ImportOptions {
     /// [mqt::na]
@@ -70,15 +72,48 @@ impl ImportOptions {
     /// Imports the `data` using the options in `self`
     pub fn import(self, data: &[u8]) -> Result<Instructions, ImportError> {
         match self {
-            Self::MqtNa(options) => mqt::na::convert::convert(
-                &mqt::na::format::parse(
-                    std::str::from_utf8(data).map_err(ImportError::InvalidUtf8)?,
+            Self::MqtNa(options) => {
+                let input = std::str::from_utf8(data).map_err(ImportError::InvalidUtf8)?;
+                // The leading comment is kept as the source of the instructions
+                let (comment, input) = mqt::na::format::split_leading_comment(input);
+                let mut instructions = mqt::na::convert::convert(
+                    &mqt::na::format::parse(input)
+                        .map_err(|e| e.into_inner())
+                        .map_err(ImportError::MqtNqParse)?,
+                    options,
                 )
-                .map_err(|e| e.into_inner())
-                .map_err(ImportError::MqtNqParse)?,
-                options,
-            )
-            .map_err(ImportError::MqtNqConvert),
+                .map_err(ImportError::MqtNqConvert)?;
+                instructions.source_comment = comment;
+                Ok(instructions)
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The leading comment of an imported file should be kept as the source-comment.
+    #[test]
+    fn mqt_na_comment_preserved() {
+        let input = format!(
+            "// Generated by a compiler\n  // from circuit.qasm\n{}",
+            include_str!("../rsc/test/example.na")
+        );
+
+        let instructions = ImportOptions::from(ImportFormat::MqtNa)
+            .import(input.as_bytes())
+            .expect("Failed to import");
+        assert_eq!(
+            instructions.source_comment.as_deref(),
+            Some("Generated by a compiler\nfrom circuit.qasm")
+        );
+        assert!(!instructions.setup.is_empty(), "Operations were dropped");
+
+        let instructions = ImportOptions::from(ImportFormat::MqtNa)
+            .import(include_bytes!("../rsc/test/example.na"))
+            .expect("Failed to import");
+        assert_eq!(instructions.source_comment, None);
+    }
+}
@@ -1,12 +1,20 @@
 //! Converter from the [na][super]-format to the [naviz][naviz_parser]-format.
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 use fraction::{ConstZero, Fraction};
-use naviz_parser::input::concrete::{
-    InstructionGroup, Instructions, RelativeTimeline, SetupInstruction, TimedInstruction,
+use naviz_parser::{
+    config::machine::MachineConfig,
+    input::concrete::{
+        InstructionGroup, Instructions, RelativeTimeline, SetupInstruction, TimedInstruction,
+    },
 };
 
+use crate::layout::auto_place;
+
 use super::format::{Number, Operation, OperationArgs, OperationList, Position};
 
 /// Options for [convert]
@@ -65,7 +73,12 @@ impl<'a> GlobalZoneNames<'a> {
 /// Maps position to name
 type PositionCache = HashMap<Position, String>;
 
-/// Tries to converts an [na][super] [OperationList] to [naviz][naviz_parser] [Instructions]
+/// Tries to converts an [na][super] [OperationList] to [naviz][naviz_parser] [Instructions].
+///
+/// If the `input` does not initialize any atoms,
+/// atoms are created at the positions referenced by the operations
+/// in the order they are first referenced,
+/// so that the later operations (e.g., shuttles) start from where the atoms were placed.
 pub fn convert(
     input: &OperationList<&str>,
     options: ConvertOptions,
@@ -76,6 +89,25 @@ pub fn convert(
     let mut timeline = RelativeTimeline::new();
     let mut setup = Vec::new();
 
+    if !input
+        .iter()
+        .any(|operation| matches!(operation.args, OperationArgs::Init(_)))
+    {
+        for position in referenced_positions(input) {
+            setup.push(SetupInstruction::Atom {
+                position: position.into(),
+                id: create_id(
+                    &options.atom_prefix,
+                    &mut atom_counter,
+                    position,
+                    &mut position_cache,
+                ),
+                appear_time: None,
+                shape: None,
+            });
+        }
+    }
+
     for operation in input.iter() {
         if let OperationArgs::Init(positions) = &operation.args {
             // Init-instruction: Create new atom
@@ -88,6 +120,8 @@ pub fn convert(
                         position,
                         &mut position_cache,
                     ),
+                    appear_time: None,
+                    shape: None,
                 });
             }
         } else {
@@ -114,6 +148,61 @@ pub fn convert(
     })
 }
 
+/// Creates an `init`-[Operation] for `n_qubits` atoms
+/// which are placed using [auto_place] (optionally onto the traps of the passed `machine`).
+///
+/// Can be prepended to an [OperationList] which operates on qubits
+/// that were not initialized at physical positions.
+/// The atoms are then numbered in the order of the qubits.
+pub fn auto_init(n_qubits: usize, machine: Option<&MachineConfig>) -> Operation<&'static str> {
+    Operation {
+        name: "init",
+        args: OperationArgs::Init(
+            auto_place(n_qubits, machine)
+                .into_iter()
+                .map(|(x, y)| Position { x, y })
+                .collect(),
+        ),
+    }
+}
+
+/// Gets the positions of the qubits referenced by the operations of the `input`
+/// in the order they are first referenced.
+/// Positions an atom was shuttled to do not reference new qubits.
+fn referenced_positions(input: &OperationList<&str>) -> Vec<Position> {
+    /// Adds the `positions` which are not `occupied` to the `referenced` positions
+    fn reference(
+        positions: &[Position],
+        occupied: &mut HashSet<Position>,
+        referenced: &mut Vec<Position>,
+    ) {
+        for &position in positions {
+            if occupied.insert(position) {
+                referenced.push(position);
+            }
+        }
+    }
+
+    let mut occupied = HashSet::new();
+    let mut referenced = Vec::new();
+    for operation in input.iter() {
+        match &operation.args {
+            OperationArgs::Shuttle { from, to } => {
+                reference(from, &mut occupied, &mut referenced);
+                for position in from.iter() {
+                    occupied.remove(position);
+                }
+                occupied.extend(to.iter().copied());
+            }
+            OperationArgs::Local { targets, .. } => {
+                reference(targets, &mut occupied, &mut referenced)
+            }
+            OperationArgs::Init(_) | OperationArgs::Global(_) => {}
+        }
+    }
+    referenced
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum OperationConversionError {
     /// Tried to convert something to a [TimedInstruction] that is not timed (e.g., `init`)
@@ -160,14 +249,17 @@ pub fn convert_operation(
                     "load" => Ok(TimedInstruction::Load {
                         position: Some((*to).into()),
                         id: name,
+                        duration: None,
                     }),
                     "store" => Ok(TimedInstruction::Store {
                         position: Some((*to).into()),
                         id: name,
+                        duration: None,
                     }),
                     "move" => Ok(TimedInstruction::Move {
                         position: (*to).into(),
                         id: name,
+                        duration: None,
                     }),
                     _ => Err(OperationConversionError::InvalidName),
                 })
@@ -200,13 +292,17 @@ fn convert_operation_instruction(
             if argument.is_some() {
                 return Err(OperationConversionError::SuperfluousArgument);
             }
-            TimedInstruction::Cz { targets }
+            TimedInstruction::Cz {
+                targets,
+                duration: None,
+            }
         }
         "ry" => {
             if let Some(argument) = argument {
                 TimedInstruction::Ry {
                     value: argument,
                     targets,
+                    duration: None,
                 }
             } else {
                 return Err(OperationConversionError::MissingArgument);
@@ -217,6 +313,7 @@ fn convert_operation_instruction(
                 TimedInstruction::Rz {
                     value: argument,
                     targets,
+                    duration: None,
                 }
             } else {
                 return Err(OperationConversionError::MissingArgument);
@@ -299,7 +396,7 @@ mod test {
         format::{parse, Operation, OperationArgs, Position},
     };
 
-    use super::convert;
+    use super::{auto_init, convert};
 
     /// Check if the imported version matches the manually converted one
     #[test]
@@ -390,18 +487,26 @@ mod test {
                 SetupInstruction::Atom {
                     position: (9.into(), 8.into()),
                     id: "atom0".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
                 SetupInstruction::Atom {
                     position: (1.into(), 2.into()),
                     id: "atom1".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
                 SetupInstruction::Atom {
                     position: (8.into(), 8.into()),
                     id: "atom2".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
                 SetupInstruction::Atom {
                     position: (0.into(), 0.into()),
                     id: "atom3".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
             ],
             instructions: vec![(
@@ -419,6 +524,7 @@ mod test {
                                 "atom2".to_string(),
                                 "atom3".to_string(),
                             ],
+                            duration: None,
                         }],
                     },
                 )],
@@ -431,4 +537,98 @@ mod test {
             "Instruction with position list incorrectly converted."
         );
     }
+
+    /// Qubits of an input without `init` should be placed at the positions
+    /// they are first referenced at, in the order they are first referenced.
+    #[test]
+    fn auto_init_without_init() {
+        let position = |x: u64, y: u64| Position {
+            x: x.into(),
+            y: y.into(),
+        };
+        let input = [
+            Operation {
+                name: "move",
+                args: OperationArgs::Shuttle {
+                    from: [position(5, 5)].into(),
+                    to: [position(7, 7)].into(),
+                },
+            },
+            Operation {
+                name: "cz",
+                args: OperationArgs::Local {
+                    argument: None,
+                    targets: [position(7, 7), position(1, 1)].into(),
+                },
+            },
+        ]
+        .into();
+
+        let converted = convert(&input, Default::default()).expect("Failed to convert");
+
+        assert_eq!(
+            converted.setup,
+            [position(5, 5), position(1, 1)]
+                .iter()
+                .enumerate()
+                .map(|(idx, &position)| SetupInstruction::Atom {
+                    position: position.into(),
+                    id: format!("atom{idx}"),
+                    appear_time: None,
+                    shape: None,
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            converted.instructions[0].1[1].2.instructions,
+            vec![TimedInstruction::Cz {
+                targets: vec!["atom0".to_string(), "atom1".to_string()],
+                duration: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn auto_placed_atoms() {
+        let init = auto_init(2, None);
+        let OperationArgs::Init(positions) = &init.args else {
+            panic!("Not an init-operation");
+        };
+        let positions = positions.clone();
+
+        let input = [
+            init,
+            Operation {
+                name: "cz",
+                args: OperationArgs::Local {
+                    argument: None,
+                    targets: positions.clone(),
+                },
+            },
+        ]
+        .into();
+
+        let converted = convert(&input, Default::default()).expect("Failed to convert");
+
+        assert_eq!(
+            converted.setup,
+            positions
+                .iter()
+                .enumerate()
+                .map(|(idx, &position)| SetupInstruction::Atom {
+                    position: position.into(),
+                    id: format!("atom{idx}"),
+                    appear_time: None,
+                    shape: None,
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            converted.instructions[0].1[0].2.instructions,
+            vec![TimedInstruction::Cz {
+                targets: vec!["atom0".to_string(), "atom1".to_string()],
+                duration: None,
+            }]
+        );
+    }
 }
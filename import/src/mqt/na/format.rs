@@ -133,6 +133,25 @@ where
     .parse(input)
 }
 
+/// Splits the leading comment block off the `input`.
+/// The comment block consists of all consecutive lines at the start
+/// which begin with `//` (after optional whitespace).
+///
+/// Returns the text of the comment block
+/// (without the `//`-markers and surrounding whitespace; one line per comment-line)
+/// or [None] if there is no leading comment,
+/// and the remaining input to pass to [parse].
+pub fn split_leading_comment(input: &str) -> (Option<String>, &str) {
+    let mut lines = Vec::new();
+    let mut rest = input;
+    while let Some(comment) = rest.trim_start().strip_prefix("//") {
+        let (line, next) = comment.split_once('\n').unwrap_or((comment, ""));
+        lines.push(line.trim());
+        rest = next;
+    }
+    ((!lines.is_empty()).then(|| lines.join("\n")), rest)
+}
+
 /// Parsers for the individual parts of the format
 pub mod parts {
     use winnow::{
@@ -1,5 +1,9 @@
 pub mod color;
+pub mod easing;
 pub mod lexer;
 pub mod parser;
+pub mod pattern;
 pub mod percentage;
 pub mod position;
+pub mod shape;
+pub mod source;
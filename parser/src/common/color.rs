@@ -43,8 +43,26 @@ impl FromStr for Color {
         let hex = |start: usize| {
             u8::from_str_radix(&s[start..=(start + 1)], 16).map_err(ParseColorError::ParseIntError)
         };
+        // Function to parse a shorthand 8-bit number (single hex-digit, repeated) from a hex-string
+        let short_hex = |start: usize| {
+            u8::from_str_radix(&s[start..=start], 16)
+                .map(|n| n * 0x11)
+                .map_err(ParseColorError::ParseIntError)
+        };
 
         match s.len() {
+            3 => Ok(Color {
+                r: short_hex(0)?,
+                g: short_hex(1)?,
+                b: short_hex(2)?,
+                a: u8::MAX,
+            }),
+            4 => Ok(Color {
+                r: short_hex(0)?,
+                g: short_hex(1)?,
+                b: short_hex(2)?,
+                a: short_hex(3)?,
+            }),
             6 => Ok(Color {
                 r: hex(0)?,
                 g: hex(2)?,
@@ -63,6 +81,46 @@ impl FromStr for Color {
 }
 
 impl Color {
+    /// Creates an opaque [Color] from its `red`, `green`, and `blue` components
+    const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            r,
+            g,
+            b,
+            a: u8::MAX,
+        }
+    }
+
+    /// Gets the [Color] with the passed (CSS-)name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "transparent" => Some(Self {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            }),
+            "black" => Some(Self::rgb(0, 0, 0)),
+            "white" => Some(Self::rgb(255, 255, 255)),
+            "gray" | "grey" => Some(Self::rgb(128, 128, 128)),
+            "silver" => Some(Self::rgb(192, 192, 192)),
+            "red" => Some(Self::rgb(255, 0, 0)),
+            "maroon" => Some(Self::rgb(128, 0, 0)),
+            "orange" => Some(Self::rgb(255, 165, 0)),
+            "yellow" => Some(Self::rgb(255, 255, 0)),
+            "olive" => Some(Self::rgb(128, 128, 0)),
+            "lime" => Some(Self::rgb(0, 255, 0)),
+            "green" => Some(Self::rgb(0, 128, 0)),
+            "cyan" | "aqua" => Some(Self::rgb(0, 255, 255)),
+            "teal" => Some(Self::rgb(0, 128, 128)),
+            "blue" => Some(Self::rgb(0, 0, 255)),
+            "navy" => Some(Self::rgb(0, 0, 128)),
+            "magenta" | "fuchsia" => Some(Self::rgb(255, 0, 255)),
+            "purple" => Some(Self::rgb(128, 0, 128)),
+            _ => None,
+        }
+    }
+
     /// Gets this color in `RGBA`-format
     pub fn rgba(&self) -> [u8; 4] {
         [self.r, self.g, self.b, self.a]
@@ -73,3 +131,30 @@ impl Color {
         [self.a, self.r, self.g, self.b]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shorthand_hex() {
+        let long: Color = "ff0000".parse().expect("Failed to parse long hex");
+        let short: Color = "f00".parse().expect("Failed to parse short hex");
+        assert_eq!(short, long);
+        assert_eq!(
+            "1a2b"
+                .parse::<Color>()
+                .expect("Failed to parse short hex with alpha"),
+            "11aa22bb"
+                .parse::<Color>()
+                .expect("Failed to parse long hex with alpha")
+        );
+    }
+
+    #[test]
+    fn named() {
+        assert_eq!(Color::from_name("red"), "f00".parse().ok());
+        assert_eq!(Color::from_name("grey"), Color::from_name("gray"));
+        assert_eq!(Color::from_name("not_a_color"), None);
+    }
+}
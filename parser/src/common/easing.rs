@@ -0,0 +1,42 @@
+use super::parser::Value;
+use crate::config::{
+    error::{Error, ErrorKind},
+    generic::ConfigItem,
+};
+
+/// The easing of the flash (size and color) of atoms during an operation.
+/// Every easing returns to the initial value at the end of the operation.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+pub enum Easing {
+    /// Linearly up to the peak at the middle of the operation and back down
+    #[default]
+    Triangle,
+    /// Smoothly up and back down along a half sine-wave
+    Sine,
+    /// Jumps to the peak at the start and back at the end of the operation
+    Pulse,
+}
+
+impl Easing {
+    /// Gets the [Easing] with the passed `name`,
+    /// or [None] if no such [Easing] exists
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "triangle" => Some(Self::Triangle),
+            "sine" => Some(Self::Sine),
+            "pulse" => Some(Self::Pulse),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<ConfigItem> for Easing {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => Self::from_name(&s),
+            _ => None,
+        }
+        .ok_or_else(|| ErrorKind::WrongType("'triangle' | 'sine' | 'pulse'").into())
+    }
+}
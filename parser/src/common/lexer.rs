@@ -2,6 +2,8 @@
 //!
 //! - Primitive [Value]s and [functions to lex them][value]
 
+use std::fmt::Display;
+
 use winnow::{
     combinator::delimited,
     error::ParserError,
@@ -21,12 +23,41 @@ pub enum Value<T> {
     Number(T),
     /// A number
     Percentage(T),
+    /// A size relative to a base font size (in `em`)
+    Em(T),
     /// A boolean
     Boolean(T),
     /// A color
     Color(T),
 }
 
+/// A number directly followed by a digit separator (`,` or `_`) and more digits
+/// (e.g., `1,5` or `1_000`).
+/// Such numbers are rejected, as the separator may be meant as decimal or thousands separator.
+/// A `,` is only rejected where a single number is expected,
+/// as it separates the elements of tuples and sets (e.g., `(1,5)`);
+/// see [value::reject_decimal_comma].
+///
+/// Available as the [cause][winnow::error::ContextError::cause] of the lex-error,
+/// which is located at the start of the number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedNumberError {
+    /// The offending literal
+    pub literal: String,
+}
+
+impl Display for MalformedNumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "malformed number `{}`: use `.` as decimal separator and no digit separators",
+            self.literal
+        )
+    }
+}
+
+impl std::error::Error for MalformedNumberError {}
+
 pub fn delimited_by<
     I: Stream + StreamIsPartial + Compare<&'static str> + FindSlice<&'static str>,
     E: ParserError<I>,
@@ -46,6 +77,7 @@ pub mod value {
     use winnow::{
         ascii::{digit0, digit1},
         combinator::{alt, opt, preceded, terminated},
+        error::{ErrMode, FromExternalError},
         stream::{AsChar, Compare, FindSlice, SliceLen, Stream, StreamIsPartial},
         token::{one_of, take_while},
         ModalResult, Parser,
@@ -83,13 +115,14 @@ pub mod value {
         I::Token: AsChar,
         I::Slice: SliceLen,
     {
+        let start = *input;
         let mut src = *input;
         type R<I> = (
             Option<<I as Stream>::Slice>,
             <I as Stream>::Slice,
             Option<(<I as Stream>::Slice, <I as Stream>::Slice)>,
         );
-        (opt("-"), digit1, opt((".", digit0)))
+        let number = (opt("-"), digit1, opt((".", digit0)))
             // get the parsed slice of the input
             // by summing the length of the individual fields
             .map(|(n, a, b): R<I>| {
@@ -99,7 +132,57 @@ pub mod value {
             })
             // and then getting a slice of the input of the specified length
             .map(|l| src.next_slice(l))
-            .parse_next(input)
+            .parse_next(input)?;
+
+        // Reject digit separators directly followed by more digits (e.g., `1_000`)
+        reject_separator(start, input, '_')?;
+
+        Ok(number)
+    }
+
+    /// Rejects a `,` directly following a number and followed by more digits (e.g., `1,5`)
+    /// with a [MalformedNumberError] located at the start of the number.
+    /// `start` is the input at the start of the number and `input` the input after the number.
+    ///
+    /// As a `,` also separates the elements of tuples and sets (e.g., `(1,5)`),
+    /// this has to be checked by the lexers wherever a single number is expected.
+    pub fn reject_decimal_comma<I: Stream + Copy>(start: I, input: &mut I) -> ModalResult<()>
+    where
+        I::Token: AsChar,
+    {
+        reject_separator(start, input, ',')
+    }
+
+    /// Rejects the `separator` directly following a number and followed by more digits
+    /// with a [MalformedNumberError] containing the full literal.
+    /// `start` is the input at the start of the number and `input` the input after the number.
+    fn reject_separator<I: Stream + Copy>(
+        start: I,
+        input: &mut I,
+        separator: char,
+    ) -> ModalResult<()>
+    where
+        I::Token: AsChar,
+    {
+        let mut rest = *input;
+        if rest.next_token().map(AsChar::as_char) != Some(separator)
+            || !rest
+                .next_token()
+                .is_some_and(|c| c.as_char().is_ascii_digit())
+        {
+            return Ok(());
+        }
+
+        let mut literal = String::new();
+        let mut rest = start;
+        while let Some(c) = rest.next_token().map(AsChar::as_char) {
+            if !(c.is_ascii_digit() || matches!(c, '-' | '.' | '_') || c == separator) {
+                break;
+            }
+            literal.push(c);
+        }
+        *input = start;
+        Err(ErrMode::from_external_error(input, MalformedNumberError { literal }).cut())
     }
 
     /// Tries to parse a [Value::Number].
@@ -126,6 +209,19 @@ pub mod value {
             .parse_next(input)
     }
 
+    /// Tries to parse a [Value::Em].
+    pub fn em<I: Stream + StreamIsPartial + Compare<&'static str> + Copy>(
+        input: &mut I,
+    ) -> ModalResult<Value<<I as Stream>::Slice>>
+    where
+        I::Token: AsChar + Clone,
+        I::Slice: SliceLen,
+    {
+        terminated(number_raw, "em")
+            .map(Value::Em)
+            .parse_next(input)
+    }
+
     /// Tries to parse a [Value::Boolean].
     pub fn boolean<I: Stream + StreamIsPartial + Compare<&'static str>>(
         input: &mut I,
@@ -133,7 +229,8 @@ pub mod value {
         alt(("true", "false")).map(Value::Boolean).parse_next(input)
     }
 
-    /// Tries to parse a [Value::Boolean].
+    /// Tries to parse a [Value::Color].
+    /// Accepts `#RRGGBBAA`, `#RRGGBB`, `#RGBA`, and `#RGB`.
     pub fn color<I: Stream + StreamIsPartial + Compare<&'static str>>(
         input: &mut I,
     ) -> ModalResult<Value<I::Slice>>
@@ -145,6 +242,8 @@ pub mod value {
             alt((
                 take_while(8, AsChar::is_hex_digit),
                 take_while(6, AsChar::is_hex_digit),
+                take_while(4, AsChar::is_hex_digit),
+                take_while(3, AsChar::is_hex_digit),
             )),
         )
         .map(Value::Color)
@@ -161,7 +260,7 @@ pub mod value {
         I::Token: AsChar + Clone,
         I::Slice: SliceLen,
     {
-        alt((string, regex, percentage, number, boolean, color)).parse_next(input)
+        alt((string, regex, percentage, em, number, boolean, color)).parse_next(input)
     }
 }
 
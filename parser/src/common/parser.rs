@@ -7,7 +7,7 @@
 use super::{color::Color, lexer::GenericToken, percentage::Percentage};
 use fraction::Fraction;
 use regex::Regex;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use token::{
     element_separator, ignore_comments, set_close, set_open, tuple_close, tuple_open,
     value_or_identifier,
@@ -33,6 +33,8 @@ pub enum Value {
     Number(Fraction),
     /// A percentage
     Percentage(Percentage),
+    /// A size relative to a base font size (in `em`)
+    Em(Fraction),
     /// A boolean
     Boolean(bool),
     /// A color
@@ -45,16 +47,17 @@ pub enum Value {
     Tuple(Vec<Value>),
 }
 
-/// For tests, allow comparing [Value]s.
+/// Allow comparing [Value]s (e.g., to find the same key when merging configs).
 /// In particular, check if [Value::Regex]s were compiled from the same source string
 /// (and use [PartialEq] for all other variants).
-#[cfg(test)]
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Regex(a), Value::Regex(b)) => a.as_str() == b.as_str(),
             (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Percentage(a), Value::Percentage(b)) => a == b,
+            (Value::Em(a), Value::Em(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
             (Value::Color(a), Value::Color(b)) => a == b,
             (Value::Identifier(a), Value::Identifier(b)) => a == b,
@@ -77,6 +80,90 @@ impl Value {
     }
 }
 
+/// Maximum number of decimals when displaying numbers which have no finite decimal representation
+const MAX_DECIMALS: usize = 10;
+
+/// Display a [Value] in the syntax it is parsed from
+/// (i.e., the displayed [Value] can be lexed and parsed again).
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        /// Writes the `values` separated by `, ` and enclosed in `open` and `close`
+        fn list(
+            f: &mut std::fmt::Formatter<'_>,
+            open: &str,
+            values: &[Value],
+            close: &str,
+        ) -> std::fmt::Result {
+            write!(f, "{open}")?;
+            for (idx, value) in values.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{value}")?;
+            }
+            write!(f, "{close}")
+        }
+
+        match self {
+            Value::String(s) => write!(f, "\"{s}\""),
+            // Regexes are lexed including their delimiters,
+            // so only add them when missing (e.g., for manually created regexes)
+            Value::Regex(r)
+                if r.as_str().len() >= 2
+                    && r.as_str().starts_with('^')
+                    && r.as_str().ends_with('$') =>
+            {
+                write!(f, "{}", r.as_str())
+            }
+            Value::Regex(r) => write!(f, "^{}$", r.as_str()),
+            Value::Number(n) => write_decimal(f, n),
+            Value::Percentage(Percentage(p)) => {
+                write_decimal(f, p)?;
+                write!(f, "%")
+            }
+            Value::Em(n) => {
+                write_decimal(f, n)?;
+                write!(f, "em")
+            }
+            Value::Boolean(b) => write!(f, "{b}"),
+            Value::Color(Color { r, g, b, a }) => write!(f, "#{r:02x}{g:02x}{b:02x}{a:02x}"),
+            Value::Identifier(i) => write!(f, "{i}"),
+            Value::Set(s) => list(f, "{", s, "}"),
+            Value::Tuple(t) => list(f, "(", t, ")"),
+        }
+    }
+}
+
+/// Writes the passed [Fraction] as a decimal number.
+/// Numbers without a finite decimal representation are rounded to [MAX_DECIMALS] decimals.
+fn write_decimal(f: &mut std::fmt::Formatter<'_>, n: &Fraction) -> std::fmt::Result {
+    let Some(&denominator) = n.denom() else {
+        // Not a number or infinite
+        return write!(f, "{n}");
+    };
+
+    // A fraction has a finite decimal representation iff its (reduced) denominator
+    // only has the prime factors `2` and `5`;
+    // the number of decimals is then the larger exponent.
+    let mut remaining = denominator;
+    let (mut twos, mut fives) = (0, 0);
+    while remaining % 2 == 0 {
+        remaining /= 2;
+        twos += 1;
+    }
+    while remaining % 5 == 0 {
+        remaining /= 5;
+        fives += 1;
+    }
+    let decimals = if remaining == 1 {
+        twos.max(fives)
+    } else {
+        MAX_DECIMALS
+    };
+
+    write!(f, "{n:.decimals$}")
+}
+
 /// Create a parser to a list-like [Value] (e.g., tuples or sets) using the passed parameters.
 pub fn list_like<I: Stream + StreamIsPartial, E: ParserError<I>, TO, ES, TC, IG>(
     open: impl Parser<I, TO, E>,
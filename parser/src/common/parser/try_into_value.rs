@@ -145,6 +145,7 @@ impl<T: TryIntoValue> TryFrom<lexer::Value<T>> for Value {
             lexer::Value::Percentage(n) => {
                 Self::Percentage(n.percentage().map_err(TryIntoValueError::PercentageError)?)
             }
+            lexer::Value::Em(n) => Self::Em(n.number().map_err(TryIntoValueError::NumberError)?),
             lexer::Value::Boolean(b) => {
                 Self::Boolean(b.boolean().map_err(TryIntoValueError::BooleanError)?)
             }
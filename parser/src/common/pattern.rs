@@ -0,0 +1,38 @@
+use super::parser::Value;
+use crate::config::{
+    error::{Error, ErrorKind},
+    generic::ConfigItem,
+};
+
+/// The pattern an atom is filled with during an operation
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+pub enum Pattern {
+    #[default]
+    Solid,
+    Ring,
+    Hatch,
+}
+
+impl Pattern {
+    /// Gets the [Pattern] with the passed `name`,
+    /// or [None] if no such [Pattern] exists
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "solid" => Some(Self::Solid),
+            "ring" => Some(Self::Ring),
+            "hatch" => Some(Self::Hatch),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<ConfigItem> for Pattern {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => Self::from_name(&s),
+            _ => None,
+        }
+        .ok_or_else(|| ErrorKind::WrongType("'solid' | 'ring' | 'hatch'").into())
+    }
+}
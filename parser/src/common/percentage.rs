@@ -19,6 +19,29 @@ impl TryFrom<ConfigItem> for Percentage {
     }
 }
 
+/// A [Percentage] between `0%` and `100%` (inclusive).
+/// Converts from a [ConfigItem] like [Percentage],
+/// but fails with [ErrorKind::OutOfRange] for values outside of that range.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct UnitPercentage(pub Percentage);
+
+impl TryFrom<ConfigItem> for UnitPercentage {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        let percentage: Percentage = value.try_into()?;
+        if percentage.0 < Fraction::from(0) || percentage.0 > Fraction::from(100) {
+            return Err(ErrorKind::OutOfRange("percentage between 0% and 100%").into());
+        }
+        Ok(Self(percentage))
+    }
+}
+
+impl From<UnitPercentage> for Percentage {
+    fn from(value: UnitPercentage) -> Self {
+        value.0
+    }
+}
+
 impl From<Percentage> for Fraction {
     fn from(value: Percentage) -> Self {
         value.0 / 100
@@ -0,0 +1,40 @@
+use super::parser::Value;
+use crate::config::{
+    error::{Error, ErrorKind},
+    generic::ConfigItem,
+};
+
+/// The shape an atom is drawn as
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+pub enum Shape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    Cross,
+}
+
+impl Shape {
+    /// Gets the [Shape] with the passed `name`,
+    /// or [None] if no such [Shape] exists
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "circle" => Some(Self::Circle),
+            "square" => Some(Self::Square),
+            "diamond" => Some(Self::Diamond),
+            "cross" => Some(Self::Cross),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<ConfigItem> for Shape {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => Self::from_name(&s),
+            _ => None,
+        }
+        .ok_or_else(|| ErrorKind::WrongType("'circle' | 'square' | 'diamond' | 'cross'").into())
+    }
+}
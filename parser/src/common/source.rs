@@ -0,0 +1,58 @@
+//! Normalization of source-texts before lexing.
+
+use std::borrow::Cow;
+
+/// The UTF-8 byte order mark
+const BOM: char = '\u{FEFF}';
+
+/// Normalizes the passed source-`text`, so that files saved by different editors lex identically:
+/// - A leading byte order mark is removed
+/// - Windows line endings (`\r\n`) are converted to `\n`
+///
+/// Only allocates if the `text` needs to be changed.
+/// Byte-offsets (e.g., of errors) of the lexers refer to the normalized text.
+pub fn normalize_source(text: &str) -> Cow<'_, str> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::instructions;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_source("\u{FEFF}a\r\nb\n"), "a\nb\n");
+        assert_eq!(
+            normalize_source("a\rb"),
+            "a\rb",
+            "Lone carriage return was changed"
+        );
+        assert!(matches!(normalize_source("a\nb"), Cow::Borrowed(_)));
+        assert!(matches!(
+            normalize_source("\u{FEFF}a\nb"),
+            Cow::Borrowed("a\nb")
+        ));
+    }
+
+    /// Instructions with a byte order mark and Windows line endings
+    /// should parse identically to the same instructions with `\n`-line endings.
+    #[test]
+    fn instructions_bom_crlf() {
+        let lf = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.naviz"
+        ));
+        let crlf = format!("{BOM}{}", lf.replace('\n', "\r\n"));
+
+        assert_eq!(
+            instructions(&normalize_source(&crlf)).expect("Failed to convert"),
+            instructions(lf).expect("Failed to convert")
+        );
+    }
+}
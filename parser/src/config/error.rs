@@ -10,6 +10,7 @@ use itertools::Itertools;
 pub enum ErrorKind {
     MissingField(&'static str),
     WrongType(&'static str),
+    OutOfRange(&'static str),
 }
 
 impl Display for ErrorKind {
@@ -17,6 +18,7 @@ impl Display for ErrorKind {
         match self {
             Self::MissingField(f) => write!(fmt, "missing field: {f}"),
             Self::WrongType(t) => write!(fmt, "wrong type: expected {t}"),
+            Self::OutOfRange(r) => write!(fmt, "out of range: expected {r}"),
         }
     }
 }
@@ -24,7 +26,8 @@ impl Display for ErrorKind {
 /// An error, which has a [ErrorKind] and collects the tagged path.
 /// Not constructed directly, but though [ErrorKind::into][Into<Error>::into].
 ///
-/// See [TagError] to tag errors.
+/// See [TagError] to tag errors
+/// and [LocateError] to attach a location.
 #[derive(Debug)]
 pub struct Error {
     /// The type of error
@@ -32,6 +35,8 @@ pub struct Error {
     /// A path of tags
     /// (usually in reverse order as closest tag to error location is tagged first)
     path: Vec<&'static str>,
+    /// The byte-offset of the item closest to the error location in the source (if known)
+    offset: Option<usize>,
 }
 
 impl Display for Error {
@@ -54,6 +59,15 @@ impl Error {
     pub fn path(&self) -> &[&'static str] {
         self.path.as_slice()
     }
+
+    /// The byte-offset of the item closest to the error location in the source.
+    /// Is [None] if the config was not created with offsets
+    /// or the error occurred at the top-level.
+    ///
+    /// See [LocateError] to attach a location.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -61,6 +75,7 @@ impl From<ErrorKind> for Error {
         Self {
             kind: value,
             path: Vec::new(),
+            offset: None,
         }
     }
 }
@@ -85,3 +100,26 @@ impl TagError for Error {
         self
     }
 }
+
+/// A trait that allows attaching a location to an [Error] using [LocateError::locate].
+pub trait LocateError {
+    /// Sets the byte-`offset` of the [Error] if it does not have one yet,
+    /// so that the location closest to the error is kept.
+    fn locate(self, offset: Option<usize>) -> Self;
+}
+
+impl<T> LocateError for Result<T, Error> {
+    fn locate(mut self, offset: Option<usize>) -> Self {
+        if let Err(e) = &mut self {
+            e.offset = e.offset.or(offset);
+        }
+        self
+    }
+}
+
+impl LocateError for Error {
+    fn locate(mut self, offset: Option<usize>) -> Self {
+        self.offset = self.offset.or(offset);
+        self
+    }
+}
@@ -2,29 +2,35 @@
 //! and allow parsing to concrete configuration using the provided helper-functions.
 
 use super::{
-    error::{Error, ErrorKind, TagError},
+    error::{Error, ErrorKind, LocateError, TagError},
     parser::{self, Value},
 };
 use crate::common::{color::Color, percentage::Percentage};
 use fraction::Fraction;
 use itertools::{Either, Itertools};
 use regex::Regex;
-use std::{collections::HashMap, hash::Hash, mem::replace};
+use std::{collections::HashMap, hash::Hash, mem::replace, ops::Range};
 
 /// A generic [Config] to later parse.
 /// The first element is the target mappings, which map from an identifier,
-/// the second element is all the mappings which mapped from some other value.
-#[derive(Default)]
-pub struct Config(pub HashMap<String, ConfigItem>, pub Box<Maps>);
+/// the second element is all the mappings which mapped from some other value,
+/// the third element is the byte-offsets of the target mappings in the source (if known).
+#[derive(Default, Clone)]
+pub struct Config(
+    pub HashMap<String, ConfigItem>,
+    pub Box<Maps>,
+    pub HashMap<String, usize>,
+);
 
 /// Collects properties which do not have an identifier as key,
 /// but some other value.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Maps {
     pub string: HashMap<String, Value>,
     pub regex: Vec<(Regex, Value)>,
     pub number: HashMap<Fraction, Value>,
     pub percentage: HashMap<Percentage, Value>,
+    pub em: HashMap<Fraction, Value>,
     pub boolean: HashMap<bool, Value>,
     pub color: HashMap<Color, Value>,
     pub tuple: Vec<(Vec<Value>, Value)>,
@@ -33,6 +39,7 @@ pub struct Maps {
 
 /// A [ConfigItem] representing either a [Value][ConfigItem::Value], a [Struct][ConfigItem::Struct],
 /// or a [Map][ConfigItem::Map]
+#[derive(Clone)]
 pub enum ConfigItem {
     /// A singular value
     Value(Value),
@@ -44,15 +51,29 @@ pub enum ConfigItem {
 
 impl Config {
     /// Insert a [parser::ConfigItem] into this [Config].
+    /// The `spans` are used to look up the byte-offset of the item (see [Config::from_spanned]).
     /// Returns [`Some(old_value)`][Some] if an old value was overwritten,
     /// or [`None`] if the value did not previously exist.
-    fn insert(&mut self, value: parser::ConfigItem) -> Option<ConfigItem> {
-        let Config(target, maps) = self;
+    fn insert(&mut self, value: parser::ConfigItem, spans: &[Range<usize>]) -> Option<ConfigItem> {
+        let Config(target, maps, offsets) = self;
+        // The byte-offset of the token at the passed `position` (counted from the end)
+        let offset = |position: usize| {
+            spans
+                .len()
+                .checked_sub(position)
+                .and_then(|index| spans.get(index))
+                .map(|span| span.start)
+        };
         match value {
             // Insert a property:
-            parser::ConfigItem::Property(key, value) => match key {
+            parser::ConfigItem::Property(key, value, position) => match key {
                 // Identifier: Insert into target-mappings (of current struct)
-                parser::Value::Identifier(id) => target.insert(id, ConfigItem::Value(value)),
+                parser::Value::Identifier(id) => {
+                    if let Some(offset) = offset(position) {
+                        offsets.insert(id.clone(), offset);
+                    }
+                    target.insert(id, ConfigItem::Value(value))
+                }
                 // Other value: Insert into other-mappings (which can then be parsed into Maps)
                 parser::Value::String(s) => maps.string.insert(s, value).map(ConfigItem::Value),
                 parser::Value::Regex(r) => {
@@ -63,6 +84,7 @@ impl Config {
                 parser::Value::Percentage(p) => {
                     maps.percentage.insert(p, value).map(ConfigItem::Value)
                 }
+                parser::Value::Em(n) => maps.em.insert(n, value).map(ConfigItem::Value),
                 parser::Value::Boolean(b) => maps.boolean.insert(b, value).map(ConfigItem::Value),
                 parser::Value::Color(c) => maps.color.insert(c, value).map(ConfigItem::Value),
                 parser::Value::Tuple(t) => {
@@ -75,37 +97,159 @@ impl Config {
                 }
             },
             // Insert a block (parses as a new struct):
-            parser::ConfigItem::Block(key, value) => {
-                target.insert(key, ConfigItem::Struct(value.into()))
+            parser::ConfigItem::Block(key, value, position) => {
+                if let Some(offset) = offset(position) {
+                    offsets.insert(key.clone(), offset);
+                }
+                target.insert(key, ConfigItem::Struct(Config::from_spanned(value, spans)))
             }
             // Insert a named block (parses as a map of new structs):
-            parser::ConfigItem::NamedBlock(key, name, value) => {
+            // The offset of the first named block is kept for the whole map.
+            parser::ConfigItem::NamedBlock(key, name, value, position) => {
+                if let Some(offset) = offset(position) {
+                    offsets.entry(key.clone()).or_insert(offset);
+                }
+                let value = Config::from_spanned(value, spans);
                 let t = target
                     .entry(key)
                     .or_insert_with(|| ConfigItem::Map(Vec::new()));
                 if let ConfigItem::Map(t) = t {
-                    t.push((name, value.into()));
+                    t.push((name, value));
                     None
                 } else {
-                    Some(replace(t, ConfigItem::Map(vec![(name, value.into())])))
+                    Some(replace(t, ConfigItem::Map(vec![(name, value)])))
                 }
             }
+            // Comments and blank lines are not kept in the generic config
+            parser::ConfigItem::Comment(_)
+            | parser::ConfigItem::TrailingComment(_)
+            | parser::ConfigItem::BlankLine => None,
         }
     }
 }
 
 impl From<parser::Config> for Config {
-    /// Converts a [parser::Config] into a [Config].
+    /// Converts a [parser::Config] into a [Config] without any byte-offsets.
     /// This silently overwrites duplicated values.
+    ///
+    /// See [Config::from_spanned] to keep the byte-offsets.
     fn from(value: parser::Config) -> Self {
+        Self::from_spanned(value, &[])
+    }
+}
+
+impl Config {
+    /// Converts a [parser::Config] into a [Config],
+    /// keeping the byte-offsets of the items in the source.
+    /// This silently overwrites duplicated values.
+    ///
+    /// The `spans` are the spans of the tokens the [parser::Config] was parsed from
+    /// (see [lex_with_spans][super::lexer::lex_with_spans]).
+    /// Errors during conversion of the resulting [Config] will then carry
+    /// the [offset][Error::offset] of the item closest to the error.
+    pub fn from_spanned(value: parser::Config, spans: &[Range<usize>]) -> Self {
         let mut target = Config::default();
         for item in value {
-            target.insert(item);
+            target.insert(item, spans);
         }
         target
     }
 }
 
+impl Config {
+    /// Merges the `patch` into this [Config].
+    ///
+    /// Items present in the `patch` override the items of this [Config],
+    /// while items absent from the `patch` are kept.
+    /// [Struct][ConfigItem::Struct]s are merged recursively,
+    /// [Map][ConfigItem::Map]-entries are merged recursively by their name
+    /// (new names are appended).
+    ///
+    /// The byte-offsets of the `patch` point into another source,
+    /// so items taken from the `patch` (and structs merged with the `patch`)
+    /// do not keep their byte-offsets.
+    pub fn merge(&mut self, Config(patch_target, patch_maps, _): Config) {
+        let Config(target, maps, offsets) = self;
+
+        for (key, patch_item) in patch_target {
+            offsets.remove(&key);
+            match (target.get_mut(&key), patch_item) {
+                (Some(ConfigItem::Struct(base)), ConfigItem::Struct(patch)) => base.merge(patch),
+                (Some(ConfigItem::Map(base)), ConfigItem::Map(patch)) => {
+                    for (name, mut patch) in patch {
+                        match base.iter_mut().find(|(n, _)| *n == name) {
+                            Some((_, base)) => base.merge(patch),
+                            None => {
+                                patch.clear_offsets();
+                                base.push((name, patch));
+                            }
+                        }
+                    }
+                }
+                (_, mut patch_item) => {
+                    patch_item.clear_offsets();
+                    target.insert(key, patch_item);
+                }
+            }
+        }
+
+        maps.merge(*patch_maps);
+    }
+
+    /// Recursively removes all byte-offsets from this [Config].
+    fn clear_offsets(&mut self) {
+        self.2.clear();
+        for item in self.0.values_mut() {
+            item.clear_offsets();
+        }
+    }
+}
+
+impl ConfigItem {
+    /// Recursively removes all byte-offsets from the [Config]s in this [ConfigItem].
+    fn clear_offsets(&mut self) {
+        match self {
+            Self::Value(_) => {}
+            Self::Struct(config) => config.clear_offsets(),
+            Self::Map(entries) => entries
+                .iter_mut()
+                .for_each(|(_, config)| config.clear_offsets()),
+        }
+    }
+}
+
+impl Maps {
+    /// Merges the `patch` into these [Maps].
+    /// Values present in the `patch` override the values of these [Maps].
+    fn merge(&mut self, patch: Maps) {
+        /// Overrides the value with an equal key or appends the pair
+        fn merge_vec<K>(
+            base: &mut Vec<(K, Value)>,
+            patch: Vec<(K, Value)>,
+            eq: fn(&K, &K) -> bool,
+        ) {
+            for (key, value) in patch {
+                match base.iter_mut().find(|(k, _)| eq(k, &key)) {
+                    Some((_, v)) => *v = value,
+                    None => base.push((key, value)),
+                }
+            }
+        }
+
+        self.string.extend(patch.string);
+        merge_vec(&mut self.regex, patch.regex, |a, b| {
+            a.as_str() == b.as_str()
+        });
+        self.number.extend(patch.number);
+        self.percentage.extend(patch.percentage);
+        self.em.extend(patch.em);
+        self.boolean.extend(patch.boolean);
+        self.color.extend(patch.color);
+        merge_vec(&mut self.tuple, patch.tuple, |a, b| a == b);
+        merge_vec(&mut self.set, patch.set, |a, b| a == b);
+    }
+}
+
 /// Get and remove a raw [ConfigItem] from the [Config] at the specified `name`.
 /// Will return [ErrorKind::MissingField] if not found.
 #[inline]
@@ -116,6 +260,12 @@ pub fn get_item_raw(config: &mut Config, name: &'static str) -> Result<ConfigIte
         .ok_or(ErrorKind::MissingField(name).into())
 }
 
+/// Get the byte-offset of the item at the specified `name` in the [Config] (if known).
+#[inline]
+fn get_offset(config: &Config, name: &str) -> Option<usize> {
+    config.2.get(name).copied()
+}
+
 /// Get a value from a [Config].
 /// Will return [ErrorKind::MissingField] if not found
 /// and all errors the target-type returns during conversion using [TryInto::try_into].
@@ -124,7 +274,42 @@ pub fn get_item<T>(config: &mut Config, name: &'static str) -> Result<T, Error>
 where
     ConfigItem: TryInto<T, Error = Error>,
 {
-    get_item_raw(config, name)?.try_into().tag(name)
+    let offset = get_offset(config, name);
+    get_item_raw(config, name)?
+        .try_into()
+        .tag(name)
+        .locate(offset)
+}
+
+/// Get an optional value from a [Config].
+/// Will return [None] if not found
+/// and all errors the target-type returns during conversion using [TryInto::try_into].
+#[inline]
+pub fn get_item_optional<T>(config: &mut Config, name: &'static str) -> Result<Option<T>, Error>
+where
+    ConfigItem: TryInto<T, Error = Error>,
+{
+    let offset = get_offset(config, name);
+    config
+        .0
+        .remove(name)
+        .map(|item| item.try_into().tag(name).locate(offset))
+        .transpose()
+}
+
+/// Get an optional struct from a [Config].
+/// Will return [None] if not found
+/// and all errors the target-type returns during conversion using [TryInto::try_into].
+#[inline]
+pub fn get_item_struct_optional<T: TryFrom<Config, Error = Error>>(
+    config: &mut Config,
+    name: &'static str,
+) -> Result<Option<T>, Error> {
+    if config.0.contains_key(name) {
+        get_item_struct(config, name).map(Some)
+    } else {
+        Ok(None)
+    }
 }
 
 /// Get a struct from a [Config].
@@ -135,11 +320,13 @@ pub fn get_item_struct<T: TryFrom<Config, Error = Error>>(
     config: &mut Config,
     name: &'static str,
 ) -> Result<T, Error> {
+    let offset = get_offset(config, name);
     match get_item_raw(config, name)? {
         ConfigItem::Struct(s) => s.try_into(),
         _ => Err(ErrorKind::WrongType("block").into()),
     }
     .tag(name)
+    .locate(offset)
 }
 
 impl TryFrom<ConfigItem> for String {
@@ -172,6 +359,40 @@ impl TryFrom<ConfigItem> for Fraction {
     }
 }
 
+/// Natural numbers (including `0`).
+/// Fails with [ErrorKind::OutOfRange] for negative or fractional numbers.
+impl TryFrom<ConfigItem> for usize {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        let number: Fraction = value.try_into()?;
+        if number.is_sign_negative() && number != Fraction::from(0) {
+            return Err(ErrorKind::OutOfRange("natural number").into());
+        }
+        match (number.numer(), number.denom()) {
+            (Some(&numer), Some(1)) => {
+                usize::try_from(numer).map_err(|_| ErrorKind::OutOfRange("natural number").into())
+            }
+            _ => Err(ErrorKind::OutOfRange("natural number").into()),
+        }
+    }
+}
+
+impl TryFrom<ConfigItem> for Vec<Fraction> {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match value {
+            ConfigItem::Value(Value::Tuple(t)) => t
+                .into_iter()
+                .map(|v| match v {
+                    Value::Number(n) => Ok(n),
+                    _ => Err(ErrorKind::WrongType("tuple(number, ...)").into()),
+                })
+                .collect(),
+            _ => Err(ErrorKind::WrongType("tuple(number, ...)").into()),
+        }
+    }
+}
+
 impl TryFrom<ConfigItem> for bool {
     type Error = Error;
     fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
@@ -187,6 +408,9 @@ impl TryFrom<ConfigItem> for Color {
     fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
         match value {
             ConfigItem::Value(Value::Color(c)) => Ok(c),
+            ConfigItem::Value(Value::Identifier(name)) => {
+                Color::from_name(&name).ok_or_else(|| ErrorKind::WrongType("color").into())
+            }
             _ => Err(ErrorKind::WrongType("color").into()),
         }
     }
@@ -200,6 +424,7 @@ pub fn get_item_named_struct<T, O>(
 where
     Config: TryInto<T, Error = Error>,
 {
+    let offset = get_offset(config, name);
     match get_item_raw(config, name)? {
         ConfigItem::Map(s) => s
             .into_iter()
@@ -209,6 +434,7 @@ where
         _ => Err(ErrorKind::WrongType("block").into()),
     }
     .tag(name)
+    .locate(offset)
 }
 
 // #[inline]
@@ -281,6 +507,7 @@ where
     M: MapOrVec<K, V>,
     MI: MapOrVec<K, Value>,
 {
+    let offset = get_offset(config, name);
     // Get the `Maps` of the specified field
     let maps = config
         .0
@@ -291,7 +518,7 @@ where
     match maps {
         // Needs to be a struct
         &mut ConfigItem::Struct(ref mut x) => {
-            let Config(_, ref mut m) = x;
+            let Config(_, ref mut m, _) = x;
             let target = K::get(m); // The target map
             let (taken, left): (M, MI) = std::mem::take(target) // Temporarily take the target map to partition
                 .into_iter()
@@ -308,6 +535,7 @@ where
         _ => Err(ErrorKind::WrongType("block").into()),
     }
     .tag(name)
+    .locate(offset)
 }
 
 /// A trait that marks types which are either a [Vec<(K, V)>] or a [HashMap<K, V>].
@@ -3,12 +3,14 @@
 //! Use [lex] to lex some input.
 
 use crate::{common, ParseError};
+use std::ops::Range;
 use token::*;
 use winnow::{
     ascii::multispace0,
-    combinator::{delimited, repeat},
+    combinator::opt,
+    error::{ContextError, ErrMode},
     prelude::*,
-    stream::{AsChar, Compare, FindSlice, SliceLen, Stream, StreamIsPartial},
+    stream::{AsChar, Compare, FindSlice, LocatingSlice, SliceLen, Stream, StreamIsPartial},
 };
 
 // Re-export the common lexer
@@ -29,6 +31,10 @@ pub enum Token<T> {
     Value(Value<T>),
     /// A comment, either single- or multiline
     Comment(T),
+    /// A comment on the same line as the previous token (see [lex_with_layout])
+    TrailingComment(T),
+    /// An empty line between two tokens (see [lex_with_layout])
+    BlankLine,
     /// Opening-symbol for a tuple
     TupleOpen,
     /// Closing-symbol for a tuple
@@ -58,7 +64,7 @@ impl<T> From<Token<T>> for Option<GenericToken<T>> {
         match value {
             Token::Identifier(i) => Some(GenericToken::Identifier(i)),
             Token::Value(v) => Some(GenericToken::Value(v)),
-            Token::Comment(c) => Some(GenericToken::Comment(c)),
+            Token::Comment(c) | Token::TrailingComment(c) => Some(GenericToken::Comment(c)),
             Token::TupleOpen => Some(GenericToken::TupleOpen),
             Token::TupleClose => Some(GenericToken::TupleClose),
             // also convert from overloaded tokens
@@ -86,7 +92,99 @@ where
     <I as Stream>::Token: AsChar + Clone,
     I::Slice: SliceLen,
 {
-    repeat(0.., delimited(multispace0, token, multispace0)).parse(input)
+    tokens(token, |token| token).parse(input)
+}
+
+/// A [Token] together with its byte-[Range] in the input
+pub type Spanned<'a> = (Token<&'a str>, Range<usize>);
+
+/// Lexes a [str] into a [Vec] of [Token]s together with their byte-[Range]s in the `input`,
+/// or returns an [Err] if lexing failed.
+pub fn lex_with_spans(input: &str) -> Result<Vec<Spanned<'_>>, ParseError<LocatingSlice<&str>>> {
+    tokens(token.with_span(), |(token, _)| token).parse(LocatingSlice::new(input))
+}
+
+/// Lexes all tokens (surrounded by whitespace) using the passed `token`-parser,
+/// where `as_token` gets the [Token] of an output of the `token`-parser.
+///
+/// Numbers with a decimal comma (e.g., `1,5`) are rejected
+/// unless they are an element of a tuple or a set
+/// (see [value::reject_decimal_comma]).
+fn tokens<I, O>(
+    mut token: impl Parser<I, O, ErrMode<ContextError>>,
+    as_token: impl Fn(&O) -> &Token<I::Slice>,
+) -> impl Parser<I, Vec<O>, ErrMode<ContextError>>
+where
+    I: Stream + StreamIsPartial + Copy,
+    <I as Stream>::Token: AsChar + Clone,
+{
+    move |input: &mut I| {
+        let mut tokens = Vec::new();
+        // For each open tuple, block, or set: Whether it contains a list of elements
+        let mut lists = Vec::new();
+        // Whether a value is expected next (i.e., a `{` opens a set instead of a block)
+        let mut value_expected = false;
+        loop {
+            multispace0.parse_next(input)?;
+            let start = *input;
+            let Some(output) = opt(token.by_ref()).parse_next(input)? else {
+                return Ok(tokens);
+            };
+            let current = as_token(&output);
+            match current {
+                Token::TupleOpen => lists.push(true),
+                Token::BlockOrSetOpen => lists.push(value_expected),
+                Token::TupleClose | Token::BlockOrSetClose => {
+                    lists.pop();
+                }
+                Token::Value(Value::Number(_)) if lists.last() != Some(&true) => {
+                    value::reject_decimal_comma(start, input)?
+                }
+                _ => {}
+            }
+            if !matches!(current, Token::Comment(_)) {
+                value_expected = match current {
+                    Token::Separator | Token::ElementSeparator | Token::TupleOpen => true,
+                    Token::BlockOrSetOpen => lists.last() == Some(&true),
+                    _ => false,
+                };
+            }
+            tokens.push(output);
+        }
+    }
+}
+
+/// Lexes a [str] into a [Vec] of [Token]s like [lex],
+/// but additionally keeps the layout of the `input`,
+/// which allows [serializing][super::parser::serialize] it again without losing formatting:
+/// Comments on the same line as the previous token are lexed as [Token::TrailingComment]s
+/// and each empty line between two tokens is lexed as a [Token::BlankLine].
+pub fn lex_with_layout(input: &str) -> Result<Vec<Token<&str>>, ParseError<LocatingSlice<&str>>> {
+    let newlines: Vec<_> = input.match_indices('\n').map(|(idx, _)| idx).collect();
+    // The (zero-based) line of the byte at the passed offset
+    let line = |offset: usize| newlines.partition_point(|&newline| newline < offset);
+
+    let mut tokens = Vec::new();
+    // The line of the last byte of the previous token
+    let mut previous_line = None;
+    for (token, span) in lex_with_spans(input)? {
+        let start_line = line(span.start);
+        match (previous_line, token) {
+            (Some(previous_line), Token::Comment(c)) if previous_line == start_line => {
+                tokens.push(Token::TrailingComment(c))
+            }
+            (previous_line, token) => {
+                if let Some(previous_line) = previous_line {
+                    let blank_lines = start_line.saturating_sub(previous_line + 1);
+                    tokens.extend(std::iter::repeat_n(Token::BlankLine, blank_lines));
+                }
+                tokens.push(token);
+            }
+        }
+        // Single-line comments include their line-ending
+        previous_line = Some(line(span.end.saturating_sub(1).max(span.start)));
+    }
+    Ok(tokens)
 }
 
 /// Lexers to lex individual [Token]s.
@@ -208,6 +306,143 @@ mod test {
         assert_eq!(lexed, expected);
     }
 
+    /// Relative sizes should be lexed with their unit,
+    /// while plain numbers stay numbers.
+    #[test]
+    fn em() {
+        let lexed = lex("a: 1.5em\nb: 2").expect("Failed to lex");
+
+        let expected = vec![
+            Token::Identifier("a"),
+            Token::Separator,
+            Token::Value(Value::Em("1.5")),
+            Token::Identifier("b"),
+            Token::Separator,
+            Token::Value(Value::Number("2")),
+        ];
+        assert_eq!(lexed, expected);
+    }
+
+    /// Numbers with digit separators should be rejected with the offending literal
+    /// (`,` only where a single number is expected),
+    /// while decimal points and separated tuple-elements (also without whitespace) are still accepted.
+    #[test]
+    fn number_separators() {
+        assert_eq!(
+            lex("x: 1.5").expect("Failed to lex decimal number"),
+            [
+                Token::Identifier("x"),
+                Token::Separator,
+                Token::Value(Value::Number("1.5")),
+            ]
+        );
+        assert_eq!(
+            lex("(1, 5)").expect("Failed to lex tuple"),
+            [
+                Token::TupleOpen,
+                Token::Value(Value::Number("1")),
+                Token::ElementSeparator,
+                Token::Value(Value::Number("5")),
+                Token::TupleClose,
+            ]
+        );
+
+        assert_eq!(
+            lex("(1,5)").expect("Failed to lex tuple without whitespace"),
+            [
+                Token::TupleOpen,
+                Token::Value(Value::Number("1")),
+                Token::ElementSeparator,
+                Token::Value(Value::Number("5")),
+                Token::TupleClose,
+            ]
+        );
+
+        assert_eq!(
+            lex("x: {1,5}")
+                .expect("Failed to lex set without whitespace")
+                .len(),
+            7
+        );
+
+        for (input, literal, offset) in [
+            ("x: 1,5", "1,5", 3),
+            ("block { x: 1,5 }", "1,5", 11),
+            ("x: 1_000", "1_000", 3),
+            ("x: -1_000.5", "-1_000.5", 3),
+            ("(1_000,5)", "1_000", 1),
+        ] {
+            let error = lex(input).expect_err("Lexed malformed number");
+            assert_eq!(error.offset(), offset, "Wrong location for {input}");
+            let cause = error
+                .inner()
+                .cause()
+                .and_then(|cause| cause.downcast_ref::<MalformedNumberError>())
+                .expect("Error has no MalformedNumberError as cause");
+            assert_eq!(cause.literal, literal);
+        }
+    }
+
+    /// The span of each token should cover its substring in the input.
+    #[test]
+    fn spans() {
+        let input = "block {\n\tkey: \"value\" // comment\n}";
+
+        let lexed = lex_with_spans(input).expect("Failed to lex");
+
+        let expected = [
+            (Token::Identifier("block"), "block"),
+            (Token::BlockOrSetOpen, "{"),
+            (Token::Identifier("key"), "key"),
+            (Token::Separator, ":"),
+            (Token::Value(Value::String("value")), "\"value\""),
+            (Token::Comment(" comment"), "// comment\n"),
+            (Token::BlockOrSetClose, "}"),
+        ];
+        assert_eq!(lexed.len(), expected.len());
+        for ((token, span), (expected_token, expected_text)) in lexed.into_iter().zip(expected) {
+            assert_eq!(token, expected_token);
+            assert_eq!(&input[span], expected_text);
+        }
+    }
+
+    /// Comments on the line of the previous token should be trailing comments
+    /// and empty lines should be kept as blank lines.
+    #[test]
+    fn layout() {
+        let input = "// standalone\n\
+                     a: 1 // trailing\n\
+                     \n\
+                     \n\
+                     b: 2 /* multi\n\
+                     line */\n\
+                     // next line\n\
+                     c: 3";
+
+        let lexed = lex_with_layout(input).expect("Failed to lex");
+
+        assert_eq!(
+            lexed,
+            [
+                Token::Comment(" standalone"),
+                Token::Identifier("a"),
+                Token::Separator,
+                Token::Value(Value::Number("1")),
+                Token::TrailingComment(" trailing"),
+                Token::BlankLine,
+                Token::BlankLine,
+                Token::Identifier("b"),
+                Token::Separator,
+                Token::Value(Value::Number("2")),
+                Token::TrailingComment(" multi\nline "),
+                Token::Comment(" next line"),
+                Token::Identifier("c"),
+                Token::Separator,
+                Token::Value(Value::Number("3")),
+            ]
+        );
+    }
+
     #[test]
     fn error_location_byte_offset_conversion() {
         let test_cases = vec![
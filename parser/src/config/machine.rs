@@ -2,12 +2,14 @@
 //! See documentation of file-format.
 
 use super::{
-    error::Error,
-    generic::{get_item, get_item_named_struct, get_item_struct, Config},
+    error::{Error, ErrorKind},
+    generic::{
+        get_item, get_item_named_struct, get_item_optional, get_item_struct, Config, ConfigItem,
+    },
     parser::Value,
     position::Position,
 };
-use fraction::Fraction;
+use fraction::{Fraction, Zero};
 use std::collections::HashMap;
 
 #[cfg_attr(test, derive(PartialEq))]
@@ -45,21 +47,150 @@ impl TryFrom<Config> for MachineConfig {
     }
 }
 
+impl MachineConfig {
+    /// Converts the passed `config` into a [MachineConfig],
+    /// inheriting all fields absent from `config` from the passed `defaults`
+    /// (e.g., the config of a bundled machine).
+    /// Fields present in `config` override the `defaults` (see [Config::merge]).
+    pub fn from_config_with_defaults(config: Config, defaults: &Config) -> Result<Self, Error> {
+        let mut merged = defaults.clone();
+        merged.merge(config);
+        merged.try_into()
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct MovementConfig {
     pub max_speed: Fraction,
+    pub model: MovementModel,
+    /// The maximum duration of a single move, if any.
+    /// Longer moves are sped up to finish within this time.
+    pub max_move_time: Option<Fraction>,
+    /// The path along which atoms are moved
+    pub interpolation: MoveInterpolation,
 }
 
 impl TryFrom<Config> for MovementConfig {
     type Error = Error;
     fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        let model = match get_item_optional(&mut value, "model")?.unwrap_or_default() {
+            MovementModelKind::ConstantJerk => MovementModel::ConstantJerk,
+            MovementModelKind::ConstantVelocity => MovementModel::ConstantVelocity,
+            MovementModelKind::Trapezoidal => MovementModel::Trapezoidal {
+                acceleration: get_item::<Positive>(&mut value, "acceleration")?.0,
+            },
+        };
         Ok(Self {
-            max_speed: get_item(&mut value, "max_speed")?,
+            max_speed: get_item::<Positive>(&mut value, "max_speed")?.0,
+            model,
+            max_move_time: get_item_optional::<Positive>(&mut value, "max_move_time")?.map(|p| p.0),
+            interpolation: get_item_optional(&mut value, "interpolation")?.unwrap_or_default(),
         })
     }
 }
 
+/// A number greater than `0`.
+/// Converts from a [ConfigItem] like [Fraction],
+/// but fails with [ErrorKind::OutOfRange] for values less than or equal to `0`.
+struct Positive(Fraction);
+
+impl TryFrom<ConfigItem> for Positive {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        let number: Fraction = value.try_into()?;
+        if number <= Fraction::zero() {
+            return Err(ErrorKind::OutOfRange("number greater than 0").into());
+        }
+        Ok(Self(number))
+    }
+}
+
+/// An integer greater than `0`.
+/// Converts from a [ConfigItem] like [usize],
+/// but fails with [ErrorKind::OutOfRange] for `0`, negative or fractional values.
+struct PositiveInteger(usize);
+
+impl TryFrom<ConfigItem> for PositiveInteger {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match usize::try_from(value) {
+            Ok(0) => Err(ErrorKind::OutOfRange("integer greater than 0").into()),
+            Ok(number) => Ok(Self(number)),
+            Err(e) if matches!(e.kind(), ErrorKind::OutOfRange(_)) => {
+                Err(ErrorKind::OutOfRange("integer greater than 0").into())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The model used to calculate movement-durations and -interpolations
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Default)]
+pub enum MovementModel {
+    /// Constant jerk, reaching `max_speed` at the center of the move
+    #[default]
+    ConstantJerk,
+    /// Constant velocity of `max_speed` for the whole move
+    ConstantVelocity,
+    /// Constant `acceleration` up to `max_speed`,
+    /// followed by constant velocity and constant deceleration
+    Trapezoidal { acceleration: Fraction },
+}
+
+/// The path along which atoms are moved
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Copy, Default)]
+pub enum MoveInterpolation {
+    /// Move along the direct (diagonal) connection
+    #[default]
+    Diagonal,
+    /// Move along an axis-aligned L-path:
+    /// first in x-direction, then in y-direction
+    ComponentWise,
+}
+
+impl TryFrom<ConfigItem> for MoveInterpolation {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        const EXPECTED: &str = "'diagonal' | 'component_wise'";
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => match s.as_str() {
+                "diagonal" => Ok(Self::Diagonal),
+                "component_wise" => Ok(Self::ComponentWise),
+                _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+            },
+            _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+        }
+    }
+}
+
+/// The kind of a [MovementModel] without its parameters
+#[derive(Default)]
+enum MovementModelKind {
+    #[default]
+    ConstantJerk,
+    ConstantVelocity,
+    Trapezoidal,
+}
+
+impl TryFrom<ConfigItem> for MovementModelKind {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        const EXPECTED: &str = "'constant_jerk' | 'constant_velocity' | 'trapezoidal'";
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => match s.as_str() {
+                "constant_jerk" => Ok(Self::ConstantJerk),
+                "constant_velocity" => Ok(Self::ConstantVelocity),
+                "trapezoidal" => Ok(Self::Trapezoidal),
+                _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+            },
+            _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+        }
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct TimeConfig {
@@ -68,6 +199,8 @@ pub struct TimeConfig {
     pub ry: Fraction,
     pub rz: Fraction,
     pub cz: Fraction,
+    pub measure: Fraction,
+    pub reset: Fraction,
     pub unit: String,
 }
 
@@ -80,6 +213,8 @@ impl TryFrom<Config> for TimeConfig {
             ry: get_item(&mut value, "ry")?,
             rz: get_item(&mut value, "rz")?,
             cz: get_item(&mut value, "cz")?,
+            measure: get_item_optional(&mut value, "measure")?.unwrap_or_default(),
+            reset: get_item_optional(&mut value, "reset")?.unwrap_or_default(),
             unit: get_item(&mut value, "unit")?,
         })
     }
@@ -105,20 +240,153 @@ impl TryFrom<Config> for DistanceConfig {
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct ZoneConfig {
-    pub from: Position,
-    pub to: Position,
+    pub shape: ZoneShape,
+    /// The maximum number of atoms in this zone, if limited
+    pub capacity: Option<usize>,
 }
 
 impl TryFrom<Config> for ZoneConfig {
     type Error = Error;
     fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        let shape = match get_item_optional(&mut value, "shape")?.unwrap_or_default() {
+            ZoneShapeKind::Rectangle => {
+                let from: Position = get_item(&mut value, "from")?;
+                let to: Position = get_item(&mut value, "to")?;
+                // Normalize the corners, so that `from` is never greater than `to`;
+                // as the rotation is around the center, this does not change the zone
+                ZoneShape::Rectangle {
+                    from: (from.0.min(to.0), from.1.min(to.1)),
+                    to: (from.0.max(to.0), from.1.max(to.1)),
+                    rotation: get_item_optional(&mut value, "rotation")?.unwrap_or_default(),
+                }
+            }
+            ZoneShapeKind::Circle => ZoneShape::Circle {
+                center: get_item(&mut value, "center")?,
+                radius: get_item(&mut value, "radius")?,
+            },
+        };
         Ok(Self {
-            from: get_item(&mut value, "from")?,
-            to: get_item(&mut value, "to")?,
+            shape,
+            capacity: get_item_optional::<PositiveInteger>(&mut value, "capacity")?.map(|c| c.0),
         })
     }
 }
 
+/// The shape of a [ZoneConfig]
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub enum ZoneShape {
+    /// A rectangle spanning `from` to `to`,
+    /// rotated clockwise by `rotation` degrees around its center
+    Rectangle {
+        from: Position,
+        to: Position,
+        rotation: Fraction,
+    },
+    /// A circle around `center` with the passed `radius`
+    Circle { center: Position, radius: Fraction },
+}
+
+impl ZoneShape {
+    /// Checks whether the passed `position` lies inside this [ZoneShape] (including its border)
+    pub fn contains(&self, (x, y): (f32, f32)) -> bool {
+        match self {
+            Self::Rectangle { from, to, rotation } => {
+                let (from, to) = (to_f32(*from), to_f32(*to));
+                let (x, y) = if rotation.is_zero() {
+                    (x, y)
+                } else {
+                    // Rotate the position back into the frame of the unrotated rectangle
+                    let center = ((from.0 + to.0) / 2., (from.1 + to.1) / 2.);
+                    rotate((x, y), center, -angle(*rotation))
+                };
+                x >= from.0 && y >= from.1 && x <= to.0 && y <= to.1
+            }
+            Self::Circle { center, radius } => {
+                let center = to_f32(*center);
+                let radius: f32 = (*radius).try_into().unwrap_or_default();
+                (x - center.0).powi(2) + (y - center.1).powi(2) <= radius.powi(2)
+            }
+        }
+    }
+
+    /// Points spanning this [ZoneShape]:
+    /// The axis-aligned bounding box of these points contains the whole shape.
+    pub fn bounding_points(&self) -> Vec<Position> {
+        match self {
+            Self::Rectangle { from, to, rotation } if rotation.is_zero() => vec![*from, *to],
+            Self::Rectangle { from, to, rotation } => {
+                let (from_f, to_f) = (to_f32(*from), to_f32(*to));
+                let center = ((from_f.0 + to_f.0) / 2., (from_f.1 + to_f.1) / 2.);
+                [
+                    (from_f.0, from_f.1),
+                    (to_f.0, from_f.1),
+                    (to_f.0, to_f.1),
+                    (from_f.0, to_f.1),
+                ]
+                .into_iter()
+                .map(|corner| {
+                    let (x, y) = rotate(corner, center, angle(*rotation));
+                    (Fraction::from(x), Fraction::from(y))
+                })
+                .collect()
+            }
+            Self::Circle {
+                center: (x, y),
+                radius,
+            } => vec![(x - radius, y - radius), (x + radius, y + radius)],
+        }
+    }
+}
+
+/// The kind of a [ZoneShape] without its parameters
+#[derive(Default)]
+enum ZoneShapeKind {
+    #[default]
+    Rectangle,
+    Circle,
+}
+
+impl TryFrom<ConfigItem> for ZoneShapeKind {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        const EXPECTED: &str = "'rectangle' | 'circle'";
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => match s.as_str() {
+                "rectangle" => Ok(Self::Rectangle),
+                "circle" => Ok(Self::Circle),
+                _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+            },
+            _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+        }
+    }
+}
+
+/// Converts the passed [Position] to [f32]s
+pub(crate) fn to_f32((x, y): Position) -> (f32, f32) {
+    (
+        x.try_into().unwrap_or_default(),
+        y.try_into().unwrap_or_default(),
+    )
+}
+
+/// Converts the passed `degrees` to radians
+fn angle(degrees: Fraction) -> f32 {
+    let degrees: f32 = degrees.try_into().unwrap_or_default();
+    degrees.to_radians()
+}
+
+/// Rotates `point` clockwise (in a coordinate system where `y` points down)
+/// by `angle` radians around `center`
+fn rotate((x, y): (f32, f32), center: (f32, f32), angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    let (dx, dy) = (x - center.0, y - center.1);
+    (
+        center.0 + dx * cos - dy * sin,
+        center.1 + dx * sin + dy * cos,
+    )
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct TrapConfig {
@@ -150,6 +418,9 @@ mod test {
             name: "Name".to_string(),
             movement: MovementConfig {
                 max_speed: Fraction::new(23u64, 1u64),
+                model: MovementModel::ConstantJerk,
+                max_move_time: None,
+                interpolation: MoveInterpolation::Diagonal,
             },
             time: TimeConfig {
                 load: Fraction::new(21u64, 5u64),
@@ -157,6 +428,8 @@ mod test {
                 ry: Fraction::new(1u64, 10u64),
                 rz: Fraction::new(3u64, 1u64),
                 cz: Fraction::new(1u64, 1u64),
+                measure: Fraction::new(2u64, 1u64),
+                reset: Fraction::new(0u64, 1u64),
                 unit: "us".to_string(),
             },
             distance: DistanceConfig {
@@ -167,15 +440,23 @@ mod test {
                 (
                     "zone0".to_string(),
                     ZoneConfig {
-                        from: (Fraction::new(0u64, 1u64), Fraction::new(0u64, 1u64)),
-                        to: (Fraction::new(10u64, 1u64), Fraction::new(10u64, 1u64)),
+                        shape: ZoneShape::Rectangle {
+                            from: (Fraction::new(0u64, 1u64), Fraction::new(0u64, 1u64)),
+                            to: (Fraction::new(10u64, 1u64), Fraction::new(10u64, 1u64)),
+                            rotation: Fraction::new(0u64, 1u64),
+                        },
+                        capacity: None,
                     },
                 ),
                 (
                     "zone1".to_string(),
                     ZoneConfig {
-                        from: (-Fraction::new(61u64, 5u64), Fraction::new(8u64, 1u64)),
-                        to: (Fraction::new(23u64, 1u64), Fraction::new(4u64, 1u64)),
+                        shape: ZoneShape::Rectangle {
+                            from: (-Fraction::new(61u64, 5u64), Fraction::new(4u64, 1u64)),
+                            to: (Fraction::new(23u64, 1u64), Fraction::new(8u64, 1u64)),
+                            rotation: Fraction::new(0u64, 1u64),
+                        },
+                        capacity: None,
                     },
                 ),
             ]),
@@ -202,4 +483,260 @@ mod test {
 
         assert_eq!(config, expected);
     }
+
+    /// Parses the passed `input` as a [MovementConfig]
+    fn movement(input: &str) -> Result<MovementConfig, Error> {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into()
+    }
+
+    #[test]
+    fn movement_model() {
+        assert_eq!(
+            movement("max_speed: 2").expect("Failed to load default model"),
+            MovementConfig {
+                max_speed: Fraction::new(2u64, 1u64),
+                model: MovementModel::ConstantJerk,
+                max_move_time: None,
+                interpolation: MoveInterpolation::Diagonal,
+            }
+        );
+        assert_eq!(
+            movement("max_speed: 2\nmodel: constant_velocity")
+                .expect("Failed to load constant-velocity model"),
+            MovementConfig {
+                max_speed: Fraction::new(2u64, 1u64),
+                model: MovementModel::ConstantVelocity,
+                max_move_time: None,
+                interpolation: MoveInterpolation::Diagonal,
+            }
+        );
+        assert_eq!(
+            movement("max_speed: 2\nmodel: trapezoidal\nacceleration: 4")
+                .expect("Failed to load trapezoidal model"),
+            MovementConfig {
+                max_speed: Fraction::new(2u64, 1u64),
+                model: MovementModel::Trapezoidal {
+                    acceleration: Fraction::new(4u64, 1u64),
+                },
+                max_move_time: None,
+                interpolation: MoveInterpolation::Diagonal,
+            }
+        );
+    }
+
+    #[test]
+    fn max_move_time() {
+        assert_eq!(
+            movement("max_speed: 2\nmax_move_time: 10")
+                .expect("Failed to load max move time")
+                .max_move_time,
+            Some(Fraction::new(10u64, 1u64))
+        );
+    }
+
+    #[test]
+    fn move_interpolation() {
+        assert_eq!(
+            movement("max_speed: 2")
+                .expect("Failed to load default interpolation")
+                .interpolation,
+            MoveInterpolation::Diagonal
+        );
+        assert_eq!(
+            movement("max_speed: 2\ninterpolation: component_wise")
+                .expect("Failed to load component-wise interpolation")
+                .interpolation,
+            MoveInterpolation::ComponentWise
+        );
+        assert!(movement("max_speed: 2\ninterpolation: curved").is_err());
+    }
+
+    #[test]
+    fn movement_model_trapezoidal_requires_acceleration() {
+        let error = movement("max_speed: 2\nmodel: trapezoidal")
+            .expect_err("Loaded trapezoidal model without acceleration");
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::MissingField("acceleration")
+        ));
+    }
+
+    #[test]
+    fn movement_requires_positive_values() {
+        for (input, path) in [
+            ("max_speed: 0", "max_speed"),
+            ("max_speed: -2", "max_speed"),
+            (
+                "max_speed: 2\nmodel: trapezoidal\nacceleration: 0",
+                "acceleration",
+            ),
+            (
+                "max_speed: 2\nmodel: trapezoidal\nacceleration: -4",
+                "acceleration",
+            ),
+            ("max_speed: 2\nmax_move_time: 0", "max_move_time"),
+            ("max_speed: 2\nmax_move_time: -1", "max_move_time"),
+        ] {
+            let error = movement(input).expect_err("Loaded non-positive value");
+            assert!(
+                matches!(error.kind(), ErrorKind::OutOfRange(_)),
+                "Wrong error kind for {input:?}: {error:#?}"
+            );
+            assert_eq!(error.path(), [path]);
+        }
+    }
+
+    /// Parses the passed `input` as a [ZoneConfig]
+    fn zone(input: &str) -> Result<ZoneConfig, Error> {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into()
+    }
+
+    /// Parses the passed `input` into a generic [Config]
+    fn generic(input: &str) -> Config {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        parser::parse(lexed.as_slice())
+            .expect("Failed to parse")
+            .into()
+    }
+
+    #[test]
+    fn partial_machine_with_defaults() {
+        let defaults = generic(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.namachine"
+        )));
+        let expected: MachineConfig = generic(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.namachine"
+        )))
+        .try_into()
+        .expect("Failed to load defaults");
+
+        let machine = MachineConfig::from_config_with_defaults(
+            generic("name: \"Partial\"\ntime { rz: 5 }\nzone zone0 { capacity: 2 }"),
+            &defaults,
+        )
+        .expect("Failed to load partial machine");
+
+        assert_eq!(machine.name, "Partial");
+        assert_eq!(machine.time.rz, Fraction::new(5u64, 1u64));
+        assert_eq!(machine.time.ry, expected.time.ry, "Time not inherited");
+        assert_eq!(machine.movement, expected.movement);
+        assert_eq!(machine.trap, expected.trap);
+        assert_eq!(machine.zone["zone0"].shape, expected.zone["zone0"].shape);
+        assert_eq!(machine.zone["zone0"].capacity, Some(2));
+        assert_eq!(machine.zone["zone1"], expected.zone["zone1"]);
+
+        assert!(
+            MachineConfig::from_config_with_defaults(generic("time { rz: \"slow\" }"), &defaults)
+                .is_err(),
+            "Invalid override was accepted"
+        );
+    }
+
+    #[test]
+    fn non_positive_integer_capacity() {
+        for capacity in ["0", "-1", "1.5"] {
+            let input = format!("from: (0, 0)\nto: (1, 1)\ncapacity: {capacity}");
+            let error = zone(&input).expect_err("Loaded invalid capacity");
+            assert!(
+                matches!(error.kind(), ErrorKind::OutOfRange(_)),
+                "Wrong error kind for {capacity:?}: {error:#?}"
+            );
+            assert_eq!(error.path(), ["capacity"]);
+        }
+        assert_eq!(
+            zone("from: (0, 0)\nto: (1, 1)\ncapacity: 3")
+                .expect("Failed to load zone")
+                .capacity,
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn circle_zone() {
+        let zone = zone("shape: circle\ncenter: (10, 10)\nradius: 5").expect("Failed to load zone");
+        assert_eq!(
+            zone.shape,
+            ZoneShape::Circle {
+                center: (Fraction::new(10u64, 1u64), Fraction::new(10u64, 1u64)),
+                radius: Fraction::new(5u64, 1u64),
+            }
+        );
+
+        assert!(zone.shape.contains((10., 10.)), "Center not in circle");
+        assert!(
+            zone.shape.contains((13., 14.)),
+            "Point on border not in circle"
+        );
+        assert!(
+            !zone.shape.contains((14., 14.)),
+            "Point in bounding box but outside circle is in circle"
+        );
+        assert_eq!(
+            zone.shape.bounding_points(),
+            vec![
+                (Fraction::new(5u64, 1u64), Fraction::new(5u64, 1u64)),
+                (Fraction::new(15u64, 1u64), Fraction::new(15u64, 1u64)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rotated_rectangle_zone() {
+        let zone = zone("from: (0, -1)\nto: (10, 1)\nrotation: 90").expect("Failed to load zone");
+
+        // Rotated by 90 degrees around (5, 0): spans (4, -5) to (6, 5)
+        assert!(
+            zone.shape.contains((5., 4.)),
+            "Point in rotated bounds rejected"
+        );
+        assert!(
+            !zone.shape.contains((1., 0.)),
+            "Point in unrotated bounds but outside rotated bounds accepted"
+        );
+        assert!(
+            !zone.shape.contains((5., 6.)),
+            "Point outside rotated bounds accepted"
+        );
+    }
+
+    #[test]
+    fn reversed_rectangle_zone() {
+        let ordered = zone("from: (0, -1)\nto: (10, 1)").expect("Failed to load zone");
+        assert_eq!(
+            ordered.shape,
+            ZoneShape::Rectangle {
+                from: (Fraction::new(0u64, 1u64), -Fraction::new(1u64, 1u64)),
+                to: (Fraction::new(10u64, 1u64), Fraction::new(1u64, 1u64)),
+                rotation: Fraction::zero(),
+            }
+        );
+
+        for input in [
+            "from: (10, 1)\nto: (0, -1)",
+            "from: (10, -1)\nto: (0, 1)",
+            "from: (0, 1)\nto: (10, -1)",
+        ] {
+            let reversed = zone(input).expect("Failed to load zone");
+            assert_eq!(reversed.shape, ordered.shape, "Not normalized: {input}");
+            assert!(
+                reversed.shape.contains((5., 0.)),
+                "Point inside reversed zone rejected: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn zone_shape_requires_parameters() {
+        let error =
+            zone("shape: circle\ncenter: (0, 0)").expect_err("Loaded circle without radius");
+        assert!(matches!(error.kind(), ErrorKind::MissingField("radius")));
+    }
 }
@@ -1,43 +1,162 @@
 use super::lexer::Token;
 use crate::{common, ParseError};
 use std::fmt::Debug;
-use token::{block_close, block_open, identifier, ignore_comments, separator};
+use token::{
+    blank_line, block_close, block_open, comment, identifier, ignore_layout, separator,
+    trailing_comment,
+};
 use try_into_value::TryIntoValue;
-use winnow::combinator::{alt, preceded, repeat, terminated};
+use winnow::combinator::{alt, repeat, terminated};
 use winnow::prelude::*;
 
 // Re-export the common parser
 pub use common::parser::*;
 
 /// A [ConfigItem] represents a single item of the config.
-#[cfg_attr(test, derive(PartialEq))]
+///
+/// Each item (except for comments and blank lines) stores its `position` in the parsed token-stream
+/// as the number of tokens from the start of the item to the end of the stream.
+/// This allows mapping the item back to its [span][super::lexer::lex_with_spans] in the source.
+/// The `position` is ignored when comparing items in tests.
 #[derive(Debug, Clone)]
 pub enum ConfigItem {
-    // `key`, `value`
-    Property(Value, Value),
-    // `identifier`, `content`
-    Block(String, Config),
-    // `identifier`, `name`, `content`
-    NamedBlock(String, Value, Config),
+    // `key`, `value`, `position`
+    Property(Value, Value, usize),
+    // `identifier`, `content`, `position`
+    Block(String, Config, usize),
+    // `identifier`, `name`, `content`, `position`
+    NamedBlock(String, Value, Config, usize),
+    // `content` of a comment (without the comment-delimiters)
+    Comment(String),
+    // `content` of a comment on the same line as the previous item
+    // (without the comment-delimiters)
+    TrailingComment(String),
+    // An empty line between two items
+    BlankLine,
 }
 
 /// A [Config] is all [ConfigItem]s of a parsed config.
 pub type Config = Vec<ConfigItem>;
 
 /// Parse a full stream of [Token]s into a [Config].
+/// Comments and blank lines are discarded.
 pub fn parse<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &[Token<S>],
+) -> Result<Config, ParseError<&[Token<S>]>> {
+    parse_with_comments(input).map(strip_comments)
+}
+
+/// Parse a full stream of [Token]s into a [Config].
+/// Comments between items are kept as [ConfigItem::Comment]s
+/// (i.e., they are attached to the following item),
+/// which allows [serializing][serialize] the config with its comments intact.
+/// When the tokens were [lexed with their layout][super::lexer::lex_with_layout],
+/// comments on the same line as the previous item are kept as [ConfigItem::TrailingComment]s
+/// and empty lines are kept as [ConfigItem::BlankLine]s.
+/// Comments and empty lines inside of an item (e.g., between key and value) are discarded.
+pub fn parse_with_comments<S: TryIntoValue + Clone + Debug + PartialEq>(
+    input: &[Token<S>],
 ) -> Result<Config, ParseError<&[Token<S>]>> {
     config.parse(input)
 }
 
+/// Recursively removes all [ConfigItem::Comment]s, [ConfigItem::TrailingComment]s,
+/// and [ConfigItem::BlankLine]s from the passed [Config].
+pub fn strip_comments(config: Config) -> Config {
+    config
+        .into_iter()
+        .filter_map(|item| match item {
+            ConfigItem::Comment(_) | ConfigItem::TrailingComment(_) | ConfigItem::BlankLine => None,
+            ConfigItem::Block(i, c, p) => Some(ConfigItem::Block(i, strip_comments(c), p)),
+            ConfigItem::NamedBlock(i, n, c, p) => {
+                Some(ConfigItem::NamedBlock(i, n, strip_comments(c), p))
+            }
+            item => Some(item),
+        })
+        .collect()
+}
+
+/// Serializes the passed [Config] into the config-format,
+/// which can be [lexed][super::lexer::lex] and [parsed][parse] again.
+/// [ConfigItem::Comment]s are written as single-line comments
+/// or as multi-line comments if they span multiple lines.
+/// [ConfigItem::TrailingComment]s are appended to the line of the previous item
+/// and [ConfigItem::BlankLine]s are written as empty lines.
+pub fn serialize(config: &[ConfigItem]) -> String {
+    let mut target = String::new();
+    serialize_into(config, 0, &mut target);
+    target
+}
+
+/// Serializes the passed [Config] into the `target` with the passed `indent`-level.
+fn serialize_into(config: &[ConfigItem], indent: usize, target: &mut String) {
+    /// Writes the passed `comment` as a single- or multi-line comment
+    fn comment(comment: &str) -> String {
+        if comment.contains('\n') {
+            format!("/*{comment}*/\n")
+        } else {
+            format!("//{comment}\n")
+        }
+    }
+
+    for item in config {
+        match item {
+            ConfigItem::TrailingComment(c) => {
+                // Continue the line of the previous item
+                if target.ends_with('\n') {
+                    target.pop();
+                }
+                target.push(' ');
+                target.push_str(&comment(c));
+                continue;
+            }
+            ConfigItem::BlankLine => {
+                target.push('\n');
+                continue;
+            }
+            _ => {}
+        }
+        target.extend(std::iter::repeat_n('\t', indent));
+        match item {
+            ConfigItem::Property(key, value, _) => {
+                target.push_str(&format!("{key}: {value}\n"));
+            }
+            ConfigItem::Block(identifier, content, _) => {
+                target.push_str(&format!("{identifier} {{\n"));
+                serialize_into(content, indent + 1, target);
+                target.extend(std::iter::repeat_n('\t', indent));
+                target.push_str("}\n");
+            }
+            ConfigItem::NamedBlock(identifier, name, content, _) => {
+                target.push_str(&format!("{identifier} {name} {{\n"));
+                serialize_into(content, indent + 1, target);
+                target.extend(std::iter::repeat_n('\t', indent));
+                target.push_str("}\n");
+            }
+            ConfigItem::Comment(c) => target.push_str(&comment(c)),
+            ConfigItem::TrailingComment(_) | ConfigItem::BlankLine => unreachable!(), // Handled above
+        }
+    }
+}
+
 /// Try to parse a [Config] from a stream of [Token]s.
+/// Comments and blank lines between items are kept
+/// (see [parse_with_comments]).
 pub fn config<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
 ) -> ModalResult<Config> {
-    preceded(
-        ignore_comments,
-        repeat(0.., terminated(config_item, ignore_comments)),
+    repeat(
+        0..,
+        alt((
+            trailing_comment
+                .try_map(TryIntoValue::string)
+                .map(ConfigItem::TrailingComment),
+            comment
+                .try_map(TryIntoValue::string)
+                .map(ConfigItem::Comment),
+            blank_line.map(|()| ConfigItem::BlankLine),
+            config_item,
+        )),
     )
     .parse_next(input)
 }
@@ -53,12 +172,13 @@ pub fn config_item<S: TryIntoValue + Clone + Debug + PartialEq>(
 pub fn property<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
 ) -> ModalResult<ConfigItem> {
+    let position = input.len();
     (
-        terminated(any_value, ignore_comments),
-        terminated(separator, ignore_comments),
+        terminated(any_value, ignore_layout),
+        terminated(separator, ignore_layout),
         any_value,
     )
-        .map(|(k, _, v)| ConfigItem::Property(k, v))
+        .map(|(k, _, v)| ConfigItem::Property(k, v, position))
         .parse_next(input)
 }
 
@@ -66,13 +186,14 @@ pub fn property<S: TryIntoValue + Clone + Debug + PartialEq>(
 pub fn block<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
 ) -> ModalResult<ConfigItem> {
+    let position = input.len();
     (
-        terminated(identifier, ignore_comments),
-        terminated(block_open, ignore_comments),
-        terminated(config, ignore_comments),
+        terminated(identifier, ignore_layout),
+        block_open,
+        config,
         block_close,
     )
-        .map(|(i, _, c, _)| ConfigItem::Block(i, c))
+        .map(|(i, _, c, _)| ConfigItem::Block(i, c, position))
         .parse_next(input)
 }
 
@@ -80,14 +201,15 @@ pub fn block<S: TryIntoValue + Clone + Debug + PartialEq>(
 pub fn named_block<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
 ) -> ModalResult<ConfigItem> {
+    let position = input.len();
     (
-        terminated(identifier, ignore_comments),
-        terminated(any_value, ignore_comments),
-        terminated(block_open, ignore_comments),
-        terminated(config, ignore_comments),
+        terminated(identifier, ignore_layout),
+        terminated(any_value, ignore_layout),
+        block_open,
+        config,
         block_close,
     )
-        .map(|(i, n, _, c, _)| ConfigItem::NamedBlock(i, n, c))
+        .map(|(i, n, _, c, _)| ConfigItem::NamedBlock(i, n, c, position))
         .parse_next(input)
 }
 
@@ -113,6 +235,51 @@ pub mod token {
     pub fn separator<S: Clone + Debug + PartialEq>(input: &mut &[Token<S>]) -> ModalResult<()> {
         one_of([Token::Separator]).void().parse_next(input)
     }
+
+    /// Try to parse a single [Token::TrailingComment].
+    pub fn trailing_comment<S: Clone + Debug + PartialEq>(
+        input: &mut &[Token<S>],
+    ) -> ModalResult<S> {
+        one_of(|t: Token<S>| matches!(t, Token::TrailingComment(_)))
+            .map(|t| match t {
+                Token::TrailingComment(c) => c,
+                _ => unreachable!(), // Parser only matches trailing comment
+            })
+            .parse_next(input)
+    }
+
+    /// Try to parse a single [Token::BlankLine].
+    pub fn blank_line<S: Clone + Debug + PartialEq>(input: &mut &[Token<S>]) -> ModalResult<()> {
+        one_of([Token::BlankLine]).void().parse_next(input)
+    }
+
+    /// Ignore all comments and blank lines until the next other token.
+    pub fn ignore_layout<S: TryIntoValue + Clone + Debug + PartialEq>(
+        input: &mut &[Token<S>],
+    ) -> ModalResult<()> {
+        repeat::<_, _, (), _, _>(0.., alt((comment.void(), blank_line)))
+            .void()
+            .parse_next(input)
+    }
+}
+
+/// Compare [ConfigItem]s while ignoring their `position`,
+/// so that items parsed from differently formatted sources compare equal.
+#[cfg(test)]
+impl PartialEq for ConfigItem {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Property(k1, v1, _), Self::Property(k2, v2, _)) => k1 == k2 && v1 == v2,
+            (Self::Block(i1, c1, _), Self::Block(i2, c2, _)) => i1 == i2 && c1 == c2,
+            (Self::NamedBlock(i1, n1, c1, _), Self::NamedBlock(i2, n2, c2, _)) => {
+                i1 == i2 && n1 == n2 && c1 == c2
+            }
+            (Self::Comment(c1), Self::Comment(c2)) => c1 == c2,
+            (Self::TrailingComment(c1), Self::TrailingComment(c2)) => c1 == c2,
+            (Self::BlankLine, Self::BlankLine) => true,
+            _ => false,
+        }
+    }
 }
 
 // Implement `ContainsToken` for `Token` and `Token`-slices.
@@ -189,14 +356,17 @@ mod test {
             ConfigItem::Property(
                 Value::Identifier("property1".to_string()),
                 Value::String("some string".to_string()),
+                25,
             ),
             ConfigItem::Property(
                 Value::Identifier("property2".to_string()),
                 Value::Number(Fraction::new(12u64, 10u64)),
+                22,
             ),
             ConfigItem::Property(
                 Value::Regex(Regex::new("regex").unwrap()),
                 Value::Identifier("identifier".to_string()),
+                19,
             ),
             ConfigItem::Block(
                 "block".to_string(),
@@ -212,13 +382,17 @@ mod test {
                                 b: 50,
                                 a: 255,
                             }),
+                            9,
                         ),
                         ConfigItem::Property(
                             Value::Number(Fraction::new(42u64, 1u64)),
                             Value::Boolean(true),
+                            6,
                         ),
                     ],
+                    12,
                 )],
+                14,
             ),
         ];
 
@@ -227,6 +401,37 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    /// Items should store the number of tokens from their start to the end of the stream.
+    #[test]
+    fn item_positions() {
+        let input = [
+            Token::Identifier("property"),
+            Token::Separator,
+            Token::Value(lexer::Value::Number("1")),
+            Token::Identifier("block"),
+            Token::BlockOrSetOpen,
+            Token::Identifier("named"),
+            Token::Identifier("name"),
+            Token::BlockOrSetOpen,
+            Token::BlockOrSetClose,
+            Token::BlockOrSetClose,
+        ];
+
+        let parsed = parse(&input).expect("Failed to parse");
+
+        let [ConfigItem::Property(_, _, property), ConfigItem::Block(_, block, position)] =
+            parsed.as_slice()
+        else {
+            panic!("Parsed unexpected items: {parsed:?}");
+        };
+        assert_eq!(*property, 10);
+        assert_eq!(*position, 7);
+        let [ConfigItem::NamedBlock(_, _, _, named)] = block.as_slice() else {
+            panic!("Parsed unexpected block content: {block:?}");
+        };
+        assert_eq!(*named, 5);
+    }
+
     #[test]
     fn parser_error_context_available() {
         // Minimal invalid config: two identifiers without separator should error
@@ -239,4 +444,105 @@ mod test {
         let _context = crate::test_utils::collect_context(err);
         // No assertion; ensures error produced and context retrieval works.
     }
+
+    /// Lexes and parses the passed `input` keeping all comments
+    fn parse_commented(input: &str) -> Config {
+        let lexed = super::super::lexer::lex(input).expect("Failed to lex");
+        parse_with_comments(&lexed).expect("Failed to parse")
+    }
+
+    /// Inline and standalone comments should be kept and survive serialization.
+    #[test]
+    fn comments_round_trip() {
+        let input = "// standalone comment\n\
+                     name: \"Test\" // inline comment\n\
+                     block { // comment after block open\n\
+                     \tvalue: 1.25\n\
+                     \t/* multi-line\n\
+                     \tcomment */\n\
+                     \tnamed ^a.*$ {\n\
+                     \t\tcolor: #ff000080\n\
+                     \t\tscale: 50%\n\
+                     \t}\n\
+                     \t// comment before block close\n\
+                     }\n";
+
+        let parsed = parse_commented(input);
+        let comment = |c: &str| ConfigItem::Comment(c.to_string());
+        assert_eq!(parsed[0], comment(" standalone comment"));
+        assert_eq!(parsed[2], comment(" inline comment"));
+        let ConfigItem::Block(_, block, _) = &parsed[3] else {
+            panic!("Block not parsed as block: {:?}", parsed[3]);
+        };
+        assert_eq!(block[0], comment(" comment after block open"));
+        assert_eq!(block[2], comment(" multi-line\n\tcomment "));
+        assert_eq!(block[4], comment(" comment before block close"));
+
+        let serialized = serialize(&parsed);
+        assert_eq!(
+            parse_commented(&serialized),
+            parsed,
+            "Serialized config differs:\n{serialized}"
+        );
+        assert_eq!(
+            strip_comments(parsed),
+            parse(&super::super::lexer::lex(input).unwrap()).unwrap(),
+            "Stripped config differs from config parsed without comments"
+        );
+    }
+
+    /// A config lexed with its layout should serialize to exactly the input,
+    /// including blank lines and trailing comments.
+    #[test]
+    fn layout_round_trip() {
+        let input = "// standalone comment\n\
+                     name: \"Test\" // inline comment\n\
+                     \n\
+                     block { // comment after block open\n\
+                     \tvalue: 1.25\n\
+                     \n\
+                     \t/* multi-line\n\
+                     \tcomment */\n\
+                     \tnamed ^a.*$ {\n\
+                     \t\tcolor: #ff000080 // inline comment in named block\n\
+                     \t\tscale: 50%\n\
+                     \t}\n\
+                     } // comment after block close\n\
+                     \n\
+                     \n\
+                     other: true\n";
+
+        let lexed = super::super::lexer::lex_with_layout(input).expect("Failed to lex");
+        let parsed = parse_with_comments(&lexed).expect("Failed to parse");
+        assert_eq!(
+            parsed[2],
+            ConfigItem::TrailingComment(" inline comment".to_string())
+        );
+        assert_eq!(parsed[3], ConfigItem::BlankLine);
+
+        assert_eq!(serialize(&parsed), input);
+    }
+
+    /// The example configs should be unchanged after serialization.
+    #[test]
+    fn example_round_trip() {
+        for input in [
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/rsc/test/example.namachine"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/rsc/test/example.nastyle"
+            )),
+        ] {
+            let parsed = parse_commented(input);
+            let serialized = serialize(&parsed);
+            assert_eq!(
+                parse_commented(&serialized),
+                parsed,
+                "Serialized config differs:\n{serialized}"
+            );
+        }
+    }
 }
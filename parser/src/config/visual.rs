@@ -1,12 +1,22 @@
 //! The [VisualConfig] and sub-types.
 //! See documentation of file-format.
 
-use crate::common::{color::Color, percentage::Percentage};
+use crate::common::{
+    color::Color,
+    easing::Easing,
+    pattern::Pattern,
+    percentage::{Percentage, UnitPercentage},
+    shape::Shape,
+};
 
 use super::{
     error::{Error, ErrorKind},
-    generic::{get_item, get_item_map, get_item_named_struct, get_item_struct, Config, ConfigItem},
+    generic::{
+        get_item, get_item_map, get_item_named_struct, get_item_optional, get_item_struct,
+        get_item_struct_optional, Config, ConfigItem,
+    },
     parser::Value,
+    position::Position,
 };
 use fraction::Fraction;
 use regex::Regex;
@@ -23,6 +33,8 @@ pub struct VisualConfig {
     pub sidebar: SidebarConfig,
     pub time: TimeConfig,
     pub viewport: ViewportConfig,
+    /// The base font, which relative font sizes refer to
+    pub font: BaseFontConfig,
 }
 
 impl TryFrom<Config> for VisualConfig {
@@ -38,6 +50,7 @@ impl TryFrom<Config> for VisualConfig {
             sidebar: get_item_struct(&mut value, "sidebar")?,
             time: get_item_struct(&mut value, "time")?,
             viewport: get_item_struct(&mut value, "viewport")?,
+            font: get_item_struct_optional(&mut value, "font")?.unwrap_or_default(),
         })
     }
 }
@@ -49,6 +62,12 @@ pub struct AtomConfig {
     pub shuttling: ShuttlingConfig,
     pub legend: AtomLegendConfig,
     pub radius: Fraction,
+    /// The shape of all atoms, unless overwritten per atom
+    pub shape: Shape,
+    /// An optional outline drawn around all atoms
+    pub outline: Option<OutlineConfig>,
+    /// Optional faint markers at the final positions of all atoms
+    pub ghost: Option<GhostConfig>,
 }
 
 impl TryFrom<Config> for AtomConfig {
@@ -59,6 +78,43 @@ impl TryFrom<Config> for AtomConfig {
             shuttling: get_item_struct(&mut value, "shuttling")?,
             legend: get_item_struct(&mut value, "legend")?,
             radius: get_item(&mut value, "radius")?,
+            shape: get_item_optional(&mut value, "shape")?.unwrap_or_default(),
+            outline: get_item_struct_optional(&mut value, "outline")?,
+            ghost: get_item_struct_optional(&mut value, "ghost")?,
+        })
+    }
+}
+
+/// An outline (stroke) drawn around atoms, independent of their fill
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct OutlineConfig {
+    pub color: Color,
+    pub width: Fraction,
+}
+
+impl TryFrom<Config> for OutlineConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            color: get_item(&mut value, "color")?,
+            width: get_item(&mut value, "width")?,
+        })
+    }
+}
+
+/// Faint markers drawn at the final positions of the atoms
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct GhostConfig {
+    pub color: Color,
+}
+
+impl TryFrom<Config> for GhostConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            color: get_item(&mut value, "color")?,
         })
     }
 }
@@ -97,12 +153,15 @@ impl TryFrom<Config> for ShuttlingConfig {
 pub struct AtomLegendConfig {
     pub name: Vec<(Regex, String)>,
     pub font: FontConfig,
+    /// What to display as the label of the atoms
+    pub show: AtomLabel,
 }
 
 #[cfg(test)]
 impl PartialEq for AtomLegendConfig {
     fn eq(&self, other: &Self) -> bool {
         self.font == other.font
+            && self.show == other.show
             && self.name.len() == other.name.len()
             && self
                 .name
@@ -118,15 +177,45 @@ impl TryFrom<Config> for AtomLegendConfig {
         Ok(Self {
             name: get_item_map(&mut value, "name")?,
             font: get_item_struct(&mut value, "font")?,
+            show: get_item_optional(&mut value, "show")?.unwrap_or_default(),
         })
     }
 }
 
+/// What to display as the label of an atom
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Copy, Default)]
+pub enum AtomLabel {
+    /// The name mapped from the id (see [AtomLegendConfig::name])
+    #[default]
+    Name,
+    /// The raw id of the atom
+    Id,
+    /// The name followed by the id
+    Both,
+}
+
+impl TryFrom<ConfigItem> for AtomLabel {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        const EXPECTED: &str = "'name' | 'id' | 'both'";
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => match s.as_str() {
+                "name" => Ok(Self::Name),
+                "id" => Ok(Self::Id),
+                "both" => Ok(Self::Both),
+                _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+            },
+            _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+        }
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct FontConfig {
     pub family: String,
-    pub size: Fraction,
+    pub size: FontSize,
     pub color: Color,
 }
 
@@ -141,6 +230,60 @@ impl TryFrom<Config> for FontConfig {
     }
 }
 
+/// A font size, either absolute or relative to the [BaseFontConfig] (in `em`)
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Copy)]
+pub enum FontSize {
+    Absolute(Fraction),
+    Relative(Fraction),
+}
+
+impl FontSize {
+    /// Returns the contained size if `self` is [Absolute][FontSize::Absolute]
+    /// or the scaled `base` size if `self` is [Relative][FontSize::Relative].
+    pub fn get(self, base: Fraction) -> Fraction {
+        match self {
+            Self::Absolute(n) => n,
+            Self::Relative(n) => n * base,
+        }
+    }
+}
+
+impl TryFrom<ConfigItem> for FontSize {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        match value {
+            ConfigItem::Value(Value::Number(n)) => Ok(Self::Absolute(n)),
+            ConfigItem::Value(Value::Em(n)) => Ok(Self::Relative(n)),
+            _ => Err(ErrorKind::WrongType("number | em").into()),
+        }
+    }
+}
+
+/// The base font, which [relative font sizes][FontSize::Relative] refer to
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct BaseFontConfig {
+    pub size: Fraction,
+}
+
+impl Default for BaseFontConfig {
+    fn default() -> Self {
+        Self {
+            size: Fraction::new(16u64, 1u64),
+        }
+    }
+}
+
+impl TryFrom<Config> for BaseFontConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            size: get_item(&mut value, "size")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ZoneConfig {
     pub config: Vec<(Regex, ZoneConfigConfig)>,
@@ -177,6 +320,9 @@ impl TryFrom<Config> for ZoneConfig {
 #[derive(Debug, Clone)]
 pub struct ZoneConfigConfig {
     pub color: Color,
+    pub fill: Option<Color>,
+    /// The line color while the zone holds more atoms than its capacity
+    pub over_capacity: Option<Color>,
     pub line: LineConfig,
     pub name: String,
 }
@@ -186,6 +332,8 @@ impl TryFrom<Config> for ZoneConfigConfig {
     fn try_from(mut value: Config) -> Result<Self, Self::Error> {
         Ok(Self {
             color: get_item(&mut value, "color")?,
+            fill: get_item_optional(&mut value, "fill")?,
+            over_capacity: get_item_optional(&mut value, "over_capacity")?,
             line: get_item_struct(&mut value, "line")?,
             name: get_item(&mut value, "name")?,
         })
@@ -221,7 +369,7 @@ impl TryFrom<Config> for DashConfig {
     fn try_from(mut value: Config) -> Result<Self, Self::Error> {
         Ok(Self {
             length: get_item(&mut value, "length")?,
-            duty: get_item(&mut value, "duty")?,
+            duty: get_item::<UnitPercentage>(&mut value, "duty")?.into(),
         })
     }
 }
@@ -265,7 +413,9 @@ impl TryFrom<Config> for OperationConfig {
 pub struct OperationConfigConfig {
     pub ry: OperationConfigConfigConfig,
     pub rz: OperationConfigConfigConfig,
-    pub cz: OperationConfigConfigConfig,
+    pub cz: CzConfig,
+    pub measure: Option<MeasureConfig>,
+    pub reset: Option<OperationConfigConfigConfig>,
 }
 
 impl TryFrom<Config> for OperationConfigConfig {
@@ -275,16 +425,102 @@ impl TryFrom<Config> for OperationConfigConfig {
             ry: get_item_struct(&mut value, "ry")?,
             rz: get_item_struct(&mut value, "rz")?,
             cz: get_item_struct(&mut value, "cz")?,
+            measure: get_item_struct_optional(&mut value, "measure")?,
+            reset: get_item_struct_optional(&mut value, "reset")?,
+        })
+    }
+}
+
+impl OperationConfigConfig {
+    /// Iterates over all configured operations.
+    /// Operations which are not configured (e.g., a missing `measure`) are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = &OperationConfigConfigConfig> {
+        [&self.rz, &self.ry, &self.cz.operation]
+            .into_iter()
+            .chain(self.measure.as_ref().map(|m| &m.operation))
+            .chain(self.reset.as_ref())
+    }
+}
+
+/// The config for `cz`-gates:
+/// The [OperationConfigConfigConfig] during the gate
+/// and whether to indicate the interaction distance around the targeted atoms.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct CzConfig {
+    pub operation: OperationConfigConfigConfig,
+    pub show_interaction_radius: bool,
+}
+
+impl TryFrom<Config> for CzConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            show_interaction_radius: get_item_optional(&mut value, "show_interaction_radius")?
+                .unwrap_or_default(),
+            operation: value.try_into()?,
         })
     }
 }
 
+/// The config for measurements:
+/// The [OperationConfigConfigConfig] during the measurement
+/// and the colors of the measured results.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct MeasureConfig {
+    pub operation: OperationConfigConfigConfig,
+    pub result: MeasureResultConfig,
+}
+
+impl TryFrom<Config> for MeasureConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            result: get_item_struct(&mut value, "result")?,
+            operation: value.try_into()?,
+        })
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct MeasureResultConfig {
+    pub zero: Color,
+    pub one: Color,
+}
+
+impl TryFrom<Config> for MeasureResultConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            zero: get_item(&mut value, "zero")?,
+            one: get_item(&mut value, "one")?,
+        })
+    }
+}
+
+impl MeasureResultConfig {
+    /// Gets the color for the passed measurement-`result`
+    pub fn get(&self, result: bool) -> Color {
+        if result {
+            self.one
+        } else {
+            self.zero
+        }
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct OperationConfigConfigConfig {
     pub color: Color,
     pub name: String,
     pub radius: NumberOrPercentage,
+    /// The pattern to fill the atoms with during the operation
+    pub pattern: Pattern,
+    /// The easing of the flash of the atoms during the operation
+    pub easing: Easing,
 }
 
 impl TryFrom<Config> for OperationConfigConfigConfig {
@@ -294,6 +530,8 @@ impl TryFrom<Config> for OperationConfigConfigConfig {
             color: get_item(&mut value, "color")?,
             name: get_item(&mut value, "name")?,
             radius: get_item(&mut value, "radius")?,
+            pattern: get_item_optional(&mut value, "pattern")?.unwrap_or_default(),
+            easing: get_item_optional(&mut value, "easing")?.unwrap_or_default(),
         })
     }
 }
@@ -393,6 +631,8 @@ pub struct CoordinateConfig {
     pub number: NumberConfig,
     pub axis: AxisConfig,
     pub margin: Fraction,
+    /// The direction in which the y-axis points
+    pub y_axis: YAxis,
 }
 
 impl TryFrom<Config> for CoordinateConfig {
@@ -403,15 +643,46 @@ impl TryFrom<Config> for CoordinateConfig {
             number: get_item_struct(&mut value, "number")?,
             axis: get_item_struct(&mut value, "axis")?,
             margin: get_item(&mut value, "margin")?,
+            y_axis: get_item_optional(&mut value, "y_axis")?.unwrap_or_default(),
         })
     }
 }
 
+/// The direction in which the y-axis points on screen
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Copy, Default)]
+pub enum YAxis {
+    /// `y` increases downwards (origin in the top-left)
+    #[default]
+    Down,
+    /// `y` increases upwards (origin in the bottom-left)
+    Up,
+}
+
+impl TryFrom<ConfigItem> for YAxis {
+    type Error = Error;
+    fn try_from(value: ConfigItem) -> Result<Self, Self::Error> {
+        const EXPECTED: &str = "'up' | 'down'";
+        match value {
+            ConfigItem::Value(Value::Identifier(s)) => match s.as_str() {
+                "up" => Ok(Self::Up),
+                "down" => Ok(Self::Down),
+                _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+            },
+            _ => Err(ErrorKind::WrongType(EXPECTED).into()),
+        }
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct TickConfig {
     pub x: Fraction,
     pub y: Fraction,
+    /// The position the uniformly spaced ticks are aligned to
+    pub origin: Position,
+    /// Explicit tick positions, which replace the uniformly spaced ticks
+    pub positions: Option<TickPositionsConfig>,
     pub color: Color,
     pub line: LineConfig,
     pub display: bool,
@@ -423,6 +694,8 @@ impl TryFrom<Config> for TickConfig {
         Ok(Self {
             x: get_item(&mut value, "x")?,
             y: get_item(&mut value, "y")?,
+            origin: get_item_optional(&mut value, "origin")?.unwrap_or_default(),
+            positions: get_item_struct_optional(&mut value, "positions")?,
             color: get_item(&mut value, "color")?,
             line: get_item_struct(&mut value, "line")?,
             display: get_item(&mut value, "display")?,
@@ -430,12 +703,31 @@ impl TryFrom<Config> for TickConfig {
     }
 }
 
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct TickPositionsConfig {
+    pub x: Vec<Fraction>,
+    pub y: Vec<Fraction>,
+}
+
+impl TryFrom<Config> for TickPositionsConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            x: get_item(&mut value, "x")?,
+            y: get_item(&mut value, "y")?,
+        })
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug, Clone)]
 pub struct NumberConfig {
     pub x: NumberConfigConfig<TopBottomPosition>,
     pub y: NumberConfigConfig<LeftRightPosition>,
     pub display: bool,
+    /// The number of significant digits to round the numbers to
+    pub precision: Option<usize>,
     pub font: FontConfig,
 }
 
@@ -446,6 +738,7 @@ impl TryFrom<Config> for NumberConfig {
             x: get_item_struct(&mut value, "x")?,
             y: get_item_struct(&mut value, "y")?,
             display: get_item(&mut value, "display")?,
+            precision: get_item_optional(&mut value, "precision")?,
             font: get_item_struct(&mut value, "font")?,
         })
     }
@@ -579,7 +872,8 @@ impl TryFrom<Config> for SidebarPaddingConfig {
 pub struct TimeConfig {
     pub display: bool,
     pub prefix: String,
-    pub precision: Fraction,
+    /// The number of decimal places to show
+    pub precision: usize,
     pub font: FontConfig,
 }
 
@@ -600,6 +894,9 @@ impl TryFrom<Config> for TimeConfig {
 pub struct ViewportConfig {
     pub margin: Fraction,
     pub color: Color,
+    pub background_image: Option<BackgroundImageConfig>,
+    /// Additional space around the content
+    pub padding: PaddingConfig,
 }
 
 impl TryFrom<Config> for ViewportConfig {
@@ -608,6 +905,72 @@ impl TryFrom<Config> for ViewportConfig {
         Ok(Self {
             margin: get_item(&mut value, "margin")?,
             color: get_item(&mut value, "color")?,
+            background_image: get_item_struct_optional(&mut value, "background_image")?,
+            padding: get_item_struct_optional(&mut value, "padding")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Padding on each side of the content (in content-coordinates).
+/// Each side defaults to `0`.
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone, Default)]
+pub struct PaddingConfig {
+    pub top: Fraction,
+    pub bottom: Fraction,
+    pub left: Fraction,
+    pub right: Fraction,
+}
+
+impl TryFrom<Config> for PaddingConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            top: get_item_optional(&mut value, "top")?.unwrap_or_default(),
+            bottom: get_item_optional(&mut value, "bottom")?.unwrap_or_default(),
+            left: get_item_optional(&mut value, "left")?.unwrap_or_default(),
+            right: get_item_optional(&mut value, "right")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// An image drawn behind the machine
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct BackgroundImageConfig {
+    /// The path to the image
+    pub path: String,
+    /// The area the image spans (in content-coordinates)
+    pub extent: ExtentConfig,
+    /// The opacity of the image (`0` to `1`)
+    pub opacity: Fraction,
+}
+
+impl TryFrom<Config> for BackgroundImageConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            path: get_item(&mut value, "path")?,
+            extent: get_item_struct(&mut value, "extent")?,
+            opacity: get_item_optional(&mut value, "opacity")?.unwrap_or(Fraction::new(1u64, 1u64)),
+        })
+    }
+}
+
+/// A rectangular area, denoted by two opposite corners
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Clone)]
+pub struct ExtentConfig {
+    pub from: Position,
+    pub to: Position,
+}
+
+impl TryFrom<Config> for ExtentConfig {
+    type Error = Error;
+    fn try_from(mut value: Config) -> Result<Self, Self::Error> {
+        Ok(Self {
+            from: get_item(&mut value, "from")?,
+            to: get_item(&mut value, "to")?,
         })
     }
 }
@@ -647,7 +1010,7 @@ mod test {
                     name: vec![(Regex::new("^.*$").unwrap(), "$0".to_string())],
                     font: FontConfig {
                         family: "Nice Font".to_string(),
-                        size: Fraction::new(14u64, 1u64),
+                        size: FontSize::Absolute(Fraction::new(14u64, 1u64)),
                         color: Color {
                             r: 255,
                             g: 0,
@@ -655,8 +1018,12 @@ mod test {
                             a: 255,
                         },
                     },
+                    show: AtomLabel::Name,
                 },
                 radius: Fraction::new(32u64, 1u64),
+                shape: Shape::Circle,
+                outline: None,
+                ghost: None,
             },
             zone: ZoneConfig {
                 config: vec![
@@ -669,6 +1036,13 @@ mod test {
                                 b: 255,
                                 a: 255,
                             },
+                            fill: Some(Color {
+                                r: 0,
+                                g: 0,
+                                b: 255,
+                                a: 32,
+                            }),
+                            over_capacity: None,
                             line: LineConfig {
                                 thickness: Fraction::new(2u64, 1u64),
                                 dash: DashConfig {
@@ -688,6 +1062,8 @@ mod test {
                                 b: 52,
                                 a: 255,
                             },
+                            fill: None,
+                            over_capacity: None,
                             line: LineConfig {
                                 thickness: Fraction::new(1u64, 1u64),
                                 dash: DashConfig {
@@ -715,6 +1091,8 @@ mod test {
                         },
                         name: "ry".to_string(),
                         radius: NumberOrPercentage::Number(Fraction::new(32u64, 1u64)),
+                        pattern: Pattern::Solid,
+                        easing: Easing::Triangle,
                     },
                     rz: OperationConfigConfigConfig {
                         color: Color {
@@ -727,17 +1105,55 @@ mod test {
                         radius: NumberOrPercentage::Percentage(Percentage(Fraction::new(
                             48u64, 1u64,
                         ))),
+                        pattern: Pattern::Solid,
+                        easing: Easing::Triangle,
                     },
-                    cz: OperationConfigConfigConfig {
-                        color: Color {
-                            r: 192,
-                            g: 255,
-                            b: 238,
-                            a: 255,
+                    cz: CzConfig {
+                        operation: OperationConfigConfigConfig {
+                            color: Color {
+                                r: 192,
+                                g: 255,
+                                b: 238,
+                                a: 255,
+                            },
+                            name: "cz".to_string(),
+                            radius: NumberOrPercentage::Number(Fraction::new(13u64, 1u64)),
+                            pattern: Pattern::Solid,
+                            easing: Easing::Triangle,
                         },
-                        name: "cz".to_string(),
-                        radius: NumberOrPercentage::Number(Fraction::new(13u64, 1u64)),
+                        show_interaction_radius: false,
                     },
+                    measure: Some(MeasureConfig {
+                        operation: OperationConfigConfigConfig {
+                            color: Color {
+                                r: 255,
+                                g: 255,
+                                b: 0,
+                                a: 255,
+                            },
+                            name: "measure".to_string(),
+                            radius: NumberOrPercentage::Percentage(Percentage(Fraction::new(
+                                100u64, 1u64,
+                            ))),
+                            pattern: Pattern::Solid,
+                            easing: Easing::Triangle,
+                        },
+                        result: MeasureResultConfig {
+                            zero: Color {
+                                r: 0,
+                                g: 255,
+                                b: 0,
+                                a: 255,
+                            },
+                            one: Color {
+                                r: 255,
+                                g: 0,
+                                b: 0,
+                                a: 255,
+                            },
+                        },
+                    }),
+                    reset: None,
                 },
                 legend: LegendConfig {
                     display: true,
@@ -781,6 +1197,8 @@ mod test {
                 tick: TickConfig {
                     x: Fraction::new(10u64, 1u64),
                     y: Fraction::new(20u64, 1u64),
+                    origin: Default::default(),
+                    positions: None,
                     color: Color {
                         r: 0,
                         g: 0,
@@ -806,9 +1224,10 @@ mod test {
                         position: LeftRightPosition::Left,
                     },
                     display: true,
+                    precision: None,
                     font: FontConfig {
                         family: "Font".to_string(),
-                        size: Fraction::new(8u64, 1u64),
+                        size: FontSize::Absolute(Fraction::new(8u64, 1u64)),
                         color: Color {
                             r: 0,
                             g: 0,
@@ -823,7 +1242,7 @@ mod test {
                     display: true,
                     font: FontConfig {
                         family: "New Font".to_string(),
-                        size: Fraction::new(18u64, 1u64),
+                        size: FontSize::Absolute(Fraction::new(18u64, 1u64)),
                         color: Color {
                             r: 0,
                             g: 0,
@@ -833,11 +1252,12 @@ mod test {
                     },
                 },
                 margin: Fraction::new(12u64, 1u64),
+                y_axis: YAxis::Down,
             },
             sidebar: SidebarConfig {
                 font: FontConfig {
                     family: "Yet another font".to_string(),
-                    size: Fraction::new(10u64, 1u64),
+                    size: FontSize::Absolute(Fraction::new(10u64, 1u64)),
                     color: Color {
                         r: 0,
                         g: 0,
@@ -856,10 +1276,10 @@ mod test {
             time: TimeConfig {
                 display: true,
                 prefix: "Time: ".to_string(),
-                precision: Fraction::new(1u64, 1u64),
+                precision: 1,
                 font: FontConfig {
                     family: "Last Font".to_string(),
-                    size: Fraction::new(12u64, 1u64),
+                    size: FontSize::Absolute(Fraction::new(12u64, 1u64)),
                     color: Color {
                         r: 0,
                         g: 0,
@@ -876,7 +1296,10 @@ mod test {
                     b: 255,
                     a: 255,
                 },
+                background_image: None,
+                padding: PaddingConfig::default(),
             },
+            font: BaseFontConfig::default(),
         };
 
         let lexed = lexer::lex(input).expect("Failed to lex");
@@ -886,4 +1309,290 @@ mod test {
 
         assert_eq!(config, expected);
     }
+
+    /// Parses the passed `input` into a generic [Config]
+    fn generic(input: &str) -> Config {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        parser::parse(lexed.as_slice())
+            .expect("Failed to parse")
+            .into()
+    }
+
+    /// The example config
+    const EXAMPLE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/rsc/test/example.nastyle"
+    ));
+
+    /// Overlaying a patch should only override the patched values.
+    #[test]
+    fn merge_viewport_color() {
+        let mut expected: VisualConfig = generic(EXAMPLE).try_into().unwrap();
+        expected.viewport.color = Color {
+            r: 0x12,
+            g: 0x34,
+            b: 0x56,
+            a: 255,
+        };
+
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("viewport { color: #123456 }"));
+        let merged: VisualConfig = merged.try_into().expect("Failed to load merged config");
+
+        assert_eq!(merged, expected);
+    }
+
+    /// Named blocks should be merged by their name.
+    #[test]
+    fn merge_named_block() {
+        let mut expected: VisualConfig = generic(EXAMPLE).try_into().unwrap();
+        expected.zone.config[0].1.name = "Patched zone".to_string();
+        expected.zone.config.push((
+            Regex::new("^new$").unwrap(),
+            expected.zone.config[1].1.clone(),
+        ));
+
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic(
+            r#"zone {
+                config ^zone.*$ { name: "Patched zone" }
+                config ^new$ {
+                    color: #000034
+                    line { thickness: 1 dash { length: 5 duty: 20% } }
+                    name: "Normal zone"
+                }
+            }"#,
+        ));
+        let merged: VisualConfig = merged.try_into().expect("Failed to load merged config");
+
+        assert_eq!(merged, expected);
+    }
+
+    /// A background image can be added to the viewport.
+    #[test]
+    fn background_image() {
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic(
+            r#"viewport {
+                background_image {
+                    path: "schematic.png"
+                    extent { from: (0, 0) to: (100, 50) }
+                    opacity: 0.5
+                }
+            }"#,
+        ));
+        let config: VisualConfig = merged.try_into().expect("Failed to load config");
+
+        assert_eq!(
+            config.viewport.background_image,
+            Some(BackgroundImageConfig {
+                path: "schematic.png".to_string(),
+                extent: ExtentConfig {
+                    from: (Fraction::new(0u64, 1u64), Fraction::new(0u64, 1u64)),
+                    to: (Fraction::new(100u64, 1u64), Fraction::new(50u64, 1u64)),
+                },
+                opacity: Fraction::new(1u64, 2u64),
+            })
+        );
+    }
+
+    #[test]
+    fn atom_shape() {
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("atom { shape: square }"));
+        let config: VisualConfig = merged.try_into().expect("Failed to load config");
+        assert_eq!(config.atom.shape, Shape::Square);
+
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("atom { shape: hexagon }"));
+        assert!(
+            VisualConfig::try_from(merged).is_err(),
+            "Unknown shape was accepted"
+        );
+    }
+
+    #[test]
+    fn atom_outline() {
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("atom { outline { color: #000000 width: 2 } }"));
+        let config: VisualConfig = merged.try_into().expect("Failed to load config");
+        assert_eq!(
+            config.atom.outline,
+            Some(OutlineConfig {
+                color: Color {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                },
+                width: Fraction::new(2u64, 1u64),
+            })
+        );
+    }
+
+    #[test]
+    fn cz_interaction_radius() {
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic(
+            "operation { config { cz { show_interaction_radius: true } } }",
+        ));
+        let config: VisualConfig = merged.try_into().expect("Failed to load config");
+        assert!(config.operation.config.cz.show_interaction_radius);
+        assert_eq!(config.operation.config.cz.operation.name, "cz");
+    }
+
+    #[test]
+    fn operation_easing() {
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("operation { config { ry { easing: sine } } }"));
+        let config: VisualConfig = merged.try_into().expect("Failed to load config");
+        assert_eq!(config.operation.config.ry.easing, Easing::Sine);
+        assert_eq!(
+            config.operation.config.rz.easing,
+            Easing::Triangle,
+            "Easing does not default to triangle"
+        );
+
+        let mut merged = generic(EXAMPLE);
+        merged.merge(generic("operation { config { ry { easing: bounce } } }"));
+        assert!(
+            VisualConfig::try_from(merged).is_err(),
+            "Unknown easing was accepted"
+        );
+    }
+
+    /// Parses the passed `input` as a [FontConfig]
+    fn font(input: &str) -> FontConfig {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into().expect("Failed to load font")
+    }
+
+    #[test]
+    fn font_size_relative() {
+        let base = Fraction::new(12u64, 1u64);
+        let size = |s| font(&format!("family: \"Font\"\ncolor: #000000\nsize: {s}")).size;
+
+        assert_eq!(size("1em"), FontSize::Relative(Fraction::new(1u64, 1u64)));
+        assert_eq!(size("1em").get(base), base, "1em is not the base size");
+        assert_eq!(
+            size("2em").get(base),
+            Fraction::new(24u64, 1u64),
+            "2em is not double the base size"
+        );
+        assert_eq!(
+            size("1.5em").get(base),
+            Fraction::new(18u64, 1u64),
+            "1.5em is not 1.5 times the base size"
+        );
+        assert_eq!(
+            size("7").get(base),
+            Fraction::new(7u64, 1u64),
+            "Plain number is not absolute"
+        );
+    }
+
+    #[test]
+    fn color_formats() {
+        let color = |c| font(&format!("family: \"Font\"\ncolor: {c}\nsize: 1")).color;
+        let red = Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+
+        assert_eq!(color("#f00"), red, "Short hex-color is not red");
+        assert_eq!(color("#ff0000"), red, "Hex-color is not red");
+        assert_eq!(color("red"), red, "Named color is not red");
+    }
+
+    /// Parses the passed `input` as a [TickConfig]
+    fn tick(input: &str) -> TickConfig {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into().expect("Failed to load tick")
+    }
+
+    #[test]
+    fn tick_positions() {
+        let base = "x: 1\ny: 2\ncolor: #000000\n\
+            line {\nthickness: 1\ndash {\nlength: 0\nduty: 100%\n}\n}\n\
+            display: true\n";
+
+        let uniform = tick(base);
+        assert_eq!(uniform.origin, Default::default());
+        assert_eq!(uniform.positions, None);
+
+        let explicit = tick(&format!(
+            "{base}origin: (1, 2)\npositions {{\nx: (0, 1.5, 4)\ny: (3)\n}}"
+        ));
+        assert_eq!(
+            explicit.origin,
+            (Fraction::new(1u64, 1u64), Fraction::new(2u64, 1u64))
+        );
+        assert_eq!(
+            explicit.positions,
+            Some(TickPositionsConfig {
+                x: vec![
+                    Fraction::new(0u64, 1u64),
+                    Fraction::new(3u64, 2u64),
+                    Fraction::new(4u64, 1u64),
+                ],
+                y: vec![Fraction::new(3u64, 1u64)],
+            })
+        );
+    }
+
+    #[test]
+    fn time_precision_natural() {
+        let time = |precision: &str| -> Result<TimeConfig, Error> {
+            let input = format!(
+                "display: true\nprefix: \"\"\nprecision: {precision}\n\
+                 font {{ size: 12 color: #000000 family: \"Arial\" }}"
+            );
+            let lexed = lexer::lex(input.as_str()).expect("Failed to lex");
+            let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+            let generic: Config = parsed.into();
+            generic.try_into()
+        };
+
+        assert_eq!(time("2").expect("Failed to load precision").precision, 2);
+        for precision in ["-1", "1.5"] {
+            let error = time(precision).expect_err("Loaded invalid precision");
+            assert!(
+                matches!(error.kind(), ErrorKind::OutOfRange(_)),
+                "Wrong error kind for {precision:?}: {error:#?}"
+            );
+            assert_eq!(error.path(), ["precision"]);
+        }
+    }
+
+    /// Parses the passed `input` as a [DashConfig]
+    fn dash(input: &str) -> Result<DashConfig, Error> {
+        let lexed = lexer::lex(input).expect("Failed to lex");
+        let parsed = parser::parse(lexed.as_slice()).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into()
+    }
+
+    #[test]
+    fn dash_duty_range() {
+        let half = dash("length: 2\nduty: 50%").expect("Failed to load valid duty");
+        assert_eq!(half.duty, Percentage(Fraction::new(50u64, 1u64)));
+
+        let error = dash("length: 2\nduty: 150%").expect_err("Duty above 100% was accepted");
+        assert!(matches!(error.kind(), ErrorKind::OutOfRange(_)));
+        assert_eq!(error.path(), ["duty"]);
+    }
+
+    #[test]
+    fn y_axis() {
+        let identifier = |s: &str| ConfigItem::Value(Value::Identifier(s.to_string()));
+        assert_eq!(YAxis::try_from(identifier("up")).unwrap(), YAxis::Up);
+        assert_eq!(YAxis::try_from(identifier("down")).unwrap(), YAxis::Down);
+        assert!(YAxis::try_from(identifier("left")).is_err());
+    }
 }
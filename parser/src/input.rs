@@ -1,3 +1,9 @@
 pub mod concrete;
 pub mod lexer;
 pub mod parser;
+pub mod preprocess;
+pub mod retarget;
+pub mod stats;
+pub mod validate;
+
+pub use preprocess::{preprocess, preprocess_with_map};
@@ -3,14 +3,21 @@
 //! [TimedInstruction]s are collected into an [AbsoluteTimeline],
 //! which in turn contains [RelativeTimeline]s.
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    slice,
+};
 
 use super::{
     lexer::TimeSpec,
-    parser::{InstructionOrDirective, Value},
+    parser::{Argument, InstructionOrDirective, Value},
+};
+use crate::{
+    common::{color::Color, shape::Shape},
+    config::position::Position,
 };
-use crate::config::position::Position;
-use fraction::{Fraction, Zero};
+use fraction::{Fraction, One, Zero};
 use itertools::{Either, Itertools};
 
 /// Timeline which has multiple relative timelines starting at fixed positions.
@@ -36,7 +43,16 @@ pub struct InstructionGroup {
 /// See documentation of file format.
 #[derive(Debug, PartialEq, Clone)]
 pub enum SetupInstruction {
-    Atom { position: Position, id: String },
+    Atom {
+        position: Position,
+        id: String,
+        /// The time at which the atom appears,
+        /// or [None] if it exists from the start
+        appear_time: Option<Fraction>,
+        /// The shape of the atom,
+        /// or [None] to use the shape from the visual config
+        shape: Option<Shape>,
+    },
 }
 
 impl SetupInstruction {
@@ -46,34 +62,82 @@ impl SetupInstruction {
             Self::Atom { .. } => "atom",
         }
     }
+
+    /// Sets the time at which this [SetupInstruction] takes effect
+    fn at_time(self, time: Option<Fraction>) -> Self {
+        match self {
+            Self::Atom {
+                position,
+                id,
+                shape,
+                ..
+            } => Self::Atom {
+                position,
+                id,
+                appear_time: time,
+                shape,
+            },
+        }
+    }
 }
 
 /// A single instruction which requires a time.
 /// See documentation of file format.
+///
+/// Each instruction has an optional explicit `duration` (`dur=<time>`),
+/// which overrides the duration computed from the machine.
 #[derive(Debug, PartialEq, Clone)]
 pub enum TimedInstruction {
     Load {
         position: Option<Position>,
         id: String,
+        duration: Option<Fraction>,
     },
     Store {
         position: Option<Position>,
         id: String,
+        duration: Option<Fraction>,
     },
     Move {
         position: Position,
         id: String,
+        duration: Option<Fraction>,
+    },
+    /// Moves all atoms with the passed `ids` by the same `offset`
+    MoveBy {
+        offset: Position,
+        ids: Vec<String>,
+        duration: Option<Fraction>,
     },
     Rz {
         value: Fraction,
         targets: Vec<String>,
+        duration: Option<Fraction>,
     },
     Ry {
         value: Fraction,
         targets: Vec<String>,
+        duration: Option<Fraction>,
     },
     Cz {
         targets: Vec<String>,
+        duration: Option<Fraction>,
+    },
+    Measure {
+        targets: Vec<String>,
+        /// The measured classical value, if known
+        result: Option<bool>,
+        duration: Option<Fraction>,
+    },
+    Reset {
+        targets: Vec<String>,
+        duration: Option<Fraction>,
+    },
+    /// Sets the line color of the zone with the passed `id` to `color`
+    Highlight {
+        id: String,
+        color: Color,
+        duration: Option<Fraction>,
     },
 }
 
@@ -83,11 +147,47 @@ impl TimedInstruction {
         match self {
             Self::Load { .. } => "load",
             Self::Store { .. } => "store",
-            Self::Move { .. } => "move",
+            Self::Move { .. } | Self::MoveBy { .. } => "move",
             Self::Rz { .. } => "rz",
             Self::Ry { .. } => "ry",
             Self::Cz { .. } => "cz",
+            Self::Measure { .. } => "measure",
+            Self::Reset { .. } => "reset",
+            Self::Highlight { .. } => "highlight",
+        }
+    }
+
+    /// Get the explicit duration of a [TimedInstruction], if set
+    pub fn duration(&self) -> Option<Fraction> {
+        match self {
+            Self::Load { duration, .. }
+            | Self::Store { duration, .. }
+            | Self::Move { duration, .. }
+            | Self::MoveBy { duration, .. }
+            | Self::Rz { duration, .. }
+            | Self::Ry { duration, .. }
+            | Self::Cz { duration, .. }
+            | Self::Measure { duration, .. }
+            | Self::Reset { duration, .. }
+            | Self::Highlight { duration, .. } => *duration,
+        }
+    }
+
+    /// Sets the explicit duration of this [TimedInstruction]
+    fn with_duration(mut self, explicit: Option<Fraction>) -> Self {
+        match &mut self {
+            Self::Load { duration, .. }
+            | Self::Store { duration, .. }
+            | Self::Move { duration, .. }
+            | Self::MoveBy { duration, .. }
+            | Self::Rz { duration, .. }
+            | Self::Ry { duration, .. }
+            | Self::Cz { duration, .. }
+            | Self::Measure { duration, .. }
+            | Self::Reset { duration, .. }
+            | Self::Highlight { duration, .. } => *duration = explicit,
         }
+        self
     }
 }
 
@@ -96,6 +196,14 @@ impl TimedInstruction {
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct Directives {
     pub targets: Vec<String>,
+    /// The time-window `(start, end)` to show, if any
+    pub window: Option<(Fraction, Fraction)>,
+    /// The weights by which the sizes of atoms are scaled (by atom-id).
+    /// Atoms without a weight have a weight of `1`.
+    pub weights: HashMap<String, Fraction>,
+    /// The aliases for groups of ids (by alias-name).
+    /// The members are already expanded to atom-ids.
+    pub aliases: HashMap<String, Vec<String>>,
 }
 
 /// The parsed instructions, split into [Directives], [SetupInstruction]s, and [TimedInstruction]s.
@@ -104,6 +212,10 @@ pub struct Instructions {
     pub directives: Directives,
     pub setup: Vec<SetupInstruction>,
     pub instructions: AbsoluteTimeline,
+    /// A comment describing the source of these instructions
+    /// (e.g., the leading comment of an imported file), if any.
+    /// Not part of the `.naviz`-format; meant to be re-emitted as a comment.
+    pub source_comment: Option<String>,
 }
 
 /// Error during the parsing of instructions in [Instructions::new].
@@ -141,112 +253,303 @@ pub enum ParseInstructionsError {
         /// Name of instructions or directives
         name: Vec<&'static str>,
     },
-    /// A [SetupInstruction] was given a time
+    /// A [SetupInstruction] was given a relative time
+    /// (setup instructions only allow absolute times)
     SuperfluousTime {
         /// Name of instructions or directives
         name: Vec<&'static str>,
     },
+    /// Multiple atoms were declared with the same id
+    DuplicateAtomId {
+        /// The id of the atoms
+        id: String,
+    },
+    /// An alias was defined multiple times
+    /// or with the id of an atom
+    DuplicateAlias {
+        /// The name of the alias
+        name: String,
+    },
+    /// An instruction was given a named argument it does not accept
+    UnknownNamedArgument {
+        /// Name of the instruction
+        name: &'static str,
+        /// Name of the named argument
+        argument: String,
+    },
+    /// An alias was used before it was defined
+    UndefinedAlias {
+        /// The name of the alias
+        name: String,
+    },
+    /// A member of an alias is neither a previously declared atom nor a previously defined alias
+    UnknownAliasMember {
+        /// The name of the alias
+        name: String,
+        /// The unknown member
+        member: String,
+    },
+    /// The input exceeds a configured [InstructionLimits]-limit
+    LimitExceeded {
+        /// The kind of limit which was exceeded
+        kind: LimitKind,
+        /// The configured limit
+        limit: usize,
+    },
+}
+
+/// The kind of a limit in [InstructionLimits]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LimitKind {
+    /// See [InstructionLimits::max_atoms]
+    Atoms,
+    /// See [InstructionLimits::max_instructions]
+    Instructions,
+}
+
+/// Limits on the size of [Instructions],
+/// allowing to bound the resources used for untrusted inputs.
+/// See [Instructions::new_with_limits].
+/// All limits are unlimited ([None]) by default.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct InstructionLimits {
+    /// The maximum number of declared atoms
+    pub max_atoms: Option<usize>,
+    /// The maximum number of instructions (both setup and timed)
+    pub max_instructions: Option<usize>,
+}
+
+impl InstructionLimits {
+    /// Checks the number of `atoms` and `instructions` against these limits
+    fn check(&self, atoms: usize, instructions: usize) -> Result<(), ParseInstructionsError> {
+        let checks = [
+            (LimitKind::Atoms, self.max_atoms, atoms),
+            (LimitKind::Instructions, self.max_instructions, instructions),
+        ];
+        for (kind, limit, actual) in checks {
+            if let Some(limit) = limit.filter(|limit| actual > *limit) {
+                return Err(ParseInstructionsError::LimitExceeded { kind, limit });
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Instructions {
-    /// Try to parse [Instructions] from a [Vec] of [InstructionOrDirective]s.
-    pub fn new(input: Vec<InstructionOrDirective>) -> Result<Self, ParseInstructionsError> {
+    /// Try to parse [Instructions] from [InstructionOrDirective]s.
+    /// The [InstructionOrDirective]s may be passed incrementally
+    /// (e.g., from [parse_stream][super::parser::parse_stream]).
+    pub fn new(
+        input: impl IntoIterator<Item = InstructionOrDirective>,
+    ) -> Result<Self, ParseInstructionsError> {
+        Self::new_with_limits(input, InstructionLimits::default())
+    }
+
+    /// Try to parse [Instructions] from [InstructionOrDirective]s
+    /// (see [Instructions::new]),
+    /// returning [ParseInstructionsError::LimitExceeded]
+    /// as soon as the input exceeds one of the passed `limits`.
+    pub fn new_with_limits(
+        input: impl IntoIterator<Item = InstructionOrDirective>,
+        limits: InstructionLimits,
+    ) -> Result<Self, ParseInstructionsError> {
+        Self::new_located(input, limits).map_err(|(_, e)| e)
+    }
+
+    /// Try to parse [Instructions] from [InstructionOrDirective]s
+    /// (see [Instructions::new_with_limits]).
+    /// On error, also returns the index of the [InstructionOrDirective] in `input`
+    /// which caused the error,
+    /// or [None] if the error is not caused by a single [InstructionOrDirective]
+    /// (e.g., [ParseInstructionsError::DuplicateAtomId]).
+    pub fn new_located(
+        input: impl IntoIterator<Item = InstructionOrDirective>,
+        limits: InstructionLimits,
+    ) -> Result<Self, (Option<usize>, ParseInstructionsError)> {
         let mut instructions = Instructions::default();
 
         let mut prev = None;
+        // The number of timed instructions inserted so far
+        let mut timed_count = 0;
+        // The ids used in targets so far which were not expanded from an alias
+        let mut used_ids = HashSet::new();
 
-        for i in input {
-            match i {
-                InstructionOrDirective::Directive { name, args } => match name.as_str() {
-                    "target" => {
-                        let id = id(args, "#target")?;
-                        instructions.directives.targets.push(id);
-                    }
-                    _ => return Err(ParseInstructionsError::UnknownDirective { name }),
-                },
+        for (index, i) in input.into_iter().enumerate() {
+            let insert = || -> Result<(), ParseInstructionsError> {
+                match i {
+                    InstructionOrDirective::Directive { name, args } => match name.as_str() {
+                        "target" => {
+                            let id = id(args, "#target")?;
+                            instructions.directives.targets.push(id);
+                        }
+                        "window" => {
+                            let (start, end) = two_numbers(args, "#window")?;
+                            if start >= end {
+                                return Err(ParseInstructionsError::WrongTypeOfArgument {
+                                    name: "#window",
+                                    expected: &[&["number", "number greater than start"]],
+                                });
+                            }
+                            instructions.directives.window = Some((start, end));
+                        }
+                        "weight" => {
+                            let (id, weight) = id_number(args, "#weight")?;
+                            if weight <= Fraction::zero() {
+                                return Err(ParseInstructionsError::WrongTypeOfArgument {
+                                    name: "#weight",
+                                    expected: &[&["id", "positive number"]],
+                                });
+                            }
+                            instructions.directives.weights.insert(id, weight);
+                        }
+                        "alias" => {
+                            let (name, members) = id_targets(args, "#alias")?;
+                            if used_ids.contains(&name) {
+                                return Err(ParseInstructionsError::UndefinedAlias { name });
+                            }
+                            let atoms: HashSet<_> = instructions
+                                .setup
+                                .iter()
+                                .map(|SetupInstruction::Atom { id, .. }| id.as_str())
+                                .collect();
+                            let aliases = &instructions.directives.aliases;
+                            if aliases.contains_key(&name) || atoms.contains(name.as_str()) {
+                                return Err(ParseInstructionsError::DuplicateAlias { name });
+                            }
+                            let mut expanded = Vec::with_capacity(members.len());
+                            for member in members {
+                                if let Some(ids) = aliases.get(&member) {
+                                    expanded.extend(ids.iter().cloned());
+                                } else if atoms.contains(member.as_str()) {
+                                    expanded.push(member);
+                                } else {
+                                    return Err(ParseInstructionsError::UnknownAliasMember {
+                                        name,
+                                        member,
+                                    });
+                                }
+                            }
+                            instructions.directives.aliases.insert(name, expanded);
+                        }
+                        _ => return Err(ParseInstructionsError::UnknownDirective { name }),
+                    },
 
-                InstructionOrDirective::Instruction { time, name, args } => {
-                    match parse_instruction(name.into(), args)? {
-                        Instruction::SetupInstruction(setup) => {
-                            if time.is_some() {
-                                Err(ParseInstructionsError::SuperfluousTime {
-                                    name: vec![setup.str()],
-                                })?
+                    InstructionOrDirective::Instruction { time, name, args } => {
+                        match parse_instruction_with_named(name.into(), args)?
+                            .expand_aliases(&instructions.directives.aliases, &mut used_ids)
+                        {
+                            Instruction::SetupInstruction(setup) => {
+                                let time = setup_time(&time, slice::from_ref(&setup))?;
+                                instructions.setup.push(setup.at_time(time));
+                            }
+                            Instruction::TimedInstruction(instruction) => {
+                                timed_count += 1;
+                                insert_at_time(
+                                    time,
+                                    false,
+                                    vec![instruction],
+                                    &mut prev,
+                                    &mut instructions.instructions,
+                                )?
                             }
-                            instructions.setup.push(setup);
                         }
-                        Instruction::TimedInstruction(instruction) => insert_at_time(
+                    }
+
+                    InstructionOrDirective::GroupedTime {
+                        time,
+                        variable,
+                        group,
+                    } => {
+                        let (setup, timed): (Vec<_>, Vec<_>) = group
+                            .into_iter()
+                            .map(|(name, args)| {
+                                parse_instruction_with_named(name.into(), args).map(|i| {
+                                    i.expand_aliases(
+                                        &instructions.directives.aliases,
+                                        &mut used_ids,
+                                    )
+                                })
+                            })
+                            .process_results(|i| {
+                                i.partition_map(|i| match i {
+                                    Instruction::SetupInstruction(setup) => Either::Left(setup),
+                                    Instruction::TimedInstruction(instruction) => {
+                                        Either::Right(instruction)
+                                    }
+                                })
+                            })?;
+                        let setup_time = setup_time(&time, &setup)?;
+                        setup
+                            .into_iter()
+                            .for_each(|s| instructions.setup.push(s.at_time(setup_time)));
+                        timed_count += timed.len();
+                        insert_at_time(
                             time,
-                            false,
-                            vec![instruction],
+                            variable,
+                            timed,
                             &mut prev,
                             &mut instructions.instructions,
-                        )?,
+                        )?;
                     }
-                }
 
-                InstructionOrDirective::GroupedTime {
-                    time,
-                    variable,
-                    group,
-                } => {
-                    let (setup, timed): (Vec<_>, Vec<_>) = group
-                        .into_iter()
-                        .map(|(name, args)| parse_instruction(name.into(), args))
-                        .process_results(|i| {
-                            i.partition_map(|i| match i {
-                                Instruction::SetupInstruction(setup) => Either::Left(setup),
-                                Instruction::TimedInstruction(instruction) => {
-                                    Either::Right(instruction)
-                                }
-                            })
-                        })?;
-                    if !setup.is_empty() && time.is_some() {
-                        Err(ParseInstructionsError::SuperfluousTime {
-                            name: setup.iter().map(SetupInstruction::str).collect(),
-                        })?
-                    }
-                    setup.into_iter().for_each(|s| instructions.setup.push(s));
-                    insert_at_time(
+                    InstructionOrDirective::GroupedInstruction {
                         time,
                         variable,
-                        timed,
-                        &mut prev,
-                        &mut instructions.instructions,
-                    )?;
-                }
-
-                InstructionOrDirective::GroupedInstruction {
-                    time,
-                    variable,
-                    name,
-                    group,
-                } => {
-                    let (setup, timed): (Vec<_>, Vec<_>) = group
-                        .into_iter()
-                        .map(|args| parse_instruction(name.as_str().into(), args))
-                        .process_results(|i| {
-                            i.partition_map(|i| match i {
-                                Instruction::SetupInstruction(setup) => Either::Left(setup),
-                                Instruction::TimedInstruction(instruction) => {
-                                    Either::Right(instruction)
-                                }
+                        name,
+                        group,
+                    } => {
+                        let (setup, timed): (Vec<_>, Vec<_>) = group
+                            .into_iter()
+                            .map(|args| {
+                                parse_instruction_with_named(name.as_str().into(), args).map(|i| {
+                                    i.expand_aliases(
+                                        &instructions.directives.aliases,
+                                        &mut used_ids,
+                                    )
+                                })
                             })
-                        })?;
-                    if !setup.is_empty() && time.is_some() {
-                        Err(ParseInstructionsError::SuperfluousTime {
-                            name: setup.iter().map(SetupInstruction::str).collect(),
-                        })?
+                            .process_results(|i| {
+                                i.partition_map(|i| match i {
+                                    Instruction::SetupInstruction(setup) => Either::Left(setup),
+                                    Instruction::TimedInstruction(instruction) => {
+                                        Either::Right(instruction)
+                                    }
+                                })
+                            })?;
+                        let setup_time = setup_time(&time, &setup)?;
+                        setup
+                            .into_iter()
+                            .for_each(|s| instructions.setup.push(s.at_time(setup_time)));
+                        timed_count += timed.len();
+                        insert_at_time(
+                            time,
+                            variable,
+                            timed,
+                            &mut prev,
+                            &mut instructions.instructions,
+                        )?;
                     }
-                    setup.into_iter().for_each(|s| instructions.setup.push(s));
-                    insert_at_time(
-                        time,
-                        variable,
-                        timed,
-                        &mut prev,
-                        &mut instructions.instructions,
-                    )?;
                 }
+
+                let atoms = instructions.setup.len();
+                limits.check(atoms, atoms + timed_count)
+            };
+            insert().map_err(|e| (Some(index), e))?;
+        }
+
+        let mut ids = HashSet::new();
+        for SetupInstruction::Atom { id, .. } in &instructions.setup {
+            if !ids.insert(id.as_str()) {
+                return Err((
+                    None,
+                    ParseInstructionsError::DuplicateAtomId { id: id.clone() },
+                ));
+            }
+            if instructions.directives.aliases.contains_key(id) {
+                return Err((
+                    None,
+                    ParseInstructionsError::DuplicateAlias { name: id.clone() },
+                ));
             }
         }
 
@@ -256,12 +559,115 @@ impl Instructions {
     }
 }
 
+/// Gets the time at which the passed [SetupInstruction]s take effect from their `time`.
+/// Setup instructions may only be given [absolute][TimeSpec::Absolute] times;
+/// returns [ParseInstructionsError::SuperfluousTime] for relative times.
+fn setup_time(
+    time: &Option<(TimeSpec, Fraction)>,
+    setup: &[SetupInstruction],
+) -> Result<Option<Fraction>, ParseInstructionsError> {
+    match time {
+        _ if setup.is_empty() => Ok(None),
+        None => Ok(None),
+        Some((TimeSpec::Absolute, time)) => Ok(Some(*time)),
+        Some((TimeSpec::Relative { .. }, _)) => Err(ParseInstructionsError::SuperfluousTime {
+            name: setup.iter().map(SetupInstruction::str).collect(),
+        }),
+    }
+}
+
 /// An instruction: Either a [TimedInstruction] or a [SetupInstruction]
 enum Instruction {
     TimedInstruction(TimedInstruction),
     SetupInstruction(SetupInstruction),
 }
 
+impl Instruction {
+    /// Replaces all ids in targets (and sets of ids) which are names of `aliases`
+    /// by the members of the alias.
+    /// All other ids in targets are recorded in `used_ids`.
+    fn expand_aliases(
+        self,
+        aliases: &HashMap<String, Vec<String>>,
+        used_ids: &mut HashSet<String>,
+    ) -> Self {
+        let expand = |ids: Vec<String>, used_ids: &mut HashSet<String>| -> Vec<String> {
+            ids.into_iter()
+                .flat_map(|id| match aliases.get(&id) {
+                    Some(members) => members.clone(),
+                    None => {
+                        used_ids.insert(id.clone());
+                        vec![id]
+                    }
+                })
+                .collect()
+        };
+        match self {
+            Self::TimedInstruction(instruction) => Self::TimedInstruction(match instruction {
+                TimedInstruction::MoveBy {
+                    offset,
+                    ids,
+                    duration,
+                } => TimedInstruction::MoveBy {
+                    offset,
+                    ids: expand(ids, used_ids),
+                    duration,
+                },
+                TimedInstruction::Rz {
+                    value,
+                    targets,
+                    duration,
+                } => TimedInstruction::Rz {
+                    value,
+                    targets: expand(targets, used_ids),
+                    duration,
+                },
+                TimedInstruction::Ry {
+                    value,
+                    targets,
+                    duration,
+                } => TimedInstruction::Ry {
+                    value,
+                    targets: expand(targets, used_ids),
+                    duration,
+                },
+                TimedInstruction::Cz { targets, duration } => TimedInstruction::Cz {
+                    targets: expand(targets, used_ids),
+                    duration,
+                },
+                TimedInstruction::Measure {
+                    targets,
+                    result,
+                    duration,
+                } => TimedInstruction::Measure {
+                    targets: expand(targets, used_ids),
+                    result,
+                    duration,
+                },
+                TimedInstruction::Reset { targets, duration } => TimedInstruction::Reset {
+                    targets: expand(targets, used_ids),
+                    duration,
+                },
+                instruction @ (TimedInstruction::Load { .. }
+                | TimedInstruction::Store { .. }
+                | TimedInstruction::Move { .. }
+                | TimedInstruction::Highlight { .. }) => instruction,
+            }),
+            setup @ Self::SetupInstruction(_) => setup,
+        }
+    }
+}
+
+impl Instruction {
+    /// Get the name of an [Instruction]
+    fn str(&self) -> &'static str {
+        match self {
+            Self::TimedInstruction(instruction) => instruction.str(),
+            Self::SetupInstruction(setup) => setup.str(),
+        }
+    }
+}
+
 impl From<SetupInstruction> for Instruction {
     fn from(value: SetupInstruction) -> Self {
         Self::SetupInstruction(value)
@@ -275,6 +681,42 @@ impl From<TimedInstruction> for Instruction {
 }
 
 /// Parses a single [Instruction] from the instruction name and its arguments.
+/// The named argument `dur=<time>` sets the explicit duration of a [TimedInstruction].
+/// Will return an [Err] if incompatible arguments were given.
+fn parse_instruction_with_named(
+    name: Cow<str>,
+    args: Vec<Argument>,
+) -> Result<Instruction, ParseInstructionsError> {
+    let (args, named): (Vec<_>, Vec<_>) = args.into_iter().partition_map(|arg| match arg {
+        Argument::Positional(value) => Either::Left(value),
+        Argument::Named(name, value) => Either::Right((name, value)),
+    });
+    let mut instruction = parse_instruction(name, args)?;
+
+    for (argument, value) in named {
+        instruction = match (instruction, argument.as_str()) {
+            (Instruction::TimedInstruction(timed), "dur") => match value {
+                Value::Number(n) if n >= Fraction::zero() => timed.with_duration(Some(n)).into(),
+                _ => {
+                    return Err(ParseInstructionsError::WrongTypeOfArgument {
+                        name: timed.str(),
+                        expected: &[&["dur=<non-negative number>"]],
+                    })
+                }
+            },
+            (instruction, _) => {
+                return Err(ParseInstructionsError::UnknownNamedArgument {
+                    name: instruction.str(),
+                    argument,
+                })
+            }
+        };
+    }
+
+    Ok(instruction)
+}
+
+/// Parses a single [Instruction] from the instruction name and its positional arguments.
 /// Will return an [Err] if incompatible arguments were given.
 fn parse_instruction(
     name: Cow<str>,
@@ -282,32 +724,97 @@ fn parse_instruction(
 ) -> Result<Instruction, ParseInstructionsError> {
     Ok(match &*name {
         "atom" => {
-            let (position, id) = position_id(args, "atom")?;
-            SetupInstruction::Atom { position, id }.into()
+            let (position, id, shape) = position_id_maybe_shape(args, "atom")?;
+            SetupInstruction::Atom {
+                position,
+                id,
+                appear_time: None,
+                shape,
+            }
+            .into()
         }
         "load" => {
             let (position, id) = maybe_position_id(args, "load")?;
-            TimedInstruction::Load { position, id }.into()
+            TimedInstruction::Load {
+                position,
+                id,
+                duration: None,
+            }
+            .into()
         }
         "store" => {
             let (position, id) = maybe_position_id(args, "store")?;
-            TimedInstruction::Store { position, id }.into()
+            TimedInstruction::Store {
+                position,
+                id,
+                duration: None,
+            }
+            .into()
         }
-        "move" => {
-            let (position, id) = position_id(args, "move")?;
-            TimedInstruction::Move { position, id }.into()
+        "move" => match position_id_or_ids(args, "move")? {
+            (position, Either::Left(id)) => TimedInstruction::Move {
+                position,
+                id,
+                duration: None,
+            },
+            (offset, Either::Right(ids)) => TimedInstruction::MoveBy {
+                offset,
+                ids,
+                duration: None,
+            },
         }
+        .into(),
         "rz" => {
             let (value, targets) = number_target(args, "rz")?;
-            TimedInstruction::Rz { value, targets }.into()
+            TimedInstruction::Rz {
+                value,
+                targets,
+                duration: None,
+            }
+            .into()
         }
         "ry" => {
             let (value, targets) = number_target(args, "ry")?;
-            TimedInstruction::Ry { value, targets }.into()
+            TimedInstruction::Ry {
+                value,
+                targets,
+                duration: None,
+            }
+            .into()
         }
         "cz" => {
             let targets = target(args, "cz")?;
-            TimedInstruction::Cz { targets }.into()
+            TimedInstruction::Cz {
+                targets,
+                duration: None,
+            }
+            .into()
+        }
+        "measure" => {
+            let (targets, result) = target_maybe_bit(args, "measure")?;
+            TimedInstruction::Measure {
+                targets,
+                result,
+                duration: None,
+            }
+            .into()
+        }
+        "reset" => {
+            let targets = target(args, "reset")?;
+            TimedInstruction::Reset {
+                targets,
+                duration: None,
+            }
+            .into()
+        }
+        "highlight" => {
+            let (id, color) = id_color(args, "highlight")?;
+            TimedInstruction::Highlight {
+                id,
+                color,
+                duration: None,
+            }
+            .into()
         }
         _ => Err(ParseInstructionsError::UnknownInstruction {
             name: name.into_owned(),
@@ -340,19 +847,13 @@ fn insert_at_time(
     let (spec, mut time) = time.ok_or_else(|| ParseInstructionsError::MissingTime {
         name: instructions.iter().map(TimedInstruction::str).collect(),
     })?;
+    let group = InstructionGroup {
+        variable,
+        instructions,
+    };
     match spec {
         TimeSpec::Absolute => {
-            target.push((
-                time,
-                vec![(
-                    true,
-                    Fraction::zero(),
-                    InstructionGroup {
-                        variable,
-                        instructions,
-                    },
-                )],
-            ));
+            target.push((time, vec![(true, Fraction::zero(), group)]));
             *prev = Some(target.len() - 1);
         }
         TimeSpec::Relative {
@@ -363,27 +864,10 @@ fn insert_at_time(
                 time *= -1;
             }
             if let Some(idx) = prev {
-                target[*idx].1.push((
-                    from_start,
-                    time,
-                    InstructionGroup {
-                        variable,
-                        instructions,
-                    },
-                ));
+                target[*idx].1.push((from_start, time, group));
                 // prev stays the same
             } else {
-                target.push((
-                    time,
-                    vec![(
-                        from_start,
-                        time,
-                        InstructionGroup {
-                            variable,
-                            instructions,
-                        },
-                    )],
-                ));
+                target.push((time, vec![(from_start, time, group)]));
                 *prev = Some(target.len() - 1);
             }
         }
@@ -391,27 +875,68 @@ fn insert_at_time(
     Ok(())
 }
 
-/// Tries to parse the arguments into a position and an id.
+/// Tries to parse the arguments into a position, an id, and optionally a [Shape].
 /// Returns a [ParseInstructionsError] if there is a wrong number of arguments
 /// or they have wrong types.
-fn position_id(
+fn position_id_maybe_shape(
     args: Vec<Value>,
     name: &'static str,
-) -> Result<(Position, String), ParseInstructionsError> {
+) -> Result<(Position, String, Option<Shape>), ParseInstructionsError> {
     let error = || ParseInstructionsError::WrongTypeOfArgument {
         name,
-        expected: &[&["position", "id"]],
+        expected: &[&["position", "id"], &["position", "id", "shape"]],
     };
 
-    match n_args(args, name, &[2])? {
-        [Value::Tuple(t), Value::Identifier(id)] => match maybe_get_n(t).map_err(|_| error())? {
-            [Value::Number(x), Value::Number(y)] => Ok(((x, y), id)),
+    let (position, id, shape) = match maybe_get_n(args) {
+        Ok([position, id, Value::Identifier(shape)]) => (
+            position,
+            id,
+            Some(Shape::from_name(&shape).ok_or_else(error)?),
+        ),
+        Ok(_) => return Err(error()),
+        Err(args) => {
+            let [position, id] = n_args(args, name, &[2, 3])?;
+            (position, id, None)
+        }
+    };
+
+    match (position, id) {
+        (Value::Tuple(t), Value::Identifier(id)) => match maybe_get_n(t).map_err(|_| error())? {
+            [Value::Number(x), Value::Number(y)] => Ok(((x, y), id, shape)),
             _ => Err(error()),
         },
         _ => Err(error()),
     }
 }
 
+/// Tries to parse the arguments into a position and either an id or a set of ids.
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn position_id_or_ids(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(Position, Either<String, Vec<String>>), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["position", "id"], &["position", "set(id)"]],
+    };
+
+    match n_args(args, name, &[2])? {
+        [Value::Tuple(t), target] => {
+            let position = match maybe_get_n(t).map_err(|_| error())? {
+                [Value::Number(x), Value::Number(y)] => (x, y),
+                _ => return Err(error()),
+            };
+            match target {
+                Value::Identifier(id) => Ok((position, Either::Left(id))),
+                ids @ Value::Set(_) => Ok((position, Either::Right(value_to_target(ids, error)?))),
+                _ => Err(error()),
+            }
+        }
+        _ => Err(error()),
+    }
+}
+
 /// Tries to parse the arguments into a position and an id or into just an id.
 /// Returns a [ParseInstructionsError] if there is a wrong number of arguments
 /// or they have wrong types.
@@ -459,6 +984,91 @@ fn number_target(
     }
 }
 
+/// Tries to parse the arguments into two numbers.
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn two_numbers(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(Fraction, Fraction), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["number", "number"]],
+    };
+
+    match n_args(args, name, &[2])? {
+        [Value::Number(a), Value::Number(b)] => Ok((a, b)),
+        _ => Err(error()),
+    }
+}
+
+/// Tries to parse the arguments into an id and a color.
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn id_color(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(String, Color), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["id", "color"]],
+    };
+
+    match n_args(args, name, &[2])? {
+        [Value::Identifier(id), Value::Color(color)] => Ok((id, color)),
+        _ => Err(error()),
+    }
+}
+
+/// Tries to parse the arguments into an id and a number.
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn id_number(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(String, Fraction), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["id", "number"]],
+    };
+
+    match n_args(args, name, &[2])? {
+        [Value::Identifier(id), Value::Number(n)] => Ok((id, n)),
+        _ => Err(error()),
+    }
+}
+
+/// Tries to parse the arguments into an id followed by at least one target.
+/// The targets are concatenated.
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn id_targets(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(String, Vec<String>), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["id", "target", "..."]],
+    };
+
+    if args.len() < 2 {
+        return Err(ParseInstructionsError::WrongNumberOfArguments {
+            name,
+            expected: &[2],
+            actual: args.len(),
+        });
+    }
+    let mut args = args.into_iter();
+    let Some(Value::Identifier(id)) = args.next() else {
+        return Err(error());
+    };
+    let targets = args
+        .map(|target| value_to_target(target, error))
+        .flatten_ok()
+        .collect::<Result<_, _>>()?;
+    Ok((id, targets))
+}
+
 /// Tries to parse the arguments into just an id.
 /// Returns a [ParseInstructionsError] if there is a wrong number of arguments
 /// or they have wrong types.
@@ -487,6 +1097,37 @@ fn target(args: Vec<Value>, name: &'static str) -> Result<Vec<String>, ParseInst
     value_to_target(target, error)
 }
 
+/// Tries to parse the arguments into a target and optionally a bit (`0` or `1`).
+/// Returns a [ParseInstructionsError] if there is a wrong number of arguments
+/// or they have wrong types.
+fn target_maybe_bit(
+    args: Vec<Value>,
+    name: &'static str,
+) -> Result<(Vec<String>, Option<bool>), ParseInstructionsError> {
+    let error = || ParseInstructionsError::WrongTypeOfArgument {
+        name,
+        expected: &[&["target", "bit"], &["target"]],
+    };
+
+    match maybe_get_n(args) {
+        Ok([target, Value::Number(n)]) => {
+            let bit = if n.is_zero() {
+                false
+            } else if n == Fraction::one() {
+                true
+            } else {
+                return Err(error());
+            };
+            Ok((value_to_target(target, error)?, Some(bit)))
+        }
+        Ok(_) => Err(error()),
+        Err(args) => {
+            let [target] = n_args(args, name, &[1, 2])?;
+            Ok((value_to_target(target, error)?, None))
+        }
+    }
+}
+
 /// Tries to convert a [Value] to a target (list of IDs).
 /// Will return the `error` if the [Value] contains anything
 /// apart from [Set][Value::Set]s and [Identifier][Value::Identifier]s.
@@ -531,7 +1172,10 @@ pub fn n_args<T, const N: usize>(
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::input::{lexer, parser};
+    use crate::{
+        input::{lexer, parser},
+        test_utils::instructions,
+    };
 
     #[test]
     pub fn example() {
@@ -543,20 +1187,29 @@ mod test {
         let expected = Instructions {
             directives: Directives {
                 targets: vec!["example".to_string()],
+                window: None,
+                weights: HashMap::new(),
+                aliases: HashMap::new(),
             },
 
             setup: vec![
                 SetupInstruction::Atom {
                     position: (Fraction::new(0u64, 1u64), Fraction::new(0u64, 1u64)),
                     id: "atom0".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
                 SetupInstruction::Atom {
                     position: (Fraction::new(16u64, 1u64), Fraction::new(0u64, 1u64)),
                     id: "atom1".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
                 SetupInstruction::Atom {
                     position: (Fraction::new(32u64, 1u64), Fraction::new(0u64, 1u64)),
                     id: "atom2".to_string(),
+                    appear_time: None,
+                    shape: None,
                 },
             ],
             instructions: vec![(
@@ -571,6 +1224,7 @@ mod test {
                                 TimedInstruction::Load {
                                     position: None,
                                     id: "atom0".to_string(),
+                                    duration: None,
                                 },
                                 TimedInstruction::Load {
                                     position: Some((
@@ -578,6 +1232,7 @@ mod test {
                                         Fraction::new(2u64, 1u64),
                                     )),
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 },
                             ],
                         },
@@ -590,6 +1245,7 @@ mod test {
                             instructions: vec![TimedInstruction::Move {
                                 position: (Fraction::new(8u64, 1u64), Fraction::new(8u64, 1u64)),
                                 id: "atom0".to_string(),
+                                duration: None,
                             }],
                         },
                     ),
@@ -601,6 +1257,7 @@ mod test {
                             instructions: vec![TimedInstruction::Move {
                                 position: (Fraction::new(16u64, 1u64), Fraction::new(16u64, 1u64)),
                                 id: "atom1".to_string(),
+                                duration: None,
                             }],
                         },
                     ),
@@ -612,6 +1269,7 @@ mod test {
                             instructions: vec![TimedInstruction::Store {
                                 position: None,
                                 id: "atom0".to_string(),
+                                duration: None,
                             }],
                         },
                     ),
@@ -623,6 +1281,7 @@ mod test {
                             instructions: vec![TimedInstruction::Store {
                                 position: None,
                                 id: "atom1".to_string(),
+                                duration: None,
                             }],
                         },
                     ),
@@ -634,6 +1293,7 @@ mod test {
                             instructions: vec![TimedInstruction::Rz {
                                 value: Fraction::new(3141u64, 1000u64),
                                 targets: vec!["atom0".to_string()],
+                                duration: None,
                             }],
                         },
                     ),
@@ -645,6 +1305,7 @@ mod test {
                             instructions: vec![TimedInstruction::Ry {
                                 value: Fraction::new(3141u64, 1000u64),
                                 targets: vec!["atom1".to_string()],
+                                duration: None,
                             }],
                         },
                     ),
@@ -655,6 +1316,7 @@ mod test {
                             variable: false,
                             instructions: vec![TimedInstruction::Cz {
                                 targets: vec!["zone0".to_string()],
+                                duration: None,
                             }],
                         },
                     ),
@@ -666,16 +1328,19 @@ mod test {
                             instructions: vec![
                                 TimedInstruction::Cz {
                                     targets: vec!["zone1".to_string()],
+                                    duration: None,
                                 },
                                 TimedInstruction::Ry {
                                     value: Fraction::new(3141u64, 1000u64),
                                     targets: vec!["atom0".to_string()],
+                                    duration: None,
                                 },
                             ],
                         },
                     ),
                 ],
             )],
+            source_comment: None,
         };
 
         let lexed = lexer::lex(input).expect("Failed to lex");
@@ -701,11 +1366,11 @@ mod test {
                 time: None,
                 name: "atom".to_string(),
                 args: vec![
-                    Value::Tuple(vec![
+                    Argument::Positional(Value::Tuple(vec![
                         Value::Number(Fraction::new(0u64, 1u64)),
                         Value::Number(Fraction::new(0u64, 1u64)),
-                    ]),
-                    Value::Identifier("atom1".to_string()),
+                    ])),
+                    Argument::Positional(Value::Identifier("atom1".to_string())),
                 ],
             },
             InstructionOrDirective::Instruction {
@@ -717,7 +1382,7 @@ mod test {
                     Fraction::new(0u64, 1u64),
                 )),
                 name: "load".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -728,7 +1393,7 @@ mod test {
                     Fraction::new(2u64, 1u64),
                 )),
                 name: "store".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -739,12 +1404,12 @@ mod test {
                     Fraction::new(3u64, 1u64),
                 )),
                 name: "load".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((TimeSpec::Absolute, Fraction::new(20u64, 1u64))),
                 name: "store".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -755,7 +1420,7 @@ mod test {
                     Fraction::new(2u64, 1u64),
                 )),
                 name: "load".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -766,7 +1431,7 @@ mod test {
                     Fraction::new(0u64, 1u64),
                 )),
                 name: "store".to_string(),
-                args: vec![Value::Identifier("atom1".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
             },
             InstructionOrDirective::GroupedTime {
                 time: Some((TimeSpec::Absolute, Fraction::new(20u64, 1u64))),
@@ -774,11 +1439,11 @@ mod test {
                 group: vec![
                     (
                         "load".to_string(),
-                        vec![Value::Identifier("atom0".to_string())],
+                        vec![Argument::Positional(Value::Identifier("atom0".to_string()))],
                     ),
                     (
                         "load".to_string(),
-                        vec![Value::Identifier("atom1".to_string())],
+                        vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
                     ),
                 ],
             },
@@ -793,8 +1458,8 @@ mod test {
                 variable: true,
                 name: "store".to_string(),
                 group: vec![
-                    vec![Value::Identifier("atom0".to_string())],
-                    vec![Value::Identifier("atom1".to_string())],
+                    vec![Argument::Positional(Value::Identifier("atom0".to_string()))],
+                    vec![Argument::Positional(Value::Identifier("atom1".to_string()))],
                 ],
             },
         ];
@@ -802,10 +1467,15 @@ mod test {
         let expected = Instructions {
             directives: Directives {
                 targets: vec!["machine_a".to_string(), "machine_b".to_string()],
+                window: None,
+                weights: HashMap::new(),
+                aliases: HashMap::new(),
             },
             setup: vec![SetupInstruction::Atom {
                 position: (Fraction::new(0u64, 1u64), Fraction::new(0u64, 1u64)),
                 id: "atom1".to_string(),
+                appear_time: None,
+                shape: None,
             }],
             instructions: vec![
                 (
@@ -819,6 +1489,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Load {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -830,6 +1501,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Store {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -841,6 +1513,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Load {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -857,6 +1530,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Store {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -868,6 +1542,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Load {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -879,6 +1554,7 @@ mod test {
                                 instructions: vec![TimedInstruction::Store {
                                     position: None,
                                     id: "atom1".to_string(),
+                                    duration: None,
                                 }],
                             },
                         ),
@@ -896,10 +1572,12 @@ mod test {
                                     TimedInstruction::Load {
                                         position: None,
                                         id: "atom0".to_string(),
+                                        duration: None,
                                     },
                                     TimedInstruction::Load {
                                         position: None,
                                         id: "atom1".to_string(),
+                                        duration: None,
                                     },
                                 ],
                             },
@@ -913,10 +1591,12 @@ mod test {
                                     TimedInstruction::Store {
                                         position: None,
                                         id: "atom0".to_string(),
+                                        duration: None,
                                     },
                                     TimedInstruction::Store {
                                         position: None,
                                         id: "atom1".to_string(),
+                                        duration: None,
                                     },
                                 ],
                             },
@@ -924,10 +1604,484 @@ mod test {
                     ],
                 ),
             ],
+            source_comment: None,
         };
 
         let actual = Instructions::new(input).expect("Failed to parse into tree");
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    pub fn measure_reset() {
+        let parse = |name, args| match parse_instruction(Cow::Borrowed(name), args) {
+            Ok(Instruction::TimedInstruction(i)) => Ok(i),
+            Ok(Instruction::SetupInstruction(i)) => {
+                panic!("Parsed as setup instruction: {i:?}")
+            }
+            Err(e) => Err(e),
+        };
+        let atom = || Value::Identifier("atom0".to_string());
+
+        assert_eq!(
+            parse("measure", vec![atom()]).expect("Failed to parse measure"),
+            TimedInstruction::Measure {
+                targets: vec!["atom0".to_string()],
+                result: None,
+                duration: None,
+            }
+        );
+        assert_eq!(
+            parse("measure", vec![atom(), Value::Number(Fraction::one())])
+                .expect("Failed to parse measure with result"),
+            TimedInstruction::Measure {
+                targets: vec!["atom0".to_string()],
+                result: Some(true),
+                duration: None,
+            }
+        );
+        assert!(
+            matches!(
+                parse("measure", vec![atom(), Value::Number(Fraction::from(2))]),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Measurement result must be a bit"
+        );
+        assert_eq!(
+            parse("reset", vec![atom()]).expect("Failed to parse reset"),
+            TimedInstruction::Reset {
+                targets: vec!["atom0".to_string()],
+                duration: None,
+            }
+        );
+    }
+
+    #[test]
+    pub fn highlight() {
+        let parse = |args| match parse_instruction(Cow::Borrowed("highlight"), args) {
+            Ok(Instruction::TimedInstruction(i)) => Ok(i),
+            Ok(Instruction::SetupInstruction(i)) => {
+                panic!("Parsed as setup instruction: {i:?}")
+            }
+            Err(e) => Err(e),
+        };
+        let zone = || Value::Identifier("zone0".to_string());
+        let color = Color {
+            r: 255,
+            g: 204,
+            b: 0,
+            a: 255,
+        };
+
+        assert_eq!(
+            parse(vec![zone(), Value::Color(color)]).expect("Failed to parse highlight"),
+            TimedInstruction::Highlight {
+                id: "zone0".to_string(),
+                color,
+                duration: None,
+            }
+        );
+        assert!(
+            matches!(
+                parse(vec![zone(), Value::Number(Fraction::one())]),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Highlight requires a color"
+        );
+    }
+
+    /// `move` should move a single id to a position or a set of ids by an offset.
+    #[test]
+    pub fn move_single_or_set() {
+        let parse = |args| match parse_instruction(Cow::Borrowed("move"), args) {
+            Ok(Instruction::TimedInstruction(i)) => Ok(i),
+            Ok(Instruction::SetupInstruction(i)) => {
+                panic!("Parsed as setup instruction: {i:?}")
+            }
+            Err(e) => Err(e),
+        };
+        let position = || {
+            Value::Tuple(vec![
+                Value::Number(Fraction::from(1)),
+                Value::Number(Fraction::from(-2)),
+            ])
+        };
+        let id = |id: &str| Value::Identifier(id.to_string());
+
+        assert_eq!(
+            parse(vec![position(), id("atom0")]).expect("Failed to parse move"),
+            TimedInstruction::Move {
+                position: (Fraction::from(1), Fraction::from(-2)),
+                id: "atom0".to_string(),
+                duration: None,
+            }
+        );
+        assert_eq!(
+            parse(vec![
+                position(),
+                Value::Set(vec![id("atom0"), id("atom1"), id("atom2")])
+            ])
+            .expect("Failed to parse move with set"),
+            TimedInstruction::MoveBy {
+                offset: (Fraction::from(1), Fraction::from(-2)),
+                ids: vec![
+                    "atom0".to_string(),
+                    "atom1".to_string(),
+                    "atom2".to_string()
+                ],
+                duration: None,
+            }
+        );
+        assert!(
+            matches!(
+                parse(vec![position(), Value::Number(Fraction::one())]),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Move target must be an id or a set of ids"
+        );
+    }
+
+    /// `atom` should accept an absolute time at which the atom appears.
+    #[test]
+    pub fn atom_appear_time() {
+        let parsed = instructions("atom (0, 0) a\n@5 atom (1, 2) b")
+            .expect("Failed to parse atom with time");
+        assert_eq!(
+            parsed.setup,
+            [
+                SetupInstruction::Atom {
+                    position: (Fraction::from(0), Fraction::from(0)),
+                    id: "a".to_string(),
+                    appear_time: None,
+                    shape: None,
+                },
+                SetupInstruction::Atom {
+                    position: (Fraction::from(1), Fraction::from(2)),
+                    id: "b".to_string(),
+                    appear_time: Some(Fraction::from(5)),
+                    shape: None,
+                },
+            ]
+        );
+
+        assert!(
+            matches!(
+                instructions("@+ atom (0, 0) a"),
+                Err(ParseInstructionsError::SuperfluousTime { .. })
+            ),
+            "Relative times are not allowed for atoms"
+        );
+    }
+    /// `atom` should accept an optional shape.
+    #[test]
+    pub fn atom_shape() {
+        let parsed = instructions(
+            "atom (0, 0) a square
+@5 atom (1, 2) b diamond",
+        )
+        .expect("Failed to parse atom with shape");
+        let shapes: Vec<_> = parsed
+            .setup
+            .iter()
+            .map(|SetupInstruction::Atom { shape, .. }| *shape)
+            .collect();
+        assert_eq!(shapes, [Some(Shape::Square), Some(Shape::Diamond)]);
+
+        assert!(
+            matches!(
+                instructions("atom (0, 0) a hexagon"),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Unknown shape was accepted"
+        );
+    }
+
+    /// Errors should be located at the [InstructionOrDirective] which caused them,
+    /// or at none if they are not caused by a single one.
+    #[test]
+    pub fn located_errors() {
+        let located = |input| {
+            let lexed = lexer::lex(input).expect("Failed to lex");
+            let parsed = parser::parse(&lexed).expect("Failed to parse");
+            Instructions::new_located(parsed, InstructionLimits::default())
+                .map(|_| ())
+                .expect_err("Invalid instructions passed")
+        };
+
+        assert!(matches!(
+            located("atom (0, 0) q0\n@0 load q0\n@0 unknown q0"),
+            (Some(2), ParseInstructionsError::UnknownInstruction { .. })
+        ));
+        assert!(matches!(
+            located("atom (0, 0) q0\natom (1, 1) q0"),
+            (None, ParseInstructionsError::DuplicateAtomId { .. })
+        ));
+    }
+
+    /// Exceeding a configured limit should be an error,
+    /// while staying at or under the limit should pass.
+    #[test]
+    pub fn limits() {
+        let with_limits = |input, limits| {
+            let lexed = lexer::lex(input).expect("Failed to lex");
+            let parsed = parser::parse(&lexed).expect("Failed to parse");
+            Instructions::new_with_limits(parsed, limits)
+        };
+        let input = "atom (0, 0) q0\natom (1, 1) q1\n@0 [\nload q0\nload q1\n]";
+
+        assert!(
+            matches!(
+                with_limits(
+                    input,
+                    InstructionLimits {
+                        max_atoms: Some(1),
+                        ..Default::default()
+                    }
+                ),
+                Err(ParseInstructionsError::LimitExceeded {
+                    kind: LimitKind::Atoms,
+                    limit: 1
+                })
+            ),
+            "Atom limit was not enforced"
+        );
+        assert!(
+            matches!(
+                with_limits(
+                    input,
+                    InstructionLimits {
+                        max_instructions: Some(3),
+                        ..Default::default()
+                    }
+                ),
+                Err(ParseInstructionsError::LimitExceeded {
+                    kind: LimitKind::Instructions,
+                    limit: 3
+                })
+            ),
+            "Instruction limit was not enforced"
+        );
+        assert!(
+            with_limits(
+                input,
+                InstructionLimits {
+                    max_atoms: Some(2),
+                    max_instructions: Some(4),
+                }
+            )
+            .is_ok(),
+            "Input within limits was rejected"
+        );
+        assert!(
+            with_limits(input, InstructionLimits::default()).is_ok(),
+            "Input was rejected without limits"
+        );
+    }
+
+    /// Declaring two atoms with the same id should be an error.
+    #[test]
+    pub fn duplicate_atom_id() {
+        assert!(
+            matches!(
+                instructions("atom (0, 0) q0\natom (1, 1) q0"),
+                Err(ParseInstructionsError::DuplicateAtomId { id }) if id == "q0"
+            ),
+            "Duplicate atom id was accepted"
+        );
+        assert!(
+            matches!(
+                instructions("atom (0, 0) q0\n@5 atom [\n(1, 1) q1\n(2, 2) q0\n]"),
+                Err(ParseInstructionsError::DuplicateAtomId { .. })
+            ),
+            "Duplicate atom id in group was accepted"
+        );
+        assert!(
+            instructions("atom (0, 0) q0\natom (1, 1) q1").is_ok(),
+            "Distinct atom ids were rejected"
+        );
+    }
+
+    /// `#window` should set the time-window to show.
+    #[test]
+    pub fn window() {
+        let parsed = instructions("#window 2 7.5").expect("Failed to parse window");
+        assert_eq!(
+            parsed.directives.window,
+            Some((Fraction::from(2), Fraction::new(15u64, 2u64)))
+        );
+
+        assert!(
+            matches!(
+                instructions("#window 2"),
+                Err(ParseInstructionsError::WrongNumberOfArguments { .. })
+            ),
+            "Window requires a start and an end"
+        );
+        assert!(
+            matches!(
+                instructions("#window a b"),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Window bounds must be numbers"
+        );
+        for window in ["#window 7 2", "#window 2 2"] {
+            assert!(
+                matches!(
+                    instructions(window),
+                    Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+                ),
+                "Reversed or empty window {window:?} was accepted"
+            );
+        }
+    }
+
+    /// `#weight` should set the weight of an atom.
+    #[test]
+    pub fn weight() {
+        let parsed = instructions("#weight a 2\n#weight b 0.5\natom (0, 0) a")
+            .expect("Failed to parse weights");
+        assert_eq!(
+            parsed.directives.weights,
+            HashMap::from([
+                ("a".to_string(), Fraction::from(2)),
+                ("b".to_string(), Fraction::new(1u64, 2u64)),
+            ])
+        );
+
+        assert!(
+            matches!(
+                instructions("#weight 2 a"),
+                Err(ParseInstructionsError::WrongTypeOfArgument { .. })
+            ),
+            "Weight requires an id followed by a number"
+        );
+        for weight in ["0", "-1"] {
+            assert!(
+                matches!(
+                    instructions(&format!("#weight a {weight}")),
+                    Err(ParseInstructionsError::WrongTypeOfArgument {
+                        name: "#weight",
+                        ..
+                    })
+                ),
+                "Accepted non-positive weight {weight}"
+            );
+        }
+    }
+
+    /// Aliases should be expanded to their members wherever a set of ids is allowed.
+    #[test]
+    pub fn alias_expanded() {
+        let parsed = instructions(
+            "atom (0, 0) a\n\
+             atom (1, 0) b\n\
+             atom (2, 0) c\n\
+             #alias row a b\n\
+             #alias all row c\n\
+             @0 move (0, 1) {row}\n\
+             @+ cz all",
+        )
+        .expect("Failed to parse aliases");
+
+        assert_eq!(
+            parsed.directives.aliases["all"],
+            ["a", "b", "c"],
+            "Nested alias not expanded"
+        );
+        let instructions: Vec<_> = parsed.instructions[0]
+            .1
+            .iter()
+            .flat_map(|(_, _, group)| &group.instructions)
+            .collect();
+        assert_eq!(
+            instructions,
+            [
+                &TimedInstruction::MoveBy {
+                    offset: (Fraction::zero(), Fraction::one()),
+                    ids: vec!["a".to_string(), "b".to_string()],
+                    duration: None,
+                },
+                &TimedInstruction::Cz {
+                    targets: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    duration: None,
+                },
+            ]
+        );
+    }
+
+    /// `dur=<time>` should set the duration of the instruction it is passed to.
+    #[test]
+    pub fn duration_argument() {
+        let parsed = instructions(
+            "atom (0, 0) a\n\
+             atom (1, 1) b\n\
+             @0 cz {a, b} dur=5\n\
+             @1 [\n\
+             rz 1 a\n\
+             ry 1 b dur=0.5\n\
+             ]",
+        )
+        .expect("Failed to parse");
+        let durations: Vec<_> = parsed
+            .instructions
+            .iter()
+            .flat_map(|(_, timeline)| timeline)
+            .map(|(_, _, group)| {
+                group
+                    .instructions
+                    .iter()
+                    .map(TimedInstruction::duration)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(
+            durations,
+            [
+                vec![Some(Fraction::from(5))],
+                vec![None, Some(Fraction::new(1u64, 2u64))],
+            ]
+        );
+
+        assert!(matches!(
+            instructions("atom (0, 0) a dur=1"),
+            Err(ParseInstructionsError::UnknownNamedArgument { name: "atom", argument })
+                if argument == "dur"
+        ));
+        assert!(matches!(
+            instructions("atom (0, 0) a\n@0 cz a speed=1"),
+            Err(ParseInstructionsError::UnknownNamedArgument { name: "cz", argument })
+                if argument == "speed"
+        ));
+        assert!(matches!(
+            instructions("atom (0, 0) a\n@0 cz a dur=a"),
+            Err(ParseInstructionsError::WrongTypeOfArgument { name: "cz", .. })
+        ));
+    }
+
+    /// Aliases must be defined before use, once, and only from known members.
+    #[test]
+    pub fn alias_invalid() {
+        assert!(
+            matches!(
+                instructions("atom (0, 0) a\n@0 cz row\n#alias row a"),
+                Err(ParseInstructionsError::UndefinedAlias { name }) if name == "row"
+            ),
+            "Alias was used before its definition"
+        );
+        assert!(
+            matches!(
+                instructions("atom (0, 0) a\n#alias row a\n#alias row a"),
+                Err(ParseInstructionsError::DuplicateAlias { name }) if name == "row"
+            ),
+            "Alias was defined twice"
+        );
+        assert!(
+            matches!(
+                instructions("atom (0, 0) a\n#alias row a b"),
+                Err(ParseInstructionsError::UnknownAliasMember { name, member })
+                    if name == "row" && member == "b"
+            ),
+            "Alias contains an unknown member"
+        );
+    }
 }
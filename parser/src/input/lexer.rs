@@ -1,12 +1,14 @@
 //! Lexer for the `.naviz` format.
 //! Use [lex] to lex into a stream of [Token]s.
 
-use crate::common;
+use crate::{common, ParseError};
+use std::ops::Range;
 use token::token;
 use winnow::{
     ascii::{multispace0, space0},
-    combinator::{preceded, repeat, terminated},
-    stream::{AsChar, Compare, FindSlice, SliceLen, Stream, StreamIsPartial},
+    combinator::opt,
+    error::{ContextError, ErrMode},
+    stream::{AsChar, Compare, FindSlice, LocatingSlice, SliceLen, Stream, StreamIsPartial},
     Parser,
 };
 
@@ -51,6 +53,8 @@ pub enum Token<T> {
     Directive(T),
     /// The separator between instructions
     Separator,
+    /// The separator between the name and the value of a named argument (`<name>=<value>`)
+    Assign,
 }
 
 impl<T> From<GenericToken<T>> for Token<T> {
@@ -103,7 +107,7 @@ where
     <I as Stream>::Token: AsChar + Clone,
     I::Slice: SliceLen,
 {
-    preceded(multispace0, repeat(0.., terminated(token, space0)))
+    tokens(token, |token| token)
         .parse(input)
         .map(|mut tokens: Vec<_>| {
             // Ensure separator at end of token-stream
@@ -115,6 +119,66 @@ where
         })
 }
 
+/// A [Token] together with its byte-[Range] in the input
+pub type Spanned<'a> = (Token<&'a str>, Range<usize>);
+
+/// Lexes a [str] into a [Vec] of [Token]s together with their byte-[Range]s in the `input`
+/// (see [lex]), or returns an [Err] if lexing failed.
+/// A [Token::Separator] appended to the end of the token-stream has an empty span at the end of the `input`.
+pub fn lex_with_spans(input: &str) -> Result<Vec<Spanned<'_>>, ParseError<LocatingSlice<&str>>> {
+    tokens(token.with_span(), |(token, _)| token)
+        .parse(LocatingSlice::new(input))
+        .map(|mut tokens: Vec<_>| {
+            // Ensure separator at end of token-stream
+            match tokens.last() {
+                Some((Token::Separator, _)) => { /* Already exists */ }
+                _ => tokens.push((Token::Separator, input.len()..input.len())),
+            }
+            tokens
+        })
+}
+
+/// Lexes all tokens (followed by spaces) after leading whitespace using the passed `token`-parser,
+/// where `as_token` gets the [Token] of an output of the `token`-parser.
+///
+/// Numbers with a decimal comma (e.g., `1,5`) are rejected
+/// unless they are an element of a tuple or a set
+/// (see [value::reject_decimal_comma]).
+fn tokens<I, O>(
+    mut token: impl Parser<I, O, ErrMode<ContextError>>,
+    as_token: impl Fn(&O) -> &Token<I::Slice>,
+) -> impl Parser<I, Vec<O>, ErrMode<ContextError>>
+where
+    I: Stream + StreamIsPartial + Copy,
+    <I as Stream>::Token: AsChar + Clone,
+{
+    move |input: &mut I| {
+        multispace0.parse_next(input)?;
+        let mut tokens = Vec::new();
+        // For each open tuple, group, or set: Whether it contains a list of elements
+        let mut lists = Vec::new();
+        loop {
+            let start = *input;
+            let Some(output) = opt(token.by_ref()).parse_next(input)? else {
+                return Ok(tokens);
+            };
+            match as_token(&output) {
+                Token::TupleOpen | Token::SetOpen => lists.push(true),
+                Token::GroupOpen { .. } => lists.push(false),
+                Token::TupleClose | Token::SetClose | Token::GroupClose => {
+                    lists.pop();
+                }
+                Token::Value(Value::Number(_)) if lists.last() != Some(&true) => {
+                    value::reject_decimal_comma(start, input)?
+                }
+                _ => {}
+            }
+            tokens.push(output);
+            space0.parse_next(input)?;
+        }
+    }
+}
+
 pub mod token {
     use super::*;
     use winnow::{
@@ -212,6 +276,13 @@ pub mod token {
             .parse_next(input)
     }
 
+    /// Tries to parse a single [Token::Assign].
+    pub fn assign<I: Stream + StreamIsPartial + Compare<&'static str>>(
+        input: &mut I,
+    ) -> ModalResult<Token<<I as Stream>::Slice>> {
+        "=".map(|_| Token::Assign).parse_next(input)
+    }
+
     /// Tries to parse any [Token].
     pub fn token<
         I: Stream
@@ -241,6 +312,7 @@ pub mod token {
             time_symbol,
             directive,
             separator,
+            assign,
         ))
         .parse_next(input)
     }
@@ -364,6 +436,33 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn named_argument() {
+        let input = "@=0 cz {a, b} dur=2.5";
+
+        let expected = vec![
+            Token::TimeSymbol(TimeSpec::Relative {
+                from_start: true,
+                positive: true,
+            }),
+            Token::Value(Value::Number("0")),
+            Token::Identifier("cz"),
+            Token::SetOpen,
+            Token::Identifier("a"),
+            Token::ElementSeparator,
+            Token::Identifier("b"),
+            Token::SetClose,
+            Token::Identifier("dur"),
+            Token::Assign,
+            Token::Value(Value::Number("2.5")),
+            Token::Separator,
+        ];
+
+        let actual = lex(input).expect("Failed to lex");
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn error_location_byte_offset_conversion() {
         let test_cases = vec![
@@ -462,4 +561,84 @@ mod test {
         let (line, column) = byte_offset_to_line_column("\n\n\n", 3);
         assert_eq!((line, column), (4, 1));
     }
+
+    /// The span of each token should cover its substring in the input.
+    #[test]
+    fn spans() {
+        let input = "atom (0, 0) a\n@+ rz 0.5 a";
+
+        let lexed = lex_with_spans(input).expect("Failed to lex");
+
+        let expected = [
+            (Token::Identifier("atom"), "atom"),
+            (Token::TupleOpen, "("),
+            (Token::Value(Value::Number("0")), "0"),
+            (Token::ElementSeparator, ","),
+            (Token::Value(Value::Number("0")), "0"),
+            (Token::TupleClose, ")"),
+            (Token::Identifier("a"), "a"),
+            (Token::Separator, "\n"),
+            (
+                Token::TimeSymbol(TimeSpec::Relative {
+                    from_start: false,
+                    positive: true,
+                }),
+                "@+",
+            ),
+            (Token::Identifier("rz"), "rz"),
+            (Token::Value(Value::Number("0.5")), "0.5"),
+            (Token::Identifier("a"), "a"),
+            (Token::Separator, ""),
+        ];
+        assert_eq!(lexed.len(), expected.len());
+        for ((token, span), (expected_token, expected_text)) in lexed.into_iter().zip(expected) {
+            assert_eq!(token, expected_token);
+            assert_eq!(&input[span], expected_text);
+        }
+    }
+
+    /// Tuples without whitespace after the element separator should not be lexed as malformed numbers.
+    #[test]
+    pub fn tuple_without_whitespace() {
+        assert_eq!(
+            lex("@0 move (1,5) a").expect("Failed to lex tuple without whitespace"),
+            [
+                Token::TimeSymbol(TimeSpec::Absolute),
+                Token::Value(Value::Number("0")),
+                Token::Identifier("move"),
+                Token::TupleOpen,
+                Token::Value(Value::Number("1")),
+                Token::ElementSeparator,
+                Token::Value(Value::Number("5")),
+                Token::TupleClose,
+                Token::Identifier("a"),
+                Token::Separator,
+            ]
+        );
+        assert!(
+            lex("@0 cz {a,b}").is_ok(),
+            "Failed to lex set without whitespace"
+        );
+    }
+
+    /// Numbers with a decimal comma should be rejected with the offending literal
+    /// wherever a single number is expected.
+    #[test]
+    pub fn decimal_comma() {
+        for (input, literal, offset) in [
+            ("@0 rz 1,5 a", "1,5", 6),
+            ("@1,5 rz 1 a", "1,5", 1),
+            ("#window 0 1,5", "1,5", 10),
+            ("@0 [rz 1,5 a]", "1,5", 7),
+        ] {
+            let error = lex(input).expect_err("Lexed malformed number");
+            assert_eq!(error.offset(), offset, "Wrong location for {input}");
+            let cause = error
+                .inner()
+                .cause()
+                .and_then(|cause| cause.downcast_ref::<MalformedNumberError>())
+                .expect("Error has no MalformedNumberError as cause");
+            assert_eq!(cause.literal, literal);
+        }
+    }
 }
@@ -2,14 +2,20 @@
 //! Takes tokens lexed by the [lexer][super::lexer].
 
 use super::lexer::{TimeSpec, Token};
-use crate::common::{self, parser::try_into_value::TryIntoValue};
+use crate::{
+    common::{self, parser::try_into_value::TryIntoValue},
+    ParseErrorInner,
+};
 use fraction::{Fraction, Zero};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use token::{
-    comment, group_close, group_open, identifier, ignore_comments, number, separator, time_symbol,
+    assign, comment, group_close, group_open, identifier, ignore_comments, number, separator,
+    time_symbol,
 };
 use winnow::{
     combinator::{alt, opt, preceded, repeat, terminated},
+    error::{ErrMode, FromExternalError},
+    stream::Stream,
     ModalResult, Parser,
 };
 
@@ -23,7 +29,7 @@ pub enum InstructionOrDirective {
     Instruction {
         time: Option<(TimeSpec, Fraction)>,
         name: String,
-        args: Vec<Value>,
+        args: Vec<Argument>,
     },
     /// A single time with multiple instructions
     GroupedTime {
@@ -31,7 +37,7 @@ pub enum InstructionOrDirective {
         /// The durations are allowed to vary
         variable: bool,
         /// The grouped part: instruction and arguments
-        group: Vec<(String, Vec<Value>)>,
+        group: Vec<(String, Vec<Argument>)>,
     },
     /// A single time and instructions with multiple argument-instances
     GroupedInstruction {
@@ -40,12 +46,43 @@ pub enum InstructionOrDirective {
         variable: bool,
         name: String,
         /// The grouped part: argument-instances
-        group: Vec<Vec<Value>>,
+        group: Vec<Vec<Argument>>,
     },
     /// A single directive
     Directive { name: String, args: Vec<Value> },
 }
 
+/// An argument of an instruction
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    /// A positional argument
+    Positional(Value),
+    /// A named argument (`<name>=<value>`)
+    Named(String, Value),
+}
+
+/// Unbalanced groups found while parsing.
+/// Available as the [cause][ParseErrorInner::cause] of the parse-error,
+/// which is located at the offending [Token].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupError {
+    /// A [Token::GroupClose] without a preceding [Token::GroupOpen]
+    UnmatchedGroupClose,
+    /// A [Token::GroupOpen] which is not closed before the next group or the end of the input
+    UnterminatedGroup,
+}
+
+impl Display for GroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnmatchedGroupClose => write!(f, "`]` without matching `[`"),
+            Self::UnterminatedGroup => write!(f, "`[` is never closed"),
+        }
+    }
+}
+
+impl std::error::Error for GroupError {}
+
 /// Parse a full stream of [Token]s into a [Vec] of [InstructionOrDirective]s.
 pub fn parse<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &[Token<S>],
@@ -56,6 +93,90 @@ pub fn parse<S: TryIntoValue + Clone + Debug + PartialEq>(
     instruction_or_directives.parse(input)
 }
 
+/// Parse a full stream of [Token]s into a [Vec] of [InstructionOrDirective]s (see [parse]),
+/// each together with the index of its first [Token] in the `input`.
+pub fn parse_with_offsets<S: TryIntoValue + Clone + Debug + PartialEq>(
+    input: &[Token<S>],
+) -> Result<Vec<(InstructionOrDirective, usize)>, StreamParseError> {
+    let len = input.len();
+    preceded(
+        ignore_comments_and_separators,
+        repeat(
+            0..,
+            terminated(
+                move |input: &mut &[Token<S>]| {
+                    let offset = len - input.len();
+                    instruction_or_directive
+                        .parse_next(input)
+                        .map(|item| (item, offset))
+                },
+                ignore_comments_and_separators,
+            ),
+        ),
+    )
+    .parse(input)
+    .map_err(|e| StreamParseError {
+        offset: e.offset(),
+        inner: e.into_inner(),
+    })
+}
+
+/// Error returned by [parse_stream] and [parse_with_offsets].
+#[derive(Debug)]
+pub struct StreamParseError {
+    offset: usize,
+    inner: ParseErrorInner,
+}
+
+impl StreamParseError {
+    /// The index of the [Token] at which parsing failed
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The inner error
+    pub fn inner(&self) -> &ParseErrorInner {
+        &self.inner
+    }
+
+    /// The inner error
+    pub fn into_inner(self) -> ParseErrorInner {
+        self.inner
+    }
+}
+
+/// Parse a stream of [Token]s into [InstructionOrDirective]s one at a time.
+///
+/// Yields the same [InstructionOrDirective]s as [parse] without collecting them,
+/// so that large inputs can be processed incrementally.
+/// Stops after yielding the first error.
+pub fn parse_stream<'t, S: TryIntoValue + Clone + Debug + PartialEq>(
+    input: &'t [Token<S>],
+) -> impl Iterator<Item = Result<InstructionOrDirective, StreamParseError>> + 't {
+    let mut remaining = input;
+    let mut failed = ignore_comments_and_separators
+        .parse_next(&mut remaining)
+        .is_err();
+    std::iter::from_fn(move || {
+        if failed || remaining.is_empty() {
+            return None;
+        }
+        let offset = input.len() - remaining.len();
+        match terminated(instruction_or_directive, ignore_comments_and_separators)
+            .parse_next(&mut remaining)
+        {
+            Ok(item) => Some(Ok(item)),
+            Err(e) => {
+                failed = true;
+                Some(Err(StreamParseError {
+                    offset,
+                    inner: e.into_inner().unwrap_or_default(),
+                }))
+            }
+        }
+    })
+}
+
 /// Parse all [Instruction][InstructionOrDirective::Instruction]s,
 /// [GroupedTime][InstructionOrDirective::GroupedTime]s,,
 /// [GroupedInstruction][InstructionOrDirective::GroupedInstruction]s,,
@@ -67,15 +188,62 @@ pub fn instruction_or_directives<S: TryIntoValue + Clone + Debug + PartialEq>(
         ignore_comments_and_separators,
         repeat(
             0..,
-            terminated(
-                alt((instruction, directive, grouped_time, grouped_instruction)),
-                ignore_comments_and_separators,
-            ),
+            terminated(instruction_or_directive, ignore_comments_and_separators),
         ),
     )
     .parse_next(input)
 }
 
+/// Try to parse a single [Instruction][InstructionOrDirective::Instruction],
+/// [GroupedTime][InstructionOrDirective::GroupedTime],
+/// [GroupedInstruction][InstructionOrDirective::GroupedInstruction],
+/// or [Directive][InstructionOrDirective::Directive]
+pub fn instruction_or_directive<S: TryIntoValue + Clone + Debug + PartialEq>(
+    input: &mut &[Token<S>],
+) -> ModalResult<InstructionOrDirective> {
+    alt((
+        instruction,
+        directive,
+        grouped_time,
+        grouped_instruction,
+        unmatched_group_close,
+    ))
+    .parse_next(input)
+}
+
+/// Fails with [GroupError::UnmatchedGroupClose] if the next [Token] is a [Token::GroupClose].
+/// Groups are fully parsed by [grouped_time] and [grouped_instruction],
+/// so a [Token::GroupClose] at the start of an instruction has no matching [Token::GroupOpen].
+fn unmatched_group_close<S: Clone + Debug + PartialEq>(
+    input: &mut &[Token<S>],
+) -> ModalResult<InstructionOrDirective> {
+    let start = input.checkpoint();
+    group_close.parse_next(input)?;
+    input.reset(&start);
+    Err(ErrMode::from_external_error(input, GroupError::UnmatchedGroupClose).cut())
+}
+
+/// Parse a [Token::GroupOpen] (see [group_open])
+/// which must be closed before the next group opens or the input ends.
+/// Fails with [GroupError::UnterminatedGroup] at the [Token::GroupOpen] otherwise.
+fn closed_group_open<S: Clone + Debug + PartialEq>(input: &mut &[Token<S>]) -> ModalResult<bool> {
+    let start = input.checkpoint();
+    let variable = group_open.parse_next(input)?;
+    let closed = input
+        .iter()
+        .find_map(|token| match token {
+            Token::GroupClose => Some(true),
+            Token::GroupOpen { .. } => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false);
+    if !closed {
+        input.reset(&start);
+        return Err(ErrMode::from_external_error(input, GroupError::UnterminatedGroup).cut());
+    }
+    Ok(variable)
+}
+
 /// Try to parse an [Instruction][InstructionOrDirective::Instruction] from a stream of [Token]s.
 pub fn instruction<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
@@ -83,7 +251,7 @@ pub fn instruction<S: TryIntoValue + Clone + Debug + PartialEq>(
     (
         terminated(opt(time), ignore_comments),
         terminated(identifier, ignore_comments),
-        repeat(0.., terminated(any_value, ignore_comments)),
+        repeat(0.., terminated(argument, ignore_comments)),
         separator,
     )
         .map(|(time, name, args, _)| InstructionOrDirective::Instruction { time, name, args })
@@ -103,6 +271,19 @@ pub fn directive<S: TryIntoValue + Clone + Debug + PartialEq>(
         .parse_next(input)
 }
 
+/// Try to parse an [Argument] of an instruction from a stream of [Token]s:
+/// Either a [Named][Argument::Named] argument (`<name>=<value>`)
+/// or a [Positional][Argument::Positional] [Value].
+pub fn argument<S: TryIntoValue + Clone + Debug + PartialEq>(
+    input: &mut &[Token<S>],
+) -> ModalResult<Argument> {
+    alt((
+        (identifier, assign, any_value).map(|(name, _, value)| Argument::Named(name, value)),
+        any_value.map(Argument::Positional),
+    ))
+    .parse_next(input)
+}
+
 /// Try to parse a time ([TimeSpec] and accompanying number) from a stream of [Token]s.
 pub fn time<S: TryIntoValue + Clone + Debug>(
     input: &mut &[Token<S>],
@@ -121,7 +302,7 @@ pub fn grouped_time<S: TryIntoValue + Clone + Debug + PartialEq>(
     let grouped_instruction = terminated(
         (
             terminated(identifier, ignore_comments),
-            repeat(0.., terminated(any_value, ignore_comments)),
+            repeat(0.., terminated(argument, ignore_comments)),
         ),
         separator,
     );
@@ -136,7 +317,7 @@ pub fn grouped_time<S: TryIntoValue + Clone + Debug + PartialEq>(
 
     (
         opt(time),
-        terminated((group_open, grouped_instructions), group_close),
+        terminated((closed_group_open, grouped_instructions), group_close),
     )
         .map(
             |(time, (variable, group))| InstructionOrDirective::GroupedTime {
@@ -153,7 +334,7 @@ pub fn grouped_instruction<S: TryIntoValue + Clone + Debug + PartialEq>(
     input: &mut &[Token<S>],
 ) -> ModalResult<InstructionOrDirective> {
     let grouped_value = terminated(
-        repeat(0.., terminated(any_value, ignore_comments)),
+        repeat(0.., terminated(argument, ignore_comments)),
         separator,
     );
 
@@ -168,7 +349,7 @@ pub fn grouped_instruction<S: TryIntoValue + Clone + Debug + PartialEq>(
     (
         opt(time),
         terminated(identifier, ignore_comments),
-        terminated((group_open, grouped_values), group_close),
+        terminated((closed_group_open, grouped_values), group_close),
     )
         .map(
             |(time, name, (variable, group))| InstructionOrDirective::GroupedInstruction {
@@ -226,6 +407,11 @@ pub mod token {
         one_of([Token::Separator]).void().parse_next(input)
     }
 
+    /// Try to parse a single [Token::Assign].
+    pub fn assign<S: Clone + Debug + PartialEq>(input: &mut &[Token<S>]) -> ModalResult<()> {
+        one_of([Token::Assign]).void().parse_next(input)
+    }
+
     /// Try to parse a single [Token::Value] where the value is a [Value::Number].
     pub fn number<S: TryIntoValue + Clone + Debug>(
         input: &mut &[Token<S>],
@@ -365,15 +551,15 @@ mod test {
                 time: None,
                 name: "instruction".to_string(),
                 args: vec![
-                    Value::Identifier("argument".to_string()),
-                    Value::String("argument".to_string()),
-                    Value::Regex(Regex::new("argument").unwrap()),
+                    Argument::Positional(Value::Identifier("argument".to_string())),
+                    Argument::Positional(Value::String("argument".to_string())),
+                    Argument::Positional(Value::Regex(Regex::new("argument").unwrap())),
                 ],
             },
             InstructionOrDirective::Instruction {
                 time: Some((TimeSpec::Absolute, Fraction::new(0u64, 1u64))),
                 name: "timed_instruction".to_string(),
-                args: vec![Value::Identifier("arg".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("arg".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -384,7 +570,7 @@ mod test {
                     Fraction::new(1u64, 1u64),
                 )),
                 name: "negative_timed_instruction".to_string(),
-                args: vec![Value::Identifier("arg".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("arg".to_string()))],
             },
             InstructionOrDirective::Instruction {
                 time: Some((
@@ -395,7 +581,7 @@ mod test {
                     Fraction::new(2u64, 1u64),
                 )),
                 name: "positive_start_timed_instruction".to_string(),
-                args: vec![Value::Identifier("arg".to_string())],
+                args: vec![Argument::Positional(Value::Identifier("arg".to_string()))],
             },
             InstructionOrDirective::GroupedTime {
                 time: Some((
@@ -409,11 +595,15 @@ mod test {
                 group: vec![
                     (
                         "group_instruction_a".to_string(),
-                        vec![Value::Number(Fraction::new(1u64, 1u64))],
+                        vec![Argument::Positional(Value::Number(Fraction::new(
+                            1u64, 1u64,
+                        )))],
                     ),
                     (
                         "group_instruction_b".to_string(),
-                        vec![Value::Number(Fraction::new(2u64, 1u64))],
+                        vec![Argument::Positional(Value::Number(Fraction::new(
+                            2u64, 1u64,
+                        )))],
                     ),
                 ],
             },
@@ -422,8 +612,12 @@ mod test {
                 variable: true,
                 name: "group_instruction".to_string(),
                 group: vec![
-                    vec![Value::Number(Fraction::new(1u64, 1u64))],
-                    vec![Value::Number(Fraction::new(2u64, 1u64))],
+                    vec![Argument::Positional(Value::Number(Fraction::new(
+                        1u64, 1u64,
+                    )))],
+                    vec![Argument::Positional(Value::Number(Fraction::new(
+                        2u64, 1u64,
+                    )))],
                 ],
             },
         ];
@@ -515,12 +709,39 @@ mod test {
         let expected = vec![InstructionOrDirective::Instruction {
             time: Some((TimeSpec::Absolute, Fraction::ZERO)),
             name: "timed_instruction".to_string(),
-            args: vec![Value::Set(vec![
+            args: vec![Argument::Positional(Value::Set(vec![
                 Value::Identifier("t1".to_string()),
                 Value::Identifier("t2".to_string()),
                 Value::Set(vec![Value::Identifier("t3".to_string())]),
                 Value::Set(vec![Value::Set(vec![Value::Identifier("t4".to_string())])]),
-            ])],
+            ]))],
+        }];
+
+        let actual = parse(&input).expect("Failed to parse");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn named_argument() {
+        let input = vec![
+            Token::TimeSymbol(TimeSpec::Absolute),
+            Token::Value(lexer::Value::Number("0")),
+            Token::Identifier("cz"),
+            Token::Identifier("t1"),
+            Token::Identifier("dur"),
+            Token::Assign,
+            Token::Value(lexer::Value::Number("2")),
+            Token::Separator,
+        ];
+
+        let expected = vec![InstructionOrDirective::Instruction {
+            time: Some((TimeSpec::Absolute, Fraction::ZERO)),
+            name: "cz".to_string(),
+            args: vec![
+                Argument::Positional(Value::Identifier("t1".to_string())),
+                Argument::Named("dur".to_string(), Value::Number(Fraction::from(2))),
+            ],
         }];
 
         let actual = parse(&input).expect("Failed to parse");
@@ -586,4 +807,102 @@ mod test {
             .collect();
         // No assertion on non-emptiness; purpose is to ensure error occurs and context retrieval works.
     }
+
+    /// Streaming should yield the same items as parsing the whole input at once.
+    #[test]
+    pub fn stream_equals_batch() {
+        let input = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.naviz"
+        ));
+        let lexed = crate::input::lexer::lex(input).expect("Failed to lex");
+
+        let batch = parse(&lexed).expect("Failed to parse");
+        let stream: Vec<_> = parse_stream(&lexed)
+            .collect::<Result<_, _>>()
+            .expect("Failed to parse stream");
+
+        assert_eq!(stream, batch);
+    }
+
+    /// Parsing with offsets should yield the same items as [parse]
+    /// and point to the first token of each item.
+    #[test]
+    pub fn offsets() {
+        let input = vec![
+            Token::Separator,
+            Token::Identifier("instruction"),
+            Token::Separator,
+            Token::Comment("comment"),
+            Token::Separator,
+            Token::Directive("directive"),
+            Token::Identifier("arg"),
+            Token::Separator,
+        ];
+
+        let (items, offsets): (Vec<_>, Vec<_>) = parse_with_offsets(&input)
+            .expect("Failed to parse")
+            .into_iter()
+            .unzip();
+        assert_eq!(items, parse(&input).expect("Failed to parse"));
+        assert_eq!(offsets, [1, 5]);
+    }
+
+    /// Streaming should yield all items before an error and stop after the error.
+    #[test]
+    pub fn stream_stops_at_error() {
+        let input = vec![
+            Token::Identifier("instruction"),
+            Token::Separator,
+            Token::GroupOpen { variable: false },
+            Token::Identifier("instruction"),
+        ];
+
+        let mut stream = parse_stream(&input);
+        assert!(matches!(stream.next(), Some(Ok(_))));
+        let error = stream
+            .next()
+            .expect("Missing error")
+            .expect_err("Parsed invalid input");
+        assert_eq!(error.offset(), 2);
+        assert!(stream.next().is_none(), "Stream continued after error");
+    }
+
+    /// Returns the token-offset and [GroupError] of the error when parsing `input`
+    fn group_error(input: &[Token<&str>]) -> (usize, GroupError) {
+        let error = parse(input).expect_err("Parsed unbalanced group");
+        let cause = error
+            .inner()
+            .cause()
+            .and_then(|cause| cause.downcast_ref::<GroupError>())
+            .copied()
+            .expect("Missing group error");
+        (error.offset(), cause)
+    }
+
+    #[test]
+    fn unmatched_group_close() {
+        let input = vec![
+            Token::Identifier("instruction"),
+            Token::Separator,
+            Token::GroupClose,
+            Token::Separator,
+        ];
+
+        assert_eq!(group_error(&input), (2, GroupError::UnmatchedGroupClose));
+    }
+
+    #[test]
+    fn unterminated_group() {
+        let input = vec![
+            Token::Identifier("instruction"),
+            Token::Separator,
+            Token::Identifier("grouped"),
+            Token::GroupOpen { variable: false },
+            Token::Value(lexer::Value::Number("1")),
+            Token::Separator,
+        ];
+
+        assert_eq!(group_error(&input), (3, GroupError::UnterminatedGroup));
+    }
 }
@@ -0,0 +1,373 @@
+//! Textual preprocessing of instruction-inputs before lexing.
+//! See [preprocess].
+
+use std::{
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
+
+/// The directive which includes another file
+const INCLUDE_DIRECTIVE: &str = "#include";
+
+/// An error which occurred while [preprocessing][preprocess] an input.
+/// `E` is the error returned by the resolver.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PreprocessError<E> {
+    /// An `#include`-directive was not followed by a quoted path.
+    /// Contains the (`1`-based) line in the file containing the directive
+    /// and the chain of includes leading to that file.
+    Malformed { line: usize, chain: Vec<String> },
+    /// The resolver failed to resolve the included `path`
+    Resolve { path: String, error: E },
+    /// A file (transitively) includes itself.
+    /// Contains the chain of includes which leads back to the first occurrence.
+    Cycle(Vec<String>),
+}
+
+impl<E: Display> Display for PreprocessError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed { line, chain } => {
+                write!(f, "Malformed include on line {line}")?;
+                if let Some(file) = chain.last() {
+                    write!(f, " of \"{file}\"")?;
+                }
+                Ok(())
+            }
+            Self::Resolve { path, error } => write!(f, "Failed to include \"{path}\": {error}"),
+            Self::Cycle(chain) => write!(f, "Cyclic include: {}", chain.join(" -> ")),
+        }
+    }
+}
+
+/// Inlines all `#include "<path>"`-directives of the passed `source`.
+/// Each directive is replaced by the contents of the included file
+/// (which is preprocessed recursively).
+///
+/// The parser has no access to the filesystem:
+/// The contents of a file are fetched using the passed `resolver`,
+/// which receives the path of the included file relative to the root `source`,
+/// i.e., includes of included files are resolved relative to the directory of the including file.
+///
+/// An include must be on its own line.
+/// Returns an error if an include could not be resolved
+/// or if a file (transitively) includes itself.
+///
+/// See [preprocess_with_map] to map offsets in the output back to the files.
+pub fn preprocess<E>(
+    source: &str,
+    resolver: impl Fn(&str) -> Result<String, E>,
+) -> Result<String, PreprocessError<E>> {
+    preprocess_with_map(source, resolver).map(|(output, _)| output)
+}
+
+/// Inlines all `#include "<path>"`-directives of the passed `source` like [preprocess],
+/// but also returns a [SourceMap] to map offsets in the output back to the files.
+pub fn preprocess_with_map<E>(
+    source: &str,
+    resolver: impl Fn(&str) -> Result<String, E>,
+) -> Result<(String, SourceMap), PreprocessError<E>> {
+    let mut output = String::with_capacity(source.len());
+    let mut map = SourceMap::default();
+    preprocess_into(
+        source,
+        None,
+        &resolver,
+        &mut Vec::new(),
+        &mut output,
+        &mut map,
+    )?;
+    Ok((output, map))
+}
+
+/// A contiguous part of the preprocessed output which was copied from a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    /// The start of this segment in the output
+    output_start: usize,
+    /// The index of the file in [SourceMap::files]
+    /// or [None] if the segment was copied from the root source
+    file: Option<usize>,
+    /// The start of this segment in its file
+    source_start: usize,
+}
+
+/// Maps byte-offsets in the output of [preprocess_with_map]
+/// back to the files they were copied from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// The segments of the output, sorted by their start
+    segments: Vec<Segment>,
+    /// The included files as `(path, contents)`-pairs
+    files: Vec<(String, String)>,
+}
+
+impl SourceMap {
+    /// Records that `len` bytes at `output_start` were copied from `source_start` in `file`.
+    /// Extends the previous segment if it continues in the same file.
+    fn push(&mut self, output_start: usize, file: Option<usize>, source_start: usize) {
+        if let Some(last) = self.segments.last() {
+            if last.file == file
+                && output_start - last.output_start == source_start - last.source_start
+            {
+                return;
+            }
+        }
+        self.segments.push(Segment {
+            output_start,
+            file,
+            source_start,
+        });
+    }
+
+    /// Maps the passed `offset` in the preprocessed output back to the file it was copied from.
+    /// Returns the path of that file ([None] for the root source),
+    /// the contents of that file (the passed `root` for the root source),
+    /// and the offset in that file.
+    pub fn locate<'a>(&'a self, root: &'a str, offset: usize) -> (Option<&'a str>, &'a str, usize) {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.output_start <= offset);
+        let Some(segment) = index.checked_sub(1).map(|i| &self.segments[i]) else {
+            return (None, root, offset);
+        };
+        let offset = segment.source_start + (offset - segment.output_start);
+        match segment.file {
+            Some(file) => {
+                let (path, contents) = &self.files[file];
+                (Some(path), contents, offset.min(contents.len()))
+            }
+            None => (None, root, offset.min(root.len())),
+        }
+    }
+}
+
+/// Resolves the included `path` relative to the directory of the including file `parent`
+/// ([None] for the root source).
+/// `.` and `..` are removed lexically, such that cycles are detected on equal paths.
+fn resolve_path(parent: Option<&str>, path: &str) -> String {
+    let joined = match parent.and_then(|parent| Path::new(parent).parent()) {
+        Some(directory) => directory.join(path),
+        None => PathBuf::from(path),
+    };
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    resolved.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                resolved.pop();
+            }
+            component => resolved.push(component),
+        }
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+/// Preprocesses `source` into `output`.
+/// `file` is the index of the `source` in the `map` ([None] for the root source);
+/// `chain` contains the (resolved) includes which lead to `source`.
+fn preprocess_into<E>(
+    source: &str,
+    file: Option<usize>,
+    resolver: &impl Fn(&str) -> Result<String, E>,
+    chain: &mut Vec<String>,
+    output: &mut String,
+    map: &mut SourceMap,
+) -> Result<(), PreprocessError<E>> {
+    let mut line_start = 0;
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        let source_start = line_start;
+        line_start += line.len();
+
+        let Some(argument) = line.trim().strip_prefix(INCLUDE_DIRECTIVE) else {
+            map.push(output.len(), file, source_start);
+            output.push_str(line);
+            continue;
+        };
+        if !argument.is_empty() && !argument.starts_with(char::is_whitespace) {
+            // Other directive which starts with `include`
+            map.push(output.len(), file, source_start);
+            output.push_str(line);
+            continue;
+        }
+
+        let path = argument
+            .trim()
+            .strip_prefix('"')
+            .and_then(|p| p.strip_suffix('"'))
+            .filter(|p| !p.contains('"'))
+            .ok_or_else(|| PreprocessError::Malformed {
+                line: idx + 1,
+                chain: chain.clone(),
+            })?;
+        let path = resolve_path(chain.last().map(String::as_str), path);
+
+        if chain.contains(&path) {
+            let mut cycle = chain.clone();
+            cycle.push(path);
+            return Err(PreprocessError::Cycle(cycle));
+        }
+
+        let included = resolver(&path).map_err(|error| PreprocessError::Resolve {
+            path: path.clone(),
+            error,
+        })?;
+
+        let included_file = map.files.len();
+        map.files.push((path.clone(), included));
+        // Temporarily take the contents to not borrow the map while preprocessing
+        let included = std::mem::take(&mut map.files[included_file].1);
+        chain.push(path);
+        preprocess_into(&included, Some(included_file), resolver, chain, output, map)?;
+        chain.pop();
+        map.files[included_file].1 = included;
+
+        // Keep the line-break of the directive
+        if !output.is_empty() && !output.ends_with('\n') && line.ends_with('\n') {
+            map.push(output.len(), file, line_start - 1);
+            output.push('\n');
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// A resolver which resolves from the passed `files`
+    fn resolver<'a>(
+        files: &'a HashMap<&'static str, &'static str>,
+    ) -> impl Fn(&str) -> Result<String, String> + 'a {
+        move |path| {
+            files
+                .get(path)
+                .map(|c| c.to_string())
+                .ok_or_else(|| format!("not found: {path}"))
+        }
+    }
+
+    #[test]
+    fn two_files() {
+        let files = HashMap::from([("setup.naviz", "atom (0, 0) atom0\n")]);
+        let input = "#target example\n#include \"setup.naviz\"\n@0 load atom0\n";
+
+        assert_eq!(
+            preprocess(input, resolver(&files)),
+            Ok("#target example\natom (0, 0) atom0\n@0 load atom0\n".to_string())
+        );
+    }
+
+    #[test]
+    fn nested_without_trailing_newline() {
+        let files = HashMap::from([
+            ("a.naviz", "#include \"b.naviz\"\n@0 load atom0"),
+            ("b.naviz", "atom (0, 0) atom0"),
+        ]);
+        let input = "#include \"a.naviz\"\n@1 store atom0\n";
+
+        assert_eq!(
+            preprocess(input, resolver(&files)),
+            Ok("atom (0, 0) atom0\n@0 load atom0\n@1 store atom0\n".to_string())
+        );
+    }
+
+    #[test]
+    fn nested_relative_to_parent() {
+        let files = HashMap::from([
+            (
+                "sub/a.naviz",
+                "#include \"b.naviz\"\n#include \"../c.naviz\"\n",
+            ),
+            ("sub/b.naviz", "atom (0, 0) atom0\n"),
+            ("c.naviz", "@0 load atom0\n"),
+        ]);
+
+        assert_eq!(
+            preprocess("#include \"./sub/a.naviz\"\n", resolver(&files)),
+            Ok("atom (0, 0) atom0\n@0 load atom0\n".to_string())
+        );
+    }
+
+    #[test]
+    fn source_map() {
+        let files = HashMap::from([("setup.naviz", "atom (0, 0) atom0\natom (1, 0) atom1\n")]);
+        let input = "#target example\n#include \"setup.naviz\"\n@0 load atom0\n";
+
+        let (output, map) = preprocess_with_map(input, resolver(&files)).unwrap();
+        let offset_of = |needle| output.find(needle).unwrap();
+
+        assert_eq!(map.locate(input, offset_of("example")), (None, input, 8));
+        assert_eq!(
+            map.locate(input, offset_of("atom1")),
+            (Some("setup.naviz"), files["setup.naviz"], 30)
+        );
+        assert_eq!(
+            map.locate(input, offset_of("@0")),
+            (None, input, input.find("@0").unwrap())
+        );
+    }
+
+    #[test]
+    fn other_directives_untouched() {
+        let input = "#includes_nothing value\n#target example\n";
+
+        assert_eq!(
+            preprocess(input, |_| Err::<String, _>(())),
+            Ok(input.to_string())
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let files = HashMap::from([
+            ("a.naviz", "#include \"b.naviz\"\n"),
+            ("b.naviz", "#include \"a.naviz\"\n"),
+        ]);
+
+        assert_eq!(
+            preprocess("#include \"a.naviz\"\n", resolver(&files)),
+            Err(PreprocessError::Cycle(vec![
+                "a.naviz".to_string(),
+                "b.naviz".to_string(),
+                "a.naviz".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn unresolved() {
+        let files = HashMap::new();
+
+        assert_eq!(
+            preprocess("#include \"missing.naviz\"\n", resolver(&files)),
+            Err(PreprocessError::Resolve {
+                path: "missing.naviz".to_string(),
+                error: "not found: missing.naviz".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let files = HashMap::new();
+
+        assert_eq!(
+            preprocess(
+                "#target example\n#include missing.naviz\n",
+                resolver(&files)
+            ),
+            Err(PreprocessError::Malformed {
+                line: 2,
+                chain: Vec::new(),
+            })
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Re-targeting of [Instructions] written for one machine onto another machine.
+//! See [remap_positions].
+
+use fraction::{Fraction, Zero};
+
+use super::{
+    concrete::{Instructions, SetupInstruction, TimedInstruction},
+    validate::{fmt_position, InstructionIndex, SemanticWarning},
+};
+use crate::config::{
+    machine::{to_f32, MachineConfig},
+    position::Position,
+};
+
+/// The axis-aligned bounding box of the traps of a machine
+struct TrapGrid {
+    min: Position,
+    max: Position,
+}
+
+impl TrapGrid {
+    /// Gets the [TrapGrid] of the passed `machine`,
+    /// or [None] if the machine has no traps.
+    fn of(machine: &MachineConfig) -> Option<Self> {
+        machine
+            .trap
+            .values()
+            .map(|trap| trap.position)
+            .fold(None, |grid: Option<Self>, (x, y)| {
+                Some(match grid {
+                    None => Self {
+                        min: (x, y),
+                        max: (x, y),
+                    },
+                    Some(Self { min, max }) => Self {
+                        min: (min.0.min(x), min.1.min(y)),
+                        max: (max.0.max(x), max.1.max(y)),
+                    },
+                })
+            })
+    }
+
+    /// The size of this [TrapGrid] along both axes
+    fn extent(&self) -> Position {
+        (self.max.0 - self.min.0, self.max.1 - self.min.1)
+    }
+}
+
+/// Maps positions from the [TrapGrid] of one machine onto the traps of another machine
+struct Remapper<'a> {
+    from: TrapGrid,
+    to: TrapGrid,
+    /// The target machine
+    machine: &'a MachineConfig,
+    /// The maximum distance of a mapped position to the trap it is snapped to
+    tolerance: f32,
+}
+
+impl<'a> Remapper<'a> {
+    /// Creates a new [Remapper] between the passed machines,
+    /// or [None] if either machine has no traps.
+    fn new(from: &MachineConfig, to: &'a MachineConfig) -> Option<Self> {
+        let traps: Vec<_> = to.trap.values().map(|trap| to_f32(trap.position)).collect();
+        let min_distance = traps
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| traps[idx + 1..].iter().map(move |b| distance(*a, *b)))
+            .filter(|d| *d > 0.)
+            .fold(f32::INFINITY, f32::min);
+        Some(Self {
+            from: TrapGrid::of(from)?,
+            to: TrapGrid::of(to)?,
+            machine: to,
+            tolerance: min_distance / 2.,
+        })
+    }
+
+    /// Scales the passed `offset` from the source grid to the target grid.
+    /// Axes along which the source grid has no extent are not scaled.
+    fn scale(&self, (x, y): Position) -> Position {
+        let scale = |value: Fraction, from: Fraction, to: Fraction| {
+            if from.is_zero() {
+                value
+            } else {
+                value * to / from
+            }
+        };
+        let (from, to) = (self.from.extent(), self.to.extent());
+        (scale(x, from.0, to.0), scale(y, from.1, to.1))
+    }
+
+    /// Maps the passed `position` onto the target grid
+    /// and snaps it to the nearest trap of the target machine.
+    /// If no trap is close, the mapped position is returned as [Err].
+    fn map(&self, (x, y): Position) -> Result<Position, Position> {
+        let (dx, dy) = self.scale((x - self.from.min.0, y - self.from.min.1));
+        let mapped = (self.to.min.0 + dx, self.to.min.1 + dy);
+        let mapped_f32 = to_f32(mapped);
+        self.machine
+            .trap
+            .values()
+            .map(|trap| (trap.position, distance(to_f32(trap.position), mapped_f32)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|(_, d)| *d <= self.tolerance)
+            .map(|(trap, _)| trap)
+            .ok_or(mapped)
+    }
+
+    /// Remaps the passed `position` in place (see [Remapper::map]).
+    /// Pushes a [SemanticWarning] for the instruction at `index`
+    /// if no trap is close to the mapped position.
+    fn remap(
+        &self,
+        position: &mut Position,
+        index: InstructionIndex,
+        warnings: &mut Vec<SemanticWarning>,
+    ) {
+        match self.map(*position) {
+            Ok(trap) => *position = trap,
+            Err(mapped) => {
+                warnings.push(SemanticWarning {
+                    index,
+                    message: format!(
+                        "No trap of `{}` near position {} (remapped from {})",
+                        self.machine.name,
+                        fmt_position(&mapped),
+                        fmt_position(position)
+                    ),
+                });
+                *position = mapped;
+            }
+        }
+    }
+}
+
+/// Remaps the positions of the passed `instructions`, written for the machine `from`,
+/// onto the machine `to`:
+/// Each position is scaled from the bounding box of the traps of `from`
+/// onto the bounding box of the traps of `to`
+/// and snapped to the nearest trap of `to`.
+/// If no trap of `to` is close to a scaled position
+/// (i.e., within half the smallest distance between two traps of `to`),
+/// the scaled position is kept and a [SemanticWarning] is returned.
+/// Offsets of relative moves are scaled, but not snapped.
+///
+/// If either machine has no traps, the `instructions` are not changed.
+pub fn remap_positions(
+    instructions: &mut Instructions,
+    from: &MachineConfig,
+    to: &MachineConfig,
+) -> Vec<SemanticWarning> {
+    let mut warnings = Vec::new();
+    let Some(remapper) = Remapper::new(from, to) else {
+        return warnings;
+    };
+
+    for (idx, setup) in instructions.setup.iter_mut().enumerate() {
+        match setup {
+            SetupInstruction::Atom { position, .. } => {
+                remapper.remap(position, InstructionIndex::Setup(idx), &mut warnings)
+            }
+        }
+    }
+
+    let timed = instructions
+        .instructions
+        .iter_mut()
+        .flat_map(|(_, timeline)| timeline)
+        .flat_map(|(_, _, group)| &mut group.instructions);
+    for (idx, instruction) in timed.enumerate() {
+        let index = InstructionIndex::Timed(idx);
+        match instruction {
+            TimedInstruction::Load {
+                position: Some(position),
+                ..
+            }
+            | TimedInstruction::Store {
+                position: Some(position),
+                ..
+            }
+            | TimedInstruction::Move { position, .. } => {
+                remapper.remap(position, index, &mut warnings)
+            }
+            TimedInstruction::MoveBy { offset, .. } => *offset = remapper.scale(*offset),
+            TimedInstruction::Load { position: None, .. }
+            | TimedInstruction::Store { position: None, .. }
+            | TimedInstruction::Rz { .. }
+            | TimedInstruction::Ry { .. }
+            | TimedInstruction::Cz { .. }
+            | TimedInstruction::Measure { .. }
+            | TimedInstruction::Reset { .. }
+            | TimedInstruction::Highlight { .. } => {}
+        }
+    }
+
+    warnings
+}
+
+/// The euclidean distance between `a` and `b`
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::{self, generic::Config, machine::TrapConfig},
+        test_utils::instructions,
+    };
+
+    /// Loads the example machine with a grid of traps
+    /// at the passed `xs` and `ys`
+    fn machine(xs: &[i64], ys: &[i64]) -> MachineConfig {
+        let input = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.namachine"
+        ));
+        let lexed = config::lexer::lex(input).expect("Failed to lex");
+        let parsed = config::parser::parse(&lexed).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        let mut machine: MachineConfig = generic.try_into().expect("Failed to load machine");
+        machine.trap = xs
+            .iter()
+            .flat_map(|x| ys.iter().map(move |y| (*x, *y)))
+            .map(|(x, y)| {
+                (
+                    format!("trap_{x}_{y}"),
+                    TrapConfig {
+                        position: (pos(x), pos(y)),
+                    },
+                )
+            })
+            .collect();
+        machine
+    }
+
+    /// Converts the passed coordinate into a [Fraction]
+    fn pos(value: i64) -> Fraction {
+        Fraction::from(value)
+    }
+
+    /// All positions of the passed `instructions`, in order
+    fn positions(instructions: &Instructions) -> Vec<Position> {
+        let setup = instructions.setup.iter().map(|setup| match setup {
+            SetupInstruction::Atom { position, .. } => *position,
+        });
+        let timed = instructions
+            .instructions
+            .iter()
+            .flat_map(|(_, timeline)| timeline)
+            .flat_map(|(_, _, group)| &group.instructions)
+            .filter_map(|instruction| match instruction {
+                TimedInstruction::Load { position, .. }
+                | TimedInstruction::Store { position, .. } => *position,
+                TimedInstruction::Move { position, .. } => Some(*position),
+                TimedInstruction::MoveBy { offset, .. } => Some(*offset),
+                _ => None,
+            });
+        setup.chain(timed).collect()
+    }
+
+    #[test]
+    fn snap_to_target_grid() {
+        let mut instructions = instructions(
+            "atom (0, 0) atom0\n\
+             atom (10, 10) atom1\n\
+             @0 move (5, 5) atom0\n\
+             @1 move (2, 0) atom0\n\
+             @2 store (10, 0) atom1\n\
+             @3 move (10, 5) {atom0, atom1}",
+        )
+        .expect("Failed to convert");
+        let from = machine(&[0, 10], &[0, 10]);
+        let to = machine(&[100, 104, 108], &[50, 54, 58]);
+
+        let warnings = remap_positions(&mut instructions, &from, &to);
+
+        assert!(warnings.is_empty(), "Unexpected warnings: {warnings:?}");
+        assert_eq!(
+            positions(&instructions),
+            [
+                (pos(100), pos(50)),
+                (pos(108), pos(58)),
+                (pos(104), pos(54)),
+                (pos(100), pos(50)),
+                (pos(108), pos(50)),
+                (pos(8), pos(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn warn_without_close_trap() {
+        let mut instructions = instructions(
+            "atom (0, 0) atom0\n\
+             @0 move (5, 5) atom0",
+        )
+        .expect("Failed to convert");
+        let from = machine(&[0, 10], &[0, 10]);
+        let to = machine(&[0, 20], &[0, 20]);
+
+        let warnings = remap_positions(&mut instructions, &from, &to);
+
+        assert_eq!(
+            warnings,
+            [SemanticWarning {
+                index: InstructionIndex::Timed(0),
+                message: "No trap of `Name` near position (10, 10) (remapped from (5, 5))"
+                    .to_string(),
+            }]
+        );
+        assert_eq!(
+            positions(&instructions),
+            [(pos(0), pos(0)), (pos(10), pos(10))]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Summary statistics of [Instructions].
+//! See [Instructions::stats].
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use fraction::Fraction;
+
+use super::concrete::{Instructions, SetupInstruction, TimedInstruction};
+
+/// Statistics of [Instructions].
+/// Created by [Instructions::stats].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct InstructionStats {
+    /// The number of declared atoms
+    pub atoms: usize,
+    /// The number of gates (`rz`, `ry`, `cz`, `measure`, `reset`) by name
+    pub gates: BTreeMap<&'static str, usize>,
+    /// The number of `move`-instructions
+    pub moves: usize,
+    /// The number of `load`-instructions
+    pub loads: usize,
+    /// The number of `store`-instructions
+    pub stores: usize,
+    /// The number of distinct zones targeted by gates
+    /// (i.e., targets which are not declared atoms)
+    pub zones: usize,
+    /// The earliest and latest start time which is known without a machine,
+    /// or [None] if there are no timed instructions.
+    /// Start times which depend on the duration of previous instructions are not included.
+    pub time_span: Option<(Fraction, Fraction)>,
+}
+
+impl InstructionStats {
+    /// The total number of gates
+    pub fn total_gates(&self) -> usize {
+        self.gates.values().sum()
+    }
+}
+
+impl Instructions {
+    /// Gets the [InstructionStats] of these [Instructions]
+    pub fn stats(&self) -> InstructionStats {
+        let atoms: HashSet<_> = self
+            .setup
+            .iter()
+            .map(|setup| match setup {
+                SetupInstruction::Atom { id, .. } => id.as_str(),
+            })
+            .collect();
+
+        let mut stats = InstructionStats {
+            atoms: atoms.len(),
+            ..Default::default()
+        };
+        let mut zones = HashSet::new();
+        let mut span = |time: Fraction| {
+            stats.time_span = Some(match stats.time_span {
+                Some((from, to)) => (from.min(time), to.max(time)),
+                None => (time, time),
+            });
+        };
+
+        let mut timed = Vec::new();
+        for (absolute, timeline) in &self.instructions {
+            span(*absolute);
+            // The exact start time of the previous group, if known
+            let mut start = Some(*absolute);
+            for (from_start, offset, group) in timeline {
+                start = if *from_start {
+                    start.map(|s| s + *offset)
+                } else {
+                    // Depends on the duration of the previous group
+                    None
+                };
+                if let Some(start) = start {
+                    span(start);
+                }
+                timed.extend(&group.instructions);
+            }
+        }
+
+        for instruction in timed {
+            match instruction {
+                TimedInstruction::Load { .. } => stats.loads += 1,
+                TimedInstruction::Store { .. } => stats.stores += 1,
+                TimedInstruction::Move { .. } | TimedInstruction::MoveBy { .. } => stats.moves += 1,
+                TimedInstruction::Rz { targets, .. }
+                | TimedInstruction::Ry { targets, .. }
+                | TimedInstruction::Cz { targets, .. }
+                | TimedInstruction::Measure { targets, .. }
+                | TimedInstruction::Reset { targets, .. } => {
+                    *stats.gates.entry(instruction.str()).or_default() += 1;
+                    zones.extend(
+                        targets
+                            .iter()
+                            .map(String::as_str)
+                            .filter(|target| !atoms.contains(target)),
+                    );
+                }
+                TimedInstruction::Highlight { .. } => {}
+            }
+        }
+        stats.zones = zones.len();
+
+        stats
+    }
+}
+
+impl Instructions {
+    /// Gets the (sorted and distinct) ids of the zones referenced by these [Instructions]:
+    /// Targets of gates which are not declared atoms and ids of highlighted zones.
+    ///
+    /// Without [targets][super::concrete::Directives::targets],
+    /// these can be used to prefer machines which define the referenced zones.
+    pub fn referenced_zone_ids(&self) -> Vec<&str> {
+        let atoms: HashSet<_> = self
+            .setup
+            .iter()
+            .map(|setup| match setup {
+                SetupInstruction::Atom { id, .. } => id.as_str(),
+            })
+            .collect();
+
+        let mut zones = BTreeSet::new();
+        let timed = self
+            .instructions
+            .iter()
+            .flat_map(|(_, timeline)| timeline)
+            .flat_map(|(_, _, group)| &group.instructions);
+        for instruction in timed {
+            match instruction {
+                TimedInstruction::Rz { targets, .. }
+                | TimedInstruction::Ry { targets, .. }
+                | TimedInstruction::Cz { targets, .. }
+                | TimedInstruction::Measure { targets, .. }
+                | TimedInstruction::Reset { targets, .. } => zones.extend(
+                    targets
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|target| !atoms.contains(target)),
+                ),
+                TimedInstruction::Highlight { id, .. } => {
+                    zones.insert(id.as_str());
+                }
+                TimedInstruction::Load { .. }
+                | TimedInstruction::Store { .. }
+                | TimedInstruction::Move { .. }
+                | TimedInstruction::MoveBy { .. } => {}
+            }
+        }
+
+        zones.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::instructions;
+
+    #[test]
+    fn example() {
+        let stats = instructions(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.naviz"
+        )))
+        .expect("Failed to convert")
+        .stats();
+
+        assert_eq!(
+            stats,
+            InstructionStats {
+                atoms: 3,
+                gates: BTreeMap::from([("cz", 2), ("ry", 2), ("rz", 1)]),
+                moves: 2,
+                loads: 2,
+                stores: 2,
+                zones: 2,
+                time_span: Some((Fraction::from(0), Fraction::from(0))),
+            }
+        );
+        assert_eq!(stats.total_gates(), 5);
+    }
+
+    #[test]
+    fn time_span() {
+        let stats = instructions(
+            "atom (0, 0) atom0\n\
+             @2 load atom0\n\
+             @=+3 rz 1 atom0\n\
+             @+ store atom0\n\
+             @10 reset atom0",
+        )
+        .expect("Failed to convert")
+        .stats();
+
+        assert_eq!(
+            stats.time_span,
+            Some((Fraction::from(2), Fraction::from(10)))
+        );
+        assert_eq!(stats.zones, 0);
+    }
+
+    #[test]
+    fn referenced_zone_ids() {
+        let instructions = instructions(
+            "atom (0, 0) atom0\n\
+             @0 cz zone0\n\
+             @1 rz 1 atom0\n\
+             @2 highlight zone1 #ff0000\n\
+             @3 cz zone0",
+        )
+        .expect("Failed to convert");
+
+        assert_eq!(instructions.referenced_zone_ids(), vec!["zone0", "zone1"]);
+    }
+}
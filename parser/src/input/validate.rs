@@ -0,0 +1,258 @@
+//! Semantic checks of [Instructions] against a [MachineConfig].
+//! See [Instructions::validate_against].
+
+use std::{collections::HashSet, fmt::Display};
+
+use fraction::{Fraction, Zero};
+
+use super::concrete::{Instructions, SetupInstruction, TimedInstruction};
+use crate::config::{machine::MachineConfig, position::Position};
+
+/// The location of an instruction in [Instructions].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InstructionIndex {
+    /// Index into [Instructions::setup]
+    Setup(usize),
+    /// Index into the [TimedInstruction]s
+    /// in the order they appear when iterating [Instructions::instructions]
+    Timed(usize),
+}
+
+/// A semantic problem of an instruction,
+/// which does not prevent the instructions from being animated.
+/// Created by [Instructions::validate_against].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SemanticWarning {
+    /// The offending instruction
+    pub index: InstructionIndex,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl Display for SemanticWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            InstructionIndex::Setup(idx) => write!(f, "Setup instruction {idx}: ")?,
+            InstructionIndex::Timed(idx) => write!(f, "Instruction {idx}: ")?,
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The bounds of a machine, spanned by its zones and traps
+struct Bounds {
+    min: Position,
+    max: Position,
+}
+
+impl Bounds {
+    /// Gets the [Bounds] of the passed `machine`,
+    /// or [None] if the machine has neither zones nor traps.
+    fn of(machine: &MachineConfig) -> Option<Self> {
+        machine
+            .zone
+            .values()
+            .flat_map(|zone| zone.shape.bounding_points())
+            .chain(machine.trap.values().map(|trap| trap.position))
+            .fold(None, |bounds: Option<Self>, (x, y)| {
+                Some(match bounds {
+                    None => Self {
+                        min: (x, y),
+                        max: (x, y),
+                    },
+                    Some(Self { min, max }) => Self {
+                        min: (min.0.min(x), min.1.min(y)),
+                        max: (max.0.max(x), max.1.max(y)),
+                    },
+                })
+            })
+    }
+
+    /// Checks whether `position` lies within these [Bounds]
+    fn contains(&self, (x, y): Position) -> bool {
+        self.min.0 <= x && x <= self.max.0 && self.min.1 <= y && y <= self.max.1
+    }
+}
+
+impl Instructions {
+    /// Checks these [Instructions] for semantic problems on the passed `machine`:
+    /// - Each instruction targets declared atoms (or zones of the `machine`, where allowed)
+    /// - Positions lie within the bounds spanned by the zones and traps of the `machine`
+    /// - Start times are non-negative
+    ///
+    /// Start times which depend on the duration of previous instructions are not checked.
+    pub fn validate_against(&self, machine: &MachineConfig) -> Vec<SemanticWarning> {
+        let mut warnings = Vec::new();
+        let bounds = Bounds::of(machine);
+        let out_of_bounds = |position: Position| {
+            bounds
+                .as_ref()
+                .is_some_and(|bounds| !bounds.contains(position))
+        };
+
+        let mut atoms = HashSet::new();
+        for (idx, setup) in self.setup.iter().enumerate() {
+            let mut warn = |message| {
+                warnings.push(SemanticWarning {
+                    index: InstructionIndex::Setup(idx),
+                    message,
+                })
+            };
+            match setup {
+                SetupInstruction::Atom {
+                    position,
+                    id,
+                    appear_time,
+                    shape: _,
+                } => {
+                    if !atoms.insert(id.as_str()) {
+                        warn(format!("Atom `{id}` is declared multiple times"));
+                    }
+                    if out_of_bounds(*position) {
+                        warn(format!(
+                            "Position {} of atom `{id}` is outside of the machine",
+                            fmt_position(position)
+                        ));
+                    }
+                    if appear_time.is_some_and(|t| t < Fraction::zero()) {
+                        warn(format!("Atom `{id}` appears at a negative time"));
+                    }
+                }
+            }
+        }
+
+        let mut idx = 0;
+        for (absolute, timeline) in &self.instructions {
+            // The exact start time of the previous group, if known
+            let mut start = Some(*absolute);
+            for (from_start, offset, group) in timeline {
+                start = if *from_start {
+                    start.map(|s| s + *offset)
+                } else {
+                    // Depends on the duration of the previous group
+                    None
+                };
+                for instruction in &group.instructions {
+                    let mut warn = |message| {
+                        warnings.push(SemanticWarning {
+                            index: InstructionIndex::Timed(idx),
+                            message,
+                        })
+                    };
+                    let name = instruction.str();
+
+                    if start.is_some_and(|s| s < Fraction::zero()) {
+                        warn(format!("`{name}` starts at a negative time"));
+                    }
+
+                    let (atom_ids, targets, position): (&[String], &[String], _) = match instruction
+                    {
+                        TimedInstruction::Load { position, id, .. }
+                        | TimedInstruction::Store { position, id, .. } => {
+                            (std::slice::from_ref(id), &[], *position)
+                        }
+                        TimedInstruction::Move { position, id, .. } => {
+                            (std::slice::from_ref(id), &[], Some(*position))
+                        }
+                        TimedInstruction::MoveBy { ids, .. } => (ids, &[], None),
+                        TimedInstruction::Rz { targets, .. }
+                        | TimedInstruction::Ry { targets, .. }
+                        | TimedInstruction::Cz { targets, .. }
+                        | TimedInstruction::Measure { targets, .. }
+                        | TimedInstruction::Reset { targets, .. } => (&[], targets, None),
+                        TimedInstruction::Highlight { id, .. } => {
+                            if !machine.zone.contains_key(id) {
+                                warn(format!("`{name}` targets undefined zone `{id}`"));
+                            }
+                            (&[], &[], None)
+                        }
+                    };
+
+                    for id in atom_ids {
+                        if !atoms.contains(id.as_str()) {
+                            warn(format!("`{name}` targets undefined atom `{id}`"));
+                        }
+                    }
+                    for id in targets {
+                        if !atoms.contains(id.as_str()) && !machine.zone.contains_key(id) {
+                            warn(format!("`{name}` targets undefined atom or zone `{id}`"));
+                        }
+                    }
+                    if let Some(position) = position.filter(|p| out_of_bounds(*p)) {
+                        warn(format!(
+                            "`{name}` targets position {} outside of the machine",
+                            fmt_position(&position)
+                        ));
+                    }
+
+                    idx += 1;
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+/// Formats the passed [Position] as `(x, y)`
+pub(super) fn fmt_position((x, y): &Position) -> String {
+    format!("({x}, {y})")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        config::{self, generic::Config},
+        test_utils::instructions,
+    };
+
+    /// Loads the example machine
+    fn machine() -> MachineConfig {
+        let input = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/rsc/test/example.namachine"
+        ));
+        let lexed = config::lexer::lex(input).expect("Failed to lex");
+        let parsed = config::parser::parse(&lexed).expect("Failed to parse");
+        let generic: Config = parsed.into();
+        generic.try_into().expect("Failed to load machine")
+    }
+
+    #[test]
+    fn undefined_atom_move() {
+        let warnings = instructions(
+            "atom (0, 0) atom0\n\
+             @0 move (5, 5) atom1",
+        )
+        .expect("Failed to convert")
+        .validate_against(&machine());
+
+        assert_eq!(
+            warnings,
+            [SemanticWarning {
+                index: InstructionIndex::Timed(0),
+                message: "`move` targets undefined atom `atom1`".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_position() {
+        let warnings = instructions(
+            "atom (0, 0) atom0\n\
+             @0 move (5, 5) atom0\n\
+             @1 move (1000, 5) atom0",
+        )
+        .expect("Failed to convert")
+        .validate_against(&machine());
+
+        assert_eq!(
+            warnings,
+            [SemanticWarning {
+                index: InstructionIndex::Timed(1),
+                message: "`move` targets position (1000, 5) outside of the machine".to_string(),
+            }]
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub mod input;
 pub mod test_utils;
 
 pub use common::position::byte_offset_to_line_column;
+pub use common::source::normalize_source;
 
 /// Error returned when parsing/lexing.
 /// Contains Reference to the input.
@@ -4,9 +4,22 @@
 // Re-export public utility for convenience in tests.
 pub use crate::common::position::byte_offset_to_line_column;
 
+use crate::input::{
+    concrete::{Instructions, ParseInstructionsError},
+    lexer, parser,
+};
+
 /// Collect stringified context frames from a winnow ParseError.
 pub fn collect_context<I>(
     err: winnow::error::ParseError<I, winnow::error::ContextError>,
 ) -> Vec<String> {
     err.into_inner().context().map(|c| c.to_string()).collect()
 }
+
+/// Lexes, parses, and converts the passed `input` into [Instructions].
+/// Panics if lexing or parsing fails.
+pub fn instructions(input: &str) -> Result<Instructions, ParseInstructionsError> {
+    let lexed = lexer::lex(input).expect("Failed to lex");
+    let parsed = parser::parse(&lexed).expect("Failed to parse");
+    Instructions::new(parsed)
+}
@@ -2,7 +2,7 @@ use std::num::NonZeroU64;
 
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    Buffer, BufferUsages, Device, Queue,
+    Buffer, BufferAddress, BufferUsages, Device, Queue,
 };
 
 /// Implementors of this trait can update the passed buffer.
@@ -15,6 +15,10 @@ pub trait BufferUpdater {
         label: Option<&'static str>,
         usage: BufferUsages,
     );
+
+    /// Writes `data` into the passed buffer at the byte-`offset`.
+    /// Does not resize the buffer: `data` must fit into the buffer.
+    fn write<T: bytemuck::NoUninit>(&mut self, buffer: &Buffer, offset: BufferAddress, data: &[T]);
 }
 
 impl BufferUpdater for (&Device, &Queue) {
@@ -47,4 +51,9 @@ impl BufferUpdater for (&Device, &Queue) {
                 .copy_from_slice(bytemuck::cast_slice(data));
         }
     }
+
+    fn write<T: bytemuck::NoUninit>(&mut self, buffer: &Buffer, offset: BufferAddress, data: &[T]) {
+        let (_, queue) = self;
+        queue.write_buffer(buffer, offset, bytemuck::cast_slice(data));
+    }
 }
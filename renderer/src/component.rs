@@ -4,7 +4,7 @@ use naga_oil::compose::Composer;
 use naviz_state::{config::Config, state::State};
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor,
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
     BindGroupLayoutEntry, BlendComponent, BlendFactor, BlendState, Buffer, BufferAddress,
     BufferUsages, ColorTargetState, ColorWrites, Device, FragmentState, MultisampleState,
     PipelineLayoutDescriptor, PrimitiveState, PrimitiveTopology, Queue, RenderPass, RenderPipeline,
@@ -21,10 +21,13 @@ use crate::{
 };
 
 pub mod atoms;
+pub mod background;
 pub mod drawable;
+pub mod ghosts;
 pub mod legend;
 pub mod machine;
 pub mod primitive;
+pub mod progress;
 pub mod time;
 pub mod updatable;
 
@@ -70,6 +73,7 @@ pub struct Component<Spec: bytemuck::NoUninit> {
     render_pipeline: RenderPipeline,
     instance_buffer: Buffer,
     instance_count: u32,
+    bind_group_layout: BindGroupLayout,
     bind_group: BindGroup,
     phantom: PhantomData<Spec>,
 }
@@ -175,6 +179,7 @@ impl<Spec: bytemuck::NoUninit> Component<Spec> {
             render_pipeline,
             instance_buffer,
             instance_count: specs.len() as u32,
+            bind_group_layout,
             bind_group,
             phantom: PhantomData,
         }
@@ -191,6 +196,31 @@ impl<Spec: bytemuck::NoUninit> Component<Spec> {
         self.instance_count = spec.len() as u32;
     }
 
+    /// Overwrites the specs starting at index `start` with the passed `spec`,
+    /// keeping all other specs.
+    /// The overwritten range must lie within the current specs.
+    pub fn update_range<U: BufferUpdater>(&mut self, updater: &mut U, start: usize, spec: &[Spec]) {
+        debug_assert!(
+            start + spec.len() <= self.instance_count as usize,
+            "Range out of bounds"
+        );
+        updater.write(
+            &self.instance_buffer,
+            (start * size_of::<Spec>()) as BufferAddress,
+            spec,
+        );
+    }
+
+    /// Replaces the entries of the uniform buffer group (group `2`).
+    /// The `entries` must match the layout passed in [ComponentSpec::uniform].
+    pub fn update_uniform(&mut self, device: &Device, entries: &[BindGroupEntry]) {
+        self.bind_group = device.create_bind_group(&BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries,
+            label: Some("uniform buffer group"),
+        });
+    }
+
     /// Draws this component
     pub fn draw(&self, render_pass: &mut RenderPass<'_>) {
         if self.instance_count == 0 {
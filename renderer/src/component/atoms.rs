@@ -1,6 +1,6 @@
 use naviz_state::{
-    config::{AtomsConfig, Config},
-    state::{AtomState, State},
+    config::Config,
+    state::{AtomState, AtomsDiff, State},
 };
 use wgpu::{Device, Queue, RenderPass};
 
@@ -14,6 +14,7 @@ use super::{
     primitive::{
         circles::{CircleSpec, Circles},
         lines::{LineSpec, Lines},
+        shapes::{ShapeSpec, Shapes},
         text::{Alignment, HAlignment, Text, TextSpec, VAlignment},
     },
     updatable::Updatable,
@@ -21,13 +22,17 @@ use super::{
 };
 
 /// A component to draw atoms:
-/// - Circle representing atom
+/// - Shape representing atom
+/// - Outline around atom
 /// - Shuttle lines
+/// - Interaction-distance indicator
 /// - Label
 pub struct Atoms {
     viewport: Viewport,
-    atoms: Circles,
+    atoms: Shapes,
+    outlines: Shapes,
     shuttles: Lines,
+    interactions: Circles,
     labels: Text,
     viewport_projection: ViewportProjection,
 }
@@ -47,20 +52,30 @@ impl Atoms {
         }: ComponentInit,
     ) -> Self {
         let AtomSpec {
-            atom_circles,
+            atom_shapes,
+            outlines,
             shuttles,
+            interactions,
             labels,
         } = get_specs(config, state, viewport_projection);
         let viewport = Viewport::new(viewport_projection, device);
 
         Self {
-            atoms: Circles::new(
+            atoms: Shapes::new(
                 device,
                 format,
                 globals,
                 &viewport,
                 shader_composer,
-                &atom_circles,
+                &atom_shapes,
+            ),
+            outlines: Shapes::new(
+                device,
+                format,
+                globals,
+                &viewport,
+                shader_composer,
+                &outlines,
             ),
             shuttles: Lines::new(
                 device,
@@ -70,12 +85,61 @@ impl Atoms {
                 shader_composer,
                 &shuttles,
             ),
+            interactions: Circles::new(
+                device,
+                format,
+                globals,
+                &viewport,
+                shader_composer,
+                &interactions,
+            ),
             labels: Text::new(device, queue, format, labels, screen_resolution),
             viewport,
             viewport_projection,
         }
     }
 
+    /// Updates these [Atoms] to resemble the new [State],
+    /// where only the atoms in `diff` changed compared to the previous [State].
+    /// Only the shapes of the changed atoms are written;
+    /// the outlines, shuttles, interaction-indicators, and labels are rebuilt if any atom changed.
+    /// See [Updatable::update].
+    pub fn update_changed(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        config: &Config,
+        state: &State,
+        diff: &AtomsDiff,
+    ) {
+        let AtomsDiff::Changed(changed) = diff else {
+            self.update(updater, device, queue, config, state);
+            return;
+        };
+        if changed.is_empty() {
+            return;
+        }
+
+        // Write consecutive changed atoms at once
+        for run in changed.chunk_by(|a, b| a + 1 == *b) {
+            let (first, last) = (run[0], run[run.len() - 1]);
+            let shapes: Vec<_> = state.atoms[first..=last].iter().map(get_shape).collect();
+            self.atoms.update_range(updater, first, &shapes);
+        }
+        self.outlines.update(updater, &get_outlines(config, state));
+        self.shuttles.update(
+            updater,
+            &get_shuttles(config, state, self.viewport_projection),
+        );
+        self.interactions
+            .update(updater, &get_interactions(config, state));
+        self.labels.update(
+            (device, queue),
+            get_labels(config, state, self.viewport_projection),
+        );
+    }
+
     /// Updates the viewport resolution of these [Atoms]
     pub fn update_viewport(
         &mut self,
@@ -100,7 +164,9 @@ impl Drawable for Atoms {
     ) {
         self.viewport.bind(render_pass);
         self.shuttles.draw(render_pass);
+        self.interactions.draw(render_pass);
         self.atoms.draw(render_pass);
+        self.outlines.draw(render_pass);
         self.labels.draw::<REBIND>(render_pass, rebind);
     }
 }
@@ -115,12 +181,16 @@ impl Updatable for Atoms {
         state: &State,
     ) {
         let AtomSpec {
-            atom_circles,
+            atom_shapes,
+            outlines,
             shuttles,
+            interactions,
             labels,
         } = get_specs(config, state, self.viewport_projection);
-        self.atoms.update(updater, &atom_circles);
+        self.atoms.update(updater, &atom_shapes);
+        self.outlines.update(updater, &outlines);
         self.shuttles.update(updater, &shuttles);
+        self.interactions.update(updater, &interactions);
         self.labels.update((device, queue), labels);
     }
 
@@ -141,10 +211,14 @@ impl Updatable for Atoms {
 
 #[derive(Clone, Debug)]
 struct AtomSpec<'a, TextIterator: IntoIterator<Item = (&'a str, (f32, f32), Alignment)>> {
-    /// Circles representing the atoms
-    atom_circles: Vec<CircleSpec>,
+    /// Shapes representing the atoms
+    atom_shapes: Vec<ShapeSpec>,
+    /// Outlines of the atoms
+    outlines: Vec<ShapeSpec>,
     /// Lines representing the atom shuttles
     shuttles: Vec<LineSpec>,
+    /// Rings indicating the interaction distance around interacting atoms
+    interactions: Vec<CircleSpec>,
     /// Labels drawn over the atoms at their positions
     labels: TextSpec<'a, TextIterator>,
 }
@@ -155,30 +229,58 @@ fn get_specs<'a>(
     state: &'a State,
     viewport_projection: ViewportProjection,
 ) -> AtomSpec<'a, impl IntoIterator<Item = (&'a str, (f32, f32), Alignment)>> {
-    let atoms = &state.atoms;
-    let AtomsConfig { shuttle, label } = &config.atoms;
+    AtomSpec {
+        atom_shapes: state.atoms.iter().map(get_shape).collect(),
+        outlines: get_outlines(config, state),
+        shuttles: get_shuttles(config, state, viewport_projection),
+        interactions: get_interactions(config, state),
+        labels: get_labels(config, state, viewport_projection),
+    }
+}
 
-    // The circles for the atoms
-    let atom_circles: Vec<_> = atoms
+/// Gets the shape representing the passed atom
+fn get_shape(
+    AtomState {
+        position,
+        size,
+        color,
+        shape,
+        pattern,
+        shuttle: _,
+        interaction: _,
+        label: _,
+    }: &AtomState,
+) -> ShapeSpec {
+    ShapeSpec::new((*position).into(), *size, *color, *shape).with_pattern(*pattern)
+}
+
+/// Gets the outlines of the atoms (if an outline is configured) in the shapes of the atoms.
+/// The outlines are drawn inside the atoms, so that they do not change the size of the atoms.
+/// Hidden atoms (i.e., atoms with a size of `0`) are not outlined.
+fn get_outlines(config: &Config, state: &State) -> Vec<ShapeSpec> {
+    let Some(outline) = &config.atoms.outline else {
+        return Vec::new();
+    };
+    state
+        .atoms
         .iter()
-        .map(
-            |AtomState {
-                 position,
-                 size,
-                 color,
-                 shuttle: _,
-                 label: _,
-             }| CircleSpec {
-                center: (*position).into(),
-                radius: *size,
-                color: *color,
-                radius_inner: 0.,
-            },
-        )
-        .collect();
+        .filter(|a| a.size > 0.)
+        .map(|a| {
+            ShapeSpec::new(a.position.into(), a.size, outline.color, a.shape)
+                .with_outline(outline.width)
+        })
+        .collect()
+}
 
-    // The shuttle lines
-    let shuttles: Vec<_> = atoms
+/// Gets the shuttle lines of the shuttling atoms
+fn get_shuttles(
+    config: &Config,
+    state: &State,
+    viewport_projection: ViewportProjection,
+) -> Vec<LineSpec> {
+    let shuttle = &config.atoms.shuttle;
+    state
+        .atoms
         .iter()
         .filter(|s| s.shuttle)
         .flat_map(
@@ -186,7 +288,10 @@ fn get_specs<'a>(
                  position: (x, y),
                  size: _,
                  color: _,
+                 shape: _,
+                 pattern: _,
                  shuttle: _,
+                 interaction: _,
                  label: _,
              }| {
                 [
@@ -209,17 +314,44 @@ fn get_specs<'a>(
                 ]
             },
         )
-        .collect();
+        .collect()
+}
+
+/// Gets the rings indicating the interaction distance around the interacting atoms
+fn get_interactions(config: &Config, state: &State) -> Vec<CircleSpec> {
+    let interaction = &config.atoms.interaction;
+    state
+        .atoms
+        .iter()
+        .filter(|a| a.interaction)
+        .map(|a| CircleSpec {
+            center: a.position.into(),
+            radius: interaction.radius,
+            radius_inner: (interaction.radius - interaction.width).max(0.),
+            color: interaction.color,
+        })
+        .collect()
+}
 
-    // The labels
-    let labels: Vec<_> = atoms
+/// Gets the labels drawn over the atoms at their positions
+fn get_labels<'a>(
+    config: &'a Config,
+    state: &'a State,
+    viewport_projection: ViewportProjection,
+) -> TextSpec<'a, impl IntoIterator<Item = (&'a str, (f32, f32), Alignment)>> {
+    let label = &config.atoms.label;
+    let labels: Vec<_> = state
+        .atoms
         .iter()
         .map(
             |AtomState {
                  position: (x, y),
                  size: _,
                  color: _,
+                 shape: _,
+                 pattern: _,
                  shuttle: _,
+                 interaction: _,
                  label,
              }| {
                 (
@@ -231,15 +363,76 @@ fn get_specs<'a>(
         )
         .collect();
 
-    AtomSpec {
-        atom_circles,
-        shuttles,
-        labels: TextSpec {
-            viewport_projection,
-            font_size: label.size,
-            font_family: &label.family,
-            texts: labels,
-            color: label.color,
-        },
+    TextSpec {
+        viewport_projection,
+        font_size: label.size,
+        font_family: &label.family,
+        texts: labels,
+        color: label.color,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use naviz_state::{config::OutlineConfig, Shape};
+
+    use super::*;
+
+    /// An atom with a square shape should be drawn using the square geometry.
+    #[test]
+    fn square_atom() {
+        let mut state = State::example();
+        state.atoms[1].shape = Shape::Square;
+
+        let AtomSpec { atom_shapes, .. } =
+            get_specs(&Config::example(), &state, ViewportProjection::identity());
+        assert_eq!(atom_shapes[0].shape(), Shape::Circle);
+        assert_eq!(atom_shapes[1].shape(), Shape::Square);
+    }
+
+    /// Only interacting atoms should get an interaction-indicator.
+    #[test]
+    fn interaction_indicator() {
+        let config = Config::example();
+        let mut state = State::example();
+
+        let AtomSpec { interactions, .. } =
+            get_specs(&config, &state, ViewportProjection::identity());
+        assert!(interactions.is_empty(), "Indicator without interaction");
+
+        state.atoms[0].interaction = true;
+        state.atoms[2].interaction = true;
+        let AtomSpec { interactions, .. } =
+            get_specs(&config, &state, ViewportProjection::identity());
+        assert_eq!(interactions.len(), 2, "Missing indicators");
+        assert_eq!(
+            interactions[1].center,
+            <[f32; 2]>::from(state.atoms[2].position)
+        );
+        assert_eq!(interactions[1].radius, config.atoms.interaction.radius);
+    }
+
+    /// An outline should be drawn at the edge of each atom in the shape of the atom.
+    #[test]
+    fn atom_outline() {
+        let mut config = Config::example();
+        let mut state = State::example();
+        state.atoms[3].shape = Shape::Diamond;
+
+        let AtomSpec { outlines, .. } = get_specs(&config, &state, ViewportProjection::identity());
+        assert!(outlines.is_empty(), "Outline without config");
+
+        config.atoms.outline = Some(OutlineConfig {
+            width: 1.,
+            color: [0, 0, 0, 255],
+        });
+        let AtomSpec { outlines, .. } = get_specs(&config, &state, ViewportProjection::identity());
+        assert_eq!(outlines.len(), state.atoms.len(), "Missing outlines");
+        let (outline, atom) = (&outlines[3], &state.atoms[3]);
+        assert_eq!(outline.center, <[f32; 2]>::from(atom.position));
+        assert_eq!(outline.radius, atom.size);
+        assert_eq!(outline.shape(), Shape::Diamond);
+        assert_eq!(outline.color, [0, 0, 0, 255]);
+        assert_eq!(outlines[0].shape(), Shape::Circle);
     }
 }
@@ -0,0 +1,354 @@
+use log::warn;
+use naviz_state::{config::Config, state::State};
+use wgpu::{
+    util::{DeviceExt, TextureDataOrder},
+    AddressMode, BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType, BufferAddress,
+    Device, Extent3d, FilterMode, Queue, RenderPass, Sampler, SamplerBindingType,
+    SamplerDescriptor, ShaderStages, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
+    VertexAttribute, VertexFormat,
+};
+
+use crate::{
+    buffer_updater::BufferUpdater,
+    component::drawable::Drawable,
+    viewport::{Viewport, ViewportProjection},
+};
+
+use super::{updatable::Updatable, Component, ComponentInit, ComponentSpec};
+
+/// A decoded image which can be drawn by the [Background]
+#[derive(Clone, Debug)]
+pub struct BackgroundImage {
+    /// The size of the image in pixels (`width`, `height`)
+    pub size: (u32, u32),
+    /// The pixels of the image as row-major `RGBA8`-data
+    pub data: Vec<u8>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct BackgroundSpec {
+    /// The top-left corner of the image
+    start: [f32; 2],
+    /// The size of the image
+    size: [f32; 2],
+    /// The opacity of the image
+    opacity: f32,
+}
+
+/// A component to draw a static image behind the machine.
+/// The image spans the [extent][naviz_state::config::BackgroundConfig::extent] of the [Config]
+/// and is set using [Background::set_image].
+///
+/// Draws nothing if no image is configured or set.
+pub struct Background {
+    viewport: Viewport,
+    image: Component<BackgroundSpec>,
+    sampler: Sampler,
+    /// The spec from the [Config], if an image is configured
+    spec: Option<BackgroundSpec>,
+    /// Whether an image was set
+    has_image: bool,
+}
+
+impl Background {
+    pub fn new(
+        ComponentInit {
+            device,
+            queue,
+            format,
+            globals,
+            shader_composer,
+            config,
+            state: _,
+            viewport_projection,
+            screen_resolution: _,
+        }: ComponentInit,
+    ) -> Self {
+        let viewport = Viewport::new(viewport_projection, device);
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("background sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+        // Placeholder until an image is set
+        let texture = create_texture_view(device, queue, &transparent());
+
+        let image = Component::new(
+            device,
+            format,
+            globals,
+            &viewport,
+            shader_composer,
+            ComponentSpec {
+                specs: &[],
+                attributes: &[
+                    VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: VertexFormat::Float32x2,
+                    },
+                    VertexAttribute {
+                        offset: size_of::<[f32; 2]>() as BufferAddress,
+                        shader_location: 1,
+                        format: VertexFormat::Float32x2,
+                    },
+                    VertexAttribute {
+                        offset: (size_of::<[f32; 2]>() * 2) as BufferAddress,
+                        shader_location: 2,
+                        format: VertexFormat::Float32,
+                    },
+                ],
+                shader_source: include_str!("background.wgsl"),
+                shader_path: "background.wgsl",
+                uniform: Some((&layout_entries(), &bind_group_entries(&texture, &sampler))),
+            },
+        );
+
+        Self {
+            viewport,
+            image,
+            sampler,
+            spec: get_spec(config),
+            has_image: false,
+        }
+    }
+
+    /// Sets the image to draw.
+    /// Pass [None] to remove the current image.
+    /// Images larger than the maximum texture size of the `device` are rejected
+    /// (and a warning is logged).
+    pub fn set_image(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        image: Option<&BackgroundImage>,
+    ) {
+        let max_size = device.limits().max_texture_dimension_2d;
+        let image = image.filter(|image| {
+            let fits = image.size.0 <= max_size && image.size.1 <= max_size;
+            if !fits {
+                warn!(
+                    "Background image of size {}x{} exceeds the maximum texture size of {max_size}",
+                    image.size.0, image.size.1
+                );
+            }
+            fits
+        });
+        let texture = create_texture_view(device, queue, image.unwrap_or(&transparent()));
+        self.image
+            .update_uniform(device, &bind_group_entries(&texture, &self.sampler));
+        self.has_image = image.is_some();
+        self.update_spec(updater);
+    }
+
+    /// Updates the instance buffer to draw the image
+    /// iff an image is both configured and set
+    fn update_spec(&mut self, updater: &mut impl BufferUpdater) {
+        let spec = self.spec.filter(|_| self.has_image);
+        self.image.update(updater, spec.as_slice());
+    }
+}
+
+impl Drawable for Background {
+    /// Draws this [Background].
+    ///
+    /// May overwrite bind groups.
+    /// Will never call `rebind`, as [Background] only overwrites its own groups.
+    #[inline]
+    fn draw<const REBIND: bool>(
+        &self,
+        render_pass: &mut RenderPass<'_>,
+        _rebind: impl Fn(&mut RenderPass),
+    ) {
+        self.viewport.bind(render_pass);
+        self.image.draw(render_pass);
+    }
+}
+
+impl Updatable for Background {
+    fn update(
+        &mut self,
+        _updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        _config: &Config,
+        _state: &State,
+    ) {
+        // Nothing depends on state
+    }
+
+    fn update_full(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        config: &Config,
+        _state: &State,
+        viewport_projection: ViewportProjection,
+    ) {
+        self.viewport.update(updater, viewport_projection);
+        self.spec = get_spec(config);
+        self.update_spec(updater);
+    }
+}
+
+/// Gets the [BackgroundSpec] for the passed [Config],
+/// or [None] if no image is configured.
+fn get_spec(config: &Config) -> Option<BackgroundSpec> {
+    config.background.as_ref().map(|background| {
+        let ((x0, y0), (x1, y1)) = background.extent;
+        BackgroundSpec {
+            start: [x0.min(x1), y0.min(y1)],
+            size: [(x1 - x0).abs(), (y1 - y0).abs()],
+            opacity: background.opacity.clamp(0., 1.),
+        }
+    })
+}
+
+/// A single transparent pixel
+fn transparent() -> BackgroundImage {
+    BackgroundImage {
+        size: (1, 1),
+        data: vec![0; 4],
+    }
+}
+
+/// Uploads the passed [BackgroundImage] to a texture
+fn create_texture_view(device: &Device, queue: &Queue, image: &BackgroundImage) -> TextureView {
+    device
+        .create_texture_with_data(
+            queue,
+            &TextureDescriptor {
+                label: Some("background image"),
+                size: Extent3d {
+                    width: image.size.0,
+                    height: image.size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                // Not sRGB: colors are passed through like all other colors
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            TextureDataOrder::LayerMajor,
+            &image.data,
+        )
+        .create_view(&TextureViewDescriptor::default())
+}
+
+/// The layout of the texture and sampler
+fn layout_entries() -> [BindGroupLayoutEntry; 2] {
+    [
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Texture {
+                sample_type: TextureSampleType::Float { filterable: true },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            count: None,
+        },
+    ]
+}
+
+/// The entries binding the passed texture and sampler
+fn bind_group_entries<'a>(
+    texture: &'a TextureView,
+    sampler: &'a Sampler,
+) -> [BindGroupEntry<'a>; 2] {
+    [
+        BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(texture),
+        },
+        BindGroupEntry {
+            binding: 1,
+            resource: BindingResource::Sampler(sampler),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        globals::Globals,
+        shaders::{create_composer, load_default_shaders},
+    };
+
+    use super::*;
+    use crate::skip_without_adapter;
+
+    /// The [Background] should build and accept a 1x1 image,
+    /// but only draw it if an image is configured.
+    #[test]
+    fn build_with_image() {
+        let (device, queue) = skip_without_adapter!(device);
+        let mut composer = load_default_shaders(create_composer()).unwrap();
+        let globals = Globals::new(&device);
+        let mut config = Config::example();
+        let image = BackgroundImage {
+            size: (1, 1),
+            data: vec![255, 0, 0, 255],
+        };
+
+        // Not configured
+        let mut background = Background::new(ComponentInit {
+            device: &device,
+            queue: &queue,
+            format: TextureFormat::Rgba8Unorm,
+            globals: &globals,
+            shader_composer: &mut composer,
+            config: &config,
+            state: &State::example(),
+            viewport_projection: ViewportProjection::identity(),
+            screen_resolution: (100, 100),
+        });
+        background.set_image(&mut (&device, &queue), &device, &queue, Some(&image));
+        assert_eq!(background.image.instance_count, 0, "Drawn without config");
+
+        // Configured
+        config.background = Some(naviz_state::config::BackgroundConfig {
+            path: "test.png".to_string(),
+            extent: ((0., 0.), (10., 10.)),
+            opacity: 0.5,
+        });
+        background.update_full(
+            &mut (&device, &queue),
+            &device,
+            &queue,
+            &config,
+            &State::example(),
+            ViewportProjection::identity(),
+        );
+        assert_eq!(background.image.instance_count, 1, "Image not drawn");
+
+        // Too large
+        let max_size = device.limits().max_texture_dimension_2d;
+        let too_large = BackgroundImage {
+            size: (max_size + 1, 1),
+            data: vec![0; (max_size as usize + 1) * 4],
+        };
+        background.set_image(&mut (&device, &queue), &device, &queue, Some(&too_large));
+        assert_eq!(background.image.instance_count, 0, "Oversized image drawn");
+
+        // Removed
+        background.set_image(&mut (&device, &queue), &device, &queue, None);
+        assert_eq!(background.image.instance_count, 0, "Drawn without image");
+    }
+}
@@ -0,0 +1,44 @@
+#import viewport::{viewport_projection, with_opacity};
+
+struct BackgroundSpec {
+	@location(0) start: vec2<f32>,
+	@location(1) size: vec2<f32>,
+	@location(2) opacity: f32,
+}
+
+struct VOutput {
+	// position in the image (`0` to `1`)
+	@location(0) uv: vec2<f32>,
+	@location(1) opacity: f32,
+	@builtin(position) position: vec4<f32>,
+};
+
+@group(2) @binding(0)
+var image: texture_2d<f32>;
+@group(2) @binding(1)
+var image_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32, spec: BackgroundSpec) -> VOutput {
+	// Corners of the image
+	var uv = array<vec2<f32>,4>(
+		vec2<f32>(0.0, 0.0),
+		vec2<f32>(0.0, 1.0),
+		vec2<f32>(1.0, 1.0),
+		vec2<f32>(1.0, 0.0),
+	);
+	// Indices for triangles of the image
+	var idx = array<u32, 6>(0, 1, 2, 2, 3, 0);
+
+	var out: VOutput;
+	out.uv = uv[idx[in_vertex_index]];
+	out.opacity = spec.opacity;
+	out.position = viewport_projection * vec4<f32>(spec.start + out.uv * spec.size, 0.0, 1.0);
+	return out;
+}
+
+@fragment
+fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
+	let color = textureSample(image, image_sampler, in.uv);
+	return with_opacity(vec4<f32>(color.rgb, color.a * in.opacity));
+}
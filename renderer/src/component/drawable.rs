@@ -1,6 +1,11 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
 
-use wgpu::RenderPass;
+use wgpu::{Device, Queue, RenderPass};
+
+use crate::buffer_updater::BufferUpdater;
 
 /// A trait for something which can be drawn/rendered.
 pub trait Drawable {
@@ -17,33 +22,124 @@ pub trait Drawable {
     );
 }
 
+/// A trait for [Drawable]s which can be drawn with reduced opacity.
+pub trait Fadable {
+    /// Sets the `opacity` (`0` to `1`) which is multiplied into the output color
+    fn set_opacity(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        opacity: f32,
+    );
+}
+
 /// A [Drawable] which wraps a child-[Drawable] and decides whether the child is visible or hidden.
-/// If the child is not visible, it will never be drawn.
+/// If the child is fully hidden, it will never be drawn.
+///
+/// Changes in visibility can fade over a [duration][Hidable::set_fade_duration].
+/// A running fade is advanced using [Hidable::advance]
+/// and applied to the child using [Hidable::apply_opacity].
 pub struct Hidable<Child: Drawable> {
     visible: bool,
+    /// The current opacity (`0` to `1`)
+    opacity: f32,
+    /// The opacity last passed to the child
+    applied_opacity: f32,
+    /// The duration of a fade between fully hidden and fully visible
+    fade_duration: Duration,
     child: Child,
 }
 
 impl<Child: Drawable> Hidable<Child> {
     /// Creates a new [Hidable] wrapping the passed `child`.
-    /// Will be visible by default.
+    /// Will be visible by default and toggle visibility instantly.
     pub fn new(child: Child) -> Self {
         Self {
             visible: true,
+            opacity: 1.,
+            applied_opacity: 1.,
+            fade_duration: Duration::ZERO,
             child,
         }
     }
 
-    /// Update the visibility of this [Hidable]
+    /// Update the visibility of this [Hidable].
+    /// Will fade to the new visibility if a [fade duration][Hidable::set_fade_duration] is set.
     pub fn set_visible(&mut self, visible: bool) {
         self.visible = visible;
+        if self.fade_duration.is_zero() {
+            self.opacity = self.target_opacity();
+        }
     }
 
-    /// Update the visibility of this [Hidable]
+    /// Update the visibility of this [Hidable].
+    /// Does not fade.
     pub fn with_visibility(mut self, visible: bool) -> Self {
-        self.set_visible(visible);
+        self.visible = visible;
+        self.opacity = self.target_opacity();
         self
     }
+
+    /// Sets the duration of a fade between fully hidden and fully visible.
+    /// A zero duration toggles visibility instantly.
+    pub fn set_fade_duration(&mut self, fade_duration: Duration) {
+        self.fade_duration = fade_duration;
+        if fade_duration.is_zero() {
+            self.opacity = self.target_opacity();
+        }
+    }
+
+    /// The current opacity (`0` to `1`)
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Whether this [Hidable] is currently fading
+    pub fn is_fading(&self) -> bool {
+        self.opacity != self.target_opacity()
+    }
+
+    /// Advances a running fade by the `elapsed` time.
+    /// Returns whether this [Hidable] is still fading.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let target = self.target_opacity();
+        if self.fade_duration.is_zero() {
+            self.opacity = target;
+            return false;
+        }
+        let step = elapsed.as_secs_f32() / self.fade_duration.as_secs_f32();
+        self.opacity = if target > self.opacity {
+            (self.opacity + step).min(target)
+        } else {
+            (self.opacity - step).max(target)
+        };
+        self.is_fading()
+    }
+
+    /// The opacity to fade towards
+    fn target_opacity(&self) -> f32 {
+        if self.visible {
+            1.
+        } else {
+            0.
+        }
+    }
+}
+
+impl<Child: Drawable + Fadable> Hidable<Child> {
+    /// Passes the current opacity to the child if it changed
+    pub fn apply_opacity(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+    ) {
+        if self.applied_opacity != self.opacity {
+            self.child.set_opacity(updater, device, queue, self.opacity);
+            self.applied_opacity = self.opacity;
+        }
+    }
 }
 
 impl<Child: Drawable> Drawable for Hidable<Child> {
@@ -52,7 +148,7 @@ impl<Child: Drawable> Drawable for Hidable<Child> {
         render_pass: &mut RenderPass<'_>,
         rebind: impl Fn(&mut RenderPass),
     ) {
-        if !self.visible {
+        if self.opacity <= 0. {
             // no bindings are changed, therefore we never need to rebind
             return;
         }
@@ -72,3 +168,57 @@ impl<Child: Drawable> DerefMut for Hidable<Child> {
         &mut self.child
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A [Drawable] which draws nothing
+    struct Empty;
+
+    impl Drawable for Empty {
+        fn draw<const REBIND: bool>(
+            &self,
+            _render_pass: &mut RenderPass<'_>,
+            _rebind: impl Fn(&mut RenderPass),
+        ) {
+        }
+    }
+
+    #[test]
+    fn instant_toggle() {
+        let mut hidable = Hidable::new(Empty);
+        hidable.set_visible(false);
+        assert_eq!(hidable.opacity(), 0.);
+        assert!(!hidable.is_fading());
+    }
+
+    #[test]
+    fn fade_out() {
+        let mut hidable = Hidable::new(Empty);
+        hidable.set_fade_duration(Duration::from_millis(200));
+        hidable.set_visible(false);
+        assert_eq!(hidable.opacity(), 1.);
+
+        assert!(hidable.advance(Duration::from_millis(50)));
+        assert!(
+            hidable.opacity() > 0. && hidable.opacity() < 1.,
+            "Opacity during fade should be between 0 and 1, was {}",
+            hidable.opacity()
+        );
+
+        assert!(!hidable.advance(Duration::from_millis(200)));
+        assert_eq!(hidable.opacity(), 0.);
+    }
+
+    #[test]
+    fn fade_reverses() {
+        let mut hidable = Hidable::new(Empty).with_visibility(false);
+        hidable.set_fade_duration(Duration::from_secs(1));
+        hidable.set_visible(true);
+        hidable.advance(Duration::from_millis(500));
+        hidable.set_visible(false);
+        hidable.advance(Duration::from_millis(250));
+        assert!((hidable.opacity() - 0.25).abs() < 1e-6);
+    }
+}
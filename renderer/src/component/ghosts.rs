@@ -0,0 +1,137 @@
+use naviz_state::{config::Config, state::State};
+use wgpu::{Device, Queue, RenderPass};
+
+use crate::{
+    buffer_updater::BufferUpdater,
+    component::drawable::Drawable,
+    viewport::{Viewport, ViewportProjection},
+};
+
+use super::{
+    primitive::circles::{CircleSpec, Circles},
+    updatable::Updatable,
+    ComponentInit,
+};
+
+/// A component to draw faint markers ("ghosts") at the final positions of the atoms
+/// (see [GhostConfig][naviz_state::config::GhostConfig]).
+/// The ghosts only depend on the [Config] and are shown throughout the whole animation.
+///
+/// Draws nothing if no ghosts are configured.
+pub struct Ghosts {
+    viewport: Viewport,
+    ghosts: Circles,
+}
+
+impl Ghosts {
+    pub fn new(
+        ComponentInit {
+            device,
+            queue: _,
+            format,
+            globals,
+            shader_composer,
+            config,
+            state: _,
+            viewport_projection,
+            screen_resolution: _,
+        }: ComponentInit,
+    ) -> Self {
+        let viewport = Viewport::new(viewport_projection, device);
+
+        Self {
+            ghosts: Circles::new(
+                device,
+                format,
+                globals,
+                &viewport,
+                shader_composer,
+                &get_ghosts(config),
+            ),
+            viewport,
+        }
+    }
+}
+
+impl Drawable for Ghosts {
+    /// Draws these [Ghosts].
+    ///
+    /// May overwrite bind groups.
+    /// Will never call `rebind`, as [Ghosts] only overwrites its own groups.
+    #[inline]
+    fn draw<const REBIND: bool>(
+        &self,
+        render_pass: &mut RenderPass<'_>,
+        _rebind: impl Fn(&mut RenderPass),
+    ) {
+        self.viewport.bind(render_pass);
+        self.ghosts.draw(render_pass);
+    }
+}
+
+impl Updatable for Ghosts {
+    fn update(
+        &mut self,
+        _updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        _config: &Config,
+        _state: &State,
+    ) {
+        // Nothing depends on state
+    }
+
+    fn update_full(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        config: &Config,
+        _state: &State,
+        viewport_projection: ViewportProjection,
+    ) {
+        self.viewport.update(updater, viewport_projection);
+        self.ghosts.update(updater, &get_ghosts(config));
+    }
+}
+
+/// Gets the circles marking the final positions of the atoms (if ghosts are configured)
+fn get_ghosts(config: &Config) -> Vec<CircleSpec> {
+    let Some(ghost) = &config.atoms.ghost else {
+        return Vec::new();
+    };
+    ghost
+        .positions
+        .iter()
+        .map(|position| CircleSpec {
+            center: (*position).into(),
+            radius: ghost.radius,
+            radius_inner: 0.,
+            color: ghost.color,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use naviz_state::config::GhostConfig;
+
+    use super::*;
+
+    /// One ghost should be drawn per configured position.
+    #[test]
+    fn ghost_per_position() {
+        let mut config = Config::example();
+        assert!(get_ghosts(&config).is_empty(), "Ghosts without config");
+
+        config.atoms.ghost = Some(GhostConfig {
+            radius: 3.,
+            color: [0, 0, 0, 64],
+            positions: vec![(1., 2.), (3., 4.)],
+        });
+        let ghosts = get_ghosts(&config);
+        assert_eq!(ghosts.len(), 2);
+        assert_eq!(ghosts[1].center, [3., 4.]);
+        assert_eq!(ghosts[1].radius, 3.);
+    }
+}
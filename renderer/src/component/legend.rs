@@ -6,13 +6,14 @@ use wgpu::{Device, Queue, RenderPass};
 
 use crate::{
     buffer_updater::BufferUpdater,
-    component::drawable::Drawable,
+    component::drawable::{Drawable, Fadable},
+    quality::RenderQuality,
     viewport::{Viewport, ViewportProjection},
 };
 
 use super::{
     primitive::{
-        circles::{CircleSpec, Circles},
+        shapes::{ShapeSpec, Shapes},
         text::{Alignment, HAlignment, Text, TextSpec, VAlignment},
     },
     updatable::Updatable,
@@ -21,11 +22,11 @@ use super::{
 
 /// A component to draw the legend:
 /// - A heading per block
-/// - Entries, with an optional colored circle
+/// - Entries, with an optional colored shape
 pub struct Legend {
     viewport: Viewport,
     text: Text,
-    colors: Circles,
+    colors: Shapes,
 }
 
 impl Legend {
@@ -47,7 +48,7 @@ impl Legend {
 
         Self {
             text: Text::new(device, queue, format, text, screen_resolution),
-            colors: Circles::new(device, format, globals, &viewport, shader_composer, &colors),
+            colors: Shapes::new(device, format, globals, &viewport, shader_composer, &colors),
             viewport,
         }
     }
@@ -62,6 +63,11 @@ impl Legend {
         self.text
             .update_viewport((device, queue), screen_resolution);
     }
+
+    /// Sets the [RenderQuality] of this [Legend]
+    pub fn set_quality(&mut self, device: &Device, queue: &Queue, quality: RenderQuality) {
+        self.text.set_quality((device, queue), quality);
+    }
 }
 
 impl Drawable for Legend {
@@ -80,6 +86,19 @@ impl Drawable for Legend {
     }
 }
 
+impl Fadable for Legend {
+    fn set_opacity(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        opacity: f32,
+    ) {
+        self.viewport.set_opacity(updater, opacity);
+        self.text.set_opacity((device, queue), opacity);
+    }
+}
+
 impl Updatable for Legend {
     fn update(
         &mut self,
@@ -112,8 +131,8 @@ impl Updatable for Legend {
 struct LegendSpec<'a, TextIterator: IntoIterator<Item = (&'a str, (f32, f32), Alignment)>> {
     /// The legend text to draw
     text: TextSpec<'a, TextIterator>,
-    /// The circles representing the colors to the left of the text
-    colors: Vec<CircleSpec>,
+    /// The shapes representing the colors to the left of the text
+    colors: Vec<ShapeSpec>,
 }
 
 /// Gets the specs for [Legend] from the passed [State] and [Config].
@@ -156,7 +175,13 @@ fn get_specs(
         y += entry_skip;
 
         // entries
-        for LegendEntry { text, color } in entries.iter() {
+        for LegendEntry {
+            text,
+            color,
+            shape,
+            pattern,
+        } in entries.iter()
+        {
             // text
             texts.push((
                 text.as_str(),
@@ -164,14 +189,17 @@ fn get_specs(
                 Alignment(HAlignment::Left, VAlignment::Center),
             ));
 
-            // colored circle
+            // colored shape
             if let Some(color) = color {
-                colors.push(CircleSpec {
-                    center: [*color_circle_radius, y],
-                    radius: *color_circle_radius,
-                    radius_inner: 0.,
-                    color: *color,
-                });
+                colors.push(
+                    ShapeSpec::new(
+                        [*color_circle_radius, y],
+                        *color_circle_radius,
+                        *color,
+                        *shape,
+                    )
+                    .with_pattern(*pattern),
+                );
             }
             y += entry_skip;
         }
@@ -1,21 +1,24 @@
 use naviz_state::{
     config::{
-        Config, GridConfig, HPosition, LineConfig, MachineConfig, TrapConfig, VPosition, ZoneConfig,
+        Config, GridConfig, HPosition, LineConfig, MachineConfig, TrapConfig, VPosition,
+        ZoneConfig, ZoneShape,
     },
     state::State,
+    Color, Size,
 };
 use wgpu::{Device, Queue, RenderPass};
 
 use crate::{
     buffer_updater::BufferUpdater,
     component::drawable::Drawable,
+    quality::RenderQuality,
     viewport::{Viewport, ViewportProjection, ViewportSource},
 };
 
 use super::{
     primitive::{
         circles::{CircleSpec, Circles},
-        lines::{LineSpec, Lines},
+        lines::{snap_to_pixels, LineSpec, Lines},
         rectangles::{RectangleSpec, Rectangles},
         text::{Alignment, HAlignment, Text, TextSpec, VAlignment},
     },
@@ -33,11 +36,25 @@ pub struct Machine {
     static_traps: Circles,
     coordinate_legend: Text,
     zones: Rectangles,
+    circle_zones: Circles,
+    /// The zone colors of the [State] the zones were last updated with
+    zone_colors: Vec<Color>,
+    /// The [RenderQuality] to draw the grid with
+    quality: RenderQuality,
+    /// The screen resolution, used to snap the grid to pixels
+    screen_resolution: (u32, u32),
 }
 
 /// Padding between the grid and the legend (numbers and labels)
 const LABEL_PADDING: f32 = 12.;
 
+/// Approximate width of a single character (relative to the font size),
+/// used to estimate the width of the legend numbers
+const CHAR_WIDTH: f32 = 0.6;
+
+/// Minimum gap between neighboring legend numbers (relative to the font size)
+const LABEL_GAP: f32 = 0.5;
+
 impl Machine {
     pub fn new(
         ComponentInit {
@@ -47,7 +64,7 @@ impl Machine {
             globals,
             shader_composer,
             config,
-            state: _,
+            state,
             viewport_projection,
             screen_resolution,
         }: ComponentInit,
@@ -58,7 +75,8 @@ impl Machine {
             traps,
             labels,
             zones,
-        } = get_specs(config, viewport_projection, &mut text_buffer);
+            circle_zones,
+        } = get_specs(config, state, viewport_projection, &mut text_buffer);
         let viewport = Viewport::new(viewport_projection, device);
 
         Self {
@@ -73,10 +91,28 @@ impl Machine {
             static_traps: Circles::new(device, format, globals, &viewport, shader_composer, &traps),
             coordinate_legend: Text::new(device, queue, format, labels, screen_resolution),
             zones: Rectangles::new(device, format, globals, &viewport, shader_composer, zones),
+            circle_zones: Circles::new(
+                device,
+                format,
+                globals,
+                &viewport,
+                shader_composer,
+                &circle_zones,
+            ),
+            zone_colors: state.zones.clone(),
             viewport,
+            quality: RenderQuality::default(),
+            screen_resolution,
         }
     }
 
+    /// Sets the [RenderQuality] of this [Machine].
+    /// The grid is only snapped to pixels on the next [full update][Updatable::update_full].
+    pub fn set_quality(&mut self, device: &Device, queue: &Queue, quality: RenderQuality) {
+        self.quality = quality;
+        self.coordinate_legend.set_quality((device, queue), quality);
+    }
+
     /// Updates the viewport resolution of this [Machine]
     pub fn update_viewport(
         &mut self,
@@ -84,6 +120,7 @@ impl Machine {
         queue: &Queue,
         screen_resolution: (u32, u32),
     ) {
+        self.screen_resolution = screen_resolution;
         self.coordinate_legend
             .update_viewport((device, queue), screen_resolution);
     }
@@ -103,6 +140,7 @@ impl Drawable for Machine {
         self.background_grid.draw(render_pass);
         self.static_traps.draw(render_pass);
         self.zones.draw(render_pass);
+        self.circle_zones.draw(render_pass);
         self.coordinate_legend.draw::<REBIND>(render_pass, rebind);
     }
 }
@@ -110,13 +148,20 @@ impl Drawable for Machine {
 impl Updatable for Machine {
     fn update(
         &mut self,
-        _updater: &mut impl BufferUpdater,
+        updater: &mut impl BufferUpdater,
         _device: &Device,
         _queue: &Queue,
-        _config: &Config,
-        _state: &State,
+        config: &Config,
+        state: &State,
     ) {
-        // Nothing depends on state
+        // Only the zone colors depend on state
+        if self.zone_colors == state.zones {
+            return;
+        }
+        let (zones, circle_zones) = get_zone_specs(&config.machine.zones, &state.zones);
+        self.zones.update(updater, zones);
+        self.circle_zones.update(updater, &circle_zones);
+        self.zone_colors.clone_from(&state.zones);
     }
 
     fn update_full(
@@ -125,21 +170,27 @@ impl Updatable for Machine {
         device: &Device,
         queue: &Queue,
         config: &Config,
-        _state: &State,
+        state: &State,
         viewport_projection: ViewportProjection,
     ) {
         self.viewport.update(updater, viewport_projection);
         let mut text_buffer = Vec::new();
         let MachineSpec {
-            lines,
+            mut lines,
             traps,
             labels,
             zones,
-        } = get_specs(config, viewport_projection, &mut text_buffer);
+            circle_zones,
+        } = get_specs(config, state, viewport_projection, &mut text_buffer);
+        if self.quality == RenderQuality::Crisp {
+            snap_to_pixels(&mut lines, viewport_projection, self.screen_resolution);
+        }
         self.background_grid.update(updater, &lines);
         self.static_traps.update(updater, &traps);
         self.coordinate_legend.update((device, queue), labels);
         self.zones.update(updater, zones);
+        self.circle_zones.update(updater, &circle_zones);
+        self.zone_colors.clone_from(&state.zones);
     }
 }
 
@@ -158,13 +209,16 @@ struct MachineSpec<'a, TextIterator: IntoIterator<Item = (&'a str, (f32, f32), A
     traps: Vec<CircleSpec>,
     /// Axis labels (including the numbers)
     labels: TextSpec<'a, TextIterator>,
-    /// Rectangles to draw for the zones
+    /// Rectangles to draw for the rectangular zones
     zones: Vec<RectangleSpec>,
+    /// Circles to draw for the circular zones
+    circle_zones: Vec<CircleSpec>,
 }
 
 /// Gets the specs for [Machine] from the passed [State] and [Config].
 fn get_specs<'a>(
     config: &'a Config,
+    state: &State,
     viewport_projection: ViewportProjection,
     text_buffer: &'a mut Vec<(String, (f32, f32), Alignment)>,
 ) -> MachineSpec<'a, impl IntoIterator<Item = (&'a str, (f32, f32), Alignment)>> {
@@ -175,14 +229,20 @@ fn get_specs<'a>(
 
     let traps = get_trap_specs(traps);
 
-    build_number_labels(grid, text_buffer, viewport_source);
+    build_number_labels(
+        grid,
+        text_buffer,
+        viewport_source,
+        viewport_projection.flip_y,
+    );
     let texts = add_grid_legend(
         grid,
         viewport_source,
+        viewport_projection.flip_y,
         text_buffer.iter().map(|(t, p, a)| (t.as_str(), *p, *a)),
     );
 
-    let zones = get_zone_specs(zones);
+    let (zones, circle_zones) = get_zone_specs(zones, &state.zones);
 
     MachineSpec {
         lines,
@@ -195,6 +255,7 @@ fn get_specs<'a>(
             color: grid.legend.font.color,
         },
         zones,
+        circle_zones,
     }
 }
 
@@ -206,12 +267,10 @@ fn get_grid_lines_specs(grid: &GridConfig, vp: ViewportSource) -> Vec<LineSpec>
         return Vec::new();
     }
 
-    // The viewport-edges clamped to the grid/legend steps
-    let vp_left_grid = clamp_to(vp.left(), grid.step.0);
-    let vp_top_grid = clamp_to(vp.top(), grid.step.1);
+    let (xs, ys) = grid_positions(grid, grid.step, vp);
 
     // create LineSpecs; first x, then y
-    range_f32(vp_left_grid, vp.right(), grid.step.0)
+    xs.into_iter()
         .map(|x| LineSpec {
             start: [x, vp.top()],
             end: [x, vp.bottom()],
@@ -220,19 +279,54 @@ fn get_grid_lines_specs(grid: &GridConfig, vp: ViewportSource) -> Vec<LineSpec>
             segment_length: grid.line.segment_length,
             duty: grid.line.duty,
         })
-        .chain(
-            range_f32(vp_top_grid, vp.bottom(), grid.step.1).map(|y| LineSpec {
-                start: [vp.left(), y],
-                end: [vp.right(), y],
-                color: grid.line.color,
-                width: grid.line.width,
-                segment_length: grid.line.segment_length,
-                duty: grid.line.duty,
-            }),
-        )
+        .chain(ys.into_iter().map(|y| LineSpec {
+            start: [vp.left(), y],
+            end: [vp.right(), y],
+            color: grid.line.color,
+            width: grid.line.width,
+            segment_length: grid.line.segment_length,
+            duty: grid.line.duty,
+        }))
         .collect()
 }
 
+/// Gets the x- and y-positions of the grid inside the [ViewportSource].
+/// Uses the explicit [ticks][GridConfig::ticks] if set,
+/// or positions spaced by `step` and aligned to the [origin][GridConfig::origin] otherwise.
+fn grid_positions(grid: &GridConfig, step: Size, vp: ViewportSource) -> (Vec<f32>, Vec<f32>) {
+    /// Gets the positions along a single axis
+    fn axis(explicit: Option<&[f32]>, origin: f32, step: f32, start: f32, end: f32) -> Vec<f32> {
+        match explicit {
+            Some(ticks) => ticks
+                .iter()
+                .copied()
+                .filter(|t| (start..=end).contains(t))
+                .collect(),
+            None => {
+                // First position inside the viewport
+                // (with some tolerance for floating-point errors of fractional steps)
+                let first = origin + clamp_to(start - origin, step);
+                let first = if start - first > step * 1e-3 {
+                    first + step
+                } else {
+                    first
+                };
+                range_f32(first, end, step).collect()
+            }
+        }
+    }
+
+    let (explicit_x, explicit_y) = grid
+        .ticks
+        .as_ref()
+        .map(|(x, y)| (Some(x.as_slice()), Some(y.as_slice())))
+        .unwrap_or_default();
+    (
+        axis(explicit_x, grid.origin.0, step.0, vp.left(), vp.right()),
+        axis(explicit_y, grid.origin.1, step.1, vp.top(), vp.bottom()),
+    )
+}
+
 /// Create the [CircleSpec]s for the static traps
 fn get_trap_specs(traps: &TrapConfig) -> Vec<CircleSpec> {
     traps
@@ -248,10 +342,12 @@ fn get_trap_specs(traps: &TrapConfig) -> Vec<CircleSpec> {
 }
 
 /// Fill the `text_buffer` with the strings for the legend numbers in x- and y-direction.
+/// `flip_y` is the [flip][ViewportProjection::flip_y] of the viewport.
 fn build_number_labels(
     grid: &GridConfig,
     text_buffer: &mut Vec<(String, (f32, f32), Alignment)>,
     vp: ViewportSource,
+    flip_y: bool,
 ) {
     if !grid.legend.display_numbers {
         // Don't display number labels
@@ -259,47 +355,150 @@ fn build_number_labels(
         return;
     }
 
-    // The viewport-edges clamped to the grid/legend steps
-    let vp_left_legend = clamp_to(vp.left(), grid.legend.step.0);
-    let vp_top_legend = clamp_to(vp.top(), grid.legend.step.1);
+    let font_size = grid.legend.font.size;
+    let (xs, ys) = grid_positions(grid, grid.legend.step, vp);
+    let (numbers_x, numbers_y) = if grid.ticks.is_some() {
+        // Explicit ticks are irregularly spaced: label each tick
+        let label = |values: Vec<f32>| {
+            values
+                .into_iter()
+                .map(|v| (v, format_number(v, grid.legend.precision)))
+                .collect::<Vec<_>>()
+        };
+        (label(xs), label(ys))
+    } else {
+        (
+            thin_numbers(
+                xs,
+                grid.origin.0,
+                grid.legend.step.0,
+                grid.legend.precision,
+                font_size,
+                |n| n.chars().count() as f32 * font_size * CHAR_WIDTH,
+            ),
+            thin_numbers(
+                ys,
+                grid.origin.1,
+                grid.legend.step.1,
+                grid.legend.precision,
+                font_size,
+                |_| font_size,
+            ),
+        )
+    };
 
-    *text_buffer = range_f32(vp_left_legend, vp.right(), grid.legend.step.0)
-        .map(|x| {
+    *text_buffer = numbers_x
+        .into_iter()
+        .map(|(x, text)| {
             (
-                format!("{x}"),
+                text,
                 (
                     x,
-                    grid.legend
-                        .position
-                        .0
+                    source_v_position(grid.legend.position.0, flip_y)
                         .get(vp.top() - LABEL_PADDING, vp.bottom() + LABEL_PADDING),
                 ),
                 Alignment(HAlignment::Center, get_v_alignment(grid.legend.position.0)),
             )
         })
-        .chain(
-            range_f32(vp_top_legend, vp.bottom(), grid.legend.step.1).map(|y| {
+        .chain(numbers_y.into_iter().map(|(y, text)| {
+            (
+                text,
                 (
-                    format!("{y}"),
-                    (
-                        grid.legend
-                            .position
-                            .1
-                            .get(vp.left() - LABEL_PADDING, vp.right() + LABEL_PADDING),
-                        y,
-                    ),
-                    Alignment(get_h_alignment(grid.legend.position.1), VAlignment::Center),
-                )
-            }),
-        )
+                    grid.legend
+                        .position
+                        .1
+                        .get(vp.left() - LABEL_PADDING, vp.right() + LABEL_PADDING),
+                    y,
+                ),
+                Alignment(get_h_alignment(grid.legend.position.1), VAlignment::Center),
+            )
+        }))
+        .collect();
+}
+
+/// Formats the legend numbers at the passed `values` (which are spaced by `step`)
+/// to `precision` significant digits
+/// and skips numbers such that neighboring labels do not overlap.
+///
+/// `extent` returns the size of a formatted number along the axis.
+/// Only every n-th number is kept, where n is a [nice stride][nice_stride].
+/// Numbers are selected by their position on the grid (relative to `origin`),
+/// so the same numbers stay visible when the viewport moves.
+fn thin_numbers(
+    values: impl IntoIterator<Item = f32>,
+    origin: f32,
+    step: f32,
+    precision: usize,
+    font_size: f32,
+    extent: impl Fn(&str) -> f32,
+) -> Vec<(f32, String)> {
+    let numbers: Vec<_> = values
+        .into_iter()
+        .map(|v| {
+            // Format the exact grid position to drop accumulated floating-point errors
+            let exact = origin + ((v - origin) / step).round() * step;
+            (v, format_number(exact, precision))
+        })
         .collect();
+    let max_extent = numbers.iter().map(|(_, n)| extent(n)).fold(0., f32::max);
+    let stride = nice_stride(step, max_extent + font_size * LABEL_GAP) as i64;
+
+    numbers
+        .into_iter()
+        .filter(|(v, _)| (((v - origin) / step).round() as i64).rem_euclid(stride) == 0)
+        .collect()
+}
+
+/// Formats the passed `value` rounded to `precision` significant digits
+/// (at least one).
+/// Digits before the decimal point are never dropped
+/// and trailing zeros of the decimals are removed.
+fn format_number(value: f32, precision: usize) -> String {
+    if value == 0. || !value.is_finite() {
+        // Also prevents printing `-0`
+        return format!("{}", if value == 0. { 0. } else { value });
+    }
+
+    let magnitude = value.abs().log10().floor() as i64;
+    let decimals = (precision.max(1) as i64 - 1 - magnitude).max(0) as usize;
+    let formatted = format!("{value:.decimals$}");
+    let formatted = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+    match formatted {
+        "-0" => "0".to_owned(),
+        formatted => formatted.to_owned(),
+    }
+}
+
+/// Gets the smallest "nice" stride (`1`, `2`, `5`, `10`, `20`, `50`, ...)
+/// such that `stride * spacing` is at least `min_distance`.
+fn nice_stride(spacing: f32, min_distance: f32) -> u64 {
+    if spacing <= 0. || !spacing.is_finite() || !min_distance.is_finite() {
+        return 1;
+    }
+
+    let mut magnitude = 1;
+    loop {
+        for factor in [1, 2, 5] {
+            let stride = factor * magnitude;
+            if stride as f32 * spacing >= min_distance {
+                return stride;
+            }
+        }
+        magnitude *= 10;
+    }
 }
 
-/// Add the grid legends to the `texts`
+/// Add the grid legends to the `texts`.
+/// `flip_y` is the [flip][ViewportProjection::flip_y] of the viewport.
 #[inline]
 fn add_grid_legend<'a>(
     grid: &'a GridConfig,
     vp: ViewportSource,
+    flip_y: bool,
     texts: impl IntoIterator<Item = (&'a str, (f32, f32), Alignment)>,
 ) -> impl Iterator<Item = (&'a str, (f32, f32), Alignment)> {
     let texts = texts.into_iter();
@@ -320,7 +519,7 @@ fn add_grid_legend<'a>(
                     .1
                     .inverse()
                     .get(vp.left() - LABEL_PADDING, vp.right() + LABEL_PADDING),
-                grid.legend.position.0.get(vp.top(), vp.bottom()),
+                source_v_position(grid.legend.position.0, flip_y).get(vp.top(), vp.bottom()),
             ),
             Alignment(
                 get_h_alignment(grid.legend.position.1.inverse()),
@@ -331,10 +530,7 @@ fn add_grid_legend<'a>(
             grid.legend.labels.1.as_str(),
             (
                 grid.legend.position.1.get(vp.left(), vp.right()),
-                grid.legend
-                    .position
-                    .0
-                    .inverse()
+                source_v_position(grid.legend.position.0.inverse(), flip_y)
                     .get(vp.top() - LABEL_PADDING, vp.bottom() + LABEL_PADDING),
             ),
             Alignment(
@@ -345,35 +541,81 @@ fn add_grid_legend<'a>(
     ])
 }
 
-/// Build the [RectangleSpec]s for the zones
-fn get_zone_specs(zones: &[ZoneConfig]) -> Vec<RectangleSpec> {
-    zones
-        .iter()
-        .copied()
-        .map(
-            |ZoneConfig {
-                 start,
-                 size,
-                 line:
-                     LineConfig {
-                         width,
-                         segment_length,
-                         duty,
-                         color,
-                     },
-             }| RectangleSpec {
+/// Build the [RectangleSpec]s for the rectangular zones
+/// and the [CircleSpec]s for the circular zones.
+/// Circular zones are always drawn with a solid line.
+/// The line colors are overwritten by the passed `colors` (by index) if present.
+fn get_zone_specs(zones: &[ZoneConfig], colors: &[Color]) -> (Vec<RectangleSpec>, Vec<CircleSpec>) {
+    let mut rectangles = Vec::new();
+    let mut fills = Vec::new();
+    let mut borders = Vec::new();
+    for (
+        idx,
+        ZoneConfig {
+            start,
+            size,
+            shape,
+            line:
+                LineConfig {
+                    width,
+                    segment_length,
+                    duty,
+                    color,
+                },
+            fill,
+        },
+    ) in zones.iter().copied().enumerate()
+    {
+        let color = colors.get(idx).copied().unwrap_or(color);
+        match shape {
+            ZoneShape::Rectangle { rotation } => rectangles.push(RectangleSpec {
                 start: start.into(),
                 size: size.into(),
                 color,
+                fill,
                 width,
                 duty,
                 segment_length,
-            },
-        )
-        .collect()
+                rotation,
+            }),
+            ZoneShape::Circle => {
+                let radius = size.0.abs() / 2.;
+                let center = [start.0 + size.0 / 2., start.1 + size.1 / 2.];
+                if let Some(fill) = fill {
+                    fills.push(CircleSpec {
+                        center,
+                        radius,
+                        radius_inner: 0.,
+                        color: fill,
+                    });
+                }
+                borders.push(CircleSpec {
+                    center,
+                    radius: radius + width / 2.,
+                    radius_inner: (radius - width / 2.).max(0.),
+                    color,
+                });
+            }
+        }
+    }
+    // Fills are drawn beneath all borders
+    fills.extend(borders);
+    (rectangles, fills)
+}
+
+/// Gets the [VPosition] in the source-coordinates of the viewport
+/// for the passed [VPosition] on the screen.
+/// The source is mirrored vertically if `flip_y` is set (see [ViewportProjection::flip_y]).
+#[inline]
+fn source_v_position(p: VPosition, flip_y: bool) -> VPosition {
+    if flip_y {
+        p.inverse()
+    } else {
+        p
+    }
 }
 
-/// Gets the [VAlignment] based on the passed [VPosition]
+/// Gets the [VAlignment] based on the passed [VPosition] on the screen
 #[inline]
 fn get_v_alignment(p: VPosition) -> VAlignment {
     p.get(VAlignment::Bottom, VAlignment::Top)
@@ -386,10 +628,10 @@ fn get_h_alignment(p: HPosition) -> HAlignment {
 }
 
 /// Clamps `num` to be in steps of `step`.
-/// Will always round down.
+/// Will always round down (also for negative `num`).
 #[inline]
 fn clamp_to(num: f32, step: f32) -> f32 {
-    num - num % step
+    num - num.rem_euclid(step)
 }
 
 #[cfg(test)]
@@ -413,6 +655,7 @@ mod test {
                 width: 1.,
                 height: 1.,
             },
+            flip_y: false,
         }
     }
 
@@ -423,7 +666,12 @@ mod test {
         let config = Config::example();
         let viewport_projection = viewport_identity();
         let mut text_buffer = Vec::new();
-        let specs = get_specs(&config, viewport_projection, &mut text_buffer);
+        let specs = get_specs(
+            &config,
+            &State::default(),
+            viewport_projection,
+            &mut text_buffer,
+        );
 
         assert!(!specs.lines.is_empty(), "Did not produce any lines");
         assert_eq!(
@@ -454,7 +702,12 @@ mod test {
 
         let viewport_projection = viewport_identity();
         let mut text_buffer = Vec::new();
-        let specs = get_specs(&config, viewport_projection, &mut text_buffer);
+        let specs = get_specs(
+            &config,
+            &State::default(),
+            viewport_projection,
+            &mut text_buffer,
+        );
 
         assert!(specs.lines.is_empty(), "Should not produce any lines");
         assert_eq!(
@@ -472,4 +725,321 @@ mod test {
             "Should not produce any text specs"
         );
     }
+
+    /// Viewport covering `0..size` in both directions
+    fn viewport(size: f32) -> ViewportSource {
+        ViewportSource {
+            x: 0.,
+            y: 0.,
+            width: size,
+            height: size,
+        }
+    }
+
+    /// Strides should snap to nice multiples.
+    #[test]
+    fn nice_strides() {
+        assert_eq!(nice_stride(10., 5.), 1);
+        assert_eq!(nice_stride(1., 1.5), 2);
+        assert_eq!(nice_stride(1., 3.), 5);
+        assert_eq!(nice_stride(1., 7.), 10);
+        assert_eq!(nice_stride(1., 150.), 200);
+        assert_eq!(nice_stride(0., 1.), 1);
+    }
+
+    /// A tight grid with a large font should skip numbers.
+    #[test]
+    fn thin_tight_grid() {
+        let mut grid = Config::example().machine.grid;
+        grid.legend.display_numbers = true;
+        grid.legend.step = (1., 1.);
+        grid.legend.font.size = 10.;
+
+        let mut text_buffer = Vec::new();
+        build_number_labels(&grid, &mut text_buffer, viewport(100.), false);
+
+        let ticks = 2 * range_f32(0., 100., 1.).count();
+        assert!(!text_buffer.is_empty(), "Did not produce any numbers");
+        assert!(
+            text_buffer.len() < ticks,
+            "Did not skip any numbers ({} numbers for {ticks} ticks)",
+            text_buffer.len()
+        );
+        assert!(text_buffer.iter().any(|(t, _, _)| t == "0"));
+        assert!(text_buffer
+            .iter()
+            .all(|(t, _, _)| t.parse::<f32>().unwrap() % 10. == 0.));
+    }
+
+    /// With a flipped y-axis, the legend should stay on the configured side of the screen,
+    /// i.e., on the mirrored side in the source-coordinates.
+    #[test]
+    fn legend_flip_y() {
+        let mut grid = Config::example().machine.grid;
+        grid.legend.display_numbers = true;
+        grid.legend.display_labels = true;
+        grid.legend.step = (50., 50.);
+        grid.legend.position = (VPosition::Top, HPosition::Left);
+        let vp = viewport(100.);
+
+        // y-coordinate and vertical alignment of the first x-number and of the y-axis label
+        let placement = |flip_y| {
+            let mut text_buffer = Vec::new();
+            build_number_labels(&grid, &mut text_buffer, vp, flip_y);
+            let number = text_buffer[0].clone();
+            let texts: Vec<_> = add_grid_legend(&grid, vp, flip_y, Vec::new()).collect();
+            let label = texts[1];
+            ((number.1 .1, number.2 .1), (label.1 .1, label.2 .1))
+        };
+
+        let (number, label) = placement(false);
+        assert_eq!(number.0, vp.top() - LABEL_PADDING);
+        assert!(matches!(number.1, VAlignment::Bottom));
+        assert_eq!(label.0, vp.bottom() + LABEL_PADDING);
+        assert!(matches!(label.1, VAlignment::Top));
+
+        let (number, label) = placement(true);
+        assert_eq!(number.0, vp.bottom() + LABEL_PADDING);
+        assert!(matches!(number.1, VAlignment::Bottom));
+        assert_eq!(label.0, vp.top() - LABEL_PADDING);
+        assert!(matches!(label.1, VAlignment::Top));
+    }
+
+    /// Thinned numbers should be selected relative to the grid origin.
+    #[test]
+    fn thin_origin() {
+        let mut grid = Config::example().machine.grid;
+        grid.legend.display_numbers = true;
+        grid.legend.step = (1., 1.);
+        grid.legend.font.size = 10.;
+        grid.origin = (3.5, 3.5);
+
+        let mut text_buffer = Vec::new();
+        build_number_labels(&grid, &mut text_buffer, viewport(100.), false);
+
+        assert!(!text_buffer.is_empty(), "Did not produce any numbers");
+        assert!(text_buffer.iter().any(|(t, _, _)| t == "3.5"));
+        assert!(text_buffer
+            .iter()
+            .all(|(t, _, _)| (t.parse::<f32>().unwrap() - 3.5) % 10. == 0.));
+    }
+
+    /// A coarse grid should keep all numbers.
+    #[test]
+    fn thin_coarse_grid() {
+        let mut grid = Config::example().machine.grid;
+        grid.legend.display_numbers = true;
+        grid.legend.step = (100., 100.);
+        grid.legend.font.size = 10.;
+
+        let mut text_buffer = Vec::new();
+        build_number_labels(&grid, &mut text_buffer, viewport(1000.), false);
+
+        assert_eq!(text_buffer.len(), 2 * range_f32(0., 1000., 100.).count());
+    }
+
+    /// Explicit ticks should produce exactly the lines inside the viewport.
+    #[test]
+    fn explicit_ticks() {
+        let mut grid = Config::example().machine.grid;
+        grid.ticks = Some((vec![-5., 0., 1.5, 7., 200.], vec![3.]));
+
+        let lines = get_grid_lines_specs(&grid, viewport(100.));
+        let xs: Vec<_> = lines
+            .iter()
+            .filter(|l| l.start[0] == l.end[0])
+            .map(|l| l.start[0])
+            .collect();
+        let ys: Vec<_> = lines
+            .iter()
+            .filter(|l| l.start[1] == l.end[1])
+            .map(|l| l.start[1])
+            .collect();
+        assert_eq!(xs, vec![0., 1.5, 7.]);
+        assert_eq!(ys, vec![3.]);
+
+        grid.legend.display_numbers = true;
+        let mut text_buffer = Vec::new();
+        build_number_labels(&grid, &mut text_buffer, viewport(100.), false);
+        let numbers: Vec<_> = text_buffer.iter().map(|(t, _, _)| t.as_str()).collect();
+        assert_eq!(numbers, vec!["0", "1.5", "7", "3"]);
+    }
+
+    /// Uniform ticks should be aligned to the origin.
+    #[test]
+    fn ticks_origin() {
+        let mut grid = Config::example().machine.grid;
+        grid.step = (10., 10.);
+        grid.origin = (3., 5.);
+
+        let (xs, ys) = grid_positions(&grid, grid.step, viewport(30.));
+        assert_eq!(xs, vec![3., 13., 23.]);
+        assert_eq!(ys, vec![5., 15., 25.]);
+    }
+
+    /// Viewports spanning negative coordinates should start at the first tick inside the viewport.
+    #[test]
+    fn ticks_negative_viewport() {
+        let mut grid = Config::example().machine.grid;
+        grid.step = (10., 10.);
+        grid.origin = (0., 0.);
+        let vp = ViewportSource {
+            x: -25.,
+            y: -30.,
+            width: 50.,
+            height: 40.,
+        };
+
+        let (xs, ys) = grid_positions(&grid, grid.step, vp);
+        assert_eq!(xs, vec![-20., -10., 0., 10., 20.]);
+        assert_eq!(ys, vec![-30., -20., -10., 0., 10.]);
+    }
+
+    /// Numbers should be rounded to the configured precision without trailing zeros.
+    #[test]
+    fn number_formatting() {
+        assert_eq!(format_number(0., 3), "0");
+        assert_eq!(format_number(-0., 3), "0");
+        assert_eq!(format_number(1. / 3., 3), "0.333");
+        assert_eq!(format_number(-5. / 3., 3), "-1.67");
+        assert_eq!(format_number(2.5, 3), "2.5");
+        assert_eq!(format_number(-40., 3), "-40");
+        assert_eq!(format_number(12345., 2), "12345");
+        assert_eq!(format_number(-0.0001, 2), "-0.0001");
+        assert_eq!(format_number(1. / 3., 0), "0.3");
+    }
+
+    /// Fractional steps on a viewport spanning negative coordinates
+    /// should produce cleanly formatted numbers inside the viewport.
+    #[test]
+    fn fractional_negative_numbers() {
+        let mut grid = Config::example().machine.grid;
+        grid.legend.display_numbers = true;
+        grid.legend.step = (1. / 3., 1. / 3.);
+        grid.legend.font.size = 0.01;
+        grid.legend.precision = 3;
+        grid.origin = (0., 0.);
+        let vp = ViewportSource {
+            x: -2.1,
+            y: -1.1,
+            width: 4.2,
+            height: 2.2,
+        };
+
+        let mut text_buffer = Vec::new();
+        build_number_labels(&grid, &mut text_buffer, vp, false);
+
+        // Numbers along the x-axis are vertically centered
+        let xs: Vec<_> = text_buffer
+            .iter()
+            .filter(|(_, _, Alignment(h, _))| matches!(h, HAlignment::Center))
+            .collect();
+        let numbers: Vec<_> = xs.iter().map(|(t, _, _)| t.as_str()).collect();
+        assert_eq!(
+            numbers,
+            vec![
+                "-2", "-1.67", "-1.33", "-1", "-0.667", "-0.333", "0", "0.333", "0.667", "1",
+                "1.33", "1.67", "2"
+            ]
+        );
+        for (text, (x, _), _) in xs {
+            assert!(
+                (vp.left() - 1e-4..=vp.right() + 1e-4).contains(x),
+                "Number {text} placed outside of viewport at {x}"
+            );
+            assert!(
+                (x - text.parse::<f32>().unwrap()).abs() < 1e-2,
+                "Number {text} placed at {x}"
+            );
+        }
+    }
+
+    /// Circular zones should be drawn as circles (fill beneath the border) instead of rectangles.
+    #[test]
+    fn circle_zone_specs() {
+        let mut zone = Config::example().machine.zones[0];
+        zone.start = (0., 0.);
+        zone.size = (10., 10.);
+        zone.shape = ZoneShape::Circle;
+        zone.fill = Some([0, 0, 255, 32]);
+
+        let (rectangles, circles) = get_zone_specs(&[zone], &[]);
+        assert!(rectangles.is_empty(), "Circular zone produced rectangles");
+        assert_eq!(circles.len(), 2, "Expected fill and border");
+        assert_eq!(circles[0].color, [0, 0, 255, 32], "Fill is not drawn first");
+        assert_eq!(circles[0].center, [5., 5.]);
+        assert_eq!(circles[0].radius, 5.);
+    }
+
+    /// Zone colors of the state should replace the configured line colors.
+    #[test]
+    fn zone_colors_from_state() {
+        let mut zone = Config::example().machine.zones[0];
+        zone.shape = ZoneShape::Rectangle { rotation: 0. };
+        zone.line.color = [0, 0, 0, 255];
+
+        let (rectangles, _) = get_zone_specs(&[zone], &[]);
+        assert_eq!(rectangles[0].color, [0, 0, 0, 255]);
+
+        let (rectangles, _) = get_zone_specs(&[zone], &[[255, 204, 0, 255]]);
+        assert_eq!(
+            rectangles[0].color,
+            [255, 204, 0, 255],
+            "Zone color was not overwritten"
+        );
+    }
+
+    /// In [RenderQuality::Crisp], the vertices of horizontal grid lines
+    /// should lie on whole pixels.
+    #[test]
+    fn crisp_grid_integer_aligned() {
+        let config = Config::example();
+        let viewport_projection = ViewportProjection {
+            source: ViewportSource {
+                x: -3.,
+                y: -7.,
+                width: 113.,
+                height: 131.,
+            },
+            target: ViewportTarget::default(),
+            flip_y: false,
+        };
+        let screen_resolution = (317, 241);
+        let mut text_buffer = Vec::new();
+        let mut lines = get_specs(
+            &config,
+            &State::default(),
+            viewport_projection,
+            &mut text_buffer,
+        )
+        .lines;
+        snap_to_pixels(&mut lines, viewport_projection, screen_resolution);
+
+        let mat: glam::Mat4 = viewport_projection.into();
+        let to_pixels = |[x, y]: [f32; 2]| {
+            let p = mat.transform_point3(glam::Vec3::new(x, y, 0.));
+            [
+                (p.x + 1.) / 2. * screen_resolution.0 as f32,
+                (1. - p.y) / 2. * screen_resolution.1 as f32,
+            ]
+        };
+
+        let horizontal: Vec<_> = lines.iter().filter(|l| l.start[1] == l.end[1]).collect();
+        assert!(!horizontal.is_empty(), "No horizontal grid lines");
+        for line in horizontal {
+            let half = line.width / 2.;
+            for [x, y] in [
+                [line.start[0], line.start[1] - half],
+                [line.start[0], line.start[1] + half],
+                [line.end[0], line.end[1] - half],
+                [line.end[0], line.end[1] + half],
+            ] {
+                let [px, py] = to_pixels([x, y]);
+                assert!(
+                    (px - px.round()).abs() < 1e-2 && (py - py.round()).abs() < 1e-2,
+                    "Vertex ({px}, {py}) of {line:?} is not on a whole pixel"
+                );
+            }
+        }
+    }
 }
@@ -1,4 +1,5 @@
 pub mod circles;
 pub mod lines;
 pub mod rectangles;
+pub mod shapes;
 pub mod text;
@@ -1,5 +1,5 @@
 #import util::to_color;
-#import viewport::viewport_projection;
+#import viewport::{viewport_projection, with_opacity};
 
 struct CircleSpec {
 	@location(0) center: vec2<f32>,
@@ -51,6 +51,6 @@ fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
 	if dist > in.radius || dist < in.radius_inner { // not in draw region
 		return vec4<f32>(1.0, 1.0, 1.0, 0.0);
 	} else { // in draw region
-		return to_color(in.color);
+		return with_opacity(to_color(in.color));
 	}
 }
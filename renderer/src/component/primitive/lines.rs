@@ -1,16 +1,16 @@
-use std::ops::{Deref, DerefMut};
-
+use glam::{Mat4, Vec3};
 use naga_oil::compose::Composer;
-use wgpu::{BufferAddress, Device, TextureFormat, VertexAttribute, VertexFormat};
+use wgpu::{BufferAddress, Device, RenderPass, TextureFormat, VertexAttribute, VertexFormat};
 
 use crate::{
+    buffer_updater::BufferUpdater,
     component::{Component, ComponentSpec},
     globals::Globals,
-    viewport::Viewport,
+    viewport::{Viewport, ViewportProjection},
 };
 
 /// A [Component] which draws one or multiple lines to the screen
-pub struct Lines(Component<LineSpec>);
+pub struct Lines(Component<LineInstance>);
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
@@ -29,6 +29,37 @@ pub struct LineSpec {
     pub duty: f32,
 }
 
+/// A [LineSpec] as passed to the GPU,
+/// extended by the dash-phase at the start of the line.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineInstance {
+    spec: LineSpec,
+    /// The distance of the start of the line along the line's direction,
+    /// measured from the projection of the origin onto the line.
+    /// Dashes are placed relative to this origin,
+    /// so that dashes of collinear and parallel lines line up.
+    phase: f32,
+}
+
+impl From<&LineSpec> for LineInstance {
+    fn from(spec: &LineSpec) -> Self {
+        let direction = [spec.end[0] - spec.start[0], spec.end[1] - spec.start[1]];
+        let length = direction[0].hypot(direction[1]);
+        let phase = if length > 0. {
+            (direction[0] * spec.start[0] + direction[1] * spec.start[1]) / length
+        } else {
+            0.
+        };
+        Self { spec: *spec, phase }
+    }
+}
+
+/// Converts the passed [LineSpec]s to [LineInstance]s
+fn to_instances(lines: &[LineSpec]) -> Vec<LineInstance> {
+    lines.iter().map(Into::into).collect()
+}
+
 impl Lines {
     /// Create new [Lines]
     pub fn new(
@@ -46,7 +77,7 @@ impl Lines {
             viewport,
             shader_composer,
             ComponentSpec {
-                specs: lines,
+                specs: &to_instances(lines),
                 attributes: &[
                     VertexAttribute {
                         offset: 0,
@@ -86,6 +117,11 @@ impl Lines {
                         shader_location: 5,
                         format: VertexFormat::Float32,
                     },
+                    VertexAttribute {
+                        offset: size_of::<LineSpec>() as BufferAddress,
+                        shader_location: 6,
+                        format: VertexFormat::Float32,
+                    },
                 ],
                 shader_source: include_str!("lines.wgsl"),
                 shader_path: "lines.wgsl",
@@ -93,17 +129,138 @@ impl Lines {
             },
         ))
     }
+
+    /// Update this component to have the new `spec`
+    pub fn update<U: BufferUpdater>(&mut self, updater: &mut U, spec: &[LineSpec]) {
+        self.0.update(updater, &to_instances(spec));
+    }
+
+    /// Draws this component
+    #[inline]
+    pub fn draw(&self, render_pass: &mut RenderPass<'_>) {
+        self.0.draw(render_pass);
+    }
 }
 
-impl Deref for Lines {
-    type Target = Component<LineSpec>;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+/// Snaps the passed [LineSpec]s to the pixel-grid of a screen
+/// with the passed `screen_resolution` when drawn using the passed [ViewportProjection].
+///
+/// Widths are rounded to whole pixels (at least one pixel).
+/// Endpoints of horizontal and vertical lines are moved
+/// such that the edges of the drawn line lie on pixel boundaries
+/// (i.e., lines of odd widths are centered on pixel centers);
+/// endpoints of other lines are rounded to whole pixels.
+pub fn snap_to_pixels(
+    lines: &mut [LineSpec],
+    viewport_projection: ViewportProjection,
+    screen_resolution: (u32, u32),
+) {
+    let mat: Mat4 = viewport_projection.into();
+    let inverse = mat.inverse();
+    let (width, height) = (screen_resolution.0 as f32, screen_resolution.1 as f32);
+    // Pixels per content-unit in x- and y-direction
+    let scale_x = (mat.x_axis.x * width / 2.).abs();
+    let scale_y = (mat.y_axis.y * height / 2.).abs();
+
+    let to_pixels = |[x, y]: [f32; 2]| {
+        let p = mat.transform_point3(Vec3::new(x, y, 0.));
+        [(p.x + 1.) / 2. * width, (1. - p.y) / 2. * height]
+    };
+    let from_pixels = |[x, y]: [f32; 2]| {
+        let p = inverse.transform_point3(Vec3::new(x / width * 2. - 1., 1. - y / height * 2., 0.));
+        [p.x, p.y]
+    };
+
+    for line in lines {
+        let start = to_pixels(line.start);
+        let end = to_pixels(line.end);
+        let horizontal = (start[1] - end[1]).abs() < f32::EPSILON;
+        let vertical = (start[0] - end[0]).abs() < f32::EPSILON;
+
+        let scale = match (horizontal, vertical) {
+            (true, false) => scale_y,
+            (false, true) => scale_x,
+            _ => (scale_x + scale_y) / 2.,
+        };
+        if scale == 0. {
+            continue;
+        }
+        let line_width = (line.width * scale).round().max(1.);
+        // Center such that `center ± line_width / 2` lies on whole pixels
+        let center = |v: f32| (v - line_width / 2.).round() + line_width / 2.;
+
+        let (start, end) = match (horizontal, vertical) {
+            (true, false) => {
+                let y = center(start[1]);
+                ([start[0].round(), y], [end[0].round(), y])
+            }
+            (false, true) => {
+                let x = center(start[0]);
+                ([x, start[1].round()], [x, end[1].round()])
+            }
+            _ => (start.map(f32::round), end.map(f32::round)),
+        };
+
+        line.start = from_pixels(start);
+        line.end = from_pixels(end);
+        line.width = line_width / scale;
     }
 }
 
-impl DerefMut for Lines {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A dashed line from `start` to `end`
+    fn line(start: [f32; 2], end: [f32; 2]) -> LineSpec {
+        LineSpec {
+            start,
+            end,
+            color: [0, 0, 0, 255],
+            width: 1.,
+            segment_length: 3.,
+            duty: 0.5,
+        }
+    }
+
+    /// Collinear segments should continue the dash-pattern of the previous segment.
+    #[test]
+    fn collinear_phase_continuous() {
+        let first = LineInstance::from(&line([1., 2.], [11., 2.]));
+        let second = LineInstance::from(&line([11., 2.], [18.5, 2.]));
+
+        assert_eq!(first.phase, 1.);
+        assert_eq!(
+            second.phase,
+            first.phase + 10.,
+            "Second segment does not continue the first"
+        );
+    }
+
+    /// Parallel lines (e.g., of a grid) should have aligned dashes.
+    #[test]
+    fn parallel_phase_aligned() {
+        let horizontal = [
+            line([0., 0.], [20., 0.]),
+            line([0., 10.], [20., 10.]),
+            line([0., 20.], [20., 20.]),
+        ]
+        .map(|l| LineInstance::from(&l).phase);
+        assert_eq!(horizontal, [0.; 3], "Horizontal grid lines not aligned");
+
+        let vertical = [line([5., 0.], [5., 20.]), line([15., 0.], [15., 20.])]
+            .map(|l| LineInstance::from(&l).phase);
+        assert_eq!(vertical, [0.; 2], "Vertical grid lines not aligned");
+
+        // Reversed direction: phase is measured in the other direction
+        let reversed = LineInstance::from(&line([20., 4.], [0., 4.]));
+        assert_eq!(reversed.phase, -20.);
+    }
+
+    /// Degenerate lines should not produce invalid phases.
+    #[test]
+    fn empty_line_phase() {
+        let empty = LineInstance::from(&line([3., 4.], [3., 4.]));
+        assert_eq!(empty.phase, 0.);
     }
 }
@@ -1,5 +1,5 @@
 #import util::to_color;
-#import viewport::viewport_projection;
+#import viewport::{viewport_projection, with_opacity};
 
 struct LineSpec {
 	@location(0) start: vec2<f32>,
@@ -8,6 +8,7 @@ struct LineSpec {
 	@location(3) width: f32,
 	@location(4) segment_length: f32,
 	@location(5) duty: f32,
+	@location(6) phase: f32,
 }
 
 struct VOutput {
@@ -20,6 +21,8 @@ struct VOutput {
 	@location(3) segment_length: f32,
 	@location(4) duty: f32,
 	@location(5) color: u32,
+	// distance along the line from the projected origin
+	@location(6) dist: f32,
 	@builtin(position) position: vec4<f32>,
 };
 
@@ -28,8 +31,8 @@ fn vs_main(@builtin(vertex_index) in_vertex_index: u32, spec: LineSpec) -> VOutp
 	// Generate vertices for the rectangle to draw the line as
 
 	// Construct perpendicular vector of length width / 2
-	var perp = normalize(spec.end - spec.start);
-	perp = vec2<f32>(perp.y, -perp.x);
+	let dir = normalize(spec.end - spec.start);
+	var perp = vec2<f32>(dir.y, -dir.x);
 	perp = perp * spec.width / 2;
 
 	// Positions for bounding rect of line
@@ -50,6 +53,7 @@ fn vs_main(@builtin(vertex_index) in_vertex_index: u32, spec: LineSpec) -> VOutp
 	out.segment_length = spec.segment_length;
 	out.duty = spec.duty;
 	out.color = spec.color;
+	out.dist = spec.phase + dot(dir, pos[idx[in_vertex_index]] - spec.start);
 	out.position = viewport_projection * vec4<f32>(pos[idx[in_vertex_index]].x, pos[idx[in_vertex_index]].y, 0.0, 1.0);
 	return out;
 }
@@ -58,17 +62,18 @@ fn vs_main(@builtin(vertex_index) in_vertex_index: u32, spec: LineSpec) -> VOutp
 fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
 	// Not dashed (no segments or fully drawn segments)
 	if in.segment_length <= 0 || in.duty >= 1 {
-		return to_color(in.color);
+		return with_opacity(to_color(in.color));
 	}
 
 	// Dashed
-	var dist = dot(normalize(in.end - in.start), in.pos - in.start); // Current distance
+	// Current distance, measured from a common origin to align dashes of different lines
+	var dist = in.dist;
 	dist = dist + ((in.duty * in.segment_length) / 2); // offset by half a drawn segment
 
 	var dist_local = fract(dist / in.segment_length); // Distance in local segment
 
 	if dist_local <= in.duty { // Draw dash
-		return to_color(in.color);
+		return with_opacity(to_color(in.color));
 	} else { // Draw empty
 		return vec4<f32>(1.0, 1.0, 1.0, 0.0);
 	}
@@ -19,6 +19,10 @@ pub struct RectangleSpec {
     pub segment_length: f32,
     /// The duty-cycle of a dash-segment (how much of the segment should be drawn)
     pub duty: f32,
+    /// The color to fill the rectangle with (drawn beneath the line), if it should be filled
+    pub fill: Option<[u8; 4]>,
+    /// The clockwise rotation (in degrees) of the rectangle around its center
+    pub rotation: f32,
 }
 
 /// A [Component] which draws one or multiple rectangles to the screen
@@ -59,60 +63,169 @@ impl Rectangles {
     }
 }
 
-/// Converts a slice of [RectangleSpec]s to a [Vec] of [LineSpec]s
+/// Converts a slice of [RectangleSpec]s to a [Vec] of [LineSpec]s.
+/// Fills are drawn as a single line spanning the whole rectangle
+/// and are ordered before all borders.
 fn rectangles_to_lines(rectangles: impl IntoIterator<Item = RectangleSpec>) -> Vec<LineSpec> {
-    rectangles
-        .into_iter()
-        .flat_map(
-            |RectangleSpec {
-                 start: [x, y],
-                 size: [w, h],
-                 color,
-                 width,
-                 segment_length,
-                 duty,
-             }| {
-                // Offset positions by half line-width to prevent ugly corners
-                let delta = width / 2.;
-                // +----->
-                // |      ^
-                // v      |
-                //  <-----+
-                [
+    let rectangles: Vec<_> = rectangles.into_iter().collect();
+    let fills = rectangles.iter().filter_map(
+        |RectangleSpec {
+             start: [x, y],
+             size: [w, h],
+             fill,
+             rotation,
+             ..
+         }| {
+            // Empty rectangles have nothing to fill
+            fill.filter(|_| *w != 0. && *h != 0.).map(|color| {
+                rotate(
                     LineSpec {
-                        start: [x - delta, y],
-                        end: [x + w + delta, y],
+                        start: [*x, y + h / 2.],
+                        end: [x + w, y + h / 2.],
                         color,
-                        width,
-                        segment_length,
-                        duty,
+                        width: h.abs(),
+                        segment_length: 0.,
+                        duty: 1.,
                     },
-                    LineSpec {
-                        end: [x + w, y - delta],
-                        start: [x + w, y + h + delta],
-                        color,
-                        width,
-                        segment_length,
-                        duty,
-                    },
-                    LineSpec {
-                        start: [x + w + delta, y + h],
-                        end: [x - delta, y + h],
-                        color,
-                        width,
-                        segment_length,
-                        duty,
-                    },
-                    LineSpec {
-                        end: [x, y + h + delta],
-                        start: [x, y - delta],
-                        color,
-                        width,
-                        segment_length,
-                        duty,
-                    },
-                ]
-            },
-        )
-        .collect()
+                    [x + w / 2., y + h / 2.],
+                    *rotation,
+                )
+            })
+        },
+    );
+    let borders = rectangles.iter().copied().flat_map(
+        |RectangleSpec {
+             start: [x, y],
+             size: [w, h],
+             color,
+             width,
+             segment_length,
+             duty,
+             fill: _,
+             rotation,
+         }| {
+            // Offset positions by half line-width to prevent ugly corners
+            let delta = width / 2.;
+            let center = [x + w / 2., y + h / 2.];
+            // +----->
+            // |      ^
+            // v      |
+            //  <-----+
+            [
+                LineSpec {
+                    start: [x - delta, y],
+                    end: [x + w + delta, y],
+                    color,
+                    width,
+                    segment_length,
+                    duty,
+                },
+                LineSpec {
+                    end: [x + w, y - delta],
+                    start: [x + w, y + h + delta],
+                    color,
+                    width,
+                    segment_length,
+                    duty,
+                },
+                LineSpec {
+                    start: [x + w + delta, y + h],
+                    end: [x - delta, y + h],
+                    color,
+                    width,
+                    segment_length,
+                    duty,
+                },
+                LineSpec {
+                    end: [x, y + h + delta],
+                    start: [x, y - delta],
+                    color,
+                    width,
+                    segment_length,
+                    duty,
+                },
+            ]
+            .map(|line| rotate(line, center, rotation))
+        },
+    );
+    fills.chain(borders).collect()
+}
+
+/// Rotates the passed `line` clockwise by `rotation` degrees around `center`
+fn rotate(line: LineSpec, center: [f32; 2], rotation: f32) -> LineSpec {
+    if rotation == 0. {
+        return line;
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let rotate_point = |[x, y]: [f32; 2]| {
+        let (dx, dy) = (x - center[0], y - center[1]);
+        [
+            center[0] + dx * cos - dy * sin,
+            center[1] + dx * sin + dy * cos,
+        ]
+    };
+    LineSpec {
+        start: rotate_point(line.start),
+        end: rotate_point(line.end),
+        ..line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A rectangle with the passed `fill`
+    fn rectangle(fill: Option<[u8; 4]>) -> RectangleSpec {
+        RectangleSpec {
+            start: [0., 0.],
+            size: [10., 20.],
+            color: [0, 0, 0, 255],
+            width: 1.,
+            segment_length: 0.,
+            duty: 1.,
+            fill,
+            rotation: 0.,
+        }
+    }
+
+    /// A rectangle without a fill should only produce its four borders.
+    #[test]
+    fn no_fill() {
+        let lines = rectangles_to_lines([rectangle(None)]);
+        assert_eq!(
+            lines.len(),
+            4,
+            "Unfilled rectangle should only have borders"
+        );
+    }
+
+    /// A filled rectangle should produce an additional fill spanning the rectangle
+    /// which is drawn before all borders.
+    #[test]
+    fn fill() {
+        let fill = [0, 0, 255, 32];
+        let lines = rectangles_to_lines([rectangle(None), rectangle(Some(fill))]);
+        assert_eq!(lines.len(), 9, "Filled rectangle should have an extra fill");
+
+        let first = &lines[0];
+        assert_eq!(first.color, fill, "Fill is not drawn first");
+        assert_eq!(first.start, [0., 10.]);
+        assert_eq!(first.end, [10., 10.]);
+        assert_eq!(first.width, 20.);
+    }
+
+    /// A rotated rectangle should rotate its borders and fill around its center.
+    #[test]
+    fn rotated() {
+        let lines = rectangles_to_lines([RectangleSpec {
+            rotation: 90.,
+            ..rectangle(Some([0, 0, 255, 32]))
+        }]);
+
+        let fill = &lines[0];
+        // Fill spans the rectangle vertically when rotated by 90 degrees
+        assert!((fill.start[0] - 5.).abs() < 1e-4 && (fill.start[1] - 5.).abs() < 1e-4);
+        assert!((fill.end[0] - 5.).abs() < 1e-4 && (fill.end[1] - 15.).abs() < 1e-4);
+    }
 }
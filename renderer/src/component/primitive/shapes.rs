@@ -0,0 +1,223 @@
+use std::ops::{Deref, DerefMut};
+
+use naga_oil::compose::Composer;
+use naviz_state::{Pattern, Shape};
+use wgpu::{BufferAddress, Device, TextureFormat, VertexAttribute, VertexFormat};
+
+use crate::{
+    component::{Component, ComponentSpec},
+    globals::Globals,
+    viewport::Viewport,
+};
+
+/// Id of [Shape::Circle] in the shader
+const SHAPE_CIRCLE: u32 = 0;
+/// Id of [Shape::Square] in the shader
+const SHAPE_SQUARE: u32 = 1;
+/// Id of [Shape::Diamond] in the shader
+const SHAPE_DIAMOND: u32 = 2;
+/// Id of [Shape::Cross] in the shader
+const SHAPE_CROSS: u32 = 3;
+
+/// Id of [Pattern::Solid] in the shader
+const PATTERN_SOLID: u32 = 0;
+/// Id of [Pattern::Ring] in the shader
+const PATTERN_RING: u32 = 1;
+/// Id of [Pattern::Hatch] in the shader
+const PATTERN_HATCH: u32 = 2;
+
+/// A [Component] which draws one or multiple [Shape]s to the screen
+pub struct Shapes(Component<ShapeSpec>);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShapeSpec {
+    /// The center of the shape
+    pub center: [f32; 2],
+    /// The radius of the shape (i.e., half the size of its bounding square)
+    pub radius: f32,
+    /// The width of the outline if only the outline of the shape is drawn,
+    /// or `0` if the shape is filled.
+    /// Use [ShapeSpec::with_outline] to set.
+    outline: f32,
+    /// The color of the shape
+    pub color: [u8; 4],
+    /// The id of the shape in the shader.
+    /// Use [ShapeSpec::new] to create from a [Shape].
+    shape: u32,
+    /// The id of the pattern in the shader.
+    /// Use [ShapeSpec::with_pattern] to set from a [Pattern].
+    pattern: u32,
+}
+
+impl ShapeSpec {
+    /// Creates a new [ShapeSpec]
+    pub fn new(center: [f32; 2], radius: f32, color: [u8; 4], shape: Shape) -> Self {
+        Self {
+            center,
+            radius,
+            outline: 0.,
+            color,
+            shape: match shape {
+                Shape::Circle => SHAPE_CIRCLE,
+                Shape::Square => SHAPE_SQUARE,
+                Shape::Diamond => SHAPE_DIAMOND,
+                Shape::Cross => SHAPE_CROSS,
+            },
+            pattern: PATTERN_SOLID,
+        }
+    }
+
+    /// Fills this [ShapeSpec] with the passed [Pattern]
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = match pattern {
+            Pattern::Solid => PATTERN_SOLID,
+            Pattern::Ring => PATTERN_RING,
+            Pattern::Hatch => PATTERN_HATCH,
+        };
+        self
+    }
+
+    /// Only draws the outline of this [ShapeSpec] with the passed `width` (inside the shape).
+    /// The shape is filled if the `width` is at least the radius.
+    pub fn with_outline(mut self, width: f32) -> Self {
+        self.outline = width.max(0.);
+        self
+    }
+
+    /// The [Shape] of this [ShapeSpec]
+    pub fn shape(&self) -> Shape {
+        match self.shape {
+            SHAPE_SQUARE => Shape::Square,
+            SHAPE_DIAMOND => Shape::Diamond,
+            SHAPE_CROSS => Shape::Cross,
+            _ => Shape::Circle,
+        }
+    }
+
+    /// The [Pattern] of this [ShapeSpec]
+    pub fn pattern(&self) -> Pattern {
+        match self.pattern {
+            PATTERN_RING => Pattern::Ring,
+            PATTERN_HATCH => Pattern::Hatch,
+            _ => Pattern::Solid,
+        }
+    }
+}
+
+impl Shapes {
+    /// Create new [Shapes]
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        globals: &Globals,
+        viewport: &Viewport,
+        shader_composer: &mut Composer,
+        shapes: &[ShapeSpec],
+    ) -> Self {
+        Self(Component::new(
+            device,
+            format,
+            globals,
+            viewport,
+            shader_composer,
+            ComponentSpec {
+                specs: shapes,
+                attributes: &[
+                    VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: VertexFormat::Float32x2,
+                    },
+                    VertexAttribute {
+                        offset: size_of::<[f32; 2]>() as BufferAddress,
+                        shader_location: 1,
+                        format: VertexFormat::Float32,
+                    },
+                    VertexAttribute {
+                        offset: (size_of::<[f32; 2]>() + size_of::<f32>()) as BufferAddress,
+                        shader_location: 2,
+                        format: VertexFormat::Float32,
+                    },
+                    VertexAttribute {
+                        offset: (size_of::<[f32; 2]>() + 2 * size_of::<f32>()) as BufferAddress,
+                        shader_location: 3,
+                        format: VertexFormat::Uint32,
+                    },
+                    VertexAttribute {
+                        offset: (size_of::<[f32; 2]>() + 2 * size_of::<f32>() + size_of::<u32>())
+                            as BufferAddress,
+                        shader_location: 4,
+                        format: VertexFormat::Uint32,
+                    },
+                    VertexAttribute {
+                        offset: (size_of::<[f32; 2]>()
+                            + 2 * size_of::<f32>()
+                            + 2 * size_of::<u32>())
+                            as BufferAddress,
+                        shader_location: 5,
+                        format: VertexFormat::Uint32,
+                    },
+                ],
+                shader_source: include_str!("shapes.wgsl"),
+                shader_path: "shapes.wgsl",
+                uniform: None,
+            },
+        ))
+    }
+}
+
+impl Deref for Shapes {
+    type Target = Component<ShapeSpec>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Shapes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Every [Shape] should map to its own id in the shader and back.
+    #[test]
+    fn shape_ids() {
+        let shapes = [Shape::Circle, Shape::Square, Shape::Diamond, Shape::Cross];
+        let specs = shapes.map(|shape| ShapeSpec::new([0., 0.], 1., [0; 4], shape));
+
+        assert_eq!(
+            specs.map(|s| s.shape),
+            [SHAPE_CIRCLE, SHAPE_SQUARE, SHAPE_DIAMOND, SHAPE_CROSS]
+        );
+        assert_eq!(specs.map(|s| s.shape()), shapes);
+    }
+
+    /// Every [Pattern] should map to its own id in the shader and back.
+    #[test]
+    fn pattern_ids() {
+        let patterns = [Pattern::Solid, Pattern::Ring, Pattern::Hatch];
+        let specs = patterns.map(|pattern| {
+            ShapeSpec::new([0., 0.], 1., [0; 4], Shape::Circle).with_pattern(pattern)
+        });
+
+        assert_eq!(
+            specs.map(|s| s.pattern),
+            [PATTERN_SOLID, PATTERN_RING, PATTERN_HATCH]
+        );
+        assert_eq!(specs.map(|s| s.pattern()), patterns);
+    }
+
+    /// Outlines should be filled by default and never have a negative width.
+    #[test]
+    fn outline_width() {
+        let spec = ShapeSpec::new([0., 0.], 1., [0; 4], Shape::Square);
+        assert_eq!(spec.outline, 0.);
+        assert_eq!(spec.with_outline(0.25).outline, 0.25);
+        assert_eq!(spec.with_outline(-1.).outline, 0.);
+    }
+}
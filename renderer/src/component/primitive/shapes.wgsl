@@ -0,0 +1,120 @@
+#import util::to_color;
+#import viewport::{viewport_projection, with_opacity};
+
+// Ids of the shapes; must match the constants in `shapes.rs`
+const SHAPE_CIRCLE: u32 = 0u;
+const SHAPE_SQUARE: u32 = 1u;
+const SHAPE_DIAMOND: u32 = 2u;
+const SHAPE_CROSS: u32 = 3u;
+
+// Ids of the patterns; must match the constants in `shapes.rs`
+const PATTERN_SOLID: u32 = 0u;
+const PATTERN_RING: u32 = 1u;
+const PATTERN_HATCH: u32 = 2u;
+
+struct ShapeSpec {
+	@location(0) center: vec2<f32>,
+	@location(1) radius: f32,
+	@location(2) outline: f32,
+	@location(3) color: u32,
+	@location(4) shape: u32,
+	@location(5) pattern: u32,
+}
+
+struct VOutput {
+	// current position relative to the center in input space
+	@location(0) offset: vec2<f32>,
+	@location(1) radius: f32,
+	@location(2) outline: f32,
+	@location(3) color: u32,
+	@location(4) shape: u32,
+	@location(5) pattern: u32,
+	@builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32, spec: ShapeSpec) -> VOutput {
+	// Generate vertices for the bounding square to draw the shape in
+
+	// Offsets of the corners of the bounding square
+	var offset = array<vec2<f32>,4>(
+		vec2<f32>(-spec.radius,  spec.radius),
+		vec2<f32>(-spec.radius, -spec.radius),
+		vec2<f32>( spec.radius, -spec.radius),
+		vec2<f32>( spec.radius,  spec.radius),
+	);
+	// Indices for triangles of bounding square
+	var idx = array<u32, 6>(0, 1, 2, 2, 3, 0);
+
+	var out: VOutput;
+	out.offset = offset[idx[in_vertex_index]];
+	out.radius = spec.radius;
+	out.outline = spec.outline;
+	out.color = spec.color;
+	out.shape = spec.shape;
+	out.pattern = spec.pattern;
+	out.position = viewport_projection * vec4<f32>(spec.center + out.offset, 0.0, 1.0);
+	return out;
+}
+
+// Checks whether the `offset` from the center lies inside the `shape` of the passed `radius`
+fn inside(shape: u32, offset: vec2<f32>, radius: f32) -> bool {
+	return inside_inset(shape, offset, radius, 0.0);
+}
+
+// Checks whether the `offset` from the center lies inside the `shape` of the passed `radius`
+// with its edges moved inwards by `inset`
+fn inside_inset(shape: u32, offset: vec2<f32>, radius: f32, inset: f32) -> bool {
+	let d = abs(offset);
+	if shape == SHAPE_SQUARE {
+		return max(d.x, d.y) <= radius - inset;
+	} else if shape == SHAPE_DIAMOND {
+		// edges are diagonal
+		return d.x + d.y <= radius - inset * sqrt(2.0);
+	} else if shape == SHAPE_CROSS {
+		// plus-sign with arms a third of the size wide
+		return max(d.x, d.y) <= radius - inset && min(d.x, d.y) <= radius / 3.0 - inset;
+	} else { // SHAPE_CIRCLE
+		return length(offset) <= radius - inset;
+	}
+}
+
+// Checks whether the `offset` from the center is filled with the color by the `pattern`.
+// Unfilled parts are drawn in a contrasting color,
+// so that the pattern stays visible on any background.
+fn filled(pattern: u32, shape: u32, offset: vec2<f32>, radius: f32) -> bool {
+	if pattern == PATTERN_RING {
+		return !inside(shape, offset, radius * 0.5);
+	} else if pattern == PATTERN_HATCH {
+		// diagonal stripes; four per diameter
+		return fract((offset.x + offset.y) / (radius * 0.5)) < 0.5;
+	} else { // PATTERN_SOLID
+		return true;
+	}
+}
+
+// Black or white, whichever contrasts more with the passed `color`
+fn contrast(color: vec4<f32>) -> vec4<f32> {
+	let luminance = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+	if luminance > 0.5 {
+		return vec4<f32>(0.0, 0.0, 0.0, color.a);
+	} else {
+		return vec4<f32>(1.0, 1.0, 1.0, color.a);
+	}
+}
+
+@fragment
+fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
+	// inside the shape, but not inside the cutout of an outline
+	let in_outline = in.outline <= 0.0 || !inside_inset(in.shape, in.offset, in.radius, in.outline);
+	if inside(in.shape, in.offset, in.radius) && in_outline { // in draw region
+		let color = to_color(in.color);
+		if filled(in.pattern, in.shape, in.offset, in.radius) {
+			return with_opacity(color);
+		} else {
+			return with_opacity(contrast(color));
+		}
+	} else { // not in draw region
+		return vec4<f32>(1.0, 1.0, 1.0, 0.0);
+	}
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use glam::{Mat4, Vec3};
 use glyphon::{
     Attrs, Buffer, Cache, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache,
@@ -6,7 +8,7 @@ use glyphon::{
 use log::warn;
 use wgpu::{Device, MultisampleState, Queue, RenderPass, TextureFormat};
 
-use crate::viewport::ViewportProjection;
+use crate::{quality::RenderQuality, viewport::ViewportProjection};
 
 #[derive(Clone, Copy, Default, Debug)]
 pub enum HAlignment {
@@ -43,13 +45,111 @@ pub struct TextSpec<'a, TextIterator: IntoIterator<Item = (&'a str, (f32, f32),
     pub color: [u8; 4],
 }
 
+/// The vertical position of a [LabelSpan]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Script {
+    /// Regular text
+    #[default]
+    Normal,
+    /// Subscript: `_{...}`
+    Sub,
+    /// Superscript: `^{...}`
+    Super,
+}
+
+impl Script {
+    /// The factor to scale the font size by
+    fn scale(self) -> f32 {
+        match self {
+            Self::Normal => 1.,
+            Self::Sub | Self::Super => 0.6,
+        }
+    }
+
+    /// The vertical shift (downwards) relative to the base font size
+    fn shift(self) -> f32 {
+        match self {
+            Self::Normal => 0.,
+            Self::Sub => 0.2,
+            Self::Super => -0.4,
+        }
+    }
+}
+
+/// A part of a label which is rendered with a single [Script]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LabelSpan<'a> {
+    pub text: &'a str,
+    pub script: Script,
+}
+
+/// Parses a label into lines of [LabelSpan]s.
+///
+/// Lines are separated by `\n`.
+/// Subscripts are written as `_{...}`, superscripts as `^{...}`.
+/// Unclosed groups are kept as-is.
+pub fn parse_label(label: &str) -> Vec<Vec<LabelSpan<'_>>> {
+    label.split('\n').map(parse_label_line).collect()
+}
+
+/// Parses a single line of a label into [LabelSpan]s.
+/// Will always return at least one span.
+fn parse_label_line(line: &str) -> Vec<LabelSpan<'_>> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while let Some((idx, script)) = find_script(rest) {
+        let inner_start = idx + 2;
+        let Some(len) = rest[inner_start..].find('}') else {
+            break;
+        };
+        if idx > 0 {
+            spans.push(LabelSpan {
+                text: &rest[..idx],
+                script: Script::Normal,
+            });
+        }
+        spans.push(LabelSpan {
+            text: &rest[inner_start..inner_start + len],
+            script,
+        });
+        rest = &rest[inner_start + len + 1..];
+    }
+    if !rest.is_empty() || spans.is_empty() {
+        spans.push(LabelSpan {
+            text: rest,
+            script: Script::Normal,
+        });
+    }
+    spans
+}
+
+/// Finds the start of the next `_{` or `^{` in the passed `text`.
+fn find_script(text: &str) -> Option<(usize, Script)> {
+    text.char_indices().find_map(|(i, c)| {
+        let script = match c {
+            '_' => Script::Sub,
+            '^' => Script::Super,
+            _ => return None,
+        };
+        text[i + 1..].starts_with('{').then_some((i, script))
+    })
+}
+
+/// A laid-out label consisting of one [Buffer] per [LabelSpan]
+struct LabelBuffer {
+    /// The buffers and their offsets (in unscaled text-space) from the top left of the label
+    spans: Vec<(Buffer, (f32, f32))>,
+    /// The size of the whole label (in unscaled text-space)
+    size: (f32, f32),
+}
+
 /// The cache containing the pre-baked data to bake.
 ///
 /// Create using [BakeCache::create],
 /// fully bake using [Text::bake].
 struct BakeCache {
     /// The text-buffers
-    text_buffers: Vec<(Buffer, (f32, f32), Alignment)>,
+    text_buffers: Vec<(LabelBuffer, (f32, f32), Alignment)>,
     /// The text color
     color: Color,
     /// The viewport projection to render in
@@ -66,6 +166,12 @@ pub struct Text {
     font_system: FontSystem,
     swash_cache: SwashCache,
     bake_cache: BakeCache,
+    /// Whether the font-families used so far are loaded (by name)
+    font_families: HashMap<String, bool>,
+    /// The opacity multiplied into the text color (`0` to `1`)
+    opacity: f32,
+    /// The [RenderQuality] to place the text with
+    quality: RenderQuality,
 }
 
 impl Text {
@@ -76,12 +182,8 @@ impl Text {
         spec: TextSpec<'a, TextIterator>,
         screen_resolution: (u32, u32),
     ) -> Self {
-        let mut font_system = FontSystem::new();
-        // Load a default font
-        // Used when system-fonts cannot be loaded (e.g., on web)
-        font_system
-            .db_mut()
-            .load_font_data(include_bytes!(env!("DEFAULT_FONT_PATH")).to_vec());
+        let mut font_system = new_font_system();
+        let mut font_families = HashMap::new();
 
         let swash_cache = SwashCache::new();
         let cache = Cache::new(device);
@@ -95,10 +197,18 @@ impl Text {
             glyphon_viewport,
             text_renderer,
 
-            bake_cache: BakeCache::create(spec, screen_resolution, &mut font_system),
+            bake_cache: BakeCache::create(
+                spec,
+                screen_resolution,
+                &mut font_system,
+                &mut font_families,
+            ),
 
             font_system,
+            font_families,
             swash_cache,
+            opacity: 1.,
+            quality: RenderQuality::default(),
         };
         text.bake(device, queue);
         text
@@ -114,6 +224,7 @@ impl Text {
             spec,
             self.bake_cache.screen_resolution,
             &mut self.font_system,
+            &mut self.font_families,
         );
         self.bake(device, queue);
     }
@@ -128,6 +239,19 @@ impl Text {
         self.bake(device, queue);
     }
 
+    /// Sets the `opacity` (`0` to `1`) which is multiplied into the text color
+    pub fn set_opacity(&mut self, (device, queue): (&Device, &Queue), opacity: f32) {
+        self.opacity = opacity;
+        self.bake(device, queue);
+    }
+
+    /// Sets the [RenderQuality] to place the text with.
+    /// [RenderQuality::Crisp] places the text on whole pixels.
+    pub fn set_quality(&mut self, (device, queue): (&Device, &Queue), quality: RenderQuality) {
+        self.quality = quality;
+        self.bake(device, queue);
+    }
+
     /// Bakes the [BakeCache] of this [Text] to the [Text::text_renderer]
     fn bake(&mut self, device: &Device, queue: &Queue) {
         let BakeCache {
@@ -146,15 +270,18 @@ impl Text {
             },
         );
 
+        let color = with_opacity(*color, self.opacity);
+
         // create the TextAreas
-        let text_areas = text_buffers.iter().map(|(buf, pos, alignment)| {
-            to_text_area(
+        let text_areas = text_buffers.iter().flat_map(|(buf, pos, alignment)| {
+            to_text_areas(
                 buf,
                 *pos,
                 *alignment,
-                *color,
+                color,
                 *viewport_projection,
                 *screen_resolution,
+                self.quality,
             )
         });
 
@@ -172,6 +299,12 @@ impl Text {
             .unwrap();
     }
 
+    /// Checks whether the font-family with the passed name
+    /// can be used by this [Text] (case-insensitive).
+    pub fn has_font_family(&self, family: &str) -> bool {
+        has_font_family(&self.font_system, family)
+    }
+
     /// Draws this [Text].
     ///
     /// Will overwrite bind groups.
@@ -192,7 +325,8 @@ impl Text {
 }
 
 impl BakeCache {
-    /// Creates a new [BakeCache] from the passed [TextSpec]
+    /// Creates a new [BakeCache] from the passed [TextSpec].
+    /// Whether the font-family is loaded is looked up in (and cached into) `font_families`.
     fn create<'a, TextIterator: IntoIterator<Item = (&'a str, (f32, f32), Alignment)>>(
         TextSpec {
             viewport_projection,
@@ -203,13 +337,16 @@ impl BakeCache {
         }: TextSpec<'a, TextIterator>,
         screen_resolution: (u32, u32),
         font_system: &mut FontSystem,
+        font_families: &mut HashMap<String, bool>,
     ) -> Self {
+        check_font_family(font_families, font_system, font_family);
+
         // create the text buffers
         let text_buffers: Vec<_> = texts
             .into_iter()
             .map(|(text, pos, alignment)| {
                 (
-                    to_text_buffer(text, font_system, font_size, font_family),
+                    to_label_buffer(text, font_system, font_size, font_family),
                     pos,
                     alignment,
                 )
@@ -225,6 +362,76 @@ impl BakeCache {
     }
 }
 
+/// Multiplies the passed `opacity` (`0` to `1`) into the alpha of the passed `color`
+fn with_opacity(color: Color, opacity: f32) -> Color {
+    Color::rgba(
+        color.r(),
+        color.g(),
+        color.b(),
+        (color.a() as f32 * opacity).round() as u8,
+    )
+}
+
+/// Creates a new [FontSystem] with the system-fonts and the bundled default font loaded
+fn new_font_system() -> FontSystem {
+    let mut font_system = FontSystem::new();
+    // Load a default font
+    // Used when system-fonts cannot be loaded (e.g., on web)
+    font_system
+        .db_mut()
+        .load_font_data(include_bytes!(env!("DEFAULT_FONT_PATH")).to_vec());
+    font_system
+}
+
+/// Lists the names of all font-families which can be used to render text
+/// (i.e., the system-fonts and the bundled default font).
+/// The names are sorted and contain no duplicates.
+pub fn available_font_families() -> Vec<String> {
+    font_families(&new_font_system())
+}
+
+/// Lists the sorted and deduplicated names of all font-families in the passed [FontSystem]
+fn font_families(font_system: &FontSystem) -> Vec<String> {
+    let mut families: Vec<_> = font_system
+        .db()
+        .faces()
+        .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+        .collect();
+    families.sort();
+    families.dedup();
+    families
+}
+
+/// Checks whether the font-family with the passed name
+/// is loaded in the passed [FontSystem] (case-insensitive)
+fn has_font_family(font_system: &FontSystem, family: &str) -> bool {
+    font_system
+        .db()
+        .faces()
+        .flat_map(|face| face.families.iter())
+        .any(|(name, _)| name.eq_ignore_ascii_case(family))
+}
+
+/// Checks whether the font-family with the passed name is loaded in the passed [FontSystem]
+/// (see [has_font_family]).
+/// The result is cached in `font_families`,
+/// so a missing font-family is only looked up and warned about once.
+fn check_font_family(
+    font_families: &mut HashMap<String, bool>,
+    font_system: &FontSystem,
+    family: &str,
+) -> bool {
+    if let Some(found) = font_families.get(family) {
+        return *found;
+    }
+    let found = has_font_family(font_system, family);
+    if !found {
+        warn!("Font-family {family:?} not found, falling back to another font");
+    }
+    font_families.insert(family.to_owned(), found);
+    found
+}
+
 /// Creates a [glyphon::Buffer] of the passed `text`.
 fn to_text_buffer(
     text: &str,
@@ -240,16 +447,74 @@ fn to_text_buffer(
     text_buffer
 }
 
-/// Creates a [TextArea] of the passed [glyphon::Buffer].
+/// Creates a [LabelBuffer] of the passed `text`.
+/// Will lay out the [LabelSpan]s of each line next to each other
+/// and align them on a common baseline.
+fn to_label_buffer(
+    text: &str,
+    font_system: &mut FontSystem,
+    font_size: f32,
+    font_family: &str,
+) -> LabelBuffer {
+    /// The baseline of the first line of the passed [Buffer]
+    fn baseline(buffer: &Buffer) -> f32 {
+        buffer.layout_runs().next().map(|r| r.line_y).unwrap_or(0.)
+    }
+
+    let mut spans = Vec::new();
+    let mut width: f32 = 0.;
+    let mut y = 0.;
+    for line in parse_label(text) {
+        let buffers: Vec<_> = line
+            .into_iter()
+            .map(|span| {
+                (
+                    to_text_buffer(
+                        span.text,
+                        font_system,
+                        font_size * span.script.scale(),
+                        font_family,
+                    ),
+                    span.script,
+                )
+            })
+            .collect();
+
+        let line_baseline = buffers.iter().map(|(b, _)| baseline(b)).fold(0., f32::max);
+        let line_height = buffers
+            .iter()
+            .map(|(b, _)| b.metrics().line_height)
+            .fold(0., f32::max);
+
+        let mut x = 0.;
+        for (buffer, script) in buffers {
+            let top = y + line_baseline - baseline(&buffer) + script.shift() * font_size;
+            let w = buffer.layout_runs().map(|r| r.line_w).fold(0., f32::max);
+            spans.push((buffer, (x, top)));
+            x += w;
+        }
+
+        width = width.max(x);
+        y += line_height;
+    }
+
+    LabelBuffer {
+        spans,
+        size: (width, y),
+    }
+}
+
+/// Creates the [TextArea]s of the passed [LabelBuffer].
 /// Will handle alignment.
-fn to_text_area<'a>(
-    text_buffer: &'a Buffer,
+fn to_text_areas<'a>(
+    label_buffer: &'a LabelBuffer,
     (x, y): (f32, f32),
     alignment: Alignment,
     color: Color,
     viewport: ViewportProjection,
     screen_resolution: (u32, u32),
-) -> TextArea<'a> {
+    quality: RenderQuality,
+) -> impl Iterator<Item = TextArea<'a>> {
     let bounds = TextBounds {
         left: 0,
         top: 0,
@@ -275,31 +540,29 @@ fn to_text_area<'a>(
     let (x, y) = get_aligned_position(
         alignment,
         (x, y),
-        || {
-            text_buffer
-                .layout_runs()
-                .map(|r| r.line_w)
-                .fold(0., f32::max)
-                * scale
-        },
-        || {
-            text_buffer
-                .layout_runs()
-                .map(|r| r.line_height)
-                .sum::<f32>()
-                * scale
-        },
+        || label_buffer.size.0 * scale,
+        || label_buffer.size.1 * scale,
     );
 
-    TextArea {
-        buffer: text_buffer,
-        left: x,
-        top: y,
-        scale,
-        bounds,
-        default_color: color,
-        custom_glyphs: &[],
-    }
+    label_buffer
+        .spans
+        .iter()
+        .map(move |(buffer, (offset_x, offset_y))| {
+            let (left, top) = (x + offset_x * scale, y + offset_y * scale);
+            let (left, top) = match quality {
+                RenderQuality::Crisp => (left.round(), top.round()),
+                RenderQuality::Smooth => (left, top),
+            };
+            TextArea {
+                buffer,
+                left,
+                top,
+                scale,
+                bounds,
+                default_color: color,
+                custom_glyphs: &[],
+            }
+        })
 }
 
 /// Aligns an element at the passed position.
@@ -334,8 +597,9 @@ fn get_scale(projection_matrix: Mat4, screen_resolution: (u32, u32)) -> f32 {
 
     let canvas_unit_x = projection_matrix.transform_vector3(Vec3::new(1., 0., 0.)).x / 2.0;
     let scale_x = canvas_unit_x * screen_resolution.0 as f32;
+    // y may point in either direction (see `ViewportProjection::flip_y`)
     let canvas_unit_y = projection_matrix.transform_vector3(Vec3::new(0., 1., 0.)).y / 2.0;
-    let scale_y = -canvas_unit_y * screen_resolution.1 as f32;
+    let scale_y = canvas_unit_y.abs() * screen_resolution.1 as f32;
 
     const MAX_DIFF: f32 = 0.001;
     if cfg!(debug_assertions) && (scale_x - scale_y).abs() > MAX_DIFF {
@@ -345,3 +609,152 @@ fn get_scale(projection_matrix: Mat4, screen_resolution: (u32, u32)) -> f32 {
     // Average directions
     (scale_x + scale_y) / 2.
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A [FontSystem] with only the default font loaded
+    fn font_system() -> FontSystem {
+        let mut db = glyphon::fontdb::Database::new();
+        db.load_font_data(include_bytes!(env!("DEFAULT_FONT_PATH")).to_vec());
+        FontSystem::new_with_locale_and_db("en-US".to_string(), db)
+    }
+
+    /// Plain labels should be kept as a single span.
+    #[test]
+    fn parse_plain() {
+        assert_eq!(
+            parse_label("atom_0"),
+            vec![vec![LabelSpan {
+                text: "atom_0",
+                script: Script::Normal,
+            }]]
+        );
+    }
+
+    /// Newlines, subscripts, and superscripts should be split into separate spans.
+    #[test]
+    fn parse_scripts() {
+        assert_eq!(
+            parse_label("q_{0}\nx^{2}y"),
+            vec![
+                vec![
+                    LabelSpan {
+                        text: "q",
+                        script: Script::Normal,
+                    },
+                    LabelSpan {
+                        text: "0",
+                        script: Script::Sub,
+                    },
+                ],
+                vec![
+                    LabelSpan {
+                        text: "x",
+                        script: Script::Normal,
+                    },
+                    LabelSpan {
+                        text: "2",
+                        script: Script::Super,
+                    },
+                    LabelSpan {
+                        text: "y",
+                        script: Script::Normal,
+                    },
+                ],
+            ]
+        );
+    }
+
+    /// Unclosed groups should be kept as-is.
+    #[test]
+    fn parse_unclosed() {
+        assert_eq!(
+            parse_label("q_{0"),
+            vec![vec![LabelSpan {
+                text: "q_{0",
+                script: Script::Normal,
+            }]]
+        );
+    }
+
+    /// A subscript should be laid out as a separate run with reduced metrics.
+    #[test]
+    fn subscript_layout() {
+        let mut font_system = font_system();
+        let label = to_label_buffer("q_{0}", &mut font_system, 10., "Hack");
+
+        assert_eq!(
+            label.spans.len(),
+            2,
+            "Subscript was not split into two runs"
+        );
+        let (normal, (normal_x, normal_y)) = &label.spans[0];
+        let (sub, (sub_x, sub_y)) = &label.spans[1];
+        assert_eq!(normal.layout_runs().count(), 1);
+        assert_eq!(sub.layout_runs().count(), 1);
+        assert!(
+            sub.metrics().font_size < normal.metrics().font_size,
+            "Subscript does not have reduced metrics"
+        );
+        assert!(sub_x > normal_x, "Subscript is not placed after base");
+        assert!(sub_y > normal_y, "Subscript is not shifted down");
+    }
+
+    /// Multi-line labels should stack the lines vertically.
+    #[test]
+    fn multiline_layout() {
+        let mut font_system = font_system();
+        let single = to_label_buffer("a", &mut font_system, 10., "Hack");
+        let multi = to_label_buffer("a\nb", &mut font_system, 10., "Hack");
+
+        assert_eq!(multi.spans.len(), 2);
+        assert!(multi.size.1 > single.size.1, "Lines are not stacked");
+        assert!(multi.spans[1].1 .1 > multi.spans[0].1 .1);
+    }
+
+    /// The bundled default font should always be listed as available.
+    #[test]
+    fn default_font_available() {
+        let default_families = font_families(&font_system());
+        assert!(!default_families.is_empty(), "Default font has no family");
+
+        let available = available_font_families();
+        for family in &default_families {
+            assert!(available.contains(family), "{family} is not available");
+            assert!(has_font_family(&font_system(), &family.to_uppercase()));
+        }
+        assert!(!has_font_family(&font_system(), "Not A Font Family"));
+    }
+
+    /// Font-family lookups should be cached, including missing families.
+    #[test]
+    fn font_family_lookup_cached() {
+        let font_system = font_system();
+        let family = font_families(&font_system)
+            .into_iter()
+            .next()
+            .expect("No font loaded");
+        let mut cache = HashMap::new();
+
+        assert!(check_font_family(&mut cache, &font_system, &family));
+        assert!(!check_font_family(
+            &mut cache,
+            &font_system,
+            "Not A Font Family"
+        ));
+        assert_eq!(
+            cache,
+            HashMap::from([(family, true), ("Not A Font Family".to_owned(), false)])
+        );
+
+        // Cached results are used without looking up again
+        cache.insert("Not A Font Family".to_owned(), true);
+        assert!(check_font_family(
+            &mut cache,
+            &font_system,
+            "Not A Font Family"
+        ));
+    }
+}
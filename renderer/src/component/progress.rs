@@ -0,0 +1,154 @@
+use naviz_state::{config::Config, state::State};
+use wgpu::{Device, Queue, RenderPass};
+
+use crate::{
+    buffer_updater::BufferUpdater,
+    component::drawable::{Drawable, Fadable},
+    viewport::{Viewport, ViewportProjection, ViewportSource, ViewportTarget},
+};
+
+use super::{
+    primitive::rectangles::{RectangleSpec, Rectangles},
+    updatable::Updatable,
+    ComponentInit,
+};
+
+/// A component to draw a progress bar along the bottom edge of the screen.
+/// The bar is drawn in the color of the time (see [TimeConfig][naviz_state::config::TimeConfig]).
+pub struct Progress {
+    viewport: Viewport,
+    bar: Rectangles,
+    /// The current progress (`0` to `1`)
+    progress: f32,
+    /// The color of the bar
+    color: [u8; 4],
+}
+
+impl Progress {
+    /// The height of the bar (in [wgpu] coordinate-space)
+    pub const HEIGHT: f32 = 0.03;
+
+    /// The [ViewportProjection] of the bar:
+    /// Maps `0..1` onto the full width of the bottom edge of the screen.
+    pub const VIEWPORT_PROJECTION: ViewportProjection = ViewportProjection {
+        source: ViewportSource {
+            x: 0.,
+            y: 0.,
+            width: 1.,
+            height: 1.,
+        },
+        target: ViewportTarget {
+            x: -1.,
+            y: -1.,
+            width: 2.,
+            height: Self::HEIGHT,
+        },
+        flip_y: false,
+    };
+
+    pub fn new(
+        ComponentInit {
+            device,
+            queue: _,
+            format,
+            globals,
+            shader_composer,
+            config,
+            state: _,
+            viewport_projection,
+            screen_resolution: _,
+        }: ComponentInit,
+    ) -> Self {
+        let color = config.time.font.color;
+        let viewport = Viewport::new(viewport_projection, device);
+
+        Self {
+            bar: Rectangles::new(
+                device,
+                format,
+                globals,
+                &viewport,
+                shader_composer,
+                get_specs(0., color),
+            ),
+            viewport,
+            progress: 0.,
+            color,
+        }
+    }
+
+    /// Sets the current `progress` (`0` to `1`; clamped)
+    pub fn set_progress(&mut self, updater: &mut impl BufferUpdater, progress: f32) {
+        self.progress = progress.clamp(0., 1.);
+        self.bar
+            .update(updater, get_specs(self.progress, self.color));
+    }
+}
+
+impl Drawable for Progress {
+    /// Draws this [Progress].
+    ///
+    /// Does not use the `rebind`-function, as no globals are overwritten.
+    fn draw<const REBIND: bool>(
+        &self,
+        render_pass: &mut RenderPass<'_>,
+        _rebind: impl Fn(&mut RenderPass),
+    ) {
+        self.viewport.bind(render_pass);
+        self.bar.draw(render_pass);
+    }
+}
+
+impl Fadable for Progress {
+    fn set_opacity(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        opacity: f32,
+    ) {
+        self.viewport.set_opacity(updater, opacity);
+    }
+}
+
+impl Updatable for Progress {
+    fn update(
+        &mut self,
+        _updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        _config: &Config,
+        _state: &State,
+    ) {
+        // Nothing depends on state; progress is set using `set_progress`
+    }
+
+    fn update_full(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        _device: &Device,
+        _queue: &Queue,
+        config: &Config,
+        _state: &State,
+        viewport_projection: ViewportProjection,
+    ) {
+        self.viewport.update(updater, viewport_projection);
+        self.color = config.time.font.color;
+        self.bar
+            .update(updater, get_specs(self.progress, self.color));
+    }
+}
+
+/// Gets the specs for [Progress] at the passed `progress` and with the passed `color`.
+fn get_specs(progress: f32, color: [u8; 4]) -> [RectangleSpec; 1] {
+    [RectangleSpec {
+        start: [0., 0.],
+        size: [progress, 1.],
+        color,
+        width: 0.,
+        segment_length: 0.,
+        duty: 1.,
+        fill: Some(color),
+        rotation: 0.,
+    }]
+}
@@ -2,7 +2,10 @@ use naviz_state::{config::Config, state::State};
 use wgpu::{Device, Queue, RenderPass};
 
 use crate::{
-    buffer_updater::BufferUpdater, component::drawable::Drawable, viewport::ViewportProjection,
+    buffer_updater::BufferUpdater,
+    component::drawable::{Drawable, Fadable},
+    quality::RenderQuality,
+    viewport::ViewportProjection,
 };
 
 use super::{
@@ -53,6 +56,11 @@ impl Time {
         self.text
             .update_viewport((device, queue), screen_resolution);
     }
+
+    /// Sets the [RenderQuality] of this [Time]
+    pub fn set_quality(&mut self, device: &Device, queue: &Queue, quality: RenderQuality) {
+        self.text.set_quality((device, queue), quality);
+    }
 }
 
 impl Drawable for Time {
@@ -70,6 +78,18 @@ impl Drawable for Time {
     }
 }
 
+impl Fadable for Time {
+    fn set_opacity(
+        &mut self,
+        _updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        opacity: f32,
+    ) {
+        self.text.set_opacity((device, queue), opacity);
+    }
+}
+
 impl Updatable for Time {
     fn update(
         &mut self,
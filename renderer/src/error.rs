@@ -0,0 +1,46 @@
+//! Errors which can occur when setting up the [Renderer][crate::renderer::Renderer].
+
+use std::fmt::Display;
+
+use naga_oil::compose::ComposerError;
+
+/// An error which occurred while creating a [Renderer][crate::renderer::Renderer].
+/// See [Renderer::try_new][crate::renderer::Renderer::try_new].
+#[derive(Debug)]
+pub enum RendererError {
+    /// A shader could not be loaded or composed
+    Shader(Box<ComposerError>),
+    /// The adapter does not support a limit required by the renderer
+    Limits {
+        /// The name of the limit
+        limit: &'static str,
+        /// The value required by the renderer
+        required: u32,
+        /// The value supported by the adapter
+        available: u32,
+    },
+}
+
+impl From<Box<ComposerError>> for RendererError {
+    fn from(error: Box<ComposerError>) -> Self {
+        Self::Shader(error)
+    }
+}
+
+impl Display for RendererError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Shader(error) => write!(f, "Failed to load shader: {error}"),
+            Self::Limits {
+                limit,
+                required,
+                available,
+            } => write!(
+                f,
+                "Unsupported graphics adapter: {limit} is {available}, but {required} is required"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
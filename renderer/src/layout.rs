@@ -74,6 +74,7 @@ impl Layout {
                 height: legend_height,
             },
             target: legend_target,
+            flip_y: false,
         };
 
         // grow time target by gobbling free space on the left
@@ -96,6 +97,7 @@ impl Layout {
                 height: time_height,
             },
             target: time_target,
+            flip_y: false,
         };
 
         Self {
@@ -117,6 +119,48 @@ impl Layout {
             time: None,
         }
     }
+
+    /// Adds a `time`-viewport overlaid onto the bottom-left corner of the screen
+    /// (above a bar of height `bar_height`),
+    /// if this [Layout] does not already contain space for the time.
+    pub fn with_overlay_time(
+        self,
+        screen_size: (u32, u32),
+        time_height: f32,
+        bar_height: f32,
+    ) -> Self {
+        if self.time.is_some() {
+            return self;
+        }
+
+        let time_target = ViewportTarget {
+            x: -1. + Self::PADDING,
+            y: -1. + bar_height + Self::PADDING,
+            width: 0.4 - 2. * Self::PADDING,
+            height: 0.1 - 2. * Self::PADDING,
+        };
+
+        // calculate appropriate time width
+        let time_width = calculate_width(
+            time_height,
+            screen_size,
+            (time_target.width, time_target.height),
+        );
+
+        Self {
+            time: Some(ViewportProjection {
+                source: ViewportSource {
+                    x: 0.,
+                    y: 0.,
+                    width: time_width,
+                    height: time_height,
+                },
+                target: time_target,
+                flip_y: false,
+            }),
+            ..self
+        }
+    }
 }
 
 /// Gets a [ViewportProjection] that fits and centers the [ViewportSource] into the [ViewportTarget].
@@ -135,6 +179,7 @@ fn fit_and_center(
     let projection = ViewportProjection {
         source,
         target: center_in(target, size),
+        flip_y: false,
     };
     shrink_target_by_source_padding(projection, padding_y)
 }
@@ -184,7 +229,11 @@ fn center_in(bounds: ViewportTarget, size: (f32, f32)) -> ViewportTarget {
 /// Shrinks the target by the specified `padding_y` in source-space.
 /// Will keep aspect ratio.
 fn shrink_target_by_source_padding(
-    ViewportProjection { source, target }: ViewportProjection,
+    ViewportProjection {
+        source,
+        target,
+        flip_y,
+    }: ViewportProjection,
     padding_y: f32,
 ) -> ViewportProjection {
     let px = padding_y / source.width * target.width;
@@ -198,6 +247,7 @@ fn shrink_target_by_source_padding(
             width: target.width - px,
             height: target.height - py,
         },
+        flip_y,
     }
 }
 
@@ -1,7 +1,12 @@
 pub mod buffer_updater;
 pub mod component;
+pub mod error;
 pub mod globals;
 pub mod layout;
+pub mod quality;
 pub mod renderer;
 pub mod shaders;
+pub mod split;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod viewport;
@@ -0,0 +1,15 @@
+/// The quality with which the [Renderer][crate::renderer::Renderer] draws.
+///
+/// The renderer never uses multisampling,
+/// so both qualities render without MSAA.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderQuality {
+    /// Pixel-exact output (e.g., for publication figures):
+    /// line endpoints and widths are snapped to whole pixels
+    /// and text is placed on whole pixels.
+    Crisp,
+    /// Geometry and text are placed exactly as specified,
+    /// which may blur thin lines at small sizes.
+    #[default]
+    Smooth,
+}
@@ -1,39 +1,61 @@
-use naviz_state::{config::Config, state::State};
+use std::time::Duration;
+
+use naviz_state::{
+    config::Config,
+    state::{AtomsDiff, State},
+};
 use wgpu::{Device, Queue, RenderPass, TextureFormat};
 
 use crate::{
     buffer_updater::BufferUpdater,
     component::{
         atoms::Atoms,
+        background::{Background, BackgroundImage},
         drawable::{Drawable, Hidable},
+        ghosts::Ghosts,
         legend::Legend,
         machine::Machine,
+        progress::Progress,
         time::Time,
         updatable::Updatable,
         ComponentInit,
     },
+    error::RendererError,
     globals::Globals,
     layout::Layout,
-    shaders::{create_composer, load_default_shaders},
-    viewport::{ViewportProjection, ViewportSource},
+    quality::RenderQuality,
+    shaders::{
+        check_limits, create_composer, load_default_shaders, validate_shaders, COMPONENT_SHADERS,
+    },
+    viewport::{Camera, ViewportProjection, ViewportSource},
 };
 
 /// The main renderer, which renders the visualization output
 pub struct Renderer {
     globals: Globals,
 
+    background: Background,
     machine: Machine,
+    ghosts: Ghosts,
     atoms: Atoms,
     legend: Hidable<Legend>,
     time: Hidable<Time>,
+    progress: Hidable<Progress>,
     screen_resolution: (u32, u32),
     /// Whether to force the [content-only-layout][Layout::new_content_only].
     /// Independent of the selected style.
     force_zen: bool,
+    /// The [Camera] looking at the content, or [None] to show the whole content.
+    camera: Option<Camera>,
+    /// Whether to overlay the time and a [Progress]-bar onto the content.
+    overlay: bool,
 }
 
 impl Renderer {
-    /// Creates a new [Renderer] on the passed [Device] and for the passed [TextureFormat]
+    /// Creates a new [Renderer] on the passed [Device] and for the passed [TextureFormat].
+    ///
+    /// Panics if the renderer could not be set up.
+    /// See [Renderer::try_new] for a non-panicking version.
     pub fn new(
         device: &Device,
         queue: &Queue,
@@ -42,8 +64,25 @@ impl Renderer {
         state: &State,
         screen_resolution: (u32, u32),
     ) -> Self {
-        let mut composer =
-            load_default_shaders(create_composer()).expect("Failed to load default shader modules");
+        Self::try_new(device, queue, format, config, state, screen_resolution)
+            .unwrap_or_else(|e| panic!("Failed to create renderer: {e}"))
+    }
+
+    /// Tries to create a new [Renderer] on the passed [Device] and for the passed [TextureFormat].
+    ///
+    /// Returns an error if the shaders could not be composed
+    /// or if the [Device] does not support the required limits.
+    pub fn try_new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        config: &Config,
+        state: &State,
+        screen_resolution: (u32, u32),
+    ) -> Result<Self, RendererError> {
+        check_limits(&device.limits())?;
+        let mut composer = load_default_shaders(create_composer())?;
+        validate_shaders(&mut composer, &COMPONENT_SHADERS)?;
 
         let globals = Globals::new(device);
 
@@ -53,7 +92,18 @@ impl Renderer {
             time,
         } = get_layout(config, screen_resolution, false);
 
-        Self {
+        Ok(Self {
+            background: Background::new(ComponentInit {
+                device,
+                queue,
+                format,
+                globals: &globals,
+                shader_composer: &mut composer,
+                config,
+                state,
+                viewport_projection: content,
+                screen_resolution,
+            }),
             machine: Machine::new(ComponentInit {
                 device,
                 queue,
@@ -65,6 +115,17 @@ impl Renderer {
                 viewport_projection: content,
                 screen_resolution,
             }),
+            ghosts: Ghosts::new(ComponentInit {
+                device,
+                queue,
+                format,
+                globals: &globals,
+                shader_composer: &mut composer,
+                config,
+                state,
+                viewport_projection: content,
+                screen_resolution,
+            }),
             atoms: Atoms::new(ComponentInit {
                 device,
                 queue,
@@ -100,10 +161,24 @@ impl Renderer {
                 screen_resolution,
             }))
             .with_visibility(time.is_some()),
+            progress: Hidable::new(Progress::new(ComponentInit {
+                device,
+                queue,
+                format,
+                globals: &globals,
+                shader_composer: &mut composer,
+                config,
+                state,
+                viewport_projection: Progress::VIEWPORT_PROJECTION,
+                screen_resolution,
+            }))
+            .with_visibility(false),
             globals,
             screen_resolution,
             force_zen: false,
-        }
+            camera: None,
+            overlay: false,
+        })
     }
 
     /// Whether to force the [content-only-layout][Layout::new_content_only].
@@ -112,6 +187,75 @@ impl Renderer {
         self.force_zen = force_zen;
     }
 
+    /// Sets the [Camera] looking at the content (i.e., the machine and the atoms),
+    /// or [None] to fit the whole content.
+    /// Requires a call to [Self::update_full] to take effect.
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    /// Whether to overlay the time and a [Progress]-bar onto the content.
+    /// If the layout does not contain space for the time,
+    /// the time will be drawn in the bottom-left corner above the bar.
+    /// Requires a call to [Self::update_full] to take effect.
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Sets the [RenderQuality] to draw with.
+    /// Requires a call to [Self::update_full] to take effect on the grid.
+    pub fn set_quality(&mut self, device: &Device, queue: &Queue, quality: RenderQuality) {
+        self.machine.set_quality(device, queue, quality);
+        self.legend.set_quality(device, queue, quality);
+        self.time.set_quality(device, queue, quality);
+    }
+
+    /// Sets the duration over which the legend, time, and [Progress]-bar
+    /// fade in or out when their visibility changes.
+    /// A zero duration (the default) toggles visibility instantly.
+    /// Running fades are advanced using [Self::animate].
+    pub fn set_fade_duration(&mut self, fade_duration: Duration) {
+        self.legend.set_fade_duration(fade_duration);
+        self.time.set_fade_duration(fade_duration);
+        self.progress.set_fade_duration(fade_duration);
+    }
+
+    /// Advances running fades (see [Self::set_fade_duration]) by the `elapsed` time.
+    /// Returns whether any fade is still running.
+    #[must_use]
+    pub fn animate(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        elapsed: Duration,
+    ) -> bool {
+        let legend = self.legend.advance(elapsed);
+        let time = self.time.advance(elapsed);
+        let progress = self.progress.advance(elapsed);
+        self.apply_opacity(updater, device, queue);
+        legend || time || progress
+    }
+
+    /// Sets the `progress` (`0` to `1`) displayed by the [Progress]-bar
+    /// (see [Self::set_overlay]).
+    pub fn set_progress(&mut self, updater: &mut impl BufferUpdater, progress: f32) {
+        self.progress.set_progress(updater, progress);
+    }
+
+    /// Sets the image to draw behind the machine
+    /// (see [BackgroundConfig][naviz_state::config::BackgroundConfig]).
+    /// Pass [None] to remove the current image.
+    pub fn set_background_image(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        image: Option<&BackgroundImage>,
+    ) {
+        self.background.set_image(updater, device, queue, image);
+    }
+
     /// Updates this [Renderer] to resemble the new [State].
     /// See [Updatable::update].
     pub fn update(
@@ -122,12 +266,37 @@ impl Renderer {
         config: &Config,
         state: &State,
     ) {
+        self.background
+            .update(updater, device, queue, config, state);
         self.machine.update(updater, device, queue, config, state);
+        self.ghosts.update(updater, device, queue, config, state);
         self.atoms.update(updater, device, queue, config, state);
         self.legend.update(updater, device, queue, config, state);
         self.time.update(updater, device, queue, config, state);
     }
 
+    /// Updates this [Renderer] to resemble the new [State],
+    /// where only the atoms in `diff` changed compared to the previous [State].
+    /// See [Updatable::update].
+    pub fn update_changed(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        config: &Config,
+        state: &State,
+        diff: &AtomsDiff,
+    ) {
+        self.background
+            .update(updater, device, queue, config, state);
+        self.machine.update(updater, device, queue, config, state);
+        self.ghosts.update(updater, device, queue, config, state);
+        self.atoms
+            .update_changed(updater, device, queue, config, state, diff);
+        self.legend.update(updater, device, queue, config, state);
+        self.time.update(updater, device, queue, config, state);
+    }
+
     /// Updates this [Renderer] to resemble the new [State] and [Config].
     /// See [Updatable::update_full].
     pub fn update_full(
@@ -138,14 +307,29 @@ impl Renderer {
         config: &Config,
         state: &State,
     ) {
+        let mut layout = get_layout(config, self.screen_resolution, self.force_zen);
+        if let Some(camera) = self.camera {
+            layout.content = layout.content.with_camera(camera);
+        }
+        if self.overlay {
+            layout = layout.with_overlay_time(
+                self.screen_resolution,
+                config.time.font.size * 1.2,
+                Progress::HEIGHT,
+            );
+        }
         let Layout {
             content,
             legend,
             time,
-        } = get_layout(config, self.screen_resolution, self.force_zen);
+        } = layout;
 
+        self.background
+            .update_full(updater, device, queue, config, state, content);
         self.machine
             .update_full(updater, device, queue, config, state, content);
+        self.ghosts
+            .update_full(updater, device, queue, config, state, content);
         self.atoms
             .update_full(updater, device, queue, config, state, content);
         self.legend.update_full(
@@ -166,6 +350,23 @@ impl Renderer {
             time.unwrap_or(ViewportProjection::identity()),
         );
         self.time.set_visible(time.is_some());
+        self.progress.update_full(
+            updater,
+            device,
+            queue,
+            config,
+            state,
+            Progress::VIEWPORT_PROJECTION,
+        );
+        self.progress.set_visible(self.overlay);
+        self.apply_opacity(updater, device, queue);
+    }
+
+    /// Passes the current opacities of all [Hidable]s to their children
+    fn apply_opacity(&mut self, updater: &mut impl BufferUpdater, device: &Device, queue: &Queue) {
+        self.legend.apply_opacity(updater, device, queue);
+        self.time.apply_opacity(updater, device, queue);
+        self.progress.apply_opacity(updater, device, queue);
     }
 
     /// Updates the viewport resolution of this [Renderer]
@@ -188,8 +389,11 @@ impl Renderer {
     pub fn draw(&self, render_pass: &mut RenderPass<'_>) {
         self.rebind(render_pass);
 
+        self.background.draw::<true>(render_pass, self.rebind_fn());
         self.machine.draw::<true>(render_pass, self.rebind_fn());
+        self.ghosts.draw::<true>(render_pass, self.rebind_fn());
         self.atoms.draw::<true>(render_pass, self.rebind_fn());
+        self.progress.draw::<true>(render_pass, self.rebind_fn());
         self.legend.draw::<false>(render_pass, self.rebind_fn()); // No rebind: time does not need globals
         self.time.draw::<false>(render_pass, self.rebind_fn());
     }
@@ -207,20 +411,48 @@ impl Renderer {
     }
 }
 
+/// Gets the [ViewportProjection] of the content (i.e., the machine and the atoms)
+/// as drawn by a [Renderer] with the passed `screen_resolution` for the passed [Config].
+/// `force_zen` and `camera` have the same meaning as in [Renderer::set_force_zen]
+/// and [Renderer::set_camera].
+///
+/// Positions on the screen can be mapped back into content-coordinates
+/// using [ViewportProjection::unproject].
+pub fn content_projection(
+    config: &Config,
+    screen_resolution: (u32, u32),
+    force_zen: bool,
+    camera: Option<Camera>,
+) -> ViewportProjection {
+    let content = get_layout(config, screen_resolution, force_zen).content;
+    match camera {
+        Some(camera) => content.with_camera(camera),
+        None => content,
+    }
+}
+
 /// Gets the [Layout] to use based on the passed [Config].
 /// Will detect which [Layout] to use based on which parts should be displayed in the [Config].
 /// If `force_content_only` is `true`, will always use [Layout::new_content_only].
 fn get_layout(config: &Config, screen_resolution: (u32, u32), force_content_only: bool) -> Layout {
     const LEGEND_HEIGHT: f32 = 1024.;
-
-    // content source
-    let content = ViewportSource::from_tl_br(config.content_extent.0, config.content_extent.1);
+    /// The minimum size of the content (e.g., for a single atom)
+    const MIN_CONTENT_SIZE: f32 = 1.;
+
+    // content source (including the configured padding)
+    let ((left, top), (right, bottom)) = config.content_extent;
+    let padding = config.padding;
+    let content = ViewportSource::from_tl_br(
+        (left - padding.left, top - padding.top),
+        (right + padding.right, bottom + padding.bottom),
+    )
+    .with_min_size(MIN_CONTENT_SIZE);
 
     // Calculate dynamic content padding based on grid legend configuration and content dimensions
     let content_padding_y =
         calculate_content_padding(&config.machine.grid.legend, content.width, content.height);
 
-    if force_content_only || (!config.display_time() && !config.display_sidebar()) {
+    let layout = if force_content_only || (!config.display_time() && !config.display_sidebar()) {
         // no time and no sidebar
         Layout::new_content_only(screen_resolution, content, content_padding_y)
     } else {
@@ -232,6 +464,11 @@ fn get_layout(config: &Config, screen_resolution: (u32, u32), force_content_only
             LEGEND_HEIGHT,
             config.time.font.size * 1.2,
         )
+    };
+
+    Layout {
+        content: layout.content.with_flip_y(config.flip_y),
+        ..layout
     }
 }
 
@@ -324,6 +561,156 @@ mod test {
         );
     }
 
+    /// Unprojecting a projected content-position should yield the original position.
+    #[test]
+    fn content_projection_unprojects() {
+        let mut config = Config::example();
+        for flip_y in [false, true] {
+            config.flip_y = flip_y;
+            let projection = content_projection(&config, (1920, 1080), false, None);
+            let matrix = glam::Mat4::from(projection);
+            for position in [(0., 0.), (12., 34.), (-5., 60.)] {
+                let projected = matrix.project_point3(glam::Vec3::new(position.0, position.1, 0.));
+                let (x, y) = projection.unproject((projected.x, projected.y));
+                assert!(
+                    (x - position.0).abs() < 1e-3 && (y - position.1).abs() < 1e-3,
+                    "Unprojected {position:?} to {:?} (flip_y: {flip_y})",
+                    (x, y)
+                );
+            }
+        }
+    }
+
+    /// A [Camera] should center its point and magnify the fitted content by its zoom.
+    #[test]
+    fn camera_centers_content() {
+        let config = Config::example();
+        let fitted = content_projection(&config, (1920, 1080), false, None);
+        let camera = Camera {
+            center: (12., 34.),
+            zoom: 4.,
+        };
+        let projection = content_projection(&config, (1920, 1080), false, Some(camera));
+
+        let (x, y) = projection.unproject((
+            projection.target.x + projection.target.width / 2.,
+            projection.target.y + projection.target.height / 2.,
+        ));
+        assert!(
+            (x - 12.).abs() < 1e-3 && (y - 34.).abs() < 1e-3,
+            "Camera center is not centered: {:?}",
+            (x, y)
+        );
+        assert_eq!(projection.source.width, fitted.source.width / 4.);
+        assert_eq!(projection.source.height, fitted.source.height / 4.);
+    }
+
+    #[test]
+    fn degenerate_content_expanded() {
+        let mut config = Config::example();
+        config.content_extent = ((5., 7.), (5., 7.));
+
+        let layout = get_layout(&config, (1920, 1080), false);
+        let source = layout.content.source;
+        assert!(source.width > 0. && source.height > 0., "{source:?}");
+        assert_eq!(
+            (source.x + source.width / 2., source.y + source.height / 2.),
+            (5., 7.),
+            "Expanded content is not centered"
+        );
+        assert!(
+            glam::Mat4::from(layout.content).is_finite(),
+            "Projection is not finite"
+        );
+    }
+
+    #[test]
+    fn configured_padding_shifts_content() {
+        let mut config = Config::example();
+        let unpadded = get_layout(&config, (1920, 1080), false).content.source;
+
+        config.padding.left = 20.;
+        let padded = get_layout(&config, (1920, 1080), false).content.source;
+
+        assert_eq!(padded.x, unpadded.x - 20.);
+        assert_eq!(padded.width, unpadded.width + 20.);
+        assert_eq!(padded.y, unpadded.y, "Left padding changed y-origin");
+        assert_eq!(padded.height, unpadded.height);
+    }
+
+    #[test]
+    fn minimal_example_layout() {
+        let config = Config::example_minimal();
+        let state = State::example_minimal();
+
+        let layout = get_layout(&config, (64, 64), false);
+        assert!(layout.legend.is_none(), "Minimal example has a legend");
+        assert!(layout.time.is_none(), "Minimal example has a time");
+
+        let target = layout.content.target;
+        assert!(target.width > 0. && target.height > 0., "Content is empty");
+        let (x, y) = state.atoms[0].position;
+        let atom = glam::Mat4::from(layout.content).transform_point3(glam::Vec3::new(x, y, 0.));
+        assert!(
+            (target.x..=target.x + target.width).contains(&atom.x)
+                && (target.y..=target.y + target.height).contains(&atom.y),
+            "Atom at {atom} is outside of the content"
+        );
+    }
+
+    /// The minimal example should render its atom at a small resolution.
+    #[test]
+    fn minimal_example_render() {
+        let (device, queue) = crate::skip_without_adapter!(device);
+        let resolution = (64, 64);
+        let config = Config::example_minimal();
+        let state = State::example_minimal();
+
+        let renderer = Renderer::new(
+            &device,
+            &queue,
+            TextureFormat::Rgba8Unorm,
+            &config,
+            &state,
+            resolution,
+        );
+        let frame = crate::test_utils::render(&device, &queue, resolution, |render_pass| {
+            renderer.draw(render_pass)
+        });
+
+        // Alpha of the pixel at the passed position in wgpu-coordinates
+        let alpha = |position: glam::Vec3| {
+            let x = ((position.x + 1.) / 2. * resolution.0 as f32) as usize;
+            let y = ((1. - position.y) / 2. * resolution.1 as f32) as usize;
+            frame[(y * resolution.0 as usize + x) * 4 + 3]
+        };
+        let (x, y) = state.atoms[0].position;
+        let atom = glam::Mat4::from(get_layout(&config, resolution, false).content)
+            .transform_point3(glam::Vec3::new(x, y, 0.));
+        assert_ne!(alpha(atom), 0, "Atom was not drawn");
+        assert_eq!(alpha(glam::Vec3::new(-1., 1., 0.)), 0, "Corner was drawn");
+    }
+
+    #[test]
+    fn flipped_y_axis_mirrors_content() {
+        let mut config = Config::example();
+        let atom = glam::Vec3::new(10., 20., 0.);
+
+        let down = get_layout(&config, (1920, 1080), false).content;
+        config.flip_y = true;
+        let up = get_layout(&config, (1920, 1080), false).content;
+
+        let down_position = glam::Mat4::from(down).transform_point3(atom);
+        let up_position = glam::Mat4::from(up).transform_point3(atom);
+        let midline = down.target.y + down.target.height / 2.;
+
+        assert!((down_position.x - up_position.x).abs() < 1e-5);
+        assert!(
+            ((down_position.y - midline) + (up_position.y - midline)).abs() < 1e-5,
+            "{down_position} is not mirrored to {up_position} across {midline}"
+        );
+    }
+
     #[test]
     fn calculate_content_padding_with_labels_and_numbers() {
         use naviz_state::config::{FontConfig, GridLegendConfig, HPosition, VPosition};
@@ -339,6 +726,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For content (800x600): aspect ratio = 0.75 < 0.83, so wide machine scaling applies
@@ -367,6 +755,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: false,
             display_numbers: false,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         let padding = calculate_content_padding(&grid_legend, 800.0, 600.0);
@@ -388,6 +777,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For content (800x600): aspect ratio = 0.75 < 0.83, so wide machine scaling applies
@@ -417,6 +807,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For content (800x600): aspect ratio = 0.75 < 0.83, so wide machine scaling applies
@@ -446,6 +837,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For very tall machine (100x2000): aspect ratio = 20.0 > 1.2, clamped to 4.0
@@ -470,6 +862,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For square machine (500x500): aspect ratio = 1.0, between 0.67 and 1.5, so no scaling
@@ -492,6 +885,7 @@ mod test {
             position: (VPosition::Bottom, HPosition::Left),
             display_labels: true,
             display_numbers: true,
+            precision: GridLegendConfig::DEFAULT_PRECISION,
         };
 
         // For moderately tall machine (100x300): aspect ratio = 3.0 > 1.2
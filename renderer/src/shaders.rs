@@ -5,7 +5,38 @@ use std::{borrow::Cow, collections::HashMap};
 use naga_oil::compose::{
     ComposableModuleDescriptor, Composer, ComposerError, NagaModuleDescriptor, ShaderDefValue,
 };
-use wgpu::{Device, ShaderModule, ShaderModuleDescriptor, ShaderSource};
+use wgpu::{Device, Limits, ShaderModule, ShaderModuleDescriptor, ShaderSource};
+
+use crate::error::RendererError;
+
+/// The shaders of the components as `(source, path)`.
+/// Used to [validate][validate_shaders] the shaders before creating any components.
+pub const COMPONENT_SHADERS: [(&str, &str); 4] = [
+    (
+        include_str!("./component/background.wgsl"),
+        "background.wgsl",
+    ),
+    (
+        include_str!("./component/primitive/circles.wgsl"),
+        "circles.wgsl",
+    ),
+    (
+        include_str!("./component/primitive/lines.wgsl"),
+        "lines.wgsl",
+    ),
+    (
+        include_str!("./component/primitive/shapes.wgsl"),
+        "shapes.wgsl",
+    ),
+];
+
+/// The number of bind groups used by the components
+/// (globals, viewport, and component-specific uniform).
+const REQUIRED_BIND_GROUPS: u32 = 3;
+
+/// The maximum number of vertex attributes used by any component
+/// (see [Lines][crate::component::primitive::lines::Lines]).
+const REQUIRED_VERTEX_ATTRIBUTES: u32 = 7;
 
 /// Helper to link a shader `source` using a [Composer]
 /// and then compile it on the [Device].
@@ -64,3 +95,93 @@ pub fn load_default_shaders(mut composer: Composer) -> Result<Composer, Box<Comp
 
     Ok(composer)
 }
+
+/// Links all passed `shaders` (as `(source, path)`) using the passed [Composer]
+/// without compiling them on a device.
+/// Returns the first error encountered.
+pub fn validate_shaders(
+    composer: &mut Composer,
+    shaders: &[(&str, &str)],
+) -> Result<(), Box<ComposerError>> {
+    for (source, path) in shaders {
+        composer.make_naga_module(NagaModuleDescriptor {
+            source,
+            file_path: path,
+            ..Default::default()
+        })?;
+    }
+    Ok(())
+}
+
+/// Checks whether the passed [Limits] (e.g., from [Device::limits])
+/// are sufficient for the renderer.
+pub fn check_limits(limits: &Limits) -> Result<(), RendererError> {
+    let checks = [
+        (
+            "max_bind_groups",
+            REQUIRED_BIND_GROUPS,
+            limits.max_bind_groups,
+        ),
+        (
+            "max_vertex_attributes",
+            REQUIRED_VERTEX_ATTRIBUTES,
+            limits.max_vertex_attributes,
+        ),
+    ];
+    for (limit, required, available) in checks {
+        if available < required {
+            return Err(RendererError::Limits {
+                limit,
+                required,
+                available,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The bundled shaders should link against the default modules.
+    #[test]
+    fn component_shaders_valid() {
+        let mut composer =
+            load_default_shaders(create_composer()).expect("Failed to load default shaders");
+        validate_shaders(&mut composer, &COMPONENT_SHADERS).expect("Invalid component shader");
+    }
+
+    /// A broken shader should yield an error instead of panicking.
+    #[test]
+    fn broken_shader() {
+        let mut composer =
+            load_default_shaders(create_composer()).expect("Failed to load default shaders");
+        let result = validate_shaders(
+            &mut composer,
+            &[(
+                "@vertex fn vs_main( -> @builtin(position) vec4<f32> {",
+                "broken.wgsl",
+            )],
+        );
+        assert!(result.is_err());
+    }
+
+    /// Insufficient limits should be reported.
+    #[test]
+    fn insufficient_limits() {
+        assert!(check_limits(&Limits::default()).is_ok());
+        let limits = Limits {
+            max_bind_groups: 2,
+            ..Limits::default()
+        };
+        assert!(matches!(
+            check_limits(&limits),
+            Err(RendererError::Limits {
+                limit: "max_bind_groups",
+                required: 3,
+                available: 2,
+            })
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+//! A renderer which renders two visualizations side by side.
+//! See [SplitRenderer].
+
+use naviz_state::{config::Config, state::State};
+use wgpu::{Device, Queue, RenderPass, TextureFormat};
+
+use crate::{buffer_updater::BufferUpdater, error::RendererError, renderer::Renderer};
+
+/// Renders two visualizations side by side (e.g., to compare two compilations).
+///
+/// Composes two [Renderer]s, which each render into one half of the target:
+/// the `left` into the left half and the `right` into the right half.
+/// Both halves are driven by their own [Config] and [State],
+/// which should be taken from their animators at the same time.
+pub struct SplitRenderer {
+    left: Renderer,
+    right: Renderer,
+    screen_resolution: (u32, u32),
+}
+
+impl SplitRenderer {
+    /// Creates a new [SplitRenderer] on the passed [Device] and for the passed [TextureFormat].
+    /// `left` and `right` contain the [Config] and [State] of the respective half.
+    ///
+    /// Panics if the renderers could not be set up.
+    /// See [SplitRenderer::try_new] for a non-panicking version.
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        left: (&Config, &State),
+        right: (&Config, &State),
+        screen_resolution: (u32, u32),
+    ) -> Self {
+        Self::try_new(device, queue, format, left, right, screen_resolution)
+            .unwrap_or_else(|e| panic!("Failed to create renderer: {e}"))
+    }
+
+    /// Tries to create a new [SplitRenderer].
+    /// See [SplitRenderer::new] and [Renderer::try_new].
+    pub fn try_new(
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        (left_config, left_state): (&Config, &State),
+        (right_config, right_state): (&Config, &State),
+        screen_resolution: (u32, u32),
+    ) -> Result<Self, RendererError> {
+        let (left_resolution, right_resolution) = split_resolution(screen_resolution);
+        Ok(Self {
+            left: Renderer::try_new(
+                device,
+                queue,
+                format,
+                left_config,
+                left_state,
+                left_resolution,
+            )?,
+            right: Renderer::try_new(
+                device,
+                queue,
+                format,
+                right_config,
+                right_state,
+                right_resolution,
+            )?,
+            screen_resolution,
+        })
+    }
+
+    /// The [Renderer] of the left half
+    pub fn left_mut(&mut self) -> &mut Renderer {
+        &mut self.left
+    }
+
+    /// The [Renderer] of the right half
+    pub fn right_mut(&mut self) -> &mut Renderer {
+        &mut self.right
+    }
+
+    /// Updates both halves to resemble their new [State]s.
+    /// See [Renderer::update].
+    pub fn update(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        (left_config, left_state): (&Config, &State),
+        (right_config, right_state): (&Config, &State),
+    ) {
+        self.left
+            .update(updater, device, queue, left_config, left_state);
+        self.right
+            .update(updater, device, queue, right_config, right_state);
+    }
+
+    /// Updates both halves to resemble their new [State]s and [Config]s.
+    /// See [Renderer::update_full].
+    pub fn update_full(
+        &mut self,
+        updater: &mut impl BufferUpdater,
+        device: &Device,
+        queue: &Queue,
+        (left_config, left_state): (&Config, &State),
+        (right_config, right_state): (&Config, &State),
+    ) {
+        self.left
+            .update_full(updater, device, queue, left_config, left_state);
+        self.right
+            .update_full(updater, device, queue, right_config, right_state);
+    }
+
+    /// Updates the viewport resolution of this [SplitRenderer].
+    /// Each half receives half of the width.
+    pub fn update_viewport(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        screen_resolution: (u32, u32),
+    ) {
+        self.screen_resolution = screen_resolution;
+        let (left, right) = split_resolution(screen_resolution);
+        self.left.update_viewport(device, queue, left);
+        self.right.update_viewport(device, queue, right);
+    }
+
+    /// Draws both halves to the passed [RenderPass].
+    ///
+    /// The render-pass is expected to cover a target of the `screen_resolution`
+    /// (see [SplitRenderer::update_viewport]).
+    /// The viewport of the render-pass is reset to the full target afterwards.
+    pub fn draw(&self, render_pass: &mut RenderPass<'_>) {
+        let (width, height) = self.screen_resolution;
+        let (left, right) = split_resolution(self.screen_resolution);
+
+        render_pass.set_viewport(0., 0., left.0 as f32, height as f32, 0., 1.);
+        self.left.draw(render_pass);
+        render_pass.set_viewport(left.0 as f32, 0., right.0 as f32, height as f32, 0., 1.);
+        self.right.draw(render_pass);
+
+        render_pass.set_viewport(0., 0., width as f32, height as f32, 0., 1.);
+    }
+}
+
+/// Splits the passed `screen_resolution` into the resolutions of the left and the right half.
+/// If the width is odd, the right half receives the additional pixel.
+fn split_resolution((width, height): (u32, u32)) -> ((u32, u32), (u32, u32)) {
+    let left = width / 2;
+    ((left, height), (width - left, height))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{skip_without_adapter, test_utils::render};
+
+    #[test]
+    fn split_resolutions() {
+        assert_eq!(split_resolution((512, 256)), ((256, 256), (256, 256)));
+        assert_eq!(split_resolution((5, 2)), ((2, 2), (3, 2)));
+    }
+
+    /// Both halves should be drawn into their side of the target.
+    #[test]
+    fn draws_both_halves() {
+        let (device, queue) = skip_without_adapter!(device);
+        let (width, height) = (512, 256);
+        let format = TextureFormat::Rgba8Unorm;
+        let config = Config::example();
+        let left_state = State::example();
+        let mut right_state = State::example();
+        right_state.atoms.truncate(1);
+
+        let renderer = SplitRenderer::new(
+            &device,
+            &queue,
+            format,
+            (&config, &left_state),
+            (&config, &right_state),
+            (width, height),
+        );
+        let frame = render(&device, &queue, (width, height), |render_pass| {
+            renderer.draw(render_pass)
+        });
+
+        // Whether any pixel in the column-range was drawn to (i.e., is not transparent)
+        let drawn = |columns: std::ops::Range<u32>| {
+            (0..height).any(|y| {
+                columns
+                    .clone()
+                    .any(|x| frame[((y * width + x) * 4 + 3) as usize] != 0)
+            })
+        };
+        assert!(drawn(0..width / 2), "Left half was not drawn");
+        assert!(drawn(width / 2..width), "Right half was not drawn");
+    }
+}
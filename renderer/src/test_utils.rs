@@ -0,0 +1,150 @@
+//! Helpers for tests which render on a GPU.
+//! Only available in tests or with the `test-utils`-feature.
+
+use std::sync::mpsc::channel;
+
+use wgpu::{
+    BufferDescriptor, BufferUsages, Color, CommandEncoderDescriptor, Device, DeviceDescriptor,
+    Extent3d, Instance, InstanceDescriptor, LoadOp, MapMode, Operations, Queue, RenderPass,
+    RenderPassColorAttachment, RenderPassDescriptor, RequestAdapterOptions, StoreOp,
+    TexelCopyBufferInfo, TexelCopyBufferLayout, TexelCopyTextureInfo, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+
+/// Creates a headless [Device] and [Queue],
+/// or [None] if no backend or adapter is available (e.g., on CI without a GPU)
+pub fn device() -> Option<(Device, Queue)> {
+    if !adapter_available() {
+        return None;
+    }
+    pollster::block_on(async {
+        let instance = Instance::new(InstanceDescriptor::new_without_display_handle());
+        let adapter = instance
+            .request_adapter(&RequestAdapterOptions {
+                force_fallback_adapter: false,
+                compatible_surface: None,
+                ..Default::default()
+            })
+            .await
+            .ok()?;
+        adapter
+            .request_device(&DeviceDescriptor::default())
+            .await
+            .ok()
+    })
+}
+
+/// Checks whether an adapter to render with is available (e.g., not on CI without a GPU)
+pub fn adapter_available() -> bool {
+    if Instance::enabled_backend_features().is_empty() {
+        return false;
+    }
+    let instance = Instance::new(InstanceDescriptor::new_without_display_handle());
+    pollster::block_on(instance.request_adapter(&Default::default())).is_ok()
+}
+
+/// Draws into a transparent [TextureFormat::Rgba8Unorm]-texture of the passed `size` using `draw`
+/// and reads back the RGBA-pixels row by row.
+///
+/// The width must be a multiple of `64` pixels,
+/// so that the rows do not need to be padded when copying.
+pub fn render(
+    device: &Device,
+    queue: &Queue,
+    (width, height): (u32, u32),
+    draw: impl FnOnce(&mut RenderPass<'_>),
+) -> Vec<u8> {
+    assert!(
+        (width * 4).is_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+        "Width of {width} pixels needs padded rows"
+    );
+    let texture = device.create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: (width * height * 4) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+    {
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &texture.create_view(&Default::default()),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::TRANSPARENT),
+                    store: StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+        draw(&mut render_pass);
+    }
+    encoder.copy_texture_to_buffer(
+        TexelCopyTextureInfo {
+            aspect: TextureAspect::All,
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = channel();
+    slice.map_async(MapMode::Read, move |result| tx.send(result).unwrap());
+    let _ = device.poll(wgpu::PollType::wait_indefinitely());
+    rx.recv().unwrap().expect("Failed to map buffer");
+    slice.get_mapped_range().to_vec()
+}
+
+/// Skips the current test (by returning from it) if no adapter is available.
+/// See [adapter_available].
+///
+/// When passed `device`, evaluates to a [Device] and [Queue] (see [device]) instead.
+#[macro_export]
+macro_rules! skip_without_adapter {
+    () => {
+        if !$crate::test_utils::adapter_available() {
+            eprintln!("No adapter available, skipping");
+            return;
+        }
+    };
+    (device) => {
+        match $crate::test_utils::device() {
+            Some(device) => device,
+            None => {
+                eprintln!("No adapter available, skipping");
+                return;
+            }
+        }
+    };
+}
@@ -17,6 +17,20 @@ use crate::buffer_updater::BufferUpdater;
 pub struct ViewportProjection {
     pub source: ViewportSource,
     pub target: ViewportTarget,
+    /// Mirror the `source` vertically,
+    /// i.e., map the [bottom][ViewportSource::bottom] of the `source`
+    /// to the top of the `target` instead of the other way around
+    pub flip_y: bool,
+}
+
+/// A camera looking at the content of a [ViewportProjection]:
+/// Shows the content around the `center`, magnified by `zoom`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// The point (in [source][ViewportSource]-coordinates) to show in the center
+    pub center: (f32, f32),
+    /// The magnification (`1` keeps the size of the [source][ViewportSource])
+    pub zoom: f32,
 }
 
 /// The source-coordinates of the viewport.
@@ -56,18 +70,71 @@ impl ViewportProjection {
                 width: 2.,
                 height: 2.,
             },
+            flip_y: false,
         }
     }
+
+    /// Maps the passed `point` from [target][ViewportTarget]-coordinates
+    /// back into [source][ViewportSource]-coordinates
+    /// (i.e., the inverse of this projection).
+    pub fn unproject(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let relative_x = (x - self.target.x) / self.target.width;
+        let relative_y = (y - self.target.y) / self.target.height;
+        // The target points upwards, the source downwards (unless flipped)
+        let relative_y = if self.flip_y {
+            relative_y
+        } else {
+            1. - relative_y
+        };
+        (
+            self.source.x + relative_x * self.source.width,
+            self.source.y + relative_y * self.source.height,
+        )
+    }
+
+    /// Looks at the [source][ViewportSource] through the passed [Camera]:
+    /// The source is shrunk by the [zoom][Camera::zoom] and moved to the [center][Camera::center],
+    /// while the [target][ViewportTarget] stays the same.
+    pub fn with_camera(self, camera: Camera) -> Self {
+        let width = self.source.width / camera.zoom;
+        let height = self.source.height / camera.zoom;
+        Self {
+            source: ViewportSource {
+                x: camera.center.0 - width / 2.,
+                y: camera.center.1 - height / 2.,
+                width,
+                height,
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether the y-axis of the source points upwards.
+    /// See [ViewportProjection::flip_y].
+    pub fn with_flip_y(self, flip_y: bool) -> Self {
+        Self { flip_y, ..self }
+    }
 }
 
 impl From<ViewportProjection> for Mat4 {
-    fn from(ViewportProjection { source, target }: ViewportProjection) -> Self {
+    fn from(
+        ViewportProjection {
+            source,
+            target,
+            flip_y,
+        }: ViewportProjection,
+    ) -> Self {
+        let (bottom, top) = if flip_y {
+            (source.top(), source.bottom())
+        } else {
+            (source.bottom(), source.top())
+        };
         // Content -> Between
         let from_content = glam::camera::rh::proj::directx::orthographic(
             source.left(),
             source.right(),
-            source.bottom(),
-            source.top(),
+            bottom,
+            top,
             -1.,
             1.,
         );
@@ -101,13 +168,15 @@ impl Default for ViewportTarget {
 }
 
 /// A viewport, which holds all uniform buffers unique to a viewport,
-/// such as the [ViewportProjection]-spec.
+/// such as the [ViewportProjection]-spec and the opacity.
 ///
 /// Will bind to group `1`.
 pub struct Viewport {
     bind_group: BindGroup,
     bind_group_layout: BindGroupLayout,
     projection_matrix: Buffer,
+    /// The opacity, padded to `16` bytes
+    opacity: Buffer,
 }
 
 impl Viewport {
@@ -122,26 +191,50 @@ impl Viewport {
             usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
         });
 
+        let opacity = device.create_buffer_init(&BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(&opacity_data(1.)),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        });
+
         let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
-            entries: &[BindGroupLayoutEntry {
-                binding: 0,
-                visibility: ShaderStages::VERTEX,
-                ty: BindingType::Buffer {
-                    ty: BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::VERTEX,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
             label: None,
         });
 
         let bind_group = device.create_bind_group(&BindGroupDescriptor {
             layout: &bind_group_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: projection_matrix.as_entire_binding(),
-            }],
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: projection_matrix.as_entire_binding(),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: opacity.as_entire_binding(),
+                },
+            ],
             label: None,
         });
 
@@ -149,6 +242,7 @@ impl Viewport {
             bind_group,
             bind_group_layout,
             projection_matrix,
+            opacity,
         }
     }
 
@@ -164,6 +258,12 @@ impl Viewport {
         );
     }
 
+    /// Sets the `opacity` (`0` to `1`) which is multiplied into the output color
+    /// of everything drawn in this [Viewport]
+    pub fn set_opacity(&mut self, updater: &mut impl BufferUpdater, opacity: f32) {
+        updater.write(&self.opacity, 0, &opacity_data(opacity));
+    }
+
     /// Binds this [Viewport] to group `1`
     pub fn bind(&self, render_pass: &mut RenderPass<'_>) {
         render_pass.set_bind_group(1, &self.bind_group, &[]);
@@ -175,6 +275,12 @@ impl Viewport {
     }
 }
 
+/// The data of the opacity-buffer for the passed `opacity`.
+/// Padded to `16` bytes to satisfy uniform buffer alignment.
+fn opacity_data(opacity: f32) -> [f32; 4] {
+    [opacity, 0., 0., 0.]
+}
+
 impl ViewportSource {
     /// Creates a [ViewportSource] from a given start-point and size.
     pub fn from_point_size(from: (f32, f32), size: (f32, f32)) -> Self {
@@ -196,6 +302,27 @@ impl ViewportSource {
         }
     }
 
+    /// Expands this [ViewportSource] to be at least `min_size` wide and high,
+    /// keeping its center.
+    /// A degenerate (e.g., zero-sized) [ViewportSource] would result in a non-finite projection.
+    pub fn with_min_size(self, min_size: f32) -> Self {
+        let expand = |start: f32, size: f32| {
+            if size < min_size {
+                (start + (size - min_size) / 2., min_size)
+            } else {
+                (start, size)
+            }
+        };
+        let (x, width) = expand(self.x, self.width);
+        let (y, height) = expand(self.y, self.height);
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
     /// The left edge (minimum `x`) of this [ViewportSource]
     pub fn left(&self) -> f32 {
         self.x
@@ -215,4 +342,9 @@ impl ViewportSource {
     pub fn bottom(&self) -> f32 {
         self.height + self.y
     }
+
+    /// The center of this [ViewportSource]
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2., self.y + self.height / 2.)
+    }
 }
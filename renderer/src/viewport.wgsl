@@ -2,3 +2,11 @@
 
 @group(1) @binding(0)
 var<uniform> viewport_projection: mat4x4<f32>;
+
+@group(1) @binding(1)
+var<uniform> viewport_opacity: f32;
+
+// Multiplies the opacity of the viewport into the alpha of the passed color
+fn with_opacity(color: vec4<f32>) -> vec4<f32> {
+	return vec4<f32>(color.rgb, color.a * viewport_opacity);
+}
@@ -11,6 +11,8 @@ pub enum Error {
     ParseError(usize, ParseErrorInner),
     ConfigReadError(naviz_parser::config::error::Error),
     NotRemovableError,
+    #[cfg(feature = "watch")]
+    WatchError(notify::Error),
 }
 
 impl Error {
@@ -15,11 +15,19 @@ use std::{
 use directories::ProjectDirs;
 use error::{Error, Result};
 use include_dir::{include_dir, Dir};
-use naviz_parser::config::{generic::Config, machine::MachineConfig, visual::VisualConfig};
+use naviz_parser::config::{
+    generic::{get_item, get_item_optional, Config},
+    machine::MachineConfig,
+    parser::{self, ConfigItem, Value},
+    visual::VisualConfig,
+};
+use naviz_parser::normalize_source;
 #[cfg(test)]
 use tempfile::TempDir;
 
 pub mod error;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 static BUNDLED_MACHINES: Dir = include_dir!("$CARGO_MANIFEST_DIR/../configs/machines");
 static BUNDLED_STYLES: Dir = include_dir!("$CARGO_MANIFEST_DIR/../configs/styles");
@@ -28,7 +36,13 @@ const MACHINES_SUBDIR: &str = "machines";
 const STYLES_SUBDIR: &str = "styles";
 
 /// A repository of config files.
-pub struct Repository(HashMap<String, RepositoryEntry>);
+pub struct Repository {
+    /// The entries of this repository by `id`
+    entries: HashMap<String, RepositoryEntry>,
+    /// The watcher for changes in the user-directory, if watching is enabled
+    #[cfg(feature = "watch")]
+    watcher: Option<watch::RepositoryWatcher>,
+}
 
 /// The project directories for this application
 #[cfg(not(test))]
@@ -63,13 +77,17 @@ fn reset_temp_dir() {
 impl Repository {
     /// Creates a new empty repository
     pub fn empty() -> Self {
-        Self(Default::default())
+        Self {
+            entries: Default::default(),
+            #[cfg(feature = "watch")]
+            watcher: None,
+        }
     }
 
     /// Loads the passed bundled config into the passed [Repository]
     fn load_bundled(mut self, bundled: &Dir<'static>) -> Result<Self> {
-        self.0 = insert_results(
-            self.0,
+        self.entries = insert_results(
+            self.entries,
             bundled.files().map(|f| {
                 RepositoryEntry::new_with_id(
                     f.path()
@@ -84,6 +102,19 @@ impl Repository {
         Ok(self)
     }
 
+    /// Creates a new repository from the passed `(id, contents)`-pairs.
+    /// The contents are kept in memory; the filesystem is not accessed.
+    pub fn from_entries(entries: impl IntoIterator<Item = (String, Vec<u8>)>) -> Result<Self> {
+        let mut repository = Self::empty();
+        repository.entries = insert_results(
+            repository.entries,
+            entries.into_iter().map(|(id, contents)| {
+                RepositoryEntry::new_with_id(id, RepositorySource::InMemory(contents))
+            }),
+        )?;
+        Ok(repository)
+    }
+
     /// Loads the bundled machines into the passed [Repository]
     pub fn bundled_machines(self) -> Result<Self> {
         self.load_bundled(&BUNDLED_MACHINES)
@@ -122,8 +153,8 @@ impl Repository {
     /// Loads the configs from the passed `subdir` of the user-directory
     /// into the passed [Repository]
     fn load_user_dir(mut self, subdir: &str) -> Result<Self> {
-        self.0 = insert_results(
-            self.0,
+        self.entries = insert_results(
+            self.entries,
             Self::user_dir(subdir)?
                 .read_dir()
                 .map_err(Error::IoError)?
@@ -161,12 +192,9 @@ impl Repository {
         self.load_user_dir(STYLES_SUBDIR)
     }
 
-    /// Imports a `file` into the passed `subdir` in the user-directory.
-    /// Will validate that the config can be parsed into a valid `C`.
-    fn import_to_user_dir<C>(&mut self, subdir: &str, file: &Path) -> Result<()>
-    where
-        Config: TryInto<C, Error = naviz_parser::config::error::Error>,
-    {
+    /// Imports a `file` into the user-directory.
+    /// Will validate that the config can be parsed into a valid config of the passed [ConfigKind].
+    fn import_to_user_dir(&mut self, kind: ConfigKind, file: &Path) -> Result<()> {
         if !file.is_file() {
             return Err(Error::IoError(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -182,16 +210,21 @@ impl Repository {
         // Create temporary entry with the source path to check if the config is valid
         let entry = RepositoryEntry::new(RepositorySource::UserDir(file.to_owned()))?;
         // Ensure the config is valid (i.e., can be parsed correctly)
-        entry
-            .contents_as_config()?
-            .try_into()
-            .map_err(Error::ConfigReadError)?;
+        check_config_bytes(&entry.contents()?, kind)?;
 
         // Import: Copy to target path
-        let target_path = Self::user_dir(subdir)?.join(file.file_name().unwrap());
-        fs::copy(file, &target_path).map_err(Error::IoError)?;
+        let target_path = Self::user_dir(kind.subdir())?.join(file.file_name().unwrap());
+        let resolved = match (kind, file.parent()) {
+            (ConfigKind::Style, Some(dir)) => resolve_background_image(&entry.contents()?, dir)?,
+            _ => None,
+        };
+        match resolved {
+            Some(contents) => fs::write(&target_path, contents),
+            None => fs::copy(file, &target_path).map(|_| ()),
+        }
+        .map_err(Error::IoError)?;
 
-        self.0.insert(
+        self.entries.insert(
             id,
             // New repository entry with correct target path
             RepositoryEntry::new(RepositorySource::UserDir(target_path))?,
@@ -203,23 +236,25 @@ impl Repository {
     /// Import a machine into the user-directory.
     /// Will validate that the config can be parsed into a valid [MachineConfig].
     pub fn import_machine_to_user_dir(&mut self, file: &Path) -> Result<()> {
-        self.import_to_user_dir::<MachineConfig>(MACHINES_SUBDIR, file)
+        self.import_to_user_dir(ConfigKind::Machine, file)
     }
 
     /// Import a style into the user-directory.
     /// Will validate that the config can be parsed into a valid [VisualConfig].
+    /// A relative background image path is resolved relative to the original `file`,
+    /// as the image is not imported.
     pub fn import_style_to_user_dir(&mut self, file: &Path) -> Result<()> {
-        self.import_to_user_dir::<VisualConfig>(STYLES_SUBDIR, file)
+        self.import_to_user_dir(ConfigKind::Style, file)
     }
 
     /// Delete an imported config from the user dir.
     pub fn remove_from_user_dir(&mut self, id: &str) -> Result<()> {
-        let (id, entry) = self.0.remove_entry(id).ok_or(Error::IdError)?;
+        let (id, entry) = self.entries.remove_entry(id).ok_or(Error::IdError)?;
 
-        let RepositorySource::UserDir(path) = entry.source else {
+        let Some(path) = entry.source.user_dir_path().map(Path::to_path_buf) else {
             // Not imported from user-dir
             // => add back entry and return error
-            self.0.insert(id, entry);
+            self.entries.insert(id, entry);
             return Err(Error::NotRemovableError);
         };
 
@@ -228,16 +263,64 @@ impl Repository {
         Ok(())
     }
 
+    /// The disk usage of the entries imported into the user-directory:
+    /// `(id, size in bytes)`-pairs, sorted by `id`.
+    /// Entries which are not stored in the user-directory (e.g., bundled configs) are not included.
+    /// See [Repository::user_dir_total_usage] for the sum of all sizes.
+    pub fn user_dir_usage(&self) -> Result<Vec<(String, u64)>> {
+        let mut usage = self
+            .entries
+            .iter()
+            .filter_map(|(id, entry)| Some((id, entry.source.user_dir_path()?)))
+            .map(|(id, path)| {
+                let size = fs::metadata(path).map_err(Error::IoError)?.len();
+                Ok((id.clone(), size))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        usage.sort();
+        Ok(usage)
+    }
+
+    /// The total disk usage (in bytes) of the entries imported into the user-directory.
+    /// See [Repository::user_dir_usage].
+    pub fn user_dir_total_usage(&self) -> Result<u64> {
+        Ok(self
+            .user_dir_usage()?
+            .into_iter()
+            .map(|(_, size)| size)
+            .sum())
+    }
+
     /// The list of entries of this repository: `(id, name, removable)`-pairs
     pub fn list(&self) -> impl Iterator<Item = (&str, &str, bool)> {
-        self.0
+        self.entries
             .iter()
             .map(|(id, entry)| (id.as_str(), entry.name(), entry.source.is_removable()))
     }
 
+    /// The list of entries of this repository including their categories:
+    /// `(id, name, category, removable)`-pairs.
+    /// The category is read from the optional `category`-property of the config.
+    pub fn list_with_categories(&self) -> impl Iterator<Item = (&str, &str, Option<&str>, bool)> {
+        self.entries.iter().map(|(id, entry)| {
+            (
+                id.as_str(),
+                entry.name(),
+                entry.category(),
+                entry.source.is_removable(),
+            )
+        })
+    }
+
+    /// The path of the entry with `id` in the user-directory
+    /// or [None] if there is no such entry or it is not stored in the user-directory.
+    pub fn user_dir_path(&self, id: &str) -> Option<&Path> {
+        self.entries.get(id)?.source.user_dir_path()
+    }
+
     /// Checks whether the repository has an entry with `id`
     pub fn has(&self, id: &str) -> bool {
-        self.0.contains_key(id)
+        self.entries.contains_key(id)
     }
 
     /// Tries to get the raw contents of the entry with the passed `id`.
@@ -247,7 +330,7 @@ impl Repository {
     /// - `Some(Err)`: An entry exists, but failed to load the data
     /// - `Some(Ok)`: The data of the found entry
     pub fn get_raw<'a>(&'a self, id: &str) -> Option<Result<Cow<'a, [u8]>>> {
-        self.0.get(id).map(|e| e.contents())
+        self.entries.get(id).map(|e| e.contents())
     }
 
     /// Tries to get the contents of the entry with the passed `id` as some [Config].
@@ -260,36 +343,146 @@ impl Repository {
     where
         Config: TryInto<C, Error = naviz_parser::config::error::Error>,
     {
-        self.0.get(id).map(|e| {
+        self.entries.get(id).map(|e| {
             e.contents_as_config()?
                 .try_into()
                 .map_err(Error::ConfigReadError)
         })
     }
 
-    /// Try to get any config from this repository
+    /// Tries to get every entry of this repository as some [Config]
+    /// (see [Repository::get]) and collects the failures:
+    /// `(id, error)`-pairs, sorted by `id`.
+    /// An empty result means that every entry in [Repository::list] can be loaded.
+    pub fn verify<C>(&self) -> Vec<(String, Error)>
+    where
+        Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+    {
+        self.verify_where::<C>(|_| true)
+    }
+
+    /// Like [Repository::verify], but only tries to get the entries stored in the user-directory
+    /// (see [Repository::user_dir_path]); other entries are not loaded at all.
+    pub fn verify_user_dir<C>(&self) -> Vec<(String, Error)>
+    where
+        Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+    {
+        self.verify_where::<C>(|entry| entry.source.user_dir_path().is_some())
+    }
+
+    /// Tries to get every entry matching the `filter` as some [Config]
+    /// and collects the failures (see [Repository::verify]).
+    fn verify_where<C>(&self, filter: impl Fn(&RepositoryEntry) -> bool) -> Vec<(String, Error)>
+    where
+        Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+    {
+        let mut failures: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| filter(entry))
+            .filter_map(|(id, _)| Some((id.clone(), self.get::<C>(id)?.err()?)))
+            .collect();
+        failures.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        failures
+    }
+
+    /// Tries to get the entry with the passed `patch_id` layered over the entry with the passed `base_id`
+    /// as some [Config].
+    /// Values present in the patch override the base, while absent values are inherited
+    /// (see [Config::merge]).
+    ///
+    /// Returns:
+    /// - `None`: No entry with the passed `base_id` or `patch_id` exists
+    /// - `Some(Err)`: Both entries exist, but failed to load the data or failed to convert to `C`
+    /// - `Some(Ok)`: The merged config of the found entries
+    pub fn get_with_base<C>(&self, base_id: &str, patch_id: &str) -> Option<Result<C>>
+    where
+        Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+    {
+        let base = self.entries.get(base_id)?;
+        let patch = self.entries.get(patch_id)?;
+        Some((|| {
+            let mut config = base.contents_as_config()?;
+            config.merge(patch.contents_as_config()?);
+            config.try_into().map_err(Error::ConfigReadError)
+        })())
+    }
+
+    /// Tries to get the [ConfigMetadata] of the entry with the passed `id`.
+    ///
+    /// Returns:
+    /// - `None`: No entry with the passed `id` exists
+    /// - `Some(Err)`: An entry exists, but failed to load the data or to read the metadata
+    /// - `Some(Ok)`: The metadata of the found entry
+    pub fn metadata(&self, id: &str) -> Option<Result<ConfigMetadata>> {
+        self.entries
+            .get(id)
+            .map(|e| ConfigMetadata::try_from(e.contents_as_config()?))
+    }
+
+    /// Try to get any config from this repository.
+    ///
+    /// The entries are tried in the (lexicographic) order of their ids,
+    /// so the returned config is the same for the same entries
+    /// (unlike the iteration order of the entries).
     pub fn try_get_any<C>(&self) -> Option<(&str, C)>
     where
         Config: TryInto<C>,
     {
-        self.0
-            .iter()
-            .filter_map(|(id, entry)| {
-                Some((
-                    id.as_str(),
-                    entry.contents_as_config().ok()?.try_into().ok()?,
-                ))
-            })
-            .next()
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_unstable_by_key(|(id, _)| id.as_str());
+        entries.into_iter().find_map(|(id, entry)| {
+            Some((
+                id.as_str(),
+                entry.contents_as_config().ok()?.try_into().ok()?,
+            ))
+        })
+    }
+}
+
+/// The header-properties of a config.
+/// See [Repository::metadata].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMetadata {
+    /// The name of the config
+    pub name: String,
+    /// The author of the config, if set
+    pub author: Option<String>,
+    /// The version of the config, if set
+    pub version: Option<String>,
+    /// A description of the config, if set
+    pub description: Option<String>,
+    /// The category to group the config into, if set
+    pub category: Option<String>,
+}
+
+impl TryFrom<Config> for ConfigMetadata {
+    type Error = Error;
+
+    /// Reads the [ConfigMetadata] from the top-level properties of the passed [Config].
+    /// All other properties are ignored.
+    fn try_from(mut config: Config) -> Result<Self> {
+        let read = |config: &mut Config, name| {
+            get_item_optional(config, name).map_err(Error::ConfigReadError)
+        };
+        Ok(Self {
+            name: get_item(&mut config, "name").map_err(Error::ConfigReadError)?,
+            author: read(&mut config, "author")?,
+            version: read(&mut config, "version")?,
+            description: read(&mut config, "description")?,
+            category: read(&mut config, "category")?,
+        })
     }
 }
 
 /// An entry in the repository.
-/// Contains a cached `name`, an `id`, and the `source`.
+/// Contains a cached `name` and `category`, an `id`, and the `source`.
 /// Is hashed and checked for equality only by `id`.
 struct RepositoryEntry {
     /// The name as read from the config-file
     name: String,
+    /// The category as read from the config-file
+    category: Option<String>,
     /// The source of the entry
     source: RepositorySource,
 }
@@ -297,16 +490,18 @@ struct RepositoryEntry {
 impl RepositoryEntry {
     /// Creates a new [RepositoryEntry] from the passed `source`.
     /// Will also return the `id` for usage as a [HashMap]-entry.
-    /// Will extract the name from the `source`.
+    /// Will extract the name and category from the `source`.
     fn new_with_id(id: String, source: RepositorySource) -> Result<(String, Self)> {
         Ok((id, Self::new(source)?))
     }
 
     /// Creates a new [RepositoryEntry] from the passed `source`.
-    /// Will extract the name from the `source`.
+    /// Will extract the name and category from the `source`.
     fn new(source: RepositorySource) -> Result<Self> {
+        let mut config = source.contents_as_config()?;
         Ok(Self {
-            name: source.name()?,
+            name: get_item(&mut config, "name").map_err(Error::ConfigReadError)?,
+            category: get_item_optional(&mut config, "category").map_err(Error::ConfigReadError)?,
             source,
         })
     }
@@ -316,6 +511,11 @@ impl RepositoryEntry {
         &self.name
     }
 
+    /// The category of this [RepositoryEntry], if set
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
     /// The contents of this [RepositoryEntry]
     pub fn contents<'a>(&'a self) -> Result<Cow<'a, [u8]>> {
         self.source.contents()
@@ -333,20 +533,24 @@ enum RepositorySource {
     Bundled(&'static [u8]),
     /// Stored in the user-directory
     UserDir(PathBuf),
+    /// Kept in memory
+    InMemory(Vec<u8>),
+    /// Stored in the user-directory,
+    /// but the file is currently invalid
+    /// and the last valid contents are used instead
+    #[cfg(feature = "watch")]
+    UserDirSnapshot(PathBuf, Vec<u8>),
 }
 
 impl RepositorySource {
-    /// Extract the config-name from this [RepositorySource]
-    pub fn name(&self) -> Result<String> {
-        naviz_parser::config::generic::get_item(&mut self.contents_as_config()?, "name")
-            .map_err(Error::ConfigReadError)
-    }
-
     /// Read the contents of this [RepositorySource]
     pub fn contents<'a>(&'a self) -> Result<Cow<'a, [u8]>> {
         Ok(match self {
             Self::Bundled(c) => Cow::Borrowed(c),
             Self::UserDir(p) => fs::read(p).map(Cow::Owned).map_err(Error::IoError)?,
+            Self::InMemory(c) => Cow::Borrowed(c),
+            #[cfg(feature = "watch")]
+            Self::UserDirSnapshot(_, c) => Cow::Borrowed(c),
         })
     }
 
@@ -357,20 +561,115 @@ impl RepositorySource {
 
     /// Check whether a [RepositoryEntry] from this [RepositorySource] can be removed.
     pub fn is_removable(&self) -> bool {
+        self.user_dir_path().is_some()
+    }
+
+    /// The path of this [RepositorySource] in the user-directory
+    /// or [None] if it is not stored in the user-directory.
+    pub fn user_dir_path(&self) -> Option<&Path> {
         match self {
-            Self::Bundled(_) => false,
-            Self::UserDir(_) => true,
+            Self::Bundled(_) | Self::InMemory(_) => None,
+            Self::UserDir(p) => Some(p),
+            #[cfg(feature = "watch")]
+            Self::UserDirSnapshot(p, _) => Some(p),
         }
     }
 }
 
-/// Try to parse a [Config] from the passed `bytes`
+/// The kind of a config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigKind {
+    /// A [MachineConfig]
+    Machine,
+    /// A style ([VisualConfig])
+    Style,
+}
+
+impl ConfigKind {
+    /// The subdirectory of the user-directory where configs of this [ConfigKind] are stored
+    fn subdir(self) -> &'static str {
+        match self {
+            Self::Machine => MACHINES_SUBDIR,
+            Self::Style => STYLES_SUBDIR,
+        }
+    }
+}
+
+/// Checks whether the passed `bytes` contain a valid config of the passed [ConfigKind].
+/// Will lex, parse, and convert the config
+/// and return the first error encountered.
+pub fn check_config_bytes(bytes: &[u8], kind: ConfigKind) -> Result<()> {
+    match kind {
+        ConfigKind::Machine => parse_config_bytes::<MachineConfig>(bytes).map(|_| ()),
+        ConfigKind::Style => parse_config_bytes::<VisualConfig>(bytes).map(|_| ()),
+    }
+}
+
+/// Try to parse some config `C` from the passed `bytes`.
+/// Will lex, parse, and convert the config
+/// and return the first error encountered.
+pub fn parse_config_bytes<C>(bytes: &[u8]) -> Result<C>
+where
+    Config: TryInto<C, Error = naviz_parser::config::error::Error>,
+{
+    config_from_bytes(bytes)?
+        .try_into()
+        .map_err(Error::ConfigReadError)
+}
+
+/// Try to parse a [Config] from the passed `bytes`.
+/// The [Config] keeps the byte-offsets of its items,
+/// which are reported by [Error::ConfigReadError]s when converting it.
+///
+/// The text is normalized using [normalize_source] before lexing,
+/// so all byte-offsets refer to the normalized text.
 pub fn config_from_bytes(bytes: &[u8]) -> Result<Config> {
-    let config =
-        naviz_parser::config::lexer::lex(std::str::from_utf8(bytes).map_err(Error::UTF8Error)?)
-            .map_err(Error::lex_error)?;
-    let config = naviz_parser::config::parser::parse(&config).map_err(Error::parse_error)?;
-    Ok(config.into())
+    let text = normalize_source(std::str::from_utf8(bytes).map_err(Error::UTF8Error)?);
+    let (tokens, spans): (Vec<_>, Vec<_>) = naviz_parser::config::lexer::lex_with_spans(&text)
+        .map_err(Error::lex_error)?
+        .into_iter()
+        .unzip();
+    let config = naviz_parser::config::parser::parse(&tokens).map_err(Error::parse_error)?;
+    Ok(Config::from_spanned(config, &spans))
+}
+
+/// Resolves a relative `viewport.background_image.path` of the style in the passed `bytes`
+/// relative to `dir` (the directory of the style before importing it),
+/// as the image itself is not imported into the user-directory.
+/// Comments and blank lines of the style are kept.
+///
+/// Returns the updated style or [None] if it has no relative background image path.
+fn resolve_background_image(bytes: &[u8], dir: &Path) -> Result<Option<String>> {
+    /// The contents of all blocks with the passed `identifier` in the passed `config`
+    fn blocks<'a>(
+        config: &'a mut parser::Config,
+        identifier: &'a str,
+    ) -> impl Iterator<Item = &'a mut parser::Config> {
+        config.iter_mut().filter_map(move |item| match item {
+            ConfigItem::Block(i, content, _) if i == identifier => Some(content),
+            _ => None,
+        })
+    }
+
+    let text = normalize_source(std::str::from_utf8(bytes).map_err(Error::UTF8Error)?);
+    let tokens = naviz_parser::config::lexer::lex_with_layout(&text).map_err(Error::lex_error)?;
+    let mut config = parser::parse_with_comments(&tokens).map_err(Error::parse_error)?;
+
+    let mut resolved = false;
+    for viewport in blocks(&mut config, "viewport") {
+        for image in blocks(viewport, "background_image") {
+            for item in image {
+                if let ConfigItem::Property(Value::Identifier(key), Value::String(path), _) = item {
+                    if key == "path" && Path::new(path).is_relative() {
+                        *path = dir.join(&*path).to_string_lossy().into_owned();
+                        resolved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(resolved.then(|| parser::serialize(&config)))
 }
 
 /// Insert an [Iterator] of [Result]s into the `target` [HashMap].
@@ -390,10 +689,402 @@ fn insert_results<K: Eq + Hash, V>(
 
 #[cfg(test)]
 mod tests {
-    use naviz_parser::config::{machine::MachineConfig, visual::VisualConfig};
+    use std::collections::BTreeMap;
+
+    use naviz_parser::config::{error::ErrorKind, machine::MachineConfig, visual::VisualConfig};
 
     use super::*;
 
+    /// All bundled configs should pass [check_config_bytes].
+    #[test]
+    fn check_bundled_configs() {
+        for (bundled, kind) in [
+            (&BUNDLED_MACHINES, ConfigKind::Machine),
+            (&BUNDLED_STYLES, ConfigKind::Style),
+        ] {
+            for file in bundled.files() {
+                check_config_bytes(file.contents(), kind).unwrap_or_else(|e| {
+                    panic!("Config {} is invalid:\n{e:#?}", file.path().display())
+                });
+            }
+        }
+    }
+
+    /// A config with a value of the wrong type should fail with the path to the value.
+    #[test]
+    fn check_invalid_config() {
+        let error = check_config_bytes(
+            include_bytes!("../rsc/test/invalid.namachine"),
+            ConfigKind::Machine,
+        )
+        .expect_err("Invalid config passed check");
+
+        let Error::ConfigReadError(error) = error else {
+            panic!("Invalid config failed with wrong error: {error:#?}");
+        };
+        assert!(
+            matches!(error.kind(), ErrorKind::WrongType("number")),
+            "Invalid config failed with wrong error kind: {error:#?}"
+        );
+        assert_eq!(error.path(), ["max_speed", "movement"]);
+        let offset = error
+            .offset()
+            .expect("Invalid config failed without offset");
+        assert_eq!(
+            naviz_parser::byte_offset_to_line_column(
+                include_str!("../rsc/test/invalid.namachine"),
+                offset
+            ),
+            (4, 2),
+            "Invalid config failed at wrong location"
+        );
+    }
+
+    /// A config with a byte order mark and Windows line endings
+    /// should parse identically to the same config with `\n`-line endings.
+    #[test]
+    fn config_bom_crlf() {
+        for (bundled, kind) in [
+            (&BUNDLED_MACHINES, ConfigKind::Machine),
+            (&BUNDLED_STYLES, ConfigKind::Style),
+        ] {
+            let file = bundled.files().next().expect("No bundled config");
+            let lf = std::str::from_utf8(file.contents()).unwrap();
+            let crlf = format!("\u{FEFF}{}", lf.replace('\n', "\r\n"));
+
+            // Formats the parsed config with its maps sorted by key,
+            // as the iteration order of a `HashMap` differs between instances
+            let parse = |bytes: &[u8]| match kind {
+                ConfigKind::Machine => {
+                    let MachineConfig {
+                        name,
+                        movement,
+                        time,
+                        distance,
+                        zone,
+                        trap,
+                    } = parse_config_bytes(bytes).expect("Failed to parse machine");
+                    let zone: BTreeMap<_, _> = zone.into_iter().collect();
+                    let trap: BTreeMap<_, _> = trap.into_iter().collect();
+                    format!("{name:?} {movement:?} {time:?} {distance:?} {zone:?} {trap:?}")
+                }
+                ConfigKind::Style => format!(
+                    "{:?}",
+                    parse_config_bytes::<VisualConfig>(bytes).expect("Failed to parse style")
+                ),
+            };
+            assert_eq!(parse(crlf.as_bytes()), parse(lf.as_bytes()));
+        }
+    }
+
+    /// A config with a missing field should fail at the location of the enclosing block.
+    #[test]
+    fn check_missing_field_location() {
+        let error = check_config_bytes(
+            include_bytes!("../rsc/test/missing_field.namachine"),
+            ConfigKind::Machine,
+        )
+        .expect_err("Config with missing field passed check");
+
+        let Error::ConfigReadError(error) = error else {
+            panic!("Config with missing field failed with wrong error: {error:#?}");
+        };
+        assert!(
+            matches!(error.kind(), ErrorKind::MissingField("load")),
+            "Config with missing field failed with wrong error kind: {error:#?}"
+        );
+        assert_eq!(error.path(), ["time"]);
+        let offset = error
+            .offset()
+            .expect("Config with missing field failed without offset");
+        assert_eq!(
+            naviz_parser::byte_offset_to_line_column(
+                include_str!("../rsc/test/missing_field.namachine"),
+                offset
+            ),
+            (7, 1),
+            "Config with missing field failed at wrong location"
+        );
+    }
+
+    /// A config of the wrong [ConfigKind] should not pass [check_config_bytes].
+    #[test]
+    fn check_wrong_kind() {
+        let machine = BUNDLED_MACHINES
+            .files()
+            .next()
+            .expect("No bundled machines");
+        assert!(
+            check_config_bytes(machine.contents(), ConfigKind::Style).is_err(),
+            "Machine passed check as style"
+        );
+    }
+
+    /// A [Repository] created from in-memory entries should list and get all entries.
+    #[test]
+    fn from_entries() {
+        let styles: Vec<_> = BUNDLED_STYLES
+            .files()
+            .take(2)
+            .map(|f| {
+                (
+                    f.path()
+                        .file_stem()
+                        .expect("Failed to get id from filename")
+                        .to_string_lossy()
+                        .into_owned(),
+                    f.contents().to_vec(),
+                )
+            })
+            .collect();
+        // sanity-check: need two styles
+        assert_eq!(styles.len(), 2, "Not enough bundled styles");
+
+        let repo =
+            Repository::from_entries(styles.clone()).expect("Failed to create in-memory repo");
+
+        assert_eq!(repo.list().count(), 2, "Wrong number of entries");
+        for (id, contents) in styles {
+            let (_, name, removable) = repo
+                .list()
+                .find(|(i, _, _)| *i == id)
+                .expect("In-memory entry missing from `list`");
+            assert!(!removable, "In-memory entry is marked as removable");
+
+            let style = repo
+                .get::<VisualConfig>(&id)
+                .expect("In-memory entry exists in `list`, but `get` returned `None`")
+                .expect("In-memory entry is invalid");
+            assert_eq!(style.name, name, "Extracted name does not match config");
+            assert_eq!(
+                repo.get_raw(&id)
+                    .expect("In-memory entry missing")
+                    .expect("Failed to get raw contents")
+                    .as_ref(),
+                contents.as_slice(),
+                "Raw contents differ"
+            );
+        }
+    }
+
+    /// The metadata should contain all set header-properties and ignore all other properties.
+    #[test]
+    fn metadata() {
+        let repo = Repository::from_entries([
+            (
+                "full".to_string(),
+                b"name: \"Full\"\n\
+                author: \"Someone\"\n\
+                version: \"1.2\"\n\
+                description: \"A style\"\n\
+                category: \"Experimental\"\n\
+                unknown: 42\n"
+                    .to_vec(),
+            ),
+            ("minimal".to_string(), b"name: \"Minimal\"\n".to_vec()),
+        ])
+        .expect("Failed to create in-memory repo");
+
+        assert_eq!(
+            repo.metadata("full")
+                .expect("Entry missing")
+                .expect("Failed to read metadata"),
+            ConfigMetadata {
+                name: "Full".to_string(),
+                author: Some("Someone".to_string()),
+                version: Some("1.2".to_string()),
+                description: Some("A style".to_string()),
+                category: Some("Experimental".to_string()),
+            }
+        );
+        assert_eq!(
+            repo.metadata("minimal")
+                .expect("Entry missing")
+                .expect("Failed to read metadata"),
+            ConfigMetadata {
+                name: "Minimal".to_string(),
+                author: None,
+                version: None,
+                description: None,
+                category: None,
+            }
+        );
+        assert!(
+            repo.metadata("missing").is_none(),
+            "Got metadata of missing entry"
+        );
+    }
+
+    /// Categories should be read from the configs and listed with the entries.
+    #[test]
+    fn list_with_categories() {
+        let repo = Repository::from_entries([
+            (
+                "experimental".to_string(),
+                b"name: \"Experimental\"\ncategory: \"Experimental\"\n".to_vec(),
+            ),
+            ("plain".to_string(), b"name: \"Plain\"\n".to_vec()),
+        ])
+        .expect("Failed to create in-memory repo");
+
+        let mut list: Vec<_> = repo.list_with_categories().collect();
+        list.sort();
+        assert_eq!(
+            list,
+            vec![
+                ("experimental", "Experimental", Some("Experimental"), false),
+                ("plain", "Plain", None, false),
+            ]
+        );
+    }
+
+    /// [Repository::try_get_any] should always return the entry with the smallest id.
+    #[test]
+    fn try_get_any_stable() {
+        let repo = Repository::from_entries(
+            ["delta", "alpha", "charlie", "bravo", "echo"]
+                .map(|id| (id.to_string(), format!("name: \"{id}\"\n").into_bytes())),
+        )
+        .expect("Failed to create in-memory repo");
+
+        for _ in 0..8 {
+            let (id, metadata) = repo
+                .try_get_any::<ConfigMetadata>()
+                .expect("Failed to get any entry");
+            assert_eq!(id, "alpha");
+            assert_eq!(metadata.name, "alpha");
+        }
+    }
+
+    /// Invalid in-memory entries should fail to create a [Repository].
+    #[test]
+    fn from_entries_invalid() {
+        assert!(
+            Repository::from_entries([("invalid".to_string(), b"name: ".to_vec())]).is_err(),
+            "Created repository from invalid entry"
+        );
+    }
+
+    /// [Repository::verify] should report exactly the entries which cannot be loaded.
+    #[test]
+    fn verify() {
+        let valid = BUNDLED_STYLES
+            .files()
+            .next()
+            .expect("No bundled styles")
+            .contents()
+            .to_vec();
+        let repo = Repository::from_entries([
+            ("valid".to_string(), valid),
+            (
+                "corrupt".to_string(),
+                b"name: \"Corrupt\" viewport { color: 42 }".to_vec(),
+            ),
+        ])
+        .expect("Failed to create in-memory repo");
+
+        let failures = repo.verify::<VisualConfig>();
+        assert_eq!(failures.len(), 1, "Wrong number of failures: {failures:#?}");
+        let (id, error) = &failures[0];
+        assert_eq!(id, "corrupt");
+        assert!(
+            matches!(error, Error::ConfigReadError(_)),
+            "Corrupt entry failed with wrong error: {error:#?}"
+        );
+    }
+
+    /// [Repository::verify_user_dir] should only report entries stored in the user-directory.
+    #[test]
+    fn verify_user_dir() {
+        reset_temp_dir();
+
+        let corrupt = b"name: \"Corrupt\" viewport { color: 42 }";
+        let dir = Repository::user_dir(STYLES_SUBDIR).expect("Failed to get config subdirectory");
+        fs::write(dir.join("corrupt_user.nastyle"), corrupt).expect("Failed to write config");
+
+        let repo = Repository::from_entries([("corrupt_memory".to_string(), corrupt.to_vec())])
+            .expect("Failed to create in-memory repo")
+            .user_dir_styles()
+            .expect("Failed to load user-dir styles");
+
+        assert_eq!(repo.verify::<VisualConfig>().len(), 2);
+        let failures = repo.verify_user_dir::<VisualConfig>();
+        assert_eq!(failures.len(), 1, "Wrong number of failures: {failures:#?}");
+        assert_eq!(failures[0].0, "corrupt_user");
+    }
+
+    /// A patch should override the base only where it sets values.
+    #[test]
+    fn get_with_base() {
+        let base = BUNDLED_STYLES
+            .files()
+            .next()
+            .expect("No bundled styles")
+            .contents()
+            .to_vec();
+        let repo = Repository::from_entries([
+            ("base".to_string(), base),
+            (
+                "patch".to_string(),
+                b"name: \"Patched\" viewport { color: #123456 }".to_vec(),
+            ),
+        ])
+        .expect("Failed to create in-memory repo");
+
+        let base: VisualConfig = repo.get("base").unwrap().unwrap();
+        let patched: VisualConfig = repo
+            .get_with_base("base", "patch")
+            .expect("Entries exist, but `get_with_base` returned `None`")
+            .expect("Failed to load merged config");
+
+        assert_eq!(patched.name, "Patched");
+        assert_eq!(
+            (
+                patched.viewport.color.r,
+                patched.viewport.color.g,
+                patched.viewport.color.b
+            ),
+            (0x12, 0x34, 0x56)
+        );
+        assert_eq!(patched.viewport.margin, base.viewport.margin);
+        assert_eq!(patched.zone.config.len(), base.zone.config.len());
+        assert_eq!(patched.time.font.family, base.time.font.family);
+
+        assert!(repo
+            .get_with_base::<VisualConfig>("base", "missing")
+            .is_none());
+        assert!(repo
+            .get_with_base::<VisualConfig>("missing", "patch")
+            .is_none());
+    }
+
+    /// Errors in items of the patch should not point into the base.
+    #[test]
+    fn get_with_base_error_location() {
+        let base = BUNDLED_STYLES
+            .files()
+            .next()
+            .expect("No bundled styles")
+            .contents()
+            .to_vec();
+        let repo = Repository::from_entries([
+            ("base".to_string(), base),
+            (
+                "patch".to_string(),
+                b"name: \"Patched\"\n\n\nviewport { color: \"red\" }".to_vec(),
+            ),
+        ])
+        .expect("Failed to create in-memory repo");
+
+        let Some(Err(Error::ConfigReadError(error))) =
+            repo.get_with_base::<VisualConfig>("base", "patch")
+        else {
+            panic!("Invalid patch did not fail with a config error");
+        };
+        assert_eq!(error.path(), ["color", "viewport"]);
+        assert_eq!(error.offset(), None, "Error points into the base");
+    }
+
     /// Check if all bundled machines can be loaded and parsed successfully.
     #[test]
     fn bundled_machines() {
@@ -424,6 +1115,38 @@ mod tests {
         }
     }
 
+    /// The usage of the user-directory should contain the sizes of all files in the user-directory,
+    /// but not the bundled configs.
+    #[test]
+    fn user_dir_usage() {
+        reset_temp_dir();
+
+        let dir = Repository::user_dir(MACHINES_SUBDIR).expect("Failed to get config subdirectory");
+        let first = "name: \"First\"\n";
+        let second = "name: \"Second machine\"\ncategory: \"Test\"\n";
+        fs::write(dir.join("first.namachine"), first).expect("Failed to write config");
+        fs::write(dir.join("second.namachine"), second).expect("Failed to write config");
+
+        let repo = Repository::empty()
+            .bundled_machines()
+            .expect("Failed to load bundled machines")
+            .user_dir_machines()
+            .expect("Failed to load user-dir machines");
+
+        assert_eq!(
+            repo.user_dir_usage().expect("Failed to get usage"),
+            [
+                ("first".to_owned(), first.len() as u64),
+                ("second".to_owned(), second.len() as u64),
+            ]
+        );
+        assert_eq!(
+            repo.user_dir_total_usage()
+                .expect("Failed to get total usage"),
+            (first.len() + second.len()) as u64
+        );
+    }
+
     /// Test importing configs from the `subdir` of the bundled configs to the `subdir` of the [TEMP_DIR].
     /// Will use `import_fn` to import the configs to the repo.
     /// Takes care of resetting the [TEMP_DIR].
@@ -518,6 +1241,54 @@ mod tests {
         test_import_configs(STYLES_SUBDIR, Repository::import_style_to_user_dir);
     }
 
+    /// A relative background image path should still point to the image after importing the style.
+    #[test]
+    fn import_style_background_image() {
+        reset_temp_dir();
+
+        let source_dir = TEMP_DIR.with_borrow(|t| t.path().join("source"));
+        fs::create_dir_all(&source_dir).expect("Failed to create source directory");
+        let source = BUNDLED_STYLES
+            .get_file("tum.nastyle")
+            .and_then(|f| f.contents_utf8())
+            .expect("Failed to get bundled style")
+            .replacen(
+                "viewport {",
+                "viewport {\n\
+                 \tbackground_image { // schematic\n\
+                 \t\tpath: \"images/schematic.png\"\n\
+                 \t\textent { from: (0, 0) to: (1, 1) }\n\
+                 \t\topacity: 1\n\
+                 \t}\n",
+                1,
+            );
+        let source_path = source_dir.join("schematic.nastyle");
+        fs::write(&source_path, source).expect("Failed to write style");
+
+        let mut repo = Repository::empty();
+        repo.import_style_to_user_dir(&source_path)
+            .expect("Failed to import style");
+
+        let style: VisualConfig = repo
+            .get("schematic")
+            .expect("Imported style does not exist")
+            .expect("Failed to load imported style");
+        let image = style
+            .viewport
+            .background_image
+            .expect("Background image not imported");
+        assert_eq!(
+            Path::new(&image.path),
+            source_dir.join("images/schematic.png")
+        );
+        let imported = fs::read_to_string(repo.user_dir_path("schematic").unwrap())
+            .expect("Failed to read imported style");
+        assert!(
+            imported.contains("background_image { // schematic"),
+            "Comment not kept in imported style:\n{imported}"
+        );
+    }
+
     /// Should not be able to remove bundled configs.
     #[test]
     fn cannot_remove_bundled_configs() {
@@ -0,0 +1,292 @@
+//! Watching the user-directory of a [Repository] for changes.
+//! Only available with the `watch`-feature.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    sync::{
+        mpsc::{channel, Receiver},
+        Mutex, PoisonError,
+    },
+};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    check_config_bytes,
+    error::{Error, Result},
+    ConfigKind, Repository, RepositoryEntry, RepositorySource,
+};
+
+/// A change to a [Repository] which was applied by [Repository::poll_changes].
+#[derive(Debug)]
+pub enum ChangeEvent {
+    /// A new entry with the `id` was added
+    Added(String),
+    /// The entry with the `id` was modified
+    Modified(String),
+    /// The entry with the `id` was removed
+    Removed(String),
+    /// The entry with the `id` was changed but is invalid.
+    /// The previous entry (if any) was kept.
+    Invalid(String, Error),
+    /// The watcher reported an error
+    WatchError(Error),
+}
+
+/// Watches a subdirectory of the user-directory for changes.
+pub(crate) struct RepositoryWatcher {
+    /// The watcher; needs to be kept alive to receive events
+    _watcher: RecommendedWatcher,
+    /// The received events.
+    /// Wrapped in a [Mutex] to keep the [Repository] [Sync].
+    events: Mutex<Receiver<notify::Result<Event>>>,
+    /// The watched directory
+    directory: PathBuf,
+    /// The kind of the watched configs
+    kind: ConfigKind,
+    /// The last valid contents of the watched files
+    snapshots: HashMap<PathBuf, Vec<u8>>,
+}
+
+impl RepositoryWatcher {
+    /// Creates a new [RepositoryWatcher] for the passed `directory`.
+    /// Changed configs will be validated to be of the passed [ConfigKind].
+    /// The last valid contents of the passed `entries` in the `directory` are snapshotted.
+    fn new(
+        directory: PathBuf,
+        kind: ConfigKind,
+        entries: &HashMap<String, RepositoryEntry>,
+    ) -> Result<Self> {
+        let (tx, events) = channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(Error::WatchError)?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(Error::WatchError)?;
+        Ok(Self {
+            _watcher: watcher,
+            events: Mutex::new(events),
+            snapshots: entries
+                .values()
+                .filter_map(|entry| match &entry.source {
+                    RepositorySource::UserDir(p) if p.parent() == Some(directory.as_path()) => {
+                        Some((p.clone(), fs::read(p).ok()?))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            directory,
+            kind,
+        })
+    }
+
+    /// Drains all received events.
+    /// Returns the deduplicated changed paths and all errors.
+    fn drain(&mut self) -> (Vec<PathBuf>, Vec<Error>) {
+        let mut seen = HashSet::new();
+        let mut paths = Vec::new();
+        let mut errors = Vec::new();
+        let events = self
+            .events
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        for event in events.try_iter() {
+            match event {
+                Ok(event) => paths.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|p| p.parent() == Some(self.directory.as_path()))
+                        .filter(|p| seen.insert(p.clone())),
+                ),
+                Err(e) => errors.push(Error::WatchError(e)),
+            }
+        }
+        (paths, errors)
+    }
+}
+
+impl Repository {
+    /// Watches the subdirectory of the user-directory for configs of the passed [ConfigKind].
+    fn watch_user_dir(&mut self, kind: ConfigKind) -> Result<()> {
+        self.watcher = Some(RepositoryWatcher::new(
+            Self::user_dir(kind.subdir())?,
+            kind,
+            &self.entries,
+        )?);
+        Ok(())
+    }
+
+    /// Watches the machines in the user-directory for changes.
+    /// Changes can be applied using [Repository::poll_changes].
+    pub fn watch_user_dir_machines(&mut self) -> Result<()> {
+        self.watch_user_dir(ConfigKind::Machine)
+    }
+
+    /// Watches the styles in the user-directory for changes.
+    /// Changes can be applied using [Repository::poll_changes].
+    pub fn watch_user_dir_styles(&mut self) -> Result<()> {
+        self.watch_user_dir(ConfigKind::Style)
+    }
+
+    /// Applies all changes to the watched user-directory since the last call
+    /// and returns the applied changes.
+    /// Invalid changes will not evict the previous entry,
+    /// but are returned as [ChangeEvent::Invalid].
+    ///
+    /// Returns no changes if this [Repository] is not watching for changes.
+    pub fn poll_changes(&mut self) -> Vec<ChangeEvent> {
+        let Some(watcher) = &mut self.watcher else {
+            return Vec::new();
+        };
+        let (paths, errors) = watcher.drain();
+        let entries = &mut self.entries;
+
+        errors
+            .into_iter()
+            .map(ChangeEvent::WatchError)
+            .chain(
+                paths
+                    .into_iter()
+                    .filter_map(|path| watcher.apply_change(entries, path)),
+            )
+            .collect()
+    }
+}
+
+impl RepositoryWatcher {
+    /// Re-reads the config at the passed `path` and updates the `entries` accordingly.
+    /// Returns the applied change or [None] if nothing changed.
+    ///
+    /// If the config is invalid, the entry will keep its last valid contents.
+    fn apply_change(
+        &mut self,
+        entries: &mut HashMap<String, RepositoryEntry>,
+        path: PathBuf,
+    ) -> Option<ChangeEvent> {
+        let id = path.file_stem()?.to_string_lossy().into_owned();
+
+        if !path.is_file() {
+            self.snapshots.remove(&path);
+            // Only remove the entry if it was loaded from the removed file
+            return match entries.get(&id).and_then(|e| e.source.user_dir_path()) {
+                Some(p) if p == path => {
+                    entries.remove(&id);
+                    Some(ChangeEvent::Removed(id))
+                }
+                _ => None,
+            };
+        }
+
+        let entry = fs::read(&path)
+            .map_err(Error::IoError)
+            .and_then(|contents| {
+                check_config_bytes(&contents, self.kind)?;
+                let entry = RepositoryEntry::new(RepositorySource::UserDir(path.clone()))?;
+                Ok((entry, contents))
+            });
+        match entry {
+            Ok((entry, contents)) => {
+                self.snapshots.insert(path, contents);
+                Some(if entries.insert(id.clone(), entry).is_some() {
+                    ChangeEvent::Modified(id)
+                } else {
+                    ChangeEvent::Added(id)
+                })
+            }
+            Err(e) => {
+                // Keep serving the last valid contents
+                if let (Some(entry), Some(snapshot)) =
+                    (entries.get_mut(&id), self.snapshots.get(&path))
+                {
+                    if matches!(&entry.source, RepositorySource::UserDir(p) if *p == path) {
+                        entry.source = RepositorySource::UserDirSnapshot(path, snapshot.clone());
+                    }
+                }
+                Some(ChangeEvent::Invalid(id, e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::Path,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use naviz_parser::config::machine::MachineConfig;
+
+    use super::*;
+    use crate::{reset_temp_dir, MACHINES_SUBDIR};
+
+    /// Polls the passed [Repository] until `predicate` matches any event or a timeout is reached.
+    fn poll_until(repo: &mut Repository, predicate: impl Fn(&ChangeEvent) -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if repo.poll_changes().iter().any(&predicate) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    /// Imports the bundled example machine into a watched [Repository].
+    /// Returns the repository and the path of the imported machine.
+    fn watched_example_machine() -> (Repository, PathBuf) {
+        reset_temp_dir();
+
+        let source =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../configs/machines/example.namachine");
+        let mut repo = Repository::empty();
+        repo.import_machine_to_user_dir(&source)
+            .expect("Failed to import machine");
+        repo.watch_user_dir_machines()
+            .expect("Failed to watch user-dir");
+        let path = Repository::user_dir(MACHINES_SUBDIR)
+            .expect("Failed to get config subdirectory")
+            .join("example.namachine");
+        (repo, path)
+    }
+
+    /// Touching a watched file should yield a [ChangeEvent::Modified].
+    #[test]
+    fn touch_yields_modified() {
+        let (mut repo, path) = watched_example_machine();
+
+        let contents = fs::read(&path).expect("Failed to read imported machine");
+        fs::write(&path, contents).expect("Failed to touch imported machine");
+
+        assert!(
+            poll_until(
+                &mut repo,
+                |e| matches!(e, ChangeEvent::Modified(id) if id == "example")
+            ),
+            "Touching the file did not yield a `Modified`-event"
+        );
+    }
+
+    /// A malformed edit should yield a [ChangeEvent::Invalid] and keep the previous entry.
+    #[test]
+    fn malformed_edit_keeps_entry() {
+        let (mut repo, path) = watched_example_machine();
+
+        fs::write(&path, "name: \"Broken\"\nmovement {").expect("Failed to edit machine");
+
+        assert!(
+            poll_until(
+                &mut repo,
+                |e| matches!(e, ChangeEvent::Invalid(id, _) if id == "example")
+            ),
+            "Malformed edit did not yield an `Invalid`-event"
+        );
+        repo.get::<MachineConfig>("example")
+            .expect("Previous entry was evicted")
+            .expect("Previous entry is no longer valid");
+    }
+}
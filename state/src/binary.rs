@@ -0,0 +1,338 @@
+//! A compact binary encoding of [State]s, e.g., for streaming an animation to a remote viewer.
+//!
+//! A full frame is encoded using [encode_frame] and decoded using [decode_frame].
+//! Consecutive frames can be encoded as a delta using [encode_delta],
+//! which only contains the atoms that changed (identified by their index in [State::atoms])
+//! and is applied onto the previous frame using [apply_delta].
+//!
+//! All numbers are encoded in little-endian;
+//! lengths and indices are encoded as `u32`.
+
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    state::{AtomState, State},
+    Color, Pattern, Shape,
+};
+
+/// An error when decoding a frame or delta
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended before the frame or delta was complete
+    UnexpectedEnd,
+    /// The input continued after the frame or delta was complete
+    TrailingBytes,
+    /// A string was not valid UTF-8
+    InvalidUtf8,
+    /// The flags of an atom contained an unknown shape or pattern
+    InvalidFlags(u8),
+    /// A delta referenced an atom which does not exist and is not appended
+    InvalidIndex(u32),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of input"),
+            Self::TrailingBytes => write!(f, "unexpected bytes after the end of the input"),
+            Self::InvalidUtf8 => write!(f, "string is not valid UTF-8"),
+            Self::InvalidFlags(flags) => write!(f, "invalid atom-flags: {flags:#010b}"),
+            Self::InvalidIndex(index) => write!(f, "invalid atom-index: {index}"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Encodes the passed [State] as a full frame.
+/// Decode using [decode_frame].
+pub fn encode_frame(state: &State) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_len(&mut out, state.atoms.len());
+    for atom in &state.atoms {
+        write_atom(&mut out, atom);
+    }
+    write_common(&mut out, state);
+    out
+}
+
+/// Decodes a full frame encoded by [encode_frame]
+pub fn decode_frame(bytes: &[u8]) -> Result<State, DecodeError> {
+    let mut reader = Reader(bytes);
+    let count = reader.len()?;
+    let atoms = (0..count)
+        .map(|_| reader.atom())
+        .collect::<Result<_, _>>()?;
+    let (time, zones) = reader.common()?;
+    reader.finish()?;
+    Ok(State { atoms, time, zones })
+}
+
+/// Encodes the changes from the `previous` to the `next` [State] as a delta.
+/// Atoms are identified by their index:
+/// The delta contains all atoms of `next` which differ from the atom at the same index in `previous`
+/// (including all atoms which do not exist in `previous`).
+/// Apply using [apply_delta].
+pub fn encode_delta(previous: &State, next: &State) -> Vec<u8> {
+    let changed: Vec<_> = next
+        .atoms
+        .iter()
+        .enumerate()
+        .filter(|(idx, atom)| previous.atoms.get(*idx) != Some(*atom))
+        .collect();
+
+    let mut out = Vec::new();
+    write_len(&mut out, next.atoms.len());
+    write_len(&mut out, changed.len());
+    for (idx, atom) in changed {
+        write_len(&mut out, idx);
+        write_atom(&mut out, atom);
+    }
+    write_common(&mut out, next);
+    out
+}
+
+/// Applies a delta encoded by [encode_delta] onto the passed `state`,
+/// which must be the `previous` [State] the delta was encoded from.
+///
+/// On error, `state` may be partially updated.
+pub fn apply_delta(state: &mut State, delta: &[u8]) -> Result<(), DecodeError> {
+    let mut reader = Reader(delta);
+    let count = reader.len()?;
+    state.atoms.truncate(count);
+    for _ in 0..reader.len()? {
+        let idx = reader.u32()?;
+        let atom = reader.atom()?;
+        match (idx as usize).cmp(&state.atoms.len()) {
+            std::cmp::Ordering::Less => state.atoms[idx as usize] = atom,
+            std::cmp::Ordering::Equal => state.atoms.push(atom),
+            std::cmp::Ordering::Greater => return Err(DecodeError::InvalidIndex(idx)),
+        }
+    }
+    if state.atoms.len() != count {
+        // Not all appended atoms were contained in the delta
+        return Err(DecodeError::InvalidIndex(state.atoms.len() as u32));
+    }
+    let (time, zones) = reader.common()?;
+    reader.finish()?;
+    state.time = time;
+    state.zones = zones;
+    Ok(())
+}
+
+/// Writes the passed length (or index) as `u32`
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    let len: u32 = len.try_into().expect("Length does not fit into u32");
+    out.extend_from_slice(&len.to_le_bytes());
+}
+
+/// Writes the passed string prefixed by its length
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// Writes the passed [AtomState]
+fn write_atom(
+    out: &mut Vec<u8>,
+    AtomState {
+        position: (x, y),
+        size,
+        color,
+        shape,
+        pattern,
+        shuttle,
+        interaction,
+        label,
+    }: &AtomState,
+) {
+    for value in [x, y, size] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out.extend_from_slice(color);
+    out.push(flags(*shape, *pattern, *shuttle, *interaction));
+    write_str(out, label);
+}
+
+/// Writes the parts of the [State] which are not atoms (i.e., the time and zones)
+fn write_common(out: &mut Vec<u8>, state: &State) {
+    write_str(out, &state.time);
+    write_len(out, state.zones.len());
+    for zone in &state.zones {
+        out.extend_from_slice(zone);
+    }
+}
+
+/// Packs the discrete properties of an atom into a single byte:
+/// - Bits `0..2`: [Shape]
+/// - Bits `2..4`: [Pattern]
+/// - Bit `4`: shuttle
+/// - Bit `5`: interaction
+fn flags(shape: Shape, pattern: Pattern, shuttle: bool, interaction: bool) -> u8 {
+    let shape = match shape {
+        Shape::Circle => 0,
+        Shape::Square => 1,
+        Shape::Diamond => 2,
+        Shape::Cross => 3,
+    };
+    let pattern = match pattern {
+        Pattern::Solid => 0,
+        Pattern::Ring => 1,
+        Pattern::Hatch => 2,
+    };
+    shape | (pattern << 2) | ((shuttle as u8) << 4) | ((interaction as u8) << 5)
+}
+
+/// Unpacks the flags packed by [flags]
+fn unpack_flags(flags: u8) -> Result<(Shape, Pattern, bool, bool), DecodeError> {
+    let shape = match flags & 0b11 {
+        0 => Shape::Circle,
+        1 => Shape::Square,
+        2 => Shape::Diamond,
+        _ => Shape::Cross,
+    };
+    let pattern = match (flags >> 2) & 0b11 {
+        0 => Pattern::Solid,
+        1 => Pattern::Ring,
+        2 => Pattern::Hatch,
+        _ => return Err(DecodeError::InvalidFlags(flags)),
+    };
+    if flags >> 6 != 0 {
+        return Err(DecodeError::InvalidFlags(flags));
+    }
+    Ok((shape, pattern, flags & (1 << 4) != 0, flags & (1 << 5) != 0))
+}
+
+/// Reads values from the remaining bytes
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    /// Takes the next `N` bytes
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let (bytes, rest) = self
+            .0
+            .split_first_chunk()
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        self.0 = rest;
+        Ok(*bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        self.u32().map(|len| len as usize)
+    }
+
+    fn f32(&mut self) -> Result<f32, DecodeError> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn color(&mut self) -> Result<Color, DecodeError> {
+        self.bytes()
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.len()?;
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    /// Reads an atom written by [write_atom]
+    fn atom(&mut self) -> Result<AtomState, DecodeError> {
+        let position = (self.f32()?, self.f32()?);
+        let size = self.f32()?;
+        let color = self.color()?;
+        let [flags] = self.bytes()?;
+        let (shape, pattern, shuttle, interaction) = unpack_flags(flags)?;
+        Ok(AtomState {
+            position,
+            size,
+            color,
+            shape,
+            pattern,
+            shuttle,
+            interaction,
+            label: self.string()?,
+        })
+    }
+
+    /// Reads the time and zones written by [write_common]
+    fn common(&mut self) -> Result<(String, Vec<Color>), DecodeError> {
+        let time = self.string()?;
+        let count = self.len()?;
+        let zones = (0..count).map(|_| self.color()).collect::<Result<_, _>>()?;
+        Ok((time, zones))
+    }
+
+    /// Ensures that all bytes were read
+    fn finish(self) -> Result<(), DecodeError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A [State] using all shapes, patterns, and flags
+    fn state() -> State {
+        let mut state = State::example();
+        state.atoms[1].shape = Shape::Cross;
+        state.atoms[2].pattern = Pattern::Hatch;
+        state.atoms[3].interaction = true;
+        state.atoms[4].label = "ä 🚀".to_owned();
+        state.zones = vec![[1, 2, 3, 4], [255, 0, 0, 128]];
+        state
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let state = state();
+        let encoded = encode_frame(&state);
+        assert_eq!(decode_frame(&encoded), Ok(state));
+
+        assert_eq!(
+            decode_frame(&encoded[..encoded.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+    }
+
+    #[test]
+    fn delta_reconstructs_next() {
+        let previous = state();
+
+        let mut next = previous.clone();
+        next.atoms[0].position = (7., 8.);
+        next.atoms[5].shuttle = !next.atoms[5].shuttle;
+        next.atoms.push(AtomState {
+            label: "new".to_owned(),
+            ..next.atoms[6].clone()
+        });
+        next.time = "Time: 43 us".to_owned();
+        next.zones[0] = [0, 0, 0, 0];
+
+        let delta = encode_delta(&previous, &next);
+        assert!(
+            delta.len() < encode_frame(&next).len(),
+            "Delta is not smaller than a full frame"
+        );
+        let mut applied = previous.clone();
+        apply_delta(&mut applied, &delta).expect("Failed to apply delta");
+        assert_eq!(applied, next);
+
+        // Removing atoms
+        let delta = encode_delta(&next, &previous);
+        apply_delta(&mut applied, &delta).expect("Failed to apply delta");
+        assert_eq!(applied, previous);
+    }
+}
@@ -0,0 +1,73 @@
+//! Conversions between [Color] and the color-types of other crates.
+//!
+//! [Color] is an alias of `[u8; 4]` (non-premultiplied RGBA),
+//! so the conversions are provided as functions instead of [From]-implementations.
+//! Each conversion is only available with the feature of its crate:
+//! - `egui`: [egui::Color32](https://docs.rs/egui/latest/egui/struct.Color32.html)
+//! - `wgpu`: [wgpu::Color](https://docs.rs/wgpu/latest/wgpu/struct.Color.html)
+
+#[cfg(any(feature = "egui", feature = "wgpu"))]
+use crate::Color;
+
+/// Converts the passed [Color] into an `egui::Color32`.
+///
+/// `egui::Color32` is premultiplied,
+/// so translucent colors may not survive a round-trip through [from_egui] exactly.
+#[cfg(feature = "egui")]
+pub fn to_egui([r, g, b, a]: Color) -> ecolor::Color32 {
+    ecolor::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Converts the passed `egui::Color32` into a [Color]
+#[cfg(feature = "egui")]
+pub fn from_egui(color: ecolor::Color32) -> Color {
+    color.to_srgba_unmultiplied()
+}
+
+/// Converts the passed [Color] into a `wgpu::Color`
+#[cfg(feature = "wgpu")]
+pub fn to_wgpu([r, g, b, a]: Color) -> wgpu_types::Color {
+    let channel = |c: u8| c as f64 / u8::MAX as f64;
+    wgpu_types::Color {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+        a: channel(a),
+    }
+}
+
+/// Converts the passed `wgpu::Color` into a [Color].
+/// Channels are clamped to the range `0..=1`.
+#[cfg(feature = "wgpu")]
+pub fn from_wgpu(color: wgpu_types::Color) -> Color {
+    let channel = |c: f64| (c.clamp(0., 1.) * u8::MAX as f64).round() as u8;
+    [
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(any(feature = "egui", feature = "wgpu"))]
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "egui")]
+    fn egui_round_trip() {
+        for color in [[0, 0, 0, 255], [255, 204, 0, 255], [12, 34, 56, 255]] {
+            assert_eq!(from_egui(to_egui(color)), color);
+        }
+        assert_eq!(from_egui(to_egui([0, 0, 0, 0])), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[cfg(feature = "wgpu")]
+    fn wgpu_round_trip() {
+        for color in [[0, 0, 0, 0], [255, 204, 0, 128], [12, 34, 56, 255]] {
+            assert_eq!(from_wgpu(to_wgpu(color)), color);
+        }
+    }
+}
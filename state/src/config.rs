@@ -1,4 +1,4 @@
-use crate::{Color, Extent, Position, Size};
+use crate::{union, Color, Extent, Pattern, Position, Shape, Size};
 
 /// Static config (i.e., does not usually change)
 #[derive(Clone, Debug)]
@@ -13,6 +13,33 @@ pub struct Config {
     pub time: TimeConfig,
     /// The extent of the content (in content-coordinates), denoted by top-left and bottom-right
     pub content_extent: Extent,
+    /// The image to draw behind the machine, if any
+    pub background: Option<BackgroundConfig>,
+    /// Additional space around the content (in content-coordinates)
+    pub padding: Padding,
+    /// Whether the y-axis points upwards on screen
+    /// (instead of downwards, which is the default)
+    pub flip_y: bool,
+}
+
+/// Space on each side of an area
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Padding {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct BackgroundConfig {
+    /// The path of the image.
+    /// The image itself has to be loaded and passed to the renderer separately.
+    pub path: String,
+    /// The area the image spans (in content-coordinates), denoted by top-left and bottom-right
+    pub extent: Extent,
+    /// The opacity of the image (`0` to `1`)
+    pub opacity: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +56,11 @@ pub struct MachineConfig {
 pub struct GridConfig {
     /// The distance between the lines in x- and y-direction
     pub step: Size,
+    /// The position the uniformly spaced lines are aligned to
+    pub origin: Position,
+    /// Explicit positions of the lines in x- and y-direction.
+    /// If set, these are used instead of the uniformly spaced lines (and numbers).
+    pub ticks: Option<(Vec<f32>, Vec<f32>)>,
     /// The config for the grid lines
     pub line: LineConfig,
     /// The config for the legend at the sides
@@ -51,6 +83,13 @@ pub struct GridLegendConfig {
     pub display_labels: bool,
     /// Whether to display the numbers on the axes
     pub display_numbers: bool,
+    /// The number of significant digits to round the numbers on the axes to
+    pub precision: usize,
+}
+
+impl GridLegendConfig {
+    /// The default [precision][GridLegendConfig::precision] of the numbers
+    pub const DEFAULT_PRECISION: usize = 6;
 }
 
 #[derive(Clone, Debug)]
@@ -67,12 +106,59 @@ pub struct TrapConfig {
 
 #[derive(Clone, Copy, Debug)]
 pub struct ZoneConfig {
-    /// The top-left point of the zone
+    /// The top-left point of the zone (before rotation).
+    /// For circular zones, the top-left point of the bounding square.
     pub start: Position,
-    /// The size of the zone
+    /// The size of the zone (before rotation).
+    /// For circular zones, the size of the bounding square.
     pub size: Size,
+    /// The shape of the zone
+    pub shape: ZoneShape,
     /// The config of the line for this zone
     pub line: LineConfig,
+    /// The fill-color of this zone, if it should be filled
+    pub fill: Option<[u8; 4]>,
+}
+
+impl ZoneConfig {
+    /// Gets the bounds of this zone (including its rotation)
+    pub fn bounds(&self) -> Extent {
+        let (x, y) = self.start;
+        let (w, h) = self.size;
+        let corners = [(x, y), (x + w, y), (x, y + h), (x + w, y + h)];
+        let corners = match self.shape {
+            ZoneShape::Rectangle { rotation } if rotation != 0. => {
+                // Rotate clockwise around the center
+                let (sin, cos) = rotation.to_radians().sin_cos();
+                let (cx, cy) = (x + w / 2., y + h / 2.);
+                corners.map(|(px, py)| {
+                    let (dx, dy) = (px - cx, py - cy);
+                    (cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+                })
+            }
+            _ => corners,
+        };
+        corners
+            .into_iter()
+            .map(|corner| (corner, corner))
+            .reduce(union)
+            .unwrap_or_default()
+    }
+}
+
+/// The shape of a [ZoneConfig]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoneShape {
+    /// A rectangle, rotated clockwise by `rotation` degrees around its center
+    Rectangle { rotation: f32 },
+    /// A circle inscribed into the bounds of the zone
+    Circle,
+}
+
+impl Default for ZoneShape {
+    fn default() -> Self {
+        Self::Rectangle { rotation: 0. }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -81,6 +167,41 @@ pub struct AtomsConfig {
     pub shuttle: LineConfig,
     /// The config for the labels of the atoms
     pub label: FontConfig,
+    /// The config for the interaction-distance indicator
+    /// (drawn around [interacting][crate::state::AtomState::interaction] atoms)
+    pub interaction: InteractionConfig,
+    /// The outline drawn around all atoms, if any
+    pub outline: Option<OutlineConfig>,
+    /// The markers drawn at the final positions of the atoms, if any
+    pub ghost: Option<GhostConfig>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutlineConfig {
+    /// The width of the outline (drawn inside the atom)
+    pub width: f32,
+    /// The color of the outline
+    pub color: Color,
+}
+
+#[derive(Clone, Debug)]
+pub struct GhostConfig {
+    /// The radius of the markers
+    pub radius: f32,
+    /// The color of the markers
+    pub color: Color,
+    /// The positions of the markers (i.e., the final positions of the atoms)
+    pub positions: Vec<Position>,
+}
+
+#[derive(Clone, Debug)]
+pub struct InteractionConfig {
+    /// The radius of the indicator (i.e., the interaction distance)
+    pub radius: f32,
+    /// The width of the indicator-ring
+    pub width: f32,
+    /// The color of the indicator
+    pub color: Color,
 }
 
 #[derive(Clone, Debug)]
@@ -115,9 +236,13 @@ pub struct LegendSection {
 pub struct LegendEntry {
     /// The label of this entry
     pub text: String,
-    /// The color for the circle next to the text.
-    /// [None] will render no circle.
+    /// The color for the shape next to the text.
+    /// [None] will render no shape.
     pub color: Option<Color>,
+    /// The shape next to the text
+    pub shape: Shape,
+    /// The pattern to fill the shape with
+    pub pattern: Pattern,
 }
 
 #[derive(Clone, Debug)]
@@ -216,12 +341,28 @@ impl Config {
         !self.legend.entries.is_empty()
     }
 
+    /// Gets the bounds of the static content of the machine:
+    /// All traps (including their radius) and zones (including their rotation).
+    /// Is an empty [Extent] at the origin if the machine has neither traps nor zones.
+    pub fn static_bounds(&self) -> Extent {
+        let TrapConfig {
+            positions, radius, ..
+        } = &self.machine.traps;
+        let traps = positions
+            .iter()
+            .map(|(x, y)| ((x - radius, y - radius), (x + radius, y + radius)));
+        let zones = self.machine.zones.iter().map(ZoneConfig::bounds);
+        traps.chain(zones).reduce(union).unwrap_or_default()
+    }
+
     /// An example [Config]
     pub fn example() -> Self {
         Self {
             machine: MachineConfig {
                 grid: GridConfig {
                     step: (20., 20.),
+                    origin: (0., 0.),
+                    ticks: None,
                     line: LineConfig {
                         width: 1.,
                         segment_length: 0.,
@@ -240,6 +381,7 @@ impl Config {
                         position: (VPosition::Bottom, HPosition::Left),
                         display_labels: true,
                         display_numbers: true,
+                        precision: GridLegendConfig::DEFAULT_PRECISION,
                     },
                 },
                 traps: TrapConfig {
@@ -261,32 +403,38 @@ impl Config {
                     ZoneConfig {
                         start: (-10., -10.),
                         size: (120., 36.),
+                        shape: ZoneShape::default(),
                         line: LineConfig {
                             width: 1.,
                             segment_length: 0.,
                             duty: 1.,
                             color: [0, 122, 255, 255],
                         },
+                        fill: None,
                     },
                     ZoneConfig {
                         start: (-10., 30.),
                         size: (120., 46.),
+                        shape: ZoneShape::default(),
                         line: LineConfig {
                             width: 1.,
                             segment_length: 0.,
                             duty: 1.,
                             color: [255, 122, 0, 255],
                         },
+                        fill: None,
                     },
                     ZoneConfig {
                         start: (-10., 80.),
                         size: (120., 36.),
+                        shape: ZoneShape::default(),
                         line: LineConfig {
                             width: 1.,
                             segment_length: 0.,
                             duty: 1.,
                             color: [0, 122, 255, 255],
                         },
+                        fill: None,
                     },
                 ],
             },
@@ -302,6 +450,13 @@ impl Config {
                     color: [0, 0, 0, 255],
                     family: "Fira Mono".to_owned(),
                 },
+                interaction: InteractionConfig {
+                    radius: 12.,
+                    width: 0.5,
+                    color: [192, 255, 238, 64],
+                },
+                outline: None,
+                ghost: None,
             },
             legend: LegendConfig {
                 font: FontConfig {
@@ -320,14 +475,20 @@ impl Config {
                             LegendEntry {
                                 text: "Top".to_owned(),
                                 color: Some([0, 122, 255, 255]),
+                                shape: Shape::Circle,
+                                pattern: Pattern::Solid,
                             },
                             LegendEntry {
                                 text: "Middle".to_owned(),
                                 color: Some([255, 122, 0, 255]),
+                                shape: Shape::Circle,
+                                pattern: Pattern::Solid,
                             },
                             LegendEntry {
                                 text: "Bottom".to_owned(),
                                 color: Some([0, 122, 255, 255]),
+                                shape: Shape::Circle,
+                                pattern: Pattern::Solid,
                             },
                         ],
                     },
@@ -336,6 +497,8 @@ impl Config {
                         entries: vec![LegendEntry {
                             text: "Atom".to_owned(),
                             color: Some([255, 128, 32, 255]),
+                            shape: Shape::Circle,
+                            pattern: Pattern::Solid,
                         }],
                     },
                     LegendSection {
@@ -343,6 +506,8 @@ impl Config {
                         entries: vec![LegendEntry {
                             text: "Bar".to_owned(),
                             color: None,
+                            shape: Shape::Circle,
+                            pattern: Pattern::Solid,
                         }],
                     },
                 ],
@@ -356,6 +521,135 @@ impl Config {
                 display: true,
             },
             content_extent: ((0., 0.), (100., 120.)),
+            padding: Padding::default(),
+            flip_y: false,
+            background: None,
         }
     }
+
+    /// A minimal example [Config]:
+    /// A single trap without zones, legend, or time.
+    /// Cheaper to create and render than [Config::example].
+    pub fn example_minimal() -> Self {
+        let font = FontConfig {
+            size: 4.,
+            color: [0, 0, 0, 255],
+            family: "Fira Mono".to_owned(),
+        };
+        let line = LineConfig {
+            width: 0.5,
+            segment_length: 0.,
+            duty: 1.,
+            color: [127, 127, 127, 255],
+        };
+        Self {
+            machine: MachineConfig {
+                grid: GridConfig {
+                    step: (10., 10.),
+                    origin: (0., 0.),
+                    ticks: None,
+                    line,
+                    display_ticks: false,
+                    legend: GridLegendConfig {
+                        step: (10., 10.),
+                        font: font.clone(),
+                        labels: ("x".to_owned(), "y".to_owned()),
+                        position: (VPosition::Bottom, HPosition::Left),
+                        display_labels: false,
+                        display_numbers: false,
+                        precision: GridLegendConfig::DEFAULT_PRECISION,
+                    },
+                },
+                traps: TrapConfig {
+                    positions: vec![(0., 0.)],
+                    radius: 3.,
+                    line_width: 0.5,
+                    color: [100, 100, 130, 255],
+                },
+                zones: Vec::new(),
+            },
+            atoms: AtomsConfig {
+                shuttle: line,
+                label: font.clone(),
+                interaction: InteractionConfig {
+                    radius: 6.,
+                    width: 0.5,
+                    color: [192, 255, 238, 64],
+                },
+                outline: None,
+                ghost: None,
+            },
+            legend: LegendConfig {
+                font: font.clone(),
+                heading_skip: 0.,
+                entry_skip: 0.,
+                color_circle_radius: 0.,
+                color_padding: 0.,
+                entries: Vec::new(),
+            },
+            time: TimeConfig {
+                font,
+                display: false,
+            },
+            // Large enough to leave space for the minimum content-padding of the renderer
+            content_extent: ((-20., -20.), (20., 20.)),
+            padding: Padding::default(),
+            flip_y: false,
+            background: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn static_bounds_example() {
+        // Traps reach from `(-3, -3)` to `(101, 105)`,
+        // zones from `(-10, -10)` to `(110, 116)`
+        assert_eq!(
+            Config::example().static_bounds(),
+            ((-10., -10.), (110., 116.))
+        );
+    }
+
+    #[test]
+    fn minimal_example() {
+        let config = Config::example_minimal();
+        assert!(!config.display_sidebar(), "Minimal example has a legend");
+        assert!(!config.display_time(), "Minimal example has a time");
+
+        let ((left, top), (right, bottom)) = config.static_bounds();
+        let ((min_x, min_y), (max_x, max_y)) = config.content_extent;
+        assert!(left < right && top < bottom, "Trap has no extent");
+        assert!(
+            min_x <= left && min_y <= top && right <= max_x && bottom <= max_y,
+            "Trap outside of content"
+        );
+    }
+
+    #[test]
+    fn static_bounds_empty() {
+        let mut config = Config::example();
+        config.machine.traps.positions.clear();
+        config.machine.zones.clear();
+        assert_eq!(config.static_bounds(), ((0., 0.), (0., 0.)));
+    }
+
+    #[test]
+    fn rotated_zone_bounds() {
+        let mut zone = Config::example().machine.zones[0];
+        zone.start = (0., -1.);
+        zone.size = (10., 2.);
+        zone.shape = ZoneShape::Rectangle { rotation: 90. };
+
+        let ((left, top), (right, bottom)) = zone.bounds();
+        let approx = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(
+            approx(left, 4.) && approx(right, 6.) && approx(top, -5.) && approx(bottom, 5.),
+            "Wrong bounds of rotated zone: {:?}",
+            zone.bounds()
+        );
+    }
 }
@@ -1,7 +1,42 @@
+#[cfg(feature = "binary")]
+pub mod binary;
+pub mod color;
 pub mod config;
 pub mod state;
+pub mod svg;
 
 pub type Color = [u8; 4];
 pub type Position = (f32, f32);
 pub type Size = (f32, f32);
 pub type Extent = (Position, Position);
+
+/// The smallest [Extent] containing both passed [Extent]s
+fn union((a_tl, a_br): Extent, (b_tl, b_br): Extent) -> Extent {
+    (
+        (a_tl.0.min(b_tl.0), a_tl.1.min(b_tl.1)),
+        (a_br.0.max(b_br.0), a_br.1.max(b_br.1)),
+    )
+}
+
+/// The shape to draw an atom as
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Shape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    Cross,
+}
+
+/// The pattern to fill an atom with,
+/// which encodes the current operation in addition to its color
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// Filled with the color
+    #[default]
+    Solid,
+    /// Only the outer ring is filled with the color
+    Ring,
+    /// Diagonal stripes of the color
+    Hatch,
+}
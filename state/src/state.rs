@@ -1,15 +1,19 @@
-use crate::{Color, Position};
+use crate::{config::Config, union, Color, Extent, Pattern, Position, Shape};
 
 /// Dynamic state (i.e., often changes)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct State {
     /// The atoms
     pub atoms: Vec<AtomState>,
     // The time (full string to draw; i.e., with time prefix)
     pub time: String,
+    /// The current line colors of the zones
+    /// (in the order of [MachineConfig::zones][crate::config::MachineConfig::zones]).
+    /// Zones without a color keep the color of their config.
+    pub zones: Vec<Color>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AtomState {
     /// The position of this atom
     pub position: Position,
@@ -17,13 +21,148 @@ pub struct AtomState {
     pub size: f32,
     /// The color of this atom
     pub color: Color,
+    /// The shape of this atom
+    pub shape: Shape,
+    /// The pattern of this atom
+    pub pattern: Pattern,
     /// Whether this atom is currently shuttling
     pub shuttle: bool,
+    /// Whether to indicate the interaction distance around this atom
+    /// (see [InteractionConfig][crate::config::InteractionConfig])
+    pub interaction: bool,
     /// The label to draw on this atom
     pub label: String,
 }
 
+/// The atoms which changed between two [State]s.
+/// Indices refer to [State::atoms].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AtomsDiff {
+    /// The number of atoms changed; all atoms need to be updated
+    All,
+    /// Only the atoms at these (ascending) indices changed
+    Changed(Vec<usize>),
+}
+
+impl AtomsDiff {
+    /// Checks whether no atom changed
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Changed(changed) if changed.is_empty())
+    }
+}
+
 impl State {
+    /// Gets the atoms which differ between this [State] and the `previous` [State]
+    pub fn diff_atoms(&self, previous: &State) -> AtomsDiff {
+        if self.atoms.len() != previous.atoms.len() {
+            return AtomsDiff::All;
+        }
+        AtomsDiff::Changed(
+            self.atoms
+                .iter()
+                .zip(&previous.atoms)
+                .enumerate()
+                .filter(|(_, (current, previous))| current != previous)
+                .map(|(idx, _)| idx)
+                .collect(),
+        )
+    }
+
+    /// Gets the atom at the passed `position` (in content-coordinates) and its index.
+    /// An atom is hit if the `position` lies within its [size][AtomState::size].
+    /// If multiple atoms are hit, the atom whose center is nearest to the `position` is returned.
+    pub fn atom_at(&self, position: Position) -> Option<(usize, &AtomState)> {
+        self.atoms
+            .iter()
+            .enumerate()
+            .map(|(idx, atom)| {
+                let dx = atom.position.0 - position.0;
+                let dy = atom.position.1 - position.1;
+                (idx, atom, dx * dx + dy * dy)
+            })
+            .filter(|(_, atom, distance)| *distance <= atom.size * atom.size)
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+            .map(|(idx, atom, _)| (idx, atom))
+    }
+
+    /// Gets the bounds of all drawn atoms (including their size),
+    /// or [None] if no atom is drawn.
+    /// Atoms with a size of `0` (e.g., atoms which did not appear yet) are ignored.
+    pub fn atom_bounds(&self) -> Option<Extent> {
+        self.atoms
+            .iter()
+            .filter(|atom| atom.size > 0.)
+            .map(|atom| {
+                let ((x, y), size) = (atom.position, atom.size);
+                ((x - size, y - size), (x + size, y + size))
+            })
+            .reduce(union)
+    }
+
+    /// Gets the bounds of the whole content:
+    /// The [static bounds][Config::static_bounds] of the passed `config`
+    /// combined with the [atom bounds][State::atom_bounds] of this [State].
+    pub fn bounds(&self, config: &Config) -> Extent {
+        let static_bounds = config.static_bounds();
+        self.atom_bounds()
+            .map_or(static_bounds, |atoms| union(static_bounds, atoms))
+    }
+
+    /// Linearly interpolates between the [State]s `a` (at `t = 0`) and `b` (at `t = 1`).
+    /// `t` is clamped to `0..=1`.
+    ///
+    /// Positions, sizes and colors of the atoms and the zone colors are interpolated.
+    /// All other fields (and the atoms themselves) are taken from the nearer [State].
+    /// Atoms are matched by index if their labels agree, otherwise by label;
+    /// atoms without a counterpart are taken unchanged from the nearer [State].
+    pub fn lerp(a: &State, b: &State, t: f32) -> State {
+        let t = t.clamp(0., 1.);
+        let (nearer, other) = if t < 0.5 { (a, b) } else { (b, a) };
+        let atoms = nearer
+            .atoms
+            .iter()
+            .enumerate()
+            .map(|(idx, atom)| {
+                let counterpart = other
+                    .atoms
+                    .get(idx)
+                    .filter(|o| o.label == atom.label)
+                    .or_else(|| other.atoms.iter().find(|o| o.label == atom.label));
+                let Some(counterpart) = counterpart else {
+                    return atom.clone();
+                };
+                let (from, to) = if t < 0.5 {
+                    (atom, counterpart)
+                } else {
+                    (counterpart, atom)
+                };
+                AtomState {
+                    position: (
+                        lerp(from.position.0, to.position.0, t),
+                        lerp(from.position.1, to.position.1, t),
+                    ),
+                    size: lerp(from.size, to.size, t),
+                    color: lerp_color(from.color, to.color, t),
+                    ..atom.clone()
+                }
+            })
+            .collect();
+        let zones = if a.zones.len() == b.zones.len() {
+            a.zones
+                .iter()
+                .zip(&b.zones)
+                .map(|(from, to)| lerp_color(*from, *to, t))
+                .collect()
+        } else {
+            nearer.zones.clone()
+        };
+        State {
+            atoms,
+            time: nearer.time.clone(),
+            zones,
+        }
+    }
+
     /// An example [State]
     pub fn example() -> Self {
         Self {
@@ -34,11 +173,173 @@ impl State {
                     position: (x, y),
                     size: 3.,
                     color: [255, 128, 32, 255],
+                    shape: Shape::Circle,
+                    pattern: Pattern::Solid,
                     shuttle: s,
+                    interaction: false,
                     label: format!("{idx}"),
                 })
                 .collect(),
             time: "Time: 42 us".to_owned(),
+            zones: Vec::new(),
+        }
+    }
+
+    /// A minimal example [State]:
+    /// A single atom in the trap of [Config::example_minimal][crate::config::Config::example_minimal].
+    /// Cheaper to create and render than [State::example].
+    pub fn example_minimal() -> Self {
+        Self {
+            atoms: vec![AtomState {
+                position: (0., 0.),
+                size: 3.,
+                color: [255, 128, 32, 255],
+                shape: Shape::Circle,
+                pattern: Pattern::Solid,
+                shuttle: false,
+                interaction: false,
+                label: "0".to_owned(),
+            }],
+            time: String::new(),
+            zones: Vec::new(),
+        }
+    }
+}
+
+/// Linearly interpolates between `from` and `to`
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from * (1. - t) + to * t
+}
+
+/// Linearly interpolates each channel between `from` and `to`
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    std::array::from_fn(|i| lerp(from[i] as f32, to[i] as f32, t).round() as u8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A [State] with atoms at the passed `positions` with the passed `size`
+    fn state(positions: &[Position], size: f32) -> State {
+        State {
+            atoms: positions
+                .iter()
+                .enumerate()
+                .map(|(idx, position)| AtomState {
+                    position: *position,
+                    size,
+                    color: [0, 0, 0, 255],
+                    shape: Shape::Circle,
+                    pattern: Pattern::Solid,
+                    shuttle: false,
+                    interaction: false,
+                    label: format!("{idx}"),
+                })
+                .collect(),
+            time: String::new(),
+            zones: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn atom_at_center() {
+        let state = state(&[(0., 0.), (10., 0.)], 2.);
+        assert_eq!(state.atom_at((0., 0.)).map(|(idx, _)| idx), Some(0));
+        assert_eq!(state.atom_at((10., 0.)).map(|(idx, _)| idx), Some(1));
+    }
+
+    #[test]
+    fn atom_at_within_radius() {
+        let state = state(&[(0., 0.), (10., 0.)], 2.);
+        assert_eq!(state.atom_at((1., 1.)).map(|(idx, _)| idx), Some(0));
+        assert_eq!(state.atom_at((10., -2.)).map(|(idx, _)| idx), Some(1));
+        assert!(state.atom_at((5., 0.)).is_none(), "Hit atom outside radius");
+        assert!(state.atom_at((2., 2.)).is_none(), "Hit atom outside radius");
+    }
+
+    #[test]
+    fn atom_at_nearest() {
+        let state = state(&[(0., 0.), (3., 0.)], 2.);
+        assert_eq!(state.atom_at((1., 0.)).map(|(idx, _)| idx), Some(0));
+        assert_eq!(state.atom_at((2., 0.)).map(|(idx, _)| idx), Some(1));
+    }
+
+    #[test]
+    fn atom_bounds_empty() {
+        assert_eq!(state(&[], 2.).atom_bounds(), None);
+        assert_eq!(
+            state(&[(1., 1.)], 0.).atom_bounds(),
+            None,
+            "Hidden atom has bounds"
+        );
+    }
+
+    #[test]
+    fn bounds_mixed() {
+        let state = state(&[(0., 0.), (200., -50.)], 2.);
+        assert_eq!(state.atom_bounds(), Some(((-2., -52.), (202., 2.))));
+
+        let config = Config::example();
+        assert_eq!(state.bounds(&config), ((-10., -52.), (202., 116.)));
+    }
+
+    #[test]
+    fn lerp_halfway() {
+        let a = State::example();
+        let mut b = State::example();
+        for atom in &mut b.atoms {
+            atom.position = (atom.position.0 + 10., atom.position.1 - 4.);
+            atom.size = 5.;
+            atom.color = [255, 0, 32, 255];
+            atom.shuttle = !atom.shuttle;
+        }
+        b.time = "Time: 43 us".to_owned();
+
+        let blended = State::lerp(&a, &b, 0.5);
+        assert_eq!(blended.atoms.len(), a.atoms.len());
+        for ((blended, a), b) in blended.atoms.iter().zip(&a.atoms).zip(&b.atoms) {
+            assert_eq!(
+                blended.position,
+                (a.position.0 + 5., a.position.1 - 2.),
+                "Position not interpolated"
+            );
+            assert_eq!(blended.size, 4., "Size not interpolated");
+            assert_eq!(blended.color, [255, 64, 32, 255], "Color not interpolated");
+            assert_eq!(
+                blended.shuttle, b.shuttle,
+                "Discrete field not from nearer state"
+            );
+            assert_eq!(blended.label, a.label);
         }
+        assert_eq!(blended.time, b.time);
+
+        assert_eq!(State::lerp(&a, &b, 0.).atoms, a.atoms);
+        assert_eq!(State::lerp(&a, &b, 1.).atoms, b.atoms);
+    }
+
+    #[test]
+    fn lerp_matches_by_label() {
+        let a = state(&[(0., 0.), (10., 0.)], 2.);
+        let mut b = state(&[(20., 0.), (0., 10.)], 2.);
+        b.atoms.swap(0, 1);
+
+        // Atoms are in the order of the nearer state (`b`)
+        let blended = State::lerp(&a, &b, 0.5);
+        assert_eq!(blended.atoms[0].label, "1");
+        assert_eq!(blended.atoms[0].position, (5., 5.));
+        assert_eq!(blended.atoms[1].label, "0");
+        assert_eq!(blended.atoms[1].position, (10., 0.));
+    }
+
+    #[test]
+    fn minimal_example_in_trap() {
+        let state = State::example_minimal();
+        let trap = crate::config::Config::example_minimal()
+            .machine
+            .traps
+            .positions[0];
+        assert_eq!(state.atoms.len(), 1);
+        assert_eq!(state.atom_at(trap).map(|(idx, _)| idx), Some(0));
     }
 }
@@ -0,0 +1,270 @@
+//! Export of the legend as SVG.
+//! See [LegendConfig::to_svg].
+
+use std::fmt::Write;
+
+use crate::{
+    config::{LegendConfig, LegendEntry, LegendSection},
+    Color, Pattern, Shape,
+};
+
+/// The approximate width of a character relative to the font size.
+/// Used to estimate the width of the texts, as no font-metrics are available.
+const CHAR_WIDTH: f32 = 0.6;
+
+impl LegendConfig {
+    /// Renders this legend as a standalone SVG-document.
+    ///
+    /// The layout matches the legend drawn by the renderer:
+    /// Each section starts with its heading, followed by its entries,
+    /// where each entry has a colored swatch (if it has a [color][LegendEntry::color])
+    /// followed by its text.
+    /// Each swatch is marked with the class `swatch`.
+    ///
+    /// The width of the document is estimated from the length of the texts.
+    pub fn to_svg(&self) -> String {
+        let LegendConfig {
+            font,
+            heading_skip,
+            entry_skip,
+            color_circle_radius: radius,
+            color_padding,
+            entries,
+        } = self;
+        let text_x = 2. * radius + color_padding;
+
+        let mut defs = String::new();
+        let mut body = String::new();
+        let mut y = *heading_skip;
+        let mut width: f32 = 0.;
+        for LegendSection {
+            name: heading,
+            entries,
+        } in entries
+        {
+            text(&mut body, heading, 0., y);
+            width = width.max(text_width(heading, font.size));
+            y += entry_skip;
+
+            for LegendEntry {
+                text: label,
+                color,
+                shape,
+                pattern,
+            } in entries
+            {
+                if let Some(color) = color {
+                    let fill = match pattern {
+                        Pattern::Solid | Pattern::Ring => paint(*color),
+                        Pattern::Hatch => {
+                            let id = format!("hatch-{}", defs.matches("<pattern").count());
+                            hatch(&mut defs, &id, *color, *radius);
+                            format!("fill=\"url(#{id})\"")
+                        }
+                    };
+                    swatch(&mut body, (*radius, y), *radius, *shape, *pattern, &fill);
+                }
+                text(&mut body, label, text_x, y);
+                width = width.max(text_x + text_width(label, font.size));
+                y += entry_skip;
+            }
+
+            y += heading_skip - entry_skip;
+        }
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{y}\" viewBox=\"0 0 {width} {y}\">"
+        );
+        if !defs.is_empty() {
+            let _ = writeln!(svg, "<defs>\n{defs}</defs>");
+        }
+        let [r, g, b, a] = font.color;
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"{}\" font-size=\"{}\" fill=\"#{r:02x}{g:02x}{b:02x}\" fill-opacity=\"{}\" dominant-baseline=\"central\">",
+            escape(&font.family),
+            font.size,
+            opacity(a),
+        );
+        svg.push_str(&body);
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// Appends a `text`-element containing `content` at `(x, y)` (left, vertically centered)
+fn text(out: &mut String, content: &str, x: f32, y: f32) {
+    let _ = writeln!(out, "<text x=\"{x}\" y=\"{y}\">{}</text>", escape(content));
+}
+
+/// Appends the swatch of a legend-entry centered at `center` with the passed `radius`.
+/// `fill` are the attributes of the fill-color.
+fn swatch(
+    out: &mut String,
+    (x, y): (f32, f32),
+    radius: f32,
+    shape: Shape,
+    pattern: Pattern,
+    fill: &str,
+) {
+    // A ring is drawn as an outline of half the radius,
+    // so the outer edge of the stroke stays at the radius
+    let (radius, paint) = match pattern {
+        Pattern::Ring => (
+            radius * 0.75,
+            format!(
+                "fill=\"none\" {} stroke-width=\"{}\"",
+                fill.replace("fill", "stroke"),
+                radius / 2.
+            ),
+        ),
+        Pattern::Solid | Pattern::Hatch => (radius, fill.to_owned()),
+    };
+    let _ = match shape {
+        Shape::Circle => writeln!(
+            out,
+            "<circle class=\"swatch\" cx=\"{x}\" cy=\"{y}\" r=\"{radius}\" {paint}/>"
+        ),
+        Shape::Square => writeln!(
+            out,
+            "<rect class=\"swatch\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {paint}/>",
+            x - radius,
+            y - radius,
+            2. * radius,
+            2. * radius,
+        ),
+        Shape::Diamond => writeln!(
+            out,
+            "<polygon class=\"swatch\" points=\"{}\" {paint}/>",
+            points([(0., -1.), (1., 0.), (0., 1.), (-1., 0.)], (x, y), radius)
+        ),
+        Shape::Cross => {
+            let w = 1. / 3.;
+            writeln!(
+                out,
+                "<polygon class=\"swatch\" points=\"{}\" {paint}/>",
+                points(
+                    [
+                        (-w, -1.),
+                        (w, -1.),
+                        (w, -w),
+                        (1., -w),
+                        (1., w),
+                        (w, w),
+                        (w, 1.),
+                        (-w, 1.),
+                        (-w, w),
+                        (-1., w),
+                        (-1., -w),
+                        (-w, -w),
+                    ],
+                    (x, y),
+                    radius,
+                )
+            )
+        }
+    };
+}
+
+/// Appends a pattern of diagonal stripes in the passed `color` with the passed `id`
+fn hatch(out: &mut String, id: &str, color: Color, radius: f32) {
+    let size = radius / 2.;
+    let _ = writeln!(
+        out,
+        "<pattern id=\"{id}\" width=\"{size}\" height=\"{size}\" patternUnits=\"userSpaceOnUse\" patternTransform=\"rotate(45)\">\
+        <rect width=\"{}\" height=\"{size}\" {}/></pattern>",
+        size / 2.,
+        paint(color),
+    );
+}
+
+/// Formats the passed unit-`points` scaled by `radius` and translated to `center`
+/// as the `points`-attribute of a polygon
+fn points<const N: usize>(points: [(f32, f32); N], (x, y): (f32, f32), radius: f32) -> String {
+    points
+        .map(|(px, py)| format!("{},{}", x + px * radius, y + py * radius))
+        .join(" ")
+}
+
+/// The fill-attributes for the passed [Color]
+fn paint([r, g, b, a]: Color) -> String {
+    format!(
+        "fill=\"#{r:02x}{g:02x}{b:02x}\" fill-opacity=\"{}\"",
+        opacity(a)
+    )
+}
+
+/// The opacity (`0` to `1`) of the passed alpha-channel
+fn opacity(alpha: u8) -> f32 {
+    alpha as f32 / u8::MAX as f32
+}
+
+/// The estimated width of `text` in the passed `font_size`.
+/// See [CHAR_WIDTH].
+fn text_width(text: &str, font_size: f32) -> f32 {
+    text.chars().count() as f32 * font_size * CHAR_WIDTH
+}
+
+/// Escapes the passed `text` for use in XML
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::config::Config;
+
+    use super::*;
+
+    #[test]
+    fn one_swatch_per_entry() {
+        let legend = Config::example().legend;
+        let svg = legend.to_svg();
+
+        let colored = legend
+            .entries
+            .iter()
+            .flat_map(|section| &section.entries)
+            .filter(|entry| entry.color.is_some())
+            .count();
+        assert_eq!(svg.matches("class=\"swatch\"").count(), colored);
+
+        for section in &legend.entries {
+            assert!(svg.contains(&format!(">{}</text>", section.name)));
+            for entry in &section.entries {
+                assert!(svg.contains(&format!(">{}</text>", entry.text)));
+            }
+        }
+    }
+
+    #[test]
+    fn swatch_shapes_and_patterns() {
+        let mut legend = Config::example().legend;
+        legend.entries[0].entries[0].shape = Shape::Square;
+        legend.entries[0].entries[1].pattern = Pattern::Ring;
+        legend.entries[0].entries[2].pattern = Pattern::Hatch;
+        let svg = legend.to_svg();
+
+        assert!(svg.contains("<rect class=\"swatch\""), "Square not drawn");
+        assert!(svg.contains("fill=\"none\" stroke="), "Ring not drawn");
+        assert!(svg.contains("fill=\"url(#hatch-0)\""), "Hatch not drawn");
+        assert!(svg.contains("<pattern id=\"hatch-0\""), "Hatch not defined");
+    }
+
+    #[test]
+    fn escape_text() {
+        assert_eq!(escape("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
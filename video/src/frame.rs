@@ -0,0 +1,300 @@
+//! Rendering of single frames directly from files.
+//! See [render_single_frame].
+
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use fraction::Fraction;
+use naviz_animator::animator::Animator;
+use naviz_parser::{
+    config::{generic::Config, machine::MachineConfig, visual::VisualConfig},
+    input::{
+        concrete::Instructions,
+        preprocess::{preprocess_with_map, PreprocessError},
+    },
+    normalize_source,
+};
+
+use crate::VideoExport;
+
+/// The stage of [render_single_frame] which failed
+#[derive(Debug)]
+pub enum RenderFrameError {
+    /// Failed to read the file at `path`
+    Load { path: PathBuf, error: io::Error },
+    /// Failed to resolve the includes of the input at `path`
+    Include {
+        path: PathBuf,
+        error: PreprocessError<io::Error>,
+    },
+    /// Failed to lex the file at `path`.
+    /// Contains the byte-`offset` where lexing failed.
+    Lex { path: PathBuf, offset: usize },
+    /// Failed to parse the file at `path`.
+    /// Contains the byte-`offset` of the token where parsing failed.
+    Parse { path: PathBuf, offset: usize },
+    /// Failed to convert the parsed file at `path`
+    Convert { path: PathBuf, message: String },
+    /// Failed to render or write the frame
+    Render(String),
+}
+
+impl Display for RenderFrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load { path, error } => {
+                write!(f, "Failed to load `{}`: {error}", path.display())
+            }
+            Self::Include { path, error } => {
+                write!(f, "Failed to preprocess `{}`: {error}", path.display())
+            }
+            Self::Lex { path, offset } => {
+                write!(f, "Failed to lex `{}` at byte {offset}", path.display())
+            }
+            Self::Parse { path, offset } => {
+                write!(f, "Failed to parse `{}` at byte {offset}", path.display())
+            }
+            Self::Convert { path, message } => {
+                write!(f, "Failed to convert `{}`: {message}", path.display())
+            }
+            Self::Render(message) => write!(f, "Failed to render frame: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderFrameError {}
+
+/// Reads the file at `path` and normalizes it using [normalize_source].
+/// Reported offsets refer to the normalized file.
+fn load(path: &Path) -> Result<String, RenderFrameError> {
+    fs::read_to_string(path)
+        .map(|text| normalize_source(&text).into_owned())
+        .map_err(|error| RenderFrameError::Load {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Loads the config at `path` into `C`
+fn load_config<C: TryFrom<Config>>(path: &Path) -> Result<C, RenderFrameError>
+where
+    C::Error: Display,
+{
+    let source = load(path)?;
+    let (lexed, spans): (Vec<_>, Vec<_>) =
+        naviz_parser::config::lexer::lex_with_spans(source.as_str())
+            .map_err(|e| RenderFrameError::Lex {
+                path: path.to_path_buf(),
+                offset: e.offset(),
+            })?
+            .into_iter()
+            .unzip();
+    let parsed =
+        naviz_parser::config::parser::parse(&lexed).map_err(|e| RenderFrameError::Parse {
+            path: path.to_path_buf(),
+            offset: spans
+                .get(e.offset())
+                .map(|span| span.start)
+                .unwrap_or(source.len()),
+        })?;
+    let generic: Config = parsed.into();
+    generic
+        .try_into()
+        .map_err(|e: C::Error| RenderFrameError::Convert {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+}
+
+/// Loads the instructions at `path`.
+/// Includes are resolved relative to the directory containing the including file.
+/// Errors are reported in the (included) file they occurred in.
+fn load_instructions(path: &Path) -> Result<Instructions, RenderFrameError> {
+    let root = load(path)?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+    let (source, source_map) = preprocess_with_map(&root, |include| {
+        fs::read_to_string(directory.join(include)).map(|text| normalize_source(&text).into_owned())
+    })
+    .map_err(|error| RenderFrameError::Include {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    // Locates the passed offset of the preprocessed source in the opened or included file
+    let locate = |offset| {
+        let (file, _, offset) = source_map.locate(&root, offset);
+        let file = file.map_or_else(|| path.to_path_buf(), |file| directory.join(file));
+        (file, offset)
+    };
+
+    let (lexed, spans): (Vec<_>, Vec<_>) =
+        naviz_parser::input::lexer::lex_with_spans(source.as_str())
+            .map_err(|e| {
+                let (path, offset) = locate(e.offset());
+                RenderFrameError::Lex { path, offset }
+            })?
+            .into_iter()
+            .unzip();
+    let parsed = naviz_parser::input::parser::parse_with_offsets(&lexed).map_err(|e| {
+        let (path, offset) = locate(
+            spans
+                .get(e.offset())
+                .map(|span| span.start)
+                .unwrap_or(source.len()),
+        );
+        RenderFrameError::Parse { path, offset }
+    })?;
+    let (parsed, tokens): (Vec<_>, Vec<_>) = parsed.into_iter().unzip();
+    Instructions::new_located(parsed, Default::default()).map_err(|(index, e)| {
+        let path = index
+            .and_then(|index| tokens.get(index))
+            .and_then(|token| spans.get(*token))
+            .map_or_else(|| path.to_path_buf(), |span| locate(span.start).0);
+        RenderFrameError::Convert {
+            path,
+            message: format!("{e:?}"),
+        }
+    })
+}
+
+/// Renders the state at `time` of the `input` on the passed `machine` and `style`
+/// in the passed `resolution` and writes it as a PNG to `out`.
+///
+/// Loads all files, builds an [Animator] and renders the frame offscreen.
+/// Blocks until the frame is written.
+pub fn render_single_frame(
+    machine: &Path,
+    style: &Path,
+    input: &Path,
+    time: Fraction,
+    resolution: (u32, u32),
+    out: &Path,
+) -> Result<(), RenderFrameError> {
+    let machine: MachineConfig = load_config(machine)?;
+    let style: VisualConfig = load_config(style)?;
+    let instructions = load_instructions(input)?;
+    let time: f32 = time
+        .try_into()
+        .map_err(|_| RenderFrameError::Render(format!("Invalid time: {time}")))?;
+
+    let animator = Animator::new(machine, style, instructions);
+    let mut export = pollster::block_on(VideoExport::try_new(animator, resolution, 1))
+        .map_err(RenderFrameError::Render)?;
+    export
+        .export_snapshot(time, out)
+        .map_err(RenderFrameError::Render)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use naviz_renderer::skip_without_adapter;
+
+    /// Path to the example-file with the passed `extension` of the parser
+    fn example(extension: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../parser/rsc/test/example")
+            .with_extension(extension)
+    }
+
+    #[test]
+    fn example_frame() {
+        skip_without_adapter!();
+
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let out = dir.path().join("frame.png");
+        render_single_frame(
+            &example("namachine"),
+            &example("nastyle"),
+            &example("naviz"),
+            Fraction::new(1u64, 2u64),
+            (64, 48),
+            &out,
+        )
+        .expect("Failed to render frame");
+
+        let image = image::open(&out).expect("Failed to read frame");
+        assert_eq!((image.width(), image.height()), (64, 48));
+    }
+
+    /// Errors in an included file should be reported in that file,
+    /// and nested includes should be resolved relative to the including file.
+    #[test]
+    fn error_in_nested_include() {
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).expect("Failed to create directory");
+        let input = dir.path().join("input.naviz");
+        fs::write(&input, "#include \"sub/a.naviz\"\n").unwrap();
+        fs::write(sub.join("a.naviz"), "#include \"b.naviz\"\n").unwrap();
+        fs::write(sub.join("b.naviz"), "atom (0, 0) atom0\n@0 unknown atom0\n").unwrap();
+
+        let result = render_single_frame(
+            &example("namachine"),
+            &example("nastyle"),
+            &input,
+            Fraction::from(0),
+            (64, 48),
+            Path::new("unused.png"),
+        );
+
+        assert!(
+            matches!(
+                result,
+                Err(RenderFrameError::Convert { ref path, .. })
+                    if path == &dir.path().join("sub").join("b.naviz")
+            ),
+            "Expected convert error in included file, got {result:?}"
+        );
+    }
+
+    /// Files with a BOM and CRLF line endings should load like their LF version.
+    #[test]
+    fn bom_and_crlf() {
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let crlf = |extension: &str| {
+            let text = fs::read_to_string(example(extension)).expect("Failed to read example");
+            let path = dir.path().join("crlf").with_extension(extension);
+            fs::write(&path, format!("\u{FEFF}{}", text.replace('\n', "\r\n")))
+                .expect("Failed to write file");
+            path
+        };
+
+        let machine: MachineConfig =
+            load_config(&crlf("namachine")).expect("Failed to load machine");
+        let expected: MachineConfig =
+            load_config(&example("namachine")).expect("Failed to load machine");
+        assert_eq!(machine.name, expected.name);
+        load_config::<VisualConfig>(&crlf("nastyle")).expect("Failed to load style");
+        assert_eq!(
+            load_instructions(&crlf("naviz"))
+                .expect("Failed to load instructions")
+                .setup
+                .len(),
+            load_instructions(&example("naviz"))
+                .expect("Failed to load instructions")
+                .setup
+                .len()
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let missing = example("missing");
+        let result = render_single_frame(
+            &missing,
+            &example("nastyle"),
+            &example("naviz"),
+            Fraction::from(0),
+            (64, 48),
+            Path::new("unused.png"),
+        );
+
+        assert!(
+            matches!(result, Err(RenderFrameError::Load { ref path, .. }) if path == &missing),
+            "Expected load error, got {result:?}"
+        );
+    }
+}
@@ -1,17 +1,25 @@
 use std::{
+    fs,
     io::{BufRead, BufReader, Write},
+    ops::RangeInclusive,
     path::Path,
     process::{Command, ExitStatus, Stdio},
     sync::mpsc::{channel, Sender},
     thread,
+    time::{Duration, Instant},
 };
 
+pub mod frame;
+
 use naviz_animator::animator::Animator;
-use naviz_renderer::renderer::Renderer;
+use naviz_renderer::{
+    component::background::BackgroundImage, quality::RenderQuality, renderer::Renderer,
+};
+use naviz_state::{color::to_wgpu, state::State};
 use wgpu::{
-    Buffer, BufferView, Color, CommandEncoderDescriptor, Device, DeviceDescriptor, Extent3d,
-    Features, Instance, InstanceDescriptor, Limits, LoadOp, MapMode, MemoryHints, Operations,
-    Queue, RenderPassColorAttachment, RenderPassDescriptor, StoreOp, TexelCopyBufferInfo,
+    Buffer, BufferView, CommandEncoderDescriptor, Device, DeviceDescriptor, Extent3d, Features,
+    Instance, InstanceDescriptor, Limits, LoadOp, MapMode, MemoryHints, Operations, Queue,
+    RenderPassColorAttachment, RenderPassDescriptor, StoreOp, TexelCopyBufferInfo,
     TexelCopyBufferLayout, TexelCopyTextureInfo, Texture, TextureAspect, TextureDescriptor,
     TextureDimension, TextureFormat, TextureUsages,
 };
@@ -26,6 +34,17 @@ pub struct VideoExport {
     output_buffer: Buffer,
     fps: u32,
     screen_resolution: (u32, u32),
+    /// The current state, reused for every frame to avoid reallocations
+    state: State,
+    /// Whether to render with a transparent background.
+    /// See [VideoExport::set_transparent].
+    transparent: bool,
+    /// Whether to overlay the time and a progress-bar onto the frames.
+    /// See [VideoExport::set_overlay].
+    overlay: bool,
+    /// The number of sub-frames averaged per frame, if motion blur is enabled.
+    /// See [VideoExport::set_motion_blur].
+    motion_blur: Option<u32>,
 }
 
 /// Video progress update event
@@ -36,10 +55,66 @@ pub enum VideoProgress {
     Encode(f32, f32),
     /// Finished export (with `ffmpeg` exit status; may not always be successful)
     Done(ExitStatus),
+    /// Finished export of an image-sequence
+    /// (with the error, if the frames could not be written)
+    SequenceDone(Result<(), String>),
+    /// Frame-update; sent after each rendered frame
+    Frames(FrameProgress),
+}
+
+/// The number of rendered frames and the time spent rendering them.
+/// Allows computing the [average fps][FrameProgress::fps] and an [ETA][FrameProgress::eta].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameProgress {
+    /// The number of frames rendered so far
+    pub done: u64,
+    /// The total number of frames to render
+    pub total: u64,
+    /// The wall-clock time elapsed since the export started
+    pub elapsed: Duration,
+}
+
+impl FrameProgress {
+    /// The average number of frames rendered per second,
+    /// or [None] if no time has elapsed yet.
+    pub fn fps(&self) -> Option<f32> {
+        let elapsed = self.elapsed.as_secs_f32();
+        (elapsed > 0.).then(|| self.done as f32 / elapsed)
+    }
+
+    /// The estimated remaining time, extrapolated from the [average fps][FrameProgress::fps],
+    /// or [None] if no frames were rendered yet.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.done == 0 {
+            return None;
+        }
+        let remaining = self.total.saturating_sub(self.done);
+        Some(self.elapsed.mul_f64(remaining as f64 / self.done as f64))
+    }
+}
+
+/// The supported numbers of sub-frames for [VideoExport::set_motion_blur]
+pub const MOTION_BLUR_SAMPLES: RangeInclusive<u32> = 2..=8;
+
+/// File extensions of the formats which can keep a transparent background
+pub const TRANSPARENT_FORMATS: [&str; 4] = ["png", "gif", "webm", "mov"];
+
+/// Checks whether the format of the passed `target` (by its extension)
+/// can keep a transparent background.
+/// PNG-targets need to contain a pattern (e.g., `frame_%04d.png`) to export an image-sequence.
+pub fn supports_transparency(target: &Path) -> bool {
+    target
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| {
+            TRANSPARENT_FORMATS
+                .iter()
+                .any(|format| format.eq_ignore_ascii_case(e))
+        })
 }
 
 /// Creates a headless rendering [Device] and [Queue]
-async fn create_device() -> (Device, Queue) {
+async fn create_device() -> Result<(Device, Queue), String> {
     let instance = Instance::new(InstanceDescriptor::new_without_display_handle());
     let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
@@ -48,7 +123,7 @@ async fn create_device() -> (Device, Queue) {
             force_fallback_adapter: false,
         })
         .await
-        .expect("No adapter");
+        .map_err(|e| format!("No adapter: {e}"))?;
     adapter
         .request_device(&DeviceDescriptor {
             label: Some("naviz video renderer"),
@@ -59,13 +134,19 @@ async fn create_device() -> (Device, Queue) {
             experimental_features: wgpu::ExperimentalFeatures::default(),
         })
         .await
-        .expect("Failed to create device")
+        .map_err(|e| format!("Failed to create device: {e}"))
+}
+
+/// Gets the size of a row of `width` pixels of `pixel_size` in the output-buffer,
+/// which is padded to [COPY_BYTES_PER_ROW_ALIGNMENT][wgpu::COPY_BYTES_PER_ROW_ALIGNMENT].
+fn padded_bytes_per_row(width: u32, pixel_size: u32) -> u32 {
+    (width * pixel_size).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
 }
 
 /// Creates an output-buffer on the passed [Device] for the specified `screen_resolution` and `pixel_size`.
 fn create_output_buffer(device: &Device, screen_resolution: (u32, u32), pixel_size: u32) -> Buffer {
-    let output_buffer_size =
-        (pixel_size * screen_resolution.0 * screen_resolution.1) as wgpu::BufferAddress;
+    let output_buffer_size = (padded_bytes_per_row(screen_resolution.0, pixel_size)
+        * screen_resolution.1) as wgpu::BufferAddress;
     let output_buffer_desc = wgpu::BufferDescriptor {
         size: output_buffer_size,
         usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
@@ -79,7 +160,21 @@ impl VideoExport {
     /// Creates a new [VideoExport] from the passed [Animator]
     /// and with the passed `screen_resolution` and `fps`
     pub async fn new(animator: Animator, screen_resolution: (u32, u32), fps: u32) -> Self {
-        let (device, queue) = create_device().await;
+        Self::try_new(animator, screen_resolution, fps)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to create video export: {e}"))
+    }
+
+    /// Creates a new [VideoExport] from the passed [Animator]
+    /// and with the passed `screen_resolution` and `fps`.
+    /// Returns an error if no device could be created
+    /// or the [Renderer] could not be set up.
+    pub async fn try_new(
+        animator: Animator,
+        screen_resolution: (u32, u32),
+        fps: u32,
+    ) -> Result<Self, String> {
+        let (device, queue) = create_device().await?;
         let texture_format = TextureFormat::Rgba8Unorm;
         let texture = device.create_texture(&TextureDescriptor {
             label: Some("naviz render target"),
@@ -96,14 +191,16 @@ impl VideoExport {
             view_formats: &[],
         });
 
-        let renderer = Renderer::new(
+        let state = animator.state((0.).into());
+        let renderer = Renderer::try_new(
             &device,
             &queue,
             texture_format,
             &animator.config(),
-            &animator.state((0.).into()),
+            &state,
             screen_resolution,
-        );
+        )
+        .map_err(|e| format!("Failed to create renderer: {e}"))?;
 
         let output_buffer = create_output_buffer(
             &device,
@@ -111,7 +208,7 @@ impl VideoExport {
             texture_format.components() as u32,
         );
 
-        Self {
+        Ok(Self {
             animator,
             renderer,
             texture,
@@ -120,21 +217,89 @@ impl VideoExport {
             output_buffer,
             fps,
             screen_resolution,
-        }
+            state,
+            transparent: false,
+            overlay: false,
+            motion_blur: None,
+        })
     }
 
-    /// Gets the frame times for the duration of the [Animator] and the set `fps`.
-    fn get_frame_times(&self) -> impl Iterator<Item = f32> {
-        let frame_count: u64 = (self.animator.duration() * self.fps)
+    /// Whether to render with a transparent background instead of the background color.
+    /// Only applied when exporting to a format which [supports transparency][supports_transparency].
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
+    /// Whether to burn the time and a progress-bar (along the bottom edge) into the frames.
+    /// The time is drawn in the bottom-left corner if the style does not already display it.
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+        self.renderer.set_overlay(overlay);
+        self.renderer.update_full(
+            &mut (&self.device, &self.queue),
+            &self.device,
+            &self.queue,
+            &self.animator.config(),
+            &self.state,
+        );
+    }
+
+    /// Sets the [RenderQuality] to render the frames with
+    /// (see [Renderer::set_quality]).
+    pub fn set_quality(&mut self, quality: RenderQuality) {
+        self.renderer
+            .set_quality(&self.device, &self.queue, quality);
+        self.renderer.update_full(
+            &mut (&self.device, &self.queue),
+            &self.device,
+            &self.queue,
+            &self.animator.config(),
+            &self.state,
+        );
+    }
+
+    /// Sets the image to draw behind the machine
+    /// (see [Renderer::set_background_image]).
+    /// The image is only drawn if the style configures a background image.
+    /// Pass [None] to remove the current image.
+    pub fn set_background_image(&mut self, image: Option<&BackgroundImage>) {
+        self.renderer.set_background_image(
+            &mut (&self.device, &self.queue),
+            &self.device,
+            &self.queue,
+            image,
+        );
+    }
+
+    /// Enables motion blur by averaging `samples` sub-frames
+    /// (clamped to [MOTION_BLUR_SAMPLES]) spread over the duration of each frame.
+    /// Smooths fast moves when exporting at a low `fps`.
+    /// Pass [None] to disable motion blur.
+    /// Only applied to videos and image-sequences, not to snapshots.
+    pub fn set_motion_blur(&mut self, samples: Option<u32>) {
+        self.motion_blur =
+            samples.map(|s| s.clamp(*MOTION_BLUR_SAMPLES.start(), *MOTION_BLUR_SAMPLES.end()));
+    }
+
+    /// Gets the number of frames for the duration of the [Animator] and the set `fps`.
+    /// This is the length of [VideoExport::get_frame_times].
+    fn frame_count(&self) -> u64 {
+        let last_frame: u64 = (self.animator.duration() * self.fps)
             .ceil()
             .try_into()
             .unwrap();
+        last_frame + 1
+    }
+
+    /// Gets the frame times for the duration of the [Animator] and the set `fps`.
+    fn get_frame_times(&self) -> impl Iterator<Item = f32> {
         let fps = self.fps;
-        (0..=frame_count).map(move |i| i as f32 * (1. / fps as f32))
+        (0..self.frame_count()).map(move |i| i as f32 * (1. / fps as f32))
     }
 
     /// Exports a video the the specified `target`-path using system-installed `ffmpeg`
     pub fn export_video(&mut self, target: &Path, progress: Sender<VideoProgress>) {
+        let transparent = self.transparent && supports_transparency(target);
         let mut ffmpeg = Command::new("ffmpeg")
             .args([
                 "-progress",
@@ -149,13 +314,17 @@ impl VideoExport {
                 format!("{}/1", self.fps).as_str(),
                 "-i",
                 "-",
+                "-y",
+            ])
+            .args(if transparent {
+                // Let ffmpeg select a pixel format which keeps the alpha-channel
+                &[][..]
+            } else {
                 // Set chroma subsampling for some video players
                 // See https://trac.ffmpeg.org/wiki/Encode/H.264#Encodingfordumbplayers
                 // Should be ignored on formats that don't support it
-                "-vf",
-                "format=yuv420p",
-                "-y",
-            ])
+                &["-vf", "format=yuv420p"][..]
+            })
             .arg(target)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -179,16 +348,19 @@ impl VideoExport {
             });
         });
 
-        self.get_frame_times().for_each(|time| {
-            self.set_time(time);
-            let frame = self.get_frame();
-            ffmpeg_input
-                .write_all(&frame)
+        let total = self.frame_count();
+        let start = Instant::now();
+        let times: Vec<_> = self.get_frame_times().collect();
+        for (idx, time) in times.into_iter().enumerate() {
+            self.render_frame(time, transparent, |frame| ffmpeg_input.write_all(frame))
                 .expect("Failed to send frame to ffmpeg");
-            drop(frame);
-            self.output_buffer.unmap();
             let _ = progress.send(VideoProgress::Render(time, duration));
-        });
+            let _ = progress.send(VideoProgress::Frames(FrameProgress {
+                done: idx as u64 + 1,
+                total,
+                elapsed: start.elapsed(),
+            }));
+        }
         ffmpeg_input
             .flush()
             .expect("Failed to flush frames to ffmpeg");
@@ -200,37 +372,151 @@ impl VideoExport {
         }
     }
 
+    /// Exports the frames as an image-sequence of PNGs
+    /// (`frame_00000.png`, `frame_00001.png`, ...) into the specified `dir`.
+    /// Creates `dir` if it does not exist.
+    /// Keeps the background transparent if [set][VideoExport::set_transparent].
+    pub fn export_png_sequence(&mut self, dir: &Path, progress: Sender<VideoProgress>) {
+        let result = self.write_png_sequence(dir, &progress);
+        let _ = progress.send(VideoProgress::SequenceDone(result));
+    }
+
+    /// Writes the frames for [VideoExport::export_png_sequence]
+    fn write_png_sequence(
+        &mut self,
+        dir: &Path,
+        progress: &Sender<VideoProgress>,
+    ) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {e}"))?;
+
+        let duration = self.animator.duration().try_into().unwrap();
+        let total = self.frame_count();
+        let start = Instant::now();
+        let resolution = self.screen_resolution;
+        let times: Vec<_> = self.get_frame_times().collect();
+        for (idx, time) in times.into_iter().enumerate() {
+            let path = dir.join(format!("frame_{idx:05}.png"));
+            self.render_frame(time, self.transparent, |frame| {
+                save_frame(frame, resolution, &path)
+            })?;
+            // Encoding happens while rendering
+            let _ = progress.send(VideoProgress::Render(time, duration));
+            let _ = progress.send(VideoProgress::Encode(time, duration));
+            let _ = progress.send(VideoProgress::Frames(FrameProgress {
+                done: idx as u64 + 1,
+                total,
+                elapsed: start.elapsed(),
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Exports a single frame at the passed `time` as a PNG to the specified `target`-path.
+    /// Keeps the background transparent if [set][VideoExport::set_transparent].
+    pub fn export_snapshot(&mut self, time: f32, target: &Path) -> Result<(), String> {
+        self.set_time(time);
+        self.with_frame(self.transparent, |frame| {
+            save_frame(frame, self.screen_resolution, target)
+        })
+    }
+
+    /// Renders the frame at the passed `time` and passes it to `consume` (see [Self::with_frame]).
+    /// If [motion blur][Self::set_motion_blur] is enabled,
+    /// the sub-frames spread over the duration of the frame are averaged.
+    fn render_frame<R>(
+        &mut self,
+        time: f32,
+        transparent: bool,
+        consume: impl FnOnce(&[u8]) -> R,
+    ) -> R {
+        let Some(samples) = self.motion_blur else {
+            self.set_time(time);
+            return self.with_frame(transparent, consume);
+        };
+
+        let frame_duration = 1. / self.fps as f32;
+        let mut sum: Vec<u32> = Vec::new();
+        for sample in 0..samples {
+            self.set_time(time + frame_duration * sample as f32 / samples as f32);
+            self.with_frame(transparent, |frame| {
+                sum.resize(frame.len(), 0);
+                sum.iter_mut()
+                    .zip(frame)
+                    .for_each(|(sum, value)| *sum += *value as u32);
+            });
+        }
+        let frame: Vec<_> = sum
+            .into_iter()
+            .map(|sum| ((sum + samples / 2) / samples) as u8)
+            .collect();
+        consume(&frame)
+    }
+
     /// Updates the [Renderer] to have the state of the [Animator] at the passed `time`
     fn set_time(&mut self, time: f32) {
-        self.renderer.update(
+        let diff = self.animator.state_into(time.into(), &mut self.state);
+        self.renderer.update_changed(
             &mut (&self.device, &self.queue),
             &self.device,
             &self.queue,
             &self.animator.config(),
-            &self.animator.state(time.into()),
+            &self.state,
+            &diff,
         );
+        if self.overlay {
+            let duration: f32 = self.animator.duration().try_into().unwrap();
+            let progress = if duration > 0. { time / duration } else { 1. };
+            self.renderer
+                .set_progress(&mut (&self.device, &self.queue), progress);
+        }
+    }
+
+    /// Renders the current frame (see [Self::get_frame])
+    /// and passes the tightly packed pixel data to `consume`.
+    /// The mapped [BufferView] is passed directly
+    /// unless its rows contain padding, which is stripped.
+    fn with_frame<R>(&self, transparent: bool, consume: impl FnOnce(&[u8]) -> R) -> R {
+        let frame = self.get_frame(transparent);
+        let pixel_size = self.texture.format().components() as u32;
+        let row_size = (self.screen_resolution.0 * pixel_size) as usize;
+        let padded_row_size = padded_bytes_per_row(self.screen_resolution.0, pixel_size) as usize;
+        let result = if row_size == padded_row_size {
+            consume(&frame)
+        } else {
+            let packed: Vec<_> = frame
+                .chunks_exact(padded_row_size)
+                .flat_map(|row| &row[..row_size])
+                .copied()
+                .collect();
+            consume(&packed)
+        };
+        drop(frame);
+        self.output_buffer.unmap();
+        result
     }
 
     /// Renders the current frame and gets the resulting data as a [BufferView].
+    /// The rows of the data are [padded][padded_bytes_per_row].
+    /// If `transparent` is set, the background is left transparent instead of being filled.
     /// [Self::output_buffer] will need to be [unmapped][Buffer::unmap] after the [BufferView] was used.
-    fn get_frame(&self) -> BufferView {
+    fn get_frame(&self, transparent: bool) -> BufferView {
         let mut encoder = self
             .device
             .create_command_encoder(&CommandEncoderDescriptor { label: None });
         {
-            let [r, g, b, a] = self.animator.background();
+            let background = if transparent {
+                [0; 4]
+            } else {
+                self.animator.background()
+            };
             let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(RenderPassColorAttachment {
                     view: &self.texture.create_view(&Default::default()),
                     resolve_target: None,
                     ops: Operations {
-                        load: LoadOp::Clear(Color {
-                            r: r as f64 / u8::MAX as f64,
-                            g: g as f64 / u8::MAX as f64,
-                            b: b as f64 / u8::MAX as f64,
-                            a: a as f64 / u8::MAX as f64,
-                        }),
+                        load: LoadOp::Clear(to_wgpu(background)),
                         store: StoreOp::Store,
                     },
                     depth_slice: None,
@@ -254,9 +540,10 @@ impl VideoExport {
                 buffer: &self.output_buffer,
                 layout: TexelCopyBufferLayout {
                     offset: 0,
-                    bytes_per_row: Some(
-                        self.screen_resolution.0 * self.texture.format().components() as u32,
-                    ),
+                    bytes_per_row: Some(padded_bytes_per_row(
+                        self.screen_resolution.0,
+                        self.texture.format().components() as u32,
+                    )),
                     rows_per_image: Some(self.screen_resolution.1),
                 },
             },
@@ -276,3 +563,260 @@ impl VideoExport {
         buffer_slice.get_mapped_range()
     }
 }
+
+/// Saves the passed `frame` of the passed `resolution` as a PNG to the specified `path`
+fn save_frame(frame: &[u8], resolution: (u32, u32), path: &Path) -> Result<(), String> {
+    let (width, height) = resolution;
+    image::save_buffer(path, frame, width, height, image::ExtendedColorType::Rgba8)
+        .map_err(|e| format!("Failed to write `{}`: {e}", path.display()))
+}
+
+/// Loads a [BackgroundImage] for [VideoExport::set_background_image] from the passed `path`.
+pub fn load_background_image(path: &Path) -> Result<BackgroundImage, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load `{}`: {e}", path.display()))?
+        .into_rgba8();
+    Ok(BackgroundImage {
+        size: image.dimensions(),
+        data: image.into_raw(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use naviz_parser::{config::generic::Config, input::concrete::Instructions};
+    use naviz_renderer::skip_without_adapter;
+
+    use super::*;
+
+    /// Parses the passed config `input` into `C`
+    fn config<C: TryFrom<Config>>(input: &str) -> C {
+        let lexed = naviz_parser::config::lexer::lex(input).expect("Failed to lex config");
+        let generic: Config = naviz_parser::config::parser::parse(&lexed)
+            .expect("Failed to parse config")
+            .into();
+        generic.try_into().ok().expect("Failed to load config")
+    }
+
+    /// Creates an [Animator] from the example-inputs of the parser
+    fn example_animator() -> Animator {
+        let input =
+            naviz_parser::input::lexer::lex(include_str!("../../parser/rsc/test/example.naviz"))
+                .expect("Failed to lex instructions");
+        let input =
+            naviz_parser::input::parser::parse(&input).expect("Failed to parse instructions");
+        Animator::new(
+            config(include_str!("../../parser/rsc/test/example.namachine")),
+            config(include_str!("../../parser/rsc/test/example.nastyle")),
+            Instructions::new(input).expect("Failed to convert instructions"),
+        )
+    }
+
+    #[test]
+    fn transparent_background() {
+        skip_without_adapter!();
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), (64, 36), 1));
+        video.set_time(0.);
+
+        // Alpha of the top-left pixel, which is part of the background
+        let alpha = |video: &VideoExport, transparent| {
+            video.with_frame(transparent, |frame: &[u8]| frame[3])
+        };
+
+        assert_eq!(
+            alpha(&video, false),
+            255,
+            "Opaque background is transparent"
+        );
+        assert_eq!(alpha(&video, true), 0, "Transparent background is filled");
+    }
+
+    /// A set background image should be drawn when the style configures one.
+    #[test]
+    fn background_image() {
+        skip_without_adapter!();
+        let style = include_str!("../../parser/rsc/test/example.nastyle").replacen(
+            "viewport {",
+            "viewport {\n\
+             \tbackground_image {\n\
+             \t\tpath: \"background.png\"\n\
+             \t\textent { from: (-1000, -1000) to: (1000, 1000) }\n\
+             \t\topacity: 1\n\
+             \t}\n",
+            1,
+        );
+        let mut animator = example_animator();
+        animator.apply_visual(config(&style));
+        let mut video = pollster::block_on(VideoExport::new(animator, (64, 36), 1));
+        video.set_time(0.);
+        let without_image = video.with_frame(false, <[u8]>::to_vec);
+
+        video.set_background_image(Some(&BackgroundImage {
+            size: (1, 1),
+            data: vec![255, 0, 0, 255],
+        }));
+        video.set_time(0.);
+        let with_image = video.with_frame(false, <[u8]>::to_vec);
+
+        assert_ne!(without_image, with_image, "Background image not drawn");
+    }
+
+    #[test]
+    fn overlay_progress_bar() {
+        skip_without_adapter!();
+        let resolution = (320, 180);
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), resolution, 1));
+        let duration: f32 = video.animator.duration().try_into().unwrap();
+
+        // Pixels of the bottom row, which is covered by the progress-bar
+        let bottom_row = |video: &VideoExport| {
+            let row_size = resolution.0 as usize * 4;
+            video.with_frame(false, |frame: &[u8]| {
+                frame[frame.len() - row_size..].to_vec()
+            })
+        };
+
+        video.set_time(duration / 2.);
+        let without_overlay = bottom_row(&video);
+
+        video.set_overlay(true);
+        video.set_time(duration / 2.);
+        let with_overlay = bottom_row(&video);
+
+        assert_ne!(
+            without_overlay, with_overlay,
+            "Overlay did not change the progress-bar region"
+        );
+    }
+
+    #[test]
+    fn png_sequence() {
+        skip_without_adapter!();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), (64, 36), 1));
+        let (tx, rx) = channel();
+        video.export_png_sequence(dir.path(), tx);
+
+        assert!(
+            rx.iter()
+                .any(|p| matches!(p, VideoProgress::SequenceDone(Ok(())))),
+            "Export did not finish successfully"
+        );
+        for name in ["frame_00000.png", "frame_00001.png"] {
+            let image = image::open(dir.path().join(name)).expect("Failed to open frame");
+            assert_eq!((image.width(), image.height()), (64, 36));
+        }
+    }
+
+    #[test]
+    fn frame_progress() {
+        skip_without_adapter!();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), (64, 36), 4));
+        let total = video.frame_count();
+        let (tx, rx) = channel();
+        video.export_png_sequence(dir.path(), tx);
+
+        let frames: Vec<_> = rx
+            .iter()
+            .filter_map(|p| match p {
+                VideoProgress::Frames(f) => Some(f),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(frames.len() as u64, total, "Not every frame was reported");
+        for (idx, frame) in frames.iter().enumerate() {
+            assert_eq!(
+                frame.done,
+                idx as u64 + 1,
+                "Frame counts are not consecutive"
+            );
+            assert_eq!(frame.total, total, "Total frame count changed");
+        }
+        assert!(
+            frames.windows(2).all(|w| w[0].elapsed <= w[1].elapsed),
+            "Elapsed time is not monotonic"
+        );
+        assert_eq!(
+            frames.last().and_then(FrameProgress::eta),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn frame_progress_eta() {
+        let progress = FrameProgress {
+            done: 10,
+            total: 40,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.fps(), Some(5.));
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+
+        let start = FrameProgress {
+            done: 0,
+            total: 40,
+            elapsed: Duration::ZERO,
+        };
+        assert_eq!(start.fps(), None);
+        assert_eq!(start.eta(), None);
+    }
+
+    #[test]
+    fn snapshot() {
+        skip_without_adapter!();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let target = dir.path().join("snapshot.png");
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), (64, 36), 1));
+        video
+            .export_snapshot(0.5, &target)
+            .expect("Failed to export snapshot");
+
+        let image = image::open(&target).expect("Failed to open snapshot");
+        assert_eq!((image.width(), image.height()), (64, 36));
+    }
+
+    #[test]
+    fn motion_blur() {
+        skip_without_adapter!();
+        let mut video = pollster::block_on(VideoExport::new(example_animator(), (160, 90), 2));
+        let background = video.animator.background();
+        // The first move starts after loading (`4.2`) and lasts for about half a second
+        let time = 4.2;
+
+        // Number of pixels differing from the background
+        let foreground = |video: &mut VideoExport| {
+            video.render_frame(time, false, |frame| {
+                frame
+                    .chunks_exact(4)
+                    .filter(|pixel| *pixel != background)
+                    .count()
+            })
+        };
+
+        let sharp = foreground(&mut video);
+        video.set_motion_blur(Some(8));
+        let blurred = foreground(&mut video);
+
+        assert!(
+            blurred > sharp,
+            "Motion blur did not smear the moving atoms ({blurred} <= {sharp} pixels)"
+        );
+    }
+
+    #[test]
+    fn padded_rows() {
+        assert_eq!(padded_bytes_per_row(64, 4), 256);
+        assert_eq!(padded_bytes_per_row(160, 4), 768);
+        assert_eq!(padded_bytes_per_row(1, 4), 256);
+    }
+
+    #[test]
+    fn transparent_formats() {
+        assert!(supports_transparency(Path::new("frame_%04d.png")));
+        assert!(supports_transparency(Path::new("out.GIF")));
+        assert!(!supports_transparency(Path::new("out.mp4")));
+        assert!(!supports_transparency(Path::new("out")));
+    }
+}